@@ -0,0 +1,74 @@
+//! Nested container rendering benchmark
+//!
+//! Run with `cargo bench --bench nested_render`. Renders deeply nested
+//! block quotes and lists and reports the time and the number of heap
+//! allocations per render, which is dominated by the per-container child
+//! writers.
+
+use cmark_writer::{CommonMarkWriter, ListItem, Node, ToCommonMark, WriterOptions};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const DEPTH: usize = 40;
+const ITERATIONS: u32 = 100;
+
+fn build_nested() -> Node {
+    let mut node = Node::Paragraph(vec![Node::Text("innermost".into())]);
+    for level in 0..DEPTH {
+        node = if level % 2 == 0 {
+            Node::BlockQuote(vec![
+                Node::Paragraph(vec![Node::Text("quote".into())]),
+                node,
+            ])
+        } else {
+            Node::UnorderedList(vec![ListItem::Unordered {
+                content: vec![Node::Paragraph(vec![Node::Text("item".into())]), node],
+            }])
+        };
+    }
+    Node::Document(vec![node])
+}
+
+fn main() {
+    let document = build_nested();
+    let options = WriterOptions::default();
+
+    let mut total = Duration::ZERO;
+    let mut allocations = 0;
+    for _ in 0..ITERATIONS {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let mut writer = CommonMarkWriter::with_options(options.clone());
+        document.to_commonmark(&mut writer).unwrap();
+        black_box(writer.into_string());
+        total += start.elapsed();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - before;
+    }
+
+    println!(
+        "nested containers (depth {}): {:.2?} / iteration, {} allocations / iteration",
+        DEPTH,
+        total / ITERATIONS,
+        allocations / ITERATIONS as usize
+    );
+}
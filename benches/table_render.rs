@@ -0,0 +1,66 @@
+//! Table rendering benchmark
+//!
+//! Run with `cargo bench --bench table_render` (add `--features gfm` to include
+//! aligned GFM tables). Renders a 10k-row table through the plain Markdown path
+//! and through the HTML fallback path used for tables with block content.
+
+use cmark_writer::{CommonMarkWriter, Node, ToCommonMark, WriterOptions};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROWS: usize = 10_000;
+const COLUMNS: usize = 5;
+const ITERATIONS: u32 = 10;
+
+fn build_table(with_block_cell: bool) -> Node {
+    let headers = (0..COLUMNS)
+        .map(|c| Node::Text(format!("Column {}", c).into()))
+        .collect();
+    let mut rows: Vec<Vec<Node>> = (0..ROWS)
+        .map(|r| {
+            (0..COLUMNS)
+                .map(|c| Node::Strong(vec![Node::Text(format!("cell {}-{}", r, c).into())]))
+                .collect()
+        })
+        .collect();
+    if with_block_cell {
+        rows[0][0] = Node::Paragraph(vec![Node::Text("block".into())]);
+    }
+
+    Node::Table {
+        headers,
+        #[cfg(feature = "gfm")]
+        alignments: vec![],
+        rows,
+    }
+}
+
+fn bench(name: &str, table: &Node, options: &WriterOptions) {
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let mut writer = CommonMarkWriter::with_options(options.clone());
+        let start = Instant::now();
+        table.to_commonmark(&mut writer).unwrap();
+        black_box(writer.into_string());
+        total += start.elapsed();
+    }
+    println!(
+        "{:<24} {:>10.2?} / iteration ({} rows x {} columns)",
+        name,
+        total / ITERATIONS,
+        ROWS,
+        COLUMNS
+    );
+}
+
+fn main() {
+    let inline_table = build_table(false);
+    bench("markdown table", &inline_table, &WriterOptions::default());
+
+    let block_table = build_table(true);
+    let soft = WriterOptions {
+        strict: false,
+        ..Default::default()
+    };
+    bench("html fallback table", &block_table, &soft);
+}
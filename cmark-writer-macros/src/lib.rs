@@ -44,6 +44,222 @@ pub fn derive_commonmark_only(input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Derive macro implementing `NodeClone` for custom nodes through `Clone` and `PartialEq`
+///
+/// With `#[node_clone(hash)]` the type's `Hash` implementation is used for
+/// `hash_box` too, so nodes containing it hash by content.
+///
+/// # Example
+///
+/// ```rust
+/// use cmark_writer_macros::NodeClone;
+///
+/// #[derive(Debug, Clone, PartialEq, Hash, NodeClone)]
+/// #[node_clone(hash)]
+/// struct Badge {
+///     label: String,
+/// }
+/// ```
+#[proc_macro_derive(NodeClone, attributes(node_clone))]
+pub fn derive_node_clone(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut hash = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("node_clone"))
+    {
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("hash") {
+                hash = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `hash`"))
+            }
+        });
+        if let Err(err) = result {
+            return err.to_compile_error().into();
+        }
+    }
+
+    let hash_box = if hash {
+        quote! {
+            fn hash_box(&self, state: &mut dyn ::std::hash::Hasher) {
+                let mut state = state;
+                ::std::hash::Hash::hash(self, &mut state);
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let expanded = quote! {
+        impl #impl_generics ::cmark_writer::NodeClone for #name #ty_generics #where_clause {
+            fn clone_box(&self) -> ::std::boxed::Box<dyn ::cmark_writer::NodeContent> {
+                ::std::boxed::Box::new(::std::clone::Clone::clone(self))
+            }
+
+            fn eq_box(&self, other: &dyn ::cmark_writer::NodeContent) -> bool {
+                other
+                    .as_any()
+                    .downcast_ref::<Self>()
+                    .is_some_and(|other| self == other)
+            }
+
+            #hash_box
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Derive macro implementing the plumbing of a custom node: `NodeContent`
+/// (`is_block`, `as_any`, `as_any_mut`), `NodeClone` (`clone_box`, `eq_box`)
+/// and `CustomNode`
+///
+/// The type only has to implement `Format<CommonMarkWriter>`, and
+/// `Format<HtmlWriter>` when it renders to HTML. It must implement `Debug`,
+/// `Clone` and `PartialEq`.
+///
+/// Options, given with `#[custom_node(...)]`:
+///
+/// - `block`: the node is a block; nodes are inline by default
+/// - `html`: render to HTML with the type's `Format<HtmlWriter>`
+///   implementation, instead of the placeholder comment
+/// - `hash`: hash the node with its `Hash` implementation, as
+///   `#[node_clone(hash)]` does
+///
+/// Nodes that customize `supports_commonmark` or `fallback` implement
+/// `CustomNode` by hand, deriving only `NodeClone`.
+///
+/// # Example
+///
+/// ```rust
+/// use cmark_writer::error::WriteResult;
+/// use cmark_writer::{CommonMarkWriter, Format, HtmlWriter};
+/// use cmark_writer_macros::CustomNodeBoilerplate;
+///
+/// #[derive(Debug, Clone, PartialEq, CustomNodeBoilerplate)]
+/// #[custom_node(html)]
+/// struct Kbd {
+///     key: String,
+/// }
+///
+/// impl Format<CommonMarkWriter> for Kbd {
+///     fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+///         writer.write_str(&format!("<kbd>{}</kbd>", self.key))
+///     }
+/// }
+///
+/// impl Format<HtmlWriter> for Kbd {
+///     fn format(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+///         writer.start_tag("kbd")?;
+///         writer.finish_tag()?;
+///         writer.text(&self.key)?;
+///         writer.end_tag("kbd")?;
+///         Ok(())
+///     }
+/// }
+/// ```
+#[proc_macro_derive(CustomNodeBoilerplate, attributes(custom_node))]
+pub fn derive_custom_node_boilerplate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (mut block, mut html, mut hash) = (false, false, false);
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("custom_node"))
+    {
+        let result = attr.parse_nested_meta(|meta| {
+            let flag = if meta.path.is_ident("block") {
+                &mut block
+            } else if meta.path.is_ident("html") {
+                &mut html
+            } else if meta.path.is_ident("hash") {
+                &mut hash
+            } else {
+                return Err(meta.error("expected `block`, `html` or `hash`"));
+            };
+            *flag = true;
+            Ok(())
+        });
+        if let Err(err) = result {
+            return err.to_compile_error().into();
+        }
+    }
+
+    let hash_box = if hash {
+        quote! {
+            fn hash_box(&self, state: &mut dyn ::std::hash::Hasher) {
+                let mut state = state;
+                ::std::hash::Hash::hash(self, &mut state);
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let html_render = if html {
+        quote! {
+            fn html_render(
+                &self,
+                writer: &mut ::cmark_writer::HtmlWriter,
+            ) -> ::cmark_writer::error::WriteResult<()> {
+                <Self as ::cmark_writer::ToHtml>::to_html(self, writer)
+            }
+
+            fn supports_capability(&self, capability: &str) -> bool {
+                matches!(capability, "commonmark" | "html")
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let expanded = quote! {
+        impl #impl_generics ::cmark_writer::NodeContent for #name #ty_generics #where_clause {
+            fn is_block(&self) -> bool {
+                #block
+            }
+
+            fn as_any(&self) -> &dyn ::std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+        }
+
+        impl #impl_generics ::cmark_writer::NodeClone for #name #ty_generics #where_clause {
+            fn clone_box(&self) -> ::std::boxed::Box<dyn ::cmark_writer::NodeContent> {
+                ::std::boxed::Box::new(::std::clone::Clone::clone(self))
+            }
+
+            fn eq_box(&self, other: &dyn ::cmark_writer::NodeContent) -> bool {
+                other
+                    .as_any()
+                    .downcast_ref::<Self>()
+                    .is_some_and(|other| self == other)
+            }
+
+            #hash_box
+        }
+
+        impl #impl_generics ::cmark_writer::CustomNode for #name #ty_generics #where_clause {
+            #html_render
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
 /// Custom error attribute macro, replaces the struct form errors in the original define_custom_errors! macro
 ///
 /// # Example
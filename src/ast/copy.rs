@@ -0,0 +1,160 @@
+//! Copying trees that may hold custom nodes.
+//!
+//! Custom nodes can't be cloned, so `Node::clone` panics on them. Operations
+//! that build new documents from borrowed ones copy with
+//! [`Node::clone_lossy`] instead, and in-place edits of shared subtrees go
+//! through [`make_shared_mut`].
+
+use super::{DefinitionItem, HtmlElement, ListItem, Node};
+use crate::diagnostics;
+use std::sync::Arc;
+
+impl Node {
+    /// Whether a custom node is this node or below it
+    pub(crate) fn has_custom(&self) -> bool {
+        let mut found = false;
+        self.walk_with_paths(|_, node| found |= matches!(node, Node::Custom(_)));
+        found
+    }
+
+    /// A copy of this node that leaves out the custom nodes, or `None` for a
+    /// custom node itself
+    ///
+    /// Shared subtrees are shared by the copy rather than copied, so custom
+    /// nodes inside them are kept.
+    pub(crate) fn clone_lossy(&self) -> Option<Node> {
+        if let Node::Custom(custom) = self {
+            diagnostics::warn!(
+                "Custom node {} can't be copied and is left out.",
+                custom.type_name()
+            );
+            return None;
+        }
+        if matches!(self, Node::Shared(_)) || !self.has_custom() {
+            return Some(self.clone());
+        }
+
+        let copy = match self {
+            Node::Document(children) => Node::Document(clone_all(children)),
+            Node::ConfiguredDocument { options, children } => Node::ConfiguredDocument {
+                options: options.clone(),
+                children: clone_all(children),
+            },
+            Node::Heading {
+                level,
+                content,
+                heading_type,
+                custom_id,
+            } => Node::Heading {
+                level: *level,
+                content: clone_all(content),
+                heading_type: *heading_type,
+                custom_id: custom_id.clone(),
+            },
+            Node::FootnoteDefinition { label, content } => Node::FootnoteDefinition {
+                label: label.clone(),
+                content: clone_all(content),
+            },
+            Node::Paragraph(content) => Node::Paragraph(clone_all(content)),
+            Node::BlockQuote(content) => Node::BlockQuote(clone_all(content)),
+            Node::OrderedList { start, items } => Node::OrderedList {
+                start: *start,
+                items: items.iter().map(clone_item).collect(),
+            },
+            Node::UnorderedList(items) => {
+                Node::UnorderedList(items.iter().map(clone_item).collect())
+            }
+            Node::DefinitionList(items) => Node::DefinitionList(
+                items
+                    .iter()
+                    .map(|item| DefinitionItem {
+                        term: clone_all(&item.term),
+                        definitions: item.definitions.iter().map(|d| clone_all(d)).collect(),
+                    })
+                    .collect(),
+            ),
+            #[cfg(feature = "gfm")]
+            Node::Table {
+                headers,
+                alignments,
+                rows,
+            } => Node::Table {
+                headers: clone_all(headers),
+                alignments: alignments.clone(),
+                rows: rows.iter().map(|row| clone_all(row)).collect(),
+            },
+            #[cfg(not(feature = "gfm"))]
+            Node::Table { headers, rows } => Node::Table {
+                headers: clone_all(headers),
+                rows: rows.iter().map(|row| clone_all(row)).collect(),
+            },
+            Node::Emphasis(content) => Node::Emphasis(clone_all(content)),
+            Node::Strong(content) => Node::Strong(clone_all(content)),
+            Node::Strikethrough(content) => Node::Strikethrough(clone_all(content)),
+            Node::Link {
+                url,
+                title,
+                content,
+            } => Node::Link {
+                url: url.clone(),
+                title: title.clone(),
+                content: clone_all(content),
+            },
+            Node::CrossRef { target, text } => Node::CrossRef {
+                target: target.clone(),
+                text: text.as_deref().map(clone_all),
+            },
+            Node::ReferenceLink { label, content } => Node::ReferenceLink {
+                label: label.clone(),
+                content: clone_all(content),
+            },
+            Node::Image { url, title, alt } => Node::Image {
+                url: url.clone(),
+                title: title.clone(),
+                alt: clone_all(alt),
+            },
+            Node::HtmlElement(element) => Node::HtmlElement(HtmlElement {
+                tag: element.tag.clone(),
+                attributes: element.attributes.clone(),
+                children: clone_all(&element.children),
+                self_closing: element.self_closing,
+            }),
+            // Nodes without children can't hold a custom node
+            node => node.clone(),
+        };
+        Some(copy)
+    }
+}
+
+/// [`Node::clone_lossy`] of each node
+pub(crate) fn clone_all(nodes: &[Node]) -> Vec<Node> {
+    nodes.iter().filter_map(Node::clone_lossy).collect()
+}
+
+fn clone_item(item: &ListItem) -> ListItem {
+    match item {
+        ListItem::Unordered { content } => ListItem::Unordered {
+            content: clone_all(content),
+        },
+        ListItem::Ordered { number, content } => ListItem::Ordered {
+            number: *number,
+            content: clone_all(content),
+        },
+        #[cfg(feature = "gfm")]
+        ListItem::Task { status, content } => ListItem::Task {
+            status: status.clone(),
+            content: clone_all(content),
+        },
+    }
+}
+
+/// Mutable access to a shared node, copying it first if it is shared elsewhere
+///
+/// Like [`Arc::make_mut`], but returns `None` instead of panicking when the
+/// copy would have to clone a custom node.
+pub(crate) fn make_shared_mut(inner: &mut Arc<Node>) -> Option<&mut Node> {
+    if Arc::get_mut(inner).is_none() && inner.has_custom() {
+        return None;
+    }
+    Some(Arc::make_mut(inner))
+}
@@ -7,7 +7,7 @@ use super::Node;
 use ecow::EcoString;
 
 /// HTML attribute
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct HtmlAttribute {
     /// Attribute name
     pub name: EcoString,
@@ -16,7 +16,7 @@ pub struct HtmlAttribute {
 }
 
 /// HTML element
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct HtmlElement {
     /// HTML tag name
     pub tag: EcoString,
@@ -65,12 +65,160 @@ impl HtmlElement {
         self
     }
 
+    /// Add a child node to the HTML element
+    pub fn child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Add one or more whitespace-separated classes, merging them into the
+    /// existing `class` attribute and skipping classes that are already present
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::HtmlElement;
+    ///
+    /// let element = HtmlElement::new("div").class("a").class("b a");
+    /// assert_eq!(element.get_attribute("class"), Some("a b"));
+    /// ```
+    pub fn class(mut self, class: &str) -> Self {
+        let mut classes = EcoString::from(self.get_attribute("class").unwrap_or_default());
+        for name in class.split_whitespace() {
+            if classes.split_whitespace().any(|existing| existing == name) {
+                continue;
+            }
+            if !classes.is_empty() {
+                classes.push(' ');
+            }
+            classes.push_str(name);
+        }
+        self.set_attribute("class", &classes);
+        self
+    }
+
+    /// Set a style property, merging it into the existing `style` attribute
+    ///
+    /// Setting a property that is already present replaces its value in place.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::HtmlElement;
+    ///
+    /// let element = HtmlElement::new("span")
+    ///     .style("color", "red")
+    ///     .style("font-weight", "bold")
+    ///     .style("color", "blue");
+    /// assert_eq!(
+    ///     element.get_attribute("style"),
+    ///     Some("color: blue; font-weight: bold;")
+    /// );
+    /// ```
+    pub fn style(mut self, property: &str, value: &str) -> Self {
+        let existing = self.get_attribute("style").unwrap_or_default();
+        let mut declarations: Vec<(&str, &str)> = existing
+            .split(';')
+            .filter_map(|declaration| {
+                let (name, value) = declaration.split_once(':')?;
+                Some((name.trim(), value.trim()))
+            })
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+
+        let property = property.trim();
+        match declarations
+            .iter_mut()
+            .find(|(name, _)| name.eq_ignore_ascii_case(property))
+        {
+            Some(declaration) => declaration.1 = value.trim(),
+            None => declarations.push((property, value.trim())),
+        }
+
+        let mut style = EcoString::new();
+        for (name, value) in declarations {
+            if !style.is_empty() {
+                style.push(' ');
+            }
+            style.push_str(name);
+            style.push_str(": ");
+            style.push_str(value);
+            style.push(';');
+        }
+        self.set_attribute("style", &style);
+        self
+    }
+
     /// Set whether the element is self-closing
     pub fn self_closing(mut self, is_self_closing: bool) -> Self {
         self.self_closing = is_self_closing;
         self
     }
 
+    /// Get the value of an attribute (case-insensitive name match)
+    ///
+    /// If the attribute appears more than once, the last value wins, matching
+    /// how duplicates are resolved when attributes are deduplicated on output.
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .rev()
+            .find(|attr| attr.name.eq_ignore_ascii_case(name))
+            .map(|attr| attr.value.as_str())
+    }
+
+    /// Set an attribute, replacing any existing attributes with the same name
+    ///
+    /// The attribute keeps the position of its first occurrence; if it doesn't
+    /// exist yet it's appended.
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        let mut found = false;
+        self.attributes.retain_mut(|attr| {
+            if !attr.name.eq_ignore_ascii_case(name) {
+                return true;
+            }
+            if found {
+                return false;
+            }
+            found = true;
+            attr.value = value.into();
+            true
+        });
+        if !found {
+            self.attributes.push(HtmlAttribute {
+                name: name.into(),
+                value: value.into(),
+            });
+        }
+    }
+
+    /// Attributes in emission order
+    ///
+    /// # Arguments
+    /// * `deduplicate` - Keep one attribute per name (case-insensitive), at the position
+    ///   of its first occurrence with the value of its last
+    /// * `sort` - Sort attributes by lowercased name (stable)
+    pub fn normalized_attributes(&self, deduplicate: bool, sort: bool) -> Vec<&HtmlAttribute> {
+        let mut attributes: Vec<&HtmlAttribute> = Vec::with_capacity(self.attributes.len());
+        for attr in &self.attributes {
+            let existing = deduplicate
+                .then(|| {
+                    attributes
+                        .iter()
+                        .position(|seen| seen.name.eq_ignore_ascii_case(&attr.name))
+                })
+                .flatten();
+            match existing {
+                Some(index) => attributes[index] = attr,
+                None => attributes.push(attr),
+            }
+        }
+        if sort {
+            attributes.sort_by_cached_key(|attr| attr.name.to_ascii_lowercase());
+        }
+        attributes
+    }
+
     /// Check if this element's tag matches any in the provided list (case-insensitive)
     pub fn tag_matches_any(&self, tags: &[EcoString]) -> bool {
         tags.iter().any(|tag| tag.eq_ignore_ascii_case(&self.tag))
@@ -0,0 +1,277 @@
+//! Depth-first iterators over the blocks and inlines of a node.
+//!
+//! [`Node::blocks`] and [`Node::inlines`] cover simple queries (counting
+//! images, finding the first heading) without a visitor. Children are read
+//! in place, so the only allocations are a stack as deep as the tree and the
+//! [`NodePath`] handed out with each node.
+
+use super::path::list_item_content;
+use super::{DefinitionItem, ListItem, Node, NodePath, PathSegment};
+
+impl Node {
+    /// The block nodes below this one, in document order, with their paths
+    /// relative to it
+    ///
+    /// Only blocks are descended into, so the inlines of paragraphs and the
+    /// cells of tables are skipped. [`Node::Shared`] wrappers are looked
+    /// through.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{Node, NodePath};
+    ///
+    /// let doc = Node::Document(vec![
+    ///     Node::Paragraph(vec![Node::Text("Intro".into())]),
+    ///     Node::BlockQuote(vec![Node::heading(2, vec![Node::Text("Quoted".into())])]),
+    /// ]);
+    ///
+    /// let (path, _) = doc
+    ///     .blocks()
+    ///     .find(|(_, node)| matches!(node, Node::Heading { .. }))
+    ///     .unwrap();
+    /// assert_eq!(path, NodePath::root().child(1).child(0));
+    /// ```
+    pub fn blocks(&self) -> Blocks<'_> {
+        Blocks(Walk::new(self))
+    }
+
+    /// The inline nodes below this one, in document order, with their paths
+    /// relative to it
+    ///
+    /// Nested inlines follow the node containing them, e.g. the text of a
+    /// link comes right after the link. [`Node::Shared`] wrappers are looked
+    /// through.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    ///
+    /// let doc = Node::Document(vec![Node::Paragraph(vec![
+    ///     Node::Text("A ".into()),
+    ///     Node::Strong(vec![Node::Image {
+    ///         url: "logo.png".into(),
+    ///         title: None,
+    ///         alt: vec![Node::Text("logo".into())],
+    ///     }]),
+    /// ])]);
+    ///
+    /// let images = doc
+    ///     .inlines()
+    ///     .filter(|(_, node)| matches!(node, Node::Image { .. }))
+    ///     .count();
+    /// assert_eq!(images, 1);
+    /// ```
+    pub fn inlines(&self) -> Inlines<'_> {
+        Inlines(Walk::new(self))
+    }
+}
+
+/// Iterator over the blocks below a node, see [`Node::blocks`]
+#[derive(Debug, Clone)]
+pub struct Blocks<'a>(Walk<'a>);
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = (NodePath, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Nothing below an inline is a block
+        self.0.find(Node::is_block, Node::is_block)
+    }
+}
+
+/// Iterator over the inlines below a node, see [`Node::inlines`]
+#[derive(Debug, Clone)]
+pub struct Inlines<'a>(Walk<'a>);
+
+impl<'a> Iterator for Inlines<'a> {
+    type Item = (NodePath, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.find(Node::is_inline, |_| true)
+    }
+}
+
+/// Depth-first walk below a node
+#[derive(Debug, Clone)]
+struct Walk<'a> {
+    /// Children left to visit at each depth, innermost last
+    stack: Vec<Children<'a>>,
+    /// Path of the node whose children are on top of the stack
+    path: NodePath,
+}
+
+impl<'a> Walk<'a> {
+    fn new(root: &'a Node) -> Self {
+        Self {
+            stack: vec![Children::new(root)],
+            path: NodePath::root(),
+        }
+    }
+
+    /// The next node matching `yields`, descending into the nodes matching
+    /// `descends`
+    fn find(
+        &mut self,
+        yields: impl Fn(&Node) -> bool,
+        descends: impl Fn(&Node) -> bool,
+    ) -> Option<(NodePath, &'a Node)> {
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            while self.path.len() > depth {
+                self.path.pop();
+            }
+            let Some((segment, child)) = self.stack[depth].next() else {
+                self.stack.pop();
+                continue;
+            };
+            let child = child.resolve_shared();
+            self.path.push(segment);
+            if descends(child) {
+                self.stack.push(Children::new(child));
+            }
+            if yields(child) {
+                return Some((self.path.clone(), child));
+            }
+        }
+    }
+}
+
+/// The direct children of a node with their segments, read in place
+#[derive(Debug, Clone)]
+pub(super) enum Children<'a> {
+    /// A single list of children
+    List(std::iter::Enumerate<std::slice::Iter<'a, Node>>),
+    /// The blocks of list items
+    ListItems {
+        items: &'a [ListItem],
+        item: usize,
+        child: usize,
+    },
+    /// The terms and definitions of a definition list
+    Definitions {
+        items: &'a [DefinitionItem],
+        item: usize,
+        /// `None` while in the term
+        definition: Option<usize>,
+        child: usize,
+    },
+    /// The header cells, then the body cells row by row
+    Table {
+        headers: &'a [Node],
+        rows: &'a [Vec<Node>],
+        /// `None` while in the header
+        row: Option<usize>,
+        column: usize,
+    },
+    /// A node without children
+    None,
+}
+
+impl<'a> Children<'a> {
+    pub(super) fn new(node: &'a Node) -> Self {
+        let node = node.resolve_shared();
+        if let Some(children) = node.child_list() {
+            return Children::List(children.iter().enumerate());
+        }
+        match node {
+            Node::UnorderedList(items) | Node::OrderedList { items, .. } => Children::ListItems {
+                items,
+                item: 0,
+                child: 0,
+            },
+            Node::DefinitionList(items) => Children::Definitions {
+                items,
+                item: 0,
+                definition: None,
+                child: 0,
+            },
+            Node::Table { headers, rows, .. } => Children::Table {
+                headers,
+                rows,
+                row: None,
+                column: 0,
+            },
+            _ => Children::None,
+        }
+    }
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (PathSegment, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Children::List(children) => children
+                .next()
+                .map(|(index, child)| (PathSegment::Child(index), child)),
+            Children::ListItems { items, item, child } => loop {
+                let content = list_item_content(items.get(*item)?);
+                if let Some(node) = content.get(*child) {
+                    let segment = PathSegment::ListItem {
+                        item: *item,
+                        child: *child,
+                    };
+                    *child += 1;
+                    return Some((segment, node));
+                }
+                *item += 1;
+                *child = 0;
+            },
+            Children::Definitions {
+                items,
+                item,
+                definition,
+                child,
+            } => loop {
+                let current = items.get(*item)?;
+                let part = match *definition {
+                    None => Some(&current.term),
+                    Some(index) => current.definitions.get(index),
+                };
+                let Some(part) = part else {
+                    *item += 1;
+                    *definition = None;
+                    *child = 0;
+                    continue;
+                };
+                if let Some(node) = part.get(*child) {
+                    let segment = PathSegment::Definition {
+                        item: *item,
+                        definition: *definition,
+                        child: *child,
+                    };
+                    *child += 1;
+                    return Some((segment, node));
+                }
+                *definition = Some(definition.map_or(0, |index| index + 1));
+                *child = 0;
+            },
+            Children::Table {
+                headers,
+                rows,
+                row,
+                column,
+            } => loop {
+                let (segment, cells) = match *row {
+                    None => (PathSegment::TableHeader(*column), *headers),
+                    Some(index) => (
+                        PathSegment::TableCell {
+                            row: index,
+                            column: *column,
+                        },
+                        rows.get(index)?.as_slice(),
+                    ),
+                };
+                if let Some(cell) = cells.get(*column) {
+                    *column += 1;
+                    return Some((segment, cell));
+                }
+                *row = Some(row.map_or(0, |index| index + 1));
+                *column = 0;
+            },
+            Children::None => None,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Link label normalization.
+
+use ecow::EcoString;
+
+/// Normalize a link label for matching, as the CommonMark spec describes
+///
+/// Leading and trailing whitespace is stripped, inner runs of spaces, tabs and
+/// line endings collapse to a single space, and the label is case folded, so
+/// `[Foo  Bar]` matches a definition of `[foo bar]`. Two labels refer to the
+/// same definition exactly when their normalized forms are equal.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::normalize_label;
+///
+/// assert_eq!(normalize_label("  Foo \n\tBAR "), normalize_label("foo bar"));
+/// assert_eq!(normalize_label("Straße"), normalize_label("STRASSE"));
+/// assert_ne!(normalize_label("foo"), normalize_label("foo-bar"));
+/// ```
+pub fn normalize_label(label: &str) -> EcoString {
+    let mut normalized = EcoString::new();
+    for word in label
+        .split([' ', '\t', '\n', '\r'])
+        .filter(|word| !word.is_empty())
+    {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        // Lowercasing and then uppercasing approximates Unicode case folding,
+        // e.g. `ß` and `SS` both become `SS`
+        for c in word.chars().flat_map(char::to_lowercase) {
+            normalized.extend(c.to_uppercase());
+        }
+    }
+    normalized
+}
@@ -3,12 +3,26 @@
 //! This module defines various node types for representing CommonMark documents,
 //! including headings, paragraphs, lists, code blocks, etc.
 
+mod copy;
 mod html;
+mod iter;
+mod label;
 mod node;
+mod path;
+mod slug;
 pub mod tables;
+mod url;
 
+pub(crate) use self::copy::{clone_all, make_shared_mut};
 pub use self::html::{HtmlAttribute, HtmlElement};
-pub use self::node::{CodeBlockType, HeadingType, ListItem, Node};
+pub use self::iter::{Blocks, Inlines};
+pub use self::label::normalize_label;
+pub use self::node::{CodeBlockType, DefinitionItem, HeadingType, ListItem, ListStyle, Node};
+pub(crate) use self::path::{list_item_content, list_item_content_mut};
+pub use self::path::{NodePath, PathSegment};
+pub use self::slug::{slugify, slugify_with, SlugOptions, Slugger};
+pub use self::url::MarkdownUrl;
+pub(crate) use self::url::{autolink_parts, effective_scheme, url_host};
 pub use crate::traits::CustomNode;
 
 // Re-export GFM specific types when the GFM feature is enabled
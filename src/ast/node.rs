@@ -1,12 +1,17 @@
 //! Node definitions for the CommonMark AST.
 
 use super::html::HtmlElement;
+use super::url::{is_email_address, strip_mailto, MarkdownUrl};
+use crate::options::DocumentOptions;
 use crate::traits::CustomNode;
 use ecow::EcoString;
 use std::boxed::Box;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::Arc;
 
 /// Code block type according to CommonMark specification
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum CodeBlockType {
     /// Indented code block - composed of one or more indented chunks, each preceded by four or more spaces
     Indented,
@@ -16,7 +21,7 @@ pub enum CodeBlockType {
 }
 
 /// Heading type according to CommonMark specification
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum HeadingType {
     /// ATX Type - Beginning with #
     #[default]
@@ -27,7 +32,7 @@ pub enum HeadingType {
 
 /// Table column alignment options for GFM tables
 #[cfg(feature = "gfm")]
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum TableAlignment {
     /// Left alignment (default)
     #[default]
@@ -42,7 +47,7 @@ pub enum TableAlignment {
 
 /// Task list item status for GFM task lists
 #[cfg(feature = "gfm")]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TaskListStatus {
     /// Checked/completed task
     Checked,
@@ -56,11 +61,29 @@ pub enum Node {
     /// Root document node, contains child nodes
     Document(Vec<Node>),
 
+    /// Root document node carrying option overrides that travel with the AST
+    ///
+    /// The CommonMark writer merges the overrides over its own options while
+    /// writing the children, so content-specific style choices (this document
+    /// uses `*` for emphasis) don't depend on the caller's configuration.
+    /// Otherwise it behaves like [`Node::Document`].
+    ConfiguredDocument {
+        /// Options overriding the writer's options within this document
+        options: DocumentOptions,
+        /// Child nodes
+        children: Vec<Node>,
+    },
+
     // Leaf blocks
     // Thematic breaks
     /// Thematic break (horizontal rule)
     ThematicBreak,
 
+    /// End of the summary of a document, written as `<!-- more -->` in
+    /// CommonMark and omitted in HTML (see
+    /// [`truncate_document`](crate::ops::truncate_document))
+    ReadMoreMarker,
+
     // ATX headings & Setext headings
     /// Heading, contains level (1-6) and inline content
     Heading {
@@ -70,6 +93,8 @@ pub enum Node {
         content: Vec<Node>,
         /// Heading type (ATX or Setext)
         heading_type: HeadingType,
+        /// Explicit anchor id, used instead of a generated slug
+        custom_id: Option<EcoString>,
     },
 
     // Indented code blocks & Fenced code blocks
@@ -87,6 +112,9 @@ pub enum Node {
     /// HTML block
     HtmlBlock(EcoString),
 
+    /// Raw inline HTML, such as a single tag or comment, written as is
+    HtmlInline(EcoString),
+
     // Link reference definitions
     /// Link reference definition
     LinkReferenceDefinition {
@@ -98,6 +126,15 @@ pub enum Node {
         title: Option<EcoString>,
     },
 
+    // Footnotes (GFM and Pandoc extension)
+    /// Footnote definition, written as `[^label]: content`
+    FootnoteDefinition {
+        /// Footnote label, matched case-insensitively by `FootnoteReference`
+        label: EcoString,
+        /// Footnote content, containing any block-level elements
+        content: Vec<Node>,
+    },
+
     // Paragraphs
     /// Paragraph node, containing inline elements
     Paragraph(Vec<Node>),
@@ -121,6 +158,10 @@ pub enum Node {
     /// Unordered list, containing list items
     UnorderedList(Vec<ListItem>),
 
+    /// Definition list (extension to CommonMark), containing terms and their
+    /// definitions
+    DefinitionList(Vec<DefinitionItem>),
+
     /// Table (extension to CommonMark)
     Table {
         /// Header cells
@@ -137,6 +178,16 @@ pub enum Node {
     /// Inline code
     InlineCode(EcoString),
 
+    // Math (extension)
+    /// Math formula in TeX notation, written as `$...$`, or `$$...$$` when
+    /// `display` is set (see `WriterOptions::math_delimiters`)
+    Math {
+        /// Whether the formula is typeset on its own line
+        display: bool,
+        /// TeX source of the formula, without delimiters
+        content: EcoString,
+    },
+
     // Emphasis and strong emphasis
     /// Emphasis (italic)
     Emphasis(Vec<Node>),
@@ -158,6 +209,19 @@ pub enum Node {
         content: Vec<Node>,
     },
 
+    /// Link to a heading of the document being written, resolved by the writer
+    ///
+    /// `target` is a heading's explicit id, its slug as generated by
+    /// [`Slugger`](crate::ast::Slugger) (`usage-1` for the second "Usage"), or
+    /// its text. Written as `[text](#slug)`, with the heading's text when `text`
+    /// is `None`. See [`HeadingAnchors`](crate::ops::HeadingAnchors).
+    CrossRef {
+        /// Id, slug or text of the heading
+        target: EcoString,
+        /// Link text, defaulting to the text of the heading
+        text: Option<Vec<Node>>,
+    },
+
     /// Reference link
     ReferenceLink {
         /// Link reference label
@@ -189,6 +253,9 @@ pub enum Node {
     /// GFM Extended Autolink (without angle brackets, automatically detected)
     ExtendedAutolink(EcoString),
 
+    /// Footnote reference, written as `[^label]`
+    FootnoteReference(EcoString),
+
     // Raw HTML
     /// HTML inline element
     HtmlElement(HtmlElement),
@@ -205,8 +272,19 @@ pub enum Node {
     /// Plain text
     Text(EcoString),
 
+    /// Reference to numbered item `id` of kind `kind` (e.g. `figure`), written
+    /// as text such as "Figure 3" using the writer's
+    /// [`Numbering`](crate::numbering::Numbering)
+    NumberRef(EcoString, EcoString),
+
     /// Custom node that allows users to implement their own writing behavior
     Custom(Box<dyn CustomNode>),
+
+    /// A subtree shared between documents, rendered exactly like the node it wraps
+    ///
+    /// Cloning a shared node only bumps the reference count, so repeated boilerplate
+    /// (headers, footers) can be reused across many documents without deep clones.
+    Shared(Arc<Node>),
 }
 
 impl Default for Node {
@@ -219,15 +297,22 @@ impl Clone for Node {
     fn clone(&self) -> Self {
         match self {
             Node::Document(nodes) => Node::Document(nodes.clone()),
+            Node::ConfiguredDocument { options, children } => Node::ConfiguredDocument {
+                options: options.clone(),
+                children: children.clone(),
+            },
             Node::ThematicBreak => Node::ThematicBreak,
+            Node::ReadMoreMarker => Node::ReadMoreMarker,
             Node::Heading {
                 level,
                 content,
                 heading_type,
+                custom_id,
             } => Node::Heading {
                 level: *level,
                 content: content.clone(),
                 heading_type: *heading_type,
+                custom_id: custom_id.clone(),
             },
             Node::CodeBlock {
                 language,
@@ -239,6 +324,7 @@ impl Clone for Node {
                 block_type: *block_type,
             },
             Node::HtmlBlock(html) => Node::HtmlBlock(html.clone()),
+            Node::HtmlInline(html) => Node::HtmlInline(html.clone()),
             Node::LinkReferenceDefinition {
                 label,
                 destination,
@@ -248,6 +334,10 @@ impl Clone for Node {
                 destination: destination.clone(),
                 title: title.clone(),
             },
+            Node::FootnoteDefinition { label, content } => Node::FootnoteDefinition {
+                label: label.clone(),
+                content: content.clone(),
+            },
             Node::Paragraph(content) => Node::Paragraph(content.clone()),
             Node::BlockQuote(content) => Node::BlockQuote(content.clone()),
             Node::OrderedList { start, items } => Node::OrderedList {
@@ -255,6 +345,7 @@ impl Clone for Node {
                 items: items.clone(),
             },
             Node::UnorderedList(items) => Node::UnorderedList(items.clone()),
+            Node::DefinitionList(items) => Node::DefinitionList(items.clone()),
             #[cfg(feature = "gfm")]
             Node::Table {
                 headers,
@@ -271,6 +362,10 @@ impl Clone for Node {
                 rows: rows.clone(),
             },
             Node::InlineCode(code) => Node::InlineCode(code.clone()),
+            Node::Math { display, content } => Node::Math {
+                display: *display,
+                content: content.clone(),
+            },
             Node::Emphasis(content) => Node::Emphasis(content.clone()),
             Node::Strong(content) => Node::Strong(content.clone()),
             Node::Strikethrough(content) => Node::Strikethrough(content.clone()),
@@ -283,6 +378,10 @@ impl Clone for Node {
                 title: title.clone(),
                 content: content.clone(),
             },
+            Node::CrossRef { target, text } => Node::CrossRef {
+                target: target.clone(),
+                text: text.clone(),
+            },
             Node::ReferenceLink { label, content } => Node::ReferenceLink {
                 label: label.clone(),
                 content: content.clone(),
@@ -297,10 +396,13 @@ impl Clone for Node {
                 is_email: *is_email,
             },
             Node::ExtendedAutolink(url) => Node::ExtendedAutolink(url.clone()),
+            Node::FootnoteReference(label) => Node::FootnoteReference(label.clone()),
             Node::HtmlElement(element) => Node::HtmlElement(element.clone()),
             Node::HardBreak => Node::HardBreak,
             Node::SoftBreak => Node::SoftBreak,
             Node::Text(text) => Node::Text(text.clone()),
+            Node::NumberRef(kind, id) => Node::NumberRef(kind.clone(), id.clone()),
+            Node::Shared(node) => Node::Shared(Arc::clone(node)),
             Node::Custom(_custom) => {
                 // 暂时不支持自定义节点的克隆，因为我们简化了设计
                 // 用户应该使用 Format trait 而不是直接使用 Custom 节点
@@ -314,19 +416,32 @@ impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Node::Document(a), Node::Document(b)) => a == b,
+            (
+                Node::ConfiguredDocument {
+                    options: o1,
+                    children: c1,
+                },
+                Node::ConfiguredDocument {
+                    options: o2,
+                    children: c2,
+                },
+            ) => o1 == o2 && c1 == c2,
             (Node::ThematicBreak, Node::ThematicBreak) => true,
+            (Node::ReadMoreMarker, Node::ReadMoreMarker) => true,
             (
                 Node::Heading {
                     level: l1,
                     content: c1,
                     heading_type: h1,
+                    custom_id: i1,
                 },
                 Node::Heading {
                     level: l2,
                     content: c2,
                     heading_type: h2,
+                    custom_id: i2,
                 },
-            ) => l1 == l2 && c1 == c2 && h1 == h2,
+            ) => l1 == l2 && c1 == c2 && h1 == h2 && i1 == i2,
             (
                 Node::CodeBlock {
                     language: l1,
@@ -340,6 +455,7 @@ impl PartialEq for Node {
                 },
             ) => l1 == l2 && c1 == c2 && b1 == b2,
             (Node::HtmlBlock(a), Node::HtmlBlock(b)) => a == b,
+            (Node::HtmlInline(a), Node::HtmlInline(b)) => a == b,
             (
                 Node::LinkReferenceDefinition {
                     label: l1,
@@ -352,6 +468,16 @@ impl PartialEq for Node {
                     title: t2,
                 },
             ) => l1 == l2 && d1 == d2 && t1 == t2,
+            (
+                Node::FootnoteDefinition {
+                    label: l1,
+                    content: c1,
+                },
+                Node::FootnoteDefinition {
+                    label: l2,
+                    content: c2,
+                },
+            ) => l1 == l2 && c1 == c2,
             (Node::Paragraph(a), Node::Paragraph(b)) => a == b,
             (Node::BlockQuote(a), Node::BlockQuote(b)) => a == b,
             (
@@ -365,6 +491,7 @@ impl PartialEq for Node {
                 },
             ) => s1 == s2 && i1 == i2,
             (Node::UnorderedList(a), Node::UnorderedList(b)) => a == b,
+            (Node::DefinitionList(a), Node::DefinitionList(b)) => a == b,
             #[cfg(feature = "gfm")]
             (
                 Node::Table {
@@ -390,9 +517,18 @@ impl PartialEq for Node {
                 },
             ) => h1 == h2 && r1 == r2,
             (Node::InlineCode(a), Node::InlineCode(b)) => a == b,
+            (
+                Node::Math {
+                    display: d1,
+                    content: c1,
+                },
+                Node::Math {
+                    display: d2,
+                    content: c2,
+                },
+            ) => d1 == d2 && c1 == c2,
             (Node::Emphasis(a), Node::Emphasis(b)) => a == b,
             (Node::Strong(a), Node::Strong(b)) => a == b,
-            #[cfg(feature = "gfm")]
             (Node::Strikethrough(a), Node::Strikethrough(b)) => a == b,
             (
                 Node::Link {
@@ -406,6 +542,16 @@ impl PartialEq for Node {
                     content: c2,
                 },
             ) => u1 == u2 && t1 == t2 && c1 == c2,
+            (
+                Node::CrossRef {
+                    target: t1,
+                    text: x1,
+                },
+                Node::CrossRef {
+                    target: t2,
+                    text: x2,
+                },
+            ) => t1 == t2 && x1 == x2,
             (
                 Node::ReferenceLink {
                     label: l1,
@@ -438,20 +584,138 @@ impl PartialEq for Node {
                     is_email: e2,
                 },
             ) => u1 == u2 && e1 == e2,
-            #[cfg(feature = "gfm")]
             (Node::ExtendedAutolink(a), Node::ExtendedAutolink(b)) => a == b,
+            (Node::FootnoteReference(a), Node::FootnoteReference(b)) => a == b,
             (Node::HtmlElement(a), Node::HtmlElement(b)) => a == b,
             (Node::HardBreak, Node::HardBreak) => true,
             (Node::SoftBreak, Node::SoftBreak) => true,
             (Node::Text(a), Node::Text(b)) => a == b,
+            (Node::NumberRef(k1, i1), Node::NumberRef(k2, i2)) => k1 == k2 && i1 == i2,
             (Node::Custom(a), Node::Custom(b)) => a.eq_box(&**b),
+            (Node::Shared(a), Node::Shared(b)) => Arc::ptr_eq(a, b) || a == b,
             _ => false,
         }
     }
 }
 
+impl Eq for Node {}
+
+/// Hashes consistently with `PartialEq`, so nodes can be used as cache keys.
+/// Custom nodes are hashed through [`NodeClone::hash_box`](crate::traits::NodeClone::hash_box).
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Node::Document(children)
+            | Node::Paragraph(children)
+            | Node::BlockQuote(children)
+            | Node::Emphasis(children)
+            | Node::Strong(children)
+            | Node::Strikethrough(children) => children.hash(state),
+            Node::ConfiguredDocument { options, children } => {
+                options.hash(state);
+                children.hash(state);
+            }
+            Node::ThematicBreak | Node::ReadMoreMarker | Node::HardBreak | Node::SoftBreak => {}
+            Node::Heading {
+                level,
+                content,
+                heading_type,
+                custom_id,
+            } => {
+                level.hash(state);
+                content.hash(state);
+                heading_type.hash(state);
+                custom_id.hash(state);
+            }
+            Node::CodeBlock {
+                language,
+                content,
+                block_type,
+            } => {
+                language.hash(state);
+                content.hash(state);
+                block_type.hash(state);
+            }
+            Node::HtmlBlock(text)
+            | Node::HtmlInline(text)
+            | Node::InlineCode(text)
+            | Node::ExtendedAutolink(text)
+            | Node::FootnoteReference(text)
+            | Node::Text(text) => text.hash(state),
+            Node::Math { display, content } => {
+                display.hash(state);
+                content.hash(state);
+            }
+            Node::LinkReferenceDefinition {
+                label,
+                destination,
+                title,
+            } => {
+                label.hash(state);
+                destination.hash(state);
+                title.hash(state);
+            }
+            Node::FootnoteDefinition { label, content } => {
+                label.hash(state);
+                content.hash(state);
+            }
+            Node::OrderedList { start, items } => {
+                start.hash(state);
+                items.hash(state);
+            }
+            Node::UnorderedList(items) => items.hash(state),
+            Node::DefinitionList(items) => items.hash(state),
+            Node::Table {
+                headers,
+                #[cfg(feature = "gfm")]
+                alignments,
+                rows,
+            } => {
+                headers.hash(state);
+                #[cfg(feature = "gfm")]
+                alignments.hash(state);
+                rows.hash(state);
+            }
+            Node::Link {
+                url,
+                title,
+                content,
+            } => {
+                url.hash(state);
+                title.hash(state);
+                content.hash(state);
+            }
+            Node::CrossRef { target, text } => {
+                target.hash(state);
+                text.hash(state);
+            }
+            Node::ReferenceLink { label, content } => {
+                label.hash(state);
+                content.hash(state);
+            }
+            Node::Image { url, title, alt } => {
+                url.hash(state);
+                title.hash(state);
+                alt.hash(state);
+            }
+            Node::Autolink { url, is_email } => {
+                url.hash(state);
+                is_email.hash(state);
+            }
+            Node::NumberRef(kind, id) => {
+                kind.hash(state);
+                id.hash(state);
+            }
+            Node::HtmlElement(element) => element.hash(state),
+            Node::Custom(node) => node.hash_box(state),
+            Node::Shared(node) => node.hash(state),
+        }
+    }
+}
+
 /// List item type
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ListItem {
     /// Unordered list item
     Unordered {
@@ -475,49 +739,120 @@ pub enum ListItem {
     },
 }
 
+/// Item of a [`Node::DefinitionList`]: a term and its definitions
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DefinitionItem {
+    /// The term being defined, containing inline elements on a single line
+    pub term: Vec<Node>,
+    /// Definitions of the term, each containing one or more block-level elements
+    pub definitions: Vec<Vec<Node>>,
+}
+
+impl DefinitionItem {
+    /// Create an item defining `term`
+    pub fn new(term: Vec<Node>, definitions: Vec<Vec<Node>>) -> Self {
+        Self { term, definitions }
+    }
+}
+
+/// Kind of list a [`ListItem`] is rendered for on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListStyle {
+    /// Item of an unordered list, marked with the configured list marker
+    Unordered,
+    /// Item of an ordered list, marked with the given number
+    Ordered(u32),
+}
+
 impl Node {
     /// Check if a node is a block-level node
     pub fn is_block(&self) -> bool {
+        if let Node::Shared(node) = self {
+            return node.is_block();
+        }
         matches!(
             self,
             Node::Document(_)
+                | Node::ConfiguredDocument { .. }
                 // Leaf blocks
                 | Node::ThematicBreak
+                | Node::ReadMoreMarker
                 | Node::Heading { .. }
                 | Node::CodeBlock { .. }
                 | Node::HtmlBlock(_)
                 | Node::LinkReferenceDefinition { .. }
                 | Node::Paragraph(_)
                 // Container blocks
+                | Node::FootnoteDefinition { .. }
                 | Node::BlockQuote(_)
                 | Node::OrderedList { .. }
                 | Node::UnorderedList(_)
+                | Node::DefinitionList(_)
                 | Node::Table { .. }
 
                 | Node::Custom(_)
         )
     }
 
+    /// Check if a node is a container with no content
+    ///
+    /// This covers headings, paragraphs, block quotes, lists, tables without header
+    /// cells, and emphasis, strong and strikethrough nodes. An empty document is not
+    /// considered an empty container, since it simply renders to an empty string.
+    pub fn is_empty_container(&self) -> bool {
+        match self {
+            Node::Heading { content, .. } => content.is_empty(),
+            Node::Paragraph(content)
+            | Node::BlockQuote(content)
+            | Node::Emphasis(content)
+            | Node::Strong(content)
+            | Node::Strikethrough(content) => content.is_empty(),
+            Node::OrderedList { items, .. } | Node::UnorderedList(items) => items.is_empty(),
+            Node::DefinitionList(items) => items.is_empty(),
+            Node::Table { headers, .. } => headers.is_empty(),
+            Node::Shared(node) => node.is_empty_container(),
+            _ => false,
+        }
+    }
+
+    /// The node itself, or the node behind any number of [`Node::Shared`] wrappers
+    pub fn resolve_shared(&self) -> &Node {
+        let mut node = self;
+        while let Node::Shared(inner) = node {
+            node = inner;
+        }
+        node
+    }
+
     /// Check if a node is an inline node
     pub fn is_inline(&self) -> bool {
+        if let Node::Shared(node) = self {
+            return node.is_inline();
+        }
         matches!(
             self,
             // Inlines
             // Code spans
             Node::InlineCode(_)
+                // Math
+                | Node::Math { .. }
                 // Emphasis and strong emphasis
                 | Node::Emphasis(_)
                 | Node::Strong(_)
                 | Node::Strikethrough(_)
                 // Links
                 | Node::Link { .. }
+                | Node::CrossRef { .. }
                 | Node::ReferenceLink { .. }
                 // Images
                 | Node::Image { .. }
                 // Autolinks
                 | Node::Autolink { .. }
                 | Node::ExtendedAutolink(_)
+                // Footnotes
+                | Node::FootnoteReference(_)
                 // Raw HTML
+                | Node::HtmlInline(_)
                 | Node::HtmlElement(_)
                 // Hard line breaks
                 | Node::HardBreak
@@ -525,6 +860,7 @@ impl Node {
                 | Node::SoftBreak
                 // Textual content
                 | Node::Text(_)
+                | Node::NumberRef(..)
 
                 | Node::Custom(_)
         )
@@ -534,32 +870,191 @@ impl Node {
     pub fn type_name(&self) -> &'static str {
         match self {
             Node::Document(_) => "Document",
+            Node::ConfiguredDocument { .. } => "ConfiguredDocument",
             Node::ThematicBreak => "ThematicBreak",
+            Node::ReadMoreMarker => "ReadMoreMarker",
             Node::Heading { .. } => "Heading",
             Node::CodeBlock { .. } => "CodeBlock",
             Node::HtmlBlock(_) => "HtmlBlock",
+            Node::HtmlInline(_) => "HtmlInline",
             Node::LinkReferenceDefinition { .. } => "LinkReferenceDefinition",
+            Node::FootnoteDefinition { .. } => "FootnoteDefinition",
             Node::Paragraph(_) => "Paragraph",
             Node::BlockQuote(_) => "BlockQuote",
             Node::OrderedList { .. } => "OrderedList",
             Node::UnorderedList(_) => "UnorderedList",
+            Node::DefinitionList(_) => "DefinitionList",
             Node::Table { .. } => "Table",
             Node::InlineCode(_) => "InlineCode",
+            Node::Math { .. } => "Math",
             Node::Emphasis(_) => "Emphasis",
             Node::Strong(_) => "Strong",
             Node::Strikethrough(_) => "Strikethrough",
             Node::Link { .. } => "Link",
+            Node::CrossRef { .. } => "CrossRef",
             Node::ReferenceLink { .. } => "ReferenceLink",
             Node::Image { .. } => "Image",
             Node::Autolink { .. } => "Autolink",
             Node::ExtendedAutolink(_) => "ExtendedAutolink",
+            Node::FootnoteReference(_) => "FootnoteReference",
             Node::HtmlElement(_) => "HtmlElement",
             Node::HardBreak => "HardBreak",
             Node::SoftBreak => "SoftBreak",
             Node::Text(_) => "Text",
+            Node::NumberRef(..) => "NumberRef",
             Node::Custom(_) => "Custom",
+            Node::Shared(node) => node.type_name(),
+        }
+    }
+
+    /// The text content of the node with all markup removed
+    ///
+    /// Breaks become spaces; code and autolinks contribute their literal text.
+    /// Useful for deriving heading slugs and link labels.
+    pub fn plain_text(&self) -> EcoString {
+        let mut buffer = EcoString::new();
+        self.push_plain_text(&mut buffer);
+        buffer
+    }
+
+    fn push_plain_text(&self, buffer: &mut EcoString) {
+        match self.resolve_shared() {
+            Node::Text(text) | Node::InlineCode(text) | Node::Math { content: text, .. } => {
+                buffer.push_str(text)
+            }
+            Node::Autolink { url, .. } | Node::ExtendedAutolink(url) => buffer.push_str(url),
+            Node::SoftBreak | Node::HardBreak => buffer.push(' '),
+            Node::Emphasis(children)
+            | Node::Strong(children)
+            | Node::Strikethrough(children)
+            | Node::Paragraph(children)
+            | Node::Heading {
+                content: children, ..
+            }
+            | Node::Link {
+                content: children, ..
+            }
+            | Node::ReferenceLink {
+                content: children, ..
+            }
+            | Node::Image { alt: children, .. } => {
+                for child in children {
+                    child.push_plain_text(buffer);
+                }
+            }
+            Node::HtmlElement(element) => {
+                for child in &element.children {
+                    child.push_plain_text(buffer);
+                }
+            }
+            Node::CrossRef { target, text } => match text {
+                Some(children) => {
+                    for child in children {
+                        child.push_plain_text(buffer);
+                    }
+                }
+                None => buffer.push_str(target),
+            },
+            _ => {}
+        }
+    }
+
+    /// Number of nodes in the subtree, counting this node
+    ///
+    /// Every node reached by [`walk_with_paths`](Self::walk_with_paths) counts
+    /// once: list items and table cells contribute their content, and a shared
+    /// node counts as one node plus the children of the node it wraps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    ///
+    /// let paragraph = Node::Paragraph(vec![
+    ///     Node::Text("a ".into()),
+    ///     Node::Strong(vec![Node::Text("b".into())]),
+    /// ]);
+    /// assert_eq!(paragraph.deep_len(), 4);
+    /// ```
+    pub fn deep_len(&self) -> usize {
+        let mut len = 0;
+        self.walk_with_paths(|_, _| len += 1);
+        len
+    }
+
+    /// Structural hash of the subtree, for keying render caches
+    ///
+    /// Uses the [`Hash`] implementation of `Node`, so nodes that compare equal
+    /// have the same hash, with a fixed-seed hasher: unlike `HashMap`'s
+    /// randomly seeded hasher, the value is the same across runs and processes
+    /// for a given crate version and target. Custom nodes contribute through
+    /// [`NodeClone::hash_box`](crate::traits::NodeClone::hash_box).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    ///
+    /// let a = Node::Paragraph(vec![Node::Text("same".into())]);
+    /// let b = Node::Paragraph(vec![Node::Text("same".into())]);
+    /// assert_eq!(a.content_hash(), b.content_hash());
+    /// assert_ne!(a.content_hash(), Node::Text("same".into()).content_hash());
+    /// ```
+    #[cfg(feature = "content-hash")]
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Wrap a node for sharing between documents
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    ///
+    /// let footer = Node::shared(Node::Paragraph(vec![Node::Text("© ACME".into())]));
+    /// let doc_a = Node::Document(vec![Node::Text("A".into()), footer.clone()]);
+    /// let doc_b = Node::Document(vec![Node::Text("B".into()), footer]);
+    /// # let _ = (doc_a, doc_b);
+    /// ```
+    pub fn shared(node: Node) -> Self {
+        Node::Shared(Arc::new(node))
+    }
+
+    /// Create inline text nodes from a string that may contain line breaks
+    ///
+    /// Each line becomes a text node and the line breaks (`\n` or `\r\n`)
+    /// become soft breaks, so multi-line strings can be used as paragraph
+    /// content without tripping the strict-mode newline check. Blank lines
+    /// would end a paragraph and are dropped.
+    ///
+    /// # Arguments
+    /// * `text` - Text, possibly spanning several lines
+    ///
+    /// # Returns
+    /// Text nodes separated by soft breaks
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    ///
+    /// let paragraph = Node::Paragraph(Node::text_multiline("first line\nsecond line\n"));
+    /// assert_eq!(paragraph.to_string(), "first line\nsecond line\n");
+    /// ```
+    pub fn text_multiline(text: &str) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            if !nodes.is_empty() {
+                nodes.push(Node::SoftBreak);
+            }
+            nodes.push(Node::Text(line.into()));
         }
+        nodes
     }
+
     /// Create a heading node
     ///
     /// # Arguments
@@ -573,6 +1068,25 @@ impl Node {
             level,
             content,
             heading_type: HeadingType::default(),
+            custom_id: None,
+        }
+    }
+
+    /// Create a heading node with an explicit anchor id
+    ///
+    /// # Arguments
+    /// * `level` - Heading level (1-6)
+    /// * `content` - Heading content
+    /// * `id` - Anchor id, used instead of a generated slug
+    ///
+    /// # Returns
+    /// A new heading node, default ATX type
+    pub fn heading_with_id<S: Into<EcoString>>(level: u8, content: Vec<Node>, id: S) -> Self {
+        Node::Heading {
+            level,
+            content,
+            heading_type: HeadingType::default(),
+            custom_id: Some(id.into()),
         }
     }
 
@@ -592,6 +1106,89 @@ impl Node {
         }
     }
 
+    /// Create a link node from a validated URL
+    ///
+    /// # Arguments
+    /// * `url` - Link destination
+    /// * `title` - Optional link title
+    /// * `content` - Link text
+    ///
+    /// # Returns
+    /// A new link node
+    pub fn link(url: MarkdownUrl, title: Option<EcoString>, content: Vec<Node>) -> Self {
+        Node::Link {
+            url: url.into(),
+            title,
+            content,
+        }
+    }
+
+    /// Create an image node from a validated URL
+    ///
+    /// # Arguments
+    /// * `url` - Image source
+    /// * `title` - Optional image title
+    /// * `alt` - Alternative text
+    ///
+    /// # Returns
+    /// A new image node
+    pub fn image(url: MarkdownUrl, title: Option<EcoString>, alt: Vec<Node>) -> Self {
+        Node::Image {
+            url: url.into(),
+            title,
+            alt,
+        }
+    }
+
+    /// Create a URI autolink node from a validated URL
+    ///
+    /// # Arguments
+    /// * `url` - Autolink destination
+    ///
+    /// # Returns
+    /// A new autolink node
+    pub fn autolink(url: MarkdownUrl) -> Self {
+        Node::Autolink {
+            url: url.into(),
+            is_email: false,
+        }
+    }
+
+    /// Create an email autolink node
+    ///
+    /// A leading `mailto:` is removed, since email autolinks are written without it.
+    ///
+    /// # Arguments
+    /// * `address` - Email address, with or without `mailto:`
+    ///
+    /// # Returns
+    /// A new email autolink node
+    pub fn email<S: AsRef<str>>(address: S) -> Self {
+        Node::Autolink {
+            url: strip_mailto(address.as_ref()).into(),
+            is_email: true,
+        }
+    }
+
+    /// Create an autolink node, classifying the URL as email or URI
+    ///
+    /// `mailto:` URLs and bare email addresses become email autolinks, anything
+    /// else a URI autolink.
+    ///
+    /// # Arguments
+    /// * `url` - Autolink destination
+    ///
+    /// # Returns
+    /// A new autolink node
+    pub fn url(url: MarkdownUrl) -> Self {
+        let address = strip_mailto(&url);
+        if address.len() < url.len() || (!url.is_absolute() && is_email_address(address)) {
+            Node::email(address)
+        } else {
+            Node::autolink(url)
+        }
+    }
+
     /// Create a strikethrough node
     ///
     /// # Arguments
@@ -671,6 +1268,38 @@ impl crate::traits::Format<crate::writer::CommonMarkWriter> for Node {
 
 impl crate::traits::Format<crate::writer::HtmlWriter> for Node {
     fn format(&self, writer: &mut crate::writer::HtmlWriter) -> crate::error::WriteResult<()> {
-        writer.write_node_internal(self).map_err(Into::into)
+        let result = writer.write_node_internal(self).map_err(Into::into);
+        writer.locate(self, result)
+    }
+}
+
+impl From<Arc<Node>> for Node {
+    fn from(node: Arc<Node>) -> Self {
+        Node::Shared(node)
+    }
+}
+
+/// 64-bit FNV-1a, which has no random seed and so gives stable hashes
+#[cfg(feature = "content-hash")]
+struct Fnv1aHasher(u64);
+
+#[cfg(feature = "content-hash")]
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(feature = "content-hash")]
+impl Hasher for Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
     }
 }
@@ -0,0 +1,439 @@
+//! Addressing nodes inside a document by path.
+//!
+//! This module provides [`NodePath`], a sequence of child selectors that
+//! identifies a node relative to a root node, together with [`Node::get`],
+//! [`Node::get_mut`] and [`Node::replace`] for retrieving and patching it.
+
+use super::iter::Children;
+use super::{make_shared_mut, DefinitionItem, ListItem, Node};
+use std::fmt;
+
+/// One step of a [`NodePath`]
+///
+/// Segments order by document position among siblings, so sorting paths by
+/// their segments puts them in document order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathSegment {
+    /// Child `n` of a node with a single list of children (documents, paragraphs,
+    /// headings, block quotes, emphasis, links, image alt text, HTML elements)
+    Child(usize),
+    /// Block `child` of list item `item` in an ordered or unordered list
+    ListItem {
+        /// Index of the list item
+        item: usize,
+        /// Index of the block within the item
+        child: usize,
+    },
+    /// Node `child` of item `item` in a definition list: an inline of the term
+    /// when `definition` is `None`, otherwise a block of that definition
+    Definition {
+        /// Index of the definition list item
+        item: usize,
+        /// Index of the definition, or `None` for the term
+        definition: Option<usize>,
+        /// Index of the node within the term or definition
+        child: usize,
+    },
+    /// Header cell `n` of a table
+    TableHeader(usize),
+    /// Cell `column` of body row `row` of a table
+    TableCell {
+        /// Index of the body row
+        row: usize,
+        /// Index of the cell within the row
+        column: usize,
+    },
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Child(index) => write!(f, "{}", index),
+            PathSegment::ListItem { item, child } => write!(f, "items[{}]/{}", item, child),
+            PathSegment::Definition {
+                item,
+                definition: None,
+                child,
+            } => write!(f, "terms[{}]/{}", item, child),
+            PathSegment::Definition {
+                item,
+                definition: Some(definition),
+                child,
+            } => write!(f, "definitions[{}][{}]/{}", item, definition, child),
+            PathSegment::TableHeader(index) => write!(f, "headers[{}]", index),
+            PathSegment::TableCell { row, column } => write!(f, "rows[{}][{}]", row, column),
+        }
+    }
+}
+
+/// Location of a node relative to a root node
+///
+/// An empty path addresses the root itself. [`Node::Shared`] wrappers are
+/// looked through transparently and do not take a segment.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::{Node, NodePath};
+///
+/// let mut doc = Node::Document(vec![
+///     Node::heading(1, vec![Node::Text("Title".into())]),
+///     Node::Paragraph(vec![Node::Text("First".into())]),
+/// ]);
+///
+/// let path = NodePath::root().child(1).child(0);
+/// assert_eq!(doc.get(&path), Some(&Node::Text("First".into())));
+///
+/// doc.replace(&path, Node::Text("Patched".into())).unwrap();
+/// assert_eq!(doc.get(&path), Some(&Node::Text("Patched".into())));
+/// assert_eq!(path.to_string(), "/1/0");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NodePath(Vec<PathSegment>);
+
+impl NodePath {
+    /// The empty path, addressing the root node
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Extend the path with a child index
+    pub fn child(self, index: usize) -> Self {
+        self.with(PathSegment::Child(index))
+    }
+
+    /// Extend the path with a block inside a list item
+    pub fn list_item(self, item: usize, child: usize) -> Self {
+        self.with(PathSegment::ListItem { item, child })
+    }
+
+    /// Extend the path with an inline of a definition list term
+    pub fn definition_term(self, item: usize, child: usize) -> Self {
+        self.with(PathSegment::Definition {
+            item,
+            definition: None,
+            child,
+        })
+    }
+
+    /// Extend the path with a block inside a definition of a definition list
+    pub fn definition(self, item: usize, definition: usize, child: usize) -> Self {
+        self.with(PathSegment::Definition {
+            item,
+            definition: Some(definition),
+            child,
+        })
+    }
+
+    /// Extend the path with a table header cell
+    pub fn table_header(self, index: usize) -> Self {
+        self.with(PathSegment::TableHeader(index))
+    }
+
+    /// Extend the path with a table body cell
+    pub fn table_cell(self, row: usize, column: usize) -> Self {
+        self.with(PathSegment::TableCell { row, column })
+    }
+
+    /// Extend the path with any segment
+    pub fn with(mut self, segment: PathSegment) -> Self {
+        self.0.push(segment);
+        self
+    }
+
+    /// Append a segment in place
+    pub fn push(&mut self, segment: PathSegment) {
+        self.0.push(segment);
+    }
+
+    /// Remove and return the last segment
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.0.pop()
+    }
+
+    /// The path of the parent node, or `None` for the root
+    pub fn parent(&self) -> Option<NodePath> {
+        let (_, parent) = self.0.split_last()?;
+        Some(NodePath(parent.to_vec()))
+    }
+
+    /// The segments of the path, outermost first
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// Number of segments in the path
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the path addresses the root node
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("/");
+        }
+        for segment in &self.0 {
+            write!(f, "/{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<PathSegment> for NodePath {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(iter: I) -> Self {
+        NodePath(iter.into_iter().collect())
+    }
+}
+
+impl From<Vec<PathSegment>> for NodePath {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        NodePath(segments)
+    }
+}
+
+pub(crate) fn list_item_content(item: &ListItem) -> &Vec<Node> {
+    match item {
+        ListItem::Unordered { content } | ListItem::Ordered { content, .. } => content,
+        #[cfg(feature = "gfm")]
+        ListItem::Task { content, .. } => content,
+    }
+}
+
+pub(crate) fn list_item_content_mut(item: &mut ListItem) -> &mut Vec<Node> {
+    match item {
+        ListItem::Unordered { content } | ListItem::Ordered { content, .. } => content,
+        #[cfg(feature = "gfm")]
+        ListItem::Task { content, .. } => content,
+    }
+}
+
+/// The term of a definition list item, or one of its definitions
+fn definition_part(item: &DefinitionItem, definition: Option<usize>) -> Option<&Vec<Node>> {
+    match definition {
+        None => Some(&item.term),
+        Some(index) => item.definitions.get(index),
+    }
+}
+
+fn definition_part_mut(
+    item: &mut DefinitionItem,
+    definition: Option<usize>,
+) -> Option<&mut Vec<Node>> {
+    match definition {
+        None => Some(&mut item.term),
+        Some(index) => item.definitions.get_mut(index),
+    }
+}
+
+impl Node {
+    /// The node's single list of children, if it has one
+    pub(super) fn child_list(&self) -> Option<&Vec<Node>> {
+        match self {
+            Node::Document(children)
+            | Node::ConfiguredDocument { children, .. }
+            | Node::Paragraph(children)
+            | Node::BlockQuote(children)
+            | Node::FootnoteDefinition {
+                content: children, ..
+            }
+            | Node::Emphasis(children)
+            | Node::Strong(children)
+            | Node::Strikethrough(children)
+            | Node::Heading {
+                content: children, ..
+            }
+            | Node::Link {
+                content: children, ..
+            }
+            | Node::ReferenceLink {
+                content: children, ..
+            }
+            | Node::Image { alt: children, .. }
+            | Node::CrossRef {
+                text: Some(children),
+                ..
+            } => Some(children),
+            Node::HtmlElement(element) => Some(&element.children),
+            _ => None,
+        }
+    }
+
+    fn child_list_mut(&mut self) -> Option<&mut Vec<Node>> {
+        match self {
+            Node::Document(children)
+            | Node::ConfiguredDocument { children, .. }
+            | Node::Paragraph(children)
+            | Node::BlockQuote(children)
+            | Node::FootnoteDefinition {
+                content: children, ..
+            }
+            | Node::Emphasis(children)
+            | Node::Strong(children)
+            | Node::Strikethrough(children)
+            | Node::Heading {
+                content: children, ..
+            }
+            | Node::Link {
+                content: children, ..
+            }
+            | Node::ReferenceLink {
+                content: children, ..
+            }
+            | Node::Image { alt: children, .. }
+            | Node::CrossRef {
+                text: Some(children),
+                ..
+            } => Some(children),
+            Node::HtmlElement(element) => Some(&mut element.children),
+            _ => None,
+        }
+    }
+
+    /// The direct child addressed by `segment`
+    pub fn get_child(&self, segment: PathSegment) -> Option<&Node> {
+        let node = self.resolve_shared();
+        match (segment, node) {
+            (PathSegment::Child(index), _) => node.child_list()?.get(index),
+            (
+                PathSegment::ListItem { item, child },
+                Node::UnorderedList(items) | Node::OrderedList { items, .. },
+            ) => list_item_content(items.get(item)?).get(child),
+            (
+                PathSegment::Definition {
+                    item,
+                    definition,
+                    child,
+                },
+                Node::DefinitionList(items),
+            ) => definition_part(items.get(item)?, definition)?.get(child),
+            (PathSegment::TableHeader(index), Node::Table { headers, .. }) => headers.get(index),
+            (PathSegment::TableCell { row, column }, Node::Table { rows, .. }) => {
+                rows.get(row)?.get(column)
+            }
+            _ => None,
+        }
+    }
+
+    /// Mutable access to the direct child addressed by `segment`
+    ///
+    /// A [`Node::Shared`] subtree is copied first if it is shared (copy-on-write).
+    /// Custom nodes can't be copied, so this returns `None` if the subtree is
+    /// shared and holds one.
+    pub fn get_child_mut(&mut self, segment: PathSegment) -> Option<&mut Node> {
+        let mut node = self;
+        while let Node::Shared(inner) = node {
+            node = make_shared_mut(inner)?;
+        }
+        match (segment, node) {
+            (PathSegment::Child(index), node) => node.child_list_mut()?.get_mut(index),
+            (
+                PathSegment::ListItem { item, child },
+                Node::UnorderedList(items) | Node::OrderedList { items, .. },
+            ) => list_item_content_mut(items.get_mut(item)?).get_mut(child),
+            (
+                PathSegment::Definition {
+                    item,
+                    definition,
+                    child,
+                },
+                Node::DefinitionList(items),
+            ) => definition_part_mut(items.get_mut(item)?, definition)?.get_mut(child),
+            (PathSegment::TableHeader(index), Node::Table { headers, .. }) => {
+                headers.get_mut(index)
+            }
+            (PathSegment::TableCell { row, column }, Node::Table { rows, .. }) => {
+                rows.get_mut(row)?.get_mut(column)
+            }
+            _ => None,
+        }
+    }
+
+    /// The list of siblings holding the child addressed by `segment`, with the
+    /// child's index in it
+    ///
+    /// Table cells hold a single node and have no such list. A shared subtree
+    /// is copied first, as in [`get_child_mut`](Self::get_child_mut).
+    pub(crate) fn sibling_list_mut(
+        &mut self,
+        segment: PathSegment,
+    ) -> Option<(&mut Vec<Node>, usize)> {
+        let mut node = self;
+        while let Node::Shared(inner) = node {
+            node = make_shared_mut(inner)?;
+        }
+        match (segment, node) {
+            (PathSegment::Child(index), node) => Some((node.child_list_mut()?, index)),
+            (
+                PathSegment::ListItem { item, child },
+                Node::UnorderedList(items) | Node::OrderedList { items, .. },
+            ) => Some((list_item_content_mut(items.get_mut(item)?), child)),
+            (
+                PathSegment::Definition {
+                    item,
+                    definition,
+                    child,
+                },
+                Node::DefinitionList(items),
+            ) => Some((
+                definition_part_mut(items.get_mut(item)?, definition)?,
+                child,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The direct children of the node together with the segment addressing each
+    pub fn children_with_segments(&self) -> Vec<(PathSegment, &Node)> {
+        Children::new(self).collect()
+    }
+
+    /// Visit this node and all its descendants in document order, with their paths
+    ///
+    /// [`Node::Shared`] wrappers are looked through: `f` sees the node they wrap.
+    pub fn walk_with_paths<'a, F: FnMut(&NodePath, &'a Node)>(&'a self, mut f: F) {
+        fn walk<'a, F: FnMut(&NodePath, &'a Node)>(node: &'a Node, path: &mut NodePath, f: &mut F) {
+            let node = node.resolve_shared();
+            f(path, node);
+            for (segment, child) in node.children_with_segments() {
+                path.push(segment);
+                walk(child, path, f);
+                path.pop();
+            }
+        }
+        walk(self, &mut NodePath::root(), &mut f);
+    }
+
+    /// Get the node at `path`, relative to this node
+    ///
+    /// Returns `None` if any segment doesn't match the structure of the tree.
+    pub fn get(&self, path: &NodePath) -> Option<&Node> {
+        path.segments()
+            .iter()
+            .try_fold(self, |node, segment| node.get_child(*segment))
+    }
+
+    /// Get mutable access to the node at `path`, relative to this node
+    ///
+    /// Returns `None` if any segment doesn't match the structure of the tree,
+    /// or if the path leads through a shared subtree that would have to be
+    /// copied but holds a custom node (see [`get_child_mut`](Self::get_child_mut)).
+    pub fn get_mut(&mut self, path: &NodePath) -> Option<&mut Node> {
+        path.segments()
+            .iter()
+            .try_fold(self, |node, segment| node.get_child_mut(*segment))
+    }
+
+    /// Replace the node at `path` with `node`, returning the previous node
+    ///
+    /// Returns `None` (and drops `node`) if the path does not exist.
+    pub fn replace(&mut self, path: &NodePath, node: Node) -> Option<Node> {
+        self.get_mut(path)
+            .map(|target| std::mem::replace(target, node))
+    }
+}
@@ -0,0 +1,189 @@
+//! Heading slug generation.
+
+use ecow::EcoString;
+use std::collections::HashMap;
+
+/// How heading text is turned into slugs
+///
+/// The default matches GitHub's anchors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlugOptions {
+    /// Character that replaces whitespace
+    pub separator: char,
+    /// Maximum length of a slug in characters, not counting the suffix added to
+    /// duplicates. Longer slugs are cut, without a trailing separator.
+    pub max_length: Option<usize>,
+    /// Replace accented Latin letters with their ASCII base letters (`é` to `e`,
+    /// `ß` to `ss`). Other characters are kept.
+    pub transliterate: bool,
+}
+
+impl Default for SlugOptions {
+    fn default() -> Self {
+        Self {
+            separator: '-',
+            max_length: None,
+            transliterate: false,
+        }
+    }
+}
+
+impl SlugOptions {
+    /// Set the character that replaces whitespace
+    pub fn with_separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Set the maximum slug length
+    pub fn with_max_length(mut self, max_length: Option<usize>) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Set whether accented Latin letters are transliterated to ASCII
+    pub fn with_transliterate(mut self, transliterate: bool) -> Self {
+        self.transliterate = transliterate;
+        self
+    }
+}
+
+/// Convert heading text to a URL fragment, following GitHub's rules
+///
+/// The text is lowercased, characters other than letters, digits, `-`, `_` and
+/// spaces are removed, and spaces become `-`.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::slugify;
+///
+/// assert_eq!(slugify("Hello, World!"), "hello-world");
+/// assert_eq!(slugify("API v2 (beta)"), "api-v2-beta");
+/// ```
+pub fn slugify(text: &str) -> EcoString {
+    slugify_with(text, &SlugOptions::default())
+}
+
+/// Convert heading text to a URL fragment using custom options
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::{slugify_with, SlugOptions};
+///
+/// let options = SlugOptions::default()
+///     .with_separator('_')
+///     .with_max_length(Some(12))
+///     .with_transliterate(true);
+/// assert_eq!(slugify_with("Café au lait recipes", &options), "cafe_au_lait");
+/// ```
+pub fn slugify_with(text: &str, options: &SlugOptions) -> EcoString {
+    let mut slug = EcoString::new();
+    let mut len = 0;
+    let mut push = |c: char| {
+        if options.max_length.is_some_and(|max| len >= max) {
+            return;
+        }
+        slug.push(c);
+        len += 1;
+    };
+    for c in text.trim().chars() {
+        if options.transliterate {
+            if let Some(ascii) = transliterate(c) {
+                ascii.chars().for_each(&mut push);
+                continue;
+            }
+        }
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            c.to_lowercase().for_each(&mut push);
+        } else if c.is_whitespace() {
+            push(options.separator);
+        }
+    }
+    if options.max_length.is_some() {
+        while slug.ends_with(options.separator) {
+            slug.pop();
+        }
+    }
+    slug
+}
+
+/// Lowercase ASCII replacement for an accented Latin letter
+fn transliterate(c: char) -> Option<&'static str> {
+    let ascii = match c.to_lowercase().next()? {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+/// Generates unique slugs for the headings of a document
+///
+/// Repeated slugs get a numeric suffix (`intro`, `intro-1`, `intro-2`), matching
+/// the anchors GitHub generates for duplicate headings.
+#[derive(Debug, Clone, Default)]
+pub struct Slugger {
+    options: SlugOptions,
+    seen: HashMap<EcoString, usize>,
+}
+
+impl Slugger {
+    /// Create a slugger with no slugs issued yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a slugger using custom slug options
+    pub fn with_options(options: SlugOptions) -> Self {
+        Self {
+            options,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// The options used to slugify text
+    pub fn options(&self) -> &SlugOptions {
+        &self.options
+    }
+
+    /// Slugify `text`, adding a suffix if the slug was already issued
+    pub fn slug(&mut self, text: &str) -> EcoString {
+        let base = slugify_with(text, &self.options);
+        if !self.seen.contains_key(&base) {
+            self.seen.insert(base.clone(), 0);
+            return base;
+        }
+        loop {
+            let count = self.seen.entry(base.clone()).or_default();
+            *count += 1;
+            let slug = EcoString::from(format!("{}{}{}", base, self.options.separator, count));
+            if !self.seen.contains_key(&slug) {
+                self.seen.insert(slug.clone(), 0);
+                return slug;
+            }
+        }
+    }
+}
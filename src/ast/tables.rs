@@ -3,12 +3,38 @@
 //! This module provides utilities for working with tables in CommonMark and GitHub Flavored Markdown.
 //! When the `gfm` feature is enabled, additional alignment functionality is available.
 
-use super::Node;
+use super::{make_shared_mut, Node};
+use crate::diagnostics;
+use crate::error::{WriteError, WriteResult};
+use ecow::EcoString;
+use std::cmp::Ordering;
 
 /// Table builder for creating tables with customized content
 ///
 /// This builder provides a fluent API for creating tables with
 /// headers and rows.
+///
+/// The number of columns is the number of headers, or the length of the
+/// longest row when no headers are set. [`build`](Self::build) pads short rows
+/// with empty cells, truncates long ones and fills missing alignments with the
+/// default; [`try_build`](Self::try_build) refuses long rows instead.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::tables::TableBuilder;
+/// use cmark_writer::ast::Node;
+///
+/// let table = TableBuilder::new()
+///     .headers(vec![Node::Text("Name".into()), Node::Text("Role".into())])
+///     .row([Node::Text("Ada".into())])
+///     .build();
+///
+/// match table {
+///     Node::Table { rows, .. } => assert_eq!(rows[0][1], Node::Text("".into())),
+///     _ => unreachable!(),
+/// }
+/// ```
 #[derive(Debug, Clone)]
 pub struct TableBuilder {
     /// Table header cells
@@ -40,6 +66,15 @@ impl TableBuilder {
         self
     }
 
+    /// Adds a single row to the table, from any iterator of cells
+    ///
+    /// # Arguments
+    /// * `cells` - Nodes representing the cells of the row
+    pub fn row(mut self, cells: impl IntoIterator<Item = Node>) -> Self {
+        self.rows.push(cells.into_iter().collect());
+        self
+    }
+
     /// Adds a single row to the table
     ///
     /// # Arguments
@@ -60,22 +95,78 @@ impl TableBuilder {
 
     /// Builds the final table node
     ///
+    /// Short rows are padded with empty cells and rows longer than the table
+    /// are truncated, with a warning. With the `gfm` feature, missing
+    /// alignments are filled with the default and extra ones dropped.
+    ///
     /// # Returns
-    /// A Node::Table with the specified headers and rows
+    /// A Node::Table with the specified headers, alignments, and rows
+    pub fn build(mut self) -> Node {
+        // Never fails when truncating
+        let _ = self.fit_rows(true);
+        self.into_node()
+    }
+
+    /// Builds the final table node, refusing rows longer than the table
+    ///
+    /// Short rows and alignments are completed as in [`build`](Self::build).
+    ///
+    /// # Errors
+    /// [`WriteError::InvalidStructure`] if a row has more cells than the table
+    /// has columns
+    pub fn try_build(mut self) -> WriteResult<Node> {
+        self.fit_rows(false)?;
+        Ok(self.into_node())
+    }
+
+    /// Number of columns of the table
+    fn columns(&self) -> usize {
+        if self.headers.is_empty() {
+            self.rows.iter().map(Vec::len).max().unwrap_or(0)
+        } else {
+            self.headers.len()
+        }
+    }
+
+    /// Pad every row, and the alignments, to the number of columns
+    fn fit_rows(&mut self, truncate: bool) -> WriteResult<()> {
+        let columns = self.columns();
+        // Leave an empty table as configured
+        if columns == 0 {
+            return Ok(());
+        }
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            if row.len() > columns {
+                let message = format!(
+                    "Table row {} has {} cells, but the table has {} columns",
+                    index + 1,
+                    row.len(),
+                    columns
+                );
+                if !truncate {
+                    return Err(WriteError::InvalidStructure(message.into()));
+                }
+                diagnostics::warn!("{}. Extra cells are dropped.", message);
+                row.truncate(columns);
+            }
+            row.resize(columns, Node::Text(EcoString::new()));
+        }
+        #[cfg(feature = "gfm")]
+        self.alignments
+            .resize(columns, super::TableAlignment::default());
+        Ok(())
+    }
+
     #[cfg(not(feature = "gfm"))]
-    pub fn build(self) -> Node {
+    fn into_node(self) -> Node {
         Node::Table {
             headers: self.headers,
             rows: self.rows,
         }
     }
 
-    /// Builds the final table node with alignment information
-    ///
-    /// # Returns
-    /// A Node::Table with the specified headers, alignments, and rows
     #[cfg(feature = "gfm")]
-    pub fn build(self) -> Node {
+    fn into_node(self) -> Node {
         Node::Table {
             headers: self.headers,
             alignments: self.alignments,
@@ -108,6 +199,29 @@ impl TableBuilder {
         self
     }
 
+    /// Sorts the rows added so far (see [`sort_rows`])
+    ///
+    /// # Arguments
+    /// * `keys` - Sort keys, most significant first
+    pub fn sort_rows_by(mut self, keys: &[SortKey]) -> Self {
+        sort_rows(&mut self.rows, keys);
+        self
+    }
+
+    /// Sets the alignments of the columns, from any iterator (only available
+    /// with `gfm` feature)
+    ///
+    /// Missing alignments are filled with the default when building, extra
+    /// ones are dropped.
+    ///
+    /// # Arguments
+    /// * `alignments` - Alignments, one for each column
+    #[cfg(feature = "gfm")]
+    pub fn align(mut self, alignments: impl IntoIterator<Item = super::TableAlignment>) -> Self {
+        self.alignments = alignments.into_iter().collect();
+        self
+    }
+
     /// Sets alignments for multiple columns (only available with `gfm` feature)
     ///
     /// # Arguments
@@ -153,3 +267,306 @@ pub fn centered_table(headers: Vec<Node>, rows: Vec<Vec<Node>>) -> Node {
         .add_rows(rows)
         .build()
 }
+
+/// How the cells of a column are interpreted when sorting rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnKind {
+    /// Compare the plain text of the cells
+    #[default]
+    Text,
+    /// Compare cells as numbers, so that `"9"` sorts before `"10"`. Thousands
+    /// separators (`,` and `_`) and a trailing `%` are ignored.
+    Numeric,
+    /// Compare cells as dates written year first (`2024-03-01`, `2024/3/1`,
+    /// `2024-03` or `2024`)
+    Date,
+    /// Compare numbers as numbers, dates as dates and anything else as text
+    Auto,
+}
+
+/// A cell value parsed for sorting
+///
+/// Values of different kinds order as numbers, then dates, then text.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum CellValue {
+    /// A number
+    Number(f64),
+    /// A date as `(year, month, day)`; a missing month or day is 0
+    Date(i32, u32, u32),
+    /// Anything else, as plain text
+    Text(EcoString),
+}
+
+impl CellValue {
+    /// Parse the plain text of `cell` according to `kind`
+    ///
+    /// Cells that don't parse as the requested kind are kept as text, so they
+    /// sort after every parsed value.
+    pub fn parse(cell: &Node, kind: ColumnKind) -> Self {
+        let text = cell.plain_text();
+        let trimmed = text.trim();
+        let parsed = match kind {
+            ColumnKind::Text => None,
+            ColumnKind::Numeric => parse_number(trimmed),
+            ColumnKind::Date => parse_date(trimmed),
+            ColumnKind::Auto => parse_number(trimmed).or_else(|| parse_date(trimmed)),
+        };
+        parsed.unwrap_or(CellValue::Text(text))
+    }
+
+    /// Compare two values, texts by their characters
+    ///
+    /// Numbers compare with [`f64::total_cmp`], so `NaN` sorts after every
+    /// other number (and `-NaN` before).
+    pub fn compare(&self, other: &Self) -> Ordering {
+        self.compare_with(other, &|a, b| a.cmp(b))
+    }
+
+    /// Compare two values, texts with `compare_text`
+    fn compare_with(
+        &self,
+        other: &Self,
+        compare_text: &dyn Fn(&str, &str) -> Ordering,
+    ) -> Ordering {
+        match (self, other) {
+            (CellValue::Number(a), CellValue::Number(b)) => a.total_cmp(b),
+            (CellValue::Date(y1, m1, d1), CellValue::Date(y2, m2, d2)) => {
+                (y1, m1, d1).cmp(&(y2, m2, d2))
+            }
+            (CellValue::Text(a), CellValue::Text(b)) => compare_text(a, b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    /// Position of the kind of value: numbers, then dates, then text
+    fn rank(&self) -> u8 {
+        match self {
+            CellValue::Number(_) => 0,
+            CellValue::Date(..) => 1,
+            CellValue::Text(_) => 2,
+        }
+    }
+}
+
+/// Locale-aware order of text, for sorting table columns the way readers of a
+/// language expect (`ä` after `z` in Swedish, but next to `a` in German)
+///
+/// Text cells, and cells that don't parse as their [`ColumnKind`], are
+/// compared with the collation; numbers and dates compare as they do in
+/// [`sort_rows`]. Only available with the `collation` feature.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::tables::{Collation, SortKey};
+/// use cmark_writer::ast::Node;
+///
+/// let cell = |s: &str| Node::Text(s.into());
+/// let mut rows = vec![vec![cell("ängel")], vec![cell("zebra")], vec![cell("apa")]];
+/// Collation::new("sv").unwrap().sort_rows(&mut rows, &[SortKey::new(0)]);
+///
+/// let sorted: Vec<_> = rows.iter().map(|row| row[0].plain_text()).collect();
+/// assert_eq!(sorted, ["apa", "zebra", "ängel"]);
+/// ```
+#[cfg(feature = "collation")]
+#[derive(Clone)]
+pub struct Collation(std::sync::Arc<icu_collator::Collator>);
+
+#[cfg(feature = "collation")]
+impl Collation {
+    /// The collation of `locale`, a BCP 47 language tag such as `sv` or `de-AT`
+    ///
+    /// Locales without tailored rules use the root collation of the Unicode
+    /// Collation Algorithm. Returns `None` if `locale` isn't a valid tag.
+    pub fn new(locale: &str) -> Option<Self> {
+        let locale: icu_locid::Locale = locale.parse().ok()?;
+        let options = icu_collator::CollatorOptions::new();
+        let collator = icu_collator::Collator::try_new(&(&locale).into(), options).ok()?;
+        Some(Self(std::sync::Arc::new(collator)))
+    }
+
+    /// Compare two strings
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.0.compare(a, b)
+    }
+
+    /// Sort table rows by one or more columns, like [`sort_rows`] but with
+    /// text in the order of this collation
+    pub fn sort_rows(&self, rows: &mut [Vec<Node>], keys: &[SortKey]) {
+        sort_rows_with(rows, keys, &|a, b| self.compare(a, b));
+    }
+
+    /// Sort the rows of a table node, like [`sort_rows_by`] but with text in
+    /// the order of this collation
+    pub fn sort_table(&self, table: &mut Node, keys: &[SortKey]) -> bool {
+        sort_table_with(table, keys, &|a, b| self.compare(a, b))
+    }
+}
+
+#[cfg(feature = "collation")]
+impl std::fmt::Debug for Collation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Collation(..)")
+    }
+}
+
+fn parse_number(text: &str) -> Option<CellValue> {
+    let text = text.strip_suffix('%').unwrap_or(text).trim_end();
+    if !text.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '-' | '+' | '.')) {
+        return None;
+    }
+    let digits: String = text.chars().filter(|c| !matches!(c, ',' | '_')).collect();
+    digits.parse().ok().map(CellValue::Number)
+}
+
+fn parse_date(text: &str) -> Option<CellValue> {
+    let mut parts = text.split(['-', '/', '.']);
+    let year = parts.next().filter(|year| year.len() == 4)?.parse().ok()?;
+    let mut component = |max: u32| -> Option<Option<u32>> {
+        match parts.next() {
+            None => Some(None),
+            Some(part) => part
+                .parse()
+                .ok()
+                .filter(|n| (1..=max).contains(n))
+                .map(Some),
+        }
+    };
+    let month = component(12)?;
+    let day = component(31)?;
+    if parts.next().is_some() || (month.is_none() && day.is_some()) {
+        return None;
+    }
+    Some(CellValue::Date(year, month.unwrap_or(0), day.unwrap_or(0)))
+}
+
+/// A column to sort table rows by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    /// Zero-based column index
+    pub column: usize,
+    /// How the cells of the column are compared
+    pub kind: ColumnKind,
+    /// Sort in descending order
+    pub descending: bool,
+}
+
+impl SortKey {
+    /// Sort ascending by the text of `column`
+    pub fn new(column: usize) -> Self {
+        Self {
+            column,
+            kind: ColumnKind::Text,
+            descending: false,
+        }
+    }
+
+    /// Set how the cells of the column are compared
+    pub fn kind(mut self, kind: ColumnKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sort in descending order
+    pub fn descending(mut self) -> Self {
+        self.descending = true;
+        self
+    }
+}
+
+/// Sort table rows by one or more columns
+///
+/// The sort is stable: rows that compare equal on every key keep their order.
+/// Rows missing a column sort as if the cell were empty text.
+///
+/// # Arguments
+/// * `rows` - Table rows, each containing cell nodes
+/// * `keys` - Sort keys, most significant first
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::tables::{sort_rows, ColumnKind, SortKey};
+/// use cmark_writer::ast::Node;
+///
+/// let cell = |s: &str| Node::Text(s.into());
+/// let mut rows = vec![vec![cell("10")], vec![cell("9")], vec![cell("1,000")]];
+/// sort_rows(&mut rows, &[SortKey::new(0).kind(ColumnKind::Numeric)]);
+///
+/// let sorted: Vec<_> = rows.iter().map(|row| row[0].plain_text()).collect();
+/// assert_eq!(sorted, ["9", "10", "1,000"]);
+/// ```
+pub fn sort_rows(rows: &mut [Vec<Node>], keys: &[SortKey]) {
+    sort_rows_with(rows, keys, &|a, b| a.cmp(b));
+}
+
+fn sort_rows_with(
+    rows: &mut [Vec<Node>],
+    keys: &[SortKey],
+    compare_text: &dyn Fn(&str, &str) -> Ordering,
+) {
+    let empty = Node::Text(EcoString::new());
+    let mut keyed: Vec<(Vec<CellValue>, Vec<Node>)> = rows
+        .iter_mut()
+        .map(|row| {
+            let values = keys
+                .iter()
+                .map(|key| CellValue::parse(row.get(key.column).unwrap_or(&empty), key.kind))
+                .collect();
+            (values, std::mem::take(row))
+        })
+        .collect();
+
+    keyed.sort_by(|(a, _), (b, _)| {
+        keys.iter()
+            .zip(a.iter().zip(b))
+            .map(|(key, (a, b))| {
+                let ordering = a.compare_with(b, compare_text);
+                if key.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    for (row, (_, cells)) in rows.iter_mut().zip(keyed) {
+        *row = cells;
+    }
+}
+
+/// Sort the rows of a table node by one or more columns (see [`sort_rows`])
+///
+/// # Arguments
+/// * `table` - A `Node::Table`, possibly wrapped in `Node::Shared`
+/// * `keys` - Sort keys, most significant first
+///
+/// # Returns
+/// `false` if `table` is not a table, or is a shared table holding custom
+/// nodes that is shared elsewhere too (copying it would clone them)
+pub fn sort_rows_by(table: &mut Node, keys: &[SortKey]) -> bool {
+    sort_table_with(table, keys, &|a, b| a.cmp(b))
+}
+
+fn sort_table_with(
+    table: &mut Node,
+    keys: &[SortKey],
+    compare_text: &dyn Fn(&str, &str) -> Ordering,
+) -> bool {
+    let mut node = table;
+    while let Node::Shared(inner) = node {
+        let Some(inner) = make_shared_mut(inner) else {
+            return false;
+        };
+        node = inner;
+    }
+    match node {
+        Node::Table { rows, .. } => {
+            sort_rows_with(rows, keys, compare_text);
+            true
+        }
+        _ => false,
+    }
+}
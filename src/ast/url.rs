@@ -0,0 +1,226 @@
+//! Typed URL support for link-like nodes.
+//!
+//! This module provides [`MarkdownUrl`], a validated URL newtype used when
+//! constructing `Link`, `Image` and `Autolink` nodes, so that URL problems are
+//! reported when the AST is built rather than when it is written.
+
+use crate::error::{WriteError, WriteResult};
+use ecow::EcoString;
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+
+/// A validated and normalized URL for use in link, image and autolink nodes
+///
+/// URLs created with [`MarkdownUrl::parse`] are guaranteed not to contain
+/// whitespace or control characters, and have their scheme (if any) lowercased.
+/// [`MarkdownUrl::from_trusted`] skips validation for URLs that are already known
+/// to be well-formed.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::MarkdownUrl;
+///
+/// let url = MarkdownUrl::parse("HTTPS://example.com/Path").unwrap();
+/// assert_eq!(url.as_str(), "https://example.com/Path");
+/// assert_eq!(url.scheme(), Some("https"));
+///
+/// assert!(MarkdownUrl::parse("has space").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct MarkdownUrl(EcoString);
+
+impl MarkdownUrl {
+    /// Parse and normalize a URL
+    ///
+    /// # Arguments
+    /// * `url` - The raw URL text
+    ///
+    /// # Returns
+    /// The normalized URL, or `WriteError::InvalidUrl` if it contains whitespace
+    /// or control characters
+    pub fn parse(url: &str) -> WriteResult<Self> {
+        if let Some(c) = url.chars().find(|c| c.is_whitespace() || c.is_control()) {
+            return Err(WriteError::InvalidUrl(
+                format!("{:?} contains disallowed character {:?}", url, c).into(),
+            ));
+        }
+
+        match scheme_len(url) {
+            Some(len) if url[..len].chars().any(|c| c.is_ascii_uppercase()) => {
+                let mut normalized = EcoString::from(url[..len].to_ascii_lowercase());
+                normalized.push_str(&url[len..]);
+                Ok(Self(normalized))
+            }
+            _ => Ok(Self(url.into())),
+        }
+    }
+
+    /// Create a URL without validation or normalization
+    ///
+    /// Use this for URLs coming from a trusted source that has already
+    /// validated them.
+    pub fn from_trusted<S: Into<EcoString>>(url: S) -> Self {
+        Self(url.into())
+    }
+
+    /// Get the URL as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Get the URL scheme (without the trailing `:`), if the URL is absolute
+    pub fn scheme(&self) -> Option<&str> {
+        scheme_len(&self.0).map(|len| &self.0[..len])
+    }
+
+    /// Check whether the URL is absolute (has a scheme)
+    pub fn is_absolute(&self) -> bool {
+        self.scheme().is_some()
+    }
+
+    /// Get the host of a URL with an authority component (`scheme://host/...` or `//host/...`)
+    ///
+    /// User info and port are stripped; the host is returned as written.
+    pub fn host(&self) -> Option<&str> {
+        url_host(&self.0)
+    }
+
+    /// Consume the URL and return the inner string
+    pub fn into_inner(self) -> EcoString {
+        self.0
+    }
+}
+
+/// Length of the URI scheme at the start of `url`, per the CommonMark autolink rules
+/// (an ASCII letter followed by 1-31 letters, digits, `+`, `.` or `-`, then `:`)
+pub(crate) fn scheme_len(url: &str) -> Option<usize> {
+    let colon = url.find(':')?;
+    let scheme = &url[..colon];
+    let mut chars = scheme.chars();
+    let valid = (2..=32).contains(&scheme.len())
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'));
+    valid.then_some(colon)
+}
+
+/// Scheme of `url` as a browser resolves it, lowercased, or `None` for relative URLs
+///
+/// Browsers ignore leading spaces and control characters, and tabs and line
+/// breaks anywhere in a URL, so `" java\tscript:"` is a `javascript:` URL.
+pub(crate) fn effective_scheme(url: &str) -> Option<String> {
+    let mut scheme = String::new();
+    let trimmed = url.trim_start_matches(|c: char| c <= ' ');
+    for c in trimmed.chars().filter(|c| !matches!(c, '\t' | '\n' | '\r')) {
+        match c {
+            ':' => return (!scheme.is_empty()).then_some(scheme),
+            c if c.is_ascii_alphabetic() => scheme.push(c.to_ascii_lowercase()),
+            c if !scheme.is_empty() && (c.is_ascii_digit() || matches!(c, '+' | '.' | '-')) => {
+                scheme.push(c)
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Host of a URL with an authority component, without user info or port
+pub(crate) fn url_host(url: &str) -> Option<&str> {
+    let rest = match scheme_len(url) {
+        Some(len) => url[len + 1..].strip_prefix("//")?,
+        None => url.strip_prefix("//")?,
+    };
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = if host.starts_with('[') {
+        // IPv6 literal, keep the brackets and drop any port after them
+        &host[..host.find(']').map_or(host.len(), |i| i + 1)]
+    } else {
+        host.split(':').next().unwrap_or(host)
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+/// The address of an email autolink destination, without any `mailto:` prefix
+pub(crate) fn strip_mailto(url: &str) -> &str {
+    match url.get(..7) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("mailto:") => &url[7..],
+        _ => url,
+    }
+}
+
+/// Whether `address` is an email address as accepted in CommonMark email autolinks
+pub(crate) fn is_email_address(address: &str) -> bool {
+    let Some((local, domain)) = address.split_once('@') else {
+        return false;
+    };
+    let local_ok = !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(c));
+    let label_ok = |label: &str| {
+        (1..=63).contains(&label.len())
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    local_ok && domain.split('.').all(label_ok)
+}
+
+/// Text written between `<` and `>` and the `href` of an autolink, as `(text, href)`
+///
+/// Email autolinks are written without a `mailto:` prefix and link to `mailto:`
+/// plus the address; URI autolinks without a scheme get `default_scheme`.
+pub(crate) fn autolink_parts<'a>(
+    url: &'a str,
+    is_email: bool,
+    default_scheme: &str,
+) -> (Cow<'a, str>, Cow<'a, str>) {
+    if is_email {
+        let address = strip_mailto(url);
+        (
+            Cow::Borrowed(address),
+            Cow::Owned(format!("mailto:{}", address)),
+        )
+    } else if scheme_len(url).is_some() {
+        (Cow::Borrowed(url), Cow::Borrowed(url))
+    } else {
+        let full = format!("{}://{}", default_scheme, url);
+        (Cow::Owned(full.clone()), Cow::Owned(full))
+    }
+}
+
+impl Deref for MarkdownUrl {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for MarkdownUrl {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MarkdownUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<&str> for MarkdownUrl {
+    type Error = WriteError;
+
+    fn try_from(url: &str) -> WriteResult<Self> {
+        Self::parse(url)
+    }
+}
+
+impl From<MarkdownUrl> for EcoString {
+    fn from(url: MarkdownUrl) -> Self {
+        url.0
+    }
+}
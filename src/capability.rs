@@ -0,0 +1,236 @@
+//! Output capabilities of the writers.
+//!
+//! Not every construct can be expressed by every output: CommonMark has no
+//! strikethrough unless GFM is enabled, and tables with block content only
+//! survive as an HTML fallback. [`Capability`] names these constructs, each
+//! writer reports which ones its configuration supports, and
+//! [`Node::required_capabilities`] lists what a document needs, so an
+//! application can pick an output format before rendering.
+//!
+//! The writers consult the same checks for their fallbacks, so a capability a
+//! writer reports as unsupported is exactly one that degrades on output.
+
+use crate::ast::{ListItem, Node};
+use crate::writer::{CommonMarkWriter, HtmlWriter};
+use std::fmt;
+
+/// A construct that an output may or may not be able to express
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Capability {
+    /// Tables
+    Table,
+    /// Column alignment in tables
+    TableAlignment,
+    /// Block elements (lists, paragraphs) inside table cells
+    TableBlockContent,
+    /// Strikethrough text
+    Strikethrough,
+    /// Task list items with a checkbox
+    TaskList,
+    /// Links detected without angle brackets
+    ExtendedAutolink,
+    /// Raw HTML blocks and elements
+    RawHtml,
+    /// Explicit heading ids
+    HeadingIds,
+    /// Definition lists of terms and their definitions
+    DefinitionList,
+    /// Footnote references and definitions
+    Footnotes,
+    /// Math formulas
+    Math,
+}
+
+impl Capability {
+    /// Every capability, in declaration order
+    pub const ALL: &'static [Capability] = &[
+        Capability::Table,
+        Capability::TableAlignment,
+        Capability::TableBlockContent,
+        Capability::Strikethrough,
+        Capability::TaskList,
+        Capability::ExtendedAutolink,
+        Capability::RawHtml,
+        Capability::HeadingIds,
+        Capability::DefinitionList,
+        Capability::Footnotes,
+        Capability::Math,
+    ];
+
+    /// Stable identifier of the capability, e.g. `"table_alignment"`
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::Table => "table",
+            Capability::TableAlignment => "table_alignment",
+            Capability::TableBlockContent => "table_block_content",
+            Capability::Strikethrough => "strikethrough",
+            Capability::TaskList => "task_list",
+            Capability::ExtendedAutolink => "extended_autolink",
+            Capability::RawHtml => "raw_html",
+            Capability::HeadingIds => "heading_ids",
+            Capability::DefinitionList => "definition_list",
+            Capability::Footnotes => "footnotes",
+            Capability::Math => "math",
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl CommonMarkWriter {
+    /// Whether the writer's configuration can express `capability`
+    ///
+    /// Unsupported constructs are degraded on output (strikethrough is written
+    /// as plain text, math as inline code, tables with block content fail in
+    /// strict mode).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::capability::Capability;
+    /// use cmark_writer::CommonMarkWriter;
+    ///
+    /// let writer = CommonMarkWriter::new();
+    /// assert!(writer.supports(Capability::Table));
+    /// assert!(!writer.supports(Capability::Strikethrough));
+    /// ```
+    pub fn supports(&self, capability: Capability) -> bool {
+        let options = &self.options;
+        match capability {
+            Capability::Table | Capability::RawHtml => true,
+            // Only through the HTML fallback, which strict mode refuses
+            Capability::TableBlockContent => !options.strict,
+            Capability::HeadingIds => options.attribute_blocks,
+            Capability::DefinitionList => options.definition_lists,
+            Capability::Footnotes => options.footnotes,
+            Capability::Math => options.math,
+            #[cfg(feature = "gfm")]
+            Capability::TableAlignment => options.gfm_tables,
+            #[cfg(feature = "gfm")]
+            Capability::Strikethrough => options.enable_gfm && options.gfm_strikethrough,
+            #[cfg(feature = "gfm")]
+            Capability::TaskList => options.gfm_tasklists,
+            #[cfg(feature = "gfm")]
+            Capability::ExtendedAutolink => options.gfm_autolinks,
+            #[cfg(not(feature = "gfm"))]
+            Capability::TableAlignment
+            | Capability::Strikethrough
+            | Capability::TaskList
+            | Capability::ExtendedAutolink => false,
+        }
+    }
+}
+
+impl HtmlWriter {
+    /// Whether the writer's configuration can express `capability`
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Table
+            | Capability::TableBlockContent
+            | Capability::RawHtml
+            | Capability::HeadingIds
+            | Capability::DefinitionList
+            | Capability::Footnotes
+            | Capability::Math => true,
+            #[cfg(feature = "gfm")]
+            Capability::TableAlignment
+            | Capability::Strikethrough
+            | Capability::TaskList
+            | Capability::ExtendedAutolink => self.options.enable_gfm,
+            #[cfg(not(feature = "gfm"))]
+            Capability::TableAlignment
+            | Capability::Strikethrough
+            | Capability::TaskList
+            | Capability::ExtendedAutolink => false,
+        }
+    }
+}
+
+impl Node {
+    /// The capabilities an output needs to express this node and its descendants
+    ///
+    /// Returned sorted and without duplicates.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::capability::Capability;
+    /// use cmark_writer::{CommonMarkWriter, Node};
+    ///
+    /// let doc = Node::Document(vec![Node::Paragraph(vec![Node::Strikethrough(vec![
+    ///     Node::Text("old".into()),
+    /// ])])]);
+    /// let writer = CommonMarkWriter::new();
+    /// let missing: Vec<_> = doc
+    ///     .required_capabilities()
+    ///     .into_iter()
+    ///     .filter(|capability| !writer.supports(*capability))
+    ///     .collect();
+    /// assert_eq!(missing, [Capability::Strikethrough]);
+    /// ```
+    pub fn required_capabilities(&self) -> Vec<Capability> {
+        let mut required = Vec::new();
+        self.walk_with_paths(|_, node| node.own_capabilities(&mut required));
+        required.sort();
+        required.dedup();
+        required
+    }
+
+    /// Push the capabilities this node needs itself, not counting its descendants
+    pub(crate) fn own_capabilities(&self, required: &mut Vec<Capability>) {
+        match self {
+            Node::Table { headers, rows, .. } => {
+                required.push(Capability::Table);
+                if headers
+                    .iter()
+                    .chain(rows.iter().flatten())
+                    .any(Node::is_block)
+                {
+                    required.push(Capability::TableBlockContent);
+                }
+                #[cfg(feature = "gfm")]
+                if let Node::Table { alignments, .. } = self {
+                    if alignments
+                        .iter()
+                        .any(|alignment| *alignment != crate::ast::TableAlignment::None)
+                    {
+                        required.push(Capability::TableAlignment);
+                    }
+                }
+            }
+            Node::Strikethrough(_) => required.push(Capability::Strikethrough),
+            Node::ExtendedAutolink(_) => required.push(Capability::ExtendedAutolink),
+            Node::HtmlBlock(_) | Node::HtmlInline(_) | Node::HtmlElement(_) => {
+                required.push(Capability::RawHtml)
+            }
+            Node::Heading {
+                custom_id: Some(_), ..
+            } => required.push(Capability::HeadingIds),
+            Node::DefinitionList(_) => required.push(Capability::DefinitionList),
+            Node::FootnoteReference(_) | Node::FootnoteDefinition { .. } => {
+                required.push(Capability::Footnotes)
+            }
+            Node::Math { .. } => required.push(Capability::Math),
+            Node::UnorderedList(items) | Node::OrderedList { items, .. }
+                if items.iter().any(is_task_item) =>
+            {
+                required.push(Capability::TaskList)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "gfm")]
+fn is_task_item(item: &ListItem) -> bool {
+    matches!(item, ListItem::Task { .. })
+}
+
+#[cfg(not(feature = "gfm"))]
+fn is_task_item(_item: &ListItem) -> bool {
+    false
+}
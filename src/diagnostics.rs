@@ -0,0 +1,57 @@
+//! Logging and profiling hooks used by the writers.
+//!
+//! Diagnostics go through the macros here instead of calling `log` directly.
+//! With the `tracing` feature they are emitted as `tracing` events (which are
+//! forwarded to `log` while no subscriber is installed), and each node render
+//! is wrapped in a `render_node` span carrying the node `kind` and its `depth`,
+//! so a subscriber can attribute render time to parts of a document.
+
+use crate::ast::Node;
+
+macro_rules! emit_warn {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        ::tracing::warn!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        ::log::warn!($($arg)+);
+    }};
+}
+
+macro_rules! emit_info {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        ::tracing::info!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        ::log::info!($($arg)+);
+    }};
+}
+
+// Only used by GFM code paths so far
+#[allow(unused_macros)]
+macro_rules! emit_debug {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!($($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        ::log::debug!($($arg)+);
+    }};
+}
+
+#[allow(unused_imports)]
+pub(crate) use emit_debug as debug;
+pub(crate) use {emit_info as info, emit_warn as warn};
+
+/// Guard for the span of a node render, closed when dropped
+pub(crate) struct NodeSpan {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+/// Enter the span for rendering `node` at nesting `depth` (0 for the root)
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn node_span(node: &Node, depth: usize) -> NodeSpan {
+    NodeSpan {
+        #[cfg(feature = "tracing")]
+        _entered: tracing::debug_span!("render_node", kind = node.type_name(), depth).entered(),
+    }
+}
@@ -5,11 +5,16 @@
 
 use ecow::EcoString;
 
+use crate::ast::{Node, NodePath};
+use crate::options::OptionsConflict;
 use crate::writer::html::error::HtmlWriteError as CoreHtmlWriteError;
 use std::error::Error;
 use std::fmt::{self, Display};
 use std::io;
 
+/// Number of characters of a node's text quoted in errors
+const ERROR_SNIPPET_CHARS: usize = 40;
+
 /// Errors that can occur during CommonMark writing.
 #[derive(Debug)]
 pub enum WriteError {
@@ -33,6 +38,36 @@ pub enum WriteError {
     HtmlRenderingError(CoreHtmlWriteError),
     /// An error occurred during HTML fallback rendering for tables with block elements.
     HtmlFallbackError(EcoString),
+    /// An invalid URL was encountered (e.g., containing whitespace or control characters).
+    InvalidUrl(EcoString),
+    /// A disallowed control character (NUL, BOM, other C0/C1 controls) was found in text content.
+    InvalidCharacter {
+        /// The kind of content the character was found in (e.g. "Text", "InlineCode")
+        context: EcoString,
+        /// Byte offset of the character within the content
+        position: usize,
+        /// The offending character
+        character: char,
+    },
+    /// An error raised while writing a node, with the node's location
+    ///
+    /// Errors that strict mode raises for invalid content (invalid structure,
+    /// newlines in inline elements, invalid heading levels, URLs, characters
+    /// and HTML names) are returned in this form by the outermost write of
+    /// both writers ([`ToCommonMark`](crate::ToCommonMark) and
+    /// [`ToHtml`](crate::ToHtml)), so match on
+    /// [`without_location`](Self::without_location) to tell them apart.
+    AtNode {
+        /// Path of the offending node, relative to the written node
+        path: NodePath,
+        /// Start of the text of the offending node, escaped like a Rust string
+        /// and truncated with `…`
+        snippet: EcoString,
+        /// The error itself
+        error: Box<WriteError>,
+    },
+    /// The writer options conflict with each other, see [`WriterOptions::validate`](crate::WriterOptions::validate).
+    InvalidOptions(Vec<OptionsConflict>),
     /// A custom error with a message and optional error code.
     Custom {
         /// Custom error message
@@ -75,6 +110,25 @@ impl Display for WriteError {
             WriteError::HtmlFallbackError(msg) => {
                 write!(f, "Error during HTML fallback rendering: {}", msg)
             },
+            WriteError::InvalidUrl(msg) => write!(f, "Invalid URL: {}", msg),
+            WriteError::InvalidCharacter { context, position, character } => write!(
+                f,
+                "Disallowed control character U+{:04X} in {} at byte offset {}.",
+                *character as u32, context, position
+            ),
+            WriteError::AtNode { path, snippet, error } => {
+                write!(f, "{} (at {}: \"{}\")", error, path, snippet)
+            }
+            WriteError::InvalidOptions(conflicts) => {
+                write!(f, "Conflicting writer options: ")?;
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", conflict)?;
+                }
+                write!(f, ".")
+            }
             WriteError::Custom { message, code } => {
                 if let Some(code) = code {
                     write!(f, "Custom error [{}]: {}", code, message)
@@ -86,7 +140,14 @@ impl Display for WriteError {
     }
 }
 
-impl Error for WriteError {}
+impl Error for WriteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WriteError::AtNode { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 // Allow converting fmt::Error into WriteError for convenience when using `?`
 impl From<fmt::Error> for WriteError {
@@ -110,6 +171,7 @@ impl From<CoreHtmlWriteError> for WriteError {
             CoreHtmlWriteError::InvalidHtmlAttribute(attr) => {
                 WriteError::InvalidHtmlAttribute(attr.into())
             }
+            CoreHtmlWriteError::InvalidUrl(url) => WriteError::InvalidUrl(url.into()),
             other_html_err => WriteError::HtmlRenderingError(other_html_err),
         }
     }
@@ -120,6 +182,78 @@ pub type WriteResult<T> = Result<T, WriteError>;
 
 /// Convenience methods for creating custom errors
 impl WriteError {
+    /// The error without the location added by [`WriteError::AtNode`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{Node, NodePath};
+    /// use cmark_writer::{CommonMarkWriter, ToCommonMark, WriteError};
+    ///
+    /// let doc = Node::Document(vec![Node::Paragraph(vec![Node::Link {
+    ///     url: "/docs".into(),
+    ///     title: None,
+    ///     content: vec![Node::Text("two\nlines".into())],
+    /// }])]);
+    ///
+    /// let error = doc.to_commonmark(&mut CommonMarkWriter::new()).unwrap_err();
+    /// assert_eq!(error.path(), Some(&NodePath::root().child(0).child(0).child(0)));
+    /// assert!(error.to_string().ends_with(r#"(at /0/0/0: "two\nlines")"#));
+    /// assert!(matches!(
+    ///     error.without_location(),
+    ///     WriteError::NewlineInInlineElement(_)
+    /// ));
+    /// ```
+    pub fn without_location(&self) -> &WriteError {
+        match self {
+            WriteError::AtNode { error, .. } => error.without_location(),
+            error => error,
+        }
+    }
+
+    /// Path of the node that raised the error, if known
+    pub fn path(&self) -> Option<&NodePath> {
+        match self {
+            WriteError::AtNode { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Whether strict mode raises the error for invalid content, so that it
+    /// can be attributed to a node
+    pub(crate) fn is_content_error(&self) -> bool {
+        matches!(
+            self,
+            WriteError::InvalidHeadingLevel(_)
+                | WriteError::NewlineInInlineElement(_)
+                | WriteError::InvalidStructure(_)
+                | WriteError::InvalidHtmlTag(_)
+                | WriteError::InvalidHtmlAttribute(_)
+                | WriteError::InvalidUrl(_)
+                | WriteError::InvalidCharacter { .. }
+                | WriteError::HtmlRenderingError(CoreHtmlWriteError::InvalidStructure(_))
+        )
+    }
+
+    /// The error located at the node at `path` below `root`, quoting the
+    /// start of its text
+    pub(crate) fn at_node(self, root: &Node, path: NodePath) -> WriteError {
+        let text = root.get(&path).unwrap_or(root).plain_text();
+        let mut snippet: EcoString = text
+            .chars()
+            .take(ERROR_SNIPPET_CHARS)
+            .flat_map(char::escape_debug)
+            .collect();
+        if text.chars().nth(ERROR_SNIPPET_CHARS).is_some() {
+            snippet.push('…');
+        }
+        WriteError::AtNode {
+            path,
+            snippet,
+            error: Box::new(self),
+        }
+    }
+
     /// Create a new custom error with a message
     pub fn custom<S: Into<EcoString>>(message: S) -> Self {
         WriteError::Custom {
@@ -0,0 +1,283 @@
+//! Audit of the characters the CommonMark writer escapes.
+//!
+//! Escaping rules depend on where text ends up: a `#` is harmless in the
+//! middle of a paragraph but starts a heading at the beginning of one, and a
+//! `|` only matters inside a table. [`audit`] writes every text of a corpus in
+//! each [`EscapeContext`] and lines the output up with the input, recording
+//! which characters were escaped and how. The resulting [`EscapeReport`] has a
+//! stable text form, meant to be checked in as a fixture so that changes to
+//! the escaping rules show up as a diff.
+//!
+//! Only available with the `escape-audit` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use cmark_writer::escape_audit::{audit, EscapeContext, EscapeKind};
+//! use cmark_writer::WriterOptions;
+//!
+//! let report = audit(&["# not a heading"], &WriterOptions::default());
+//! let paragraph = report.case(EscapeContext::Paragraph, "# not a heading").unwrap();
+//! assert_eq!(paragraph.escapes[0].ch, '#');
+//! assert_eq!(paragraph.escapes[0].kind, EscapeKind::Backslash);
+//!
+//! let heading = report.case(EscapeContext::Heading, "# not a heading").unwrap();
+//! assert!(heading.escapes.is_empty());
+//! ```
+
+use crate::ast::Node;
+use crate::error::WriteResult;
+use crate::options::WriterOptions;
+use crate::traits::ToCommonMark;
+use crate::writer::CommonMarkWriter;
+use ecow::EcoString;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Where a text is written for the audit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EscapeContext {
+    /// The only text of a paragraph
+    Paragraph,
+    /// The only text of a level 1 heading
+    Heading,
+    /// A table body cell
+    TableCell,
+    /// The text of an inline link
+    LinkText,
+}
+
+impl EscapeContext {
+    /// Every context, in declaration order
+    pub const ALL: &'static [EscapeContext] = &[
+        EscapeContext::Paragraph,
+        EscapeContext::Heading,
+        EscapeContext::TableCell,
+        EscapeContext::LinkText,
+    ];
+
+    /// Stable identifier of the context, e.g. `"table_cell"`
+    pub fn name(self) -> &'static str {
+        match self {
+            EscapeContext::Paragraph => "paragraph",
+            EscapeContext::Heading => "heading",
+            EscapeContext::TableCell => "table_cell",
+            EscapeContext::LinkText => "link_text",
+        }
+    }
+
+    /// A node holding `text` in this context
+    fn wrap(self, text: &str) -> Node {
+        let text = Node::Text(text.into());
+        match self {
+            EscapeContext::Paragraph => Node::Paragraph(vec![text]),
+            EscapeContext::Heading => Node::heading(1, vec![text]),
+            EscapeContext::TableCell => Node::Table {
+                headers: vec![Node::Text("h".into())],
+                #[cfg(feature = "gfm")]
+                alignments: vec![Default::default()],
+                rows: vec![vec![text]],
+            },
+            EscapeContext::LinkText => Node::Paragraph(vec![Node::Link {
+                url: "u".into(),
+                title: None,
+                content: vec![text],
+            }]),
+        }
+    }
+
+    /// Output surrounding the text written in this context, not counting
+    /// trailing newlines
+    fn frame(self) -> (&'static str, &'static str) {
+        match self {
+            EscapeContext::Paragraph => ("", ""),
+            EscapeContext::Heading => ("# ", ""),
+            EscapeContext::TableCell => ("| h |\n| --- |\n| ", " |"),
+            EscapeContext::LinkText => ("[", "](u)"),
+        }
+    }
+}
+
+impl fmt::Display for EscapeContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How a character was escaped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeKind {
+    /// Preceded by a backslash
+    Backslash,
+    /// Replaced by an HTML entity or character reference, e.g. `&#35;`
+    Entity(EcoString),
+    /// The output stops matching the input from this character on, e.g.
+    /// because whitespace was trimmed or characters were inserted
+    Unaligned,
+}
+
+impl fmt::Display for EscapeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EscapeKind::Backslash => f.write_str("backslash"),
+            EscapeKind::Entity(entity) => write!(f, "entity {}", entity),
+            EscapeKind::Unaligned => f.write_str("unaligned"),
+        }
+    }
+}
+
+/// An escaped character of an audited text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escape {
+    /// Byte offset of the character in the input
+    pub offset: usize,
+    /// The character
+    pub ch: char,
+    /// How it was escaped
+    pub kind: EscapeKind,
+}
+
+/// One text written in one context
+#[derive(Debug)]
+pub struct AuditCase {
+    /// Where the text was written
+    pub context: EscapeContext,
+    /// The text
+    pub input: EcoString,
+    /// Everything the writer produced for the context, or its error
+    pub output: WriteResult<EcoString>,
+    /// The escaped characters, in input order
+    pub escapes: Vec<Escape>,
+}
+
+/// Result of [`audit`]: one case per text and context
+#[derive(Debug, Default)]
+pub struct EscapeReport {
+    /// Cases ordered by context, then by position of the text in the corpus
+    pub cases: Vec<AuditCase>,
+}
+
+impl EscapeReport {
+    /// The case of `input` written in `context`
+    pub fn case(&self, context: EscapeContext, input: &str) -> Option<&AuditCase> {
+        self.cases
+            .iter()
+            .find(|case| case.context == context && case.input == input)
+    }
+
+    /// Every character escaped at least once in `context`
+    pub fn escaped_chars(&self, context: EscapeContext) -> BTreeSet<char> {
+        self.cases
+            .iter()
+            .filter(|case| case.context == context)
+            .flat_map(|case| &case.escapes)
+            .filter(|escape| escape.kind != EscapeKind::Unaligned)
+            .map(|escape| escape.ch)
+            .collect()
+    }
+}
+
+/// One block per context, then one line per case and one indented line per escape
+impl fmt::Display for EscapeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut context = None;
+        for case in &self.cases {
+            if context != Some(case.context) {
+                if context.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[{}]", case.context)?;
+                context = Some(case.context);
+            }
+            match &case.output {
+                Ok(output) => writeln!(f, "{:?} -> {:?}", case.input, output)?,
+                Err(error) => writeln!(f, "{:?} -> error: {}", case.input, error)?,
+            }
+            for escape in &case.escapes {
+                writeln!(f, "  {:?} at {}: {}", escape.ch, escape.offset, escape.kind)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write every text of `corpus` in every [`EscapeContext`] and record what was escaped
+pub fn audit<S: AsRef<str>>(corpus: &[S], options: &WriterOptions) -> EscapeReport {
+    let mut report = EscapeReport::default();
+    for &context in EscapeContext::ALL {
+        for input in corpus {
+            let input = input.as_ref();
+            let output = render(&context.wrap(input), options);
+            let escapes = match &output {
+                Ok(output) => match strip_frame(output, context.frame()) {
+                    Some(body) => align(input, body),
+                    None => vec![unaligned(input, 0)],
+                },
+                Err(_) => Vec::new(),
+            };
+            report.cases.push(AuditCase {
+                context,
+                input: input.into(),
+                output,
+                escapes,
+            });
+        }
+    }
+    report
+}
+
+fn render(node: &Node, options: &WriterOptions) -> WriteResult<EcoString> {
+    let mut writer = CommonMarkWriter::with_options(options.clone());
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string())
+}
+
+fn strip_frame<'a>(output: &'a str, (prefix, suffix): (&str, &str)) -> Option<&'a str> {
+    output
+        .trim_end_matches('\n')
+        .strip_prefix(prefix)?
+        .strip_suffix(suffix)
+}
+
+/// Line up `body` with `input`, character by character
+fn align(input: &str, mut body: &str) -> Vec<Escape> {
+    let mut escapes = Vec::new();
+    for (offset, ch) in input.char_indices() {
+        // Checked first, so that an escaped backslash isn't read as a literal
+        // one followed by an escaped character
+        let kind = if let Some(rest) = body.strip_prefix('\\').and_then(|b| b.strip_prefix(ch)) {
+            body = rest;
+            EscapeKind::Backslash
+        } else if let Some(rest) = body.strip_prefix(ch) {
+            body = rest;
+            continue;
+        } else if let Some(entity) = entity_for(body, ch) {
+            body = &body[entity.len()..];
+            EscapeKind::Entity(entity.into())
+        } else {
+            escapes.push(unaligned(input, offset));
+            return escapes;
+        };
+        escapes.push(Escape { offset, ch, kind });
+    }
+    if !body.is_empty() {
+        escapes.push(unaligned(input, input.len()));
+    }
+    escapes
+}
+
+/// The entity at the start of `body` if it stands for `ch`
+fn entity_for(body: &str, ch: char) -> Option<&str> {
+    let end = body.strip_prefix('&')?.find(';')? + 2;
+    let entity = &body[..end];
+    let decoded = html_escape::decode_html_entities(entity);
+    (decoded.chars().eq([ch])).then_some(entity)
+}
+
+fn unaligned(input: &str, offset: usize) -> Escape {
+    Escape {
+        offset,
+        ch: input[offset..].chars().next().unwrap_or('\0'),
+        kind: EscapeKind::Unaligned,
+    }
+}
@@ -0,0 +1,83 @@
+//! C-compatible functions for plugins and non-Rust hosts.
+//!
+//! Available with the `ffi` feature.
+//!
+//! # Writer sinks
+//!
+//! A host wraps its writer in a [`CmarkSink`] or [`HtmlSink`] and passes a
+//! pointer to it to a plugin, which calls back through the `cmark_writer_sink_*`
+//! and `cmark_writer_html_sink_*` functions. Strings are passed as a pointer and
+//! a length in bytes and must be valid UTF-8. These functions return
+//! [`CMARK_WRITER_OK`] on success and a negative status code on failure.
+//!
+//! # Rendering
+//!
+//! [`cmark_writer_render_commonmark`] and [`cmark_writer_render_html`] render a
+//! document given as JSON. Each node is an object with a `type` naming the
+//! [`Node`](crate::ast::Node) variant and fields named as in the variant:
+//!
+//! ```json
+//! {"type": "Document", "children": [
+//!   {"type": "Heading", "level": 1, "content": [{"type": "Text", "text": "Title"}]},
+//!   {"type": "UnorderedList", "items": [
+//!     {"content": [{"type": "Paragraph", "children": [{"type": "Text", "text": "item"}]}]}
+//!   ]}
+//! ]}
+//! ```
+//!
+//! - Variants holding a list of nodes (`Document`, `Paragraph`, `BlockQuote`,
+//!   `Emphasis`, `Strong`, `Strikethrough`) use `children`; variants holding a
+//!   string (`Text`, `InlineCode`, `HtmlBlock`, `HtmlInline`, `ExtendedAutolink`)
+//!   use `text`.
+//! - List items are objects with `content`, plus `number` in ordered lists or
+//!   `status` (`"checked"`/`"unchecked"`) for task items.
+//! - `Heading` takes `level`, `content` and optionally `custom_id`.
+//! - Enumerations are lowercase strings: `heading_type` (`"atx"`, `"setext"`),
+//!   `block_type` (`"fenced"`, `"indented"`) and table `alignments`.
+//! - `HtmlElement` attributes are an object mapping names to values.
+//! - Optional fields may be omitted or `null`. `Shared` and
+//!   `ConfiguredDocument` nodes can't be expressed; pass the options instead.
+//! - `Custom` nodes take `custom_type` and optionally `data`, and are built by
+//!   the deserializer registered for `custom_type` in a [`CustomNodeRegistry`].
+//! - `FootnoteReference` takes `label`; `FootnoteDefinition` takes `label` and
+//!   `content`. `DefinitionList` takes `items`, objects with a `term` (a list
+//!   of nodes) and `definitions` (a list of lists of nodes).
+//! - `Math` takes its TeX source as `content` and optionally `display`.
+//! - `CrossRef` takes the `target` heading and optionally its link `text`.
+//!
+//! # Versions
+//!
+//! The root node may carry the `version` of the format it was written in,
+//! e.g. `{"version": 3, "type": "Document", ...}`; documents without one are
+//! version 1. Older documents are upgraded to the current [`SCHEMA_VERSION`]
+//! before they are read (see [`document_from_json`]), so documents cached by
+//! earlier versions of the crate keep loading. Newer versions are rejected.
+//!
+//! The options are a JSON object with a subset of the fields of
+//! [`WriterOptions`](crate::options::WriterOptions) or
+//! [`HtmlWriterOptions`](crate::writer::HtmlWriterOptions), with characters
+//! as one-character strings and `escape_strategy` as `"contextual"` or
+//! `"always"`. Unknown fields are rejected.
+//!
+//! The returned strings are owned by the caller and released with
+//! [`cmark_writer_free_string`]. On failure null is returned and
+//! [`cmark_writer_last_error`] describes the problem.
+
+mod json;
+mod registry;
+mod render;
+mod schema;
+mod sink;
+
+pub use self::json::Json;
+#[cfg(feature = "python")]
+pub(crate) use self::json::MAX_DEPTH;
+pub use self::registry::{CustomNodeDeserializer, CustomNodeRegistry};
+pub use self::render::{
+    cmark_writer_free_string, cmark_writer_last_error, cmark_writer_render_commonmark,
+    cmark_writer_render_html, node_from_json,
+};
+#[cfg(feature = "python")]
+pub(crate) use self::render::{html_options_from_json, writer_options_from_json};
+pub use self::schema::{document_from_json, upgrade_document, SCHEMA_VERSION};
+pub use self::sink::*;
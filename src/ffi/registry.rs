@@ -0,0 +1,229 @@
+//! Deserializers for custom nodes in JSON documents.
+
+use super::json::Json;
+use super::render::node_from_json;
+use crate::ast::{CustomNode, Node};
+use ecow::EcoString;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Builds a custom node from the `data` of its JSON form
+pub type CustomNodeDeserializer =
+    dyn Fn(&Json) -> Result<Box<dyn CustomNode>, String> + Send + Sync;
+
+static GLOBAL: OnceLock<RwLock<CustomNodeRegistry>> = OnceLock::new();
+
+thread_local! {
+    /// Registries installed by `CustomNodeRegistry::scope`, innermost last
+    static SCOPED: RefCell<Vec<CustomNodeRegistry>> = const { RefCell::new(Vec::new()) };
+}
+
+fn global() -> &'static RwLock<CustomNodeRegistry> {
+    GLOBAL.get_or_init(|| RwLock::new(CustomNodeRegistry::new()))
+}
+
+/// Deserializers for custom nodes, keyed by custom type name
+///
+/// A custom node is written in JSON as
+/// `{"type": "Custom", "custom_type": "<name>", "data": ...}`, and the
+/// deserializer registered for `<name>` turns `data` (`null` when omitted) into
+/// the node. Lookups go through the registries installed with
+/// [`scope`](Self::scope) on the current thread, innermost first, and then
+/// through the process-wide registry filled by
+/// [`register_global`](Self::register_global).
+///
+/// # Example
+///
+/// ```
+/// # use cmark_writer::{CommonMarkWriter, CustomNode, Format, NodeClone, NodeContent};
+/// # use cmark_writer::error::WriteResult;
+/// # use std::any::Any;
+/// use cmark_writer::ffi::{CustomNodeRegistry, Json};
+/// use cmark_writer::ast::Node;
+///
+/// #[derive(Debug, Clone, PartialEq, NodeClone)]
+/// struct Badge(String);
+/// # impl NodeContent for Badge {
+/// #     fn is_block(&self) -> bool { false }
+/// #     fn as_any(&self) -> &dyn Any { self }
+/// #     fn as_any_mut(&mut self) -> &mut dyn Any { self }
+/// # }
+/// # impl Format<CommonMarkWriter> for Badge {
+/// #     fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+/// #         writer.write_str(&self.0)
+/// #     }
+/// # }
+/// # impl CustomNode for Badge {}
+///
+/// let registry = CustomNodeRegistry::new().with("Badge", |data| match data {
+///     Json::String(label) => Ok(Box::new(Badge(label.to_string()))),
+///     other => Err(format!("expected a string, found {}", other.kind())),
+/// });
+///
+/// let node = registry
+///     .node_from_json(r#"{"type": "Custom", "custom_type": "Badge", "data": "new"}"#)
+///     .unwrap();
+/// assert!(matches!(node, Node::Custom(_)));
+/// ```
+#[derive(Clone, Default)]
+pub struct CustomNodeRegistry {
+    deserializers: BTreeMap<EcoString, Arc<CustomNodeDeserializer>>,
+}
+
+impl fmt::Debug for CustomNodeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.deserializers.keys()).finish()
+    }
+}
+
+impl CustomNodeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the deserializer for `custom_type`, replacing any previous one
+    pub fn register<S, F>(&mut self, custom_type: S, deserializer: F)
+    where
+        S: Into<EcoString>,
+        F: Fn(&Json) -> Result<Box<dyn CustomNode>, String> + Send + Sync + 'static,
+    {
+        self.deserializers
+            .insert(custom_type.into(), Arc::new(deserializer));
+    }
+
+    /// Builder form of [`register`](Self::register)
+    pub fn with<S, F>(mut self, custom_type: S, deserializer: F) -> Self
+    where
+        S: Into<EcoString>,
+        F: Fn(&Json) -> Result<Box<dyn CustomNode>, String> + Send + Sync + 'static,
+    {
+        self.register(custom_type, deserializer);
+        self
+    }
+
+    /// Register a deserializer in the process-wide registry
+    ///
+    /// Safe to call from any thread; documents already being read keep the
+    /// deserializers they started with.
+    pub fn register_global<S, F>(custom_type: S, deserializer: F)
+    where
+        S: Into<EcoString>,
+        F: Fn(&Json) -> Result<Box<dyn CustomNode>, String> + Send + Sync + 'static,
+    {
+        global()
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .register(custom_type, deserializer);
+    }
+
+    /// Remove a deserializer from the process-wide registry, returning whether it existed
+    pub fn unregister_global(custom_type: &str) -> bool {
+        global()
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .deserializers
+            .remove(custom_type)
+            .is_some()
+    }
+
+    /// Whether a deserializer is registered for `custom_type`
+    pub fn contains(&self, custom_type: &str) -> bool {
+        self.deserializers.contains_key(custom_type)
+    }
+
+    /// Registered custom type names, sorted
+    pub fn custom_types(&self) -> impl Iterator<Item = &str> {
+        self.deserializers.keys().map(EcoString::as_str)
+    }
+
+    /// Run `f` with this registry taking precedence over the process-wide one
+    /// on the current thread
+    ///
+    /// This covers the `cmark_writer_render_*` functions called inside `f`.
+    /// Scopes nest; a type missing from this registry is looked up in the
+    /// enclosing scopes and then in the process-wide registry.
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Guard;
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                SCOPED.with(|scoped| scoped.borrow_mut().pop());
+            }
+        }
+
+        SCOPED.with(|scoped| scoped.borrow_mut().push(self.clone()));
+        let _guard = Guard;
+        f()
+    }
+
+    /// Read a JSON document into a node, using this registry for custom nodes
+    pub fn node_from_json(&self, json: &str) -> Result<Node, String> {
+        self.scope(|| node_from_json(&Json::parse(json)?))
+    }
+}
+
+/// The deserializer for `custom_type` in scope on this thread
+fn lookup(custom_type: &str) -> Option<Arc<CustomNodeDeserializer>> {
+    let scoped = SCOPED.with(|scoped| {
+        scoped
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|registry| registry.deserializers.get(custom_type).cloned())
+    });
+    scoped.or_else(|| {
+        global()
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .deserializers
+            .get(custom_type)
+            .cloned()
+    })
+}
+
+/// Every custom type name in scope on this thread, sorted and deduplicated
+fn known_custom_types() -> Vec<EcoString> {
+    let mut names: Vec<EcoString> = SCOPED.with(|scoped| {
+        scoped
+            .borrow()
+            .iter()
+            .flat_map(|registry| registry.deserializers.keys().cloned())
+            .collect()
+    });
+    names.extend(
+        global()
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .deserializers
+            .keys()
+            .cloned(),
+    );
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Build a custom node from its type name and data
+pub(super) fn custom_node_from_json(custom_type: &str, data: &Json) -> Result<Node, String> {
+    // The deserializer is called without holding any lock, so it may read nested nodes
+    let Some(deserializer) = lookup(custom_type) else {
+        let known = known_custom_types();
+        return Err(if known.is_empty() {
+            format!(
+                "Custom: unknown custom node type `{}` (no custom node types are registered)",
+                custom_type
+            )
+        } else {
+            format!(
+                "Custom: unknown custom node type `{}` (registered: {})",
+                custom_type,
+                known.join(", ")
+            )
+        });
+    };
+    deserializer(data)
+        .map(Node::Custom)
+        .map_err(|message| format!("Custom `{}`: {}", custom_type, message))
+}
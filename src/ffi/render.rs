@@ -0,0 +1,634 @@
+//! Rendering JSON documents to CommonMark and HTML from C.
+
+use super::json::Json;
+use super::registry::custom_node_from_json;
+use super::schema::document_from_json;
+use crate::ast::{
+    CodeBlockType, DefinitionItem, HeadingType, HtmlAttribute, HtmlElement, ListItem, Node,
+};
+#[cfg(feature = "gfm")]
+use crate::ast::{TableAlignment, TaskListStatus};
+use crate::options::{EscapeStrategy, WriterOptions};
+use crate::traits::ToHtml;
+use crate::writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions};
+use ecow::EcoString;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Fields of a JSON object describing a node, list item or options
+struct Fields<'a> {
+    what: &'a str,
+    members: &'a [(EcoString, Json)],
+}
+
+impl<'a> Fields<'a> {
+    fn new(what: &'a str, json: &'a Json) -> Result<Self, String> {
+        match json {
+            Json::Object(members) => Ok(Self { what, members }),
+            other => Err(format!(
+                "{}: expected an object, found {}",
+                what,
+                other.kind()
+            )),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Json> {
+        self.members
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+            .filter(|value| **value != Json::Null)
+    }
+
+    fn invalid(&self, key: &str, expected: &str) -> String {
+        format!("{}: field `{}` must be {}", self.what, key, expected)
+    }
+
+    fn opt_str(&self, key: &str) -> Result<Option<EcoString>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(self.invalid(key, "a string")),
+        }
+    }
+
+    fn str(&self, key: &str) -> Result<EcoString, String> {
+        self.opt_str(key)?
+            .ok_or_else(|| format!("{}: missing field `{}`", self.what, key))
+    }
+
+    fn opt_bool(&self, key: &str) -> Result<Option<bool>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::Bool(b)) => Ok(Some(*b)),
+            Some(_) => Err(self.invalid(key, "a boolean")),
+        }
+    }
+
+    fn opt_u32(&self, key: &str) -> Result<Option<u32>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::Number(n)) if n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(n) => {
+                Ok(Some(*n as u32))
+            }
+            Some(_) => Err(self.invalid(key, "a non-negative integer")),
+        }
+    }
+
+    fn opt_char(&self, key: &str) -> Result<Option<char>, String> {
+        let Some(s) = self.opt_str(key)? else {
+            return Ok(None);
+        };
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(Some(c)),
+            _ => Err(self.invalid(key, "a single character")),
+        }
+    }
+
+    fn array(&self, key: &str) -> Result<&'a [Json], String> {
+        match self.get(key) {
+            None => Ok(&[]),
+            Some(Json::Array(items)) => Ok(items),
+            Some(_) => Err(self.invalid(key, "an array")),
+        }
+    }
+
+    fn nodes(&self, key: &str) -> Result<Vec<Node>, String> {
+        self.array(key)?.iter().map(node_from_json).collect()
+    }
+
+    fn opt_nodes(&self, key: &str) -> Result<Option<Vec<Node>>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(_) => self.nodes(key).map(Some),
+        }
+    }
+
+    /// Fail on members not in `known`, so that misspelled options aren't silently ignored
+    fn deny_unknown(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .members
+            .iter()
+            .find(|(name, _)| !known.contains(&name.as_str()))
+        {
+            Some((name, _)) => Err(format!("{}: unknown field `{}`", self.what, name)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Build a node from its JSON form (see the [module documentation](super))
+///
+/// Custom nodes are read with the [`CustomNodeRegistry`](super::CustomNodeRegistry)
+/// in scope, so a deserializer can use this to read nodes nested in its data.
+pub fn node_from_json(json: &Json) -> Result<Node, String> {
+    let ty = match Fields::new("node", json)?.get("type") {
+        Some(Json::String(ty)) => ty.clone(),
+        _ => return Err("node: missing string field `type`".into()),
+    };
+    let fields = Fields::new(&ty, json)?;
+
+    let node = match ty.as_str() {
+        "Document" => Node::Document(fields.nodes("children")?),
+        "Paragraph" => Node::Paragraph(fields.nodes("children")?),
+        "BlockQuote" => Node::BlockQuote(fields.nodes("children")?),
+        "Emphasis" => Node::Emphasis(fields.nodes("children")?),
+        "Strong" => Node::Strong(fields.nodes("children")?),
+        "Strikethrough" => Node::Strikethrough(fields.nodes("children")?),
+        "ThematicBreak" => Node::ThematicBreak,
+        "ReadMoreMarker" => Node::ReadMoreMarker,
+        "HardBreak" => Node::HardBreak,
+        "SoftBreak" => Node::SoftBreak,
+        "Text" => Node::Text(fields.str("text")?),
+        "InlineCode" => Node::InlineCode(fields.str("text")?),
+        "HtmlBlock" => Node::HtmlBlock(fields.str("text")?),
+        "HtmlInline" => Node::HtmlInline(fields.str("text")?),
+        "Math" => Node::Math {
+            display: fields.opt_bool("display")?.unwrap_or(false),
+            content: fields.str("content")?,
+        },
+        "ExtendedAutolink" => Node::ExtendedAutolink(fields.str("text")?),
+        "Heading" => Node::Heading {
+            level: fields
+                .opt_u32("level")?
+                .and_then(|level| u8::try_from(level).ok())
+                .ok_or_else(|| fields.invalid("level", "an integer from 1 to 6"))?,
+            content: fields.nodes("content")?,
+            heading_type: match fields.opt_str("heading_type")?.as_deref() {
+                None | Some("atx") => HeadingType::Atx,
+                Some("setext") => HeadingType::Setext,
+                Some(_) => return Err(fields.invalid("heading_type", "\"atx\" or \"setext\"")),
+            },
+            custom_id: fields.opt_str("custom_id")?,
+        },
+        "CodeBlock" => Node::CodeBlock {
+            language: fields.opt_str("language")?,
+            content: fields.str("content")?,
+            block_type: match fields.opt_str("block_type")?.as_deref() {
+                None | Some("fenced") => CodeBlockType::Fenced,
+                Some("indented") => CodeBlockType::Indented,
+                Some(_) => return Err(fields.invalid("block_type", "\"fenced\" or \"indented\"")),
+            },
+        },
+        "LinkReferenceDefinition" => Node::LinkReferenceDefinition {
+            label: fields.str("label")?,
+            destination: fields.str("destination")?,
+            title: fields.opt_str("title")?,
+        },
+        "Link" => Node::Link {
+            url: fields.str("url")?,
+            title: fields.opt_str("title")?,
+            content: fields.nodes("content")?,
+        },
+        "CrossRef" => Node::CrossRef {
+            target: fields.str("target")?,
+            text: fields.opt_nodes("text")?,
+        },
+        "ReferenceLink" => Node::ReferenceLink {
+            label: fields.str("label")?,
+            content: fields.nodes("content")?,
+        },
+        "FootnoteReference" => Node::FootnoteReference(fields.str("label")?),
+        "FootnoteDefinition" => Node::FootnoteDefinition {
+            label: fields.str("label")?,
+            content: fields.nodes("content")?,
+        },
+        "DefinitionList" => Node::DefinitionList(definition_items(&fields)?),
+        "Image" => Node::Image {
+            url: fields.str("url")?,
+            title: fields.opt_str("title")?,
+            alt: fields.nodes("alt")?,
+        },
+        "Autolink" => Node::Autolink {
+            url: fields.str("url")?,
+            is_email: fields.opt_bool("is_email")?.unwrap_or(false),
+        },
+        "UnorderedList" => Node::UnorderedList(list_items(&fields, false)?),
+        "OrderedList" => Node::OrderedList {
+            start: fields.opt_u32("start")?.unwrap_or(1),
+            items: list_items(&fields, true)?,
+        },
+        "Table" => Node::Table {
+            headers: fields.nodes("headers")?,
+            #[cfg(feature = "gfm")]
+            alignments: fields
+                .array("alignments")?
+                .iter()
+                .map(|alignment| match alignment {
+                    Json::String(s) if s == "left" => Ok(TableAlignment::Left),
+                    Json::String(s) if s == "center" => Ok(TableAlignment::Center),
+                    Json::String(s) if s == "right" => Ok(TableAlignment::Right),
+                    Json::String(s) if s == "none" => Ok(TableAlignment::None),
+                    _ => Err(fields.invalid(
+                        "alignments",
+                        "an array of \"left\", \"center\", \"right\" or \"none\"",
+                    )),
+                })
+                .collect::<Result<_, _>>()?,
+            rows: fields
+                .array("rows")?
+                .iter()
+                .map(|row| match row {
+                    Json::Array(cells) => cells.iter().map(node_from_json).collect(),
+                    _ => Err(fields.invalid("rows", "an array of arrays")),
+                })
+                .collect::<Result<_, _>>()?,
+        },
+        "HtmlElement" => {
+            let attributes = match fields.get("attributes") {
+                None => Vec::new(),
+                Some(Json::Object(members)) => members
+                    .iter()
+                    .map(|(name, value)| match value {
+                        Json::String(value) => Ok(HtmlAttribute {
+                            name: name.clone(),
+                            value: value.clone(),
+                        }),
+                        _ => Err(fields.invalid("attributes", "an object of strings")),
+                    })
+                    .collect::<Result<_, _>>()?,
+                Some(_) => return Err(fields.invalid("attributes", "an object")),
+            };
+            Node::HtmlElement(HtmlElement {
+                tag: fields.str("tag")?,
+                attributes,
+                children: fields.nodes("children")?,
+                self_closing: fields.opt_bool("self_closing")?.unwrap_or(false),
+            })
+        }
+        "Custom" => {
+            let custom_type = fields.str("custom_type")?;
+            custom_node_from_json(&custom_type, fields.get("data").unwrap_or(&Json::Null))?
+        }
+        _ => return Err(format!("unsupported node type `{}`", ty)),
+    };
+    Ok(node)
+}
+
+fn list_items(fields: &Fields<'_>, ordered: bool) -> Result<Vec<ListItem>, String> {
+    fields
+        .array("items")?
+        .iter()
+        .map(|item| {
+            let item = Fields::new("list item", item)?;
+            let content = item.nodes("content")?;
+            #[cfg(feature = "gfm")]
+            if let Some(status) = item.opt_str("status")? {
+                let status = match status.as_str() {
+                    "checked" => TaskListStatus::Checked,
+                    "unchecked" => TaskListStatus::Unchecked,
+                    _ => return Err(item.invalid("status", "\"checked\" or \"unchecked\"")),
+                };
+                return Ok(ListItem::Task { status, content });
+            }
+            Ok(if ordered {
+                ListItem::Ordered {
+                    number: item.opt_u32("number")?,
+                    content,
+                }
+            } else {
+                ListItem::Unordered { content }
+            })
+        })
+        .collect()
+}
+
+fn definition_items(fields: &Fields<'_>) -> Result<Vec<DefinitionItem>, String> {
+    fields
+        .array("items")?
+        .iter()
+        .map(|item| {
+            let item = Fields::new("definition list item", item)?;
+            let definitions = item
+                .array("definitions")?
+                .iter()
+                .map(|definition| match definition {
+                    Json::Array(blocks) => blocks.iter().map(node_from_json).collect(),
+                    _ => Err(item.invalid("definitions", "an array of arrays")),
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(DefinitionItem::new(item.nodes("term")?, definitions))
+        })
+        .collect()
+}
+
+/// Build CommonMark writer options from their JSON form
+pub(crate) fn writer_options_from_json(json: &Json) -> Result<WriterOptions, String> {
+    let fields = Fields::new("options", json)?;
+    fields.deny_unknown(&[
+        "strict",
+        "hard_break_spaces",
+        "indent_spaces",
+        "list_marker",
+        "thematic_break_char",
+        "emphasis_char",
+        "strong_char",
+        "escape_special_chars",
+        "escape_strategy",
+        "trim_paragraph_trailing_hard_breaks",
+        "table_delimiter_min_dashes",
+        "table_delimiter_padding",
+        "pretty_tables",
+        "bidi_isolation",
+        "autolink_default_scheme",
+        "attribute_blocks",
+        "math",
+        "definition_lists",
+        "footnotes",
+        "table_soft_breaks_as_spaces",
+        "max_blank_lines",
+        "enable_gfm",
+    ])?;
+
+    let mut options = WriterOptions::default();
+    if let Some(strict) = fields.opt_bool("strict")? {
+        options.strict = strict;
+    }
+    if let Some(spaces) = fields.opt_bool("hard_break_spaces")? {
+        options.hard_break_spaces = spaces;
+    }
+    if let Some(indent) = fields.opt_u32("indent_spaces")? {
+        options.indent_spaces = indent as usize;
+    }
+    if let Some(marker) = fields.opt_char("list_marker")? {
+        options.list_marker = marker;
+    }
+    if let Some(c) = fields.opt_char("thematic_break_char")? {
+        options.thematic_break_char = c;
+    }
+    if let Some(c) = fields.opt_char("emphasis_char")? {
+        options.emphasis_char = c;
+    }
+    if let Some(c) = fields.opt_char("strong_char")? {
+        options.strong_char = c;
+    }
+    if let Some(escape) = fields.opt_bool("escape_special_chars")? {
+        options.escape_special_chars = escape;
+    }
+    match fields.opt_str("escape_strategy")?.as_deref() {
+        None => {}
+        Some("contextual") => options.escape_strategy = EscapeStrategy::Contextual,
+        Some("always") => options.escape_strategy = EscapeStrategy::Always,
+        Some(_) => return Err(fields.invalid("escape_strategy", "\"contextual\" or \"always\"")),
+    }
+    if let Some(trim) = fields.opt_bool("trim_paragraph_trailing_hard_breaks")? {
+        options.trim_paragraph_trailing_hard_breaks = trim;
+    }
+    if let Some(dashes) = fields.opt_u32("table_delimiter_min_dashes")? {
+        options.table_delimiter_min_dashes = (dashes as usize).max(1);
+    }
+    if let Some(padding) = fields.opt_bool("table_delimiter_padding")? {
+        options.table_delimiter_padding = padding;
+    }
+    if let Some(pretty) = fields.opt_bool("pretty_tables")? {
+        options.pretty_tables = pretty;
+    }
+    if let Some(isolate) = fields.opt_bool("bidi_isolation")? {
+        options.bidi_isolation = isolate;
+    }
+    if let Some(scheme) = fields.opt_str("autolink_default_scheme")? {
+        options.autolink_default_scheme = scheme;
+    }
+    if let Some(enabled) = fields.opt_bool("attribute_blocks")? {
+        options.attribute_blocks = enabled;
+    }
+    if let Some(enabled) = fields.opt_bool("math")? {
+        options.math = enabled;
+    }
+    if let Some(enabled) = fields.opt_bool("definition_lists")? {
+        options.definition_lists = enabled;
+    }
+    if let Some(enabled) = fields.opt_bool("footnotes")? {
+        options.footnotes = enabled;
+    }
+    if let Some(enabled) = fields.opt_bool("table_soft_breaks_as_spaces")? {
+        options.table_soft_breaks_as_spaces = enabled;
+    }
+    if let Some(max) = fields.opt_u32("max_blank_lines")? {
+        options.max_blank_lines = Some(max as usize);
+    }
+    if let Some(enable) = fields.opt_bool("enable_gfm")? {
+        #[cfg(feature = "gfm")]
+        if enable {
+            options.enable_gfm = true;
+            options.gfm_strikethrough = true;
+            options.gfm_tasklists = true;
+            options.gfm_tables = true;
+            options.gfm_autolinks = true;
+        }
+        #[cfg(not(feature = "gfm"))]
+        if enable {
+            return Err("options: `enable_gfm` requires the `gfm` feature".into());
+        }
+    }
+    Ok(options)
+}
+
+/// Build HTML writer options from their JSON form
+pub(crate) fn html_options_from_json(json: &Json) -> Result<HtmlWriterOptions, String> {
+    let fields = Fields::new("options", json)?;
+    fields.deny_unknown(&[
+        "strict",
+        "code_block_language_class_prefix",
+        "default_code_language",
+        "autolink_default_scheme",
+        "deduplicate_attributes",
+        "sort_attributes",
+        "escape_quotes_in_text",
+        "allowed_url_schemes",
+        "enable_gfm",
+    ])?;
+
+    let mut options = HtmlWriterOptions::default();
+    if let Some(strict) = fields.opt_bool("strict")? {
+        options.strict = strict;
+    }
+    if fields
+        .members
+        .iter()
+        .any(|(name, _)| name == "code_block_language_class_prefix")
+    {
+        // `null` disables the class
+        options.code_block_language_class_prefix =
+            fields.opt_str("code_block_language_class_prefix")?;
+    }
+    options.default_code_language = fields.opt_str("default_code_language")?;
+    if let Some(scheme) = fields.opt_str("autolink_default_scheme")? {
+        options.autolink_default_scheme = scheme;
+    }
+    if let Some(dedup) = fields.opt_bool("deduplicate_attributes")? {
+        options.deduplicate_attributes = dedup;
+    }
+    if let Some(sort) = fields.opt_bool("sort_attributes")? {
+        options.sort_attributes = sort;
+    }
+    if let Some(escape) = fields.opt_bool("escape_quotes_in_text")? {
+        options.escape_quotes_in_text = escape;
+    }
+    if fields.get("allowed_url_schemes").is_some() {
+        let schemes = fields
+            .array("allowed_url_schemes")?
+            .iter()
+            .map(|scheme| match scheme {
+                Json::String(scheme) => Ok(scheme.clone()),
+                _ => Err(fields.invalid("allowed_url_schemes", "an array of strings")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        options.allowed_url_schemes = Some(schemes);
+    }
+    if let Some(enable) = fields.opt_bool("enable_gfm")? {
+        #[cfg(feature = "gfm")]
+        if enable {
+            options.enable_gfm = true;
+            options.gfm_disallowed_html_tags = crate::gfm::DisallowedTags::gfm();
+        }
+        #[cfg(not(feature = "gfm"))]
+        if enable {
+            return Err("options: `enable_gfm` requires the `gfm` feature".into());
+        }
+    }
+    Ok(options)
+}
+
+/// Parse the C string arguments, render with `render` and return an owned C string
+///
+/// # Safety
+///
+/// `json_ast` must be a valid NUL-terminated string; `options_json` must be null
+/// or a valid NUL-terminated string.
+unsafe fn render_with<O>(
+    json_ast: *const c_char,
+    options_json: *const c_char,
+    parse_options: fn(&Json) -> Result<O, String>,
+    render: fn(&Node, O) -> Result<EcoString, String>,
+) -> *mut c_char
+where
+    O: Default,
+{
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        if json_ast.is_null() {
+            return Err("json_ast is null".to_string());
+        }
+        let ast = CStr::from_ptr(json_ast)
+            .to_str()
+            .map_err(|_| "json_ast is not valid UTF-8".to_string())?;
+        let node = document_from_json(Json::parse(ast)?)?;
+
+        let options = if options_json.is_null() {
+            O::default()
+        } else {
+            let options = CStr::from_ptr(options_json)
+                .to_str()
+                .map_err(|_| "options_json is not valid UTF-8".to_string())?;
+            parse_options(&Json::parse(options)?)?
+        };
+
+        let output = render(&node, options)?;
+        CString::new(output.as_str()).map_err(|_| "output contains a NUL byte".to_string())
+    }));
+
+    match result {
+        Ok(Ok(output)) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            output.into_raw()
+        }
+        Ok(Err(message)) => {
+            set_last_error(message);
+            std::ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("internal error while rendering".into());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Render a JSON AST to CommonMark
+///
+/// Returns a newly allocated string that must be released with
+/// [`cmark_writer_free_string`], or null on error (see
+/// [`cmark_writer_last_error`]). `options_json` may be null for the default options.
+///
+/// # Safety
+///
+/// `json_ast` must be a valid NUL-terminated string; `options_json` must be null
+/// or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_writer_render_commonmark(
+    json_ast: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    render_with(
+        json_ast,
+        options_json,
+        writer_options_from_json,
+        |node, options| {
+            let mut writer = CommonMarkWriter::with_options(options);
+            writer.write_node(node).map_err(|err| err.to_string())?;
+            Ok(writer.into_string())
+        },
+    )
+}
+
+/// Render a JSON AST to HTML
+///
+/// Ownership and errors work as for [`cmark_writer_render_commonmark`].
+///
+/// # Safety
+///
+/// `json_ast` must be a valid NUL-terminated string; `options_json` must be null
+/// or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_writer_render_html(
+    json_ast: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    render_with(
+        json_ast,
+        options_json,
+        html_options_from_json,
+        |node, options| {
+            let mut writer = HtmlWriter::with_options(options);
+            node.to_html(&mut writer).map_err(|err| err.to_string())?;
+            Ok(writer.into_string())
+        },
+    )
+}
+
+/// Release a string returned by one of the render functions
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by a `cmark_writer_render_*` function
+/// that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_writer_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The message of the last render error on this thread, or null
+///
+/// The pointer stays valid until the next render call on the same thread and
+/// must not be freed.
+#[no_mangle]
+pub extern "C" fn cmark_writer_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
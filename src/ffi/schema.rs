@@ -0,0 +1,131 @@
+//! Versions of the JSON document format and the upgrades between them.
+
+use super::json::Json;
+use super::render::node_from_json;
+use crate::ast::Node;
+
+/// Version of the JSON document format read by this crate
+///
+/// Version 1 is the format before versioning; documents without a `version`
+/// are read as version 1. Version 2 added the `FootnoteReference`,
+/// `FootnoteDefinition` and `DefinitionList` nodes. Version 3 added the
+/// `ReadMoreMarker`, `Math`, `CrossRef` and `HtmlInline` nodes.
+///
+/// Bump the version whenever a node type or field is added, and register an
+/// upgrade from the previous version in `UPGRADES`.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Rewrites a document of one version into the next
+type Upgrade = fn(Json) -> Result<Json, String>;
+
+/// The upgrades, indexed by the version they upgrade from, minus one
+const UPGRADES: &[Upgrade] = &[upgrade_v1, upgrade_v2];
+
+/// Version 2 only added node types, so version 1 documents are valid as is
+fn upgrade_v1(json: Json) -> Result<Json, String> {
+    Ok(json)
+}
+
+/// Node types holding inline content, and the member holding it
+const INLINE_CONTAINERS: &[(&str, &str)] = &[
+    ("Paragraph", "children"),
+    ("Emphasis", "children"),
+    ("Strong", "children"),
+    ("Strikethrough", "children"),
+    ("Heading", "content"),
+    ("Link", "content"),
+    ("ReferenceLink", "content"),
+    ("Image", "alt"),
+];
+
+/// Before version 3, raw HTML among inline content could only be an
+/// `HtmlBlock`, which the HTML writer follows with a line break; it becomes
+/// an `HtmlInline`
+fn upgrade_v2(mut json: Json) -> Result<Json, String> {
+    retype_inline_html(&mut json, false);
+    Ok(json)
+}
+
+fn retype_inline_html(json: &mut Json, inline: bool) {
+    match json {
+        Json::Array(items) => {
+            for item in items {
+                retype_inline_html(item, inline);
+            }
+        }
+        Json::Object(members) => {
+            let ty = members.iter().find_map(|(name, value)| match value {
+                Json::String(ty) if name == "type" => Some(ty.clone()),
+                _ => None,
+            });
+            if inline && ty.as_deref() == Some("HtmlBlock") {
+                for (name, value) in members.iter_mut() {
+                    if name == "type" {
+                        *value = Json::String("HtmlInline".into());
+                    }
+                }
+                return;
+            }
+            let container = INLINE_CONTAINERS
+                .iter()
+                .find(|(container, _)| ty.as_deref() == Some(*container));
+            for (name, value) in members.iter_mut() {
+                let inline = container.is_some_and(|(_, content)| name == content);
+                retype_inline_html(value, inline);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Split the `version` member off a document root, defaulting to 1
+fn take_version(json: Json) -> Result<(u32, Json), String> {
+    let Json::Object(mut members) = json else {
+        return Ok((1, json));
+    };
+    let Some(index) = members.iter().position(|(name, _)| name == "version") else {
+        return Ok((1, Json::Object(members)));
+    };
+    let version = match members.remove(index).1 {
+        Json::Number(n) if n.fract() == 0.0 && (1.0..=u32::MAX as f64).contains(&n) => n as u32,
+        _ => return Err("document: field `version` must be a positive integer".into()),
+    };
+    Ok((version, Json::Object(members)))
+}
+
+/// Bring a document of any supported version up to [`SCHEMA_VERSION`]
+///
+/// The `version` member of the root node is removed. Documents from a newer
+/// version of the format are rejected rather than misread.
+pub fn upgrade_document(json: Json) -> Result<Json, String> {
+    let (version, mut json) = take_version(json)?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "document: schema version {} is newer than the supported version {}",
+            version, SCHEMA_VERSION
+        ));
+    }
+    for upgrade in &UPGRADES[version as usize - 1..] {
+        json = upgrade(json)?;
+    }
+    Ok(json)
+}
+
+/// Build a node from a versioned JSON document
+///
+/// The root node may carry a `version` member (see [`SCHEMA_VERSION`]);
+/// the document is upgraded to the current version and then read with
+/// [`node_from_json`].
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::ffi::{document_from_json, Json};
+///
+/// let json = Json::parse(r#"{"version": 1, "type": "Text", "text": "cached"}"#).unwrap();
+/// assert_eq!(document_from_json(json).unwrap(), Node::Text("cached".into()));
+/// ```
+pub fn document_from_json(json: Json) -> Result<Node, String> {
+    node_from_json(&upgrade_document(json)?)
+}
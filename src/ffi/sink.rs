@@ -0,0 +1,144 @@
+//! Writer handles that plugins call back into.
+
+use crate::diagnostics;
+use crate::error::WriteResult;
+use crate::traits::{DynCommonMarkSink, DynHtmlSink};
+use std::os::raw::c_int;
+
+/// The call succeeded
+pub const CMARK_WRITER_OK: c_int = 0;
+/// The writer returned an error
+pub const CMARK_WRITER_ERR_WRITE: c_int = -1;
+/// A pointer argument was null
+pub const CMARK_WRITER_ERR_NULL: c_int = -2;
+/// A string argument was not valid UTF-8
+pub const CMARK_WRITER_ERR_UTF8: c_int = -3;
+
+/// Opaque handle to a CommonMark writer, passed to plugins by pointer
+pub struct CmarkSink<'a>(&'a mut dyn DynCommonMarkSink);
+
+impl<'a> CmarkSink<'a> {
+    /// Wrap a writer so that a pointer to it can cross the FFI boundary
+    pub fn new(sink: &'a mut dyn DynCommonMarkSink) -> Self {
+        Self(sink)
+    }
+}
+
+/// Opaque handle to an HTML writer, passed to plugins by pointer
+pub struct HtmlSink<'a>(&'a mut dyn DynHtmlSink);
+
+impl<'a> HtmlSink<'a> {
+    /// Wrap a writer so that a pointer to it can cross the FFI boundary
+    pub fn new(sink: &'a mut dyn DynHtmlSink) -> Self {
+        Self(sink)
+    }
+}
+
+/// Convert a pointer/length pair into a string slice
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` readable bytes.
+unsafe fn str_arg<'a>(data: *const u8, len: usize) -> Result<&'a str, c_int> {
+    if data.is_null() {
+        return if len == 0 {
+            Ok("")
+        } else {
+            Err(CMARK_WRITER_ERR_NULL)
+        };
+    }
+    let bytes = std::slice::from_raw_parts(data, len);
+    std::str::from_utf8(bytes).map_err(|_| CMARK_WRITER_ERR_UTF8)
+}
+
+fn status(result: WriteResult<()>) -> c_int {
+    match result {
+        Ok(()) => CMARK_WRITER_OK,
+        Err(err) => {
+            diagnostics::warn!("Plugin write failed: {}", err);
+            CMARK_WRITER_ERR_WRITE
+        }
+    }
+}
+
+macro_rules! sink_fn {
+    ($(#[$doc:meta])* $name:ident($sink:ty) => $method:ident) => {
+        $(#[$doc])*
+        ///
+        /// # Safety
+        ///
+        /// `sink` must be null or a valid pointer to a live handle, and `data`
+        /// must be null or point to `len` readable bytes.
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(sink: *mut $sink, data: *const u8, len: usize) -> c_int {
+            let Some(sink) = sink.as_mut() else {
+                return CMARK_WRITER_ERR_NULL;
+            };
+            match str_arg(data, len) {
+                Ok(s) => status(sink.0.$method(s)),
+                Err(code) => code,
+            }
+        }
+    };
+}
+
+sink_fn!(
+    /// Write a string to a CommonMark sink without escaping
+    cmark_writer_sink_write_str(CmarkSink<'_>) => write_str
+);
+sink_fn!(
+    /// Write text to a CommonMark sink, escaping it according to the writer's options
+    cmark_writer_sink_write_text(CmarkSink<'_>) => write_text
+);
+sink_fn!(
+    /// Open a start tag on an HTML sink
+    cmark_writer_html_sink_start_tag(HtmlSink<'_>) => start_tag
+);
+sink_fn!(
+    /// Write an end tag to an HTML sink
+    cmark_writer_html_sink_end_tag(HtmlSink<'_>) => end_tag
+);
+sink_fn!(
+    /// Write escaped text to an HTML sink
+    cmark_writer_html_sink_text(HtmlSink<'_>) => text
+);
+sink_fn!(
+    /// Write HTML to an HTML sink without escaping
+    cmark_writer_html_sink_raw_html(HtmlSink<'_>) => raw_html
+);
+
+/// Write an attribute of the open start tag to an HTML sink
+///
+/// # Safety
+///
+/// `sink` must be null or a valid pointer to a live handle, and `name` and
+/// `value` must be null or point to `name_len` and `value_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_writer_html_sink_attribute(
+    sink: *mut HtmlSink<'_>,
+    name: *const u8,
+    name_len: usize,
+    value: *const u8,
+    value_len: usize,
+) -> c_int {
+    let Some(sink) = sink.as_mut() else {
+        return CMARK_WRITER_ERR_NULL;
+    };
+    match (str_arg(name, name_len), str_arg(value, value_len)) {
+        (Ok(name), Ok(value)) => status(sink.0.attribute(name, value)),
+        (Err(code), _) | (_, Err(code)) => code,
+    }
+}
+
+/// Close the open start tag of an HTML sink
+///
+/// # Safety
+///
+/// `sink` must be null or a valid pointer to a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmark_writer_html_sink_finish_tag(sink: *mut HtmlSink<'_>) -> c_int {
+    match sink.as_mut() {
+        Some(sink) => status(sink.0.finish_tag()),
+        None => CMARK_WRITER_ERR_NULL,
+    }
+}
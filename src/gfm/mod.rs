@@ -8,6 +8,10 @@
 
 pub use crate::ast::{TableAlignment, TaskListStatus};
 pub use crate::options::WriterOptionsBuilder;
+use ecow::EcoString;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ops::Deref;
 
 pub mod formatting;
 pub mod tables;
@@ -21,3 +25,246 @@ pub mod tasks;
 pub fn gfm_options() -> crate::options::WriterOptions {
     WriterOptionsBuilder::new().enable_gfm().build()
 }
+
+/// HTML tags filtered by GFM's tagfilter extension
+///
+/// These are the defaults of `WriterOptions::gfm_disallowed_html_tags`.
+pub const DISALLOWED_TAGS: &[&str] = &[
+    "title",
+    "textarea",
+    "style",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "script",
+    "plaintext",
+];
+
+/// The default disallowed tags as an owned list
+pub fn disallowed_tags() -> Vec<EcoString> {
+    DISALLOWED_TAGS.iter().map(|&tag| tag.into()).collect()
+}
+
+/// The default disallowed tags plus `extra`, skipping tags already in the list
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::gfm::{disallowed_tags_with, is_tag_disallowed};
+///
+/// let tags = disallowed_tags_with(["object", "SCRIPT"]);
+/// assert!(is_tag_disallowed("Object", &tags));
+/// assert_eq!(tags.len(), 10);
+/// ```
+pub fn disallowed_tags_with<I, S>(extra: I) -> Vec<EcoString>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<EcoString>,
+{
+    let mut tags = DisallowedTags::gfm();
+    tags.extend(extra);
+    tags.tags
+}
+
+/// Check whether `tag` is one of `tags`, ignoring ASCII case
+pub fn is_tag_disallowed<S: AsRef<str>>(tag: &str, tags: &[S]) -> bool {
+    tags.iter().any(|t| t.as_ref().eq_ignore_ascii_case(tag))
+}
+
+/// Apply the GFM tagfilter to raw HTML
+///
+/// The `<` of every opening or closing tag whose name is in `tags` (ignoring
+/// ASCII case) is replaced with `&lt;`, so browsers show the tag as text.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::gfm::{filter_disallowed_tags, DISALLOWED_TAGS};
+///
+/// assert_eq!(
+///     filter_disallowed_tags("<SCRIPT>x</script><b>y</b>", DISALLOWED_TAGS),
+///     "&lt;SCRIPT>x&lt;/script><b>y</b>"
+/// );
+/// ```
+pub fn filter_disallowed_tags<'a, S: AsRef<str>>(html: &'a str, tags: &[S]) -> Cow<'a, str> {
+    filter_tags(html, |name| is_tag_disallowed(name, tags))
+}
+
+fn filter_tags(html: &str, is_disallowed: impl Fn(&str) -> bool) -> Cow<'_, str> {
+    let mut output = String::new();
+    let mut copied = 0;
+    for (index, _) in html.match_indices('<') {
+        let rest = &html[index + 1..];
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let after = &rest[name_len..];
+        let ends_tag = after.starts_with(|c: char| c.is_ascii_whitespace() || c == '>')
+            || after.starts_with("/>");
+        if name_len > 0 && ends_tag && is_disallowed(&rest[..name_len]) {
+            output.push_str(&html[copied..index]);
+            output.push_str("&lt;");
+            copied = index + 1;
+        }
+    }
+    if copied == 0 {
+        return Cow::Borrowed(html);
+    }
+    output.push_str(&html[copied..]);
+    Cow::Owned(output)
+}
+
+/// Tag names filtered by the GFM tagfilter, in insertion order
+///
+/// Tags are compared ignoring ASCII case: adding a tag that is already present
+/// keeps the existing spelling and position, and lookups go through a set of
+/// lowercased names instead of scanning the list, which matters for documents
+/// with many HTML elements. Dereferences to the ordered list of tags.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::gfm::DisallowedTags;
+///
+/// let mut tags = DisallowedTags::gfm();
+/// tags.insert("object");
+/// tags.insert("SCRIPT");
+/// tags.remove("Title");
+/// assert!(tags.contains("Object"));
+/// assert!(!tags.contains("title"));
+/// assert_eq!(tags.last().unwrap(), "object");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DisallowedTags {
+    tags: Vec<EcoString>,
+    lowercase: HashSet<EcoString>,
+}
+
+impl DisallowedTags {
+    /// An empty list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tags filtered by GFM's tagfilter extension, [`DISALLOWED_TAGS`]
+    pub fn gfm() -> Self {
+        DISALLOWED_TAGS.iter().copied().collect()
+    }
+
+    /// Append `tag` unless it's already present (ignoring ASCII case)
+    ///
+    /// Returns whether the tag was added.
+    pub fn insert(&mut self, tag: impl Into<EcoString>) -> bool {
+        let tag = tag.into();
+        if !self.lowercase.insert(tag.to_ascii_lowercase()) {
+            return false;
+        }
+        self.tags.push(tag);
+        true
+    }
+
+    /// Remove `tag` (ignoring ASCII case), keeping the order of the other tags
+    ///
+    /// Returns whether the tag was present.
+    pub fn remove(&mut self, tag: &str) -> bool {
+        if !self.lowercase.remove(tag.to_ascii_lowercase().as_str()) {
+            return false;
+        }
+        self.tags
+            .retain(|existing| !existing.eq_ignore_ascii_case(tag));
+        true
+    }
+
+    /// Whether `tag` is in the list, ignoring ASCII case
+    pub fn contains(&self, tag: &str) -> bool {
+        if tag.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.lowercase.contains(tag.to_ascii_lowercase().as_str())
+        } else {
+            self.lowercase.contains(tag)
+        }
+    }
+
+    /// The tags in insertion order
+    pub fn as_slice(&self) -> &[EcoString] {
+        &self.tags
+    }
+
+    /// Apply the GFM tagfilter for these tags to raw HTML, like
+    /// [`filter_disallowed_tags`]
+    pub fn filter<'a>(&self, html: &'a str) -> Cow<'a, str> {
+        filter_tags(html, |name| self.contains(name))
+    }
+}
+
+impl Deref for DisallowedTags {
+    type Target = [EcoString];
+
+    fn deref(&self) -> &[EcoString] {
+        &self.tags
+    }
+}
+
+impl PartialEq for DisallowedTags {
+    fn eq(&self, other: &Self) -> bool {
+        self.tags == other.tags
+    }
+}
+
+impl Eq for DisallowedTags {}
+
+impl<S: AsRef<str>> PartialEq<[S]> for DisallowedTags {
+    fn eq(&self, other: &[S]) -> bool {
+        self.tags.len() == other.len() && self.tags.iter().zip(other).all(|(a, b)| a == b.as_ref())
+    }
+}
+
+impl<S: AsRef<str>, const N: usize> PartialEq<[S; N]> for DisallowedTags {
+    fn eq(&self, other: &[S; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+impl<S: AsRef<str>> PartialEq<Vec<S>> for DisallowedTags {
+    fn eq(&self, other: &Vec<S>) -> bool {
+        *self == other[..]
+    }
+}
+
+impl<S: Into<EcoString>> FromIterator<S> for DisallowedTags {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut tags = Self::new();
+        tags.extend(iter);
+        tags
+    }
+}
+
+impl<S: Into<EcoString>> Extend<S> for DisallowedTags {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for tag in iter {
+            self.insert(tag);
+        }
+    }
+}
+
+impl From<Vec<EcoString>> for DisallowedTags {
+    fn from(tags: Vec<EcoString>) -> Self {
+        tags.into_iter().collect()
+    }
+}
+
+impl<S: Into<EcoString>, const N: usize> From<[S; N]> for DisallowedTags {
+    fn from(tags: [S; N]) -> Self {
+        tags.into_iter().collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a DisallowedTags {
+    type Item = &'a EcoString;
+    type IntoIter = std::slice::Iter<'a, EcoString>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tags.iter()
+    }
+}
@@ -2,7 +2,13 @@
 #![deny(missing_docs)]
 
 // AST related exports
-pub use crate::ast::{CodeBlockType, HeadingType, HtmlAttribute, HtmlElement, ListItem, Node};
+pub use crate::ast::{
+    CodeBlockType, DefinitionItem, HeadingType, HtmlAttribute, HtmlElement, ListItem, ListStyle,
+    MarkdownUrl, Node, NodePath, PathSegment,
+};
+
+// Output capabilities
+pub use crate::capability::Capability;
 
 // Error types
 pub use crate::error::{CodedError, StructureError, WriteError, WriteResult};
@@ -16,9 +22,13 @@ pub use crate::traits::{
     ConfigurableProcessor,
     // Core traits
     CustomNode,
+    // Plugin sink traits
+    DynCommonMarkSink,
+    DynHtmlSink,
     // Utility traits
     ErrorContext,
     ErrorFactory,
+    FallbackStrategy,
     Format,
     HtmlRenderable,
     InlineNodeProcessor,
@@ -26,6 +36,8 @@ pub use crate::traits::{
     NodeClone,
     NodeContent,
     NodeProcessor,
+    OutputFilter,
+    PlaceholderResolver,
     ToCommonMark,
     ToHtml,
     Writer,
@@ -38,21 +50,47 @@ pub use crate::writer::processors::{
 };
 
 // Options
-pub use crate::options::{WriterOptions, WriterOptionsBuilder};
+#[cfg(feature = "unicode-normalization")]
+pub use crate::options::NormalizationForm;
+pub use crate::options::{
+    ControlCharPolicy, DocumentOptions, EmptyContainerPolicy, EscapeStrategy, FinalNewline,
+    ListRenumberingPolicy, MathDelimiters, OptionsConflict, SoftBreakStyle, TabPolicy, TitleQuote,
+    WriterOptions, WriterOptionsBuilder,
+};
 
 // CommonMark writer
-pub use crate::writer::CommonMarkWriter;
+pub use crate::writer::PlaceholderHook;
+pub use crate::writer::{CommonMarkStreamWriter, CommonMarkWriter};
+pub use crate::writer::{Numerals, OrdinalFormatter, OrdinalHook};
 
 // HTML writer related exports
 pub use crate::writer::{HtmlWriteError, HtmlWriteResult, HtmlWriter, HtmlWriterOptions};
 
 // Export proc-macro attributes and derive macros
-pub use cmark_writer_macros::{coded_error, structure_error, CommonMarkOnly};
+pub use cmark_writer_macros::{
+    coded_error, structure_error, CommonMarkOnly, CustomNodeBoilerplate, NodeClone,
+};
 
 pub mod ast;
+pub mod capability;
+mod diagnostics;
 pub mod error;
+#[cfg(feature = "escape-audit")]
+pub mod escape_audit;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod lint;
+pub mod numbering;
+pub mod ops;
 pub mod options;
+#[cfg(feature = "parser")]
+pub mod parser;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod snapshot;
+pub mod text;
 pub mod traits;
+pub mod validate;
 pub mod writer;
 
 /// GitHub Flavored Markdown (GFM) extensions
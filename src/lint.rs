@@ -0,0 +1,170 @@
+//! Lint passes reporting content problems a writer can't catch.
+//!
+//! The writers only refuse output that is malformed. The checks here look for
+//! documents that render fine but are hard to use, e.g. for readers relying on
+//! a screen reader, and return findings that a documentation pipeline can
+//! print or fail a release on.
+
+use crate::ast::{Node, NodePath};
+use ecow::EcoString;
+use std::fmt;
+
+/// Link texts that don't say where a link goes
+const NON_DESCRIPTIVE_LINK_TEXTS: &[&str] =
+    &["click here", "here", "link", "more", "read more", "this"];
+
+/// Kind of problem found by [`a11y`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum A11yIssueKind {
+    /// An `Image`, or an `<img>` `HtmlElement`, without alternative text
+    MissingAltText,
+    /// A `Table` whose header cells are all empty
+    TableWithoutHeaders,
+    /// A heading more than one level deeper than the heading before it
+    HeadingLevelSkip {
+        /// Level of the previous heading
+        from: u8,
+        /// Level of this heading
+        to: u8,
+    },
+    /// A link whose text is a generic phrase such as "here", or its URL
+    NonDescriptiveLinkText(EcoString),
+}
+
+/// A problem found by [`a11y`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct A11yIssue {
+    /// What is wrong
+    pub kind: A11yIssueKind,
+    /// Path of the offending node, relative to the checked root
+    pub path: NodePath,
+}
+
+impl fmt::Display for A11yIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            A11yIssueKind::MissingAltText => write!(f, "image without alt text at {}", self.path),
+            A11yIssueKind::TableWithoutHeaders => {
+                write!(f, "table without headers at {}", self.path)
+            }
+            A11yIssueKind::HeadingLevelSkip { from, to } => write!(
+                f,
+                "heading level {} follows level {} at {}",
+                to, from, self.path
+            ),
+            A11yIssueKind::NonDescriptiveLinkText(text) => write!(
+                f,
+                "non-descriptive link text {:?} at {}",
+                text.as_str(),
+                self.path
+            ),
+        }
+    }
+}
+
+/// Check a document for common accessibility problems
+///
+/// Reports, in document order:
+///
+/// - images without alt text,
+/// - tables whose header cells are all empty,
+/// - headings that skip a level (an `h4` right after an `h2`); going back up
+///   any number of levels is fine,
+/// - links whose text is a generic phrase like "here" or "read more", or just
+///   their URL. Autolinks are left alone, since showing the URL is their point.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::lint::{a11y, A11yIssueKind};
+///
+/// let doc = Node::Document(vec![
+///     Node::heading(1, vec![Node::Text("Guide".into())]),
+///     Node::heading(3, vec![Node::Text("Details".into())]),
+///     Node::Paragraph(vec![Node::Link {
+///         url: "/install".into(),
+///         title: None,
+///         content: vec![Node::Text("here".into())],
+///     }]),
+/// ]);
+///
+/// let issues = a11y(&doc);
+/// assert_eq!(issues.len(), 2);
+/// assert_eq!(issues[0].kind, A11yIssueKind::HeadingLevelSkip { from: 1, to: 3 });
+/// assert_eq!(
+///     issues[1].kind,
+///     A11yIssueKind::NonDescriptiveLinkText("here".into())
+/// );
+/// ```
+pub fn a11y(root: &Node) -> Vec<A11yIssue> {
+    let mut issues = Vec::new();
+    let mut previous_level = None;
+
+    root.walk_with_paths(|path, node| {
+        let kind = match node {
+            Node::Image { alt, .. } if is_blank(alt) => Some(A11yIssueKind::MissingAltText),
+            Node::HtmlElement(element)
+                if element.tag.eq_ignore_ascii_case("img")
+                    && element
+                        .get_attribute("alt")
+                        .map_or(true, |alt| alt.trim().is_empty()) =>
+            {
+                Some(A11yIssueKind::MissingAltText)
+            }
+            Node::Table { headers, .. } if is_blank(headers) => {
+                Some(A11yIssueKind::TableWithoutHeaders)
+            }
+            Node::Heading { level, .. } => {
+                let skip = previous_level
+                    .filter(|&from| *level > from + 1)
+                    .map(|from| A11yIssueKind::HeadingLevelSkip { from, to: *level });
+                previous_level = Some(*level);
+                skip
+            }
+            Node::Link { url, .. } => link_issue(node, Some(url)),
+            // The text of a shortcut reference is its label, so only generic
+            // phrases count
+            Node::ReferenceLink { .. } => link_issue(node, None),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            issues.push(A11yIssue {
+                kind,
+                path: path.clone(),
+            });
+        }
+    });
+    issues
+}
+
+fn is_blank(nodes: &[Node]) -> bool {
+    nodes.iter().all(|node| node.plain_text().trim().is_empty())
+}
+
+fn link_issue(link: &Node, url: Option<&str>) -> Option<A11yIssueKind> {
+    let text = link.plain_text();
+    is_non_descriptive(&text, url)
+        .then(|| A11yIssueKind::NonDescriptiveLinkText(text.trim().into()))
+}
+
+/// Whether `text` says nothing about the link to `url`
+fn is_non_descriptive(text: &str, url: Option<&str>) -> bool {
+    let text = text
+        .trim()
+        .trim_end_matches(['.', ':', '!', '>', '»', '→'])
+        .trim();
+    if url.is_some_and(|url| text.eq_ignore_ascii_case(url.trim())) || looks_like_url(text) {
+        return true;
+    }
+    let text = text.to_lowercase();
+    NON_DESCRIPTIVE_LINK_TEXTS.contains(&text.as_str())
+}
+
+fn looks_like_url(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    ["http://", "https://", "www."]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+        && !text.contains(char::is_whitespace)
+}
@@ -0,0 +1,142 @@
+//! Numbering of figures, tables and other items for cross-references.
+//!
+//! A [`Numbering`] registry holds one [`Counter`] per kind of item. Numbers are
+//! assigned to item ids (in the order the items appear, typically while the
+//! document is generated), and [`Node::NumberRef`](crate::ast::Node::NumberRef)
+//! nodes are written as the resulting text, e.g. "Figure 3". Each writer
+//! carries a registry, reachable through `numbering()` and `numbering_mut()`.
+
+use ecow::EcoString;
+use std::collections::BTreeMap;
+
+/// Numbers assigned to the items of one kind, starting at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter {
+    /// Word written before the number, e.g. `"Figure"`
+    pub label: EcoString,
+    /// Ids in order of assignment; the number of an id is its index plus one
+    ids: Vec<EcoString>,
+}
+
+impl Counter {
+    /// Create a counter whose references read `"<label> <number>"`
+    pub fn new<S: Into<EcoString>>(label: S) -> Self {
+        Self {
+            label: label.into(),
+            ids: Vec::new(),
+        }
+    }
+
+    /// Number `id`, returning its existing number if it already has one
+    pub fn assign(&mut self, id: &str) -> u32 {
+        if let Some(number) = self.get(id) {
+            return number;
+        }
+        self.ids.push(id.into());
+        self.ids.len() as u32
+    }
+
+    /// The number of `id`, if it has been assigned one
+    pub fn get(&self, id: &str) -> Option<u32> {
+        self.ids
+            .iter()
+            .position(|assigned| assigned == id)
+            .map(|index| index as u32 + 1)
+    }
+
+    /// Number of items counted so far
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether no item has been counted yet
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// Counters by kind, consulted when writing `Node::NumberRef`
+///
+/// The default registry has the kinds `figure`, `table` and `equation`,
+/// labelled "Figure", "Table" and "Equation".
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::numbering::Numbering;
+///
+/// let mut numbering = Numbering::default().with_counter("listing", "Listing");
+/// numbering.assign("figure", "architecture");
+/// numbering.assign("figure", "results");
+/// numbering.assign("listing", "main");
+///
+/// assert_eq!(numbering.reference_text("figure", "results").unwrap(), "Figure 2");
+/// assert_eq!(numbering.reference_text("listing", "main").unwrap(), "Listing 1");
+/// assert_eq!(numbering.reference_text("table", "missing"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Numbering {
+    counters: BTreeMap<EcoString, Counter>,
+}
+
+impl Default for Numbering {
+    fn default() -> Self {
+        Self::empty()
+            .with_counter("figure", "Figure")
+            .with_counter("table", "Table")
+            .with_counter("equation", "Equation")
+    }
+}
+
+impl Numbering {
+    /// A registry without any kinds
+    pub fn empty() -> Self {
+        Self {
+            counters: BTreeMap::new(),
+        }
+    }
+
+    /// Add a kind of item, or relabel an existing one keeping its numbers
+    pub fn with_counter<K: Into<EcoString>, L: Into<EcoString>>(
+        mut self,
+        kind: K,
+        label: L,
+    ) -> Self {
+        let label = label.into();
+        self.counters
+            .entry(kind.into())
+            .and_modify(|counter| counter.label = label.clone())
+            .or_insert_with(|| Counter::new(label));
+        self
+    }
+
+    /// The counter for `kind`
+    pub fn counter(&self, kind: &str) -> Option<&Counter> {
+        self.counters.get(kind)
+    }
+
+    /// Number item `id` of `kind`, returning its number
+    ///
+    /// Returns `None` if the kind isn't registered.
+    pub fn assign(&mut self, kind: &str, id: &str) -> Option<u32> {
+        self.counters
+            .get_mut(kind)
+            .map(|counter| counter.assign(id))
+    }
+
+    /// The number of item `id` of `kind`, if assigned
+    pub fn get(&self, kind: &str, id: &str) -> Option<u32> {
+        self.counter(kind)?.get(id)
+    }
+
+    /// The text a reference to item `id` of `kind` is written as, e.g. "Figure 3"
+    pub fn reference_text(&self, kind: &str, id: &str) -> Option<EcoString> {
+        let counter = self.counter(kind)?;
+        let number = counter.get(id)?;
+        Some(if counter.label.is_empty() {
+            number.to_string().into()
+        } else {
+            format!("{} {}", counter.label, number).into()
+        })
+    }
+}
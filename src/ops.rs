@@ -0,0 +1,1174 @@
+//! Whole-document transformations on the AST.
+//!
+//! Most of these functions take a document and return new documents, leaving
+//! the input untouched; [`merge_adjacent_paragraphs`], [`split_paragraph_at`]
+//! and [`retitle_headings`] edit a document in place.
+
+use crate::ast::{
+    list_item_content_mut, make_shared_mut, normalize_label, slugify_with, ListItem, Node,
+    NodePath, PathSegment, SlugOptions, Slugger,
+};
+use crate::diagnostics;
+use ecow::EcoString;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Split a document into one document per section
+///
+/// A section starts at every heading whose level is `level` or lower (i.e. an
+/// `h1` also ends an `h2` section) and runs until the next such heading. The
+/// title of each section is the plain text of its heading. Content before the
+/// first heading is kept at the start of the first section.
+///
+/// Link reference definitions used by a section but located in another one are
+/// copied to the end of the section, so every section renders its reference
+/// links on its own. Labels are matched after [`normalize_label`], so `[Foo]`
+/// pulls in a definition of `[foo]`, and a section gets at most one definition
+/// per normalized label.
+///
+/// Sections of a [`Node::ConfiguredDocument`] keep its option overrides.
+///
+/// The sections are copies, and custom nodes can't be copied: they are left
+/// out with a warning. Wrap them in a [`Node::Shared`] subtree (e.g. with
+/// [`Node::shared`]) to keep them, since shared subtrees are shared by the
+/// sections rather than copied.
+///
+/// A document without matching headings is returned as a single section with
+/// an empty title; an empty document yields no sections.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::ops::split_by_heading;
+///
+/// let doc = Node::Document(vec![
+///     Node::Paragraph(vec![Node::Text("Prelude".into())]),
+///     Node::heading(1, vec![Node::Text("One".into())]),
+///     Node::heading(1, vec![Node::Text("Two".into())]),
+///     Node::Paragraph(vec![Node::Text("Body".into())]),
+/// ]);
+///
+/// let sections = split_by_heading(&doc, 1);
+/// let titles: Vec<_> = sections.iter().map(|(title, _)| title.as_str()).collect();
+/// assert_eq!(titles, ["One", "Two"]);
+/// ```
+pub fn split_by_heading(root: &Node, level: u8) -> Vec<(EcoString, Node)> {
+    let (children, overrides) = match root.resolve_shared() {
+        Node::Document(children) => (children.as_slice(), None),
+        Node::ConfiguredDocument { options, children } => (children.as_slice(), Some(options)),
+        node => (std::slice::from_ref(node), None),
+    };
+    if children.is_empty() {
+        return Vec::new();
+    }
+
+    let starts_section = |node: &Node| match node.resolve_shared() {
+        Node::Heading { level: l, .. } => *l <= level,
+        _ => false,
+    };
+
+    let mut sections: Vec<(EcoString, Vec<Node>)> = Vec::new();
+    let mut prelude = Vec::new();
+    for child in children {
+        if starts_section(child) {
+            let mut content = std::mem::take(&mut prelude);
+            content.extend(child.clone_lossy());
+            sections.push((child.plain_text(), content));
+        } else if let Some((_, content)) = sections.last_mut() {
+            content.extend(child.clone_lossy());
+        } else {
+            prelude.extend(child.clone_lossy());
+        }
+    }
+    if sections.is_empty() {
+        sections.push((EcoString::new(), prelude));
+    }
+
+    let mut definitions: Vec<&Node> = Vec::new();
+    for child in children {
+        child.walk_with_paths(|_, node| {
+            if matches!(node, Node::LinkReferenceDefinition { .. }) {
+                definitions.push(node);
+            }
+        });
+    }
+
+    sections
+        .into_iter()
+        .map(|(title, mut content)| {
+            let mut used = HashSet::new();
+            let mut defined = HashSet::new();
+            for node in &content {
+                node.walk_with_paths(|_, node| match node {
+                    Node::ReferenceLink { label, .. } => {
+                        used.insert(normalize_label(label));
+                    }
+                    Node::LinkReferenceDefinition { label, .. } => {
+                        defined.insert(normalize_label(label));
+                    }
+                    _ => {}
+                });
+            }
+            for definition in &definitions {
+                if let Node::LinkReferenceDefinition { label, .. } = definition {
+                    let label = normalize_label(label);
+                    if used.contains(&label) && defined.insert(label) {
+                        content.extend(definition.clone_lossy());
+                    }
+                }
+            }
+            // Sections keep the option overrides of the document they came from
+            let section = match overrides {
+                Some(options) => Node::ConfiguredDocument {
+                    options: options.clone(),
+                    children: content,
+                },
+                None => Node::Document(content),
+            };
+            (title, section)
+        })
+        .collect()
+}
+
+/// The HTML comment marking the end of a summary, as written for
+/// [`Node::ReadMoreMarker`]
+pub const READ_MORE_MARKER: &str = "<!-- more -->";
+
+/// Whether `node` marks the end of a summary: a [`Node::ReadMoreMarker`], or
+/// an HTML block holding just `<!-- more -->` (spacing and case don't matter)
+pub fn is_read_more_marker(node: &Node) -> bool {
+    match node.resolve_shared() {
+        Node::ReadMoreMarker => true,
+        Node::HtmlBlock(html) => html
+            .trim()
+            .strip_prefix("<!--")
+            .and_then(|rest| rest.strip_suffix("-->"))
+            .is_some_and(|text| text.trim().eq_ignore_ascii_case("more")),
+        _ => false,
+    }
+}
+
+/// What [`truncate_document`] keeps of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncateLimits {
+    /// Keep at most this many top-level blocks, headings included
+    pub max_blocks: Option<usize>,
+    /// Keep only the headings and the first paragraph after each of them (and
+    /// the first paragraph before the first heading)
+    pub first_paragraph_per_section: bool,
+    /// Stop at the first top-level read-more marker (see [`is_read_more_marker`])
+    pub stop_at_marker: bool,
+}
+
+impl Default for TruncateLimits {
+    fn default() -> Self {
+        Self {
+            max_blocks: None,
+            first_paragraph_per_section: false,
+            stop_at_marker: true,
+        }
+    }
+}
+
+impl TruncateLimits {
+    /// Stop at a read-more marker, with no other limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of top-level blocks to keep
+    pub fn with_max_blocks(mut self, max_blocks: usize) -> Self {
+        self.max_blocks = Some(max_blocks);
+        self
+    }
+
+    /// Set whether only the first paragraph of each section is kept
+    pub fn with_first_paragraph_per_section(mut self, enabled: bool) -> Self {
+        self.first_paragraph_per_section = enabled;
+        self
+    }
+
+    /// Set whether the preview ends at a read-more marker
+    pub fn with_stop_at_marker(mut self, enabled: bool) -> Self {
+        self.stop_at_marker = enabled;
+        self
+    }
+}
+
+/// Build a preview of a document, e.g. for a blog index
+///
+/// Top-level blocks are kept in order until the first read-more marker, and
+/// within the further `limits`. Read-more markers themselves are always left
+/// out. Link reference and footnote definitions don't count as blocks: the
+/// ones used by the kept content are moved to the end of the preview, the
+/// others are dropped.
+///
+/// A [`Node::ConfiguredDocument`] keeps its option overrides; any other node
+/// than a document is treated as a document holding just that node. Custom
+/// nodes can't be copied and are left out of the preview, unless they are
+/// inside a [`Node::Shared`] subtree.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::ops::{truncate_document, TruncateLimits};
+///
+/// let doc = Node::Document(vec![
+///     Node::Paragraph(vec![Node::Text("Teaser".into())]),
+///     Node::ReadMoreMarker,
+///     Node::Paragraph(vec![Node::Text("The rest".into())]),
+/// ]);
+/// let preview = truncate_document(&doc, &TruncateLimits::new());
+/// assert_eq!(preview.to_string(), "Teaser\n");
+/// ```
+pub fn truncate_document(root: &Node, limits: &TruncateLimits) -> Node {
+    let (children, overrides) = match root.resolve_shared() {
+        Node::Document(children) => (children.as_slice(), None),
+        Node::ConfiguredDocument { options, children } => (children.as_slice(), Some(options)),
+        node => (std::slice::from_ref(node), None),
+    };
+
+    let mut kept = Vec::new();
+    let mut section_has_paragraph = false;
+    for child in children {
+        if is_read_more_marker(child) {
+            if limits.stop_at_marker {
+                break;
+            }
+            continue;
+        }
+        let node = child.resolve_shared();
+        if is_definition(node) {
+            continue;
+        }
+        if limits.first_paragraph_per_section {
+            match node {
+                Node::Heading { .. } => section_has_paragraph = false,
+                Node::Paragraph(_) if !section_has_paragraph => section_has_paragraph = true,
+                _ => continue,
+            }
+        }
+        if limits.max_blocks.is_some_and(|max| kept.len() >= max) {
+            break;
+        }
+        kept.extend(child.clone_lossy());
+    }
+
+    // Definitions the kept content uses, unless it holds them itself
+    let mut used = HashSet::new();
+    let mut defined = HashSet::new();
+    for node in &kept {
+        node.walk_with_paths(|_, node| {
+            if let Some(key) = reference_key(node) {
+                used.insert(key);
+            } else if let Some(key) = definition_key(node) {
+                defined.insert(key);
+            }
+        });
+    }
+    for child in children {
+        child.walk_with_paths(|_, node| {
+            if let Some(key) = definition_key(node) {
+                if used.contains(&key) && defined.insert(key) {
+                    kept.extend(node.clone_lossy());
+                }
+            }
+        });
+    }
+
+    match overrides {
+        Some(options) => Node::ConfiguredDocument {
+            options: options.clone(),
+            children: kept,
+        },
+        None => Node::Document(kept),
+    }
+}
+
+fn is_definition(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::LinkReferenceDefinition { .. } | Node::FootnoteDefinition { .. }
+    )
+}
+
+/// Normalized label of a reference link or footnote reference, telling the two
+/// apart
+fn reference_key(node: &Node) -> Option<(bool, EcoString)> {
+    match node {
+        Node::ReferenceLink { label, .. } => Some((false, normalize_label(label))),
+        Node::FootnoteReference(label) => Some((true, normalize_label(label))),
+        _ => None,
+    }
+}
+
+/// Normalized label of a link reference or footnote definition, as
+/// [`reference_key`]
+fn definition_key(node: &Node) -> Option<(bool, EcoString)> {
+    match node {
+        Node::LinkReferenceDefinition { label, .. } => Some((false, normalize_label(label))),
+        Node::FootnoteDefinition { label, .. } => Some((true, normalize_label(label))),
+        _ => None,
+    }
+}
+
+/// Merge runs of adjacent paragraphs into single paragraphs
+///
+/// Paragraphs that directly follow each other in a document, block quote, list
+/// item or HTML element become one paragraph, with a soft break where each
+/// paragraph ended. The inline nodes themselves are moved as they are, so the
+/// text nodes on either side of a join stay separate. Shared subtrees are
+/// copied before they are changed, except those shared elsewhere that hold a
+/// custom node, which can't be copied and are left unmerged.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::ops::merge_adjacent_paragraphs;
+///
+/// let mut doc = Node::Document(vec![
+///     Node::Paragraph(vec![Node::Text("one".into())]),
+///     Node::Paragraph(vec![Node::Text("two".into())]),
+/// ]);
+/// merge_adjacent_paragraphs(&mut doc);
+/// assert_eq!(doc.to_string(), "one\ntwo\n");
+/// ```
+pub fn merge_adjacent_paragraphs(root: &mut Node) {
+    merge_paragraphs_in(root);
+}
+
+fn merge_paragraphs_in(node: &mut Node) {
+    match node {
+        Node::Document(blocks)
+        | Node::ConfiguredDocument {
+            children: blocks, ..
+        }
+        | Node::BlockQuote(blocks)
+        | Node::FootnoteDefinition {
+            content: blocks, ..
+        } => merge_paragraph_runs(blocks),
+        Node::UnorderedList(items) | Node::OrderedList { items, .. } => {
+            for item in items {
+                merge_paragraph_runs(list_item_content_mut(item));
+            }
+        }
+        Node::DefinitionList(items) => {
+            for definition in items.iter_mut().flat_map(|item| &mut item.definitions) {
+                merge_paragraph_runs(definition);
+            }
+        }
+        Node::HtmlElement(element) => merge_paragraph_runs(&mut element.children),
+        Node::Shared(inner) => {
+            if let Some(inner) = make_shared_mut(inner) {
+                merge_paragraphs_in(inner);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn merge_paragraph_runs(blocks: &mut Vec<Node>) {
+    let mut merged: Vec<Node> = Vec::with_capacity(blocks.len());
+    for mut block in blocks.drain(..) {
+        merge_paragraphs_in(&mut block);
+        let joins = matches!(
+            (merged.last(), &block),
+            (Some(Node::Paragraph(_)), Node::Paragraph(_))
+        );
+        match (merged.last_mut(), block) {
+            (Some(Node::Paragraph(previous)), Node::Paragraph(content)) if joins => {
+                if !previous.is_empty() && !content.is_empty() {
+                    previous.push(Node::SoftBreak);
+                }
+                previous.extend(content);
+            }
+            (_, block) => merged.push(block),
+        }
+    }
+    *blocks = merged;
+}
+
+/// Split the paragraph at `path` in two, at byte `offset` of its plain text
+///
+/// Offsets count the bytes of [`Node::plain_text`], the text editors show: a
+/// soft or hard break counts as one byte and markup counts as nothing. The
+/// inline nodes around the offset are split so that both halves keep their
+/// formatting (splitting inside `**bold**` gives two strong nodes), and the
+/// line breaks at the split point are dropped. The second half is inserted as
+/// a new paragraph right after the first.
+///
+/// Returns the path of the new paragraph, or `None` (leaving the document
+/// unchanged) if `path` doesn't address a paragraph in a list of blocks, or if
+/// `offset` is past the end of the text, not on a character boundary, or
+/// inside a node that can't be split (images, autolinks, HTML elements and
+/// custom nodes). Nodes are moved rather than copied, but splitting inside a
+/// [`Node::Shared`] node copies it if it is shared elsewhere, so that fails
+/// too if it holds a custom node.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::{Node, NodePath};
+/// use cmark_writer::ops::split_paragraph_at;
+///
+/// let mut doc = Node::Document(vec![Node::Paragraph(vec![
+///     Node::Text("Hello ".into()),
+///     Node::Strong(vec![Node::Text("world".into())]),
+/// ])]);
+///
+/// let new = split_paragraph_at(&mut doc, &NodePath::root().child(0), 8).unwrap();
+/// assert_eq!(new, NodePath::root().child(1));
+/// assert_eq!(doc.to_string(), "Hello **wo**\n\n**rld**\n");
+/// ```
+pub fn split_paragraph_at(root: &mut Node, path: &NodePath, offset: usize) -> Option<NodePath> {
+    let (&segment, parent) = path.segments().split_last()?;
+    let Node::Paragraph(content) = root.get(path)?.resolve_shared() else {
+        return None;
+    };
+    if !can_split_inlines(content, offset) {
+        return None;
+    }
+    let next = match segment {
+        PathSegment::Child(index) => PathSegment::Child(index + 1),
+        PathSegment::ListItem { item, child } => PathSegment::ListItem {
+            item,
+            child: child + 1,
+        },
+        PathSegment::Definition {
+            item,
+            definition,
+            child,
+        } => PathSegment::Definition {
+            item,
+            definition,
+            child: child + 1,
+        },
+        _ => return None,
+    };
+
+    let parent: NodePath = parent.iter().copied().collect();
+    let (siblings, index) = root.get_mut(&parent)?.sibling_list_mut(segment)?;
+    let mut paragraph = &mut siblings[index];
+    while let Node::Shared(inner) = paragraph {
+        paragraph = make_shared_mut(inner)?;
+    }
+    let Node::Paragraph(content) = paragraph else {
+        return None;
+    };
+    // The nodes are moved into the halves, since custom nodes can't be cloned
+    let (first, second) = split_inlines(std::mem::take(content), offset);
+    siblings[index] = Node::Paragraph(first);
+    siblings.insert(index + 1, Node::Paragraph(second));
+    Some(parent.with(next))
+}
+
+/// Whether [`split_inlines`] can split `nodes` at byte `offset` of their plain
+/// text
+fn can_split_inlines(nodes: &[Node], offset: usize) -> bool {
+    let mut remaining = offset;
+    for node in nodes {
+        let len = node.plain_text().len();
+        if remaining >= len {
+            remaining -= len;
+            continue;
+        }
+        return remaining == 0 || can_split_inline(node, remaining);
+    }
+    remaining == 0
+}
+
+/// Whether [`split_inline`] can split `node` at byte `offset`, strictly inside it
+fn can_split_inline(node: &Node, offset: usize) -> bool {
+    match node {
+        // Splitting takes a shared node apart, which copies it if it is
+        // shared elsewhere
+        Node::Shared(inner) => {
+            (Arc::strong_count(inner) == 1 || !inner.has_custom())
+                && can_split_inline(inner, offset)
+        }
+        Node::Text(text) | Node::InlineCode(text) => text.is_char_boundary(offset),
+        Node::Emphasis(children)
+        | Node::Strong(children)
+        | Node::Strikethrough(children)
+        | Node::Link {
+            content: children, ..
+        }
+        | Node::CrossRef {
+            text: Some(children),
+            ..
+        }
+        | Node::ReferenceLink {
+            content: children, ..
+        } => can_split_inlines(children, offset),
+        _ => false,
+    }
+}
+
+/// Split inline nodes at byte `offset` of their plain text, as checked by
+/// [`can_split_inlines`]
+fn split_inlines(nodes: Vec<Node>, offset: usize) -> (Vec<Node>, Vec<Node>) {
+    let mut first = Vec::new();
+    let mut second = Vec::new();
+    let mut remaining = offset;
+    let mut nodes = nodes.into_iter();
+    for node in nodes.by_ref() {
+        let len = node.plain_text().len();
+        if remaining >= len {
+            first.push(node);
+            remaining -= len;
+            continue;
+        }
+        if remaining > 0 {
+            let (head, tail) = split_inline(node, remaining);
+            first.push(head);
+            second.push(tail);
+        } else {
+            second.push(node);
+        }
+        break;
+    }
+    second.extend(nodes);
+
+    let is_break = |node: &Node| matches!(node, Node::SoftBreak | Node::HardBreak);
+    while first.last().is_some_and(is_break) {
+        first.pop();
+    }
+    let leading_breaks = second.iter().take_while(|node| is_break(node)).count();
+    second.drain(..leading_breaks);
+    (first, second)
+}
+
+/// Split one inline node at byte `offset` of its plain text, strictly inside
+/// it, as checked by [`can_split_inline`]
+fn split_inline(node: Node, offset: usize) -> (Node, Node) {
+    let split_children = |children: Vec<Node>, wrap: fn(Vec<Node>) -> Node| {
+        let (head, tail) = split_inlines(children, offset);
+        (wrap(head), wrap(tail))
+    };
+    match node {
+        Node::Shared(inner) => split_inline(
+            Arc::try_unwrap(inner).unwrap_or_else(|inner| (*inner).clone()),
+            offset,
+        ),
+        Node::Text(text) => {
+            let (head, tail) = text.split_at(offset);
+            (Node::Text(head.into()), Node::Text(tail.into()))
+        }
+        Node::InlineCode(code) => {
+            let (head, tail) = code.split_at(offset);
+            (Node::InlineCode(head.into()), Node::InlineCode(tail.into()))
+        }
+        Node::Emphasis(children) => split_children(children, Node::Emphasis),
+        Node::Strong(children) => split_children(children, Node::Strong),
+        Node::Strikethrough(children) => split_children(children, Node::Strikethrough),
+        Node::Link {
+            url,
+            title,
+            content,
+        } => {
+            let (head, tail) = split_inlines(content, offset);
+            let head = Node::Link {
+                url: url.clone(),
+                title: title.clone(),
+                content: head,
+            };
+            (
+                head,
+                Node::Link {
+                    url,
+                    title,
+                    content: tail,
+                },
+            )
+        }
+        Node::CrossRef {
+            target,
+            text: Some(content),
+        } => {
+            let (head, tail) = split_inlines(content, offset);
+            let head = Node::CrossRef {
+                target: target.clone(),
+                text: Some(head),
+            };
+            (
+                head,
+                Node::CrossRef {
+                    target,
+                    text: Some(tail),
+                },
+            )
+        }
+        Node::ReferenceLink { label, content } => {
+            let (head, tail) = split_inlines(content, offset);
+            let head = Node::ReferenceLink {
+                label: label.clone(),
+                content: head,
+            };
+            (
+                head,
+                Node::ReferenceLink {
+                    label,
+                    content: tail,
+                },
+            )
+        }
+        _ => unreachable!("rejected by can_split_inline"),
+    }
+}
+
+/// Build a table of contents for the headings of a document
+///
+/// Returns an unordered list with one item per heading, in document order,
+/// linking to the heading's explicit id or else to its slug as generated by
+/// [`Slugger`] (GitHub's anchors). Deeper headings are nested under the closest
+/// preceding shallower heading. A document without headings gives an empty
+/// list.
+///
+/// The writers insert this list in place of a paragraph that consists of
+/// their `toc_marker`, e.g. `[[TOC]]`.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::ops::table_of_contents;
+///
+/// let doc = Node::Document(vec![
+///     Node::heading(1, vec![Node::Text("Guide".into())]),
+///     Node::heading(2, vec![Node::Text("Install".into())]),
+///     Node::heading(2, vec![Node::Text("Usage".into())]),
+/// ]);
+/// assert_eq!(
+///     table_of_contents(&doc).to_string(),
+///     "- [Guide](#guide)\n  - [Install](#install)\n  - [Usage](#usage)\n"
+/// );
+/// ```
+pub fn table_of_contents(root: &Node) -> Node {
+    let mut slugger = Slugger::new();
+    let mut entries = Vec::new();
+    root.walk_with_paths(|_, node| {
+        if let Node::Heading {
+            level, custom_id, ..
+        } = node
+        {
+            let text = node.plain_text();
+            let anchor = match custom_id {
+                Some(id) => id.clone(),
+                None => slugger.slug(&text),
+            };
+            let link = Node::Link {
+                url: format!("#{}", anchor).into(),
+                title: None,
+                content: vec![Node::Text(text)],
+            };
+            entries.push((*level, link));
+        }
+    });
+    Node::UnorderedList(toc_items(&entries))
+}
+
+/// Whether a paragraph with `content` consists of the table of contents `marker`
+pub(crate) fn is_toc_marker(content: &[Node], marker: &str) -> bool {
+    matches!(content, [node] if matches!(node.resolve_shared(), Node::Text(text) if text.trim() == marker))
+}
+
+/// Whether a paragraph below `root` consists of the table of contents `marker`
+pub(crate) fn contains_toc_marker(root: &Node, marker: &str) -> bool {
+    let mut found = false;
+    root.walk_with_paths(|_, node| {
+        found |= matches!(node, Node::Paragraph(content) if is_toc_marker(content, marker));
+    });
+    found
+}
+
+/// List items for `entries`, nesting each entry's deeper successors under it
+fn toc_items(entries: &[(u8, Node)]) -> Vec<ListItem> {
+    let mut items = Vec::new();
+    let mut index = 0;
+    while index < entries.len() {
+        let (level, link) = &entries[index];
+        let end = entries[index + 1..]
+            .iter()
+            .position(|(next, _)| next <= level)
+            .map_or(entries.len(), |offset| index + 1 + offset);
+        let mut content = vec![link.clone()];
+        if end > index + 1 {
+            content.push(Node::UnorderedList(toc_items(&entries[index + 1..end])));
+        }
+        items.push(ListItem::Unordered { content });
+        index = end;
+    }
+    items
+}
+
+/// A heading that [`Node::CrossRef`] can link to, see [`HeadingAnchors`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadingAnchor {
+    /// The heading's explicit id, or else its slug, unique in the document
+    pub anchor: EcoString,
+    /// Plain text of the heading
+    pub text: EcoString,
+    /// Slug of the text, before a suffix is added to duplicates
+    base_slug: EcoString,
+}
+
+/// Why a cross-reference target doesn't name a single heading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnresolvedCrossRef {
+    /// No heading has this id, slug or text
+    Missing,
+    /// Several headings have this text; their anchors, in document order
+    Ambiguous(Vec<EcoString>),
+}
+
+/// The anchors of the headings of a document, for resolving [`Node::CrossRef`]
+///
+/// Anchors are the same as the links of [`table_of_contents`]: a heading's
+/// explicit id, or else its slug as generated by [`Slugger`], so the second
+/// "Usage" heading is `usage-1`. The writers build this for the outermost
+/// document they write.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::ops::{HeadingAnchors, UnresolvedCrossRef};
+///
+/// let doc = Node::Document(vec![
+///     Node::heading(2, vec![Node::Text("Usage".into())]),
+///     Node::heading(2, vec![Node::Text("Usage".into())]),
+///     Node::heading(2, vec![Node::Text("Getting Started".into())]),
+/// ]);
+/// let anchors = HeadingAnchors::new(&doc);
+/// assert_eq!(anchors.resolve("Getting Started").unwrap().anchor, "getting-started");
+/// assert_eq!(anchors.resolve("usage-1").unwrap().anchor, "usage-1");
+/// assert_eq!(
+///     anchors.resolve("Usage"),
+///     Err(UnresolvedCrossRef::Ambiguous(vec!["usage".into(), "usage-1".into()]))
+/// );
+/// assert_eq!(anchors.resolve("Missing"), Err(UnresolvedCrossRef::Missing));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HeadingAnchors {
+    headings: Vec<HeadingAnchor>,
+    /// Anchors that a cross-reference of the document resolves to
+    referenced: HashSet<EcoString>,
+}
+
+impl HeadingAnchors {
+    /// Collect the anchors of the headings of `root`
+    pub fn new(root: &Node) -> Self {
+        let mut slugger = Slugger::new();
+        let mut headings = Vec::new();
+        let mut targets = Vec::new();
+        root.walk_with_paths(|_, node| match node {
+            Node::Heading { custom_id, .. } => {
+                let text = node.plain_text();
+                let anchor = match custom_id {
+                    Some(id) => id.clone(),
+                    None => slugger.slug(&text),
+                };
+                headings.push(HeadingAnchor {
+                    anchor,
+                    base_slug: slugify_with(&text, slugger.options()),
+                    text,
+                });
+            }
+            Node::CrossRef { target, .. } => targets.push(target.clone()),
+            _ => {}
+        });
+
+        let mut anchors = Self {
+            headings,
+            referenced: HashSet::new(),
+        };
+        anchors.referenced = targets
+            .iter()
+            .filter_map(|target| anchors.resolve_lenient(target))
+            .map(|heading| heading.anchor.clone())
+            .collect();
+        anchors
+    }
+
+    /// The headings, in document order
+    pub fn headings(&self) -> &[HeadingAnchor] {
+        &self.headings
+    }
+
+    /// The heading `target` refers to
+    ///
+    /// `target` is matched against the anchors first, then against the slugs
+    /// of the heading texts, so "Getting Started" and "getting-started" both
+    /// name the same heading. A text shared by several headings is ambiguous
+    /// unless one of them is named by its anchor.
+    pub fn resolve(&self, target: &str) -> Result<&HeadingAnchor, UnresolvedCrossRef> {
+        if let Some(heading) = self
+            .headings
+            .iter()
+            .find(|heading| heading.anchor == target)
+        {
+            return Ok(heading);
+        }
+        let slug = slugify_with(target, &SlugOptions::default());
+        let mut matches = self
+            .headings
+            .iter()
+            .filter(|heading| !slug.is_empty() && heading.base_slug == slug);
+        match (matches.next(), matches.next()) {
+            (Some(heading), None) => Ok(heading),
+            (Some(first), Some(second)) => Err(UnresolvedCrossRef::Ambiguous(
+                [first, second]
+                    .into_iter()
+                    .chain(matches)
+                    .map(|heading| heading.anchor.clone())
+                    .collect(),
+            )),
+            (None, _) => Err(UnresolvedCrossRef::Missing),
+        }
+    }
+
+    /// Whether a cross-reference of the document links to `anchor`
+    pub fn is_referenced(&self, anchor: &str) -> bool {
+        self.referenced.contains(anchor)
+    }
+
+    /// Count every heading as linked to, as the table of contents of the
+    /// document does
+    pub(crate) fn reference_all(&mut self) {
+        self.referenced = self
+            .headings
+            .iter()
+            .map(|heading| heading.anchor.clone())
+            .collect();
+    }
+
+    /// The heading `target` refers to, taking the first of several headings
+    /// sharing its text
+    pub(crate) fn resolve_lenient(&self, target: &str) -> Option<&HeadingAnchor> {
+        match self.resolve(target) {
+            Ok(heading) => Some(heading),
+            Err(UnresolvedCrossRef::Ambiguous(anchors)) => self
+                .headings
+                .iter()
+                .find(|heading| heading.anchor == anchors[0]),
+            Err(UnresolvedCrossRef::Missing) => None,
+        }
+    }
+}
+
+/// The heading a [`Node::CrossRef`] being written links to
+///
+/// Outside `strict` mode a missing target gives `None` and an ambiguous one
+/// the first of its headings, with a warning; in strict mode both are errors,
+/// returned as their message.
+pub(crate) fn resolve_cross_ref<'a>(
+    anchors: Option<&'a HeadingAnchors>,
+    target: &str,
+    strict: bool,
+) -> Result<Option<&'a HeadingAnchor>, String> {
+    let resolved = match anchors {
+        Some(anchors) => anchors.resolve(target),
+        None => Err(UnresolvedCrossRef::Missing),
+    };
+    match resolved {
+        Ok(heading) => Ok(Some(heading)),
+        Err(UnresolvedCrossRef::Missing) => {
+            let message = format!("Unresolved cross-reference to '{}'", target);
+            if strict {
+                return Err(message);
+            }
+            diagnostics::warn!("{}", message);
+            Ok(None)
+        }
+        Err(UnresolvedCrossRef::Ambiguous(candidates)) => {
+            let message = format!(
+                "Ambiguous cross-reference to '{}', matching #{}",
+                target,
+                candidates.join(", #")
+            );
+            if strict {
+                return Err(message);
+            }
+            diagnostics::warn!("{}", message);
+            Ok(anchors.and_then(|anchors| anchors.resolve_lenient(target)))
+        }
+    }
+}
+
+/// Capitalization applied to headings by [`retitle_headings`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseStyle {
+    /// Capitalize every word, except [`TITLE_CASE_SMALL_WORDS`] that neither
+    /// start nor end the heading nor follow a colon ("Getting Started with the API")
+    Title,
+    /// Capitalize the first word and lowercase the others ("Getting started with the API")
+    Sentence,
+}
+
+/// Words kept lowercase inside a heading by [`CaseStyle::Title`]
+pub const TITLE_CASE_SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of", "off",
+    "on", "onto", "or", "per", "so", "than", "the", "to", "up", "via", "vs", "with", "yet",
+];
+
+/// Rewrite the capitalization of every heading in place
+///
+/// Only text is changed: inline code, links, images, autolinks, HTML and
+/// custom nodes are left as written, though each counts as a word when
+/// deciding which word comes first or last. Words with a capital letter after
+/// their first character (`API`, `iOS`, `JavaScript`) and the pronoun `I` are
+/// kept as they are, so acronyms and product names survive. Sentence case
+/// can't tell other proper nouns from ordinary words and lowercases them.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::ops::{retitle_headings, CaseStyle};
+///
+/// let mut doc = Node::Document(vec![Node::heading(
+///     1,
+///     vec![
+///         Node::Text("configuring the ".into()),
+///         Node::InlineCode("run_all".into()),
+///         Node::Text(" API in depth".into()),
+///     ],
+/// )]);
+///
+/// retitle_headings(&mut doc, CaseStyle::Title);
+/// assert_eq!(doc.to_string(), "# Configuring the `run_all` API in Depth\n");
+///
+/// retitle_headings(&mut doc, CaseStyle::Sentence);
+/// assert_eq!(doc.to_string(), "# Configuring the `run_all` API in depth\n");
+/// ```
+pub fn retitle_headings(root: &mut Node, style: CaseStyle) {
+    match root {
+        Node::Heading { content, .. } => retitle(content, style),
+        Node::Document(blocks)
+        | Node::ConfiguredDocument {
+            children: blocks, ..
+        }
+        | Node::BlockQuote(blocks)
+        | Node::FootnoteDefinition {
+            content: blocks, ..
+        } => {
+            for block in blocks {
+                retitle_headings(block, style);
+            }
+        }
+        Node::UnorderedList(items) | Node::OrderedList { items, .. } => {
+            for block in items.iter_mut().flat_map(list_item_content_mut) {
+                retitle_headings(block, style);
+            }
+        }
+        Node::DefinitionList(items) => {
+            for block in items
+                .iter_mut()
+                .flat_map(|item| &mut item.definitions)
+                .flatten()
+            {
+                retitle_headings(block, style);
+            }
+        }
+        Node::HtmlElement(element) => {
+            for child in &mut element.children {
+                retitle_headings(child, style);
+            }
+        }
+        Node::Shared(inner) => {
+            if let Some(inner) = make_shared_mut(inner) {
+                retitle_headings(inner, style)
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Inline content as seen by [`retitle`]
+enum Piece {
+    /// Text that may be recased
+    Text(EcoString),
+    /// A line break, separating words
+    Break,
+    /// Content that is kept as written and counts as one word
+    Protected,
+}
+
+/// A word of the heading, in the text piece `piece` (if it's text) at `range`
+struct Word {
+    piece: usize,
+    range: std::ops::Range<usize>,
+    text: bool,
+    /// Whether the word continues the previous piece's last word (`foo**bar**`)
+    continuation: bool,
+}
+
+fn retitle(content: &mut [Node], style: CaseStyle) {
+    let mut pieces = Vec::new();
+    collect_pieces(content, &mut pieces);
+
+    let mut words = Vec::new();
+    let mut in_word = false;
+    for (index, piece) in pieces.iter().enumerate() {
+        match piece {
+            Piece::Text(text) => {
+                let mut start = None;
+                for (offset, c) in text.char_indices().chain([(text.len(), ' ')]) {
+                    match (c.is_whitespace(), start) {
+                        (false, None) => start = Some(offset),
+                        (true, Some(word_start)) => {
+                            words.push(Word {
+                                piece: index,
+                                range: word_start..offset,
+                                text: true,
+                                continuation: word_start == 0 && in_word,
+                            });
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+                if !text.is_empty() {
+                    in_word = !text.ends_with(char::is_whitespace);
+                }
+            }
+            Piece::Break => in_word = false,
+            Piece::Protected => {
+                words.push(Word {
+                    piece: index,
+                    range: 0..0,
+                    text: false,
+                    continuation: in_word,
+                });
+                in_word = true;
+            }
+        }
+    }
+
+    let word_text = |word: &Word| match &pieces[word.piece] {
+        Piece::Text(text) if word.text => &text[word.range.clone()],
+        _ => "",
+    };
+    let last = words.iter().rposition(|word| !word.continuation);
+    let mut recased: Vec<Option<String>> = words.iter().map(|_| None).collect();
+    let mut first = true;
+    let mut after_colon = false;
+    for (index, word) in words.iter().enumerate() {
+        if !word.continuation {
+            if word.text {
+                let starts = first || (after_colon && style == CaseStyle::Title);
+                recased[index] = recase(word_text(word), style, starts, Some(index) == last);
+            }
+            first = false;
+        }
+        // For a word split over several nodes, its last piece counts
+        after_colon = word_text(word).ends_with(':');
+    }
+
+    let mut texts: Vec<Option<EcoString>> = pieces
+        .iter()
+        .map(|piece| match piece {
+            Piece::Text(text) => Some(text.clone()),
+            _ => None,
+        })
+        .collect();
+    for (word, recased) in words.iter().zip(recased).rev() {
+        if let (Some(recased), Some(text)) = (recased, &mut texts[word.piece]) {
+            let mut updated = String::with_capacity(text.len());
+            updated.push_str(&text[..word.range.start]);
+            updated.push_str(&recased);
+            updated.push_str(&text[word.range.end..]);
+            *text = updated.into();
+        }
+    }
+    let mut texts = texts.into_iter().flatten();
+    apply_texts(content, &mut texts);
+}
+
+fn collect_pieces(nodes: &[Node], pieces: &mut Vec<Piece>) {
+    for node in nodes {
+        match node.resolve_shared() {
+            Node::Text(text) => pieces.push(Piece::Text(text.clone())),
+            Node::Emphasis(children) | Node::Strong(children) | Node::Strikethrough(children) => {
+                collect_pieces(children, pieces)
+            }
+            Node::SoftBreak | Node::HardBreak => pieces.push(Piece::Break),
+            _ => pieces.push(Piece::Protected),
+        }
+    }
+}
+
+/// Replace the text nodes collected by [`collect_pieces`], in the same order
+fn apply_texts(nodes: &mut [Node], texts: &mut impl Iterator<Item = EcoString>) {
+    for node in nodes {
+        let node = match node {
+            Node::Shared(inner) => match make_shared_mut(inner) {
+                Some(inner) => inner,
+                None => {
+                    // Left as is, but its texts were collected too
+                    let mut skipped = Vec::new();
+                    collect_pieces(std::slice::from_ref(&**inner), &mut skipped);
+                    for piece in skipped {
+                        if let Piece::Text(_) = piece {
+                            texts.next();
+                        }
+                    }
+                    continue;
+                }
+            },
+            node => node,
+        };
+        match node {
+            Node::Text(text) => {
+                if let Some(updated) = texts.next() {
+                    *text = updated;
+                }
+            }
+            Node::Emphasis(children) | Node::Strong(children) | Node::Strikethrough(children) => {
+                apply_texts(children, texts)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The word recased for its position, or `None` if it stays as is
+fn recase(word: &str, style: CaseStyle, first: bool, last: bool) -> Option<String> {
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+    let keep = core.is_empty()
+        || core.chars().skip(1).any(char::is_uppercase)
+        || core == "I"
+        || core.starts_with("I'");
+    if keep {
+        return None;
+    }
+    let recased = match style {
+        CaseStyle::Title
+            if !first
+                && !last
+                && TITLE_CASE_SMALL_WORDS.contains(&core.to_lowercase().as_str()) =>
+        {
+            word.to_lowercase()
+        }
+        CaseStyle::Sentence if !first => word.to_lowercase(),
+        _ => capitalize(word),
+    };
+    (recased != word).then_some(recased)
+}
+
+/// `word` with its first letter in uppercase
+fn capitalize(word: &str) -> String {
+    match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((index, c)) => {
+            let mut capitalized = String::with_capacity(word.len());
+            capitalized.push_str(&word[..index]);
+            capitalized.extend(c.to_uppercase());
+            capitalized.push_str(&word[index + c.len_utf8()..]);
+            capitalized
+        }
+        None => word.to_string(),
+    }
+}
@@ -2,9 +2,355 @@
 //!
 //! This module provides configuration options for the CommonMark writer.
 
+use crate::traits::OutputFilter;
 use crate::writer::html::HtmlWriterOptions;
-#[cfg(feature = "gfm")]
+use crate::writer::{PlaceholderHook, TextDirection};
 use ecow::EcoString;
+use std::borrow::Cow;
+use std::fmt;
+use unicode_width::UnicodeWidthChar;
+
+/// How writers handle containers that have no content
+///
+/// Applies to headings, paragraphs, block quotes, lists, tables without columns,
+/// and emphasis, strong and strikethrough nodes. Empty documents are not affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyContainerPolicy {
+    /// Return an error in strict mode and omit the container otherwise
+    #[default]
+    Auto,
+    /// Skip the container entirely, including any surrounding blank lines
+    Omit,
+    /// Write the shortest valid representation of the empty container
+    /// (e.g. `#` for a heading, `>` for a block quote, or an empty HTML element
+    /// when the format has no syntax for it)
+    Placeholder,
+    /// Always return an error
+    Error,
+}
+
+impl EmptyContainerPolicy {
+    /// Resolve [`EmptyContainerPolicy::Auto`] against the writer's strict mode
+    pub fn resolve(self, strict: bool) -> Self {
+        match self {
+            EmptyContainerPolicy::Auto if strict => EmptyContainerPolicy::Error,
+            EmptyContainerPolicy::Auto => EmptyContainerPolicy::Omit,
+            policy => policy,
+        }
+    }
+}
+
+/// How writers handle control characters in text and code content
+///
+/// Applies to NUL, the byte order mark (U+FEFF), DEL and other C0/C1 control
+/// characters. Tabs, line feeds and carriage returns are always allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlCharPolicy {
+    /// Reject in strict mode and allow otherwise
+    #[default]
+    Auto,
+    /// Write control characters unchanged
+    Allow,
+    /// Remove control characters from the output
+    Strip,
+    /// Return `WriteError::InvalidCharacter` with the character's position
+    Reject,
+}
+
+impl ControlCharPolicy {
+    /// Resolve [`ControlCharPolicy::Auto`] against the writer's strict mode
+    pub fn resolve(self, strict: bool) -> Self {
+        match self {
+            ControlCharPolicy::Auto if strict => ControlCharPolicy::Reject,
+            ControlCharPolicy::Auto => ControlCharPolicy::Allow,
+            policy => policy,
+        }
+    }
+}
+
+/// How tabs in text and code content are written
+///
+/// A tab is as wide as the distance to the next tab stop, so its width depends
+/// on where it ends up: inside a list item, a tab that looked like one space
+/// in the input may push a line into an indented code block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TabPolicy {
+    /// Write tabs unchanged
+    #[default]
+    Preserve,
+    /// Replace each tab with spaces up to the next multiple of the given
+    /// width (at least 1). Columns of prose count from the start of the line
+    /// the text is on, list markers included, and tabs starting a line of
+    /// prose are written as `&#9;` so they can't indent it. Columns of code
+    /// count from the start of the code line or span.
+    ExpandToSpaces(usize),
+}
+
+impl TabPolicy {
+    /// Apply the policy to `text`, whose first character is at display
+    /// column `column`
+    pub fn apply(self, text: &str, column: usize) -> Cow<'_, str> {
+        let TabPolicy::ExpandToSpaces(width) = self else {
+            return Cow::Borrowed(text);
+        };
+        if !text.contains('\t') {
+            return Cow::Borrowed(text);
+        }
+        let width = width.max(1);
+        let mut expanded = String::with_capacity(text.len() + width);
+        let mut column = column;
+        for c in text.chars() {
+            match c {
+                '\t' => {
+                    let spaces = width - column % width;
+                    expanded.extend(std::iter::repeat(' ').take(spaces));
+                    column += spaces;
+                }
+                '\n' => {
+                    expanded.push(c);
+                    column = 0;
+                }
+                _ => {
+                    expanded.push(c);
+                    column += c.width().unwrap_or(0);
+                }
+            }
+        }
+        Cow::Owned(expanded)
+    }
+}
+
+/// How the end of the output is normalized when the writer is consumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FinalNewline {
+    /// End non-empty output with exactly one newline
+    Always,
+    /// Strip all trailing newlines
+    Never,
+    /// Leave the output as written
+    #[default]
+    Preserve,
+}
+
+impl FinalNewline {
+    /// Apply the policy to `output`
+    pub fn apply(self, output: EcoString) -> EcoString {
+        match self {
+            FinalNewline::Preserve => output,
+            FinalNewline::Never => {
+                let trimmed = output.trim_end_matches('\n');
+                if trimmed.len() == output.len() {
+                    output
+                } else {
+                    trimmed.into()
+                }
+            }
+            FinalNewline::Always => {
+                let trimmed = output.trim_end_matches('\n');
+                if trimmed.is_empty() {
+                    EcoString::new()
+                } else if output.len() == trimmed.len() + 1 {
+                    output
+                } else {
+                    let mut result = EcoString::from(trimmed);
+                    result.push('\n');
+                    result
+                }
+            }
+        }
+    }
+}
+
+/// Delimiters used around link, image and link reference definition titles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitleQuote {
+    /// `"title"`
+    #[default]
+    Double,
+    /// `'title'`
+    Single,
+    /// `(title)`
+    Paren,
+}
+
+impl TitleQuote {
+    /// Opening and closing delimiters
+    pub fn delimiters(self) -> (char, char) {
+        match self {
+            TitleQuote::Double => ('"', '"'),
+            TitleQuote::Single => ('\'', '\''),
+            TitleQuote::Paren => ('(', ')'),
+        }
+    }
+
+    /// Check whether `title` can be written with these delimiters without escaping
+    pub fn fits(self, title: &str) -> bool {
+        let (open, close) = self.delimiters();
+        !title.contains(open) && !title.contains(close)
+    }
+
+    /// Pick the delimiters for `title`: `self` if the title does not contain
+    /// them, otherwise the first alternative that fits, falling back to `self`
+    pub fn choose_for(self, title: &str) -> Self {
+        if self.fits(title) {
+            return self;
+        }
+        [TitleQuote::Double, TitleQuote::Single, TitleQuote::Paren]
+            .into_iter()
+            .find(|quote| quote.fits(title))
+            .unwrap_or(self)
+    }
+}
+
+/// Delimiters written around [`Node::Math`](crate::ast::Node::Math) formulas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MathDelimiters {
+    /// `$...$` and `$$...$$`, as read by GitHub, Pandoc and pulldown-cmark
+    #[default]
+    Dollars,
+    /// `\(...\)` and `\[...\]`, as read by LaTeX and MathJax
+    Brackets,
+}
+
+impl MathDelimiters {
+    /// Opening and closing delimiters of an inline or display formula
+    pub fn delimiters(self, display: bool) -> (&'static str, &'static str) {
+        match (self, display) {
+            (MathDelimiters::Dollars, false) => ("$", "$"),
+            (MathDelimiters::Dollars, true) => ("$$", "$$"),
+            (MathDelimiters::Brackets, false) => ("\\(", "\\)"),
+            (MathDelimiters::Brackets, true) => ("\\[", "\\]"),
+        }
+    }
+}
+
+/// Which characters of text content are escaped when
+/// [`WriterOptions::escape_special_chars`] is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EscapeStrategy {
+    /// Only the characters that would change how the text parses where it is
+    /// written, see [`escape_contextual`](crate::writer::cmark::escape_contextual)
+    #[default]
+    Contextual,
+    /// Every `\`, `*`, `_`, `[`, `]`, `<`, `>` and `` ` ``, wherever it is,
+    /// for consumers with their own ideas about Markdown syntax
+    Always,
+}
+
+/// How soft line breaks are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SoftBreakStyle {
+    /// A line break, as in the source (`\n`)
+    #[default]
+    Newline,
+    /// A space, for targets that reflow text themselves (e.g. chat messages)
+    Space,
+    /// A hard line break, for targets that keep every line (e.g. HTML email)
+    HardBreak,
+}
+
+/// What the CommonMark writer does when a custom item number in an ordered
+/// list isn't greater than the previous one (e.g. 5 then 3)
+///
+/// CommonMark only keeps the number of the first item of a list, so such
+/// numbers don't survive re-parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListRenumberingPolicy {
+    /// Write the numbers as given and emit a warning
+    #[default]
+    Warn,
+    /// Continue counting from the previous item instead
+    Renumber,
+    /// End the list and start a new one at the item, separated by an empty
+    /// HTML comment so the two lists aren't merged
+    Split,
+}
+
+/// Unicode normalization form applied to text
+#[cfg(feature = "unicode-normalization")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalizationForm {
+    /// Canonical composition (`é` as one code point)
+    #[default]
+    Nfc,
+    /// Canonical decomposition (`é` as `e` and a combining accent)
+    Nfd,
+    /// Compatibility composition, which also folds variants like `ﬁ` to `fi`
+    Nfkc,
+    /// Compatibility decomposition
+    Nfkd,
+}
+
+#[cfg(feature = "unicode-normalization")]
+impl NormalizationForm {
+    /// Normalize `text`, borrowing it when it's already in this form
+    pub fn normalize(self, text: &str) -> std::borrow::Cow<'_, str> {
+        use unicode_normalization::{
+            is_nfc_quick, is_nfd_quick, is_nfkc_quick, is_nfkd_quick, IsNormalized,
+            UnicodeNormalization,
+        };
+
+        let chars = text.chars();
+        let quick = match self {
+            NormalizationForm::Nfc => is_nfc_quick(chars),
+            NormalizationForm::Nfd => is_nfd_quick(chars),
+            NormalizationForm::Nfkc => is_nfkc_quick(chars),
+            NormalizationForm::Nfkd => is_nfkd_quick(chars),
+        };
+        if quick == IsNormalized::Yes {
+            return std::borrow::Cow::Borrowed(text);
+        }
+        std::borrow::Cow::Owned(match self {
+            NormalizationForm::Nfc => text.nfc().collect(),
+            NormalizationForm::Nfd => text.nfd().collect(),
+            NormalizationForm::Nfkc => text.nfkc().collect(),
+            NormalizationForm::Nfkd => text.nfkd().collect(),
+        })
+    }
+}
+
+/// A problem with a combination of [`WriterOptions`], found by
+/// [`WriterOptions::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionsConflict {
+    /// `hard_break_spaces` is set, but a post-processor removes the trailing
+    /// spaces its line breaks are made of
+    HardBreakSpacesTrimmed,
+    /// `setext_headings` is set, but `heading_level_offset` moves every heading
+    /// past level 2, where only ATX headings exist
+    SetextHeadingsUnreachable {
+        /// The offending offset
+        heading_level_offset: i8,
+    },
+    /// A marker character CommonMark doesn't accept for the option, set
+    /// without going through [`WriterOptionsBuilder`]
+    InvalidMarker {
+        /// Name of the option, e.g. `"list_marker"`
+        option: &'static str,
+        /// The character
+        marker: char,
+    },
+}
+
+impl fmt::Display for OptionsConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsConflict::HardBreakSpacesTrimmed => f.write_str(
+                "hard_break_spaces is set, but a post-processor trims the trailing spaces of hard breaks",
+            ),
+            OptionsConflict::SetextHeadingsUnreachable {
+                heading_level_offset,
+            } => write!(
+                f,
+                "setext_headings is set, but heading_level_offset {} writes every heading below level 2",
+                heading_level_offset
+            ),
+            OptionsConflict::InvalidMarker { option, marker } => {
+                write!(f, "{:?} is not a valid {}", marker, option)
+            }
+        }
+    }
+}
 
 /// CommonMark formatting options
 #[derive(Debug, Clone)]
@@ -25,8 +371,96 @@ pub struct WriterOptions {
     pub strong_char: char,
     /// Whether to escape special characters in text content
     pub escape_special_chars: bool,
+    /// Which characters `escape_special_chars` escapes
+    pub escape_strategy: EscapeStrategy,
     /// Whether to trim trailing hard breaks from paragraphs
     pub trim_paragraph_trailing_hard_breaks: bool,
+    /// How to handle headings, lists, tables and other containers with no content
+    pub empty_container_policy: EmptyContainerPolicy,
+    /// How control characters in text, inline code and code blocks are handled
+    pub control_chars: ControlCharPolicy,
+    /// How tabs in text, inline code and code blocks are written
+    pub tab_policy: TabPolicy,
+    /// Number of dashes in each table delimiter row cell (at least 1), not counting colons
+    pub table_delimiter_min_dashes: usize,
+    /// Whether table delimiter row cells are padded with spaces (`| :--- |` vs `|:---|`)
+    pub table_delimiter_padding: bool,
+    /// Pad table cells to the display width of their column (counting wide
+    /// and zero-width characters), so the pipes of every row line up
+    pub pretty_tables: bool,
+    /// Base direction of the document, used by `bidi_isolation`
+    pub document_direction: TextDirection,
+    /// Wrap text whose direction differs from `document_direction` in Unicode
+    /// isolate characters (U+2068 FIRST STRONG ISOLATE ... U+2069 POP DIRECTIONAL ISOLATE)
+    pub bidi_isolation: bool,
+    /// Preferred delimiters for link and image titles; another style is chosen
+    /// when the title contains the preferred delimiter
+    pub link_title_quote: TitleQuote,
+    /// Delimiters written around math formulas
+    pub math_delimiters: MathDelimiters,
+    /// Whether to write math formulas between `math_delimiters`, an extension
+    /// read by GitHub, Pandoc and MathJax. Otherwise formulas are written as
+    /// inline code
+    pub math: bool,
+    /// Whether to write definition lists in the `Term` / `: definition`
+    /// syntax of Pandoc, PHP Markdown Extra and markdown-it. Otherwise each
+    /// term is written as a paragraph of strong text followed by its
+    /// definitions
+    pub definition_lists: bool,
+    /// Whether to write footnotes as `[^label]` references and `[^label]: ...`
+    /// definitions, an extension read by GitHub, Pandoc and pulldown-cmark.
+    /// Otherwise labels are written as bracketed text (`\[label\]`), which
+    /// plain CommonMark can't mistake for links
+    pub footnotes: bool,
+    /// Whether the output of `Custom` nodes is written as is. When false, it is
+    /// escaped and written as text on a single line (for custom nodes written
+    /// as HTML, the text of that HTML), so custom nodes can't inject Markdown
+    /// or raw HTML
+    pub trust_custom_nodes: bool,
+    /// Scheme added to URI autolinks that have none (e.g. `<example.com>` is
+    /// written as `<https://example.com>`)
+    pub autolink_default_scheme: EcoString,
+    /// Whether to record the rendered size of each top-level block, reported by
+    /// `CommonMarkWriter::block_sizes`
+    pub track_block_sizes: bool,
+    /// Whether to remember the output of each block, so that blocks identical
+    /// to one written before (compared by structural hash, then equality) are
+    /// copied instead of rendered again. Pays off for documents repeating
+    /// large subtrees; blocks containing custom nodes are always rendered, and
+    /// warnings are only emitted the first time. Ignored with `trace_writes`.
+    pub memoize_blocks: bool,
+    /// Whether to record every change made to the output buffer, with the
+    /// nodes being written and the code making it, reported by
+    /// `CommonMarkWriter::write_trace`. Meant for debugging newline and
+    /// indentation problems; slows writing down considerably
+    pub trace_writes: bool,
+    /// Whether to write explicit heading ids as attribute blocks (`# Title {#id}`),
+    /// an extension understood by Pandoc, markdown-it-attrs and similar
+    pub attribute_blocks: bool,
+    /// Whether soft breaks in table cells are written as spaces instead of
+    /// being rejected, since a line break would end the table row
+    pub table_soft_breaks_as_spaces: bool,
+    /// How soft breaks are written outside table cells
+    pub soft_break_style: SoftBreakStyle,
+    /// Added to the level of every heading when it's written (e.g. `1` turns
+    /// `#` into `##`); the result is clamped to `1..=max_heading_level`
+    pub heading_level_offset: i8,
+    /// Highest heading level written (1-6); deeper headings are written at this
+    /// level, for targets that only support a few levels
+    pub max_heading_level: u8,
+    /// Whether every heading written at level 1 or 2 is written as a setext
+    /// heading (underlined with `=` or `-`), whatever its `heading_type`
+    pub setext_headings: bool,
+    /// Text of a paragraph (e.g. `[[TOC]]`) that is replaced by a table of
+    /// contents of the document's headings, built by `ops::table_of_contents`
+    pub toc_marker: Option<EcoString>,
+    /// How ordered lists whose custom item numbers go backwards are written
+    pub list_renumbering_policy: ListRenumberingPolicy,
+    /// Normalization applied to text content as it's written, so documents
+    /// assembled from several sources produce byte-identical output for
+    /// identical text; `None` writes text unchanged
+    #[cfg(feature = "unicode-normalization")]
+    pub unicode_normalization: Option<NormalizationForm>,
 
     /// Whether to enable GitHub Flavored Markdown (GFM) extensions
     #[cfg(feature = "gfm")]
@@ -48,13 +482,29 @@ pub struct WriterOptions {
     #[cfg(feature = "gfm")]
     pub gfm_autolinks: bool,
 
-    /// List of disallowed HTML tag names in GFM mode
+    /// HTML tag names filtered in GFM mode
     #[cfg(feature = "gfm")]
-    pub gfm_disallowed_html_tags: Vec<EcoString>,
+    pub gfm_disallowed_html_tags: crate::gfm::DisallowedTags,
 
     /// HTML writer options for rendering HtmlElement nodes
     /// If None, options will be automatically derived from CommonMark options
     pub html_writer_options: Option<HtmlWriterOptions>,
+
+    /// Resolver for `{{key}}` placeholders in text nodes, applied before escaping
+    pub placeholders: Option<PlaceholderHook>,
+
+    /// Filters run in order over the final output by `CommonMarkWriter::into_string()`
+    pub post_processors: Vec<Box<dyn OutputFilter>>,
+
+    /// How trailing newlines are normalized by `CommonMarkWriter::into_string()`,
+    /// after any post-processors have run
+    pub final_newline: FinalNewline,
+
+    /// Maximum number of consecutive blank lines written to the output, or `None`
+    /// for no limit. Extra blank lines are dropped as the buffer is written;
+    /// fenced code block contents are never changed. A limit of 0 removes all
+    /// blank lines, which merges adjacent paragraphs.
+    pub max_blank_lines: Option<usize>,
 }
 
 impl Default for WriterOptions {
@@ -68,7 +518,36 @@ impl Default for WriterOptions {
             emphasis_char: '_',
             strong_char: '*',
             escape_special_chars: false,
+            escape_strategy: EscapeStrategy::Contextual,
             trim_paragraph_trailing_hard_breaks: true,
+            empty_container_policy: EmptyContainerPolicy::Auto,
+            control_chars: ControlCharPolicy::Auto,
+            tab_policy: TabPolicy::Preserve,
+            table_delimiter_min_dashes: 3,
+            table_delimiter_padding: true,
+            pretty_tables: false,
+            document_direction: TextDirection::Ltr,
+            bidi_isolation: false,
+            link_title_quote: TitleQuote::Double,
+            math_delimiters: MathDelimiters::Dollars,
+            math: false,
+            definition_lists: false,
+            footnotes: false,
+            trust_custom_nodes: true,
+            autolink_default_scheme: "https".into(),
+            track_block_sizes: false,
+            memoize_blocks: false,
+            trace_writes: false,
+            attribute_blocks: false,
+            table_soft_breaks_as_spaces: true,
+            soft_break_style: SoftBreakStyle::Newline,
+            heading_level_offset: 0,
+            max_heading_level: 6,
+            setext_headings: false,
+            toc_marker: None,
+            list_renumbering_policy: ListRenumberingPolicy::Warn,
+            #[cfg(feature = "unicode-normalization")]
+            unicode_normalization: Some(NormalizationForm::Nfc),
 
             #[cfg(feature = "gfm")]
             enable_gfm: false,
@@ -86,19 +565,13 @@ impl Default for WriterOptions {
             gfm_autolinks: false,
 
             #[cfg(feature = "gfm")]
-            gfm_disallowed_html_tags: vec![
-                "title".into(),
-                "textarea".into(),
-                "style".into(),
-                "xmp".into(),
-                "iframe".into(),
-                "noembed".into(),
-                "noframes".into(),
-                "script".into(),
-                "plaintext".into(),
-            ],
+            gfm_disallowed_html_tags: crate::gfm::DisallowedTags::gfm(),
 
             html_writer_options: None,
+            placeholders: None,
+            post_processors: Vec::new(),
+            final_newline: FinalNewline::Preserve,
+            max_blank_lines: None,
         }
     }
 }
@@ -109,6 +582,177 @@ impl WriterOptions {
         self.html_writer_options = options;
         self
     }
+
+    /// Check the options for settings that contradict each other or that the
+    /// writer can't honor, returning every conflict found
+    ///
+    /// Writers run this when they are created: in strict mode, writing anything
+    /// with conflicting options fails with [`WriteError::InvalidOptions`](crate::WriteError::InvalidOptions);
+    /// otherwise each conflict is logged as a warning.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::options::{OptionsConflict, WriterOptionsBuilder};
+    /// use cmark_writer::writer::filters::TrimTrailingWhitespace;
+    ///
+    /// let options = WriterOptionsBuilder::new()
+    ///     .hard_break_spaces(true)
+    ///     .post_processor(TrimTrailingWhitespace {
+    ///         preserve_hard_breaks: false,
+    ///     })
+    ///     .build();
+    /// assert_eq!(
+    ///     options.validate(),
+    ///     Err(vec![OptionsConflict::HardBreakSpacesTrimmed])
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<OptionsConflict>> {
+        let mut conflicts = Vec::new();
+        if self.hard_break_spaces
+            && self
+                .post_processors
+                .iter()
+                .any(|filter| filter.trims_trailing_spaces())
+        {
+            conflicts.push(OptionsConflict::HardBreakSpacesTrimmed);
+        }
+        if self.setext_headings
+            && shift_heading_level(1, self.heading_level_offset, self.max_heading_level) > 2
+        {
+            conflicts.push(OptionsConflict::SetextHeadingsUnreachable {
+                heading_level_offset: self.heading_level_offset,
+            });
+        }
+        let markers: [(&'static str, char, &[char]); 4] = [
+            ("list_marker", self.list_marker, &['-', '+', '*']),
+            (
+                "thematic_break_char",
+                self.thematic_break_char,
+                &['-', '*', '_'],
+            ),
+            ("emphasis_char", self.emphasis_char, &['_', '*']),
+            ("strong_char", self.strong_char, &['_', '*']),
+        ];
+        for (option, marker, valid) in markers {
+            if !valid.contains(&marker) {
+                conflicts.push(OptionsConflict::InvalidMarker { option, marker });
+            }
+        }
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+}
+
+/// Style overrides carried by a [`Node::ConfiguredDocument`](crate::ast::Node::ConfiguredDocument)
+///
+/// Every field left as `None` keeps the writer's own setting. Values are checked
+/// like the [`WriterOptionsBuilder`] setters, so an invalid marker character
+/// leaves the writer's setting unchanged.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::options::DocumentOptions;
+///
+/// let doc = Node::ConfiguredDocument {
+///     options: DocumentOptions::new().emphasis_char('*'),
+///     children: vec![Node::Paragraph(vec![Node::Emphasis(vec![Node::Text("mine".into())])])],
+/// };
+/// assert_eq!(doc.to_string(), "*mine*\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DocumentOptions {
+    /// Character for emphasis (`_` or `*`)
+    pub emphasis_char: Option<char>,
+    /// Character for strong emphasis (`_` or `*`)
+    pub strong_char: Option<char>,
+    /// Marker for unordered lists (`-`, `+` or `*`)
+    pub list_marker: Option<char>,
+    /// Character for thematic breaks (`-`, `*` or `_`)
+    pub thematic_break_char: Option<char>,
+    /// Whether hard breaks use two spaces instead of a backslash
+    pub hard_break_spaces: Option<bool>,
+    /// Number of spaces for indentation
+    pub indent_spaces: Option<usize>,
+}
+
+impl DocumentOptions {
+    /// Create overrides that change nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the character for emphasis
+    pub fn emphasis_char(mut self, char: char) -> Self {
+        self.emphasis_char = Some(char);
+        self
+    }
+
+    /// Override the character for strong emphasis
+    pub fn strong_char(mut self, char: char) -> Self {
+        self.strong_char = Some(char);
+        self
+    }
+
+    /// Override the marker for unordered lists
+    pub fn list_marker(mut self, marker: char) -> Self {
+        self.list_marker = Some(marker);
+        self
+    }
+
+    /// Override the character for thematic breaks
+    pub fn thematic_break_char(mut self, char: char) -> Self {
+        self.thematic_break_char = Some(char);
+        self
+    }
+
+    /// Override the hard break style
+    pub fn hard_break_spaces(mut self, hard_break_spaces: bool) -> Self {
+        self.hard_break_spaces = Some(hard_break_spaces);
+        self
+    }
+
+    /// Override the number of spaces for indentation
+    pub fn indent_spaces(mut self, indent_spaces: usize) -> Self {
+        self.indent_spaces = Some(indent_spaces);
+        self
+    }
+
+    /// Whether no option is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The writer options with these overrides merged over them
+    pub fn merged_over(&self, base: &WriterOptions) -> WriterOptions {
+        let mut builder = WriterOptionsBuilder {
+            options: base.clone(),
+        };
+        if let Some(char) = self.emphasis_char {
+            builder = builder.emphasis_char(char);
+        }
+        if let Some(char) = self.strong_char {
+            builder = builder.strong_char(char);
+        }
+        if let Some(marker) = self.list_marker {
+            builder = builder.list_marker(marker);
+        }
+        if let Some(char) = self.thematic_break_char {
+            builder = builder.thematic_break_char(char);
+        }
+        if let Some(spaces) = self.hard_break_spaces {
+            builder = builder.hard_break_spaces(spaces);
+        }
+        if let Some(indent) = self.indent_spaces {
+            builder = builder.indent_spaces(indent);
+        }
+        builder.build()
+    }
 }
 
 /// Builder for WriterOptions
@@ -156,12 +800,183 @@ impl WriterOptionsBuilder {
         self
     }
 
+    /// Set which characters `escape_special_chars` escapes
+    pub fn escape_strategy(mut self, strategy: EscapeStrategy) -> Self {
+        self.options.escape_strategy = strategy;
+        self
+    }
+
     /// Set whether to trim trailing hard breaks from paragraphs
     pub fn trim_paragraph_trailing_hard_breaks(mut self, trim: bool) -> Self {
         self.options.trim_paragraph_trailing_hard_breaks = trim;
         self
     }
 
+    /// Set how containers with no content are handled
+    pub fn empty_container_policy(mut self, policy: EmptyContainerPolicy) -> Self {
+        self.options.empty_container_policy = policy;
+        self
+    }
+
+    /// Set how control characters in text and code content are handled
+    pub fn control_chars(mut self, policy: ControlCharPolicy) -> Self {
+        self.options.control_chars = policy;
+        self
+    }
+
+    /// Set how tabs in text and code content are written
+    pub fn tab_policy(mut self, policy: TabPolicy) -> Self {
+        self.options.tab_policy = policy;
+        self
+    }
+
+    /// Set the number of dashes in each table delimiter row cell (values below 1 are treated as 1)
+    pub fn table_delimiter_min_dashes(mut self, dashes: usize) -> Self {
+        self.options.table_delimiter_min_dashes = dashes;
+        self
+    }
+
+    /// Set whether table delimiter row cells are padded with spaces
+    pub fn table_delimiter_padding(mut self, padding: bool) -> Self {
+        self.options.table_delimiter_padding = padding;
+        self
+    }
+
+    /// Set whether table cells are padded so the pipes of every row line up
+    pub fn pretty_tables(mut self, enabled: bool) -> Self {
+        self.options.pretty_tables = enabled;
+        self
+    }
+
+    /// Set the base direction of the document
+    pub fn document_direction(mut self, direction: TextDirection) -> Self {
+        self.options.document_direction = direction;
+        self
+    }
+
+    /// Set whether text in the opposite direction is wrapped in Unicode isolates
+    pub fn bidi_isolation(mut self, isolate: bool) -> Self {
+        self.options.bidi_isolation = isolate;
+        self
+    }
+
+    /// Set the preferred delimiters for link and image titles
+    pub fn link_title_quote(mut self, quote: TitleQuote) -> Self {
+        self.options.link_title_quote = quote;
+        self
+    }
+
+    /// Set the delimiters written around math formulas
+    pub fn math_delimiters(mut self, delimiters: MathDelimiters) -> Self {
+        self.options.math_delimiters = delimiters;
+        self
+    }
+
+    /// Set whether the output of custom nodes is written as is
+    pub fn trust_custom_nodes(mut self, trust: bool) -> Self {
+        self.options.trust_custom_nodes = trust;
+        self
+    }
+
+    /// Set the scheme added to URI autolinks that have none
+    pub fn autolink_default_scheme<S: Into<EcoString>>(mut self, scheme: S) -> Self {
+        self.options.autolink_default_scheme = scheme.into();
+        self
+    }
+
+    /// Set whether to write math formulas between delimiters
+    pub fn math(mut self, enabled: bool) -> Self {
+        self.options.math = enabled;
+        self
+    }
+
+    /// Set whether to write definition lists in the `: definition` syntax
+    pub fn definition_lists(mut self, enabled: bool) -> Self {
+        self.options.definition_lists = enabled;
+        self
+    }
+
+    /// Set whether to write footnotes in the `[^label]` syntax
+    pub fn footnotes(mut self, enabled: bool) -> Self {
+        self.options.footnotes = enabled;
+        self
+    }
+
+    /// Set whether to write explicit heading ids as attribute blocks
+    pub fn attribute_blocks(mut self, enabled: bool) -> Self {
+        self.options.attribute_blocks = enabled;
+        self
+    }
+
+    /// Set whether soft breaks in table cells are written as spaces
+    pub fn table_soft_breaks_as_spaces(mut self, enabled: bool) -> Self {
+        self.options.table_soft_breaks_as_spaces = enabled;
+        self
+    }
+
+    /// Set how soft breaks are written
+    pub fn soft_break_style(mut self, style: SoftBreakStyle) -> Self {
+        self.options.soft_break_style = style;
+        self
+    }
+
+    /// Set the offset added to heading levels
+    pub fn heading_level_offset(mut self, offset: i8) -> Self {
+        self.options.heading_level_offset = offset;
+        self
+    }
+
+    /// Set the highest heading level written (1-6)
+    pub fn max_heading_level(mut self, level: u8) -> Self {
+        if (1..=6).contains(&level) {
+            self.options.max_heading_level = level;
+        }
+        self
+    }
+
+    /// Set whether headings of level 1 and 2 are always written as setext headings
+    pub fn setext_headings(mut self, setext: bool) -> Self {
+        self.options.setext_headings = setext;
+        self
+    }
+
+    /// Set the paragraph text replaced by a table of contents
+    pub fn toc_marker<S: Into<EcoString>>(mut self, marker: S) -> Self {
+        self.options.toc_marker = Some(marker.into());
+        self
+    }
+
+    /// Set how ordered lists whose item numbers go backwards are written
+    pub fn list_renumbering_policy(mut self, policy: ListRenumberingPolicy) -> Self {
+        self.options.list_renumbering_policy = policy;
+        self
+    }
+
+    /// Set the normalization applied to text content, or `None` to disable it
+    #[cfg(feature = "unicode-normalization")]
+    pub fn unicode_normalization(mut self, form: Option<NormalizationForm>) -> Self {
+        self.options.unicode_normalization = form;
+        self
+    }
+
+    /// Set whether to record the rendered size of each top-level block
+    pub fn track_block_sizes(mut self, track: bool) -> Self {
+        self.options.track_block_sizes = track;
+        self
+    }
+
+    /// Set whether the output of repeated blocks is copied instead of rendered again
+    pub fn memoize_blocks(mut self, memoize: bool) -> Self {
+        self.options.memoize_blocks = memoize;
+        self
+    }
+
+    /// Set whether to record every change made to the output buffer
+    pub fn trace_writes(mut self, trace: bool) -> Self {
+        self.options.trace_writes = trace;
+        self
+    }
+
     /// Set the character for thematic breaks (-, *, or _)
     pub fn thematic_break_char(mut self, char: char) -> Self {
         if char == '-' || char == '*' || char == '_' {
@@ -171,6 +986,8 @@ impl WriterOptionsBuilder {
     }
 
     /// Set the character for emphasis (_, or *)
+    ///
+    /// Other characters are ignored and keep the current setting.
     pub fn emphasis_char(mut self, char: char) -> Self {
         if char == '_' || char == '*' {
             self.options.emphasis_char = char;
@@ -179,6 +996,10 @@ impl WriterOptionsBuilder {
     }
 
     /// Set the character for strong emphasis (_, or *)
+    ///
+    /// Other characters are ignored and keep the current setting. Strong
+    /// emphasis wrapping only emphasis is written with this character too,
+    /// e.g. `___x___`.
     pub fn strong_char(mut self, char: char) -> Self {
         if char == '_' || char == '*' {
             self.options.strong_char = char;
@@ -239,8 +1060,36 @@ impl WriterOptionsBuilder {
 
     /// Set list of disallowed HTML tags in GFM mode
     #[cfg(feature = "gfm")]
-    pub fn gfm_disallowed_html_tags(mut self, tags: Vec<EcoString>) -> Self {
-        self.options.gfm_disallowed_html_tags = tags;
+    pub fn gfm_disallowed_html_tags(mut self, tags: impl Into<crate::gfm::DisallowedTags>) -> Self {
+        self.options.gfm_disallowed_html_tags = tags.into();
+        self
+    }
+
+    /// Add tags to the list of disallowed HTML tags in GFM mode
+    ///
+    /// Tags already in the list (ignoring ASCII case) are skipped.
+    #[cfg(feature = "gfm")]
+    pub fn extend_gfm_disallowed_html_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<EcoString>,
+    {
+        self.options.gfm_disallowed_html_tags.extend(tags);
+        self
+    }
+
+    /// Remove tags from the list of disallowed HTML tags in GFM mode
+    ///
+    /// Tags are matched ignoring ASCII case; the other tags keep their order.
+    #[cfg(feature = "gfm")]
+    pub fn remove_gfm_disallowed_html_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for tag in tags {
+            self.options.gfm_disallowed_html_tags.remove(tag.as_ref());
+        }
         self
     }
 
@@ -250,6 +1099,30 @@ impl WriterOptionsBuilder {
         self
     }
 
+    /// Set the resolver for `{{key}}` placeholders in text nodes
+    pub fn placeholder_resolver(mut self, resolver: PlaceholderHook) -> Self {
+        self.options.placeholders = Some(resolver);
+        self
+    }
+
+    /// Add a filter to run over the final output
+    pub fn post_processor<F: OutputFilter + 'static>(mut self, filter: F) -> Self {
+        self.options.post_processors.push(Box::new(filter));
+        self
+    }
+
+    /// Set how trailing newlines are normalized at the end of the output
+    pub fn final_newline(mut self, policy: FinalNewline) -> Self {
+        self.options.final_newline = policy;
+        self
+    }
+
+    /// Limit the number of consecutive blank lines in the output
+    pub fn max_blank_lines(mut self, max: usize) -> Self {
+        self.options.max_blank_lines = Some(max);
+        self
+    }
+
     /// Build the WriterOptions
     pub fn build(self) -> WriterOptions {
         self.options
@@ -261,3 +1134,9 @@ impl Default for WriterOptionsBuilder {
         Self::new()
     }
 }
+
+/// Apply a heading level offset, keeping the result within `1..=max_level`
+pub(crate) fn shift_heading_level(level: u8, offset: i8, max_level: u8) -> u8 {
+    let max_level = max_level.clamp(1, 6);
+    (i16::from(level) + i16::from(offset)).clamp(1, i16::from(max_level)) as u8
+}
@@ -0,0 +1,453 @@
+//! Parsing CommonMark into [`Node`] trees.
+//!
+//! [`parse`] turns CommonMark source into a [`Node::Document`] that the
+//! writers in this crate render back out, so documents can be read, edited as
+//! an AST and written again. Parsing is done by
+//! [pulldown-cmark](https://docs.rs/pulldown-cmark); with the `gfm` feature,
+//! tables, strikethrough, task lists and footnotes are recognized as well, and
+//! definition lists and math can be enabled with [`ParseOptions`].
+//!
+//! The tree keeps what the AST can express and no more:
+//!
+//! - Reference links are resolved into [`Node::Link`] and [`Node::Image`], and
+//!   link reference definitions are dropped.
+//! - Inline HTML becomes [`Node::HtmlInline`], one node per tag or comment,
+//!   with the content between tags as separate nodes.
+//! - Runs of inline content in list items are wrapped in a
+//!   [`Node::Paragraph`]; whether a list is tight is decided by the writer.
+//! - A table cell holding more than one inline node becomes a
+//!   [`Node::Paragraph`], which the CommonMark writer can only emit through
+//!   its HTML fallback.
+//!
+//! Only available with the `parser` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use cmark_writer::ast::Node;
+//! use cmark_writer::parser::parse;
+//!
+//! let doc = parse("# Title\n\nSome *text*.\n");
+//! assert_eq!(
+//!     doc,
+//!     Node::Document(vec![
+//!         Node::heading(1, vec![Node::Text("Title".into())]),
+//!         Node::Paragraph(vec![
+//!             Node::Text("Some ".into()),
+//!             Node::Emphasis(vec![Node::Text("text".into())]),
+//!             Node::Text(".".into()),
+//!         ]),
+//!     ])
+//! );
+//! ```
+
+use crate::ast::{CodeBlockType, DefinitionItem, HeadingType, ListItem, Node};
+#[cfg(feature = "gfm")]
+use crate::ast::{TableAlignment, TaskListStatus};
+use ecow::EcoString;
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, Options, Parser, Tag};
+use std::ops::Range;
+
+/// Options of [`parse_with`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Recognize `{#id}` after a heading and store it as the heading's
+    /// `custom_id`
+    pub heading_attributes: bool,
+    /// Turn straight quotes, `--`, `---` and `...` into their typographic
+    /// counterparts
+    pub smart_punctuation: bool,
+    /// Recognize definition lists: a line of text followed by lines starting
+    /// with `: `
+    pub definition_lists: bool,
+    /// Recognize `$...$` and `$$...$$` as [`Node::Math`]
+    pub math: bool,
+}
+
+impl ParseOptions {
+    /// Plain CommonMark, plus the GFM extensions when the `gfm` feature is enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether heading attributes are recognized
+    pub fn with_heading_attributes(mut self, enabled: bool) -> Self {
+        self.heading_attributes = enabled;
+        self
+    }
+
+    /// Set whether punctuation is made typographic
+    pub fn with_smart_punctuation(mut self, enabled: bool) -> Self {
+        self.smart_punctuation = enabled;
+        self
+    }
+
+    /// Set whether definition lists are recognized
+    pub fn with_definition_lists(mut self, enabled: bool) -> Self {
+        self.definition_lists = enabled;
+        self
+    }
+
+    /// Set whether math is recognized
+    pub fn with_math(mut self, enabled: bool) -> Self {
+        self.math = enabled;
+        self
+    }
+
+    fn to_pulldown(&self) -> Options {
+        let mut options = Options::empty();
+        #[cfg(feature = "gfm")]
+        {
+            options |= Options::ENABLE_TABLES
+                | Options::ENABLE_STRIKETHROUGH
+                | Options::ENABLE_TASKLISTS
+                | Options::ENABLE_FOOTNOTES;
+        }
+        if self.heading_attributes {
+            options |= Options::ENABLE_HEADING_ATTRIBUTES;
+        }
+        if self.smart_punctuation {
+            options |= Options::ENABLE_SMART_PUNCTUATION;
+        }
+        if self.definition_lists {
+            options |= Options::ENABLE_DEFINITION_LIST;
+        }
+        if self.math {
+            options |= Options::ENABLE_MATH;
+        }
+        options
+    }
+}
+
+/// Parse CommonMark `source` into a [`Node::Document`] with default options
+pub fn parse(source: &str) -> Node {
+    parse_with(source, &ParseOptions::default())
+}
+
+/// Parse CommonMark `source` into a [`Node::Document`]
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::parser::{parse_with, ParseOptions};
+///
+/// let options = ParseOptions::new().with_heading_attributes(true);
+/// let doc = parse_with("## Setup {#install}\n", &options);
+/// let Node::Document(children) = doc else { unreachable!() };
+/// assert!(matches!(
+///     &children[0],
+///     Node::Heading { custom_id: Some(id), .. } if id == "install"
+/// ));
+/// ```
+pub fn parse_with(source: &str, options: &ParseOptions) -> Node {
+    let mut builder = TreeBuilder {
+        source,
+        stack: vec![Frame::new(None, 0..source.len())],
+    };
+    for (event, range) in Parser::new_ext(source, options.to_pulldown()).into_offset_iter() {
+        builder.event(event, range);
+    }
+    // Unclosed frames can't happen with pulldown-cmark, but don't lose content if they do
+    while builder.stack.len() > 1 {
+        builder.close();
+    }
+    let root = builder.stack.pop().map(|frame| frame.children);
+    Node::Document(root.unwrap_or_default())
+}
+
+/// An open container and the nodes collected for it so far
+struct Frame<'a> {
+    /// `None` for the document
+    tag: Option<Tag<'a>>,
+    /// Source range of the container
+    range: Range<usize>,
+    children: Vec<Node>,
+    /// Rows of a table, the header row first
+    rows: Vec<Vec<Node>>,
+    /// Items of a list, with the checkbox state of task items
+    items: Vec<(Option<bool>, Vec<Node>)>,
+    /// Checkbox state of a task list item
+    task: Option<bool>,
+    /// Items of a definition list
+    definitions: Vec<DefinitionItem>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(tag: Option<Tag<'a>>, range: Range<usize>) -> Self {
+        Self {
+            tag,
+            range,
+            children: Vec::new(),
+            rows: Vec::new(),
+            items: Vec::new(),
+            task: None,
+            definitions: Vec::new(),
+        }
+    }
+}
+
+struct TreeBuilder<'a> {
+    source: &'a str,
+    stack: Vec<Frame<'a>>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    fn event(&mut self, event: Event<'a>, range: Range<usize>) {
+        match event {
+            Event::Start(tag) => self.stack.push(Frame::new(Some(tag), range)),
+            Event::End(_) => self.close(),
+            Event::Text(text) => self.push_text(&text),
+            Event::Code(code) => self.push(Node::InlineCode(code.as_ref().into())),
+            // Raw HTML, as text of the enclosing HTML block
+            Event::Html(html) => self.push_text(&html),
+            Event::InlineHtml(html) => self.push(Node::HtmlInline(html.as_ref().into())),
+            Event::SoftBreak => self.push(Node::SoftBreak),
+            Event::HardBreak => self.push(Node::HardBreak),
+            Event::Rule => self.push(Node::ThematicBreak),
+            Event::TaskListMarker(checked) => {
+                let item = self
+                    .stack
+                    .iter_mut()
+                    .rev()
+                    .find(|frame| matches!(frame.tag, Some(Tag::Item)));
+                if let Some(item) = item {
+                    item.task = Some(checked);
+                }
+            }
+            Event::FootnoteReference(label) => {
+                self.push(Node::FootnoteReference(label.as_ref().into()))
+            }
+            Event::InlineMath(text) => self.push(Node::Math {
+                display: false,
+                content: text.as_ref().into(),
+            }),
+            Event::DisplayMath(text) => self.push(Node::Math {
+                display: true,
+                content: text.as_ref().into(),
+            }),
+        }
+    }
+
+    fn top(&mut self) -> &mut Frame<'a> {
+        self.stack
+            .last_mut()
+            .expect("the document frame is never closed")
+    }
+
+    fn push(&mut self, node: Node) {
+        self.top().children.push(node);
+    }
+
+    /// Append text, merging it with preceding text
+    fn push_text(&mut self, text: &str) {
+        let children = &mut self.top().children;
+        if let Some(Node::Text(last)) = children.last_mut() {
+            last.push_str(text);
+        } else {
+            children.push(Node::Text(text.into()));
+        }
+    }
+
+    /// Pop the innermost container and add its node to the parent
+    fn close(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let Some(tag) = frame.tag else {
+            // The document frame: put it back
+            self.stack.push(frame);
+            return;
+        };
+        let children = frame.children;
+        let node = match tag {
+            Tag::Paragraph => Node::Paragraph(children),
+            Tag::Heading { level, id, .. } => {
+                let source = &self.source[frame.range];
+                let heading_type = if source.trim_start().starts_with('#') {
+                    HeadingType::Atx
+                } else {
+                    HeadingType::Setext
+                };
+                Node::Heading {
+                    level: level as u8,
+                    content: children,
+                    heading_type,
+                    custom_id: id.map(|id| id.as_ref().into()),
+                }
+            }
+            Tag::BlockQuote(_) => Node::BlockQuote(children),
+            Tag::CodeBlock(kind) => {
+                let (language, block_type) = match kind {
+                    CodeBlockKind::Indented => (None, CodeBlockType::Indented),
+                    CodeBlockKind::Fenced(info) => {
+                        let info = info.trim();
+                        let language = (!info.is_empty()).then(|| info.into());
+                        (language, CodeBlockType::Fenced)
+                    }
+                };
+                Node::CodeBlock {
+                    language,
+                    content: text_of(children),
+                    block_type,
+                }
+            }
+            Tag::HtmlBlock => Node::HtmlBlock(text_of(children)),
+            Tag::List(start) => {
+                let items = frame.items.into_iter().map(|(task, content)| {
+                    #[cfg(feature = "gfm")]
+                    if let Some(checked) = task {
+                        let status = if checked {
+                            TaskListStatus::Checked
+                        } else {
+                            TaskListStatus::Unchecked
+                        };
+                        return ListItem::Task { status, content };
+                    }
+                    #[cfg(not(feature = "gfm"))]
+                    let _ = task;
+                    match start {
+                        Some(_) => ListItem::Ordered {
+                            number: None,
+                            content,
+                        },
+                        None => ListItem::Unordered { content },
+                    }
+                });
+                match start {
+                    Some(start) => Node::OrderedList {
+                        start: u32::try_from(start).unwrap_or(u32::MAX),
+                        items: items.collect(),
+                    },
+                    None => Node::UnorderedList(items.collect()),
+                }
+            }
+            Tag::Item => {
+                let content = wrap_inlines(children);
+                self.top().items.push((frame.task, content));
+                return;
+            }
+            Tag::Emphasis => Node::Emphasis(children),
+            Tag::Strong => Node::Strong(children),
+            Tag::Strikethrough => Node::Strikethrough(children),
+            Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                ..
+            } => match link_type {
+                LinkType::Autolink | LinkType::Email => Node::Autolink {
+                    url: dest_url.as_ref().into(),
+                    is_email: link_type == LinkType::Email,
+                },
+                _ => Node::Link {
+                    url: dest_url.as_ref().into(),
+                    title: non_empty(&title),
+                    content: children,
+                },
+            },
+            Tag::Image {
+                dest_url, title, ..
+            } => Node::Image {
+                url: dest_url.as_ref().into(),
+                title: non_empty(&title),
+                alt: children,
+            },
+            #[cfg(feature = "gfm")]
+            Tag::Table(alignments) => {
+                let mut rows = frame.rows.into_iter();
+                let headers = rows.next().unwrap_or_default();
+                Node::Table {
+                    headers,
+                    alignments: alignments.into_iter().map(table_alignment).collect(),
+                    rows: rows.collect(),
+                }
+            }
+            Tag::TableHead | Tag::TableRow => {
+                self.top().rows.push(children);
+                return;
+            }
+            Tag::TableCell => cell_content(children),
+            Tag::FootnoteDefinition(label) => Node::FootnoteDefinition {
+                label: label.as_ref().into(),
+                content: children,
+            },
+            Tag::DefinitionList => Node::DefinitionList(frame.definitions),
+            Tag::DefinitionListTitle => {
+                self.top()
+                    .definitions
+                    .push(DefinitionItem::new(children, Vec::new()));
+                return;
+            }
+            Tag::DefinitionListDefinition => {
+                let content = wrap_inlines(children);
+                let definitions = &mut self.top().definitions;
+                match definitions.last_mut() {
+                    Some(item) => item.definitions.push(content),
+                    None => definitions.push(DefinitionItem::new(Vec::new(), vec![content])),
+                }
+                return;
+            }
+            // Not enabled: metadata blocks, ...
+            _ => {
+                self.top().children.extend(children);
+                return;
+            }
+        };
+        self.push(node);
+    }
+}
+
+/// The concatenated text of `nodes`
+fn text_of(nodes: Vec<Node>) -> EcoString {
+    let mut text = EcoString::new();
+    for node in nodes {
+        if let Node::Text(part) = node {
+            text.push_str(&part);
+        }
+    }
+    text
+}
+
+fn non_empty(text: &str) -> Option<EcoString> {
+    (!text.is_empty()).then(|| text.into())
+}
+
+/// Wrap each run of inline nodes of a tight list item in a paragraph
+fn wrap_inlines(nodes: Vec<Node>) -> Vec<Node> {
+    let mut blocks = Vec::new();
+    let mut inlines = Vec::new();
+    for node in nodes {
+        if node.is_block() {
+            if !inlines.is_empty() {
+                blocks.push(Node::Paragraph(std::mem::take(&mut inlines)));
+            }
+            blocks.push(node);
+        } else {
+            inlines.push(node);
+        }
+    }
+    if !inlines.is_empty() {
+        blocks.push(Node::Paragraph(inlines));
+    }
+    blocks
+}
+
+/// A table cell is a single node
+fn cell_content(mut nodes: Vec<Node>) -> Node {
+    match nodes.len() {
+        0 => Node::Text(EcoString::new()),
+        1 => nodes.remove(0),
+        _ => Node::Paragraph(nodes),
+    }
+}
+
+#[cfg(feature = "gfm")]
+fn table_alignment(alignment: pulldown_cmark::Alignment) -> TableAlignment {
+    match alignment {
+        pulldown_cmark::Alignment::None => TableAlignment::None,
+        pulldown_cmark::Alignment::Left => TableAlignment::Left,
+        pulldown_cmark::Alignment::Center => TableAlignment::Center,
+        pulldown_cmark::Alignment::Right => TableAlignment::Right,
+    }
+}
@@ -0,0 +1,446 @@
+//! Python bindings.
+//!
+//! Available with the `python` feature, which builds the `cmark_writer` Python
+//! module with PyO3. Build and install it with
+//! [maturin](https://www.maturin.rs): `maturin develop` from the repository
+//! root, where `pyproject.toml` enables the feature.
+//!
+//! A `Document` is built block by block, or from nodes written as in the JSON
+//! format of the [`ffi`](crate::ffi) module (as Python dicts, or as a JSON
+//! string for `Document.from_json`). `render_commonmark` and `render_html`
+//! take the options as keyword arguments named like the fields of the JSON
+//! options:
+//!
+//! ```python
+//! import cmark_writer
+//!
+//! doc = cmark_writer.Document()
+//! doc.heading("Results", level=2)
+//! doc.paragraph("Accuracy by model:")
+//! doc.table(["model", "accuracy"], [["baseline", 0.81], ["tuned", 0.93]])
+//! doc.append({"type": "CodeBlock", "language": "python", "content": "fit()\n"})
+//!
+//! markdown = cmark_writer.render_commonmark(doc, list_marker="*")
+//! html = cmark_writer.render_html(doc, strict=False)
+//! ```
+//!
+//! Text is written like any other text node: with `escape_special_chars=True`,
+//! values can't change the structure of the Markdown document, and HTML is
+//! always escaped. Invalid nodes and options raise `ValueError`, and
+//! documents that can't be written raise `cmark_writer.RenderError`.
+
+use crate::ast::{CodeBlockType, ListItem, Node};
+use crate::ffi::{
+    document_from_json, html_options_from_json, node_from_json, writer_options_from_json, Json,
+    MAX_DEPTH,
+};
+use crate::{CommonMarkWriter, HtmlWriter, ToHtml};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyList, PyString, PyTuple};
+
+create_exception!(
+    cmark_writer,
+    RenderError,
+    PyException,
+    "Raised when a document can't be written with the given options."
+);
+
+/// A document built from Python
+///
+/// Blocks are appended in order with the building methods or, for any other
+/// node, with `append`.
+#[pyclass(module = "cmark_writer")]
+pub struct Document {
+    /// Always a `Node::Document`
+    root: Node,
+}
+
+impl Document {
+    fn push(&mut self, node: Node) {
+        match &mut self.root {
+            Node::Document(children) => children.push(node),
+            _ => unreachable!("the root of a Python document is a document node"),
+        }
+    }
+}
+
+#[pymethods]
+impl Document {
+    /// Create a document, optionally with a list of nodes in their JSON form
+    #[new]
+    #[pyo3(signature = (children=None))]
+    fn new(children: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut document = Document {
+            root: Node::Document(Vec::new()),
+        };
+        if let Some(children) = children {
+            for child in children.try_iter()? {
+                document.append(&child?)?;
+            }
+        }
+        Ok(document)
+    }
+
+    /// Read a document from the JSON format of the `ffi` module
+    ///
+    /// Documents of older format versions are upgraded. A root node other
+    /// than a document becomes the only block of the document.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let root = Json::parse(json)
+            .and_then(document_from_json)
+            .map_err(PyValueError::new_err)?;
+        let root = match root {
+            Node::Document(_) => root,
+            node => Node::Document(vec![node]),
+        };
+        Ok(Document { root })
+    }
+
+    /// Append a node given in its JSON form, e.g.
+    /// `{"type": "ThematicBreak"}`
+    fn append(&mut self, node: &Bound<'_, PyAny>) -> PyResult<()> {
+        let node = node_from_json(&to_json(node, 0)?).map_err(PyValueError::new_err)?;
+        self.push(node);
+        Ok(())
+    }
+
+    /// Append a heading
+    #[pyo3(signature = (text, level=1))]
+    fn heading(&mut self, text: &str, level: u8) {
+        self.push(Node::heading(level, vec![Node::Text(text.into())]));
+    }
+
+    /// Append a paragraph
+    fn paragraph(&mut self, text: &str) {
+        self.push(Node::Paragraph(vec![Node::Text(text.into())]));
+    }
+
+    /// Append a fenced code block
+    #[pyo3(signature = (code, language=None))]
+    fn code_block(&mut self, code: &str, language: Option<&str>) {
+        self.push(Node::CodeBlock {
+            language: language.map(Into::into),
+            content: code.into(),
+            block_type: CodeBlockType::Fenced,
+        });
+    }
+
+    /// Append a bullet list, or a numbered one if `ordered` is set
+    ///
+    /// Each item is a value written as text, or a node in its JSON form.
+    #[pyo3(signature = (items, ordered=false))]
+    fn list(&mut self, items: &Bound<'_, PyAny>, ordered: bool) -> PyResult<()> {
+        let mut contents = Vec::new();
+        for item in items.try_iter()? {
+            let item = item?;
+            let content = if item.is_instance_of::<PyDict>() {
+                node_from_json(&to_json(&item, 0)?).map_err(PyValueError::new_err)?
+            } else {
+                Node::Paragraph(vec![cell(&item)?])
+            };
+            contents.push(vec![content]);
+        }
+        let node = if ordered {
+            let items = contents
+                .into_iter()
+                .map(|content| ListItem::Ordered {
+                    number: None,
+                    content,
+                })
+                .collect();
+            Node::OrderedList { start: 1, items }
+        } else {
+            let items = contents
+                .into_iter()
+                .map(|content| ListItem::Unordered { content })
+                .collect();
+            Node::UnorderedList(items)
+        };
+        self.push(node);
+        Ok(())
+    }
+
+    /// Append a table
+    ///
+    /// Cells are values written as text (`None` is an empty cell), or inline
+    /// nodes in their JSON form. `alignments` are `"left"`, `"center"`,
+    /// `"right"` or `"none"`, one per column, and need the `gfm` feature.
+    #[pyo3(signature = (headers, rows, alignments=None))]
+    fn table(
+        &mut self,
+        headers: &Bound<'_, PyAny>,
+        rows: &Bound<'_, PyAny>,
+        alignments: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        let headers = cells(headers)?;
+        let rows = rows
+            .try_iter()?
+            .map(|row| cells(&row?))
+            .collect::<PyResult<Vec<_>>>()?;
+        #[cfg(feature = "gfm")]
+        {
+            use crate::ast::TableAlignment;
+            let alignments = match alignments {
+                Some(alignments) => alignments
+                    .iter()
+                    .map(|alignment| match alignment.as_str() {
+                        "left" => Ok(TableAlignment::Left),
+                        "center" => Ok(TableAlignment::Center),
+                        "right" => Ok(TableAlignment::Right),
+                        "none" => Ok(TableAlignment::None),
+                        _ => Err(PyValueError::new_err(format!(
+                            "invalid alignment `{}`, expected \"left\", \"center\", \"right\" or \"none\"",
+                            alignment
+                        ))),
+                    })
+                    .collect::<PyResult<_>>()?,
+                None => vec![TableAlignment::None; headers.len()],
+            };
+            self.push(Node::table_with_alignment(headers, alignments, rows));
+        }
+        #[cfg(not(feature = "gfm"))]
+        {
+            if alignments.is_some() {
+                return Err(PyValueError::new_err(
+                    "table alignments require the `gfm` feature",
+                ));
+            }
+            self.push(Node::Table { headers, rows });
+        }
+        Ok(())
+    }
+
+    /// Append a thematic break
+    fn thematic_break(&mut self) {
+        self.push(Node::ThematicBreak);
+    }
+
+    /// Number of blocks in the document
+    fn __len__(&self) -> usize {
+        match &self.root {
+            Node::Document(children) => children.len(),
+            _ => 0,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<cmark_writer.Document with {} blocks>", self.__len__())
+    }
+}
+
+/// Render a document to CommonMark
+///
+/// The keyword arguments are the CommonMark writer options, e.g.
+/// `strict=False` or `list_marker="*"`.
+#[pyfunction]
+#[pyo3(signature = (document, **options))]
+pub fn render_commonmark(
+    document: PyRef<'_, Document>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let options = match options {
+        Some(options) => writer_options_from_json(&to_json(options.as_any(), 0)?)
+            .map_err(PyValueError::new_err)?,
+        None => Default::default(),
+    };
+    let mut writer = CommonMarkWriter::with_options(options);
+    writer
+        .write_node(&document.root)
+        .map_err(|err| RenderError::new_err(err.to_string()))?;
+    Ok(writer.into_string().to_string())
+}
+
+/// Render a document to HTML
+///
+/// The keyword arguments are the HTML writer options, e.g.
+/// `allowed_url_schemes=["https"]`.
+#[pyfunction]
+#[pyo3(signature = (document, **options))]
+pub fn render_html(
+    document: PyRef<'_, Document>,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let options = match options {
+        Some(options) => {
+            html_options_from_json(&to_json(options.as_any(), 0)?).map_err(PyValueError::new_err)?
+        }
+        None => Default::default(),
+    };
+    let mut writer = HtmlWriter::with_options(options);
+    document
+        .root
+        .to_html(&mut writer)
+        .map_err(|err| RenderError::new_err(err.to_string()))?;
+    Ok(writer.into_string().to_string())
+}
+
+/// The `cmark_writer` Python module
+#[pymodule]
+pub fn cmark_writer(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Document>()?;
+    module.add_function(wrap_pyfunction!(render_commonmark, module)?)?;
+    module.add_function(wrap_pyfunction!(render_html, module)?)?;
+    module.add("RenderError", module.py().get_type::<RenderError>())?;
+    Ok(())
+}
+
+/// The JSON form of a Python value made of dicts, lists, strings, numbers,
+/// booleans and `None`
+fn to_json(value: &Bound<'_, PyAny>, depth: usize) -> PyResult<Json> {
+    if depth > MAX_DEPTH {
+        return Err(PyValueError::new_err("value is nested too deeply"));
+    }
+    if value.is_none() {
+        return Ok(Json::Null);
+    }
+    // Before numbers, as `bool` is a subclass of `int`
+    if let Ok(boolean) = value.downcast::<PyBool>() {
+        return Ok(Json::Bool(boolean.is_true()));
+    }
+    if let Ok(string) = value.downcast::<PyString>() {
+        return Ok(Json::String(string.to_cow()?.as_ref().into()));
+    }
+    if let Ok(dict) = value.downcast::<PyDict>() {
+        let members = dict
+            .iter()
+            .map(|(key, value)| {
+                let key = key
+                    .downcast::<PyString>()
+                    .map_err(|_| PyTypeError::new_err("dict keys must be strings"))?;
+                Ok((key.to_cow()?.as_ref().into(), to_json(&value, depth + 1)?))
+            })
+            .collect::<PyResult<_>>()?;
+        return Ok(Json::Object(members));
+    }
+    if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        let items = value
+            .try_iter()?
+            .map(|item| to_json(&item?, depth + 1))
+            .collect::<PyResult<_>>()?;
+        return Ok(Json::Array(items));
+    }
+    match value.extract::<f64>() {
+        Ok(number) => Ok(Json::Number(number)),
+        Err(_) => Err(PyTypeError::new_err(format!(
+            "can't convert {} to JSON",
+            value.get_type().name()?
+        ))),
+    }
+}
+
+/// A table cell or list item: a node in its JSON form, or a value written as
+/// text
+fn cell(value: &Bound<'_, PyAny>) -> PyResult<Node> {
+    if value.is_none() {
+        return Ok(Node::Text("".into()));
+    }
+    if value.is_instance_of::<PyDict>() {
+        return node_from_json(&to_json(value, 0)?).map_err(PyValueError::new_err);
+    }
+    Ok(Node::Text(value.str()?.to_cow()?.as_ref().into()))
+}
+
+fn cells(values: &Bound<'_, PyAny>) -> PyResult<Vec<Node>> {
+    values.try_iter()?.map(|value| cell(&value?)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::ffi::c_str;
+    use std::ffi::CStr;
+
+    /// Run `code` with the module imported as `cmark_writer`
+    fn run(code: &CStr) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "cmark_writer").unwrap();
+            cmark_writer(&module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("cmark_writer", module).unwrap();
+            if let Err(error) = py.run(code, Some(&globals), None) {
+                panic!("{}", error);
+            }
+        });
+    }
+
+    #[test]
+    fn test_render_commonmark() {
+        run(c_str!(
+            r###"
+doc = cmark_writer.Document()
+doc.heading("Results", level=2)
+doc.paragraph("*not* emphasis")
+doc.list(["one", 2], ordered=True)
+doc.table(["model", "accuracy"], [["baseline", 0.81], ["tuned", None]])
+doc.code_block("fit()\n", language="python")
+assert len(doc) == 5
+markdown = cmark_writer.render_commonmark(doc, escape_special_chars=True)
+assert markdown == (
+    "## Results\n\n\\*not\\* emphasis\n\n1. one\n2. 2\n\n"
+    "| model | accuracy |\n| --- | --- |\n| baseline | 0.81 |\n| tuned |  |\n\n"
+    "```python\nfit()\n```\n"
+)
+"###
+        ));
+    }
+
+    #[test]
+    fn test_options_are_keyword_arguments() {
+        run(c_str!(
+            r###"
+doc = cmark_writer.Document([
+    {"type": "Paragraph", "children": [
+        {"type": "Strong", "children": [{"type": "Text", "text": "bold"}]},
+    ]},
+])
+doc.list(["item"])
+assert cmark_writer.render_commonmark(doc, strong_char="_", list_marker="*") == (
+    "__bold__\n\n* item\n"
+)
+try:
+    cmark_writer.render_commonmark(doc, colour=True)
+    raise AssertionError("unknown option accepted")
+except ValueError as error:
+    assert "unknown field `colour`" in str(error)
+"###
+        ));
+    }
+
+    #[test]
+    fn test_render_html() {
+        run(c_str!(
+            r###"
+doc = cmark_writer.Document.from_json('{"version": 3, "type": "Paragraph", "children": [{"type": "Link", "url": "ftp://x", "content": [{"type": "Text", "text": "a < b"}]}]}')
+assert cmark_writer.render_html(doc, strict=False) == '<p><a href="">a &lt; b</a></p>\n'
+assert cmark_writer.render_html(doc, allowed_url_schemes=["ftp"]) == (
+    '<p><a href="ftp://x">a &lt; b</a></p>\n'
+)
+try:
+    cmark_writer.render_html(doc)
+    raise AssertionError("disallowed URL accepted")
+except cmark_writer.RenderError:
+    pass
+"###
+        ));
+    }
+
+    #[test]
+    fn test_invalid_nodes() {
+        run(c_str!(
+            r###"
+doc = cmark_writer.Document()
+for node in [{"type": "Bogus"}, {"type": "Text"}, {1: "x"}, {"type": "Text", "text": object()}]:
+    try:
+        doc.append(node)
+        raise AssertionError(f"{node} accepted")
+    except (ValueError, TypeError):
+        pass
+assert len(doc) == 0
+"###
+        ));
+    }
+}
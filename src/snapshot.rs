@@ -0,0 +1,293 @@
+//! Golden-test snapshots of a document.
+//!
+//! [`Node::render_snapshot`] renders a node to CommonMark and HTML and adds an
+//! outline of its tree, all in one string meant to be compared with
+//! [insta](https://insta.rs) or any other golden-file tool. The output only
+//! depends on the node and the [`SnapshotOptions`]: line endings are
+//! normalized, every section ends with exactly one newline, and a failed
+//! render is recorded in its section instead of aborting the snapshot.
+//!
+//! Volatile content such as dates or generated ids can be replaced by
+//! redactions, which run over every section of the snapshot:
+//!
+//! ```
+//! use cmark_writer::ast::Node;
+//! use cmark_writer::snapshot::SnapshotOptions;
+//!
+//! let doc = Node::Document(vec![Node::Paragraph(vec![Node::Text(
+//!     "Generated on 2024-05-01".into(),
+//! )])]);
+//! let options = SnapshotOptions::new().redact("2024-05-01", "[date]");
+//! let snapshot = doc.render_snapshot_with(&options);
+//! assert!(!snapshot.contains("2024"));
+//! assert!(snapshot.contains("<p>Generated on [date]</p>"));
+//! ```
+
+use crate::ast::{list_item_content, ListItem, Node};
+use crate::options::WriterOptions;
+use crate::writer::multi::{render_multi, OutputTarget};
+use crate::writer::HtmlWriterOptions;
+use std::fmt::{self, Write};
+use std::sync::Arc;
+
+type Redaction = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Options of [`Node::render_snapshot_with`]
+#[derive(Clone, Default)]
+pub struct SnapshotOptions {
+    /// Options of the CommonMark section
+    pub commonmark: WriterOptions,
+    /// Options of the HTML section
+    pub html: HtmlWriterOptions,
+    redactions: Vec<Redaction>,
+}
+
+impl fmt::Debug for SnapshotOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotOptions")
+            .field("commonmark", &self.commonmark)
+            .field("html", &self.html)
+            .field("redactions", &self.redactions.len())
+            .finish()
+    }
+}
+
+impl SnapshotOptions {
+    /// Default writer options and no redactions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the options of the CommonMark section
+    pub fn with_commonmark_options(mut self, options: WriterOptions) -> Self {
+        self.commonmark = options;
+        self
+    }
+
+    /// Set the options of the HTML section
+    pub fn with_html_options(mut self, options: HtmlWriterOptions) -> Self {
+        self.html = options;
+        self
+    }
+
+    /// Replace every occurrence of `needle` with `replacement`
+    pub fn redact(self, needle: impl Into<String>, replacement: impl Into<String>) -> Self {
+        let needle = needle.into();
+        let replacement = replacement.into();
+        self.redact_with(move |text| text.replace(&needle, &replacement))
+    }
+
+    /// Rewrite every section with `redaction`, e.g. to replace timestamps
+    /// matched by a regular expression
+    ///
+    /// Redactions run in the order they were added, after the sections are
+    /// rendered and normalized.
+    pub fn redact_with(
+        mut self,
+        redaction: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.redactions.push(Arc::new(redaction));
+        self
+    }
+}
+
+impl Node {
+    /// Render a snapshot of the node with default options
+    ///
+    /// See [`render_snapshot_with`](Self::render_snapshot_with).
+    pub fn render_snapshot(&self) -> String {
+        self.render_snapshot_with(&SnapshotOptions::default())
+    }
+
+    /// Render the node to CommonMark and HTML, followed by an outline of its tree
+    ///
+    /// Each section starts with a `--- name ---` line. The format is stable
+    /// across releases apart from changes to the rendered output itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    /// use cmark_writer::snapshot::SnapshotOptions;
+    ///
+    /// let doc = Node::Document(vec![
+    ///     Node::heading(1, vec![Node::Text("Report".into())]),
+    ///     Node::Paragraph(vec![
+    ///         Node::Text("All ".into()),
+    ///         Node::Strong(vec![Node::Text("good".into())]),
+    ///     ]),
+    /// ]);
+    /// assert_eq!(
+    ///     doc.render_snapshot_with(&SnapshotOptions::new()),
+    ///     concat!(
+    ///         "--- commonmark ---\n",
+    ///         "# Report\n",
+    ///         "\n",
+    ///         "All **good**\n",
+    ///         "--- html ---\n",
+    ///         "<h1>Report</h1>\n",
+    ///         "<p>All <strong>good</strong></p>\n",
+    ///         "--- outline ---\n",
+    ///         "Document\n",
+    ///         "  Heading level=1\n",
+    ///         "    Text \"Report\"\n",
+    ///         "  Paragraph\n",
+    ///         "    Text \"All \"\n",
+    ///         "    Strong\n",
+    ///         "      Text \"good\"\n",
+    ///     )
+    /// );
+    /// ```
+    pub fn render_snapshot_with(&self, options: &SnapshotOptions) -> String {
+        let result = render_multi(
+            self,
+            &[
+                OutputTarget::CommonMark(options.commonmark.clone()),
+                OutputTarget::Html(options.html.clone()),
+            ],
+        );
+        let mut outline = String::new();
+        write_outline(self, 0, &mut outline);
+
+        let sections = result
+            .outputs
+            .iter()
+            .map(|(_, output)| match output {
+                Ok(output) => output.to_string(),
+                Err(error) => format!("error: {}", error),
+            })
+            .chain([outline]);
+        let mut snapshot = String::new();
+        for (name, section) in ["commonmark", "html", "outline"].into_iter().zip(sections) {
+            let mut section = section.replace("\r\n", "\n").replace('\r', "\n");
+            for redaction in &options.redactions {
+                section = redaction(&section);
+            }
+            let _ = writeln!(snapshot, "--- {} ---", name);
+            let section = section.trim_end_matches('\n');
+            if !section.is_empty() {
+                snapshot.push_str(section);
+                snapshot.push('\n');
+            }
+        }
+        snapshot
+    }
+}
+
+/// Append one line per node of the subtree, indented by depth
+fn write_outline(node: &Node, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let node = node.resolve_shared();
+    let _ = write!(out, "{}{}", indent, node.type_name());
+    match node {
+        Node::Heading {
+            level, custom_id, ..
+        } => {
+            let _ = write!(out, " level={}", level);
+            if let Some(id) = custom_id {
+                let _ = write!(out, " id={:?}", id.as_str());
+            }
+        }
+        Node::CodeBlock {
+            language, content, ..
+        } => {
+            if let Some(language) = language {
+                let _ = write!(out, " language={:?}", language.as_str());
+            }
+            let _ = write!(out, " {:?}", content.as_str());
+        }
+        Node::LinkReferenceDefinition {
+            label, destination, ..
+        } => {
+            let _ = write!(out, " [{}] {}", label, destination);
+        }
+        Node::OrderedList { start, .. } => {
+            let _ = write!(out, " start={}", start);
+        }
+        Node::Link { url, .. } | Node::Image { url, .. } | Node::Autolink { url, .. } => {
+            let _ = write!(out, " {}", url);
+        }
+        Node::ReferenceLink { label, .. } => {
+            let _ = write!(out, " [{}]", label);
+        }
+        Node::CrossRef { target, .. } => {
+            let _ = write!(out, " #{}", target);
+        }
+        Node::FootnoteReference(label) | Node::FootnoteDefinition { label, .. } => {
+            let _ = write!(out, " [^{}]", label);
+        }
+        Node::HtmlElement(element) => {
+            let _ = write!(out, " <{}>", element.tag);
+        }
+        Node::NumberRef(kind, id) => {
+            let _ = write!(out, " {}:{}", kind, id);
+        }
+        Node::Custom(custom) => {
+            let _ = write!(out, " {}", custom.type_name());
+        }
+        Node::Text(text)
+        | Node::InlineCode(text)
+        | Node::Math { content: text, .. }
+        | Node::HtmlBlock(text)
+        | Node::HtmlInline(text)
+        | Node::ExtendedAutolink(text) => {
+            let _ = write!(out, " {:?}", text.as_str());
+        }
+        _ => {}
+    }
+    out.push('\n');
+
+    match node {
+        Node::UnorderedList(items) | Node::OrderedList { items, .. } => {
+            for item in items {
+                let _ = writeln!(out, "{}  {}", indent, item_label(item));
+                for child in list_item_content(item) {
+                    write_outline(child, depth + 2, out);
+                }
+            }
+        }
+        Node::DefinitionList(items) => {
+            for item in items {
+                let _ = writeln!(out, "{}  Term", indent);
+                for child in &item.term {
+                    write_outline(child, depth + 2, out);
+                }
+                for definition in &item.definitions {
+                    let _ = writeln!(out, "{}  Definition", indent);
+                    for child in definition {
+                        write_outline(child, depth + 2, out);
+                    }
+                }
+            }
+        }
+        Node::Table { headers, rows, .. } => {
+            let _ = writeln!(out, "{}  Header", indent);
+            for cell in headers {
+                write_outline(cell, depth + 2, out);
+            }
+            for row in rows {
+                let _ = writeln!(out, "{}  Row", indent);
+                for cell in row {
+                    write_outline(cell, depth + 2, out);
+                }
+            }
+        }
+        node => {
+            for (_, child) in node.children_with_segments() {
+                write_outline(child, depth + 1, out);
+            }
+        }
+    }
+}
+
+fn item_label(item: &ListItem) -> String {
+    match item {
+        ListItem::Ordered {
+            number: Some(number),
+            ..
+        } => format!("Item number={}", number),
+        #[cfg(feature = "gfm")]
+        ListItem::Task { status, .. } => format!("Item task={:?}", status),
+        _ => "Item".to_string(),
+    }
+}
@@ -0,0 +1,151 @@
+//! Helpers for measuring and shortening inline content.
+//!
+//! Widths are display widths in terminal columns, as reported by
+//! `unicode-width`: most characters count as one column, wide East Asian
+//! characters as two and combining marks as none. Nodes are measured by their
+//! [`Node::plain_text`], so markup doesn't count.
+
+use crate::ast::{clone_all, Node};
+use ecow::EcoString;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Ellipsis appended to truncated content
+const ELLIPSIS: &str = "…";
+
+/// Display width of a sequence of inline nodes
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::text::display_width;
+///
+/// let nodes = [
+///     Node::Text("日本".into()),
+///     Node::Strong(vec![Node::Text("go".into())]),
+/// ];
+/// assert_eq!(display_width(&nodes), 6);
+/// ```
+pub fn display_width(nodes: &[Node]) -> usize {
+    nodes.iter().map(|node| node.plain_text().width()).sum()
+}
+
+/// Shorten inline nodes to at most `max_width` columns, ending with `…`
+///
+/// Content that already fits is returned unchanged. Otherwise the nodes are
+/// cut where the width runs out, leaving room for the ellipsis: text and code
+/// are cut between characters (never inside one), formatting and links around
+/// the cut are kept with their remaining content, and nodes that can't be cut
+/// (images, autolinks, HTML elements, custom nodes) are dropped whole.
+/// Whitespace before the ellipsis is removed.
+///
+/// Custom nodes can't be copied, so they are left out of the result even
+/// where they fit (unless they are inside a [`Node::Shared`] subtree).
+///
+/// Useful for summary table cells and previews, where slicing the rendered
+/// string could split a multi-byte character or leave markup unclosed.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::text::truncate_with_ellipsis;
+///
+/// let nodes = vec![
+///     Node::Text("Release ".into()),
+///     Node::Strong(vec![Node::Text("notes for 2.0".into())]),
+/// ];
+/// let preview = Node::Paragraph(truncate_with_ellipsis(&nodes, 14));
+/// assert_eq!(preview.to_string(), "Release **notes**…\n");
+/// ```
+pub fn truncate_with_ellipsis(nodes: &[Node], max_width: usize) -> Vec<Node> {
+    if display_width(nodes) <= max_width {
+        return clone_all(nodes);
+    }
+    let Some(mut budget) = max_width.checked_sub(ELLIPSIS.width()) else {
+        return Vec::new();
+    };
+
+    let mut truncated = truncate_nodes(nodes, &mut budget);
+    if let Some(Node::Text(text)) = truncated.last_mut() {
+        *text = text.trim_end().into();
+        if text.is_empty() {
+            truncated.pop();
+        }
+    }
+    truncated.push(Node::Text(ELLIPSIS.into()));
+    truncated
+}
+
+/// Keep the nodes that fit in `budget`, cutting the first one that doesn't
+fn truncate_nodes(nodes: &[Node], budget: &mut usize) -> Vec<Node> {
+    let mut kept = Vec::new();
+    for node in nodes {
+        let width = node.plain_text().width();
+        if width <= *budget {
+            kept.extend(node.clone_lossy());
+            *budget -= width;
+            continue;
+        }
+        if let Some(node) = truncate_node(node, budget) {
+            kept.push(node);
+        }
+        break;
+    }
+    kept
+}
+
+/// The part of `node` that fits in `budget`, if any
+fn truncate_node(node: &Node, budget: &mut usize) -> Option<Node> {
+    let wrap_children = |children: &[Node], budget: &mut usize| {
+        let children = truncate_nodes(children, budget);
+        (!children.is_empty()).then_some(children)
+    };
+    match node.resolve_shared() {
+        Node::Text(text) => truncate_str(text, budget).map(Node::Text),
+        Node::InlineCode(code) => truncate_str(code, budget).map(Node::InlineCode),
+        Node::Emphasis(children) => wrap_children(children, budget).map(Node::Emphasis),
+        Node::Strong(children) => wrap_children(children, budget).map(Node::Strong),
+        Node::Strikethrough(children) => wrap_children(children, budget).map(Node::Strikethrough),
+        Node::Link {
+            url,
+            title,
+            content,
+        } => wrap_children(content, budget).map(|content| Node::Link {
+            url: url.clone(),
+            title: title.clone(),
+            content,
+        }),
+        Node::CrossRef {
+            target,
+            text: Some(content),
+        } => wrap_children(content, budget).map(|content| Node::CrossRef {
+            target: target.clone(),
+            text: Some(content),
+        }),
+        Node::ReferenceLink { label, content } => {
+            wrap_children(content, budget).map(|content| Node::ReferenceLink {
+                label: label.clone(),
+                content,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The longest prefix of `text` that fits in `budget`, without trailing whitespace
+fn truncate_str(text: &str, budget: &mut usize) -> Option<EcoString> {
+    let mut end = 0;
+    for (index, c) in text.char_indices() {
+        let width = c.width().unwrap_or(0);
+        if width > *budget {
+            break;
+        }
+        *budget -= width;
+        end = index + c.len_utf8();
+    }
+    // Nothing after the cut fits anymore
+    *budget = 0;
+    let prefix = text[..end].trim_end();
+    (!prefix.is_empty()).then(|| prefix.into())
+}
@@ -2,6 +2,7 @@
 
 use crate::error::{WriteError, WriteResult};
 use std::any::Any;
+use std::hash::Hasher;
 
 /// Core node content trait - focused on basic properties
 pub trait NodeContent: std::fmt::Debug + Send + Sync {
@@ -27,10 +28,51 @@ pub trait NodeClone: NodeContent {
 
     /// Check equality with another node
     fn eq_box(&self, other: &dyn NodeContent) -> bool;
+
+    /// Feed the node into a hasher, used by the `Hash` implementation of `Node`
+    ///
+    /// Nodes that are equal according to [`eq_box`](Self::eq_box) must hash the
+    /// same. The default only hashes the type name, which is always consistent
+    /// but makes nodes of one type collide; implement it (or derive
+    /// `NodeClone` with `#[node_clone(hash)]`) to hash the node's content.
+    fn hash_box(&self, state: &mut dyn Hasher) {
+        state.write(self.type_name().as_bytes());
+    }
+}
+
+/// How a custom node is written to CommonMark when the writer's dialect can't
+/// express it (see [`CustomNode::supports_commonmark`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FallbackStrategy {
+    /// Embed the node's HTML rendering ([`CustomNode::html_render`])
+    #[default]
+    Html,
+    /// Write the text of the node's HTML rendering, with tags removed and
+    /// whitespace collapsed
+    PlainText,
+    /// Leave the node out, with a warning
+    Skip,
+    /// Fail with an `unsupported_custom_node` error
+    Error,
 }
 
 /// Custom node trait - now dyn compatible
 pub trait CustomNode: NodeClone + super::formatting::CommonMarkRenderable {
+    /// Whether the node can be written by `writer`, given its options
+    ///
+    /// When this returns false the writer follows [`fallback`](Self::fallback)
+    /// instead of calling `render_commonmark`. Defaults to
+    /// `supports_capability("commonmark")`.
+    fn supports_commonmark(&self, writer: &crate::writer::CommonMarkWriter) -> bool {
+        let _ = writer;
+        self.supports_capability("commonmark")
+    }
+
+    /// How the node is written when [`supports_commonmark`](Self::supports_commonmark) is false
+    fn fallback(&self) -> FallbackStrategy {
+        FallbackStrategy::Html
+    }
+
     /// Default HTML rendering implementation
     fn html_render(&self, writer: &mut crate::writer::HtmlWriter) -> WriteResult<()> {
         // Use HtmlWriter's raw_html method
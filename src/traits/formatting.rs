@@ -4,7 +4,10 @@
 //! It combines the high-level format abstraction with concrete rendering implementations.
 
 use crate::error::WriteResult;
-use crate::writer::{CommonMarkWriter, HtmlWriter};
+use crate::writer::{
+    CommonMarkWriter, HtmlWriteError, HtmlWriteResult, HtmlWriter, HtmlWriterOptions,
+};
+use ecow::EcoString;
 
 // ==== Core Rendering Traits ====
 
@@ -38,6 +41,24 @@ pub trait ToCommonMark {
 pub trait ToHtml {
     /// Format to HTML
     fn to_html(&self, writer: &mut HtmlWriter) -> WriteResult<()>;
+
+    /// Format to HTML with a new writer using `options`, returning its output
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::{HtmlWriterOptions, Node, ToHtml};
+    ///
+    /// let heading = Node::heading(1, vec![Node::Text("Title".into())]);
+    /// let options = HtmlWriterOptions::default().with_heading_level_offset(1);
+    /// assert_eq!(heading.to_html_with(&options).unwrap(), "<h2>Title</h2>\n");
+    /// ```
+    fn to_html_with(&self, options: &HtmlWriterOptions) -> HtmlWriteResult<EcoString> {
+        let mut writer = HtmlWriter::with_options(options.clone());
+        self.to_html(&mut writer)
+            .map_err(HtmlWriteError::from_write_error)?;
+        Ok(writer.into_string())
+    }
 }
 
 // ==== Automatic Implementations ====
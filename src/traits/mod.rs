@@ -7,6 +7,7 @@
 pub use self::core::*;
 pub use self::formatting::*;
 pub use self::processing::*;
+pub use self::sink::*;
 pub use self::utils::*;
 
 /// Core node and content traits
@@ -18,5 +19,8 @@ pub mod formatting;
 /// Node processing traits
 pub mod processing;
 
+/// Object-safe writer traits for plugins
+pub mod sink;
+
 /// Utility traits for error handling and configuration
 pub mod utils;
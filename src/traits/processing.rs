@@ -1,6 +1,10 @@
 //! Node processing traits
 
 use crate::error::WriteResult;
+use ecow::EcoString;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
 
 /// Node processor trait
 pub trait NodeProcessor {
@@ -38,3 +42,60 @@ pub trait InlineNodeProcessor: NodeProcessor {
     /// Validate inline content
     fn validate_inline_content(&self, node: &crate::ast::Node) -> WriteResult<()>;
 }
+
+/// Resolves `{{key}}` placeholders in `Text` nodes at render time
+///
+/// Implemented for closures and for `HashMap`s from keys to values, so a
+/// skeleton AST can be rendered with different values without mutating it.
+pub trait PlaceholderResolver: Send + Sync {
+    /// Look up the replacement for a placeholder key
+    ///
+    /// Returning `None` leaves the placeholder in the output as written.
+    fn resolve(&self, key: &str) -> Option<EcoString>;
+}
+
+impl<F> PlaceholderResolver for F
+where
+    F: Fn(&str) -> Option<EcoString> + Send + Sync,
+{
+    fn resolve(&self, key: &str) -> Option<EcoString> {
+        self(key)
+    }
+}
+
+impl<K, V, S> PlaceholderResolver for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq + Send + Sync,
+    V: AsRef<str> + Send + Sync,
+    S: BuildHasher + Send + Sync,
+{
+    fn resolve(&self, key: &str) -> Option<EcoString> {
+        self.get(key).map(|value| value.as_ref().into())
+    }
+}
+
+/// Filter run over the final CommonMark output
+///
+/// Filters are set through `WriterOptions::post_processors` and applied in order
+/// by `CommonMarkWriter::into_string()`. See [`crate::writer::filters`] for the
+/// built-in filters.
+pub trait OutputFilter: std::fmt::Debug + Send + Sync {
+    /// Transform the output
+    fn apply(&self, output: EcoString) -> EcoString;
+
+    /// Whether the filter removes trailing spaces from lines, including the
+    /// two spaces of a hard line break, which conflicts with
+    /// `WriterOptions::hard_break_spaces`
+    fn trims_trailing_spaces(&self) -> bool {
+        false
+    }
+
+    /// Clone the filter into a Box
+    fn clone_box(&self) -> Box<dyn OutputFilter>;
+}
+
+impl Clone for Box<dyn OutputFilter> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
@@ -0,0 +1,121 @@
+//! Object-safe writer interfaces for plugins
+//!
+//! Plugins loaded at runtime (dylibs, wasm hosts) can't be generic over the
+//! host's writer types. These traits expose the writer entry points a custom
+//! node needs through `dyn` references instead.
+
+use crate::ast::Node;
+use crate::error::{WriteError, WriteResult};
+use crate::options::WriterOptions;
+use crate::writer::html::HtmlWriterOptions;
+use crate::writer::{CommonMarkWriter, HtmlWriter};
+
+/// Object-safe view of a [`CommonMarkWriter`]
+pub trait DynCommonMarkSink {
+    /// Write a string without escaping
+    fn write_str(&mut self, s: &str) -> WriteResult<()>;
+
+    /// Write a character without escaping
+    fn write_char(&mut self, c: char) -> WriteResult<()>;
+
+    /// Write text, escaping it according to the writer's options
+    fn write_text(&mut self, text: &str) -> WriteResult<()>;
+
+    /// Write a node with the host's formatting
+    ///
+    /// No newline is added after the node; the calling node controls its own layout.
+    fn write_node(&mut self, node: &Node) -> WriteResult<()>;
+
+    /// The options of the writer
+    fn options(&self) -> &WriterOptions;
+
+    /// Whether the writer is in strict mode
+    fn is_strict(&self) -> bool {
+        self.options().strict
+    }
+}
+
+impl DynCommonMarkSink for CommonMarkWriter {
+    fn write_str(&mut self, s: &str) -> WriteResult<()> {
+        CommonMarkWriter::write_str(self, s)
+    }
+
+    fn write_char(&mut self, c: char) -> WriteResult<()> {
+        CommonMarkWriter::write_char(self, c)
+    }
+
+    fn write_text(&mut self, text: &str) -> WriteResult<()> {
+        self.write_text_content(text)
+    }
+
+    fn write_node(&mut self, node: &Node) -> WriteResult<()> {
+        self.write_node_content(node)
+    }
+
+    fn options(&self) -> &WriterOptions {
+        CommonMarkWriter::options(self)
+    }
+}
+
+/// Object-safe view of an [`HtmlWriter`]
+///
+/// Errors are converted to [`WriteError`] so that both sinks share one error type.
+pub trait DynHtmlSink {
+    /// Open a start tag (`<tag`), to be followed by attributes and [`finish_tag`](Self::finish_tag)
+    fn start_tag(&mut self, tag: &str) -> WriteResult<()>;
+
+    /// Write an attribute of the open start tag, escaping the value
+    fn attribute(&mut self, name: &str, value: &str) -> WriteResult<()>;
+
+    /// Close the open start tag (`>`)
+    fn finish_tag(&mut self) -> WriteResult<()>;
+
+    /// Write an end tag (`</tag>`)
+    fn end_tag(&mut self, tag: &str) -> WriteResult<()>;
+
+    /// Write escaped text
+    fn text(&mut self, text: &str) -> WriteResult<()>;
+
+    /// Write HTML without escaping
+    fn raw_html(&mut self, html: &str) -> WriteResult<()>;
+
+    /// Write a node with the host's HTML rendering
+    fn write_node(&mut self, node: &Node) -> WriteResult<()>;
+
+    /// The options of the writer
+    fn options(&self) -> &HtmlWriterOptions;
+}
+
+impl DynHtmlSink for HtmlWriter {
+    fn start_tag(&mut self, tag: &str) -> WriteResult<()> {
+        HtmlWriter::start_tag(self, tag).map_err(WriteError::from)
+    }
+
+    fn attribute(&mut self, name: &str, value: &str) -> WriteResult<()> {
+        HtmlWriter::attribute(self, name, value).map_err(WriteError::from)
+    }
+
+    fn finish_tag(&mut self) -> WriteResult<()> {
+        HtmlWriter::finish_tag(self).map_err(WriteError::from)
+    }
+
+    fn end_tag(&mut self, tag: &str) -> WriteResult<()> {
+        HtmlWriter::end_tag(self, tag).map_err(WriteError::from)
+    }
+
+    fn text(&mut self, text: &str) -> WriteResult<()> {
+        HtmlWriter::text(self, text).map_err(WriteError::from)
+    }
+
+    fn raw_html(&mut self, html: &str) -> WriteResult<()> {
+        HtmlWriter::raw_html(self, html).map_err(WriteError::from)
+    }
+
+    fn write_node(&mut self, node: &Node) -> WriteResult<()> {
+        self.write_node_internal(node).map_err(WriteError::from)
+    }
+
+    fn options(&self) -> &HtmlWriterOptions {
+        HtmlWriter::options(self)
+    }
+}
@@ -0,0 +1,234 @@
+//! Consistency checks over a document before it is written.
+//!
+//! Unlike the writers, which check one node at a time, these functions look at
+//! the document as a whole, e.g. to find links whose targets don't exist.
+
+use crate::ast::{normalize_label, slugify_with, Node, NodePath, SlugOptions, Slugger};
+use ecow::EcoString;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Kind of problem found by [`check_links`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkIssueKind {
+    /// A `ReferenceLink` whose label has no `LinkReferenceDefinition`
+    UndefinedReference,
+    /// A `#fragment` link that matches no heading slug or element `id`
+    BrokenAnchor,
+    /// A `LinkReferenceDefinition` that no `ReferenceLink` uses
+    UnusedDefinition,
+}
+
+/// A problem found by [`check_links`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkIssue {
+    /// What is wrong
+    pub kind: LinkIssueKind,
+    /// Path of the offending node, relative to the checked root
+    pub path: NodePath,
+    /// The reference label or anchor concerned
+    pub target: EcoString,
+}
+
+impl fmt::Display for LinkIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            LinkIssueKind::UndefinedReference => {
+                write!(f, "undefined reference [{}] at {}", self.target, self.path)
+            }
+            LinkIssueKind::BrokenAnchor => {
+                write!(f, "broken anchor #{} at {}", self.target, self.path)
+            }
+            LinkIssueKind::UnusedDefinition => {
+                write!(f, "unused definition [{}] at {}", self.target, self.path)
+            }
+        }
+    }
+}
+
+/// Check the links of a document for consistency
+///
+/// Reports every `ReferenceLink` without a matching definition, every link to a
+/// `#fragment` that matches neither a heading anchor (its `custom_id`, or the
+/// slug generated by [`Slugger`]) nor the `id` of an `HtmlElement`, and every
+/// definition that is never referenced. Labels are compared after
+/// [`normalize_label`], so `[Foo]` is satisfied by a definition of `[foo]`.
+/// Issues are returned in document order, with unused definitions last.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::validate::{check_links, LinkIssueKind};
+///
+/// let doc = Node::Document(vec![
+///     Node::heading(1, vec![Node::Text("Getting Started".into())]),
+///     Node::Paragraph(vec![
+///         Node::Link {
+///             url: "#getting-started".into(),
+///             title: None,
+///             content: vec![Node::Text("ok".into())],
+///         },
+///         Node::Link {
+///             url: "#missing".into(),
+///             title: None,
+///             content: vec![Node::Text("broken".into())],
+///         },
+///     ]),
+/// ]);
+///
+/// let issues = check_links(&doc);
+/// assert_eq!(issues.len(), 1);
+/// assert_eq!(issues[0].kind, LinkIssueKind::BrokenAnchor);
+/// assert_eq!(issues[0].target, "missing");
+/// ```
+pub fn check_links(root: &Node) -> Vec<LinkIssue> {
+    let mut slugger = Slugger::new();
+    let mut anchors = HashSet::new();
+    let mut definitions: Vec<(NodePath, EcoString)> = Vec::new();
+    let mut references: Vec<(NodePath, EcoString)> = Vec::new();
+    let mut fragment_links: Vec<(NodePath, EcoString)> = Vec::new();
+
+    root.walk_with_paths(|path, node| match node {
+        Node::Heading { custom_id, .. } => {
+            anchors.insert(match custom_id {
+                Some(id) => id.clone(),
+                None => slugger.slug(&node.plain_text()),
+            });
+        }
+        Node::HtmlElement(element) => {
+            if let Some(id) = element.get_attribute("id") {
+                anchors.insert(id.into());
+            }
+        }
+        Node::LinkReferenceDefinition { label, .. } => {
+            definitions.push((path.clone(), label.clone()));
+        }
+        Node::ReferenceLink { label, .. } => {
+            references.push((path.clone(), label.clone()));
+        }
+        Node::Link { url, .. } => {
+            if let Some(fragment) = url.strip_prefix('#') {
+                fragment_links.push((path.clone(), fragment.into()));
+            }
+        }
+        _ => {}
+    });
+
+    let defined: HashSet<EcoString> = definitions
+        .iter()
+        .map(|(_, label)| normalize_label(label))
+        .collect();
+    let referenced: HashSet<EcoString> = references
+        .iter()
+        .map(|(_, label)| normalize_label(label))
+        .collect();
+
+    let mut issues: Vec<LinkIssue> = references
+        .iter()
+        .filter(|(_, label)| !defined.contains(&normalize_label(label)))
+        .map(|(path, label)| LinkIssue {
+            kind: LinkIssueKind::UndefinedReference,
+            path: path.clone(),
+            target: label.clone(),
+        })
+        .chain(
+            fragment_links
+                .iter()
+                .filter(|(_, fragment)| !fragment.is_empty() && !anchors.contains(fragment))
+                .map(|(path, fragment)| LinkIssue {
+                    kind: LinkIssueKind::BrokenAnchor,
+                    path: path.clone(),
+                    target: fragment.clone(),
+                }),
+        )
+        .collect();
+    issues.sort_by(|a, b| a.path.segments().cmp(b.path.segments()));
+
+    issues.extend(
+        definitions
+            .iter()
+            .filter(|(_, label)| !referenced.contains(&normalize_label(label)))
+            .map(|(path, label)| LinkIssue {
+                kind: LinkIssueKind::UnusedDefinition,
+                path: path.clone(),
+                target: label.clone(),
+            }),
+    );
+    issues
+}
+
+/// Headings that share a slug, found by [`heading_slug_collisions`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlugCollision {
+    /// The slug the headings have in common, before any suffix is added
+    pub slug: EcoString,
+    /// Path and plain text of each heading with this slug, in document order
+    pub headings: Vec<(NodePath, EcoString)>,
+}
+
+impl fmt::Display for SlugCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "slug #{} is shared by {} headings:",
+            self.slug,
+            self.headings.len()
+        )?;
+        for (path, title) in &self.headings {
+            write!(f, " {:?} at {}", title.as_str(), path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find headings whose text produces the same slug
+///
+/// A [`Slugger`] still gives such headings distinct anchors by adding a suffix,
+/// but the suffixed anchors change whenever sections are reordered, so site
+/// generators may want to warn about them. Headings with a `custom_id` use it
+/// as their anchor instead of the slug. Collisions are returned in the order of
+/// their first heading.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::{Node, SlugOptions};
+/// use cmark_writer::validate::heading_slug_collisions;
+///
+/// let doc = Node::Document(vec![
+///     Node::heading(2, vec![Node::Text("Usage".into())]),
+///     Node::heading(2, vec![Node::Text("Options".into())]),
+///     Node::heading(2, vec![Node::Text("usage".into())]),
+/// ]);
+///
+/// let collisions = heading_slug_collisions(&doc, &SlugOptions::default());
+/// assert_eq!(collisions.len(), 1);
+/// assert_eq!(collisions[0].slug, "usage");
+/// assert_eq!(collisions[0].headings.len(), 2);
+/// ```
+pub fn heading_slug_collisions(root: &Node, options: &SlugOptions) -> Vec<SlugCollision> {
+    let mut collisions: Vec<SlugCollision> = Vec::new();
+    let mut index_by_slug: HashMap<EcoString, usize> = HashMap::new();
+
+    root.walk_with_paths(|path, node| {
+        if let Node::Heading { custom_id, .. } = node {
+            let title = node.plain_text();
+            let slug = match custom_id {
+                Some(id) => id.clone(),
+                None => slugify_with(&title, options),
+            };
+            let index = *index_by_slug.entry(slug.clone()).or_insert_with(|| {
+                collisions.push(SlugCollision {
+                    slug,
+                    headings: Vec::new(),
+                });
+                collisions.len() - 1
+            });
+            collisions[index].headings.push((path.clone(), title));
+        }
+    });
+
+    collisions.retain(|collision| collision.headings.len() > 1);
+    collisions
+}
@@ -0,0 +1,209 @@
+//! Assembling one document from fragments written for different dialects.
+
+use crate::ast::{Node, NodePath};
+use crate::capability::Capability;
+use crate::error::{WriteError, WriteResult};
+use crate::writer::{CommonMarkWriter, HtmlWriter, OutputTarget};
+use ecow::EcoString;
+use std::fmt;
+
+/// A piece of content to assemble, with the capabilities it can't do without
+#[derive(Debug, Clone)]
+pub struct Fragment {
+    /// Name of the fragment, used in errors and in the degradation report
+    pub name: EcoString,
+    /// The content; the children of a `Document` are spliced in as blocks
+    pub content: Node,
+    /// Capabilities the target must support for the fragment to be usable
+    pub requires: Vec<Capability>,
+}
+
+impl Fragment {
+    /// Create a fragment without required capabilities
+    pub fn new<S: Into<EcoString>>(name: S, content: Node) -> Self {
+        Self {
+            name: name.into(),
+            content,
+            requires: Vec::new(),
+        }
+    }
+
+    /// Require a capability of the target
+    pub fn requires(mut self, capability: Capability) -> Self {
+        self.requires.push(capability);
+        self
+    }
+}
+
+/// A node that the target writer can't express and will degrade on output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Degradation {
+    /// Name of the fragment the node came from
+    pub fragment: EcoString,
+    /// Path of the node in the assembled document
+    pub path: NodePath,
+    /// The capability the node needs
+    pub capability: Capability,
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {} (from fragment {:?}) is not supported by the target",
+            self.capability, self.path, self.fragment
+        )
+    }
+}
+
+/// The result of [`DocumentAssembler::assemble`]
+#[derive(Debug, Clone)]
+pub struct Assembly {
+    /// The merged document
+    pub document: Node,
+    /// Nodes that will be degraded when the document is written to the target,
+    /// in document order
+    pub degraded: Vec<Degradation>,
+}
+
+impl Assembly {
+    /// Whether the target can write every node of the document as is
+    pub fn is_lossless(&self) -> bool {
+        self.degraded.is_empty()
+    }
+}
+
+/// Merge fragments into one document, checking them against a target writer
+///
+/// Each fragment may declare capabilities it can't do without (say, a
+/// changelog that needs strikethrough). Assembly fails if the target doesn't
+/// support a declared capability. Any other node the target can't express is
+/// kept and reported as a [`Degradation`], so the caller can decide whether the
+/// fallback output is acceptable.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::capability::Capability;
+/// use cmark_writer::writer::assemble::{DocumentAssembler, Fragment};
+/// use cmark_writer::writer::OutputTarget;
+/// use cmark_writer::WriterOptions;
+///
+/// let intro = Node::Document(vec![Node::heading(1, vec![Node::Text("Report".into())])]);
+/// let details = Node::Paragraph(vec![Node::Strikethrough(vec![Node::Text("draft".into())])]);
+///
+/// let assembly = DocumentAssembler::new(OutputTarget::CommonMark(WriterOptions::default()))
+///     .with_fragment(Fragment::new("intro", intro))
+///     .with_fragment(Fragment::new("details", details.clone()))
+///     .assemble()
+///     .unwrap();
+/// assert_eq!(assembly.degraded.len(), 1);
+/// assert_eq!(assembly.degraded[0].capability, Capability::Strikethrough);
+/// assert_eq!(assembly.degraded[0].path.to_string(), "/1/0");
+///
+/// // A declared requirement is a hard error instead
+/// let result = DocumentAssembler::new(OutputTarget::CommonMark(WriterOptions::default()))
+///     .with_fragment(Fragment::new("details", details).requires(Capability::Strikethrough))
+///     .assemble();
+/// assert!(result.is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentAssembler {
+    target: OutputTarget,
+    fragments: Vec<Fragment>,
+}
+
+impl DocumentAssembler {
+    /// Create an assembler for documents written to `target`
+    pub fn new(target: OutputTarget) -> Self {
+        Self {
+            target,
+            fragments: Vec::new(),
+        }
+    }
+
+    /// Append a fragment
+    pub fn with_fragment(mut self, fragment: Fragment) -> Self {
+        self.fragments.push(fragment);
+        self
+    }
+
+    /// Append a fragment in place
+    pub fn push(&mut self, fragment: Fragment) {
+        self.fragments.push(fragment);
+    }
+
+    /// Whether the target writer supports `capability`
+    fn supports(&self, capability: Capability) -> bool {
+        match &self.target {
+            OutputTarget::CommonMark(options) => {
+                CommonMarkWriter::with_options(options.clone()).supports(capability)
+            }
+            OutputTarget::Html(options) => {
+                HtmlWriter::with_options(options.clone()).supports(capability)
+            }
+        }
+    }
+
+    /// Check the fragments and merge them into one document
+    ///
+    /// Fails with a `WriteError::Custom` (code `unsupported_capability`) naming
+    /// the first fragment whose declared requirements the target can't meet.
+    pub fn assemble(self) -> WriteResult<Assembly> {
+        let unsupported: Vec<Capability> = Capability::ALL
+            .iter()
+            .copied()
+            .filter(|capability| !self.supports(*capability))
+            .collect();
+
+        let mut blocks = Vec::new();
+        let mut degraded = Vec::new();
+        for fragment in self.fragments {
+            if let Some(capability) = fragment
+                .requires
+                .iter()
+                .find(|capability| unsupported.contains(capability))
+            {
+                return Err(WriteError::Custom {
+                    message: format!(
+                        "fragment {:?} requires {}, which the target doesn't support",
+                        fragment.name, capability
+                    )
+                    .into(),
+                    code: Some("unsupported_capability".into()),
+                });
+            }
+
+            let content = match fragment.content {
+                Node::Document(children) => children,
+                node => vec![node],
+            };
+            for block in content {
+                let base = NodePath::root().child(blocks.len());
+                block.walk_with_paths(|path, node| {
+                    let mut required = Vec::new();
+                    node.own_capabilities(&mut required);
+                    for capability in required {
+                        if unsupported.contains(&capability) {
+                            degraded.push(Degradation {
+                                fragment: fragment.name.clone(),
+                                path: path
+                                    .segments()
+                                    .iter()
+                                    .fold(base.clone(), |path, segment| path.with(*segment)),
+                                capability,
+                            });
+                        }
+                    }
+                });
+                blocks.push(block);
+            }
+        }
+
+        Ok(Assembly {
+            document: Node::Document(blocks),
+            degraded,
+        })
+    }
+}
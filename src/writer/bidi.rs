@@ -0,0 +1,65 @@
+//! Text direction detection for bidirectional isolation.
+
+/// Base direction of a document or text run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextDirection {
+    /// Left-to-right
+    #[default]
+    Ltr,
+    /// Right-to-left
+    Rtl,
+}
+
+impl TextDirection {
+    /// Detect the direction of `text` from its first strongly directional character
+    ///
+    /// Letters from right-to-left scripts (Hebrew, Arabic, Syriac, Thaana, N'Ko and
+    /// related blocks) are RTL; other alphabetic characters are LTR. Returns `None`
+    /// when the text has no strongly directional characters (digits, punctuation).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::writer::TextDirection;
+    ///
+    /// assert_eq!(TextDirection::detect("123 שלום"), Some(TextDirection::Rtl));
+    /// assert_eq!(TextDirection::detect("hello"), Some(TextDirection::Ltr));
+    /// assert_eq!(TextDirection::detect("42!"), None);
+    /// ```
+    pub fn detect(text: &str) -> Option<Self> {
+        text.chars().find_map(|c| {
+            if is_rtl_char(c) {
+                Some(TextDirection::Rtl)
+            } else if c.is_alphabetic() {
+                Some(TextDirection::Ltr)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The `dir` attribute value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TextDirection::Ltr => "ltr",
+            TextDirection::Rtl => "rtl",
+        }
+    }
+}
+
+/// Checks if `text` has a detectable direction different from `base`
+pub(crate) fn needs_isolation(text: &str, base: TextDirection) -> bool {
+    TextDirection::detect(text).is_some_and(|direction| direction != base)
+}
+
+/// Checks if a character is a letter from a right-to-left script
+fn is_rtl_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0590..=0x08FF // Hebrew, Arabic, Syriac, Thaana, N'Ko, Samaritan, Mandaic
+            | 0xFB1D..=0xFDFF // Hebrew and Arabic presentation forms
+            | 0xFE70..=0xFEFF // Arabic presentation forms B
+            | 0x10800..=0x10FFF // Historic RTL scripts
+            | 0x1E800..=0x1EFFF // Mende Kikakui, Adlam, Arabic mathematical symbols
+    ) && c.is_alphabetic()
+}
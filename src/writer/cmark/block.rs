@@ -1,20 +1,61 @@
 //! Block-level element writing functionality with flexible newline control.
 
 use super::CommonMarkWriter;
-use crate::ast::{CodeBlockType, HeadingType, ListItem, Node};
+use crate::ast::{CodeBlockType, DefinitionItem, HeadingType, ListItem, ListStyle, Node};
+use crate::capability::Capability;
+use crate::diagnostics;
 use crate::error::{WriteError, WriteResult};
+use crate::options::{shift_heading_level, ListRenumberingPolicy};
 use crate::writer::context::NewlineContext;
 use ecow::EcoString;
-use log;
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+/// Largest ordered list number allowed by CommonMark (at most 9 digits)
+const MAX_LIST_NUMBER: u32 = 999_999_999;
+
+/// Shortest underline written below a setext heading
+const MIN_SETEXT_UNDERLINE: usize = 3;
 
 impl CommonMarkWriter {
     /// Write a heading node
     pub fn write_heading(
+        &mut self,
+        level: u8,
+        content: &[Node],
+        heading_type: &HeadingType,
+    ) -> WriteResult<()> {
+        self.write_heading_with_id(level, content, heading_type, None)
+    }
+
+    /// Write a heading node with an optional explicit anchor id
+    ///
+    /// The id is written as an attribute block (`{#id}`) when the
+    /// `attribute_blocks` option is enabled, and dropped otherwise.
+    ///
+    /// Setext headings are underlined to the width of their longest line. A
+    /// setext heading deeper than level 2 is an error in strict mode and
+    /// written as an ATX heading otherwise.
+    pub fn write_heading_with_id(
         &mut self,
         mut level: u8,
         content: &[Node],
         heading_type: &HeadingType,
+        custom_id: Option<&str>,
     ) -> WriteResult<()> {
+        // Setext underlines only exist for levels 1 and 2
+        if *heading_type == HeadingType::Setext && (3..=6).contains(&level) {
+            if self.is_strict_mode() {
+                return Err(WriteError::InvalidStructure(
+                    format!("Setext heading of level {} (only 1 and 2 exist)", level).into(),
+                ));
+            }
+            diagnostics::warn!(
+                "Setext heading of level {} written as an ATX heading. Strict mode is off.",
+                level
+            );
+        }
+
         // Validate heading level
         if level == 0 || level > 6 {
             if self.is_strict_mode() {
@@ -22,7 +63,7 @@ impl CommonMarkWriter {
             } else {
                 let original_level = level;
                 level = level.clamp(1, 6);
-                log::warn!(
+                diagnostics::warn!(
                     "Invalid heading level: {}. Corrected to {}. Strict mode is off.",
                     original_level,
                     level
@@ -30,12 +71,31 @@ impl CommonMarkWriter {
             }
         }
 
+        let level = shift_heading_level(
+            level,
+            self.options.heading_level_offset,
+            self.options.max_heading_level,
+        );
+
+        // An empty setext heading would read as a thematic break, and setext
+        // underlines only exist for levels 1 and 2 (a level offset may have
+        // moved the heading past them)
+        let heading_type = if content.is_empty() || level > 2 {
+            &HeadingType::Atx
+        } else if self.options.setext_headings {
+            &HeadingType::Setext
+        } else {
+            heading_type
+        };
+
         match heading_type {
             HeadingType::Atx => {
                 for _ in 0..level {
                     self.write_char('#')?;
                 }
-                self.write_char(' ')?;
+                if !content.is_empty() {
+                    self.write_char(' ')?;
+                }
 
                 // Use inline context for heading content
                 self.with_temporary_context(NewlineContext::pure_inline(), |writer| {
@@ -44,8 +104,10 @@ impl CommonMarkWriter {
                     }
                     Ok(())
                 })?;
+                self.write_heading_attributes(custom_id, !content.is_empty())?;
             }
             HeadingType::Setext => {
+                let start = self.buffer.len();
                 // Use inline context for heading content
                 self.with_temporary_context(NewlineContext::pure_inline(), |writer| {
                     for node in content {
@@ -53,13 +115,19 @@ impl CommonMarkWriter {
                     }
                     Ok(())
                 })?;
-
-                self.write_char('\n')?;
+                self.write_heading_attributes(custom_id, true)?;
+
+                // As wide as the longest line of the heading, which may span
+                // several lines through hard breaks
+                let width = self.buffer[start..]
+                    .lines()
+                    .map(UnicodeWidthStr::width)
+                    .max()
+                    .unwrap_or(0)
+                    .max(MIN_SETEXT_UNDERLINE);
                 let underline_char = if level == 1 { '=' } else { '-' };
-                let min_len = 3;
-                for _ in 0..min_len {
-                    self.write_char(underline_char)?;
-                }
+                self.write_char('\n')?;
+                self.write_str(&underline_char.to_string().repeat(width))?;
             }
         }
         Ok(())
@@ -69,7 +137,10 @@ impl CommonMarkWriter {
     pub fn write_paragraph(&mut self, content: &[Node]) -> WriteResult<()> {
         // Use inline-with-blocks context to allow flexible content
         self.with_temporary_context(NewlineContext::inline_with_blocks(), |writer| {
-            writer.write_paragraph_content(content)
+            let in_paragraph = std::mem::replace(&mut writer.in_paragraph, true);
+            let result = writer.write_paragraph_content(content);
+            writer.in_paragraph = in_paragraph;
+            result
         })
     }
 
@@ -97,25 +168,28 @@ impl CommonMarkWriter {
     /// Write a blockquote node
     pub fn write_blockquote(&mut self, content: &[Node]) -> WriteResult<()> {
         // Create a temporary writer buffer to write all blockquote content
-        let mut temp_writer = CommonMarkWriter::with_context(
-            self.options.clone(),
-            NewlineContext::block(), // Use block context for blockquote content
-        );
+        // Use block context for blockquote content
+        let mut temp_writer = self.child_writer(NewlineContext::block());
 
         // Write all content to temporary buffer
-        for (i, node) in content.iter().enumerate() {
-            if i > 0 {
+        let mut first = true;
+        for node in content {
+            if temp_writer.omits_node(node) {
+                continue;
+            }
+            if !first {
                 temp_writer.write_char('\n')?;
             }
             temp_writer.write_node(node)?;
+            first = false;
         }
 
         // Get the content and apply blockquote prefix
-        let blockquote_content = temp_writer.into_string();
+        let blockquote_content = self.take_child_buffer(temp_writer);
         let formatted_content = self.apply_prefix(&blockquote_content, "> ", Some("> "));
 
         // Write formatted content
-        self.buffer.push_str(&formatted_content);
+        self.push_raw(&formatted_content);
         Ok(())
     }
 
@@ -126,17 +200,26 @@ impl CommonMarkWriter {
         content: &str,
         block_type: &CodeBlockType,
     ) -> WriteResult<()> {
+        let content = self.sanitize_control_chars(Cow::Borrowed(content), "CodeBlock")?;
+        let content = self.expand_code_tabs(content);
+        let content = content.as_ref();
         match block_type {
             CodeBlockType::Fenced => {
+                let info = match language {
+                    Some(lang) => self.sanitize_info_string(lang)?,
+                    None => Cow::Borrowed(""),
+                };
+                // Backtick fences can't carry backticks in their info string
+                let fence_char = if info.contains('`') { '~' } else { '`' };
+                let fence = code_fence(content, fence_char);
+
                 // Write opening fence
-                self.write_str("```")?;
-                if let Some(lang) = language {
-                    self.write_str(lang)?;
-                }
+                self.write_str(&fence)?;
+                self.write_str(&info)?;
                 self.write_char('\n')?;
 
-                // Write content (no processing needed for code blocks)
-                self.write_str(content)?;
+                // Write content verbatim, bypassing blank line collapsing
+                self.push_raw(content);
 
                 // Ensure content ends with newline before closing fence
                 if !content.ends_with('\n') {
@@ -144,22 +227,70 @@ impl CommonMarkWriter {
                 }
 
                 // Write closing fence
-                self.write_str("```")?;
+                self.write_str(&fence)?;
             }
             CodeBlockType::Indented => {
                 // Apply 4-space indentation to each line
                 let indented_content = self.apply_prefix(content, "    ", Some("    "));
-                self.buffer.push_str(&indented_content);
+                self.push_raw(&indented_content);
 
                 // Remove trailing newline if present (context will handle it)
                 if self.buffer.ends_with('\n') {
-                    self.buffer.pop();
+                    self.pop_raw();
                 }
             }
         }
         Ok(())
     }
 
+    /// Write the attribute block of a heading with an explicit id, if enabled
+    fn write_heading_attributes(
+        &mut self,
+        custom_id: Option<&str>,
+        spaced: bool,
+    ) -> WriteResult<()> {
+        let Some(id) = custom_id.filter(|_| self.supports(Capability::HeadingIds)) else {
+            return Ok(());
+        };
+        if id.is_empty() || id.contains(|c: char| c.is_whitespace() || c == '}') {
+            if self.is_strict_mode() {
+                return Err(WriteError::InvalidStructure(
+                    format!("Invalid heading id for an attribute block: {:?}", id).into(),
+                ));
+            }
+            diagnostics::warn!(
+                "Heading id {:?} can't be written as an attribute block, dropped",
+                id
+            );
+            return Ok(());
+        }
+        self.write_str(if spaced { " {#" } else { "{#" })?;
+        self.write_str(id)?;
+        self.write_char('}')
+    }
+
+    /// Check a code block info string, which must fit on the fence line
+    ///
+    /// Line breaks are an error in strict mode and are replaced with spaces otherwise.
+    fn sanitize_info_string<'a>(&self, info: &'a str) -> WriteResult<Cow<'a, str>> {
+        if !info.contains(['\n', '\r']) {
+            return Ok(Cow::Borrowed(info));
+        }
+        if self.is_strict_mode() {
+            return Err(WriteError::InvalidStructure(
+                format!("Code block info string contains a line break: {:?}", info).into(),
+            ));
+        }
+        diagnostics::warn!("Line break in code block info string replaced with a space");
+        let single_line = info
+            .split(['\n', '\r'])
+            .filter(|part| !part.trim().is_empty())
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(Cow::Owned(single_line))
+    }
+
     /// Write an unordered list
     pub fn write_unordered_list(&mut self, items: &[ListItem]) -> WriteResult<()> {
         self.with_temporary_context(NewlineContext::list_item(), |writer| {
@@ -183,46 +314,113 @@ impl CommonMarkWriter {
         self.with_temporary_context(NewlineContext::list_item(), |writer| {
             let mut current_number = start;
             for (i, item) in items.iter().enumerate() {
-                if i > 0 {
-                    if tight {
-                        writer.write_char('\n')?;
-                    } else {
-                        writer.write_str("\n\n")?;
-                    }
-                }
-
                 // Check if this item has a custom number
-                let number = match item {
+                let mut number = match item {
                     ListItem::Ordered {
                         number: Some(custom_num),
                         ..
-                    } => {
-                        current_number = *custom_num;
-                        current_number
-                    }
+                    } => *custom_num,
                     _ => {
                         // Use current sequential number
                         current_number
                     }
                 };
 
+                // A number that goes backwards is lost when the list is re-parsed
+                let mut split = false;
+                if i > 0 && number < current_number {
+                    match writer.options.list_renumbering_policy {
+                        ListRenumberingPolicy::Warn => {
+                            diagnostics::warn!(
+                                "Ordered list item number {} follows {}; it won't survive re-parsing",
+                                number,
+                                current_number - 1
+                            );
+                        }
+                        ListRenumberingPolicy::Renumber => number = current_number,
+                        ListRenumberingPolicy::Split => split = true,
+                    }
+                }
+
+                if split {
+                    writer.write_str("\n\n<!-- -->\n\n")?;
+                } else if i > 0 {
+                    if tight {
+                        writer.write_char('\n')?;
+                    } else {
+                        writer.write_str("\n\n")?;
+                    }
+                }
+                let number = writer.check_list_number(number)?;
+
                 writer.write_list_item(item, Some(number))?;
-                current_number += 1; // Increment for next item
+                current_number = number.saturating_add(1); // Increment for next item
             }
             Ok(())
         })
     }
 
+    /// Write a single list item on its own, as it would appear in a list of `style`
+    ///
+    /// The output is the item's marker, content and continuation indentation,
+    /// without a trailing newline, so rendered items can be cached and joined
+    /// into a list later.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{ListItem, ListStyle, Node};
+    /// use cmark_writer::CommonMarkWriter;
+    ///
+    /// let item = ListItem::Unordered {
+    ///     content: vec![Node::Paragraph(vec![Node::Text("first".into())])],
+    /// };
+    /// let mut writer = CommonMarkWriter::new();
+    /// writer.write_list_item_public(&item, ListStyle::Ordered(3)).unwrap();
+    /// assert_eq!(writer.into_string(), "3. first");
+    /// ```
+    pub fn write_list_item_public(&mut self, item: &ListItem, style: ListStyle) -> WriteResult<()> {
+        let number = match style {
+            ListStyle::Unordered => None,
+            ListStyle::Ordered(number) => Some(self.check_list_number(number)?),
+        };
+        self.with_temporary_context(NewlineContext::list_item(), |writer| {
+            writer.write_list_item(item, number)
+        })
+    }
+
+    /// Check an ordered list number against the CommonMark 9-digit limit
+    ///
+    /// Returns an error in strict mode; otherwise clamps the number with a warning.
+    fn check_list_number(&self, number: u32) -> WriteResult<u32> {
+        if number <= MAX_LIST_NUMBER {
+            return Ok(number);
+        }
+        if self.is_strict_mode() {
+            return Err(WriteError::InvalidStructure(
+                format!(
+                    "Ordered list number {} exceeds the CommonMark limit of {}",
+                    number, MAX_LIST_NUMBER
+                )
+                .into(),
+            ));
+        }
+        diagnostics::warn!(
+            "Ordered list number {} exceeds the CommonMark limit. Clamped to {}. Strict mode is off.",
+            number,
+            MAX_LIST_NUMBER
+        );
+        Ok(MAX_LIST_NUMBER)
+    }
+
     /// Write a list item
     fn write_list_item(&mut self, item: &ListItem, number: Option<u32>) -> WriteResult<()> {
         match item {
             ListItem::Unordered { content } => {
                 if let Some(num) = number {
                     // In ordered list context, treat unordered items as ordered
-                    self.write_str(&format!("{}. ", num))?;
-                    let indent = format!("{}. ", num).len();
-                    let indent_str = " ".repeat(indent);
-                    self.write_list_item_content(content, &indent_str)?;
+                    let indent = self.write_ordinal_marker(num)?;
+                    self.write_list_item_content(content, &" ".repeat(indent))?;
                 } else {
                     // In unordered list context, use unordered marker
                     let marker = self.options.list_marker;
@@ -236,15 +434,13 @@ impl CommonMarkWriter {
                 content,
             } => {
                 let actual_number = number.or(*item_num).unwrap_or(1);
-                self.write_str(&format!("{}. ", actual_number))?;
-                let indent = format!("{}. ", actual_number).len();
-                let indent_str = " ".repeat(indent);
-                self.write_list_item_content(content, &indent_str)?;
+                let indent = self.write_ordinal_marker(actual_number)?;
+                self.write_list_item_content(content, &" ".repeat(indent))?;
             }
             #[cfg(feature = "gfm")]
             ListItem::Task { status, content } => {
                 // Check if GFM task lists are enabled at runtime
-                if self.options.gfm_tasklists {
+                if self.supports(Capability::TaskList) {
                     let checkbox = match status {
                         crate::ast::TaskListStatus::Checked => "[x]",
                         crate::ast::TaskListStatus::Unchecked => "[ ]",
@@ -252,12 +448,10 @@ impl CommonMarkWriter {
                     // Use appropriate prefix based on list type
                     if let Some(num) = number {
                         // Ordered list
-                        self.write_str(&format!("{}. ", num))?;
+                        let indent = self.write_ordinal_marker(num)? + 4; // +4 for "[ ] "
                         self.write_str(checkbox)?;
                         self.write_char(' ')?;
-                        let indent = format!("{}. ", num).len() + 4; // +4 for "[ ] "
-                        let indent_str = " ".repeat(indent);
-                        self.write_list_item_content(content, &indent_str)?;
+                        self.write_list_item_content(content, &" ".repeat(indent))?;
                     } else {
                         // Unordered list
                         self.write_str("- ")?;
@@ -269,10 +463,8 @@ impl CommonMarkWriter {
                     // When GFM task lists are disabled, render as regular list items
                     if let Some(num) = number {
                         // Ordered list
-                        self.write_str(&format!("{}. ", num))?;
-                        let indent = format!("{}. ", num).len();
-                        let indent_str = " ".repeat(indent);
-                        self.write_list_item_content(content, &indent_str)?;
+                        let indent = self.write_ordinal_marker(num)?;
+                        self.write_list_item_content(content, &" ".repeat(indent))?;
                     } else {
                         // Unordered list
                         let marker = self.options.list_marker;
@@ -286,6 +478,110 @@ impl CommonMarkWriter {
         Ok(())
     }
 
+    /// Write the marker of ordered list item `number` and the space after it,
+    /// returning its display width for indenting the item's content
+    ///
+    /// Always ASCII: CommonMark only recognizes ASCII digits as list markers,
+    /// so [`HtmlWriterOptions::ordinals`](crate::HtmlWriterOptions::ordinals)
+    /// has no counterpart here.
+    fn write_ordinal_marker(&mut self, number: u32) -> WriteResult<usize> {
+        let marker = format!("{}. ", number);
+        self.write_str(&marker)?;
+        Ok(marker.width())
+    }
+
+    /// Write a definition list
+    ///
+    /// Each term is written on its own line, followed by its definitions, each
+    /// starting with `: ` and indented by four spaces on continuation lines:
+    ///
+    /// ```text
+    /// Term
+    /// : Definition
+    /// ```
+    ///
+    /// Items are separated by blank lines. Without the `definition_lists`
+    /// option, each term is written as a paragraph of strong text followed by
+    /// its definitions.
+    pub fn write_definition_list(&mut self, items: &[DefinitionItem]) -> WriteResult<()> {
+        if !self.supports(Capability::DefinitionList) {
+            return self.write_definition_list_fallback(items);
+        }
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.write_str("\n\n")?;
+            }
+            self.write_definition_item(item)?;
+        }
+        Ok(())
+    }
+
+    /// Write the terms of a definition list as strong paragraphs, each
+    /// followed by the blocks of its definitions
+    fn write_definition_list_fallback(&mut self, items: &[DefinitionItem]) -> WriteResult<()> {
+        let mut first = true;
+        for item in items {
+            let definitions = item.definitions.iter().filter(|d| !d.is_empty());
+            if !item.term.is_empty() {
+                for node in &item.term {
+                    self.check_no_newline(node, "Definition Term")?;
+                }
+                if !first {
+                    self.write_str("\n\n")?;
+                }
+                self.write_strong(&item.term)?;
+                first = false;
+            }
+            for definition in definitions {
+                if !first {
+                    self.write_str("\n\n")?;
+                }
+                self.write_list_item_content(definition, "")?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a term and its definitions
+    ///
+    /// An item without a term or without definitions doesn't read back as a
+    /// definition list item; this is an error in strict mode and written as is
+    /// otherwise.
+    fn write_definition_item(&mut self, item: &DefinitionItem) -> WriteResult<()> {
+        if item.term.is_empty() || item.definitions.is_empty() {
+            let missing = if item.term.is_empty() {
+                "term"
+            } else {
+                "definitions"
+            };
+            if self.is_strict_mode() {
+                return Err(WriteError::InvalidStructure(
+                    format!("Definition list item without {}", missing).into(),
+                ));
+            }
+            diagnostics::warn!("Definition list item without {}", missing);
+        }
+        for node in &item.term {
+            self.check_no_newline(node, "Definition Term")?;
+        }
+        for node in &item.term {
+            self.write_node_content(node)?;
+        }
+        for (index, definition) in item.definitions.iter().enumerate() {
+            if index > 0 || !item.term.is_empty() {
+                self.write_char('\n')?;
+            }
+            self.write_char(':')?;
+            if definition.is_empty() {
+                continue;
+            }
+            self.write_char(' ')?;
+            self.write_list_item_content(definition, "    ")?;
+        }
+        Ok(())
+    }
+
     /// Write list item content with proper indentation
     fn write_list_item_content(
         &mut self,
@@ -293,57 +589,89 @@ impl CommonMarkWriter {
         continuation_indent: &str,
     ) -> WriteResult<()> {
         // Create temporary writer for list item content
-        let mut temp_writer = CommonMarkWriter::with_context(
-            self.options.clone(),
-            NewlineContext::list_item(), // Use list item context for proper spacing
-        );
-
-        // Write first node directly (inline with the marker)
-        if let Some(first_node) = content.first() {
-            temp_writer.write_node_content(first_node)?;
-
-            // Handle remaining nodes with proper block spacing
-            if content.len() > 1 {
-                for node in &content[1..] {
-                    if node.is_block() {
-                        temp_writer.write_str("\n\n")?; // Add blank line before block elements
-                    } else {
-                        temp_writer.write_char('\n')?;
-                    }
-                    temp_writer.write_node_content(node)?;
+        // Use list item context for proper spacing
+        let mut temp_writer = self.child_writer(NewlineContext::list_item());
+        // The first node continues the line holding the marker
+        temp_writer.line_prefix = self.current_line();
+
+        // Write first node directly (inline with the marker), then the remaining
+        // nodes with proper block spacing
+        let mut previous: Option<&Node> = None;
+        for node in content {
+            if temp_writer.omits_node(node) {
+                continue;
+            }
+            if let Some(previous) = previous {
+                // A list may interrupt inline content, which keeps the item tight
+                // (e.g. a nested table of contents)
+                let interrupts_inline = !previous.is_block() && can_interrupt_paragraph(node);
+                if node.is_block() && !interrupts_inline {
+                    temp_writer.write_str("\n\n")?; // Add blank line before block elements
+                } else {
+                    temp_writer.write_char('\n')?;
                 }
             }
+            temp_writer.write_node_content(node)?;
+            previous = Some(node);
         }
 
         // Get content and apply continuation indentation
-        let item_content = temp_writer.into_string();
+        let item_content = self.take_child_buffer(temp_writer);
         if item_content.is_empty() {
             return Ok(());
         }
 
         // Apply indentation to continuation lines
         let formatted_content = self.apply_prefix(&item_content, continuation_indent, Some(""));
-        self.buffer.push_str(&formatted_content);
+        self.push_raw(&formatted_content);
 
         Ok(())
     }
 
     /// Write a thematic break
+    ///
+    /// After list markers of the same character (`- ---`), the whole line would
+    /// re-parse as a single thematic break, so another character is used there.
     pub fn write_thematic_break(&mut self) -> WriteResult<()> {
-        let char = self.options.thematic_break_char;
+        let line = self.current_line();
+        let mut markers = line.chars().filter(|c| !c.is_whitespace()).peekable();
+        let only_markers = markers
+            .peek()
+            .copied()
+            .filter(|&first| markers.all(|c| c == first));
+
+        let mut char = self.options.thematic_break_char;
+        if only_markers == Some(char) {
+            char = ['-', '*', '_']
+                .into_iter()
+                .find(|&c| c != char)
+                .unwrap_or(char);
+        }
         for _ in 0..3 {
             self.write_char(char)?;
         }
         Ok(())
     }
 
+    /// The output line being written, including text written on it by enclosing writers
+    pub(super) fn current_line(&self) -> EcoString {
+        match self.buffer.rfind('\n') {
+            Some(newline) => self.buffer[newline + 1..].into(),
+            None => {
+                let mut line = self.line_prefix.clone();
+                line.push_str(&self.buffer);
+                line
+            }
+        }
+    }
+
     /// Write an HTML block
     pub fn write_html_block(&mut self, content: &str) -> WriteResult<()> {
-        self.buffer.push_str(content);
+        self.write_str(content)?;
 
         // Context will handle trailing newline appropriately
         if self.buffer.ends_with('\n') {
-            self.buffer.pop(); // Remove it so context can decide
+            self.pop_raw(); // Remove it so context can decide
         }
 
         Ok(())
@@ -360,15 +688,64 @@ impl CommonMarkWriter {
         self.write_char('[')?;
         self.write_str(label)?;
         self.write_str("]: ")?;
-        self.write_str(destination)?;
+        self.write_link_destination(destination)?;
 
         if let Some(title_text) = title {
-            self.write_str(" \"")?;
-            self.write_str(title_text)?;
-            self.write_char('"')?;
+            self.write_link_title(title_text)?;
         }
 
         // Don't add explicit trailing newline - let the context system handle it
         Ok(())
     }
+
+    /// Write a footnote definition
+    ///
+    /// The first block follows the `[^label]: ` marker, the remaining lines are
+    /// indented by four spaces so they stay part of the footnote. Without the
+    /// `footnotes` option, the marker is written as `\[label\]: ` text and the
+    /// remaining blocks follow unindented.
+    pub fn write_footnote_definition(&mut self, label: &str, content: &[Node]) -> WriteResult<()> {
+        self.check_footnote_label(label)?;
+        let indent = if self.supports(Capability::Footnotes) {
+            self.write_str("[^")?;
+            self.write_str(label)?;
+            self.write_str("]:")?;
+            "    "
+        } else {
+            self.write_footnote_fallback(label)?;
+            self.write_char(':')?;
+            ""
+        };
+        if content.is_empty() {
+            return Ok(());
+        }
+        self.write_char(' ')?;
+        self.write_list_item_content(content, indent)
+    }
+}
+
+/// Build a code fence of `fence_char` longer than any fence-like run that
+/// starts a line of `content`, so the content can't close the block early
+fn code_fence(content: &str, fence_char: char) -> String {
+    let longest = content
+        .lines()
+        .map(|line| {
+            line.trim_start_matches(' ')
+                .chars()
+                .take_while(|&c| c == fence_char)
+                .count()
+        })
+        .max()
+        .unwrap_or(0);
+    fence_char.to_string().repeat(longest.max(2) + 1)
+}
+
+/// Whether `node` starts a list that may interrupt a paragraph: any non-empty
+/// bullet list, or an ordered list starting at 1
+fn can_interrupt_paragraph(node: &Node) -> bool {
+    match node.resolve_shared() {
+        Node::UnorderedList(items) => !items.is_empty(),
+        Node::OrderedList { start, items } => *start == 1 && !items.is_empty(),
+        _ => false,
+    }
 }
@@ -1,22 +1,21 @@
-//! HTML fallback handling for tables with block elements.
+//! HTML fallback handling for tables with block elements and empty containers.
 
 use super::CommonMarkWriter;
-use crate::ast::Node;
+use crate::ast::{CustomNode, Node};
 use crate::error::{WriteError, WriteResult};
+use crate::options::{EmptyContainerPolicy, FinalNewline};
+use crate::writer::html::{HtmlOutputMode, HtmlWriter, HtmlWriterOptions};
+use ecow::EcoString;
 
 #[cfg(feature = "gfm")]
 use crate::ast::TableAlignment;
 
 impl CommonMarkWriter {
-    /// Write a table as HTML (fallback for tables with block-level elements)
-    pub(super) fn write_table_as_html(
-        &mut self,
-        headers: &[Node],
-        rows: &[Vec<Node>],
-    ) -> WriteResult<()> {
-        use crate::writer::html::{HtmlWriter, HtmlWriterOptions};
-
-        let html_options = if let Some(ref custom_options) = self.options.html_writer_options {
+    /// HTML writer options used for HTML embedded in CommonMark output
+    ///
+    /// Uses `html_writer_options` when set, otherwise derives them from the CommonMark options.
+    pub(super) fn html_writer_options(&self) -> HtmlWriterOptions {
+        let options = if let Some(ref custom_options) = self.options.html_writer_options {
             custom_options.clone()
         } else {
             HtmlWriterOptions {
@@ -26,25 +25,104 @@ impl CommonMarkWriter {
                 enable_gfm: self.options.enable_gfm,
                 #[cfg(feature = "gfm")]
                 gfm_disallowed_html_tags: self.options.gfm_disallowed_html_tags.clone(),
+                empty_container_policy: self.options.empty_container_policy,
+                placeholders: self.options.placeholders.clone(),
+                autolink_default_scheme: self.options.autolink_default_scheme.clone(),
+                soft_break_style: self.options.soft_break_style,
+                heading_level_offset: self.options.heading_level_offset,
+                max_heading_level: self.options.max_heading_level,
+                ..Default::default()
             }
         };
 
-        let mut html_writer = HtmlWriter::with_options(html_options);
+        // Embedded HTML is spliced into the CommonMark buffer as written
+        HtmlWriterOptions {
+            final_newline: FinalNewline::Preserve,
+            output_mode: HtmlOutputMode::Fragment,
+            trust_custom_nodes: options.trust_custom_nodes && self.options.trust_custom_nodes,
+            ..options
+        }
+    }
 
-        // Create table node for HTML writer
-        let table_node = Node::Table {
-            headers: headers.to_vec(),
-            #[cfg(feature = "gfm")]
-            alignments: vec![],
-            rows: rows.to_vec(),
-        };
+    /// HTML writer for a fallback, sharing this writer's numbering and heading anchors
+    pub(super) fn html_fallback_writer(&self, options: HtmlWriterOptions) -> HtmlWriter {
+        let mut html_writer = HtmlWriter::with_options(options);
+        html_writer.share_numbering(&self.numbering);
+        html_writer.share_heading_anchors(self.heading_anchors.as_ref());
+        html_writer
+    }
+
+    /// Write an empty container as an empty HTML element
+    ///
+    /// Used as the placeholder for containers that have no CommonMark syntax when empty.
+    pub(super) fn write_empty_container_as_html(&mut self, node: &Node) -> WriteResult<()> {
+        let options = self
+            .html_writer_options()
+            .with_empty_container_policy(EmptyContainerPolicy::Placeholder);
+        let mut html_writer = self.html_fallback_writer(options);
+        html_writer
+            .write_node_internal(node)
+            .map_err(WriteError::HtmlRenderingError)?;
+
+        let html_output = html_writer.into_string();
+        self.push_raw(html_output.trim_end_matches('\n'));
+        Ok(())
+    }
+
+    /// Render a custom node with its HTML rendering, for its fallback
+    fn render_custom_node_html(&self, node: &dyn CustomNode) -> WriteResult<EcoString> {
+        let mut html_writer = self.html_fallback_writer(self.html_writer_options());
+        node.html_render(&mut html_writer)?;
+        Ok(html_writer.into_string())
+    }
+
+    /// Write a custom node as embedded HTML
+    pub(super) fn write_custom_node_as_html(&mut self, node: &dyn CustomNode) -> WriteResult<()> {
+        let html_output = self.render_custom_node_html(node)?;
+        if node.is_block() {
+            // Like an HTML block, the context decides on the trailing newline
+            self.write_str(html_output.trim_end_matches('\n'))
+        } else {
+            self.write_str(&html_output)
+        }
+    }
+
+    /// Write the text content of a custom node's HTML rendering
+    pub(super) fn write_custom_node_as_text(&mut self, node: &dyn CustomNode) -> WriteResult<()> {
+        let html_output = self.render_custom_node_html(node)?;
+        let text = html_text_content(&html_output);
+        if !self.options.trust_custom_nodes {
+            return self.write_untrusted_output(&text, node.is_block());
+        }
+        if node.is_block() {
+            self.write_paragraph(&[Node::Text(text)])
+        } else {
+            self.write_text_content(&text)
+        }
+    }
+
+    /// Write a table as HTML (fallback for tables with block-level elements)
+    pub(super) fn write_table_as_html(
+        &mut self,
+        headers: &[Node],
+        rows: &[Vec<Node>],
+    ) -> WriteResult<()> {
+        let mut html_writer = self.html_fallback_writer(self.html_writer_options());
 
-        html_writer.write_node_internal(&table_node).map_err(|_| {
-            WriteError::HtmlFallbackError("Failed to write table as HTML".to_string().into())
-        })?;
+        // Render the cells in place rather than building a cloned table node
+        html_writer
+            .write_table_node(
+                headers,
+                #[cfg(feature = "gfm")]
+                &[],
+                rows,
+            )
+            .map_err(|_| {
+                WriteError::HtmlFallbackError("Failed to write table as HTML".to_string().into())
+            })?;
 
         let html_output = html_writer.into_string();
-        self.buffer.push_str(&html_output);
+        self.push_raw(&html_output);
 
         Ok(())
     }
@@ -57,37 +135,43 @@ impl CommonMarkWriter {
         alignments: &[TableAlignment],
         rows: &[Vec<Node>],
     ) -> WriteResult<()> {
-        use crate::writer::html::{HtmlWriter, HtmlWriterOptions};
+        let mut html_writer = self.html_fallback_writer(self.html_writer_options());
 
-        let html_options = if let Some(ref custom_options) = self.options.html_writer_options {
-            custom_options.clone()
-        } else {
-            HtmlWriterOptions {
-                strict: self.options.strict,
-                code_block_language_class_prefix: Some("language-".into()),
-                #[cfg(feature = "gfm")]
-                enable_gfm: self.options.enable_gfm,
-                #[cfg(feature = "gfm")]
-                gfm_disallowed_html_tags: self.options.gfm_disallowed_html_tags.clone(),
-            }
-        };
-
-        let mut html_writer = HtmlWriter::with_options(html_options);
-
-        // Create table node for HTML writer
-        let table_node = Node::Table {
-            headers: headers.to_vec(),
-            alignments: alignments.to_vec(),
-            rows: rows.to_vec(),
-        };
-
-        html_writer.write_node_internal(&table_node).map_err(|_| {
-            WriteError::HtmlFallbackError("Failed to write GFM table as HTML".to_string().into())
-        })?;
+        html_writer
+            .write_table_node(headers, alignments, rows)
+            .map_err(|_| {
+                WriteError::HtmlFallbackError(
+                    "Failed to write GFM table as HTML".to_string().into(),
+                )
+            })?;
 
         let html_output = html_writer.into_string();
-        self.buffer.push_str(&html_output);
+        self.push_raw(&html_output);
 
         Ok(())
     }
 }
+
+/// Text content of an HTML fragment: tags removed, entities decoded and
+/// whitespace collapsed to single spaces
+fn html_text_content(html: &str) -> EcoString {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = html_escape::decode_html_entities(&text);
+    let mut collapsed = EcoString::new();
+    for word in text.split_whitespace() {
+        if !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(word);
+    }
+    collapsed
+}
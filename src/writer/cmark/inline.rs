@@ -1,50 +1,327 @@
 //! Inline element writing functionality.
 
-use super::utils::{escape_str, CommonMarkEscapes};
+use super::utils::{
+    escape_block_starts, escape_contextual, escape_str, is_disallowed_control, CommonMarkEscapes,
+};
 use super::CommonMarkWriter;
-use crate::ast::Node;
+use crate::ast::{autolink_parts, Node};
+use crate::capability::Capability;
+use crate::diagnostics;
 use crate::error::{WriteError, WriteResult};
+use crate::ops::resolve_cross_ref;
+use crate::options::{
+    ControlCharPolicy, EscapeStrategy, MathDelimiters, SoftBreakStyle, TabPolicy,
+};
+use crate::writer::bidi::needs_isolation;
 use ecow::EcoString;
-use log;
+use std::borrow::Cow;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 impl CommonMarkWriter {
+    /// Writes a reference to a numbered item as text, e.g. "Figure 3"
+    ///
+    /// An id without a number is an error in strict mode and written as `??`
+    /// otherwise.
+    pub fn write_number_ref(&mut self, kind: &str, id: &str) -> WriteResult<()> {
+        match self.numbering().reference_text(kind, id) {
+            Some(text) => self.write_text_content(&text),
+            None if self.options.strict => Err(WriteError::InvalidStructure(
+                format!("Unresolved number reference to {} '{}'", kind, id).into(),
+            )),
+            None => {
+                diagnostics::warn!("Unresolved number reference to {} '{}'", kind, id);
+                self.write_text_content("??")
+            }
+        }
+    }
+
+    /// Writes a link to the heading `target` names, e.g. `[Usage](#usage)`
+    ///
+    /// Without `text`, the link text is the text of the heading. A target that
+    /// names no heading of the document is an error in strict mode and written
+    /// as plain link text otherwise; a text shared by several headings links to
+    /// the first of them.
+    pub fn write_cross_ref(&mut self, target: &str, text: Option<&[Node]>) -> WriteResult<()> {
+        let heading = resolve_cross_ref(self.heading_anchors(), target, self.options.strict)
+            .map_err(|message| WriteError::InvalidStructure(message.into()))?;
+        let Some(heading) = heading else {
+            return match text {
+                Some(content) => content
+                    .iter()
+                    .try_for_each(|node| self.write_node_content(node)),
+                None => self.write_text_content(target),
+            };
+        };
+        let url: EcoString = format!("#{}", heading.anchor).into();
+        match text {
+            Some(content) => self.write_link(&url, &None, content),
+            None => {
+                let content = [Node::Text(heading.text.clone())];
+                self.write_link(&url, &None, &content)
+            }
+        }
+    }
+
     /// Writes text content with character escaping
     pub fn write_text_content(&mut self, content: &str) -> WriteResult<()> {
-        if self.options.escape_special_chars {
-            let escaped = escape_str::<CommonMarkEscapes>(content);
-            self.write_str(&escaped)?
-        } else {
-            self.write_str(content)?
+        // Substitute placeholders first so resolved values are escaped too
+        let content = match &self.options.placeholders {
+            Some(placeholders) => placeholders.substitute(content),
+            None => Cow::Borrowed(content),
+        };
+        let content = self.sanitize_control_chars(content, "Text")?;
+        #[cfg(feature = "unicode-normalization")]
+        let content = self.normalize_unicode(content);
+
+        let isolate = self.options.bidi_isolation
+            && needs_isolation(&content, self.options.document_direction);
+        if isolate {
+            self.write_char('\u{2068}')?;
         }
 
+        let content = match (
+            self.options.escape_special_chars,
+            self.options.escape_strategy,
+        ) {
+            (false, _) => content,
+            (true, EscapeStrategy::Contextual) => {
+                let preceding = self.buffer.chars().next_back();
+                escape_contextual(&content, preceding)
+            }
+            (true, EscapeStrategy::Always) => escape_str::<CommonMarkEscapes>(&content),
+        };
+        let content = if self.in_paragraph {
+            let at_line_start = self.buffer.is_empty() || self.buffer.ends_with('\n');
+            escape_block_starts(&content, at_line_start)
+        } else {
+            Cow::Borrowed(content.as_ref())
+        };
+        let content = self.expand_text_tabs(content);
+        self.write_str(&content)?;
+
+        if isolate {
+            self.write_char('\u{2069}')?;
+        }
         Ok(())
     }
 
+    /// Apply the `unicode_normalization` option to text content
+    #[cfg(feature = "unicode-normalization")]
+    fn normalize_unicode<'a>(&self, content: Cow<'a, str>) -> Cow<'a, str> {
+        let Some(form) = self.options.unicode_normalization else {
+            return content;
+        };
+        let normalized = match form.normalize(&content) {
+            Cow::Owned(normalized) => Some(normalized),
+            Cow::Borrowed(_) => None,
+        };
+        normalized.map_or(content, Cow::Owned)
+    }
+
+    /// Apply the `tab_policy` option to escaped text about to be written
+    ///
+    /// Whitespace at the start of a line would be read as indentation, and a
+    /// tab there can turn the line into an indented code block, so leading
+    /// tabs become `&#9;` character references instead of spaces. The start
+    /// of a line is where its content starts, after the markers of enclosing
+    /// list items, which count towards the indentation as well.
+    fn expand_text_tabs<'a>(&self, content: Cow<'a, str>) -> Cow<'a, str> {
+        let TabPolicy::ExpandToSpaces(width) = self.options.tab_policy else {
+            return content;
+        };
+        if !content.contains('\t') {
+            return content;
+        }
+        let width = width.max(1);
+        let mut column = self.current_line().width();
+        // Markers of enclosing containers are in the line prefix, not the buffer
+        let content_start = match self.buffer.rfind('\n') {
+            Some(newline) => &self.buffer[newline + 1..],
+            None => &self.buffer[..],
+        };
+        let mut leading = content_start.chars().all(|c| c == ' ' || c == '\t');
+        let mut expanded = String::with_capacity(content.len() + width);
+        for c in content.chars() {
+            match c {
+                '\t' if leading => {
+                    expanded.push_str("&#9;");
+                    column += 4;
+                }
+                '\t' => {
+                    let spaces = width - column % width;
+                    expanded.extend(std::iter::repeat(' ').take(spaces));
+                    column += spaces;
+                }
+                '\n' => {
+                    expanded.push(c);
+                    column = 0;
+                    leading = true;
+                }
+                ' ' => {
+                    expanded.push(c);
+                    column += 1;
+                }
+                _ => {
+                    expanded.push(c);
+                    column += c.width().unwrap_or(0);
+                    leading = false;
+                }
+            }
+        }
+        Cow::Owned(expanded)
+    }
+
+    /// Apply the `tab_policy` option to code, whose columns count from the
+    /// start of the code line or span
+    pub(super) fn expand_code_tabs<'a>(&self, content: Cow<'a, str>) -> Cow<'a, str> {
+        let expanded = match self.options.tab_policy.apply(&content, 0) {
+            Cow::Owned(expanded) => Some(expanded),
+            Cow::Borrowed(_) => None,
+        };
+        expanded.map_or(content, Cow::Owned)
+    }
+
+    /// Apply the control character policy to text or code content
+    ///
+    /// `context` names the content kind in `WriteError::InvalidCharacter`.
+    pub(super) fn sanitize_control_chars<'a>(
+        &self,
+        content: Cow<'a, str>,
+        context: &str,
+    ) -> WriteResult<Cow<'a, str>> {
+        let policy = self.options.control_chars.resolve(self.is_strict_mode());
+        if policy == ControlCharPolicy::Allow {
+            return Ok(content);
+        }
+        let Some((position, character)) = content
+            .char_indices()
+            .find(|&(_, c)| is_disallowed_control(c))
+        else {
+            return Ok(content);
+        };
+        match policy {
+            ControlCharPolicy::Reject => Err(WriteError::InvalidCharacter {
+                context: context.into(),
+                position,
+                character,
+            }),
+            _ => Ok(Cow::Owned(
+                content
+                    .chars()
+                    .filter(|&c| !is_disallowed_control(c))
+                    .collect(),
+            )),
+        }
+    }
+
     /// Writes inline code content
     pub fn write_code_content(&mut self, content: &str) -> WriteResult<()> {
-        self.write_char('`')?;
-        self.write_str(content)?;
-        self.write_char('`')?;
+        let content = self.sanitize_control_chars(Cow::Borrowed(content), "InlineCode")?;
+        let content = self.expand_code_tabs(content);
+
+        // The fence must be longer than any backtick run inside the span
+        let mut longest_run = 0;
+        let mut run = 0;
+        for c in content.chars() {
+            if c == '`' {
+                run += 1;
+                longest_run = longest_run.max(run);
+            } else {
+                run = 0;
+            }
+        }
+        let fence = "`".repeat(longest_run + 1);
+
+        // Readers strip one space from each side when both are present and the
+        // content isn't all spaces, and a backtick next to the fence would merge
+        // with it, so pad with a space in those cases
+        let all_spaces = content.chars().all(|c| c == ' ');
+        let pad = content.starts_with('`')
+            || content.ends_with('`')
+            || (!all_spaces && content.starts_with(' ') && content.ends_with(' '));
+
+        self.write_str(&fence)?;
+        if pad {
+            self.write_char(' ')?;
+        }
+        self.write_str(&content)?;
+        if pad {
+            self.write_char(' ')?;
+        }
+        self.write_str(&fence)?;
         Ok(())
     }
 
+    /// Write a math formula between the delimiters set by
+    /// [`WriterOptions::math_delimiters`](crate::WriterOptions::math_delimiters)
+    ///
+    /// The formula is written as is. A formula that wouldn't be read back as
+    /// one (empty, containing its closing delimiter, or `$` inline math with
+    /// leading or trailing whitespace) is an error in strict mode and written
+    /// anyway otherwise. Without the `math` option, the formula is written as
+    /// inline code.
+    pub fn write_math(&mut self, display: bool, content: &str) -> WriteResult<()> {
+        if !self.supports(Capability::Math) {
+            return self.write_code_content(content);
+        }
+        let content = self.sanitize_control_chars(Cow::Borrowed(content), "Math")?;
+        let delimiters = self.options.math_delimiters;
+        let (open, close) = delimiters.delimiters(display);
+
+        if let Some(problem) = math_problem(delimiters, display, &content, close) {
+            if self.options.strict {
+                return Err(WriteError::InvalidStructure(
+                    format!("Math formula '{}' {}", content, problem).into(),
+                ));
+            }
+            diagnostics::warn!("Math formula '{}' {}", content, problem);
+        }
+
+        self.write_str(open)?;
+        self.write_str(&content)?;
+        self.write_str(close)
+    }
+
     /// Write an emphasis (italic) node with custom delimiter
+    ///
+    /// Emphasis nested directly inside is redundant and flattened, since `__x__`
+    /// would read as strong emphasis. Emphasis wrapping only strong emphasis is
+    /// written in the canonical `***x***` form.
     pub fn write_emphasis(&mut self, content: &[Node]) -> WriteResult<()> {
+        let content = flatten_nested_emphasis(content, false, true);
+        if let [Node::Strong(inner)] = content[..] {
+            return self.write_strong_emphasis(inner);
+        }
         let delimiter = self.options.emphasis_char.to_string();
         self.write_delimited(content, &delimiter)
     }
 
     /// Write a strong emphasis (bold) node with custom delimiter
+    ///
+    /// Strong emphasis nested directly inside is redundant and flattened, since
+    /// `****x****` doesn't parse back as strong. Strong emphasis wrapping only
+    /// emphasis is written in the canonical `***x***` form.
     pub fn write_strong(&mut self, content: &[Node]) -> WriteResult<()> {
+        let content = flatten_nested_emphasis(content, true, false);
+        if let [Node::Emphasis(inner)] = content[..] {
+            return self.write_strong_emphasis(inner);
+        }
         let char = self.options.strong_char;
         let delimiter = format!("{}{}", char, char);
         self.write_delimited(content, &delimiter)
     }
 
+    /// Write content that is both emphasized and strong as `***x***`
+    fn write_strong_emphasis(&mut self, content: &[Node]) -> WriteResult<()> {
+        let content = flatten_nested_emphasis(content, true, true);
+        let delimiter = self.options.strong_char.to_string().repeat(3);
+        self.write_delimited(content, &delimiter)
+    }
+
     /// Write a strikethrough node (GFM extension)
     #[cfg(feature = "gfm")]
     pub fn write_strikethrough(&mut self, content: &[Node]) -> WriteResult<()> {
-        if !self.options.enable_gfm || !self.options.gfm_strikethrough {
+        if !self.supports(Capability::Strikethrough) {
             // If GFM strikethrough is disabled, just write the content without strikethrough
             for node in content.iter() {
                 self.write_node_content(node)?;
@@ -73,12 +350,10 @@ impl CommonMarkWriter {
         }
 
         self.write_str("](")?;
-        self.write_str(url)?;
+        self.write_link_destination(url)?;
 
         if let Some(title_text) = title {
-            self.write_str(" \"")?;
-            self.write_str(title_text)?;
-            self.write_char('"')?;
+            self.write_link_title(title_text)?;
         }
 
         self.write_char(')')?;
@@ -105,24 +380,98 @@ impl CommonMarkWriter {
         }
 
         self.write_str("](")?;
-        self.write_str(url)?;
+        self.write_link_destination(url)?;
 
         if let Some(title_text) = title {
-            self.write_str(" \"")?;
-            self.write_str(title_text)?;
-            self.write_char('"')?;
+            self.write_link_title(title_text)?;
         }
 
         self.write_char(')')?;
         Ok(())
     }
 
-    /// Write a soft line break
-    pub fn write_soft_break(&mut self) -> WriteResult<()> {
-        self.write_char('\n')?;
+    /// Write a link title preceded by a space, using the configured delimiters
+    ///
+    /// Switches to another delimiter style when the title contains the preferred one.
+    /// If every style conflicts, closing delimiters (and backslashes) are escaped.
+    pub(super) fn write_link_title(&mut self, title: &str) -> WriteResult<()> {
+        let quote = self.options.link_title_quote.choose_for(title);
+        let (open, close) = quote.delimiters();
+        self.write_char(' ')?;
+        self.write_char(open)?;
+        if quote.fits(title) {
+            self.write_str(title)?;
+        } else {
+            for c in title.chars() {
+                if c == open || c == close || c == '\\' {
+                    self.write_char('\\')?;
+                }
+                self.write_char(c)?;
+            }
+        }
+        self.write_char(close)?;
         Ok(())
     }
 
+    /// Write a link destination so that it parses back to `url`
+    ///
+    /// Destinations with spaces, control characters or a leading `<` are wrapped in
+    /// `<...>`; otherwise unbalanced parentheses are backslash-escaped.
+    pub(super) fn write_link_destination(&mut self, url: &str) -> WriteResult<()> {
+        let needs_angle = url.starts_with('<') || url.chars().any(|c| c == ' ' || c.is_control());
+        if needs_angle {
+            self.write_char('<')?;
+            self.write_escaped_destination(url, &['<', '>'])?;
+            self.write_char('>')?;
+            return Ok(());
+        }
+
+        let mut depth = 0usize;
+        let mut balanced = true;
+        for c in url.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => balanced = false,
+                ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        if balanced && depth == 0 {
+            self.write_escaped_destination(url, &[])
+        } else {
+            self.write_escaped_destination(url, &['(', ')'])
+        }
+    }
+
+    /// Write a destination, backslash-escaping `special` characters and any
+    /// backslash that would otherwise escape the character after it
+    fn write_escaped_destination(&mut self, url: &str, special: &[char]) -> WriteResult<()> {
+        let mut chars = url.chars().peekable();
+        while let Some(c) = chars.next() {
+            let escapes_next = c == '\\' && chars.peek().is_some_and(|n| n.is_ascii_punctuation());
+            if special.contains(&c) || escapes_next {
+                self.write_char('\\')?;
+            }
+            self.write_char(c)?;
+        }
+        Ok(())
+    }
+
+    /// Write a soft line break, in the configured `soft_break_style`
+    ///
+    /// Inside a table cell, where a line break would end the row, the break is
+    /// written as a space when `table_soft_breaks_as_spaces` is set
+    pub fn write_soft_break(&mut self) -> WriteResult<()> {
+        if self.in_table_cell {
+            return self.write_char(' ');
+        }
+        match self.options.soft_break_style {
+            SoftBreakStyle::Newline => self.write_char('\n'),
+            SoftBreakStyle::Space => self.write_char(' '),
+            SoftBreakStyle::HardBreak => self.write_hard_break(),
+        }
+    }
+
     /// Write a hard line break
     pub fn write_hard_break(&mut self) -> WriteResult<()> {
         if self.options.hard_break_spaces {
@@ -142,7 +491,7 @@ impl CommonMarkWriter {
                     "Autolink URL".to_string().into(),
                 ));
             } else {
-                log::warn!(
+                diagnostics::warn!(
                     "Newline character found in autolink URL '{}'. Writing it as is, which might result in an invalid link. Strict mode is off.",
                     url
                 );
@@ -150,17 +499,10 @@ impl CommonMarkWriter {
             }
         }
 
-        // Write the autolink with < and > delimiters
+        // Email autolinks are written without `mailto:`, URI autolinks always with a scheme
+        let (text, _) = autolink_parts(url, is_email, &self.options.autolink_default_scheme);
         self.write_char('<')?;
-
-        // For email autolinks, we don't need to add any prefix
-        // For URI autolinks, ensure it has a scheme
-        if !is_email && !url.contains(':') {
-            // Default to https if no scheme is provided
-            self.write_str("https://")?;
-        }
-
-        self.write_str(url)?;
+        self.write_str(&text)?;
         self.write_char('>')?;
 
         Ok(())
@@ -169,7 +511,7 @@ impl CommonMarkWriter {
     /// Write an extended autolink (GFM extension)
     #[cfg(feature = "gfm")]
     pub fn write_extended_autolink(&mut self, url: &str) -> WriteResult<()> {
-        if !self.options.gfm_autolinks {
+        if !self.supports(Capability::ExtendedAutolink) {
             // If GFM autolinks are disabled, write as plain text
             self.write_text_content(url)?;
             return Ok(());
@@ -183,7 +525,7 @@ impl CommonMarkWriter {
                     "Extended Autolink URL".to_string().into(),
                 ));
             } else {
-                log::warn!(
+                diagnostics::warn!(
                     "Newline character found in extended autolink URL '{}'. Writing it as is, which might result in an invalid link. Strict mode is off.",
                     url
                 );
@@ -238,6 +580,44 @@ impl CommonMarkWriter {
         Ok(())
     }
 
+    /// Write a footnote reference: `[^label]`, or `\[label\]` without the
+    /// `footnotes` option
+    pub fn write_footnote_reference(&mut self, label: &str) -> WriteResult<()> {
+        self.check_footnote_label(label)?;
+        if !self.supports(Capability::Footnotes) {
+            return self.write_footnote_fallback(label);
+        }
+        self.write_str("[^")?;
+        self.write_str(label)?;
+        self.write_char(']')
+    }
+
+    /// Write a footnote label as bracketed text, escaped so that it can't be
+    /// read as a link
+    pub(super) fn write_footnote_fallback(&mut self, label: &str) -> WriteResult<()> {
+        self.write_str("\\[")?;
+        self.write_text_content(label)?;
+        self.write_str("\\]")
+    }
+
+    /// Check that a footnote label can be written back as `[^label]`
+    ///
+    /// Labels must be non-empty and contain no whitespace or brackets. An invalid
+    /// label is an error in strict mode and written as is otherwise.
+    pub(super) fn check_footnote_label(&self, label: &str) -> WriteResult<()> {
+        if !label.is_empty() && !label.contains(|c: char| c.is_whitespace() || c == '[' || c == ']')
+        {
+            return Ok(());
+        }
+        if self.options.strict {
+            return Err(WriteError::InvalidStructure(
+                format!("Invalid footnote label '{}'", label).into(),
+            ));
+        }
+        diagnostics::warn!("Invalid footnote label '{}'", label);
+        Ok(())
+    }
+
     /// Write an AST HtmlElement node as raw HTML string into the CommonMark output.
     pub fn write_html_element(&mut self, element: &crate::ast::HtmlElement) -> WriteResult<()> {
         if self.options.strict {
@@ -252,24 +632,9 @@ impl CommonMarkWriter {
             }
         }
 
-        use crate::writer::html::{HtmlWriter, HtmlWriterOptions};
+        let mut html_writer = self.html_fallback_writer(self.html_writer_options());
 
-        let html_options = if let Some(ref custom_options) = self.options.html_writer_options {
-            custom_options.clone()
-        } else {
-            HtmlWriterOptions {
-                strict: self.options.strict,
-                code_block_language_class_prefix: Some("language-".into()),
-                #[cfg(feature = "gfm")]
-                enable_gfm: self.options.enable_gfm,
-                #[cfg(feature = "gfm")]
-                gfm_disallowed_html_tags: self.options.gfm_disallowed_html_tags.clone(),
-            }
-        };
-
-        let mut html_writer = HtmlWriter::with_options(html_options);
-
-        html_writer.write_node_internal(&Node::HtmlElement(element.clone()))?;
+        html_writer.write_html_element_node(element)?;
 
         // Get the generated HTML
         let html_output = html_writer.into_string();
@@ -278,3 +643,58 @@ impl CommonMarkWriter {
         self.write_str(&html_output)
     }
 }
+
+/// Why `content` can't be written as a formula ending with `close`, if it can't
+fn math_problem(
+    delimiters: MathDelimiters,
+    display: bool,
+    content: &str,
+    close: &str,
+) -> Option<&'static str> {
+    if content.trim().is_empty() {
+        return Some("is empty");
+    }
+    // A delimiter preceded by an odd number of backslashes is escaped
+    let closes_early = content.match_indices(close).any(|(index, _)| {
+        let backslashes = content[..index]
+            .bytes()
+            .rev()
+            .take_while(|&b| b == b'\\')
+            .count();
+        backslashes % 2 == 0
+    });
+    if closes_early {
+        return Some("contains its closing delimiter");
+    }
+    if delimiters == MathDelimiters::Dollars
+        && !display
+        && (content.starts_with(char::is_whitespace) || content.ends_with(char::is_whitespace))
+    {
+        return Some("starts or ends with whitespace");
+    }
+    None
+}
+
+/// The nodes of `content`, with strong nodes (if `strong` is active) and
+/// emphasis nodes (if `emphasis` is) replaced by their content, flattened in
+/// turn
+///
+/// Nested runs of an active kind are flattened at any depth. Emphasis inside
+/// links and other inline containers is separated from the outer delimiters
+/// and kept. Nodes are borrowed rather than cloned, since custom nodes can't
+/// be cloned.
+fn flatten_nested_emphasis(content: &[Node], strong: bool, emphasis: bool) -> Vec<&Node> {
+    fn flatten<'a>(content: &'a [Node], strong: bool, emphasis: bool, out: &mut Vec<&'a Node>) {
+        for node in content {
+            match node.resolve_shared() {
+                Node::Strong(children) if strong => flatten(children, strong, emphasis, out),
+                Node::Emphasis(children) if emphasis => flatten(children, strong, emphasis, out),
+                _ => out.push(node),
+            }
+        }
+    }
+
+    let mut flattened = Vec::with_capacity(content.len());
+    flatten(content, strong, emphasis, &mut flattened);
+    flattened
+}
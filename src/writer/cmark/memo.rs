@@ -0,0 +1,122 @@
+//! Memoization of rendered blocks, for documents repeating identical subtrees.
+
+use super::CommonMarkWriter;
+use crate::ast::Node;
+use crate::error::WriteResult;
+use crate::writer::context::{NewlineStrategy, RenderingMode};
+use ecow::EcoString;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Rendered blocks, shared by a writer and its child writers
+pub(super) type SharedBlockMemo = Arc<Mutex<BlockMemo>>;
+
+/// Output of the blocks written so far, by structural hash
+#[derive(Debug, Default)]
+pub(super) struct BlockMemo {
+    entries: HashMap<u64, Vec<MemoEntry>>,
+}
+
+/// The writer state a block's output depends on, besides the options
+#[derive(Debug, Clone, PartialEq)]
+struct MemoState {
+    line_prefix: EcoString,
+    in_table_cell: bool,
+    in_paragraph: bool,
+    mode: RenderingMode,
+    strategy: NewlineStrategy,
+    allows_blocks: bool,
+}
+
+#[derive(Debug)]
+struct MemoEntry {
+    node: Node,
+    state: MemoState,
+    output: EcoString,
+}
+
+impl BlockMemo {
+    fn get(&self, key: u64, node: &Node, state: &MemoState) -> Option<EcoString> {
+        // Hashes can collide, so the node itself is compared too
+        self.entries
+            .get(&key)?
+            .iter()
+            .find(|entry| entry.state == *state && entry.node == *node)
+            .map(|entry| entry.output.clone())
+    }
+
+    fn insert(&mut self, key: u64, entry: MemoEntry) {
+        self.entries.entry(key).or_default().push(entry);
+    }
+}
+
+impl CommonMarkWriter {
+    /// Whether `node` is written through the memo, see
+    /// [`WriterOptions::memoize_blocks`](crate::WriterOptions::memoize_blocks)
+    ///
+    /// Custom nodes may render differently each time (and can't be cloned), so
+    /// blocks containing one are always written.
+    pub(super) fn memoizes(&self, node: &Node) -> bool {
+        self.memo.is_some()
+            && !self.in_memoized_block
+            && node.is_block()
+            && !matches!(node, Node::Document(_) | Node::ConfiguredDocument { .. })
+            && !contains_custom(node)
+    }
+
+    /// Write a block, copying its output if an identical block was written in
+    /// the same state before
+    pub(super) fn write_memoized(&mut self, node: &Node) -> WriteResult<()> {
+        let Some(memo) = self.memo.clone() else {
+            return self.write_node_content(node);
+        };
+        let state = MemoState {
+            line_prefix: self.line_prefix.clone(),
+            in_table_cell: self.in_table_cell,
+            in_paragraph: self.in_paragraph,
+            mode: self.context().mode,
+            strategy: self.context().strategy,
+            allows_blocks: self.context().allows_blocks,
+        };
+        let mut hasher = DefaultHasher::new();
+        node.hash(&mut hasher);
+        let key = hasher.finish();
+
+        let cached = memo
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key, node, &state);
+        if let Some(output) = cached {
+            return self.write_str(&output);
+        }
+
+        let start = self.buffer.len();
+        self.in_memoized_block = true;
+        let result = self.write_node_content(node);
+        self.in_memoized_block = false;
+        result?;
+
+        // Blocks that removed output written before them aren't remembered
+        if let Some(output) = self.buffer.get(start..) {
+            memo.lock().unwrap_or_else(PoisonError::into_inner).insert(
+                key,
+                MemoEntry {
+                    node: node.clone(),
+                    state,
+                    output: output.into(),
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+fn contains_custom(node: &Node) -> bool {
+    matches!(node, Node::Custom(_))
+        || node
+            .children_with_segments()
+            .into_iter()
+            .any(|(_, child)| contains_custom(child))
+}
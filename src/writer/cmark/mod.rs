@@ -7,13 +7,21 @@
 //! - `table`: Table-specific writing
 //! - `utils`: Utility functions and escaping
 //! - `html_fallback`: HTML fallback handling
+//! - `stream`: Streaming output to an `io::Write` sink
+//! - `trace`: Recording of buffer changes for debugging
+//! - `memo`: Memoization of repeated blocks
 
 mod block;
 mod html_fallback;
 mod inline;
+mod memo;
+mod stream;
 mod table;
+mod trace;
 mod utils;
 mod writer;
 
-pub use utils::{escape_str, CommonMarkEscapes, Escapes};
+pub use stream::CommonMarkStreamWriter;
+pub use trace::{WriteEdit, WriteEvent, WriteTrace};
+pub use utils::{escape_contextual, escape_str, CommonMarkEscapes, Escapes};
 pub use writer::CommonMarkWriter;
@@ -0,0 +1,111 @@
+//! Streaming CommonMark output to an `io::Write` sink.
+
+use super::CommonMarkWriter;
+use crate::ast::Node;
+use crate::error::WriteResult;
+use crate::options::WriterOptions;
+use std::io::Write;
+
+/// A [`CommonMarkWriter`] that writes its output to an [`io::Write`](Write)
+/// sink as nodes complete, instead of holding the whole document in memory
+///
+/// Nodes are written with the same logic as
+/// [`CommonMarkWriter::write_all`], and complete lines are handed to the sink
+/// after every node. Only the last line stays buffered, since the separator
+/// before the next node (and the `final_newline` policy) depends on it, so
+/// the bytes written are identical to the string the buffered writer returns.
+///
+/// Post-processors see the whole output: when `post_processors` are set,
+/// nothing is written until [`finish`](Self::finish).
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::writer::cmark::CommonMarkStreamWriter;
+///
+/// let mut writer = CommonMarkStreamWriter::new(Vec::new());
+/// for part in 1..=2 {
+///     writer
+///         .append(&Node::heading(2, vec![Node::Text(format!("Part {}", part).into())]))
+///         .unwrap();
+/// }
+/// // Everything but the last line has reached the sink
+/// assert_eq!(writer.get_ref(), b"## Part 1\n\n");
+///
+/// let output = writer.finish().unwrap();
+/// assert_eq!(output, b"## Part 1\n\n## Part 2\n");
+/// ```
+#[derive(Debug)]
+pub struct CommonMarkStreamWriter<W: Write> {
+    writer: CommonMarkWriter,
+    sink: W,
+}
+
+impl<W: Write> CommonMarkStreamWriter<W> {
+    /// Create a streaming writer with default options
+    pub fn new(sink: W) -> Self {
+        Self::with_options(sink, WriterOptions::default())
+    }
+
+    /// Create a streaming writer with the given options
+    pub fn with_options(sink: W, options: WriterOptions) -> Self {
+        Self {
+            writer: CommonMarkWriter::with_options(options),
+            sink,
+        }
+    }
+
+    /// Write a stream of nodes as if they were the children of a document,
+    /// see [`CommonMarkWriter::write_all`]
+    ///
+    /// Complete lines are written to the sink after each node.
+    pub fn write_all<'a, I>(&mut self, nodes: I) -> WriteResult<()>
+    where
+        I: IntoIterator<Item = &'a Node>,
+    {
+        for node in nodes {
+            self.append(node)?;
+        }
+        Ok(())
+    }
+
+    /// Write one more node of the stream
+    pub fn append(&mut self, node: &Node) -> WriteResult<()> {
+        self.writer.append(node)?;
+        self.flush_complete_lines()
+    }
+
+    /// Write the buffered last line, flush the sink and return it
+    pub fn finish(self) -> WriteResult<W> {
+        let Self { writer, mut sink } = self;
+        sink.write_all(writer.into_string().as_bytes())?;
+        sink.flush()?;
+        Ok(sink)
+    }
+
+    /// The sink
+    pub fn get_ref(&self) -> &W {
+        &self.sink
+    }
+
+    /// Bytes written to the writer but not yet to the sink
+    pub fn buffered(&self) -> &str {
+        &self.writer.buffer
+    }
+
+    /// Hand every line but the last one (and its trailing newlines) to the sink
+    fn flush_complete_lines(&mut self) -> WriteResult<()> {
+        if !self.writer.options.post_processors.is_empty() {
+            return Ok(());
+        }
+        let buffer = &self.writer.buffer;
+        let content_end = buffer.trim_end_matches('\n').len();
+        let Some(line_end) = buffer[..content_end].rfind('\n') else {
+            return Ok(());
+        };
+        self.sink.write_all(buffer[..=line_end].as_bytes())?;
+        self.writer.buffer = buffer[line_end + 1..].into();
+        Ok(())
+    }
+}
@@ -2,11 +2,14 @@
 
 use super::CommonMarkWriter;
 use crate::ast::Node;
+use crate::capability::Capability;
 use crate::error::{WriteError, WriteResult};
-use log;
+use ecow::EcoString;
+use unicode_width::UnicodeWidthStr;
 
 #[cfg(feature = "gfm")]
 use crate::ast::TableAlignment;
+use crate::diagnostics;
 
 impl CommonMarkWriter {
     /// Check if a table contains any block-level elements in headers or cells
@@ -25,7 +28,7 @@ impl CommonMarkWriter {
     pub fn write_table(&mut self, headers: &[Node], rows: &[Vec<Node>]) -> WriteResult<()> {
         // Check if table contains block elements
         if Self::table_contains_block_elements(headers, rows) {
-            if self.is_strict_mode() {
+            if !self.supports(Capability::TableBlockContent) {
                 // In strict mode, fail immediately if block elements are present
                 return Err(WriteError::InvalidStructure(
                     "Table contains block-level elements which are not allowed in strict mode"
@@ -34,44 +37,14 @@ impl CommonMarkWriter {
                 ));
             } else {
                 // In soft mode, fallback to HTML
-                log::info!(
+                diagnostics::info!(
                     "Table contains block-level elements, falling back to HTML output in soft mode"
                 );
                 return self.write_table_as_html(headers, rows);
             }
         }
 
-        // Write header
-        self.write_char('|')?;
-        for header in headers {
-            self.check_no_newline(header, "Table Header")?;
-            self.write_char(' ')?;
-            self.write_node_content(header)?;
-            self.write_str(" |")?;
-        }
-        self.write_char('\n')?;
-
-        // Write alignment row (default to centered if no alignments provided)
-        self.write_char('|')?;
-        for _ in 0..headers.len() {
-            self.write_str(" --- |")?;
-        }
-        self.write_char('\n')?;
-
-        // Write table content
-        for row in rows {
-            self.write_char('|')?;
-            for cell in row {
-                self.check_no_newline(cell, "Table Cell")?;
-                self.write_char(' ')?;
-                self.write_node_content(cell)?;
-                self.write_str(" |")?;
-            }
-            self.write_char('\n')?;
-        }
-
-        // Don't add extra trailing newline - let the context system handle it
-        Ok(())
+        self.write_table_grid(headers, &vec![(false, false); headers.len()], rows)
     }
 
     #[cfg(feature = "gfm")]
@@ -83,13 +56,13 @@ impl CommonMarkWriter {
         rows: &[Vec<Node>],
     ) -> WriteResult<()> {
         // Only use alignment when GFM tables are enabled
-        if !self.options.gfm_tables {
+        if !self.supports(Capability::TableAlignment) {
             return self.write_table(headers, rows);
         }
 
         // Check if table contains block elements
         if Self::table_contains_block_elements(headers, rows) {
-            if self.is_strict_mode() {
+            if !self.supports(Capability::TableBlockContent) {
                 // In strict mode, fail immediately if block elements are present
                 return Err(WriteError::InvalidStructure(
                     "GFM table contains block-level elements which are not allowed in strict mode"
@@ -98,55 +71,214 @@ impl CommonMarkWriter {
                 ));
             } else {
                 // In soft mode, fallback to HTML
-                log::info!("GFM table contains block-level elements, falling back to HTML output in soft mode");
+                diagnostics::info!("GFM table contains block-level elements, falling back to HTML output in soft mode");
                 return self.write_table_as_html_with_alignment(headers, alignments, rows);
             }
         }
 
-        // Write header
+        // Use provided alignments, or default to center if not enough alignments provided
+        let colons: Vec<_> = (0..headers.len())
+            .map(
+                |i| match alignments.get(i).unwrap_or(&TableAlignment::Center) {
+                    TableAlignment::Left => (true, false),
+                    TableAlignment::Center => (true, true),
+                    TableAlignment::Right => (false, true),
+                    TableAlignment::None => (false, false),
+                },
+            )
+            .collect();
+
+        self.write_table_grid(headers, &colons, rows)
+    }
+
+    /// Write the header row, the delimiter row with the given `(left, right)`
+    /// colons for each column, and the body rows
+    fn write_table_grid(
+        &mut self,
+        headers: &[Node],
+        colons: &[(bool, bool)],
+        rows: &[Vec<Node>],
+    ) -> WriteResult<()> {
+        if self.options.pretty_tables {
+            return self.write_pretty_table(headers, colons, rows);
+        }
+
+        self.write_table_row(headers, "Table Header")?;
+
         self.write_char('|')?;
-        for header in headers {
-            self.check_no_newline(header, "Table Header")?;
-            self.write_char(' ')?;
-            self.write_node_content(header)?;
-            self.write_str(" |")?;
+        for &(left_colon, right_colon) in colons {
+            self.write_table_delimiter_cell(left_colon, right_colon, 0)?;
         }
         self.write_char('\n')?;
 
-        // Write alignment row
-        self.write_char('|')?;
+        self.write_table_body(rows)
+    }
 
-        // Use provided alignments, or default to center if not enough alignments provided
-        for i in 0..headers.len() {
-            let alignment = if i < alignments.len() {
-                &alignments[i]
-            } else {
-                &TableAlignment::Center
-            };
+    /// Write a table with every cell padded to the display width of its
+    /// column, so that the pipes line up
+    ///
+    /// Cells are rendered first to measure them; right and center aligned
+    /// columns are padded on the left as well.
+    fn write_pretty_table(
+        &mut self,
+        headers: &[Node],
+        colons: &[(bool, bool)],
+        rows: &[Vec<Node>],
+    ) -> WriteResult<()> {
+        let header_cells = self.render_table_cells(headers, "Table Header")?;
+        let body_cells = rows
+            .iter()
+            .map(|row| self.render_table_cells(row, "Table Cell"))
+            .collect::<WriteResult<Vec<_>>>()?;
 
-            match alignment {
-                TableAlignment::Left => self.write_str(" :--- |")?,
-                TableAlignment::Center => self.write_str(" :---: |")?,
-                TableAlignment::Right => self.write_str(" ---: |")?,
-                TableAlignment::None => self.write_str(" --- |")?,
+        // Wide enough for the delimiter row as well
+        let min_dashes = self.options.table_delimiter_min_dashes.max(1);
+        let mut widths: Vec<usize> = colons
+            .iter()
+            .map(|&(left, right)| min_dashes + left as usize + right as usize)
+            .collect();
+        for row in std::iter::once(&header_cells).chain(&body_cells) {
+            if widths.len() < row.len() {
+                widths.resize(row.len(), min_dashes);
+            }
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.width());
             }
         }
 
+        self.write_padded_table_row(&header_cells, &widths, colons)?;
+
+        self.write_char('|')?;
+        let padding = self.options.table_delimiter_padding;
+        for (&(left_colon, right_colon), &width) in colons.iter().zip(&widths) {
+            // Without padding, the dashes take the place of the spaces around the cells
+            let width = if padding { width } else { width + 2 };
+            self.write_table_delimiter_cell(left_colon, right_colon, width)?;
+        }
         self.write_char('\n')?;
 
-        // Write table content
+        for row in &body_cells {
+            self.write_padded_table_row(row, &widths, colons)?;
+        }
+        Ok(())
+    }
+
+    /// Render each of `cells` on its own and return their output
+    ///
+    /// Each cell is written after a `| `, as in an unpadded row, so it's
+    /// escaped the same way; everything is then removed from the buffer.
+    fn render_table_cells(&mut self, cells: &[Node], context: &str) -> WriteResult<Vec<EcoString>> {
+        let mut rendered = Vec::with_capacity(cells.len());
+        for cell in cells {
+            let row_start = self.buffer.len();
+            self.write_str("| ")?;
+            let start = self.buffer.len();
+            self.write_table_cell(cell, context)?;
+            rendered.push(self.buffer[start..].into());
+            self.truncate_raw(row_start);
+        }
+        Ok(rendered)
+    }
+
+    /// Write a row of rendered cells, each padded to the width of its column
+    /// according to its alignment
+    ///
+    /// Rows shorter than the header are completed with empty cells, which
+    /// doesn't change their meaning.
+    fn write_padded_table_row(
+        &mut self,
+        cells: &[EcoString],
+        widths: &[usize],
+        colons: &[(bool, bool)],
+    ) -> WriteResult<()> {
+        self.write_char('|')?;
+        let columns = cells.len().max(colons.len());
+        for (i, &width) in widths.iter().enumerate().take(columns) {
+            let cell = cells.get(i).map_or("", EcoString::as_str);
+            let fill = width - cell.width();
+            let before = match colons.get(i) {
+                Some((true, true)) => fill / 2,
+                Some((false, true)) => fill,
+                _ => 0,
+            };
+            self.write_char(' ')?;
+            self.write_str(&" ".repeat(before))?;
+            self.write_str(cell)?;
+            self.write_str(&" ".repeat(fill - before))?;
+            self.write_str(" |")?;
+        }
+        self.write_char('\n')
+    }
+
+    /// Write one cell of the delimiter row, followed by its closing `|`
+    ///
+    /// The dashes fill `width` columns, colons included, but there are at
+    /// least `table_delimiter_min_dashes` of them.
+    fn write_table_delimiter_cell(
+        &mut self,
+        left_colon: bool,
+        right_colon: bool,
+        width: usize,
+    ) -> WriteResult<()> {
+        let padding = self.options.table_delimiter_padding;
+        let dashes = self
+            .options
+            .table_delimiter_min_dashes
+            .max(1)
+            .max(width.saturating_sub(left_colon as usize + right_colon as usize));
+        if padding {
+            self.write_char(' ')?;
+        }
+        if left_colon {
+            self.write_char(':')?;
+        }
+        for _ in 0..dashes {
+            self.write_char('-')?;
+        }
+        if right_colon {
+            self.write_char(':')?;
+        }
+        if padding {
+            self.write_char(' ')?;
+        }
+        self.write_char('|')
+    }
+
+    /// Write all body rows of a table
+    fn write_table_body(&mut self, rows: &[Vec<Node>]) -> WriteResult<()> {
         for row in rows {
-            self.write_char('|')?;
-            for cell in row {
-                self.check_no_newline(cell, "Table Cell")?;
-                self.write_char(' ')?;
-                self.write_node_content(cell)?;
-                self.write_str(" |")?;
-            }
-            self.write_char('\n')?;
+            self.write_table_row(row, "Table Cell")?;
         }
 
         // Don't add extra trailing newline - let the context system handle it
         Ok(())
     }
+
+    /// Write a single table row
+    ///
+    /// Cells are rendered straight into the output buffer, so no intermediate
+    /// writer or string is allocated per cell.
+    fn write_table_row(&mut self, cells: &[Node], context: &str) -> WriteResult<()> {
+        self.write_char('|')?;
+        for cell in cells {
+            self.write_char(' ')?;
+            self.write_table_cell(cell, context)?;
+            self.write_str(" |")?;
+        }
+        self.write_char('\n')
+    }
+
+    /// Write the content of a table cell, which must fit on one line
+    fn write_table_cell(&mut self, cell: &Node, context: &str) -> WriteResult<()> {
+        let soft_breaks_as_spaces = self.options.table_soft_breaks_as_spaces;
+        if soft_breaks_as_spaces {
+            self.check_no_line_break(cell, context, false)?;
+        } else {
+            self.check_no_newline(cell, context)?;
+        }
+        self.in_table_cell = soft_breaks_as_spaces;
+        let result = self.write_node_content(cell);
+        self.in_table_cell = false;
+        result
+    }
 }
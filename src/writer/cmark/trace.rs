@@ -0,0 +1,160 @@
+//! Recording of every change the CommonMark writer makes to its buffer.
+
+use crate::ast::{Node, NodePath};
+use ecow::EcoString;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+
+/// A change to the buffer of a writer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteEdit {
+    /// Text appended to the buffer
+    Insert(EcoString),
+    /// Text removed from the end of the buffer
+    Remove(EcoString),
+}
+
+/// One recorded change, see [`WriteTrace`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteEvent {
+    /// Byte offset of the change in the buffer of the writer that made it
+    pub offset: usize,
+    /// What changed
+    pub edit: WriteEdit,
+    /// Type names of the nodes being written, outermost first
+    /// (e.g. `["Document", "UnorderedList", "Paragraph"]`)
+    pub nodes: Vec<&'static str>,
+    /// Path of the innermost node being written, relative to the node given
+    /// to the writer; `None` for nodes the writer makes up itself (e.g. a
+    /// table of contents) and for changes made outside any node
+    pub path: Option<NodePath>,
+    /// Where the change was made, inside the responsible writer method
+    /// (e.g. `write_list_item_content`) or custom node
+    pub location: &'static Location<'static>,
+    /// Nesting depth of the writer: block quotes and list items render their
+    /// content with a child writer, whose buffer is then prefixed and appended
+    /// to the parent's
+    pub writer_depth: usize,
+}
+
+/// Every change a [`CommonMarkWriter`](super::CommonMarkWriter) made to its
+/// buffer, recorded when [`WriterOptions::trace_writes`](crate::WriterOptions::trace_writes)
+/// is enabled
+///
+/// The changes of a child writer come right before the change that appends its
+/// output to the parent, with offsets into the child's own buffer. The
+/// `Display` form is an annotated listing of the events, one per line.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::{Node, NodePath};
+/// use cmark_writer::options::WriterOptionsBuilder;
+/// use cmark_writer::writer::cmark::WriteEdit;
+/// use cmark_writer::{CommonMarkWriter, ToCommonMark};
+///
+/// let options = WriterOptionsBuilder::new().trace_writes(true).build();
+/// let mut writer = CommonMarkWriter::with_options(options);
+/// Node::heading(2, vec![Node::Text("Usage".into())])
+///     .to_commonmark(&mut writer)
+///     .unwrap();
+///
+/// let trace = writer.write_trace().unwrap();
+/// let text = trace
+///     .events()
+///     .iter()
+///     .find(|event| event.edit == WriteEdit::Insert("Usage".into()))
+///     .unwrap();
+/// assert_eq!(text.offset, 3);
+/// assert_eq!(text.nodes, ["Heading", "Text"]);
+/// assert_eq!(text.path, Some(NodePath::root().child(0)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteTrace {
+    events: Vec<WriteEvent>,
+}
+
+impl WriteTrace {
+    /// The recorded events, in the order they happened
+    pub fn events(&self) -> &[WriteEvent] {
+        &self.events
+    }
+
+    pub(super) fn push(&mut self, event: WriteEvent) {
+        self.events.push(event);
+    }
+
+    /// Append the events of a child writer, one level deeper
+    pub(super) fn adopt(&mut self, child: WriteTrace) {
+        self.events
+            .extend(child.events.into_iter().map(|event| WriteEvent {
+                writer_depth: event.writer_depth + 1,
+                ..event
+            }));
+    }
+}
+
+/// One line per event: indentation by writer depth, offset, the change, the
+/// nodes being written and the path of the innermost one, and the location of
+/// the change
+impl fmt::Display for WriteTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            let (sign, text) = match &event.edit {
+                WriteEdit::Insert(text) => ('+', text),
+                WriteEdit::Remove(text) => ('-', text),
+            };
+            write!(
+                f,
+                "{}{:>5} {}{:?} {}",
+                "  ".repeat(event.writer_depth),
+                event.offset,
+                sign,
+                text.as_str(),
+                event.nodes.join(" > "),
+            )?;
+            if let Some(path) = &event.path {
+                write!(f, " at {}", path)?;
+            }
+            writeln!(f, " ({}:{})", event.location.file(), event.location.line())?;
+        }
+        Ok(())
+    }
+}
+
+/// Paths of the nodes of a tree being traced, looked up by address
+///
+/// A node shared by several [`Node::Shared`] wrappers has one address and
+/// several paths.
+#[derive(Debug, Default)]
+pub(super) struct NodePaths(HashMap<usize, Vec<NodePath>>);
+
+impl NodePaths {
+    /// The paths of `root` and its descendants
+    pub(super) fn of(root: &Node) -> Self {
+        let mut paths = HashMap::<usize, Vec<NodePath>>::new();
+        root.walk_with_paths(|path, node| {
+            paths.entry(address(node)).or_default().push(path.clone());
+        });
+        Self(paths)
+    }
+
+    /// The path of `node`, preferring one below `within` (the path of the
+    /// node being written around it) if it has several
+    pub(super) fn find(&self, node: &Node, within: Option<&NodePath>) -> Option<NodePath> {
+        let candidates = self.0.get(&address(node))?;
+        let below = |path: &&NodePath| {
+            within.is_some_and(|within| path.segments().starts_with(within.segments()))
+        };
+        candidates
+            .iter()
+            .find(below)
+            .or_else(|| candidates.first())
+            .cloned()
+    }
+}
+
+fn address(node: &Node) -> usize {
+    node as *const Node as usize
+}
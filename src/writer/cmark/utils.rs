@@ -78,3 +78,205 @@ pub fn escape_str<E: Escapes>(s: &str) -> Cow<'_, str> {
         Cow::Borrowed(s)
     }
 }
+
+/// Escapes only the characters of `s` that would change how it parses
+///
+/// Unlike [`escape_str`], which escapes a fixed set of characters wherever they
+/// are, a character is left alone when CommonMark reads it literally anyway:
+///
+/// - `*` and `_` only when they could open or close emphasis, so `2 * 3` and
+///   `snake_case` are kept as is,
+/// - `<` only before a letter, `/`, `!` or `?`, where it could start raw HTML
+///   or an autolink,
+/// - `\` only before ASCII punctuation or a line break,
+/// - `&` only when it starts something that reads as a character reference,
+///   like `&amp;`,
+/// - `` ` ``, `[` and `]` always, since the matching backtick or bracket may be
+///   written by a later node.
+///
+/// `preceding` is the character written right before `s`, or `None` at the
+/// start of a line. The end of `s` is treated as if anything could follow, so
+/// a trailing `*` or `\` is still escaped. Characters that only matter at the
+/// start of a line, like `#` or `1.`, are left to the block start escaping of
+/// paragraphs.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::writer::cmark::escape_contextual;
+///
+/// assert_eq!(escape_contextual("2 * 3 = snake_case", None), "2 * 3 = snake_case");
+/// assert_eq!(escape_contextual("*not emphasis*", None), r"\*not emphasis\*");
+/// assert_eq!(escape_contextual("a < b, <b>", None), r"a < b, \<b>");
+/// ```
+pub fn escape_contextual(s: &str, preceding: Option<char>) -> Cow<'_, str> {
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let mut escaped = String::new();
+    let mut copied = 0;
+    let mut index = 0;
+    while index < chars.len() {
+        let (position, c) = chars[index];
+        // Emphasis delimiters are judged as a whole run, like `**`
+        let mut end = index + 1;
+        if matches!(c, '*' | '_') {
+            while chars.get(end).is_some_and(|&(_, next)| next == c) {
+                end += 1;
+            }
+        }
+        let before = match index {
+            0 => preceding,
+            _ => Some(chars[index - 1].1),
+        };
+        let after = chars.get(end).map(|&(_, next)| next);
+
+        let escape = match c {
+            '`' | '[' | ']' => true,
+            '\\' => after.map_or(true, |next| next.is_ascii_punctuation() || next == '\n'),
+            '<' => after.map_or(true, |next| {
+                next.is_ascii_alphabetic() || matches!(next, '/' | '!' | '?')
+            }),
+            '&' => starts_char_reference(&s[position..]),
+            // A run continuing the one just written, or followed by who knows what
+            '*' | '_' => match after {
+                _ if index == 0 && preceding == Some(c) => true,
+                Some(after) => is_emphasis_delimiter(c, before, after),
+                None => true,
+            },
+            _ => false,
+        };
+        if escape {
+            for &(position, _) in &chars[index..end] {
+                escaped.push_str(&s[copied..position]);
+                escaped.push('\\');
+                copied = position;
+            }
+        }
+        index = end;
+    }
+    if escaped.is_empty() {
+        return Cow::Borrowed(s);
+    }
+    escaped.push_str(&s[copied..]);
+    Cow::Owned(escaped)
+}
+
+/// Whether a run of `c` between `before` (`None` at the start of a line) and
+/// `after` can open or close emphasis, following the CommonMark flanking rules
+fn is_emphasis_delimiter(c: char, before: Option<char>, after: char) -> bool {
+    let before_space = before.map_or(true, char::is_whitespace);
+    let before_punct = before.is_some_and(is_punctuation);
+    let after_space = after.is_whitespace();
+    let after_punct = is_punctuation(after);
+    let left_flanking = !after_space && (!after_punct || before_space || before_punct);
+    let right_flanking = !before_space && (!before_punct || after_space || after_punct);
+    if c == '*' {
+        return left_flanking || right_flanking;
+    }
+    // `_` can't open or close emphasis inside a word
+    let opens = left_flanking && (!right_flanking || before_punct);
+    let closes = right_flanking && (!left_flanking || after_punct);
+    opens || closes
+}
+
+/// Punctuation in the sense of the flanking rules, counting every non-ASCII
+/// symbol to be safe
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || !(c.is_ascii() || c.is_alphanumeric() || c.is_whitespace())
+}
+
+/// Whether `s` starts with something like `&amp;`, `&#35;` or `&#x23;`
+fn starts_char_reference(s: &str) -> bool {
+    let Some(rest) = s.strip_prefix('&') else {
+        return false;
+    };
+    let (body, rest) = match rest.strip_prefix('#') {
+        Some(numeric) => match numeric.strip_prefix(['x', 'X']) {
+            Some(hex) => split_while(hex, |c| c.is_ascii_hexdigit()),
+            None => split_while(numeric, |c| c.is_ascii_digit()),
+        },
+        None => split_while(rest, |c| c.is_ascii_alphanumeric()),
+    };
+    !body.is_empty() && rest.starts_with(';')
+}
+
+fn split_while(s: &str, f: impl Fn(char) -> bool) -> (&str, &str) {
+    s.split_at(s.find(|c| !f(c)).unwrap_or(s.len()))
+}
+
+/// Position in `line` where a backslash keeps it from starting a block
+///
+/// Covers ATX headings, block quotes, bullet and ordered list markers,
+/// thematic breaks, setext heading underlines and code fences, which would
+/// otherwise turn paragraph text into a different block when re-parsed.
+pub(crate) fn block_start_escape(line: &str) -> Option<usize> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let first = rest.chars().next()?;
+    let after = |n: usize| rest[n..].chars().next();
+    let ends_marker = |n: usize| matches!(after(n), None | Some(' ' | '\t'));
+    // A line of a single repeated character and spaces, like `***` or `- - -`
+    let only = |c: char| {
+        rest.trim_end_matches([' ', '\t'])
+            .chars()
+            .all(|x| x == c || x == ' ')
+    };
+
+    let escape = match first {
+        '>' => true,
+        '#' => {
+            let hashes = rest.len() - rest.trim_start_matches('#').len();
+            hashes <= 6 && ends_marker(hashes)
+        }
+        '-' | '+' => ends_marker(1) || only(first),
+        '*' | '_' => ends_marker(1) || (only(first) && rest.matches(first).count() >= 3),
+        '=' => only('='),
+        '`' | '~' => rest.starts_with(if first == '`' { "```" } else { "~~~" }),
+        '0'..='9' => {
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits <= 9 && matches!(after(digits), Some('.' | ')')) && ends_marker(digits + 1) {
+                return Some(indent + digits);
+            }
+            false
+        }
+        _ => false,
+    };
+    escape.then_some(indent)
+}
+
+/// Escape the lines of paragraph text that would otherwise start a block, like
+/// `# not a heading` or `1. not a list`
+///
+/// The first line is only escaped if `first_line_starts_line` is set, since
+/// text following other inline content on the same line can't start a block.
+pub(crate) fn escape_block_starts(content: &str, first_line_starts_line: bool) -> Cow<'_, str> {
+    let mut escaped = String::new();
+    let mut copied = 0;
+    let mut line_start = 0;
+    for (i, line) in content.split('\n').enumerate() {
+        if i > 0 || first_line_starts_line {
+            if let Some(position) = block_start_escape(line) {
+                let position = line_start + position;
+                escaped.push_str(&content[copied..position]);
+                escaped.push('\\');
+                copied = position;
+            }
+        }
+        line_start += line.len() + 1;
+    }
+    if escaped.is_empty() {
+        return Cow::Borrowed(content);
+    }
+    escaped.push_str(&content[copied..]);
+    Cow::Owned(escaped)
+}
+
+/// Checks if a character is a control character that breaks downstream parsers
+///
+/// Matches NUL and other C0 controls (except tab, line feed and carriage return),
+/// DEL, C1 controls and the byte order mark.
+pub(crate) fn is_disallowed_control(c: char) -> bool {
+    (c.is_control() && !matches!(c, '\t' | '\n' | '\r')) || c == '\u{FEFF}'
+}
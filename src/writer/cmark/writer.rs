@@ -1,24 +1,90 @@
 //! Main CommonMark writer struct and core functionality.
 
-use crate::ast::{CustomNode, Node};
+use super::memo::SharedBlockMemo;
+use super::trace::{NodePaths, WriteEdit, WriteEvent, WriteTrace};
+use super::utils::{escape_block_starts, escape_str, CommonMarkEscapes};
+use crate::ast::{CustomNode, Node, NodePath};
+use crate::diagnostics;
 use crate::error::{WriteError, WriteResult};
-use crate::options::WriterOptions;
+use crate::numbering::Numbering;
+use crate::ops::{is_toc_marker, table_of_contents, HeadingAnchors, READ_MORE_MARKER};
+use crate::options::{
+    DocumentOptions, EmptyContainerPolicy, OptionsConflict, SoftBreakStyle, WriterOptions,
+};
+use crate::traits::FallbackStrategy;
 use crate::writer::context::{NewlineContext, NewlineStrategy, RenderingMode};
 use ecow::EcoString;
+use std::borrow::Cow;
 use std::fmt;
+use std::panic::Location;
+use std::sync::Arc;
 
 /// CommonMark writer with flexible newline control
 ///
 /// This writer uses a context-based system for intelligent newline handling,
 /// allowing fine-grained control over formatting in different scenarios.
+///
+/// Options are held behind an [`Arc`], so the child writers created for nested
+/// containers (block quotes, list items) share them instead of cloning.
 #[derive(Debug)]
 pub struct CommonMarkWriter {
-    /// Writer options
-    pub options: WriterOptions,
+    /// Writer options, shared with any child writers
+    pub options: Arc<WriterOptions>,
     /// Buffer for storing the output text
     pub(super) buffer: EcoString,
     /// Current rendering context
     context: NewlineContext,
+    /// Rendered byte length of each top-level block, when tracked
+    block_sizes: Vec<(NodePath, usize)>,
+    /// Whether a tracked document is being written, so nested documents aren't measured
+    measuring_blocks: bool,
+    /// Last node written by `write_all`/`append`, for separating the next one
+    stream: Option<StreamPosition>,
+    /// Whether a table cell is being written with soft breaks as spaces
+    pub(super) in_table_cell: bool,
+    /// Whether paragraph text is being written, so lines that would start a
+    /// block are escaped
+    pub(super) in_paragraph: bool,
+    /// Text written on the current line by enclosing writers before this
+    /// writer's output starts, like the markers of enclosing list items
+    pub(super) line_prefix: EcoString,
+    /// Nesting depth of the node being written, reported in diagnostics spans
+    depth: usize,
+    /// Table of contents of the document being written, when `toc_marker` is set
+    toc: Option<Arc<Node>>,
+    /// Anchors of the headings of the document being written, for cross-references
+    pub(super) heading_anchors: Option<Arc<HeadingAnchors>>,
+    /// Numbers of figures, tables and equations, shared with any child writers
+    pub(super) numbering: Arc<Numbering>,
+    /// Changes made to the buffer, when `trace_writes` is enabled
+    trace: Option<WriteTrace>,
+    /// Type names and paths of the nodes being written, when `trace_writes`
+    /// is enabled
+    trace_nodes: Vec<(&'static str, Option<NodePath>)>,
+    /// Paths of the nodes of the tree being written, when `trace_writes` is
+    /// enabled; shared with any child writers
+    trace_paths: Option<Arc<NodePaths>>,
+    /// Output of the blocks written so far, when `memoize_blocks` is enabled;
+    /// shared with any child writers
+    pub(super) memo: Option<SharedBlockMemo>,
+    /// Whether a block being remembered by the memo is written, so the blocks
+    /// inside it aren't looked up separately
+    pub(super) in_memoized_block: bool,
+    /// Conflicts found in the options in strict mode, reported by every write
+    invalid_options: Vec<OptionsConflict>,
+    /// Whether content errors get the location of the offending node, which
+    /// only the outermost writer knows
+    locates_errors: bool,
+}
+
+/// What `write_all` needs to know about the previously streamed node
+#[derive(Debug, Clone, Copy)]
+struct StreamPosition {
+    /// Whether the node was a block
+    block: bool,
+    /// Whether the node still needs its trailing newline, which is only written
+    /// once another node follows (like the last node of a document)
+    pending_newline: bool,
 }
 
 impl CommonMarkWriter {
@@ -60,22 +126,166 @@ impl CommonMarkWriter {
     /// let writer = CommonMarkWriter::with_options(options);
     /// ```
     pub fn with_options(options: WriterOptions) -> Self {
+        Self::with_shared_options(Arc::new(options))
+    }
+
+    /// Create a new CommonMark writer sharing already allocated options
+    ///
+    /// Useful for rendering many documents (possibly on several threads) with
+    /// the same configuration without cloning it for every writer.
+    ///
+    /// The options are checked with [`WriterOptions::validate`]: in strict mode
+    /// writing fails with [`WriteError::InvalidOptions`] if they conflict,
+    /// otherwise every conflict is logged as a warning.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use cmark_writer::writer::CommonMarkWriter;
+    /// use cmark_writer::options::WriterOptions;
+    ///
+    /// let options = Arc::new(WriterOptions::default());
+    /// let first = CommonMarkWriter::with_shared_options(Arc::clone(&options));
+    /// let second = CommonMarkWriter::with_shared_options(options);
+    /// assert!(Arc::ptr_eq(&first.options, &second.options));
+    /// ```
+    pub fn with_shared_options(options: Arc<WriterOptions>) -> Self {
+        let invalid_options = match options.validate() {
+            Ok(()) => Vec::new(),
+            Err(conflicts) if options.strict => conflicts,
+            Err(conflicts) => {
+                for conflict in conflicts {
+                    diagnostics::warn!("{}. Strict mode is off.", conflict);
+                }
+                Vec::new()
+            }
+        };
+        Self {
+            invalid_options,
+            locates_errors: true,
+            ..Self::from_shared_options(options)
+        }
+    }
+
+    /// Create a writer without checking the options, which its parent did
+    fn from_shared_options(options: Arc<WriterOptions>) -> Self {
+        let trace = options.trace_writes.then(WriteTrace::default);
+        // Copied output wouldn't show up in the trace
+        let memo = (options.memoize_blocks && !options.trace_writes).then(SharedBlockMemo::default);
         Self {
             options,
             buffer: EcoString::new(),
             context: NewlineContext::block(),
+            block_sizes: Vec::new(),
+            measuring_blocks: false,
+            stream: None,
+            in_table_cell: false,
+            in_paragraph: false,
+            line_prefix: EcoString::new(),
+            depth: 0,
+            toc: None,
+            heading_anchors: None,
+            numbering: Arc::default(),
+            trace,
+            trace_nodes: Vec::new(),
+            trace_paths: None,
+            memo,
+            in_memoized_block: false,
+            invalid_options: Vec::new(),
+            locates_errors: false,
         }
     }
 
     /// Create a writer with a specific rendering context
     pub fn with_context(options: WriterOptions, context: NewlineContext) -> Self {
-        Self {
-            options,
-            buffer: EcoString::new(),
-            context,
+        let mut writer = Self::with_options(options);
+        writer.context = context;
+        writer
+    }
+
+    /// Create an empty child writer sharing this writer's options
+    pub(super) fn child_writer(&self, context: NewlineContext) -> Self {
+        let mut writer = Self::from_shared_options(Arc::clone(&self.options));
+        writer.context = context;
+        writer.toc = self.toc.clone();
+        writer.heading_anchors = self.heading_anchors.clone();
+        writer.numbering = Arc::clone(&self.numbering);
+        writer.trace_nodes = self.trace_nodes.clone();
+        writer.trace_paths = self.trace_paths.clone();
+        writer.memo = self.memo.clone();
+        writer.in_memoized_block = self.in_memoized_block;
+        writer
+    }
+
+    /// Build the table of contents of `root` if a `toc_marker` is set, and the
+    /// anchors its cross-references resolve to, unless the outermost document
+    /// has done so already
+    pub(crate) fn prepare_document(&mut self, root: &Node) {
+        if self.toc.is_none() && self.options.toc_marker.is_some() {
+            self.toc = Some(Arc::new(table_of_contents(root)));
+        }
+        if self.heading_anchors.is_none() {
+            self.heading_anchors = Some(Arc::new(HeadingAnchors::new(root)));
+        }
+    }
+
+    /// Anchors of the headings of the document being written
+    pub(super) fn heading_anchors(&self) -> Option<&HeadingAnchors> {
+        self.heading_anchors.as_deref()
+    }
+
+    /// The table of contents replacing a paragraph with `content`, if it's the marker
+    fn toc_for(&self, content: &[Node]) -> Option<Arc<Node>> {
+        let marker = self.options.toc_marker.as_deref()?;
+        if is_toc_marker(content, marker) {
+            self.toc.clone()
+        } else {
+            None
         }
     }
 
+    /// Numbers used to write `Node::NumberRef`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::{CommonMarkWriter, Node, ToCommonMark};
+    ///
+    /// let mut writer = CommonMarkWriter::new();
+    /// writer.numbering_mut().assign("figure", "overview");
+    /// Node::NumberRef("figure".into(), "overview".into())
+    ///     .to_commonmark(&mut writer)
+    ///     .unwrap();
+    /// assert_eq!(writer.into_string(), "Figure 1");
+    /// ```
+    pub fn numbering(&self) -> &Numbering {
+        &self.numbering
+    }
+
+    /// Mutable access to the numbers used to write `Node::NumberRef`
+    pub fn numbering_mut(&mut self) -> &mut Numbering {
+        Arc::make_mut(&mut self.numbering)
+    }
+
+    /// Replace the numbers used to write `Node::NumberRef`
+    pub fn set_numbering(&mut self, numbering: Numbering) {
+        self.numbering = Arc::new(numbering);
+    }
+
+    /// Get a reference to the writer options
+    pub fn options(&self) -> &WriterOptions {
+        &self.options
+    }
+
+    /// Get a mutable reference to the writer options
+    ///
+    /// If the options are shared with other writers they are cloned first
+    /// (copy-on-write), so changes never leak into other writers.
+    pub fn options_mut(&mut self) -> &mut WriterOptions {
+        Arc::make_mut(&mut self.options)
+    }
+
     /// Whether the writer is in strict mode
     pub(super) fn is_strict_mode(&self) -> bool {
         self.options.strict
@@ -122,13 +332,47 @@ impl CommonMarkWriter {
 
     /// Write document children with proper spacing
     pub(super) fn write_document_children(&mut self, children: &[Node]) -> WriteResult<()> {
+        if !self.options.track_block_sizes || self.measuring_blocks {
+            return self.write_document_blocks(children, false);
+        }
+        self.block_sizes.clear();
+        self.measuring_blocks = true;
+        let result = self.write_document_blocks(children, true);
+        self.measuring_blocks = false;
+        result
+    }
+
+    /// Write the children of a document with its option overrides merged over ours
+    fn write_configured_document(
+        &mut self,
+        overrides: &DocumentOptions,
+        children: &[Node],
+    ) -> WriteResult<()> {
+        if overrides.is_empty() {
+            return self.write_document_children(children);
+        }
+        let merged = Arc::new(overrides.merged_over(&self.options));
+        let original = std::mem::replace(&mut self.options, merged);
+        let result = self.write_document_children(children);
+        self.options = original;
+        result
+    }
+
+    fn write_document_blocks(&mut self, children: &[Node], measure: bool) -> WriteResult<()> {
+        let last = children.iter().rposition(|node| !self.omits_node(node));
+        let mut previous: Option<&Node> = None;
         for (i, node) in children.iter().enumerate() {
-            if i > 0 {
-                self.write_node_separator(&children[i - 1], node)?;
+            if self.omits_node(node) {
+                continue;
+            }
+            if let Some(previous) = previous {
+                self.write_node_separator(previous, node)?;
             }
+            previous = Some(node);
+            let start = self.buffer.len();
 
             // For the last child, be selective about trailing newlines
-            if i == children.len() - 1 {
+            if Some(i) == last {
                 // If it's a block element, add trailing newline
                 if node.is_block() {
                     self.write_node(node)?;
@@ -139,6 +383,11 @@ impl CommonMarkWriter {
             } else {
                 self.write_node(node)?;
             }
+
+            if measure {
+                let size = self.buffer.len() - start;
+                self.block_sizes.push((NodePath::root().child(i), size));
+            }
         }
         Ok(())
     }
@@ -146,16 +395,70 @@ impl CommonMarkWriter {
     /// Write node content without context-aware newline handling
     /// This is called by write_node() which handles the newline logic
     pub fn write_node_content(&mut self, node: &Node) -> WriteResult<()> {
+        self.check_options()?;
+        let node = node.resolve_shared();
+        let result = if self.memoizes(node) {
+            self.write_memoized(node)
+        } else {
+            self.in_node_span(node, |writer| writer.render_node_content(node))
+        };
+        self.locate(node, result)
+    }
+
+    /// Run `f` inside the diagnostics span of `node`, one level deeper
+    fn in_node_span<R>(
+        &mut self,
+        node: &Node,
+        f: impl FnOnce(&mut Self) -> WriteResult<R>,
+    ) -> WriteResult<R> {
+        let _span = diagnostics::node_span(node, self.depth);
+        self.depth += 1;
+        if self.trace.is_some() {
+            let path = self.trace_path(node);
+            self.trace_nodes.push((node.type_name(), path));
+        }
+        let result = f(self);
+        if self.trace.is_some() {
+            self.trace_nodes.pop();
+            if self.trace_nodes.is_empty() && self.locates_errors {
+                self.trace_paths = None;
+            }
+        }
+        self.depth -= 1;
+        result
+    }
+
+    /// Path of `node` for the trace; the outermost node written is the root
+    fn trace_path(&mut self, node: &Node) -> Option<NodePath> {
+        if self.trace_nodes.is_empty() && self.locates_errors {
+            self.trace_paths = Some(Arc::new(NodePaths::of(node)));
+            return Some(NodePath::root());
+        }
+        let within = self.trace_nodes.last().and_then(|(_, path)| path.as_ref());
+        self.trace_paths.as_ref()?.find(node, within)
+    }
+
+    /// Body of [`write_node_content`](Self::write_node_content) for a resolved node
+    fn render_node_content(&mut self, node: &Node) -> WriteResult<()> {
         // 处理自定义节点
         if let Node::Custom(custom_node) = node {
-            // Ensure that CustomNode trait requires render_commonmark method
-            return custom_node.render_commonmark(self);
+            return self.write_custom_node(custom_node);
         }
 
         // 处理文档节点
+        if matches!(node, Node::Document(_) | Node::ConfiguredDocument { .. }) {
+            self.prepare_document(node);
+        }
         if let Node::Document(children) = node {
             return self.write_document_children(children);
         }
+        if let Node::ConfiguredDocument { options, children } = node {
+            return self.write_configured_document(options, children);
+        }
+
+        if node.is_empty_container() {
+            return self.write_empty_container(node);
+        }
 
         // 在严格模式下检查内联元素中的换行符
         if self.options.strict
@@ -163,15 +466,17 @@ impl CommonMarkWriter {
             && !matches!(node, Node::SoftBreak | Node::HardBreak)
         {
             match node {
-                Node::Text(content) => {
-                    if content.contains('\n') {
-                        return Err(WriteError::NewlineInInlineElement("Text".into()));
-                    }
+                Node::Text(content) if content.contains('\n') => {
+                    return Err(WriteError::NewlineInInlineElement("Text".into()));
                 }
-                Node::InlineCode(content) => {
-                    if content.contains('\n') {
-                        return Err(WriteError::NewlineInInlineElement("InlineCode".into()));
-                    }
+                Node::InlineCode(content) if content.contains('\n') => {
+                    return Err(WriteError::NewlineInInlineElement("InlineCode".into()));
+                }
+                Node::Math {
+                    display: false,
+                    content,
+                } if content.contains('\n') => {
+                    return Err(WriteError::NewlineInInlineElement("Math".into()));
                 }
                 Node::Emphasis(children) | Node::Strong(children) => {
                     for child in children {
@@ -195,8 +500,13 @@ impl CommonMarkWriter {
                 level,
                 content,
                 heading_type,
-            } => self.write_heading(*level, content, heading_type),
-            Node::Paragraph(content) => self.write_paragraph(content),
+                custom_id,
+            } => self.write_heading_with_id(*level, content, heading_type, custom_id.as_deref()),
+            Node::Paragraph(content) => match self.toc_for(content) {
+                Some(toc) if toc.is_empty_container() => Ok(()),
+                Some(toc) => self.write_node_content(&toc),
+                None => self.write_paragraph(content),
+            },
             Node::BlockQuote(content) => self.write_blockquote(content),
             Node::CodeBlock {
                 language,
@@ -205,13 +515,18 @@ impl CommonMarkWriter {
             } => self.write_code_block(language, content, block_type),
             Node::UnorderedList(items) => self.write_unordered_list(items),
             Node::OrderedList { start, items } => self.write_ordered_list(items, *start, true), // Default to tight
+            Node::DefinitionList(items) => self.write_definition_list(items),
             Node::ThematicBreak => self.write_thematic_break(),
+            Node::ReadMoreMarker => self.write_html_block(READ_MORE_MARKER),
 
             // Inline elements
             Node::Text(content) => self.write_text_content(content),
+            Node::NumberRef(kind, id) => self.write_number_ref(kind, id),
+            Node::CrossRef { target, text } => self.write_cross_ref(target, text.as_deref()),
             Node::Emphasis(content) => self.write_emphasis(content),
             Node::Strong(content) => self.write_strong(content),
             Node::InlineCode(content) => self.write_code_content(content),
+            Node::Math { display, content } => self.write_math(*display, content),
             Node::Link {
                 url,
                 title,
@@ -227,9 +542,14 @@ impl CommonMarkWriter {
                 destination,
                 title,
             } => self.write_link_reference_definition(label, destination, title),
+            Node::FootnoteReference(label) => self.write_footnote_reference(label),
+            Node::FootnoteDefinition { label, content } => {
+                self.write_footnote_definition(label, content)
+            }
 
             // HTML elements
             Node::HtmlBlock(content) => self.write_html_block(content),
+            Node::HtmlInline(html) => self.write_str(html),
             Node::HtmlElement(element) => self.write_html_element(element),
 
             // Table elements
@@ -252,27 +572,140 @@ impl CommonMarkWriter {
             Node::Custom(custom_node) => self.write_custom_node(custom_node),
 
             _ => {
-                log::warn!("Unsupported node type encountered and skipped: {:?}", node);
+                diagnostics::warn!("Unsupported node type encountered and skipped: {:?}", node);
+                Ok(())
+            }
+        }
+    }
+
+    /// Effective policy for containers with no content
+    pub(super) fn empty_container_policy(&self) -> EmptyContainerPolicy {
+        self.options
+            .empty_container_policy
+            .resolve(self.options.strict)
+    }
+
+    /// Whether the node is omitted from the output: an empty container under the
+    /// `Omit` policy, or a table of contents marker in a document without headings
+    pub(super) fn omits_node(&self, node: &Node) -> bool {
+        if let Node::Paragraph(content) = node.resolve_shared() {
+            // A table of contents without headings leaves nothing behind
+            if self
+                .toc_for(content)
+                .is_some_and(|toc| toc.is_empty_container())
+            {
+                return true;
+            }
+        }
+        node.is_empty_container() && self.empty_container_policy() == EmptyContainerPolicy::Omit
+    }
+
+    /// Write a container with no content according to the empty container policy
+    fn write_empty_container(&mut self, node: &Node) -> WriteResult<()> {
+        match self.empty_container_policy() {
+            EmptyContainerPolicy::Placeholder => match node {
+                Node::Heading {
+                    level,
+                    heading_type,
+                    custom_id,
+                    ..
+                } => self.write_heading_with_id(*level, &[], heading_type, custom_id.as_deref()),
+                Node::BlockQuote(_) => self.write_char('>'),
+                // No CommonMark syntax for these when empty
+                _ => self.write_empty_container_as_html(node),
+            },
+            EmptyContainerPolicy::Error => Err(WriteError::InvalidStructure(
+                format!("Empty {} is not allowed", node.type_name()).into(),
+            )),
+            _ => {
+                if self.options.empty_container_policy == EmptyContainerPolicy::Auto {
+                    diagnostics::warn!("Empty {} omitted. Strict mode is off.", node.type_name());
+                }
                 Ok(())
             }
         }
     }
 
-    /// Write a custom node using its implementation
+    /// Write a custom node using its implementation, or its fallback when the
+    /// node can't be expressed with this writer's options
     #[allow(clippy::borrowed_box)]
-    pub(super) fn write_custom_node(&mut self, node: &Box<dyn CustomNode>) -> WriteResult<()> {
-        node.render_commonmark(self)
+    pub(crate) fn write_custom_node(&mut self, node: &Box<dyn CustomNode>) -> WriteResult<()> {
+        if node.supports_commonmark(self) {
+            if !self.options.trust_custom_nodes {
+                let mut sandbox = self.child_writer(self.context.clone());
+                node.render_commonmark(&mut sandbox)?;
+                let output = self.take_child_buffer(sandbox);
+                return self.write_untrusted_output(&output, node.is_block());
+            }
+            if !node.is_block() {
+                return node.render_commonmark(self);
+            }
+            // Collect block output the way containers collect their content, so
+            // a trailing newline written by the node doesn't add a blank line
+            // before the next block of a block quote or list item
+            let mut temp_writer = self.child_writer(self.context.clone());
+            temp_writer.line_prefix = self.current_line();
+            node.render_commonmark(&mut temp_writer)?;
+            let content = self.take_child_buffer(temp_writer);
+            self.push_raw(content.trim_end_matches('\n'));
+            return Ok(());
+        }
+        match node.fallback() {
+            FallbackStrategy::Html if self.options.trust_custom_nodes => {
+                self.write_custom_node_as_html(node.as_ref())
+            }
+            FallbackStrategy::Html | FallbackStrategy::PlainText => {
+                self.write_custom_node_as_text(node.as_ref())
+            }
+            FallbackStrategy::Skip => {
+                diagnostics::warn!(
+                    "Custom node {} can't be written as CommonMark and was skipped",
+                    node.type_name()
+                );
+                Ok(())
+            }
+            FallbackStrategy::Error => Err(WriteError::Custom {
+                message: format!("{} can't be written as CommonMark", node.type_name()).into(),
+                code: Some("unsupported_custom_node".into()),
+            }),
+        }
+    }
+
+    /// Write the output of an untrusted custom node as text
+    ///
+    /// Whitespace is collapsed to single spaces and everything that could start
+    /// Markdown syntax or raw HTML is escaped, whatever `escape_special_chars` says.
+    pub(super) fn write_untrusted_output(&mut self, output: &str, block: bool) -> WriteResult<()> {
+        let output = self.sanitize_control_chars(Cow::Borrowed(output), "Custom")?;
+        let text = output.split_whitespace().collect::<Vec<_>>().join(" ");
+        let text = escape_str::<CommonMarkEscapes>(&text);
+        let at_line_start = block || self.buffer.is_empty() || self.buffer.ends_with('\n');
+        let text = escape_block_starts(&text, at_line_start);
+        self.write_str(&text)
     }
 
     /// Check if the inline node contains a newline character and return an error if it does
+    ///
+    /// Soft breaks only count when they are written as line breaks.
     pub(super) fn check_no_newline(&self, node: &Node, context: &str) -> WriteResult<()> {
-        if Self::node_contains_newline(node) {
+        let soft_breaks = self.options.soft_break_style != SoftBreakStyle::Space;
+        self.check_no_line_break(node, context, soft_breaks)
+    }
+
+    /// Like [`check_no_newline`](Self::check_no_newline), optionally allowing soft breaks
+    pub(super) fn check_no_line_break(
+        &self,
+        node: &Node,
+        context: &str,
+        soft_breaks: bool,
+    ) -> WriteResult<()> {
+        if Self::contains_line_break(node, soft_breaks) {
             if self.is_strict_mode() {
                 return Err(WriteError::NewlineInInlineElement(
                     context.to_string().into(),
                 ));
             } else {
-                log::warn!(
+                diagnostics::warn!(
                     "Newline character found in inline element '{}', but non-strict mode allows it (output may be affected).",
                     context
                 );
@@ -281,19 +714,29 @@ impl CommonMarkWriter {
         Ok(())
     }
 
-    /// Check if the inline node contains a newline character recursively
-    pub(super) fn node_contains_newline(node: &Node) -> bool {
+    /// Check if the inline node contains a newline character recursively,
+    /// counting soft breaks only if `soft_breaks` is set
+    pub(super) fn contains_line_break(node: &Node, soft_breaks: bool) -> bool {
+        let any = |children: &[Node]| {
+            children
+                .iter()
+                .any(|child| Self::contains_line_break(child, soft_breaks))
+        };
         match node {
-            Node::Text(s) | Node::InlineCode(s) => s.contains('\n'),
-            Node::Emphasis(children) | Node::Strong(children) => {
-                children.iter().any(Self::node_contains_newline)
-            }
+            Node::Text(s) | Node::InlineCode(s) | Node::Math { content: s, .. } => s.contains('\n'),
+            Node::Emphasis(children) | Node::Strong(children) => any(children),
             #[cfg(feature = "gfm")]
-            Node::Strikethrough(children) => children.iter().any(Self::node_contains_newline),
-            Node::HtmlElement(element) => element.children.iter().any(Self::node_contains_newline),
-            Node::Link { content, .. } => content.iter().any(Self::node_contains_newline),
-            Node::Image { alt, .. } => alt.iter().any(Self::node_contains_newline),
-            Node::SoftBreak | Node::HardBreak => true,
+            Node::Strikethrough(children) => any(children),
+            Node::HtmlElement(element) => any(&element.children),
+            Node::Link { content, .. }
+            | Node::CrossRef {
+                text: Some(content),
+                ..
+            } => any(content),
+            Node::Image { alt, .. } => any(alt),
+            Node::SoftBreak => soft_breaks,
+            Node::HardBreak => true,
+            Node::Shared(node) => Self::contains_line_break(node, soft_breaks),
             // Custom nodes are handled separately
             Node::Custom(_) => false,
             _ => false,
@@ -302,7 +745,8 @@ impl CommonMarkWriter {
 
     /// Get the generated CommonMark format text
     ///
-    /// Consumes the writer and returns the generated string
+    /// Consumes the writer and returns the generated string, after running any
+    /// `post_processors` over it and applying the `final_newline` policy
     ///
     /// # Example
     ///
@@ -317,25 +761,159 @@ impl CommonMarkWriter {
     /// assert_eq!(result, "Hello");
     /// ```
     pub fn into_string(self) -> EcoString {
-        self.buffer
+        let mut output = self.buffer;
+        for filter in &self.options.post_processors {
+            output = filter.apply(output);
+        }
+        self.options.final_newline.apply(output)
+    }
+
+    /// Rendered byte length of each top-level block of the last document written
+    ///
+    /// Only recorded when [`WriterOptions::track_block_sizes`] is enabled; empty
+    /// otherwise. Sizes are measured before post-processors run and don't include
+    /// the blank lines separating blocks, so large blocks (giant tables) can be
+    /// found and paginated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{Node, NodePath};
+    /// use cmark_writer::options::WriterOptionsBuilder;
+    /// use cmark_writer::writer::CommonMarkWriter;
+    /// use cmark_writer::ToCommonMark;
+    ///
+    /// let options = WriterOptionsBuilder::new().track_block_sizes(true).build();
+    /// let mut writer = CommonMarkWriter::with_options(options);
+    /// let doc = Node::Document(vec![
+    ///     Node::Paragraph(vec![Node::Text("short".into())]),
+    ///     Node::Paragraph(vec![Node::Text("a bit longer".into())]),
+    /// ]);
+    /// doc.to_commonmark(&mut writer).unwrap();
+    /// assert_eq!(
+    ///     writer.block_sizes(),
+    ///     [(NodePath::root().child(0), 6), (NodePath::root().child(1), 13)]
+    /// );
+    /// ```
+    pub fn block_sizes(&self) -> &[(NodePath, usize)] {
+        &self.block_sizes
+    }
+
+    /// Consume a child writer and return its buffer without running
+    /// post-processors, for embedding it in this writer's output
+    pub(super) fn take_child_buffer(&mut self, child: Self) -> EcoString {
+        if let (Some(trace), Some(child_trace)) = (&mut self.trace, child.trace) {
+            trace.adopt(child_trace);
+        }
+        child.buffer
+    }
+
+    /// Every change made to the buffer so far, if
+    /// [`WriterOptions::trace_writes`] is enabled
+    ///
+    /// See [`WriteTrace`] for an example.
+    pub fn write_trace(&self) -> Option<&WriteTrace> {
+        self.trace.as_ref()
+    }
+
+    /// Record a change made to the buffer at `offset` by the code at `location`
+    fn record(&mut self, offset: usize, edit: WriteEdit, location: &'static Location<'static>) {
+        if let Some(trace) = &mut self.trace {
+            trace.push(WriteEvent {
+                offset,
+                edit,
+                nodes: self.trace_nodes.iter().map(|(name, _)| *name).collect(),
+                path: self.trace_nodes.last().and_then(|(_, path)| path.clone()),
+                location,
+                writer_depth: 0,
+            });
+        }
+    }
+
+    /// Record the text appended to the buffer since it was `start` bytes long
+    fn record_insert(&mut self, start: usize, location: &'static Location<'static>) {
+        if self.trace.is_some() && self.buffer.len() > start {
+            let text = self.buffer[start..].into();
+            self.record(start, WriteEdit::Insert(text), location);
+        }
+    }
+
+    /// Append `s` to the buffer as is, bypassing blank line collapsing
+    #[track_caller]
+    pub(super) fn push_raw(&mut self, s: &str) {
+        let start = self.buffer.len();
+        self.buffer.push_str(s);
+        self.record_insert(start, Location::caller());
+    }
+
+    /// Remove the last character of the buffer
+    #[track_caller]
+    pub(super) fn pop_raw(&mut self) -> Option<char> {
+        let c = self.buffer.pop()?;
+        if self.trace.is_some() {
+            let offset = self.buffer.len();
+            self.record(offset, WriteEdit::Remove(c.into()), Location::caller());
+        }
+        Some(c)
+    }
+
+    /// Remove everything after the first `len` bytes of the buffer
+    #[track_caller]
+    pub(super) fn truncate_raw(&mut self, len: usize) {
+        if len >= self.buffer.len() {
+            return;
+        }
+        if self.trace.is_some() {
+            let removed = self.buffer[len..].into();
+            self.record(len, WriteEdit::Remove(removed), Location::caller());
+        }
+        self.buffer.truncate(len);
     }
 
     /// Write a string to the output buffer
     ///
     /// This method is provided for custom node implementations to use
+    #[track_caller]
     pub fn write_str(&mut self, s: &str) -> WriteResult<()> {
-        self.buffer.push_str(s);
+        let start = self.buffer.len();
+        match self.options.max_blank_lines {
+            Some(max_blank_lines) if s.contains('\n') => {
+                for c in s.chars() {
+                    self.push_collapsing_blank_lines(c, max_blank_lines);
+                }
+            }
+            _ => self.buffer.push_str(s),
+        }
+        self.record_insert(start, Location::caller());
         Ok(())
     }
 
     /// Write a character to the output buffer
     ///
     /// This method is provided for custom node implementations to use
+    #[track_caller]
     pub fn write_char(&mut self, c: char) -> WriteResult<()> {
-        self.buffer.push(c);
+        let start = self.buffer.len();
+        match self.options.max_blank_lines {
+            Some(max_blank_lines) => self.push_collapsing_blank_lines(c, max_blank_lines),
+            None => self.buffer.push(c),
+        }
+        self.record_insert(start, Location::caller());
         Ok(())
     }
 
+    /// Push a character, dropping newlines that would exceed `max_blank_lines`
+    /// consecutive blank lines
+    fn push_collapsing_blank_lines(&mut self, c: char, max_blank_lines: usize) {
+        if c == '\n' {
+            let trailing_newlines = self.buffer.len() - self.buffer.trim_end_matches('\n').len();
+            if trailing_newlines > max_blank_lines {
+                return;
+            }
+        }
+        self.buffer.push(c);
+    }
+
     /// Get current rendering context
     pub fn context(&self) -> &NewlineContext {
         &self.context
@@ -386,14 +964,34 @@ impl CommonMarkWriter {
 
     /// Write a single node with context-aware formatting
     pub fn write_node(&mut self, node: &Node) -> WriteResult<()> {
+        self.check_options()?;
+        let node = node.resolve_shared();
+        let result = self.write_node_unlocated(node);
+        self.locate(node, result)
+    }
+
+    fn write_node_unlocated(&mut self, node: &Node) -> WriteResult<()> {
         // Handle document nodes specially - they manage their own newlines
+        if matches!(node, Node::Document(_) | Node::ConfiguredDocument { .. }) {
+            self.prepare_document(node);
+        }
         if let Node::Document(children) = node {
-            return self.write_document_children(children);
+            return self.in_node_span(node, |writer| writer.write_document_children(children));
+        }
+        if let Node::ConfiguredDocument { options, children } = node {
+            return self.in_node_span(node, |writer| {
+                writer.write_configured_document(options, children)
+            });
         }
 
         // Validate node is allowed in current context
         self.context.validate_node(node)?;
 
+        // Omitted empty containers don't get a trailing newline either
+        if self.omits_node(node) {
+            return Ok(());
+        }
+
         // Remember buffer state before writing
         let buffer_start = self.buffer.len();
 
@@ -414,13 +1012,87 @@ impl CommonMarkWriter {
         Ok(())
     }
 
+    /// Add the location of the offending node to a content error of the
+    /// outermost node being written
+    fn locate(&self, node: &Node, result: WriteResult<()>) -> WriteResult<()> {
+        match result {
+            Err(error) if self.locates_errors && self.depth == 0 && error.is_content_error() => {
+                let path = self
+                    .locate_error(node)
+                    .map_or_else(NodePath::root, |(path, _)| path);
+                Err(error.at_node(node, path))
+            }
+            result => result,
+        }
+    }
+
+    /// Fail if the options were found to conflict in strict mode
+    fn check_options(&self) -> WriteResult<()> {
+        if self.invalid_options.is_empty() {
+            Ok(())
+        } else {
+            Err(WriteError::InvalidOptions(self.invalid_options.clone()))
+        }
+    }
+
+    /// Find the innermost node responsible for a write error
+    ///
+    /// Writes `node` with a scratch writer sharing this writer's options. If that
+    /// fails, descends into the first child that also fails on its own, and
+    /// returns the path to the deepest such node (relative to `node`) together
+    /// with its error. Returns `None` if `node` writes successfully.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{Node, NodePath};
+    /// use cmark_writer::writer::CommonMarkWriter;
+    ///
+    /// let doc = Node::Document(vec![
+    ///     Node::Paragraph(vec![Node::Text("Fine".into())]),
+    ///     Node::heading(9, vec![Node::Text("Too deep".into())]),
+    /// ]);
+    ///
+    /// let writer = CommonMarkWriter::new();
+    /// let (path, _error) = writer.locate_error(&doc).unwrap();
+    /// assert_eq!(path, NodePath::root().child(1));
+    /// ```
+    pub fn locate_error(&self, node: &Node) -> Option<(NodePath, WriteError)> {
+        if let Err(error) = self.check_options() {
+            return Some((NodePath::root(), error));
+        }
+        let mut error = self
+            .child_writer(NewlineContext::block())
+            .write_node(node)
+            .err()?;
+        let mut path = NodePath::root();
+        let mut current = node;
+        'descend: loop {
+            for (segment, child) in current.children_with_segments() {
+                let mut writer = self.child_writer(NewlineContext::block());
+                if let Err(child_error) = writer.write_node_content(child) {
+                    path.push(segment);
+                    current = child;
+                    error = child_error;
+                    continue 'descend;
+                }
+            }
+            return Some((path, error));
+        }
+    }
+
     /// Write multiple nodes with intelligent spacing
     pub fn write_nodes(&mut self, nodes: &[Node]) -> WriteResult<()> {
-        for (i, node) in nodes.iter().enumerate() {
-            if i > 0 {
-                self.write_node_separator(&nodes[i - 1], node)?;
+        let mut previous: Option<&Node> = None;
+        for node in nodes {
+            if self.omits_node(node) {
+                continue;
+            }
+            if let Some(previous) = previous {
+                self.write_node_separator(previous, node)?;
             }
             self.write_node(node)?;
+            previous = Some(node);
         }
         Ok(())
     }
@@ -439,18 +1111,88 @@ impl CommonMarkWriter {
         content_fn(self)
     }
 
+    /// Write a stream of nodes as if they were the children of a document
+    ///
+    /// Nodes are separated according to the current context, so generators can
+    /// write blocks as they produce them instead of collecting a
+    /// [`Node::Document`] first. Separation continues across calls: the first
+    /// node of a call is separated from the last node of the previous
+    /// `write_all` or [`append`](Self::append) call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    /// use cmark_writer::writer::CommonMarkWriter;
+    ///
+    /// let blocks = vec![
+    ///     Node::heading(2, vec![Node::Text("Part 1".into())]),
+    ///     Node::heading(2, vec![Node::Text("Part 2".into())]),
+    /// ];
+    ///
+    /// let mut writer = CommonMarkWriter::new();
+    /// writer.write_all(&blocks).unwrap();
+    /// writer.append(&Node::Paragraph(vec![Node::Text("End".into())])).unwrap();
+    /// assert_eq!(writer.into_string(), "## Part 1\n\n## Part 2\n\nEnd\n");
+    /// ```
+    pub fn write_all<'a, I>(&mut self, nodes: I) -> WriteResult<()>
+    where
+        I: IntoIterator<Item = &'a Node>,
+    {
+        for node in nodes {
+            let node = node.resolve_shared();
+            if self.omits_node(node) {
+                continue;
+            }
+            if let Some(previous) = self.stream {
+                if previous.pending_newline {
+                    self.write_char('\n')?;
+                }
+                self.write_separator(previous.block, node.is_block())?;
+            }
+
+            let pending_newline = if node.is_block() {
+                self.write_node(node)?;
+                false
+            } else {
+                // Like the last child of a document, the trailing newline of an
+                // inline node waits until we know another node follows
+                self.context.validate_node(node)?;
+                let start = self.buffer.len();
+                self.write_node_content(node)?;
+                self.context
+                    .should_add_trailing_newline(&self.buffer[start..], Some(node))
+            };
+            self.stream = Some(StreamPosition {
+                block: node.is_block(),
+                pending_newline,
+            });
+        }
+        Ok(())
+    }
+
+    /// Write one more node of a stream, see [`write_all`](Self::write_all)
+    pub fn append(&mut self, node: &Node) -> WriteResult<()> {
+        self.write_all(std::iter::once(node))
+    }
+
     /// Write separator between nodes based on context
     fn write_node_separator(&mut self, prev_node: &Node, current_node: &Node) -> WriteResult<()> {
+        self.write_separator(prev_node.is_block(), current_node.is_block())
+    }
+
+    /// Write separator between nodes based on whether they are blocks
+    fn write_separator(&mut self, prev_block: bool, current_block: bool) -> WriteResult<()> {
         match self.context.mode {
             RenderingMode::Block => {
                 // Traditional block spacing
-                if prev_node.is_block() && current_node.is_block() {
+                if prev_block && current_block {
                     self.ensure_double_newline()?;
                 }
             }
             RenderingMode::InlineWithBlocks => {
                 // Smart spacing for mixed content
-                if prev_node.is_block() || current_node.is_block() {
+                if prev_block || current_block {
                     self.ensure_single_newline()?;
                 }
             }
@@ -465,14 +1207,13 @@ impl CommonMarkWriter {
             }
             RenderingMode::ListItem => {
                 // Conditional newlines for list items
-                if prev_node.is_block() && current_node.is_block() {
+                if prev_block && current_block {
                     self.ensure_single_newline()?;
                 }
             }
             RenderingMode::Custom => {
                 // Custom logic based on strategy
-                if (prev_node.is_block() || current_node.is_block())
-                    && self.context.strategy == NewlineStrategy::Always
+                if (prev_block || current_block) && self.context.strategy == NewlineStrategy::Always
                 {
                     self.ensure_single_newline()?;
                 }
@@ -502,7 +1243,11 @@ impl CommonMarkWriter {
     }
 
     /// Helper function for writing content with delimiters
-    pub(super) fn write_delimited(&mut self, content: &[Node], delimiter: &str) -> WriteResult<()> {
+    pub(super) fn write_delimited<'a>(
+        &mut self,
+        content: impl IntoIterator<Item = &'a Node>,
+        delimiter: &str,
+    ) -> WriteResult<()> {
         self.write_str(delimiter)?;
 
         // Use pure inline context for delimited content (like emphasis, strong, etc.)
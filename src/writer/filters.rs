@@ -0,0 +1,201 @@
+//! Built-in output filters for `WriterOptions::post_processors`.
+//!
+//! These cover common cleanup of the final CommonMark output, so callers don't
+//! need to wrap the writer to post-process its result.
+//!
+//! # Example
+//!
+//! ```rust
+//! use cmark_writer::options::WriterOptionsBuilder;
+//! use cmark_writer::writer::filters::{EnsureFinalNewline, TrimTrailingWhitespace};
+//! use cmark_writer::{CommonMarkWriter, Node, ToCommonMark};
+//!
+//! let options = WriterOptionsBuilder::new()
+//!     .post_processor(TrimTrailingWhitespace::default())
+//!     .post_processor(EnsureFinalNewline)
+//!     .build();
+//!
+//! let mut writer = CommonMarkWriter::with_options(options);
+//! Node::Text("Hello   ".into()).to_commonmark(&mut writer).unwrap();
+//! assert_eq!(writer.into_string(), "Hello\n");
+//! ```
+
+use crate::options::FinalNewline;
+use crate::traits::OutputFilter;
+use ecow::EcoString;
+
+/// Removes trailing spaces and tabs from every line
+///
+/// Lines inside fenced and indented code blocks are left untouched, since
+/// their whitespace is part of the code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimTrailingWhitespace {
+    /// Keep two trailing spaces on lines that end with two or more spaces and are
+    /// followed by another line, since they form a hard line break
+    pub preserve_hard_breaks: bool,
+}
+
+impl Default for TrimTrailingWhitespace {
+    fn default() -> Self {
+        Self {
+            preserve_hard_breaks: true,
+        }
+    }
+}
+
+impl OutputFilter for TrimTrailingWhitespace {
+    fn apply(&self, output: EcoString) -> EcoString {
+        let mut result = EcoString::new();
+        let mut fence: Option<(char, usize)> = None;
+        let mut indented_code = false;
+        let mut previous_blank = true;
+        let mut lines = output.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            let is_blank = line.trim().is_empty();
+            let in_code = if fence.is_some() {
+                fence = next_fence_state(fence, line);
+                // The closing fence itself is not code
+                fence.is_some()
+            } else {
+                fence = next_fence_state(None, line);
+                // An indented code block can't interrupt a paragraph, and
+                // blank lines inside it are kept as they are
+                if !is_blank {
+                    indented_code = fence.is_none()
+                        && indent_width(line) >= 4
+                        && (previous_blank || indented_code);
+                }
+                indented_code
+            };
+            previous_blank = is_blank;
+            let has_next = lines.peek().is_some();
+            if in_code {
+                result.push_str(line);
+                if has_next {
+                    result.push('\n');
+                }
+                continue;
+            }
+
+            let trimmed = line.trim_end_matches([' ', '\t']);
+            result.push_str(trimmed);
+            if self.preserve_hard_breaks
+                && has_next
+                && !trimmed.is_empty()
+                && line[trimmed.len()..].starts_with("  ")
+            {
+                result.push_str("  ");
+            }
+            if has_next {
+                result.push('\n');
+            }
+        }
+        result
+    }
+
+    fn trims_trailing_spaces(&self) -> bool {
+        !self.preserve_hard_breaks
+    }
+
+    fn clone_box(&self) -> Box<dyn OutputFilter> {
+        Box::new(*self)
+    }
+}
+
+/// Ensures non-empty output ends with exactly one newline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EnsureFinalNewline;
+
+impl OutputFilter for EnsureFinalNewline {
+    fn apply(&self, output: EcoString) -> EcoString {
+        FinalNewline::Always.apply(output)
+    }
+
+    fn clone_box(&self) -> Box<dyn OutputFilter> {
+        Box::new(*self)
+    }
+}
+
+/// Limits runs of consecutive blank lines to a maximum
+///
+/// Blank lines inside fenced code blocks are left untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxBlankLines(pub usize);
+
+impl Default for MaxBlankLines {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl OutputFilter for MaxBlankLines {
+    fn apply(&self, output: EcoString) -> EcoString {
+        let mut result = EcoString::new();
+        let mut fence: Option<(char, usize)> = None;
+        let mut blank_run = 0;
+        let mut lines = output.split('\n').peekable();
+
+        while let Some(line) = lines.next() {
+            let is_blank = line.trim().is_empty();
+            if is_blank && fence.is_none() {
+                blank_run += 1;
+                if blank_run > self.0 {
+                    continue;
+                }
+            } else {
+                blank_run = 0;
+                fence = next_fence_state(fence, line);
+            }
+
+            result.push_str(line);
+            if lines.peek().is_some() {
+                result.push('\n');
+            }
+        }
+        result
+    }
+
+    fn clone_box(&self) -> Box<dyn OutputFilter> {
+        Box::new(*self)
+    }
+}
+
+/// Columns of leading whitespace, with tabs advancing to the next multiple of 4
+fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break,
+        }
+    }
+    width
+}
+
+/// Track whether a line opens or closes a fenced code block
+///
+/// `fence` is the currently open fence (character and length), if any.
+pub(crate) fn next_fence_state(fence: Option<(char, usize)>, line: &str) -> Option<(char, usize)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return fence;
+    }
+    let rest = &line[indent..];
+    let Some(c @ ('`' | '~')) = rest.chars().next() else {
+        return fence;
+    };
+    let len = rest.len() - rest.trim_start_matches(c).len();
+    if len < 3 {
+        return fence;
+    }
+
+    match fence {
+        // Closing fences are at least as long as the opening one and have no info string
+        Some((open, open_len)) if open == c && len >= open_len && rest[len..].trim().is_empty() => {
+            None
+        }
+        Some(open) => Some(open),
+        None => Some((c, len)),
+    }
+}
@@ -0,0 +1,155 @@
+//! Adapting HTML output to email clients, see `HtmlWriterOptions::email`.
+
+use super::{EmailProfile, HtmlWriter};
+use ecow::EcoString;
+use std::borrow::Cow;
+
+/// Elements whose content isn't displayed, left out along with it
+const DROPPED_WITH_CONTENT: &[&str] = &["head", "noscript", "script", "style", "template", "title"];
+
+impl HtmlWriter {
+    /// Start collecting the inline style of a tag being opened
+    pub(super) fn start_email_style(&mut self, tag: &str) {
+        self.email_style.clear();
+        if let Some(style) = self
+            .options
+            .email
+            .as_ref()
+            .and_then(|email| email.tag_styles.get(tag))
+        {
+            push_style(&mut self.email_style, style);
+        }
+    }
+
+    /// Fold a `class` or `style` attribute of the open tag into its inline
+    /// style, returning whether it was
+    pub(super) fn collect_email_style(&mut self, key: &str, value: &str) -> bool {
+        let Some(email) = &self.options.email else {
+            return false;
+        };
+        match key {
+            "style" => push_style(&mut self.email_style, value),
+            "class" => {
+                for class in value.split_ascii_whitespace() {
+                    if let Some(style) = email.class_styles.get(class) {
+                        push_style(&mut self.email_style, style);
+                    }
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Keep the open `<img>` tag of an image node, whose width isn't known,
+    /// from growing wider than the profile's image width
+    pub(super) fn cap_email_image_width(&mut self) {
+        let Some(email) = &self.options.email else {
+            return;
+        };
+        let cap = format!("max-width: {}px", email.max_image_width);
+        push_style(&mut self.email_style, &cap);
+    }
+
+    /// Write the inline style collected for the open tag
+    pub(super) fn write_email_style(&mut self) {
+        if self.email_style.is_empty() {
+            return;
+        }
+        let style = std::mem::take(&mut self.email_style);
+        self.buffer.push_str(" style=\"");
+        self.buffer
+            .push_str(html_escape::encode_quoted_attribute(&style).as_ref());
+        self.buffer.push('"');
+    }
+}
+
+/// Whether an element with `tag` is left out with its content
+pub(super) fn drops_with_content(tag: &str) -> bool {
+    DROPPED_WITH_CONTENT
+        .iter()
+        .any(|dropped| dropped.eq_ignore_ascii_case(tag))
+}
+
+/// `width` of an `<img>` element, capped to the profile's image width
+pub(super) fn capped_image_width<'a>(email: &EmailProfile, width: &'a str) -> Cow<'a, str> {
+    match width.trim().trim_end_matches("px").parse::<u32>() {
+        Ok(pixels) if pixels <= email.max_image_width => Cow::Borrowed(width),
+        _ => Cow::Owned(email.max_image_width.to_string()),
+    }
+}
+
+/// Remove the tags `email` doesn't allow from raw HTML, keeping their content
+///
+/// Like `HtmlElement` nodes, elements in [`DROPPED_WITH_CONTENT`] are left
+/// out along with their content, up to their end tag or the end of `html`.
+pub(super) fn strip_tags<'a>(email: &EmailProfile, html: &'a str) -> Cow<'a, str> {
+    let mut output = String::new();
+    let mut copied = 0;
+    for (index, _) in html.match_indices('<') {
+        if index < copied {
+            continue;
+        }
+        let rest = &html[index + 1..];
+        let closing = rest.starts_with('/');
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) || email.allows_tag(name) {
+            continue;
+        }
+        let Some(end) = html[index..].find('>') else {
+            break;
+        };
+        output.push_str(&html[copied..index]);
+        copied = index + end + 1;
+        let self_closing = html[..copied - 1].ends_with('/');
+        if !closing && !self_closing && drops_with_content(name) {
+            copied = end_of_element(html, copied, name);
+        }
+    }
+    if copied == 0 {
+        return Cow::Borrowed(html);
+    }
+    output.push_str(&html[copied..]);
+    Cow::Owned(output)
+}
+
+/// Index just past the end tag of a `name` element whose content starts at
+/// `start`, or the length of `html` if it isn't closed
+fn end_of_element(html: &str, start: usize, name: &str) -> usize {
+    let mut search = start;
+    while let Some(offset) = html[search..].find("</") {
+        let tag = search + offset + 2;
+        let rest = &html[tag..];
+        let matches_name = rest
+            .get(..name.len())
+            .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+            && !rest[name.len()..].starts_with(|c: char| c.is_ascii_alphanumeric());
+        if matches_name {
+            return match rest.find('>') {
+                Some(end) => tag + end + 1,
+                None => html.len(),
+            };
+        }
+        search = tag;
+    }
+    html.len()
+}
+
+/// Append a declaration list to an inline style, ending it with `;`
+fn push_style(style: &mut EcoString, declarations: &str) {
+    let declarations = declarations.trim();
+    if declarations.is_empty() {
+        return;
+    }
+    if !style.is_empty() {
+        style.push(' ');
+    }
+    style.push_str(declarations);
+    if !declarations.ends_with(';') {
+        style.push(';');
+    }
+}
@@ -17,6 +17,8 @@ pub enum HtmlWriteError {
     InvalidHtmlAttribute(String),
     /// An error occurred while writing a custom node.
     CustomNodeError(String),
+    /// A URL with a scheme outside `allowed_url_schemes` was encountered.
+    InvalidUrl(String),
     // Add more specific HTML-related errors as needed
 }
 
@@ -43,6 +45,7 @@ impl Display for HtmlWriteError {
             HtmlWriteError::CustomNodeError(msg) => {
                 write!(f, "Error writing custom node: {}", msg)
             }
+            HtmlWriteError::InvalidUrl(url) => write!(f, "URL scheme not allowed: {}", url),
         }
     }
 }
@@ -74,6 +77,26 @@ impl HtmlWriteError {
                 message: format!("Custom node error: {}", msg).into(),
                 code: None,
             },
+            HtmlWriteError::InvalidUrl(url) => WriteError::InvalidUrl(url.into()),
+        }
+    }
+
+    /// Convert a `WriteError` returned by `ToHtml` back into an `HtmlWriteError`
+    ///
+    /// Errors without an HTML counterpart become `CustomNodeError` with the
+    /// error's message. The location of a `WriteError::AtNode` is dropped.
+    pub fn from_write_error(error: WriteError) -> Self {
+        match error {
+            WriteError::AtNode { error, .. } => Self::from_write_error(*error),
+            WriteError::HtmlRenderingError(err) => err,
+            WriteError::IoError(err) => HtmlWriteError::Io(err),
+            WriteError::InvalidStructure(msg) => HtmlWriteError::InvalidStructure(msg.into()),
+            WriteError::InvalidHtmlTag(tag) => HtmlWriteError::InvalidHtmlTag(tag.into()),
+            WriteError::InvalidHtmlAttribute(attr) => {
+                HtmlWriteError::InvalidHtmlAttribute(attr.into())
+            }
+            WriteError::InvalidUrl(url) => HtmlWriteError::InvalidUrl(url.into()),
+            other => HtmlWriteError::CustomNodeError(other.to_string()),
         }
     }
 }
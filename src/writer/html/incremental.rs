@@ -0,0 +1,243 @@
+//! Incremental re-rendering of documents for live previews.
+
+use super::writer::Footnotes;
+use super::{HtmlOutputMode, HtmlWriteResult, HtmlWriter};
+use crate::ast::{normalize_label, Node, NodePath, PathSegment, Slugger};
+use crate::ops::HeadingAnchors;
+use crate::options::FinalNewline;
+use ecow::EcoString;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+impl HtmlWriter {
+    /// Render the top-level blocks of `doc`, re-rendering only the dirty ones.
+    ///
+    /// Returns the HTML of every top-level block in document order, keyed by its
+    /// path (`/0`, `/1`, ...); concatenated, they equal the HTML of the whole
+    /// document. A block is re-rendered if one of `dirty_paths` points at it or
+    /// into it, or if it has not been rendered before; other blocks come from the
+    /// output cached by previous calls. A node other than a `Document` is treated
+    /// as a single block at the root path.
+    ///
+    /// Heading ids and footnote numbers are issued across the whole document,
+    /// as they are when it is rendered at once: a clean block is re-rendered as
+    /// well when the blocks before it change the ids or numbers it gets.
+    /// Cross-references resolve against the headings of the whole document too,
+    /// but a block referring to a heading that was edited must be marked dirty.
+    /// A block holding the `toc_marker` is re-rendered on every call, with the
+    /// table of contents of the whole document.
+    ///
+    /// The footnote definitions of a document are left empty where they stand.
+    /// The footnote section comes last, keyed by the root path; it is rendered
+    /// on every call, and left out if no footnote is referenced.
+    ///
+    /// The cache is keyed by position, so when blocks are inserted or removed,
+    /// every block after the change must be marked dirty (or the cache cleared
+    /// with [`clear_render_cache`](Self::clear_render_cache)). The writer's own
+    /// buffer is not touched, and blocks are rendered as fragments whatever the
+    /// `output_mode`, so a wrapping element or page is left to the caller.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{Node, NodePath};
+    /// use cmark_writer::HtmlWriter;
+    ///
+    /// let mut writer = HtmlWriter::new();
+    /// let mut doc = Node::Document(vec![
+    ///     Node::Paragraph(vec![Node::Text("one".into())]),
+    ///     Node::Paragraph(vec![Node::Text("two".into())]),
+    /// ]);
+    /// writer.render_dirty(&doc, &[]).unwrap();
+    ///
+    /// let edited = NodePath::root().child(1).child(0);
+    /// doc.replace(&edited, Node::Text("2".into()));
+    /// let blocks = writer.render_dirty(&doc, &[edited]).unwrap();
+    /// assert_eq!(blocks[1].1, "<p>2</p>\n");
+    /// ```
+    pub fn render_dirty(
+        &mut self,
+        doc: &Node,
+        dirty_paths: &[NodePath],
+    ) -> HtmlWriteResult<Vec<(NodePath, EcoString)>> {
+        // Cross-references and the table of contents of any block cover the
+        // whole document
+        let anchors = Arc::new(self.heading_anchors_of(doc));
+        let toc = self.table_of_contents(doc);
+        let (blocks, children): (Vec<(NodePath, &Node)>, _) = match doc.resolve_shared() {
+            Node::Document(children) | Node::ConfiguredDocument { children, .. } => (
+                children
+                    .iter()
+                    .enumerate()
+                    .map(|(index, child)| (NodePath::root().child(index), child))
+                    .collect(),
+                Some(children),
+            ),
+            node => (vec![(NodePath::root(), node)], None),
+        };
+
+        // Dirty paths mark the top-level block they point into
+        let dirty: HashSet<Option<PathSegment>> = dirty_paths
+            .iter()
+            .map(|path| path.segments().first().copied())
+            .collect();
+        let root_dirty = dirty.contains(&None);
+
+        // Blocks are written at the top of their writers, like the blocks of a
+        // document written with `start_footnotes`
+        let mut state = DocumentState {
+            heading_slugs: Slugger::new(),
+            footnotes: children
+                .map(|children| Footnotes::of_document(0, children))
+                .unwrap_or_default(),
+        };
+        let mut output = Vec::with_capacity(blocks.len());
+        for (path, block) in blocks {
+            // Footnote definitions are written in the footnote section
+            if state.footnotes.defers(block) {
+                output.push((path, EcoString::new()));
+                continue;
+            }
+            // The table of contents changes with the headings of other blocks
+            let is_dirty = root_dirty
+                || dirty.contains(&path.segments().first().copied())
+                || self.shows_toc(block);
+            let uses_state = DocumentState::is_used_by(block);
+            let state_before = uses_state.then(|| state.clone());
+            let issued = if uses_state {
+                state.issue(block)
+            } else {
+                Vec::new()
+            };
+            let html = match self.block_cache.get(&path) {
+                Some(cached) if !is_dirty && cached.issued == issued => cached.html.clone(),
+                _ => {
+                    let mut writer =
+                        self.block_writer(&anchors, toc.as_ref(), state_before.unwrap_or_default());
+                    writer.write_top_level_block(&path, block)?;
+                    let html = writer.into_string();
+                    let cached = CachedBlock {
+                        html: html.clone(),
+                        issued,
+                    };
+                    self.block_cache.insert(path.clone(), cached);
+                    html
+                }
+            };
+            output.push((path, html));
+        }
+
+        let live: HashSet<&NodePath> = output.iter().map(|(path, _)| path).collect();
+        self.block_cache.retain(|path, _| live.contains(path));
+
+        if let Some(children) = children {
+            let mut writer = self.block_writer(&anchors, toc.as_ref(), state);
+            writer.finish_footnotes(children)?;
+            let section = writer.into_string();
+            if !section.is_empty() {
+                output.push((NodePath::root(), section));
+            }
+        }
+        Ok(output)
+    }
+
+    /// Forget the blocks cached by [`render_dirty`](Self::render_dirty)
+    pub fn clear_render_cache(&mut self) {
+        self.block_cache.clear();
+    }
+
+    /// A writer for the top-level blocks of a document rendered one by one
+    fn block_writer(
+        &self,
+        anchors: &Arc<HeadingAnchors>,
+        toc: Option<&Node>,
+        state: DocumentState,
+    ) -> HtmlWriter {
+        let mut writer = HtmlWriter::with_context(self.options.clone(), self.context().clone());
+        writer.options.final_newline = FinalNewline::Preserve;
+        writer.options.output_mode = HtmlOutputMode::Fragment;
+        writer.share_numbering(&self.numbering);
+        writer.share_heading_anchors(Some(anchors));
+        writer.toc = toc.cloned();
+        writer.heading_slugs = state.heading_slugs;
+        writer.footnotes = state.footnotes;
+        writer
+    }
+}
+
+/// A top-level block rendered by [`HtmlWriter::render_dirty`]
+#[derive(Debug)]
+pub(super) struct CachedBlock {
+    html: EcoString,
+    /// What the block was issued from the document state
+    issued: Vec<Issued>,
+}
+
+/// Something a block gets from the blocks before it
+#[derive(Debug, PartialEq)]
+enum Issued {
+    /// Id of a heading
+    HeadingId(EcoString),
+    /// Number, count of references so far and id of a footnote
+    Footnote(usize, usize, EcoString),
+    /// A reference to a footnote that isn't defined
+    UndefinedFootnote,
+}
+
+/// State of a document carried from one top-level block to the next, as in a
+/// writer rendering the whole document
+#[derive(Debug, Clone, Default)]
+struct DocumentState {
+    heading_slugs: Slugger,
+    footnotes: Footnotes,
+}
+
+impl DocumentState {
+    /// Whether `block` depends on the state, or changes it
+    fn is_used_by(block: &Node) -> bool {
+        let mut used = false;
+        block.walk_with_paths(|_, node| {
+            used |= matches!(
+                node,
+                Node::Heading {
+                    custom_id: None,
+                    ..
+                } | Node::FootnoteReference(_)
+                    | Node::FootnoteDefinition { .. }
+            );
+        });
+        used
+    }
+
+    /// Advance the state past `block` the way writing it does, returning what
+    /// it was issued in the order it is written
+    fn issue(&mut self, block: &Node) -> Vec<Issued> {
+        let mut issued = Vec::new();
+        block.walk_with_paths(|_, node| match node {
+            Node::Heading {
+                content,
+                custom_id: None,
+                ..
+            } => {
+                let text: EcoString = content.iter().map(Node::plain_text).collect();
+                issued.push(Issued::HeadingId(self.heading_slugs.slug(&text)));
+            }
+            Node::FootnoteReference(label) => {
+                let key = normalize_label(label);
+                issued.push(match self.footnotes.reference(&key, label) {
+                    Some((number, count)) => {
+                        Issued::Footnote(number, count, self.footnotes.id(&key))
+                    }
+                    None => Issued::UndefinedFootnote,
+                });
+            }
+            Node::FootnoteDefinition { label, .. } => {
+                let key = normalize_label(label);
+                let number = self.footnotes.number(&key, label);
+                issued.push(Issued::Footnote(number, 0, self.footnotes.id(&key)));
+            }
+            _ => {}
+        });
+        issued
+    }
+}
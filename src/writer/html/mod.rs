@@ -1,14 +1,26 @@
 //! Provides HTML rendering capabilities, including the `HtmlWriter`,
 //! rendering options, and error types specific to HTML generation.
 
+mod email;
 /// HTML error types used during HTML writing.
 pub mod error;
+mod incremental;
 /// Options for configuring HTML rendering behavior.
 pub mod options;
+mod string_buffer;
+/// Element tree output for structural assertions on rendered HTML.
+#[cfg(feature = "html-tree")]
+pub mod tree;
 pub mod utils;
 /// The core `HtmlWriter` and its implementation for generating HTML.
 pub mod writer;
 
 pub use self::error::{HtmlWriteError, HtmlWriteResult};
-pub use self::options::HtmlWriterOptions;
+#[cfg(feature = "gfm")]
+pub use self::options::TaskListClasses;
+pub use self::options::{
+    AnchorPosition, AnchorStyle, BidiIsolation, Decoding, EmailProfile, ExternalLinkPolicy,
+    HtmlOutputMode, HtmlWriterOptions, Loading, RowAttributes, TableRowHook,
+};
+pub use self::string_buffer::HtmlStringWriter;
 pub use self::writer::HtmlWriter;
@@ -1,4 +1,459 @@
+use crate::ast::{effective_scheme, url_host, Node};
+use crate::options::{EmptyContainerPolicy, FinalNewline, SoftBreakStyle};
+use crate::writer::{OrdinalHook, PlaceholderHook, TextDirection};
 use ecow::EcoString;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Value of the `loading` attribute added to `<img>` tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loading {
+    /// Defer loading until the image is near the viewport
+    Lazy,
+    /// Load the image immediately
+    Eager,
+}
+
+impl Loading {
+    /// The attribute value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Loading::Lazy => "lazy",
+            Loading::Eager => "eager",
+        }
+    }
+}
+
+/// Value of the `decoding` attribute added to `<img>` tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoding {
+    /// Decode asynchronously, without blocking other content
+    Async,
+    /// Decode synchronously with other content
+    Sync,
+    /// Let the browser decide
+    Auto,
+}
+
+impl Decoding {
+    /// The attribute value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Decoding::Async => "async",
+            Decoding::Sync => "sync",
+            Decoding::Auto => "auto",
+        }
+    }
+}
+
+/// Attributes added to `<a>` tags whose URL points to a foreign host.
+///
+/// A link is external when its URL has an authority (`https://host/...` or
+/// `//host/...`) and the host is not one of `internal_hosts`. Relative links,
+/// fragments and `mailto:` links are never external.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalLinkPolicy {
+    /// Hosts treated as internal, compared case-insensitively
+    pub internal_hosts: Vec<EcoString>,
+    /// `rel` attribute for external links; defaults to `nofollow noopener`
+    pub rel: Option<EcoString>,
+    /// `target` attribute for external links; defaults to `_blank`
+    pub target: Option<EcoString>,
+}
+
+impl Default for ExternalLinkPolicy {
+    fn default() -> Self {
+        Self {
+            internal_hosts: Vec::new(),
+            rel: Some("nofollow noopener".into()),
+            target: Some("_blank".into()),
+        }
+    }
+}
+
+impl ExternalLinkPolicy {
+    /// Creates a policy treating the given hosts as internal
+    pub fn with_internal_hosts<I, S>(hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<EcoString>,
+    {
+        Self {
+            internal_hosts: hosts.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Checks whether `url` points to a host outside `internal_hosts`
+    pub fn is_external(&self, url: &str) -> bool {
+        url_host(url).is_some_and(|host| {
+            !self
+                .internal_hosts
+                .iter()
+                .any(|internal| internal.eq_ignore_ascii_case(host))
+        })
+    }
+}
+
+/// How `HtmlWriter::into_string` frames the rendered HTML
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HtmlOutputMode {
+    /// The rendered HTML as is
+    #[default]
+    Fragment,
+    /// The rendered HTML inside a `<div>` carrying the `lang` and `dir`
+    /// attributes, for embedding in a page of another language or direction
+    WrappedFragment,
+    /// A complete document: doctype, `<html>` carrying the `lang` and `dir`
+    /// attributes, a `<head>` declaring the charset, and the rendered HTML as
+    /// the `<body>`
+    Standalone,
+}
+
+/// How `HtmlWriter` isolates text whose direction differs from the document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidiIsolation {
+    /// Wrap the text in `<bdi>`
+    Bdi,
+    /// Wrap the text in `<span dir="auto">`
+    DirAuto,
+}
+
+/// Settings of the HTML email profile, see [`HtmlWriterOptions::email`]
+///
+/// Email clients ignore stylesheets and much of HTML, so with this profile:
+///
+/// - every element gets the inline style of its tag from `tag_styles`, and
+///   classes are replaced by their `class_styles` (classes without one are
+///   dropped), merged with any `style` attribute into a single `style`;
+/// - tables use a fixed layout, `table_width` wide, with header cells sharing
+///   the width equally;
+/// - images are at most `max_image_width` pixels wide, through a `max-width`
+///   style, and wider `width` attributes of `<img>` elements are capped to it;
+/// - `HtmlElement` nodes whose tag isn't in `allowed_tags` are replaced by
+///   their children (`script`, `style` and similar elements are left out with
+///   their content), and such tags are removed from raw HTML.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::writer::html::EmailProfile;
+/// use cmark_writer::{HtmlWriter, HtmlWriterOptions, Node, ToHtml};
+///
+/// let options = HtmlWriterOptions::default().with_email(EmailProfile::default());
+/// let mut writer = HtmlWriter::with_options(options);
+/// Node::Image {
+///     url: "https://example.com/chart.png".into(),
+///     title: None,
+///     alt: vec![Node::Text("Chart".into())],
+/// }
+/// .to_html(&mut writer)
+/// .unwrap();
+/// assert_eq!(
+///     writer.into_string(),
+///     "<img src=\"https://example.com/chart.png\" alt=\"Chart\" \
+///      style=\"max-width: 100%; height: auto; border: 0; max-width: 600px;\" />"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailProfile {
+    /// Inline style of every element with the tag, by lowercase tag name
+    pub tag_styles: HashMap<EcoString, EcoString>,
+    /// Inline style replacing each class, by class name
+    pub class_styles: HashMap<EcoString, EcoString>,
+    /// Largest width of images and `<img>` elements, in pixels
+    pub max_image_width: u32,
+    /// `width` attribute of tables, e.g. `100%`
+    pub table_width: EcoString,
+    /// Tags kept in `HtmlElement` nodes and raw HTML, compared ignoring ASCII case
+    pub allowed_tags: Vec<EcoString>,
+}
+
+impl Default for EmailProfile {
+    fn default() -> Self {
+        let tag_styles = [
+            ("table", "border-collapse: collapse;"),
+            (
+                "th",
+                "border: 1px solid #d0d7de; padding: 6px 12px; font-weight: bold;",
+            ),
+            ("td", "border: 1px solid #d0d7de; padding: 6px 12px;"),
+            ("img", "max-width: 100%; height: auto; border: 0;"),
+            (
+                "pre",
+                "background-color: #f6f8fa; padding: 12px; font-family: monospace;",
+            ),
+            ("code", "font-family: monospace;"),
+            (
+                "blockquote",
+                "margin: 0; padding: 0 12px; border-left: 4px solid #d0d7de;",
+            ),
+        ];
+        let allowed_tags = [
+            "a",
+            "abbr",
+            "b",
+            "blockquote",
+            "br",
+            "caption",
+            "code",
+            "col",
+            "colgroup",
+            "dd",
+            "del",
+            "div",
+            "dl",
+            "dt",
+            "em",
+            "h1",
+            "h2",
+            "h3",
+            "h4",
+            "h5",
+            "h6",
+            "hr",
+            "i",
+            "img",
+            "li",
+            "ol",
+            "p",
+            "pre",
+            "s",
+            "small",
+            "span",
+            "strong",
+            "sub",
+            "sup",
+            "table",
+            "tbody",
+            "td",
+            "tfoot",
+            "th",
+            "thead",
+            "tr",
+            "u",
+            "ul",
+        ];
+        Self {
+            tag_styles: tag_styles
+                .into_iter()
+                .map(|(tag, style)| (tag.into(), style.into()))
+                .collect(),
+            class_styles: HashMap::new(),
+            max_image_width: 600,
+            table_width: "100%".into(),
+            allowed_tags: allowed_tags.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl EmailProfile {
+    /// Whether `tag` may appear in the output
+    pub fn allows_tag(&self, tag: &str) -> bool {
+        self.allowed_tags
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Where a heading's permalink anchor goes, relative to the heading text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnchorPosition {
+    /// Before the heading text
+    Before,
+    /// After the heading text
+    #[default]
+    After,
+}
+
+/// Permalink anchor added to every heading, e.g.
+/// `<h2 id="usage">Usage<a class="anchor" href="#usage">#</a></h2>`.
+///
+/// Headings with a `custom_id` link to it; other headings get an `id` from
+/// their text, with the same slugs as the table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorStyle {
+    /// Position of the anchor in the heading
+    pub position: AnchorPosition,
+    /// Text of the anchor; defaults to `#`
+    pub symbol: EcoString,
+    /// `class` attribute of the anchor; defaults to `anchor`
+    pub class: Option<EcoString>,
+}
+
+impl Default for AnchorStyle {
+    fn default() -> Self {
+        Self {
+            position: AnchorPosition::After,
+            symbol: "#".into(),
+            class: Some("anchor".into()),
+        }
+    }
+}
+
+impl AnchorStyle {
+    /// Sets the position of the anchor in the heading.
+    pub fn with_position(mut self, position: AnchorPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets the text of the anchor.
+    pub fn with_symbol<S: Into<EcoString>>(mut self, symbol: S) -> Self {
+        self.symbol = symbol.into();
+        self
+    }
+
+    /// Sets the `class` attribute of the anchor.
+    pub fn with_class<S: Into<EcoString>>(mut self, class: Option<S>) -> Self {
+        self.class = class.map(Into::into);
+        self
+    }
+}
+
+/// Attributes added to the `<tr>` of a table body row by a [`TableRowHook`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RowAttributes {
+    /// `class` attribute of the row
+    pub class: Option<EcoString>,
+    /// `data-*` attributes of the row, as names without the `data-` prefix
+    /// and values
+    pub data: Vec<(EcoString, EcoString)>,
+}
+
+impl RowAttributes {
+    /// No attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `class` attribute of the row.
+    pub fn with_class<S: Into<EcoString>>(mut self, class: S) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// Adds a `data-{name}` attribute to the row.
+    pub fn with_data(mut self, name: impl Into<EcoString>, value: impl Into<EcoString>) -> Self {
+        self.data.push((name.into(), value.into()));
+        self
+    }
+}
+
+type RowAttributesFn = dyn Fn(usize, &[Node]) -> RowAttributes + Send + Sync;
+
+/// Computes the attributes of each table body row from its index (starting
+/// at 0 after the header row) and cells, e.g. to stripe rows or mark them by
+/// status.
+///
+/// # Example
+///
+/// ```rust
+/// use cmark_writer::ast::tables::simple_table;
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::writer::html::{RowAttributes, TableRowHook};
+/// use cmark_writer::{HtmlWriterOptions, ToHtml};
+///
+/// let hook = TableRowHook::new(|index, cells: &[Node]| {
+///     let attributes = RowAttributes::new().with_data("status", cells[1].plain_text());
+///     if index % 2 == 1 {
+///         attributes.with_class("odd")
+///     } else {
+///         attributes
+///     }
+/// });
+/// let options = HtmlWriterOptions::default().with_table_row_attributes(hook);
+/// let table = simple_table(
+///     vec![Node::Text("Job".into()), Node::Text("Status".into())],
+///     vec![
+///         vec![Node::Text("build".into()), Node::Text("ok".into())],
+///         vec![Node::Text("test".into()), Node::Text("failed".into())],
+///     ],
+/// );
+/// let html = table.to_html_with(&options).unwrap();
+/// assert!(html.contains("<tr data-status=\"ok\">"));
+/// assert!(html.contains("<tr class=\"odd\" data-status=\"failed\">"));
+/// ```
+#[derive(Clone)]
+pub struct TableRowHook(Arc<RowAttributesFn>);
+
+impl TableRowHook {
+    /// Wrap a function computing the attributes of a row.
+    pub fn new(hook: impl Fn(usize, &[Node]) -> RowAttributes + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// The attributes of the body row at `index` holding `cells`.
+    pub fn attributes(&self, index: usize, cells: &[Node]) -> RowAttributes {
+        (self.0)(index, cells)
+    }
+}
+
+impl fmt::Debug for TableRowHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TableRowHook(..)")
+    }
+}
+
+/// Hooks compare equal only if they share the same function
+impl PartialEq for TableRowHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TableRowHook {}
+
+/// Class names emitted for GFM task lists.
+///
+/// The default matches GitHub's markup:
+///
+/// ```html
+/// <ul class="contains-task-list">
+/// <li class="task-list-item"><input type="checkbox" class="task-list-item-checkbox" disabled="" checked="" /> Done</li>
+/// </ul>
+/// ```
+///
+/// Set a field to `None` to omit that class.
+#[cfg(feature = "gfm")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskListClasses {
+    /// Class on lists containing at least one task item
+    pub list: Option<EcoString>,
+    /// Class on every task list item
+    pub item: Option<EcoString>,
+    /// Additional class on checked task list items
+    pub checked_item: Option<EcoString>,
+    /// Class on the checkbox `<input>`
+    pub checkbox: Option<EcoString>,
+}
+
+#[cfg(feature = "gfm")]
+impl Default for TaskListClasses {
+    fn default() -> Self {
+        Self {
+            list: Some("contains-task-list".into()),
+            item: Some("task-list-item".into()),
+            checked_item: None,
+            checkbox: Some("task-list-item-checkbox".into()),
+        }
+    }
+}
+
+#[cfg(feature = "gfm")]
+impl TaskListClasses {
+    /// The classes emitted by earlier versions of this crate
+    /// (`task-list-item`, plus `task-list-item-checked` on checked items)
+    pub fn legacy() -> Self {
+        Self {
+            list: None,
+            item: Some("task-list-item".into()),
+            checked_item: Some("task-list-item-checked".into()),
+            checkbox: None,
+        }
+    }
+}
 
 /// Options for configuring the HTML rendering process.
 ///
@@ -19,7 +474,8 @@ use ecow::EcoString;
 ///     #[cfg(feature = "gfm")]
 ///     enable_gfm: true,
 ///     #[cfg(feature = "gfm")]
-///     gfm_disallowed_html_tags: vec!["script".into()],
+///     gfm_disallowed_html_tags: ["script"].into(),
+///     ..Default::default()
 /// };
 ///
 /// // Use the options with an HtmlWriter
@@ -32,26 +488,168 @@ pub struct HtmlWriterOptions {
     /// If None, no language class is added.
     pub code_block_language_class_prefix: Option<EcoString>,
 
+    /// Language used for code blocks without one (or with an empty one).
+    pub default_code_language: Option<EcoString>,
+
+    /// Maps code block language aliases to canonical names (e.g. `"js"` to `"javascript"`),
+    /// applied before the language class is emitted.
+    pub code_language_aliases: HashMap<EcoString, EcoString>,
+
     /// Enables GFM-specific HTML rendering behaviors.
     #[cfg(feature = "gfm")]
     pub enable_gfm: bool,
     /// A list of HTML tags that should be rendered as text when GFM is enabled.
     #[cfg(feature = "gfm")]
-    pub gfm_disallowed_html_tags: Vec<EcoString>,
+    pub gfm_disallowed_html_tags: crate::gfm::DisallowedTags,
+    /// Class names emitted for task lists when GFM is enabled.
+    #[cfg(feature = "gfm")]
+    pub task_list_classes: TaskListClasses,
+
+    /// `loading` attribute added to every generated `<img>` tag.
+    pub img_loading: Option<Loading>,
+
+    /// `decoding` attribute added to every generated `<img>` tag.
+    pub img_decoding: Option<Decoding>,
+
+    /// Base direction of the document, used by `bidi_isolation`.
+    pub document_direction: TextDirection,
+
+    /// `lang` attribute of the wrapping element (see `output_mode`), e.g. `"en"`.
+    pub lang: Option<EcoString>,
+
+    /// `dir` attribute of the wrapping element (see `output_mode`).
+    /// Independent of `document_direction`, which only drives `bidi_isolation`.
+    pub dir: Option<TextDirection>,
+
+    /// Whether the output is a fragment, a fragment wrapped in a `<div>`, or a
+    /// standalone document.
+    pub output_mode: HtmlOutputMode,
+
+    /// Isolate text nodes whose direction differs from `document_direction`.
+    pub bidi_isolation: Option<BidiIsolation>,
+
+    /// `rel` and `target` attributes added to links pointing to foreign hosts.
+    pub external_link_policy: Option<ExternalLinkPolicy>,
+
+    /// Scheme added to URI autolinks that have none.
+    pub autolink_default_scheme: EcoString,
+
+    /// Schemes allowed in `href` and `src` URLs (compared case-insensitively);
+    /// relative URLs are always allowed and `None` allows every scheme.
+    /// Other URLs, such as `javascript:` and `data:` ones, are an error in
+    /// strict mode and written as an empty attribute otherwise.
+    /// Defaults to `http`, `https` and `mailto`.
+    pub allowed_url_schemes: Option<Vec<EcoString>>,
+
+    /// Record the rendered size of each top-level block, reported by `HtmlWriter::block_sizes`.
+    pub track_block_sizes: bool,
+
+    /// Wrap the output of each top-level block between
+    /// `<!-- cmark:block start path=/0 -->` and `<!-- cmark:block end path=/0 -->`
+    /// comments, so tools patching the DOM or extracting content can find the
+    /// HTML of a block by its [`NodePath`](crate::ast::NodePath).
+    pub block_markers: bool,
+
+    /// Adapt the output to HTML email clients, see [`EmailProfile`].
+    pub email: Option<EmailProfile>,
+
+    /// Write the output of `Custom` nodes as is. When false, custom nodes
+    /// can't inject markup: their `raw_html` and the raw HTML nodes they write
+    /// are escaped as text, unsafe tags (`script`, `iframe`, ...) and event
+    /// handler or `style` attributes are an error in strict mode and left out
+    /// otherwise, and the URLs they write are checked against
+    /// `allowed_url_schemes`. They render into a writer of their own, whose
+    /// output is checked again with these options. Defaults to true.
+    pub trust_custom_nodes: bool,
+
+    /// Escape `"` and `'` in text content as `&quot;` and `&#x27;`.
+    /// Off by default: text only escapes `&`, `<` and `>`. Attribute values
+    /// always escape both quotes.
+    pub escape_quotes_in_text: bool,
+
+    /// How soft breaks are written: a newline (the default), a space, or `<br />`.
+    pub soft_break_style: SoftBreakStyle,
+
+    /// Added to the level of every heading (e.g. `1` turns `<h1>` into `<h2>`),
+    /// before clamping to `max_heading_level`.
+    pub heading_level_offset: i8,
+
+    /// Highest heading level emitted (1-6); deeper headings use this level.
+    pub max_heading_level: u8,
+
+    /// Permalink anchor added to every heading.
+    pub heading_anchor: Option<AnchorStyle>,
+
+    /// Attributes added to the `<tr>` of each table body row.
+    pub table_row_attributes: Option<TableRowHook>,
+
+    /// Text of a paragraph (e.g. `[[TOC]]`) replaced by a table of contents of the document's headings.
+    pub toc_marker: Option<EcoString>,
 
     /// Determines if HTML parsing/rendering errors should be strict (panic/Err) or lenient (warn and attempt to recover/textualize).
     pub strict: bool,
+
+    /// How to handle headings, lists, tables and other containers with no content.
+    /// `Placeholder` writes the empty element (e.g. `<ul>\n</ul>`).
+    pub empty_container_policy: EmptyContainerPolicy,
+
+    /// Emit only one attribute per name on `HtmlElement` nodes, with later duplicates winning.
+    pub deduplicate_attributes: bool,
+
+    /// Emit `HtmlElement` attributes sorted by name, for stable output.
+    pub sort_attributes: bool,
+
+    /// Resolver for `{{key}}` placeholders in text nodes, applied before escaping.
+    pub placeholders: Option<PlaceholderHook>,
+
+    /// Numbering system for the markers of ordered lists, set with CSS.
+    /// `None` leaves the numbering to the browser's default.
+    pub ordinals: Option<OrdinalHook>,
+
+    /// How trailing newlines are normalized by `HtmlWriter::into_string()`.
+    pub final_newline: FinalNewline,
 }
 
 impl Default for HtmlWriterOptions {
     fn default() -> Self {
         Self {
             code_block_language_class_prefix: Some("language-".into()),
+            default_code_language: None,
+            code_language_aliases: HashMap::new(),
             #[cfg(feature = "gfm")]
             enable_gfm: false, // Default to false, cmark.rs options should override
             #[cfg(feature = "gfm")]
-            gfm_disallowed_html_tags: Vec::new(), // Default to empty
+            gfm_disallowed_html_tags: crate::gfm::DisallowedTags::new(), // Default to empty
+            #[cfg(feature = "gfm")]
+            task_list_classes: TaskListClasses::default(),
+            img_loading: None,
+            img_decoding: None,
+            document_direction: TextDirection::Ltr,
+            lang: None,
+            dir: None,
+            output_mode: HtmlOutputMode::Fragment,
+            bidi_isolation: None,
+            external_link_policy: None,
+            autolink_default_scheme: "https".into(),
+            allowed_url_schemes: Some(vec!["http".into(), "https".into(), "mailto".into()]),
+            track_block_sizes: false,
+            block_markers: false,
+            email: None,
+            trust_custom_nodes: true,
+            escape_quotes_in_text: false,
+            soft_break_style: SoftBreakStyle::Newline,
+            heading_level_offset: 0,
+            max_heading_level: 6,
+            heading_anchor: None,
+            table_row_attributes: None,
+            toc_marker: None,
             strict: true, // Default to strict for HTML, can be overridden by cmark.rs options
+            empty_container_policy: EmptyContainerPolicy::Auto,
+            deduplicate_attributes: false,
+            sort_attributes: false,
+            placeholders: None,
+            ordinals: None,
+            final_newline: FinalNewline::Preserve,
         }
     }
 }
@@ -63,12 +661,230 @@ impl HtmlWriterOptions {
         self
     }
 
+    /// Sets the language used for code blocks without one.
+    pub fn with_default_code_language<S: Into<EcoString>>(mut self, language: Option<S>) -> Self {
+        self.default_code_language = language.map(|l| l.into());
+        self
+    }
+
+    /// Adds a code block language alias, e.g. `("js", "javascript")`.
+    pub fn with_code_language_alias(
+        mut self,
+        alias: impl Into<EcoString>,
+        language: impl Into<EcoString>,
+    ) -> Self {
+        self.code_language_aliases
+            .insert(alias.into(), language.into());
+        self
+    }
+
+    /// Resolves the language of a code block, applying the default language and aliases.
+    ///
+    /// Returns `None` if the block has no language and no default is set.
+    pub fn resolve_code_language<'a>(&'a self, language: Option<&'a str>) -> Option<&'a str> {
+        let language = language
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty())
+            .or(self.default_code_language.as_deref())?;
+        Some(
+            self.code_language_aliases
+                .get(language)
+                .map(EcoString::as_str)
+                .unwrap_or(language),
+        )
+    }
+
+    /// Sets the `loading` attribute added to generated `<img>` tags.
+    pub fn with_img_loading(mut self, loading: Option<Loading>) -> Self {
+        self.img_loading = loading;
+        self
+    }
+
+    /// Sets the `decoding` attribute added to generated `<img>` tags.
+    pub fn with_img_decoding(mut self, decoding: Option<Decoding>) -> Self {
+        self.img_decoding = decoding;
+        self
+    }
+
+    /// Sets the base direction of the document.
+    pub fn with_document_direction(mut self, direction: TextDirection) -> Self {
+        self.document_direction = direction;
+        self
+    }
+
+    /// Sets the `lang` attribute of the wrapping element.
+    pub fn with_lang<S: Into<EcoString>>(mut self, lang: Option<S>) -> Self {
+        self.lang = lang.map(Into::into);
+        self
+    }
+
+    /// Sets the `dir` attribute of the wrapping element.
+    pub fn with_dir(mut self, dir: Option<TextDirection>) -> Self {
+        self.dir = dir;
+        self
+    }
+
+    /// Sets how the rendered HTML is framed.
+    pub fn with_output_mode(mut self, mode: HtmlOutputMode) -> Self {
+        self.output_mode = mode;
+        self
+    }
+
+    /// Sets how text in the opposite direction is isolated.
+    pub fn with_bidi_isolation(mut self, isolation: Option<BidiIsolation>) -> Self {
+        self.bidi_isolation = isolation;
+        self
+    }
+
+    /// Sets the attributes added to links pointing to foreign hosts.
+    pub fn with_external_link_policy(mut self, policy: Option<ExternalLinkPolicy>) -> Self {
+        self.external_link_policy = policy;
+        self
+    }
+
+    /// Sets the scheme added to URI autolinks that have none.
+    pub fn with_autolink_default_scheme<S: Into<EcoString>>(mut self, scheme: S) -> Self {
+        self.autolink_default_scheme = scheme.into();
+        self
+    }
+
+    /// Sets the schemes allowed in `href` and `src` URLs.
+    pub fn with_allowed_url_schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<EcoString>,
+    {
+        self.allowed_url_schemes = Some(schemes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allows URLs of any scheme in `href` and `src` attributes.
+    pub fn with_any_url_scheme(mut self) -> Self {
+        self.allowed_url_schemes = None;
+        self
+    }
+
+    /// Whether `url` may be written as an `href` or `src` under `allowed_url_schemes`.
+    pub fn allows_url(&self, url: &str) -> bool {
+        let Some(allowed) = &self.allowed_url_schemes else {
+            return true;
+        };
+        match effective_scheme(url) {
+            Some(scheme) => allowed
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(&scheme)),
+            None => true,
+        }
+    }
+
+    /// Sets whether the output of custom nodes is written as is.
+    pub fn with_trust_custom_nodes(mut self, trust: bool) -> Self {
+        self.trust_custom_nodes = trust;
+        self
+    }
+
+    /// Sets whether to record the rendered size of each top-level block.
+    pub fn with_track_block_sizes(mut self, track: bool) -> Self {
+        self.track_block_sizes = track;
+        self
+    }
+
+    /// Sets whether to wrap each top-level block between marker comments.
+    pub fn with_block_markers(mut self, markers: bool) -> Self {
+        self.block_markers = markers;
+        self
+    }
+
+    /// Adapts the output to HTML email clients with the given profile.
+    pub fn with_email(mut self, profile: EmailProfile) -> Self {
+        self.email = Some(profile);
+        self
+    }
+
+    /// Sets whether to escape quotes in text content.
+    pub fn with_escape_quotes_in_text(mut self, escape: bool) -> Self {
+        self.escape_quotes_in_text = escape;
+        self
+    }
+
+    /// Sets how soft breaks are written.
+    pub fn with_soft_break_style(mut self, style: SoftBreakStyle) -> Self {
+        self.soft_break_style = style;
+        self
+    }
+
+    /// Sets the offset added to heading levels.
+    pub fn with_heading_level_offset(mut self, offset: i8) -> Self {
+        self.heading_level_offset = offset;
+        self
+    }
+
+    /// Sets the highest heading level emitted, clamped to 1-6.
+    pub fn with_max_heading_level(mut self, level: u8) -> Self {
+        self.max_heading_level = level.clamp(1, 6);
+        self
+    }
+
+    /// Sets the permalink anchor added to every heading.
+    pub fn with_heading_anchor(mut self, anchor: Option<AnchorStyle>) -> Self {
+        self.heading_anchor = anchor;
+        self
+    }
+
+    /// Sets the paragraph text replaced by a table of contents.
+    pub fn with_toc_marker<S: Into<EcoString>>(mut self, marker: Option<S>) -> Self {
+        self.toc_marker = marker.map(Into::into);
+        self
+    }
+
     /// Sets strict mode.
     pub fn with_strict(mut self, strict: bool) -> Self {
         self.strict = strict;
         self
     }
 
+    /// Sets how containers with no content are handled.
+    pub fn with_empty_container_policy(mut self, policy: EmptyContainerPolicy) -> Self {
+        self.empty_container_policy = policy;
+        self
+    }
+
+    /// Sets whether duplicate `HtmlElement` attributes are collapsed (later duplicates win).
+    pub fn with_deduplicated_attributes(mut self, deduplicate: bool) -> Self {
+        self.deduplicate_attributes = deduplicate;
+        self
+    }
+
+    /// Sets whether `HtmlElement` attributes are emitted sorted by name.
+    pub fn with_sorted_attributes(mut self, sort: bool) -> Self {
+        self.sort_attributes = sort;
+        self
+    }
+
+    /// Sets the hook computing the attributes of table body rows.
+    pub fn with_table_row_attributes(mut self, hook: TableRowHook) -> Self {
+        self.table_row_attributes = Some(hook);
+        self
+    }
+
+    /// Sets the numbering system for the markers of ordered lists.
+    pub fn with_ordinals(mut self, ordinals: OrdinalHook) -> Self {
+        self.ordinals = Some(ordinals);
+        self
+    }
+
+    /// Sets the resolver for `{{key}}` placeholders in text nodes.
+    pub fn with_placeholder_resolver(mut self, resolver: PlaceholderHook) -> Self {
+        self.placeholders = Some(resolver);
+        self
+    }
+
+    /// Sets how trailing newlines are normalized at the end of the output.
+    pub fn with_final_newline(mut self, policy: FinalNewline) -> Self {
+        self.final_newline = policy;
+        self
+    }
+
     /// Sets the code block language class prefix.
     pub fn set_code_block_prefix<S: Into<EcoString>>(&mut self, prefix: Option<S>) {
         self.code_block_language_class_prefix = prefix.map(|p| p.into());
@@ -94,14 +910,45 @@ impl HtmlWriterOptions {
 
     /// Sets the list of HTML tags that should be rendered as text when GFM is enabled.
     #[cfg(feature = "gfm")]
-    pub fn with_gfm_disallowed_tags(mut self, tags: Vec<EcoString>) -> Self {
-        self.gfm_disallowed_html_tags = tags;
+    pub fn with_gfm_disallowed_tags(mut self, tags: impl Into<crate::gfm::DisallowedTags>) -> Self {
+        self.gfm_disallowed_html_tags = tags.into();
+        self
+    }
+
+    /// Adds tags to the list rendered as text when GFM is enabled, skipping tags already present.
+    #[cfg(feature = "gfm")]
+    pub fn with_additional_gfm_disallowed_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<EcoString>,
+    {
+        self.gfm_disallowed_html_tags.extend(tags);
+        self
+    }
+
+    /// Removes tags from the list rendered as text when GFM is enabled, ignoring ASCII case.
+    #[cfg(feature = "gfm")]
+    pub fn without_gfm_disallowed_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for tag in tags {
+            self.gfm_disallowed_html_tags.remove(tag.as_ref());
+        }
+        self
+    }
+
+    /// Sets the class names emitted for task lists.
+    #[cfg(feature = "gfm")]
+    pub fn with_task_list_classes(mut self, classes: TaskListClasses) -> Self {
+        self.task_list_classes = classes;
         self
     }
 
     /// Sets the list of HTML tags that should be rendered as text when GFM is enabled.
     #[cfg(feature = "gfm")]
-    pub fn set_gfm_disallowed_tags(&mut self, tags: Vec<EcoString>) {
-        self.gfm_disallowed_html_tags = tags;
+    pub fn set_gfm_disallowed_tags(&mut self, tags: impl Into<crate::gfm::DisallowedTags>) {
+        self.gfm_disallowed_html_tags = tags.into();
     }
 }
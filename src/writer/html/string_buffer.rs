@@ -0,0 +1,76 @@
+//! Rendering HTML into a caller's `String`.
+
+use super::{HtmlWriter, HtmlWriterOptions};
+use std::ops::{Deref, DerefMut};
+
+/// An [`HtmlWriter`] whose output is appended to an existing `String`
+///
+/// Created by [`HtmlWriter::from_string_buffer`]. It dereferences to the
+/// underlying writer, so nodes are rendered as usual; [`into_inner`] then
+/// appends the HTML (with the `final_newline` policy applied) to the buffer
+/// and hands the buffer back. Existing content of the buffer is kept.
+///
+/// [`into_inner`]: Self::into_inner
+#[derive(Debug)]
+pub struct HtmlStringWriter<'a> {
+    writer: HtmlWriter,
+    buffer: &'a mut String,
+}
+
+impl<'a> HtmlStringWriter<'a> {
+    /// Append the rendered HTML to the buffer and return the buffer
+    pub fn into_inner(self) -> &'a mut String {
+        self.buffer.push_str(&self.writer.into_string());
+        self.buffer
+    }
+}
+
+impl Deref for HtmlStringWriter<'_> {
+    type Target = HtmlWriter;
+
+    fn deref(&self) -> &HtmlWriter {
+        &self.writer
+    }
+}
+
+impl DerefMut for HtmlStringWriter<'_> {
+    fn deref_mut(&mut self) -> &mut HtmlWriter {
+        &mut self.writer
+    }
+}
+
+impl HtmlWriter {
+    /// Create a writer with default options that appends its output to `buffer`
+    ///
+    /// Convenient when HTML is assembled into a `String` owned by the caller
+    /// (e.g. a page template), without handling the writer's own string type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::Node;
+    /// use cmark_writer::{HtmlWriter, ToHtml};
+    ///
+    /// let mut page = String::from("<main>\n");
+    /// let mut writer = HtmlWriter::from_string_buffer(&mut page);
+    /// Node::Paragraph(vec![Node::Text("Hi".into())])
+    ///     .to_html(&mut writer)
+    ///     .unwrap();
+    /// writer.into_inner().push_str("</main>\n");
+    /// assert_eq!(page, "<main>\n<p>Hi</p>\n</main>\n");
+    /// ```
+    pub fn from_string_buffer(buffer: &mut String) -> HtmlStringWriter<'_> {
+        Self::from_string_buffer_with_options(buffer, HtmlWriterOptions::default())
+    }
+
+    /// Create a writer with the given options that appends its output to `buffer`
+    pub fn from_string_buffer_with_options(
+        buffer: &mut String,
+        options: HtmlWriterOptions,
+    ) -> HtmlStringWriter<'_> {
+        HtmlStringWriter {
+            writer: HtmlWriter::with_options(options),
+            buffer,
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Lightweight HTML element tree for structural assertions.
+//!
+//! Rendering into a tree instead of a string lets tests and downstream tools
+//! check the structure of the generated HTML (e.g. "there is a `th` with
+//! `style="text-align: center;"`") without brittle string matching.
+//!
+//! This module is only available when the `html-tree` feature is enabled.
+//!
+//! # Example
+//!
+//! ```rust
+//! use cmark_writer::writer::html::tree;
+//! use cmark_writer::{HtmlWriterOptions, Node};
+//!
+//! let node = Node::Paragraph(vec![Node::Strong(vec![Node::Text("Hi".into())])]);
+//! let nodes = tree::render_tree(&node, &HtmlWriterOptions::default()).unwrap();
+//!
+//! let strong = tree::find_all(&nodes, "strong");
+//! assert_eq!(strong.len(), 1);
+//! assert_eq!(strong[0].text_content(), "Hi");
+//! ```
+
+use super::utils::VOID_ELEMENTS;
+use super::{HtmlWriteResult, HtmlWriter, HtmlWriterOptions};
+use crate::ast::Node;
+use ecow::EcoString;
+
+/// A node in the rendered HTML tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeNode {
+    /// An element with attributes and children
+    Element(Element),
+    /// Text content, with character references decoded
+    Text(EcoString),
+    /// An HTML comment
+    Comment(EcoString),
+}
+
+impl TreeNode {
+    /// Get the element if this node is one
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            TreeNode::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Concatenated text content of this node and its descendants
+    pub fn text_content(&self) -> EcoString {
+        let mut text = EcoString::new();
+        self.collect_text(&mut text);
+        text
+    }
+
+    fn collect_text(&self, text: &mut EcoString) {
+        match self {
+            TreeNode::Element(element) => {
+                for child in &element.children {
+                    child.collect_text(text);
+                }
+            }
+            TreeNode::Text(content) => text.push_str(content),
+            TreeNode::Comment(_) => {}
+        }
+    }
+}
+
+/// An element in the rendered HTML tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    /// Tag name, lowercased
+    pub tag: EcoString,
+    /// Attributes in source order, with values decoded
+    pub attributes: Vec<(EcoString, EcoString)>,
+    /// Child nodes
+    pub children: Vec<TreeNode>,
+}
+
+impl Element {
+    /// Get the value of an attribute (case-insensitive name match)
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attr_name, _)| attr_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Check whether the element has the given attribute
+    pub fn has_attr(&self, name: &str) -> bool {
+        self.attr(name).is_some()
+    }
+
+    /// Iterate over the child elements, skipping text and comments
+    pub fn child_elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(TreeNode::as_element)
+    }
+
+    /// Find all descendant elements with the given tag name, in document order
+    pub fn find_all(&self, tag: &str) -> Vec<&Element> {
+        find_all(&self.children, tag)
+    }
+
+    /// Concatenated text content of the element's descendants
+    pub fn text_content(&self) -> EcoString {
+        let mut text = EcoString::new();
+        for child in &self.children {
+            child.collect_text(&mut text);
+        }
+        text
+    }
+}
+
+/// Find all elements with the given tag name (case-insensitive) in a list of nodes,
+/// searching depth-first in document order
+pub fn find_all<'a>(nodes: &'a [TreeNode], tag: &str) -> Vec<&'a Element> {
+    fn walk<'a>(nodes: &'a [TreeNode], tag: &str, found: &mut Vec<&'a Element>) {
+        for node in nodes {
+            if let TreeNode::Element(element) = node {
+                if element.tag.eq_ignore_ascii_case(tag) {
+                    found.push(element);
+                }
+                walk(&element.children, tag, found);
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    walk(nodes, tag, &mut found);
+    found
+}
+
+/// Render a node to HTML and return the resulting element tree
+///
+/// # Arguments
+/// * `node` - The node to render
+/// * `options` - HTML writer options used for rendering
+pub fn render_tree(node: &Node, options: &HtmlWriterOptions) -> HtmlWriteResult<Vec<TreeNode>> {
+    let mut writer = HtmlWriter::with_options(options.clone());
+    writer.write_node_internal(node)?;
+    Ok(parse_fragment(&writer.into_string()))
+}
+
+impl HtmlWriter {
+    /// Consume the writer and parse its output into an element tree
+    pub fn into_tree(self) -> Vec<TreeNode> {
+        parse_fragment(&self.into_string())
+    }
+}
+
+/// Parse an HTML fragment into a tree
+///
+/// The parser is intentionally lenient and geared towards the well-formed markup
+/// produced by [`HtmlWriter`]: unclosed elements are closed at the end of input and
+/// unmatched end tags are ignored. Whitespace-only text between elements is kept.
+pub fn parse_fragment(html: &str) -> Vec<TreeNode> {
+    let mut stack: Vec<Element> = Vec::new();
+    let mut roots: Vec<TreeNode> = Vec::new();
+    let mut rest = html;
+
+    fn push_node(stack: &mut [Element], roots: &mut Vec<TreeNode>, node: TreeNode) {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => roots.push(node),
+        }
+    }
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").unwrap_or(comment.len());
+            push_node(
+                &mut stack,
+                &mut roots,
+                TreeNode::Comment(comment[..end].into()),
+            );
+            rest = comment.get(end + 3..).unwrap_or("");
+        } else if let Some(end_tag) = rest.strip_prefix("</") {
+            let close = end_tag.find('>').unwrap_or(end_tag.len());
+            let name = end_tag[..close].trim().to_ascii_lowercase();
+            rest = end_tag.get(close + 1..).unwrap_or("");
+            if let Some(pos) = stack.iter().rposition(|e| e.tag == name.as_str()) {
+                while stack.len() > pos {
+                    let element = stack.pop().unwrap();
+                    push_node(&mut stack, &mut roots, TreeNode::Element(element));
+                }
+            }
+        } else if let Some((element, self_closing, remaining)) = parse_start_tag(rest) {
+            rest = remaining;
+            if self_closing || VOID_ELEMENTS.contains(&element.tag.as_str()) {
+                push_node(&mut stack, &mut roots, TreeNode::Element(element));
+            } else {
+                stack.push(element);
+            }
+        } else {
+            // Text runs until the next '<' after the current position
+            let first_len = rest.chars().next().map_or(0, char::len_utf8);
+            let end = rest[first_len..]
+                .find('<')
+                .map_or(rest.len(), |i| i + first_len);
+            let text = html_escape::decode_html_entities(&rest[..end]);
+            push_node(&mut stack, &mut roots, TreeNode::Text(text.as_ref().into()));
+            rest = &rest[end..];
+        }
+    }
+
+    while let Some(element) = stack.pop() {
+        push_node(&mut stack, &mut roots, TreeNode::Element(element));
+    }
+
+    merge_adjacent_text(&mut roots);
+    roots
+}
+
+/// Parse a start tag at the beginning of `input`
+///
+/// Returns the element, whether it was written as self-closing, and the remaining input.
+fn parse_start_tag(input: &str) -> Option<(Element, bool, &str)> {
+    let body = input.strip_prefix('<')?;
+    let name_len = body
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':')))
+        .unwrap_or(body.len());
+    if name_len == 0 || !body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut element = Element {
+        tag: body[..name_len].to_ascii_lowercase().into(),
+        attributes: Vec::new(),
+        children: Vec::new(),
+    };
+    let mut rest = &body[name_len..];
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return Some((element, true, after));
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return Some((element, false, after));
+        }
+        if rest.is_empty() {
+            return None;
+        }
+
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len());
+        if name_len == 0 {
+            // Stray '/' or '=' - skip it
+            rest = &rest[1..];
+            continue;
+        }
+        let name: EcoString = rest[..name_len].into();
+        rest = rest[name_len..].trim_start();
+
+        let mut value = EcoString::new();
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (raw, remaining) = match after_eq.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after_eq[1..];
+                    let end = inner.find(quote).unwrap_or(inner.len());
+                    (&inner[..end], inner.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after_eq
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after_eq.len());
+                    (&after_eq[..end], &after_eq[end..])
+                }
+            };
+            value = html_escape::decode_html_entities(raw).as_ref().into();
+            rest = remaining;
+        }
+        element.attributes.push((name, value));
+    }
+}
+
+/// Merge adjacent text nodes produced by entity-split text runs
+fn merge_adjacent_text(nodes: &mut Vec<TreeNode>) {
+    let mut merged: Vec<TreeNode> = Vec::with_capacity(nodes.len());
+    for mut node in nodes.drain(..) {
+        if let TreeNode::Element(element) = &mut node {
+            merge_adjacent_text(&mut element.children);
+        }
+        match (merged.last_mut(), node) {
+            (Some(TreeNode::Text(previous)), TreeNode::Text(text)) => previous.push_str(&text),
+            (_, node) => merged.push(node),
+        }
+    }
+    *nodes = merged;
+}
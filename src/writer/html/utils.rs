@@ -1,5 +1,7 @@
 //! Utility functions for HTML writing.
 
+use std::borrow::Cow;
+
 /// Check if an HTML tag name is safe
 ///
 /// Tag names should only contain letters, numbers, underscores, colons, and hyphens.
@@ -10,6 +12,53 @@ pub(crate) fn is_safe_tag_name(tag: &str) -> bool {
             .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':' || c == '-')
 }
 
+/// Tags an untrusted custom node may not write: they run scripts, embed other
+/// documents, or change how the rest of the page is parsed or submitted
+const UNTRUSTED_TAGS: &[&str] = &[
+    "applet",
+    "base",
+    "embed",
+    "form",
+    "frame",
+    "frameset",
+    "iframe",
+    "link",
+    "meta",
+    "noembed",
+    "noframes",
+    "object",
+    "plaintext",
+    "script",
+    "style",
+    "template",
+    "textarea",
+    "title",
+    "xmp",
+];
+
+/// Check if an untrusted custom node may write the tag
+pub(crate) fn is_untrusted_tag_allowed(tag: &str) -> bool {
+    is_safe_tag_name(tag)
+        && !UNTRUSTED_TAGS
+            .iter()
+            .any(|unsafe_tag| tag.eq_ignore_ascii_case(unsafe_tag))
+}
+
+/// Check if an untrusted custom node may write the attribute
+///
+/// Event handlers (`on...`), inline styles and inline documents (`srcdoc`) are
+/// not allowed.
+pub(crate) fn is_untrusted_attribute_allowed(name: &str) -> bool {
+    let event_handler = name.len() > 2
+        && name
+            .get(..2)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("on"));
+    is_safe_attribute_name(name)
+        && !event_handler
+        && !name.eq_ignore_ascii_case("style")
+        && !name.eq_ignore_ascii_case("srcdoc")
+}
+
 /// Check if an HTML attribute name is safe
 ///
 /// Attribute names should only contain letters, numbers, underscores, colons, dots, and hyphens.
@@ -19,3 +68,93 @@ pub(crate) fn is_safe_attribute_name(name: &str) -> bool {
             .chars()
             .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':' || c == '-' || c == '.')
 }
+
+/// Elements that never have children or an end tag
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Check if an element never has children or an end tag
+pub(crate) fn is_void_element(tag: &str) -> bool {
+    VOID_ELEMENTS
+        .iter()
+        .any(|void| void.eq_ignore_ascii_case(tag))
+}
+
+/// A start or end tag read back from written HTML
+#[derive(Debug)]
+pub(crate) struct ParsedTag<'a> {
+    pub(crate) name: &'a str,
+    /// Whether this is an end tag
+    pub(crate) closing: bool,
+    /// Whether the start tag ends with `/>`
+    pub(crate) self_closing: bool,
+    /// Attributes in source order, with values decoded
+    pub(crate) attributes: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+/// Parse the tag at the start of `html`, returning it and its length in bytes
+///
+/// `None` unless `html` starts with a complete tag whose name starts with a
+/// letter. Attribute names aren't checked.
+pub(crate) fn parse_tag(html: &str) -> Option<(ParsedTag<'_>, usize)> {
+    let body = html.strip_prefix('<')?;
+    let closing = body.starts_with('/');
+    let body = body.strip_prefix('/').unwrap_or(body);
+    let name_len = body
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':')))
+        .unwrap_or(body.len());
+    let name = &body[..name_len];
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut tag = ParsedTag {
+        name,
+        closing,
+        self_closing: false,
+        attributes: Vec::new(),
+    };
+    let mut rest = &body[name_len..];
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix('>') {
+            return Some((tag, html.len() - after.len()));
+        }
+        if let Some(after) = rest.strip_prefix("/>").filter(|_| !closing) {
+            tag.self_closing = true;
+            return Some((tag, html.len() - after.len()));
+        }
+
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len());
+        if closing || name_len == 0 {
+            return None;
+        }
+        let name = &rest[..name_len];
+        rest = rest[name_len..].trim_start();
+
+        let mut value = Cow::Borrowed("");
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (raw, remaining) = match after_eq.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after_eq[1..];
+                    let end = inner.find(quote)?;
+                    (&inner[..end], &inner[end + 1..])
+                }
+                _ => {
+                    let end = after_eq
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after_eq.len());
+                    (&after_eq[..end], &after_eq[end..])
+                }
+            };
+            value = html_escape::decode_html_entities(raw);
+            rest = remaining;
+        }
+        tag.attributes.push((name, value));
+    }
+}
@@ -1,11 +1,31 @@
-use super::{utils, HtmlWriteError, HtmlWriteResult, HtmlWriterOptions};
-use crate::ast::{HtmlElement, ListItem, Node};
+use super::incremental::CachedBlock;
+use super::options::{AnchorPosition, RowAttributes};
+use super::{
+    email, utils, BidiIsolation, HtmlOutputMode, HtmlWriteError, HtmlWriteResult, HtmlWriterOptions,
+};
+use crate::ast::{
+    autolink_parts, normalize_label, slugify, CustomNode, DefinitionItem, HtmlAttribute,
+    HtmlElement, ListItem, Node, NodePath, Slugger,
+};
 #[cfg(feature = "gfm")]
 use crate::ast::{TableAlignment, TaskListStatus};
+#[cfg(feature = "gfm")]
+use crate::capability::Capability;
+use crate::diagnostics;
+use crate::error::{WriteError, WriteResult};
+use crate::numbering::Numbering;
+use crate::ops::{
+    contains_toc_marker, is_toc_marker, resolve_cross_ref, table_of_contents, HeadingAnchors,
+};
+use crate::options::{shift_heading_level, EmptyContainerPolicy, SoftBreakStyle};
+use crate::writer::bidi::needs_isolation;
 use crate::writer::context::NewlineContext;
+use crate::writer::ordinals::OrdinalHook;
 use ecow::EcoString;
 use html_escape;
-use log;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// HTML writer with flexible newline control for serializing CommonMark AST nodes to HTML.
 ///
@@ -58,11 +78,114 @@ pub struct HtmlWriter {
     /// Writer options
     pub options: HtmlWriterOptions,
     /// Buffer for storing the output text
-    buffer: EcoString,
+    pub(super) buffer: EcoString,
     /// Whether a tag is currently opened
     tag_opened: bool,
     /// Current rendering context for flexible newline control
     context: NewlineContext,
+    /// Rendered top-level blocks kept by `render_dirty`
+    pub(super) block_cache: HashMap<NodePath, CachedBlock>,
+    /// Rendered byte length of each top-level block, when tracked
+    block_sizes: Vec<(NodePath, usize)>,
+    /// Whether a tracked document is being written, so nested documents aren't measured
+    measuring_blocks: bool,
+    /// Nesting depth of the node being written, reported in diagnostics spans
+    depth: usize,
+    /// Table of contents of the document being written, when `toc_marker` is set
+    pub(super) toc: Option<Node>,
+    /// Anchors of the headings of the document being written, for cross-references
+    heading_anchors: Option<Arc<HeadingAnchors>>,
+    /// Numbers of figures, tables and equations
+    pub(super) numbering: Arc<Numbering>,
+    /// Slugs issued to headings so far, when `heading_anchor` is set
+    pub(super) heading_slugs: Slugger,
+    /// Footnotes of the document being written
+    pub(super) footnotes: Footnotes,
+    /// Whether this is the sandbox an untrusted custom node is written to, see
+    /// [`HtmlWriterOptions::trust_custom_nodes`]
+    untrusted: bool,
+    /// Whether the tag opened by an untrusted custom node was left out, along
+    /// with its attributes
+    dropped_tag: bool,
+    /// Inline style of the open tag, collected when `email` is set
+    pub(super) email_style: EcoString,
+}
+
+/// Footnote numbers and the definitions moved to the end of a document
+#[derive(Debug, Clone, Default)]
+pub(super) struct Footnotes {
+    /// Depth of the outermost document, whose top-level footnote definitions
+    /// are written in the footnote section
+    block_depth: Option<usize>,
+    /// Normalized labels of every definition in that document
+    defined: HashSet<EcoString>,
+    /// Number of each footnote, by order of first reference, and how often it
+    /// has been referenced
+    numbers: HashMap<EcoString, (usize, usize)>,
+    /// Id of each footnote, unique among the footnotes of the document
+    ids: HashMap<EcoString, EcoString>,
+    /// Slugs issued to footnote ids so far
+    slugs: Slugger,
+}
+
+impl Footnotes {
+    /// Footnotes of a document at `depth` with top-level blocks `children`
+    pub(super) fn of_document(depth: usize, children: &[Node]) -> Self {
+        let mut footnotes = Footnotes {
+            block_depth: Some(depth),
+            ..Footnotes::default()
+        };
+        for child in children {
+            child.walk_with_paths(|_, node| {
+                if let Node::FootnoteDefinition { label, .. } = node {
+                    footnotes.defined.insert(normalize_label(label));
+                }
+            });
+        }
+        footnotes
+    }
+
+    /// Whether `block`, a top-level block of the document, is a definition
+    /// written in the footnote section rather than where it stands
+    pub(super) fn defers(&self, block: &Node) -> bool {
+        self.block_depth.is_some()
+            && matches!(block.resolve_shared(), Node::FootnoteDefinition { .. })
+    }
+
+    /// Number of the footnote with normalized label `key`, assigning the next
+    /// one and an id derived from `label` if it has none yet
+    pub(super) fn number(&mut self, key: &EcoString, label: &str) -> usize {
+        if let Some(&(number, _)) = self.numbers.get(key) {
+            return number;
+        }
+        let number = self.numbers.len() + 1;
+        self.numbers.insert(key.clone(), (number, 0));
+        // A label without any character kept in slugs is named by its number
+        let id = if slugify(label).is_empty() {
+            self.slugs.slug(&number.to_string())
+        } else {
+            self.slugs.slug(label)
+        };
+        self.ids.insert(key.clone(), id);
+        number
+    }
+
+    /// Count one more reference, returning the footnote number and how often it
+    /// has now been referenced, or `None` if the label is known to be undefined
+    pub(super) fn reference(&mut self, key: &EcoString, label: &str) -> Option<(usize, usize)> {
+        if self.block_depth.is_some() && !self.defined.contains(key) {
+            return None;
+        }
+        self.number(key, label);
+        let entry = self.numbers.get_mut(key).expect("number was just assigned");
+        entry.1 += 1;
+        Some(*entry)
+    }
+
+    /// Id of a footnote that has been numbered
+    pub(super) fn id(&self, key: &EcoString) -> EcoString {
+        self.ids.get(key).cloned().unwrap_or_default()
+    }
 }
 
 impl HtmlWriter {
@@ -78,6 +201,18 @@ impl HtmlWriter {
             buffer: EcoString::new(),
             tag_opened: false,
             context: NewlineContext::block(), // Default to block context for HTML
+            block_cache: HashMap::new(),
+            block_sizes: Vec::new(),
+            measuring_blocks: false,
+            depth: 0,
+            toc: None,
+            heading_anchors: None,
+            numbering: Arc::default(),
+            heading_slugs: Slugger::new(),
+            footnotes: Footnotes::default(),
+            untrusted: false,
+            dropped_tag: false,
+            email_style: EcoString::new(),
         }
     }
 
@@ -88,9 +223,49 @@ impl HtmlWriter {
             buffer: EcoString::new(),
             tag_opened: false,
             context,
+            block_cache: HashMap::new(),
+            block_sizes: Vec::new(),
+            measuring_blocks: false,
+            depth: 0,
+            toc: None,
+            heading_anchors: None,
+            numbering: Arc::default(),
+            heading_slugs: Slugger::new(),
+            footnotes: Footnotes::default(),
+            untrusted: false,
+            dropped_tag: false,
+            email_style: EcoString::new(),
         }
     }
 
+    /// Numbers used to write `Node::NumberRef`.
+    pub fn numbering(&self) -> &Numbering {
+        &self.numbering
+    }
+
+    /// Mutable access to the numbers used to write `Node::NumberRef`.
+    pub fn numbering_mut(&mut self) -> &mut Numbering {
+        Arc::make_mut(&mut self.numbering)
+    }
+
+    /// Replaces the numbers used to write `Node::NumberRef`.
+    pub fn set_numbering(&mut self, numbering: Numbering) {
+        self.numbering = Arc::new(numbering);
+    }
+
+    /// Shares the numbers of a CommonMark writer using this writer as its HTML
+    /// fallback, or of the writer rendering the blocks of a document one by one.
+    pub(crate) fn share_numbering(&mut self, numbering: &Arc<Numbering>) {
+        self.numbering = Arc::clone(numbering);
+    }
+
+    /// Shares the heading anchors of a CommonMark writer using this writer as
+    /// its HTML fallback, or of the document whose blocks are rendered one by
+    /// one, so cross-references resolve against the whole document.
+    pub(crate) fn share_heading_anchors(&mut self, anchors: Option<&Arc<HeadingAnchors>>) {
+        self.heading_anchors = anchors.cloned();
+    }
+
     /// Updates the writer's options at runtime.
     pub fn set_options(&mut self, options: HtmlWriterOptions) {
         self.options = options;
@@ -140,16 +315,57 @@ impl HtmlWriter {
         self
     }
 
-    /// Consumes the writer and returns the generated HTML string.
+    /// Consumes the writer and returns the generated HTML string, framed
+    /// according to the `output_mode` and with the `final_newline` policy applied.
     pub fn into_string(mut self) -> EcoString {
         self.ensure_tag_closed().unwrap();
-        self.buffer
+        let output = self.framed_output();
+        self.options.final_newline.apply(output)
+    }
+
+    /// The buffer inside the wrapping element or document of the `output_mode`
+    fn framed_output(&mut self) -> EcoString {
+        let (open, close) = match self.options.output_mode {
+            HtmlOutputMode::Fragment => return std::mem::take(&mut self.buffer),
+            HtmlOutputMode::WrappedFragment => ("<div", "</div>\n"),
+            HtmlOutputMode::Standalone => ("<!DOCTYPE html>\n<html", "</body>\n</html>\n"),
+        };
+        let mut output = EcoString::from(open);
+        if let Some(lang) = &self.options.lang {
+            output.push_str(" lang=\"");
+            output.push_str(&html_escape::encode_quoted_attribute(lang));
+            output.push('"');
+        }
+        if let Some(dir) = self.options.dir {
+            output.push_str(" dir=\"");
+            output.push_str(dir.as_str());
+            output.push('"');
+        }
+        output.push_str(">\n");
+        if self.options.output_mode == HtmlOutputMode::Standalone {
+            output.push_str("<head>\n<meta charset=\"utf-8\" />\n</head>\n<body>\n");
+        }
+        output.push_str(&self.buffer);
+        if !self.buffer.is_empty() && !self.buffer.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(close);
+        output
+    }
+
+    /// Rendered byte length of each top-level block of the last document written.
+    ///
+    /// Only recorded when [`HtmlWriterOptions::track_block_sizes`] is enabled;
+    /// empty otherwise.
+    pub fn block_sizes(&self) -> &[(NodePath, usize)] {
+        &self.block_sizes
     }
 
     // --- Low-level HTML writing primitives ---
 
     fn ensure_tag_closed(&mut self) -> HtmlWriteResult<()> {
         if self.tag_opened {
+            self.write_email_style();
             self.buffer.push('>');
             self.tag_opened = false;
         }
@@ -161,6 +377,7 @@ impl HtmlWriter {
         self.buffer.push('<');
         self.buffer.push_str(tag_name);
         self.tag_opened = true;
+        self.start_email_style(tag_name);
         Ok(())
     }
 
@@ -168,6 +385,13 @@ impl HtmlWriter {
     ///
     /// This is a public wrapper around start_tag_internal.
     pub fn start_tag(&mut self, tag_name: &str) -> HtmlWriteResult<()> {
+        self.dropped_tag = false;
+        if self.untrusted && !utils::is_untrusted_tag_allowed(tag_name) {
+            self.reject_untrusted(HtmlWriteError::InvalidHtmlTag(tag_name.to_string()))?;
+            self.ensure_tag_closed()?;
+            self.dropped_tag = true;
+            return Ok(());
+        }
         self.start_tag_internal(tag_name)
     }
 
@@ -177,11 +401,14 @@ impl HtmlWriter {
                 "Cannot write attribute: no tag is currently open.".to_string(),
             ));
         }
+        if self.collect_email_style(key, value) {
+            return Ok(());
+        }
         self.buffer.push(' ');
         self.buffer.push_str(key);
         self.buffer.push_str("=\"");
         self.buffer
-            .push_str(html_escape::encode_text(value).as_ref());
+            .push_str(html_escape::encode_quoted_attribute(value).as_ref());
         self.buffer.push('"');
         Ok(())
     }
@@ -190,11 +417,25 @@ impl HtmlWriter {
     ///
     /// This is a public wrapper around attribute_internal.
     pub fn attribute(&mut self, key: &str, value: &str) -> HtmlWriteResult<()> {
+        if self.untrusted {
+            if self.dropped_tag {
+                return Ok(());
+            }
+            if !utils::is_untrusted_attribute_allowed(key) {
+                return self
+                    .reject_untrusted(HtmlWriteError::InvalidHtmlAttribute(key.to_string()));
+            }
+            if is_url_attribute(key) {
+                let url = self.checked_url(value)?;
+                return self.attribute_internal(key, url);
+            }
+        }
         self.attribute_internal(key, value)
     }
 
     fn finish_tag_internal(&mut self) -> HtmlWriteResult<()> {
         if self.tag_opened {
+            self.write_email_style();
             self.buffer.push('>');
             self.tag_opened = false;
         }
@@ -205,6 +446,9 @@ impl HtmlWriter {
     ///
     /// This is a public wrapper around finish_tag_internal.
     pub fn finish_tag(&mut self) -> HtmlWriteResult<()> {
+        if std::mem::take(&mut self.dropped_tag) {
+            return Ok(());
+        }
         self.finish_tag_internal()
     }
 
@@ -220,13 +464,21 @@ impl HtmlWriter {
     ///
     /// This is a public wrapper around end_tag_internal.
     pub fn end_tag(&mut self, tag_name: &str) -> HtmlWriteResult<()> {
+        if self.untrusted && !utils::is_untrusted_tag_allowed(tag_name) {
+            // The start tag was refused already
+            return Ok(());
+        }
         self.end_tag_internal(tag_name)
     }
 
     fn text_internal(&mut self, text: &str) -> HtmlWriteResult<()> {
         self.ensure_tag_closed()?;
-        self.buffer
-            .push_str(html_escape::encode_text(text).as_ref());
+        let escaped = if self.options.escape_quotes_in_text {
+            html_escape::encode_quoted_attribute(text)
+        } else {
+            html_escape::encode_text(text)
+        };
+        self.buffer.push_str(escaped.as_ref());
         Ok(())
     }
 
@@ -249,6 +501,8 @@ impl HtmlWriter {
         self.ensure_tag_closed()?;
         self.buffer.push('<');
         self.buffer.push_str(tag_name);
+        self.start_email_style(tag_name);
+        self.write_email_style();
         self.buffer.push_str(" />");
         self.tag_opened = false;
         Ok(())
@@ -260,6 +514,7 @@ impl HtmlWriter {
                 "Cannot finish self-closing tag: no tag is currently open.".to_string(),
             ));
         }
+        self.write_email_style();
         self.buffer.push_str(" />");
         self.tag_opened = false;
         Ok(())
@@ -269,6 +524,9 @@ impl HtmlWriter {
     ///
     /// This is a public wrapper around finish_self_closing_tag_internal.
     pub fn finish_self_closing_tag(&mut self) -> HtmlWriteResult<()> {
+        if std::mem::take(&mut self.dropped_tag) {
+            return Ok(());
+        }
         self.finish_self_closing_tag_internal()
     }
 
@@ -282,28 +540,267 @@ impl HtmlWriter {
     ///
     /// This method allows adding arbitrary HTML content without escaping.
     /// It should be used with caution as it can introduce security issues
-    /// if used with untrusted input.
+    /// if used with untrusted input. Within a custom node that isn't trusted
+    /// (see [`HtmlWriterOptions::trust_custom_nodes`]), the content is escaped
+    /// as text instead.
     pub fn raw_html(&mut self, html: &str) -> HtmlWriteResult<()> {
+        if self.untrusted {
+            return self.text_internal(html);
+        }
         self.raw_html_internal(html)
     }
 
+    /// Refuse markup written by an untrusted custom node
+    ///
+    /// An error in strict mode; otherwise the markup is left out with a warning.
+    fn reject_untrusted(&self, error: HtmlWriteError) -> HtmlWriteResult<()> {
+        if self.options.strict {
+            return Err(error);
+        }
+        diagnostics::warn!("Untrusted custom node output left out: {}", error);
+        Ok(())
+    }
+
+    /// Write a custom node that isn't trusted
+    ///
+    /// The node renders into a sandbox writer of its own. It may change the
+    /// options of the sandbox or replace it altogether, so its output is then
+    /// checked again against the options of this writer, which it can't reach.
+    fn write_untrusted_custom_node(&mut self, node: &dyn CustomNode) -> HtmlWriteResult<()> {
+        let mut sandbox = self.probe_writer();
+        sandbox.untrusted = true;
+        sandbox.depth = self.depth;
+        sandbox.heading_slugs = self.heading_slugs.clone();
+        // Inline styles are refused below, so they are added to the checked
+        // output instead
+        sandbox.options.email = None;
+        render_custom_node(node, &mut sandbox)?;
+        sandbox.ensure_tag_closed()?;
+        self.footnotes = std::mem::take(&mut sandbox.footnotes);
+        self.heading_slugs = std::mem::take(&mut sandbox.heading_slugs);
+        self.write_untrusted_output(&sandbox.buffer)
+    }
+
+    /// Write the output of an untrusted custom node
+    ///
+    /// Tags and attributes are checked like those the node writes through
+    /// [`start_tag`](Self::start_tag) and [`attribute`](Self::attribute), end
+    /// tags that don't close an element of the output are left out, elements
+    /// left open are closed, and anything else is escaped as text.
+    fn write_untrusted_output(&mut self, html: &str) -> HtmlWriteResult<()> {
+        let mut open: Vec<&str> = Vec::new();
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            self.text_internal(&html_escape::decode_html_entities(&rest[..start]))?;
+            rest = &rest[start..];
+            let Some((tag, len)) = utils::parse_tag(rest) else {
+                self.text_internal("<")?;
+                rest = &rest[1..];
+                continue;
+            };
+            rest = &rest[len..];
+            let email_drops = self
+                .options
+                .email
+                .as_ref()
+                .is_some_and(|email| !email.allows_tag(tag.name));
+            if !utils::is_untrusted_tag_allowed(tag.name) {
+                if !tag.closing {
+                    self.reject_untrusted(HtmlWriteError::InvalidHtmlTag(tag.name.to_string()))?;
+                }
+            } else if tag.closing {
+                if let Some(index) = open
+                    .iter()
+                    .rposition(|name| name.eq_ignore_ascii_case(tag.name))
+                {
+                    for name in open.split_off(index).into_iter().rev() {
+                        self.end_tag_internal(name)?;
+                    }
+                }
+            } else if !email_drops {
+                self.write_untrusted_start_tag(&tag)?;
+                if !tag.self_closing && !utils::is_void_element(tag.name) {
+                    open.push(tag.name);
+                }
+            }
+        }
+        self.text_internal(&html_escape::decode_html_entities(rest))?;
+        for name in open.into_iter().rev() {
+            self.end_tag_internal(name)?;
+        }
+        Ok(())
+    }
+
+    /// Write a start tag of the output of an untrusted custom node
+    fn write_untrusted_start_tag(&mut self, tag: &utils::ParsedTag<'_>) -> HtmlWriteResult<()> {
+        let image = tag.name.eq_ignore_ascii_case("img");
+        self.start_tag_internal(tag.name)?;
+        for (name, value) in &tag.attributes {
+            if !utils::is_untrusted_attribute_allowed(name) {
+                self.reject_untrusted(HtmlWriteError::InvalidHtmlAttribute(name.to_string()))?;
+            } else if is_url_attribute(name) {
+                let url = self.checked_url(value)?;
+                self.attribute_internal(name, url)?;
+            } else {
+                let value = match &self.options.email {
+                    Some(email) if image && name.eq_ignore_ascii_case("width") => {
+                        email::capped_image_width(email, value)
+                    }
+                    _ => Cow::Borrowed(value.as_ref()),
+                };
+                self.attribute_internal(name, &value)?;
+            }
+        }
+        if image {
+            self.cap_email_image_width();
+        }
+        if tag.self_closing {
+            self.finish_self_closing_tag_internal()
+        } else {
+            self.finish_tag_internal()
+        }
+    }
+
+    /// Add the location of the offending node to a content error of the
+    /// outermost node being written
+    pub(crate) fn locate(&self, node: &Node, result: WriteResult<()>) -> WriteResult<()> {
+        match result {
+            Err(error) if self.depth == 0 && error.is_content_error() => {
+                let path = self
+                    .locate_error(node)
+                    .map_or_else(NodePath::root, |(path, _)| path);
+                Err(error.at_node(node, path))
+            }
+            result => result,
+        }
+    }
+
+    /// Path of the innermost node that fails to write, with its error
+    ///
+    /// Like [`CommonMarkWriter::locate_error`](crate::CommonMarkWriter::locate_error),
+    /// each node is written on its own by a writer sharing this writer's
+    /// options and document state, so the path is relative to `node`.
+    /// Returns `None` if `node` writes without error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{HtmlElement, Node, NodePath};
+    /// use cmark_writer::HtmlWriter;
+    ///
+    /// let doc = Node::Document(vec![Node::Paragraph(vec![
+    ///     Node::Text("Fine".into()),
+    ///     Node::HtmlElement(HtmlElement::new("bad tag")),
+    /// ])]);
+    ///
+    /// let writer = HtmlWriter::new();
+    /// let (path, _error) = writer.locate_error(&doc).unwrap();
+    /// assert_eq!(path, NodePath::root().child(0).child(1));
+    /// ```
+    pub fn locate_error(&self, node: &Node) -> Option<(NodePath, WriteError)> {
+        let mut error = self.probe_writer().write_node_internal(node).err()?;
+        let mut path = NodePath::root();
+        let mut current = node.resolve_shared();
+        'descend: loop {
+            for (segment, child) in current.children_with_segments() {
+                if let Err(child_error) = self.probe_writer().write_node_internal(child) {
+                    path.push(segment);
+                    current = child.resolve_shared();
+                    error = child_error;
+                    continue 'descend;
+                }
+            }
+            return Some((path, error.into()));
+        }
+    }
+
+    /// An empty writer with this writer's options and document state, for
+    /// writing a node on its own
+    fn probe_writer(&self) -> Self {
+        let mut writer = Self::with_context(self.options.clone(), self.context.clone());
+        writer.toc = self.toc.clone();
+        writer.heading_anchors = self.heading_anchors.clone();
+        writer.numbering = Arc::clone(&self.numbering);
+        writer.footnotes = self.footnotes.clone();
+        writer
+    }
+
     // --- Main Node Dispatcher ---
 
     /// Writes an AST `Node` to HTML using the configured options.
     pub(crate) fn write_node_internal(&mut self, node: &Node) -> HtmlWriteResult<()> {
+        let node = node.resolve_shared();
+        let _span = diagnostics::node_span(node, self.depth);
+        self.depth += 1;
+        let result = self.render_node(node);
+        self.depth -= 1;
+        result
+    }
+
+    /// Body of [`write_node_internal`](Self::write_node_internal) for a resolved node
+    fn render_node(&mut self, node: &Node) -> HtmlWriteResult<()> {
+        if node.is_empty_container() {
+            match self
+                .options
+                .empty_container_policy
+                .resolve(self.options.strict)
+            {
+                EmptyContainerPolicy::Error => {
+                    return Err(HtmlWriteError::InvalidStructure(format!(
+                        "Empty {} is not allowed",
+                        node.type_name()
+                    )));
+                }
+                EmptyContainerPolicy::Omit => return Ok(()),
+                _ => {}
+            }
+        }
+
         match node {
-            Node::Document(children) => self.write_document_node(children),
-            Node::Paragraph(children) => self.write_paragraph_node(children),
+            Node::Document(children) | Node::ConfiguredDocument { children, .. } => {
+                self.prepare_document(node);
+                self.write_document_node(children)
+            }
+            Node::Paragraph(children) => {
+                let toc = self.options.toc_marker.as_deref().and_then(|marker| {
+                    is_toc_marker(children, marker)
+                        .then(|| self.toc.take())
+                        .flatten()
+                });
+                match toc {
+                    // A table of contents without headings leaves nothing behind
+                    Some(toc) if toc.is_empty_container() => {
+                        self.toc = Some(toc);
+                        Ok(())
+                    }
+                    Some(toc) => {
+                        let result = self.write_node_internal(&toc);
+                        self.toc = Some(toc);
+                        result
+                    }
+                    None => self.write_paragraph_node(children),
+                }
+            }
             Node::Text(text) => self.write_text_node(text),
-            Node::Heading { level, content, .. } => self.write_heading_node(*level, content),
+            Node::NumberRef(kind, id) => self.write_number_ref_node(kind, id),
+            Node::CrossRef { target, text } => self.write_cross_ref_node(target, text.as_deref()),
+            Node::Heading {
+                level,
+                content,
+                custom_id,
+                ..
+            } => self.write_heading_node(*level, content, custom_id.as_deref()),
             Node::Emphasis(children) => self.write_emphasis_node(children),
             Node::Strong(children) => self.write_strong_node(children),
             Node::ThematicBreak => self.write_thematic_break_node(),
+            Node::ReadMoreMarker => Ok(()), // Only marks where a summary ends
             Node::InlineCode(code) => self.write_inline_code_node(code),
+            Node::Math { display, content } => self.write_math_node(*display, content),
             Node::CodeBlock {
                 language, content, ..
             } => self.write_code_block_node(language, content),
             Node::HtmlBlock(block_content) => self.write_html_block_node(block_content),
+            Node::HtmlInline(html) => self.write_raw_markup(html).map(|_| ()),
             Node::HtmlElement(element) => self.write_html_element_node(element),
             Node::SoftBreak => self.write_soft_break_node(),
             Node::HardBreak => self.write_hard_break_node(),
@@ -316,6 +813,7 @@ impl HtmlWriter {
             Node::BlockQuote(children) => self.write_blockquote_node(children),
             Node::OrderedList { start, items } => self.write_ordered_list_node(*start, items),
             Node::UnorderedList(items) => self.write_unordered_list_node(items),
+            Node::DefinitionList(items) => self.write_definition_list_node(items),
             #[cfg(feature = "gfm")]
             Node::Strikethrough(children) => self.write_strikethrough_node(children),
             Node::Table {
@@ -333,23 +831,25 @@ impl HtmlWriter {
             #[cfg(feature = "gfm")]
             Node::ExtendedAutolink(url) => self.write_extended_autolink_node(url),
             Node::LinkReferenceDefinition { .. } => Ok(()), // Definitions are not rendered in final HTML
+            Node::FootnoteReference(label) => self.write_footnote_reference_node(label),
+            Node::FootnoteDefinition { label, content } => {
+                self.write_footnote_definition_node(label, content)
+            }
             Node::ReferenceLink { label, content } => {
                 self.write_reference_link_node(label, content)
             }
             Node::Custom(custom_node) => {
+                if !self.options.trust_custom_nodes {
+                    return self.write_untrusted_custom_node(custom_node.as_ref());
+                }
                 // Call the CustomNode's html_render method, which handles the HTML rendering
-                custom_node.html_render(self).map_err(|e| match e {
-                    crate::error::WriteError::HtmlRenderingError(html_err) => html_err,
-                    other => {
-                        crate::writer::html::HtmlWriteError::CustomNodeError(other.to_string())
-                    }
-                })
+                render_custom_node(custom_node.as_ref(), self)
             }
             // Fallback for node types not handled, especially if GFM is off and GFM nodes appear
             #[cfg(not(feature = "gfm"))]
             Node::ExtendedAutolink(url) => {
                 // Handle GFM specific nodes explicitly if feature is off
-                log::warn!("ExtendedAutolink encountered but GFM feature is not enabled. Rendering as text: {}", url);
+                diagnostics::warn!("ExtendedAutolink encountered but GFM feature is not enabled. Rendering as text: {}", url);
                 self.text_internal(url)
             }
             // All node types are handled above, but keeping this for future extensibility
@@ -358,19 +858,288 @@ impl HtmlWriter {
         }
     }
 
+    /// Build the table of contents of `root` if a `toc_marker` is set, and the
+    /// anchors its cross-references resolve to, unless the outermost document
+    /// has done so already
+    pub(crate) fn prepare_document(&mut self, root: &Node) {
+        if self.toc.is_none() {
+            self.toc = self.table_of_contents(root);
+        }
+        if self.heading_anchors.is_none() {
+            self.heading_anchors = Some(Arc::new(self.heading_anchors_of(root)));
+        }
+    }
+
+    /// The table of contents of `root`, if a `toc_marker` is set
+    pub(super) fn table_of_contents(&self, root: &Node) -> Option<Node> {
+        self.options
+            .toc_marker
+            .is_some()
+            .then(|| table_of_contents(root))
+    }
+
+    /// The anchors of the headings of `root`
+    ///
+    /// Where the table of contents is shown, it links to every heading, so
+    /// every heading gets an id.
+    pub(super) fn heading_anchors_of(&self, root: &Node) -> HeadingAnchors {
+        let mut anchors = HeadingAnchors::new(root);
+        if self.shows_toc(root) {
+            anchors.reference_all();
+        }
+        anchors
+    }
+
+    /// Whether `node` holds the `toc_marker`
+    pub(super) fn shows_toc(&self, node: &Node) -> bool {
+        self.options
+            .toc_marker
+            .as_deref()
+            .is_some_and(|marker| contains_toc_marker(node, marker))
+    }
+
     // --- Node-Specific Writing Methods (Internal) ---
 
     fn write_document_node(&mut self, children: &[Node]) -> HtmlWriteResult<()> {
-        for child in children {
-            self.write_node_internal(child)?;
+        // The outermost document collects the footnotes
+        if self.footnotes.block_depth.is_some() {
+            return self.write_document_content(children);
+        }
+        self.start_footnotes(children);
+        match self.write_document_content(children) {
+            Ok(()) => self.finish_footnotes(children),
+            Err(error) => {
+                self.footnotes = Footnotes::default();
+                Err(error)
+            }
+        }
+    }
+
+    /// Collect the footnote definitions among the top-level blocks `children`
+    /// of a document, so [`write_top_level_block`](Self::write_top_level_block)
+    /// leaves them for the footnote section written by
+    /// [`finish_footnotes`](Self::finish_footnotes)
+    pub(crate) fn start_footnotes(&mut self, children: &[Node]) {
+        self.footnotes = Footnotes::of_document(self.depth, children);
+    }
+
+    /// Write the footnote section of the document started with
+    /// [`start_footnotes`](Self::start_footnotes)
+    pub(crate) fn finish_footnotes(&mut self, children: &[Node]) -> HtmlWriteResult<()> {
+        let result = self.write_footnote_section(children);
+        self.footnotes = Footnotes::default();
+        result
+    }
+
+    fn write_document_content(&mut self, children: &[Node]) -> HtmlWriteResult<()> {
+        if !self.options.track_block_sizes || self.measuring_blocks {
+            return self.write_document_blocks(children, false);
+        }
+        self.block_sizes.clear();
+        self.measuring_blocks = true;
+        let result = self.write_document_blocks(children, true);
+        self.measuring_blocks = false;
+        result
+    }
+
+    fn write_document_blocks(&mut self, children: &[Node], measure: bool) -> HtmlWriteResult<()> {
+        let defer_footnotes = self.footnotes.block_depth == Some(self.depth);
+        for (i, child) in children.iter().enumerate() {
+            let start = self.buffer.len();
+            // Only the blocks of the outermost document get markers
+            if defer_footnotes {
+                self.write_top_level_block(&NodePath::root().child(i), child)?;
+            } else {
+                self.write_node_internal(child)?;
+            }
             // Optionally add newlines between major block elements in HTML source
             if child.is_block() && !self.buffer.ends_with('\n') {
                 // self.raw_html_internal("\n")?;
             }
+            if measure {
+                self.ensure_tag_closed()?;
+                let size = self.buffer.len() - start;
+                self.block_sizes.push((NodePath::root().child(i), size));
+            }
         }
         Ok(())
     }
 
+    /// Write a top-level block at `path`, between marker comments when
+    /// `block_markers` is set
+    ///
+    /// Footnote definitions are left for the footnote section while the
+    /// footnotes of a document are collected.
+    pub(crate) fn write_top_level_block(
+        &mut self,
+        path: &NodePath,
+        block: &Node,
+    ) -> HtmlWriteResult<()> {
+        if self.footnotes.defers(block) {
+            return Ok(());
+        }
+        if !self.options.block_markers {
+            return self.write_node_internal(block);
+        }
+        self.raw_html_internal(&format!("<!-- cmark:block start path={} -->\n", path))?;
+        self.write_node_internal(block)?;
+        self.ensure_tag_closed()?;
+        if !self.buffer.is_empty() && !self.buffer.ends_with('\n') {
+            self.raw_html_internal("\n")?;
+        }
+        self.raw_html_internal(&format!("<!-- cmark:block end path={} -->\n", path))
+    }
+
+    fn write_footnote_reference_node(&mut self, label: &str) -> HtmlWriteResult<()> {
+        let key = normalize_label(label);
+        let Some((number, count)) = self.footnotes.reference(&key, label) else {
+            if self.options.strict {
+                return Err(HtmlWriteError::InvalidStructure(format!(
+                    "Footnote reference to undefined label '{}'",
+                    label
+                )));
+            }
+            diagnostics::warn!("Footnote reference to undefined label '{}'", label);
+            return self.text_internal(&format!("[^{}]", label));
+        };
+        let id = self.footnotes.id(&key);
+        self.start_tag_internal("sup")?;
+        self.attribute_internal("class", "footnote-ref")?;
+        self.finish_tag_internal()?;
+        self.start_tag_internal("a")?;
+        self.attribute_internal("href", &format!("#fn-{}", id))?;
+        if count == 1 {
+            self.attribute_internal("id", &format!("fnref-{}", id))?;
+        } else {
+            self.attribute_internal("id", &format!("fnref-{}-{}", id, count))?;
+        }
+        self.finish_tag_internal()?;
+        self.text_internal(&number.to_string())?;
+        self.end_tag_internal("a")?;
+        self.end_tag_internal("sup")
+    }
+
+    /// Write a footnote definition where it stands, outside the footnote section
+    /// of a document
+    fn write_footnote_definition_node(
+        &mut self,
+        label: &str,
+        content: &[Node],
+    ) -> HtmlWriteResult<()> {
+        let key = normalize_label(label);
+        let number = self.footnotes.number(&key, label);
+        self.start_tag_internal("div")?;
+        self.attribute_internal("class", "footnote-definition")?;
+        self.attribute_internal("id", &format!("fn-{}", self.footnotes.id(&key)))?;
+        self.finish_tag_internal()?;
+        self.start_tag_internal("sup")?;
+        self.attribute_internal("class", "footnote-definition-label")?;
+        self.finish_tag_internal()?;
+        self.text_internal(&number.to_string())?;
+        self.end_tag_internal("sup")?;
+        self.raw_html_internal("\n")?;
+        for child in content {
+            self.write_node_internal(child)?;
+        }
+        self.end_tag_internal("div")?;
+        self.raw_html_internal("\n")
+    }
+
+    /// Write the referenced footnote definitions among the top-level `children`
+    /// in a list at the end of the document, ordered by their numbers, each
+    /// followed by links back to its references
+    fn write_footnote_section(&mut self, children: &[Node]) -> HtmlWriteResult<()> {
+        let mut definitions = Vec::new();
+        let mut seen = HashSet::new();
+        for child in children {
+            if let Node::FootnoteDefinition { label, content } = child.resolve_shared() {
+                let key = normalize_label(label);
+                // The first definition of a label wins
+                if !seen.insert(key.clone()) {
+                    continue;
+                }
+                if let Some(&(number, count)) = self.footnotes.numbers.get(&key) {
+                    if count > 0 {
+                        definitions.push((number, count, key, content));
+                    }
+                }
+            }
+        }
+        if definitions.is_empty() {
+            return Ok(());
+        }
+        definitions.sort_by_key(|&(number, ..)| number);
+
+        self.start_tag_internal("section")?;
+        self.attribute_internal("class", "footnotes")?;
+        self.finish_tag_internal()?;
+        self.raw_html_internal("\n<ol>\n")?;
+        for (number, count, key, content) in definitions {
+            let id = self.footnotes.id(&key);
+            self.start_tag_internal("li")?;
+            self.attribute_internal("id", &format!("fn-{}", id))?;
+            self.finish_tag_internal()?;
+            self.raw_html_internal("\n")?;
+            for child in content {
+                self.write_node_internal(child)?;
+            }
+            // Backlinks go at the end of a closing paragraph, or in their own
+            let in_paragraph = matches!(
+                content.last().map(Node::resolve_shared),
+                Some(Node::Paragraph(_))
+            ) && self.buffer.ends_with("</p>\n");
+            if in_paragraph {
+                self.buffer.truncate(self.buffer.len() - "</p>\n".len());
+                self.raw_html_internal(" ")?;
+            } else {
+                self.raw_html_internal("<p>")?;
+            }
+            for reference in 1..=count {
+                if reference > 1 {
+                    self.raw_html_internal(" ")?;
+                }
+                self.write_footnote_backref(&id, number, reference)?;
+            }
+            self.raw_html_internal("</p>\n</li>\n")?;
+        }
+        self.raw_html_internal("</ol>\n")?;
+        self.end_tag_internal("section")?;
+        self.raw_html_internal("\n")
+    }
+
+    /// Link back to the `reference`th reference to footnote `number`
+    fn write_footnote_backref(
+        &mut self,
+        id: &str,
+        number: usize,
+        reference: usize,
+    ) -> HtmlWriteResult<()> {
+        let (href, label) = if reference == 1 {
+            (
+                format!("#fnref-{}", id),
+                format!("Back to reference {}", number),
+            )
+        } else {
+            (
+                format!("#fnref-{}-{}", id, reference),
+                format!("Back to reference {}-{}", number, reference),
+            )
+        };
+        self.start_tag_internal("a")?;
+        self.attribute_internal("href", &href)?;
+        self.attribute_internal("class", "footnote-backref")?;
+        self.attribute_internal("aria-label", &label)?;
+        self.finish_tag_internal()?;
+        self.raw_html_internal("↩")?;
+        if reference > 1 {
+            self.start_tag_internal("sup")?;
+            self.finish_tag_internal()?;
+            self.text_internal(&reference.to_string())?;
+            self.end_tag_internal("sup")?;
+        }
+        self.end_tag_internal("a")
+    }
+
     fn write_paragraph_node(&mut self, children: &[Node]) -> HtmlWriteResult<()> {
         self.start_tag_internal("p")?;
         self.finish_tag_internal()?;
@@ -383,16 +1152,123 @@ impl HtmlWriter {
     }
 
     fn write_text_node(&mut self, text: &str) -> HtmlWriteResult<()> {
-        self.text_internal(text)
+        let text = match &self.options.placeholders {
+            Some(placeholders) => placeholders.substitute(text),
+            None => Cow::Borrowed(text),
+        };
+        let isolation = self
+            .options
+            .bidi_isolation
+            .filter(|_| needs_isolation(&text, self.options.document_direction));
+        match isolation {
+            Some(BidiIsolation::Bdi) => {
+                self.start_tag_internal("bdi")?;
+                self.text_internal(&text)?;
+                self.end_tag_internal("bdi")
+            }
+            Some(BidiIsolation::DirAuto) => {
+                self.start_tag_internal("span")?;
+                self.attribute_internal("dir", "auto")?;
+                self.text_internal(&text)?;
+                self.end_tag_internal("span")
+            }
+            None => self.text_internal(&text),
+        }
+    }
+
+    fn write_number_ref_node(&mut self, kind: &str, id: &str) -> HtmlWriteResult<()> {
+        match self.numbering.reference_text(kind, id) {
+            Some(text) => self.text_internal(&text),
+            None if self.options.strict => Err(HtmlWriteError::InvalidStructure(format!(
+                "Unresolved number reference to {} '{}'",
+                kind, id
+            ))),
+            None => {
+                diagnostics::warn!("Unresolved number reference to {} '{}'", kind, id);
+                self.text_internal("??")
+            }
+        }
+    }
+
+    fn write_cross_ref_node(&mut self, target: &str, text: Option<&[Node]>) -> HtmlWriteResult<()> {
+        let heading =
+            resolve_cross_ref(self.heading_anchors.as_deref(), target, self.options.strict)
+                .map_err(HtmlWriteError::InvalidStructure)?;
+        let Some(heading) = heading else {
+            return match text {
+                Some(content) => content
+                    .iter()
+                    .try_for_each(|node| self.write_node_internal(node)),
+                None => self.text_internal(target),
+            };
+        };
+        let url = format!("#{}", heading.anchor);
+        match text {
+            Some(content) => self.write_link_node(&url, &None, content),
+            None => {
+                let content = [Node::Text(heading.text.clone())];
+                self.write_link_node(&url, &None, &content)
+            }
+        }
     }
 
-    fn write_heading_node(&mut self, level: u8, content: &[Node]) -> HtmlWriteResult<()> {
-        let tag_name = format!("h{}", level.clamp(1, 6));
+    fn write_heading_node(
+        &mut self,
+        level: u8,
+        content: &[Node],
+        custom_id: Option<&str>,
+    ) -> HtmlWriteResult<()> {
+        let level = shift_heading_level(
+            level.clamp(1, 6),
+            self.options.heading_level_offset,
+            self.options.max_heading_level,
+        );
+        let tag_name = format!("h{}", level);
+        let anchor = self.options.heading_anchor.clone();
+        // Slugs match the links of the table of contents and cross-references.
+        // Without `heading_anchor`, only headings that are linked to, by a
+        // cross-reference or the table of contents, get an id.
+        let id: Option<EcoString> = match custom_id {
+            Some(id) => Some(id.into()),
+            None if anchor.is_some() || self.heading_anchors.is_some() => {
+                let text: EcoString = content.iter().map(Node::plain_text).collect();
+                let slug = self.heading_slugs.slug(&text);
+                let referenced = self
+                    .heading_anchors
+                    .as_ref()
+                    .is_some_and(|anchors| anchors.is_referenced(&slug));
+                (anchor.is_some() || referenced).then_some(slug)
+            }
+            None => None,
+        };
         self.start_tag_internal(&tag_name)?;
+        if let Some(id) = &id {
+            self.attribute_internal("id", id)?;
+        }
         self.finish_tag_internal()?;
+        let write_anchor = |writer: &mut Self| -> HtmlWriteResult<()> {
+            let (Some(anchor), Some(id)) = (&anchor, &id) else {
+                return Ok(());
+            };
+            writer.start_tag_internal("a")?;
+            if let Some(class) = &anchor.class {
+                writer.attribute_internal("class", class)?;
+            }
+            writer.attribute_internal("href", &format!("#{}", id))?;
+            writer.finish_tag_internal()?;
+            writer.text_internal(&anchor.symbol)?;
+            writer.end_tag_internal("a")
+        };
+        let position = anchor.as_ref().map(|anchor| anchor.position);
+        if position == Some(AnchorPosition::Before) {
+            write_anchor(self)?;
+        }
         for child in content {
             self.write_node_internal(child)?;
         }
+        if position == Some(AnchorPosition::After) {
+            write_anchor(self)?;
+        }
         self.end_tag_internal(&tag_name)?;
         self.raw_html_internal("\n")?;
         Ok(())
@@ -432,6 +1308,22 @@ impl HtmlWriter {
         Ok(())
     }
 
+    /// `<span class="math math-inline">` or `math-display`, holding the TeX
+    /// source for a client-side renderer such as KaTeX or MathJax
+    fn write_math_node(&mut self, display: bool, content: &str) -> HtmlWriteResult<()> {
+        let class = if display {
+            "math math-display"
+        } else {
+            "math math-inline"
+        };
+        self.start_tag_internal("span")?;
+        self.attribute_internal("class", class)?;
+        self.finish_tag_internal()?;
+        self.text_internal(content)?;
+        self.end_tag_internal("span")?;
+        Ok(())
+    }
+
     fn write_code_block_node(
         &mut self,
         language: &Option<EcoString>,
@@ -441,10 +1333,9 @@ impl HtmlWriter {
         self.finish_tag_internal()?; // Finish <pre> before potentially adding attributes to <code> or <span>
         self.start_tag_internal("code")?;
         if let Some(prefix) = &self.options.code_block_language_class_prefix {
-            if let Some(lang) = language {
-                if !lang.is_empty() {
-                    self.attribute_internal("class", &format!("{}{}", prefix, lang.trim()))?;
-                }
+            if let Some(lang) = self.options.resolve_code_language(language.as_deref()) {
+                let class_name = format!("{}{}", prefix, lang);
+                self.attribute_internal("class", &class_name)?;
             }
         }
         self.finish_tag_internal()?;
@@ -456,32 +1347,68 @@ impl HtmlWriter {
     }
 
     fn write_html_block_node(&mut self, block_content: &str) -> HtmlWriteResult<()> {
-        self.raw_html_internal(block_content)?;
-        if !block_content.ends_with('\n') {
+        if !self.write_raw_markup(block_content)? {
             self.raw_html_internal("\n")?;
         }
         Ok(())
     }
 
-    fn write_html_element_node(&mut self, element: &HtmlElement) -> HtmlWriteResult<()> {
+    /// Write raw HTML of the document, returning whether what was written ends
+    /// with a newline
+    fn write_raw_markup(&mut self, html: &str) -> HtmlWriteResult<bool> {
+        // GFM tagfilter: disallowed tags in raw HTML are neutralized as text
         #[cfg(feature = "gfm")]
-        if self.options.enable_gfm
-            && self
-                .options
-                .gfm_disallowed_html_tags
-                .iter()
-                .any(|tag| tag.eq_ignore_ascii_case(&element.tag))
-        {
-            log::debug!("GFM: Textualizing disallowed HTML tag: <{}>", element.tag);
+        let filtered = self
+            .options
+            .enable_gfm
+            .then(|| self.options.gfm_disallowed_html_tags.filter(html));
+        #[cfg(feature = "gfm")]
+        let html = filtered.as_deref().unwrap_or(html);
+        let stripped = self
+            .options
+            .email
+            .as_ref()
+            .map(|email| email::strip_tags(email, html));
+        let html = stripped.as_deref().unwrap_or(html);
+        if self.untrusted {
+            self.text_internal(html)?;
+        } else {
+            self.raw_html_internal(html)?;
+        }
+        Ok(html.ends_with('\n'))
+    }
+
+    pub(crate) fn write_html_element_node(&mut self, element: &HtmlElement) -> HtmlWriteResult<()> {
+        #[cfg(feature = "gfm")]
+        if self.options.enable_gfm && self.options.gfm_disallowed_html_tags.contains(&element.tag) {
+            diagnostics::debug!("GFM: Textualizing disallowed HTML tag: <{}>", element.tag);
             self.textualize_full_element_node(element)?;
             return Ok(());
         }
 
+        if self.untrusted && !utils::is_untrusted_tag_allowed(&element.tag) {
+            self.reject_untrusted(HtmlWriteError::InvalidHtmlTag(element.tag.to_string()))?;
+            return self.textualize_full_element_node(element);
+        }
+
+        // Email clients don't support the tag: keep what it displays
+        if let Some(email) = &self.options.email {
+            if !email.allows_tag(&element.tag) {
+                if email::drops_with_content(&element.tag) {
+                    return Ok(());
+                }
+                for child in &element.children {
+                    self.write_node_internal(child)?;
+                }
+                return Ok(());
+            }
+        }
+
         if !utils::is_safe_tag_name(&element.tag) {
             if self.options.strict {
                 return Err(HtmlWriteError::InvalidHtmlTag(element.tag.to_string()));
             } else {
-                log::warn!(
+                diagnostics::warn!(
                     "Invalid HTML tag name '{}' encountered. Textualizing in non-strict mode.",
                     element.tag
                 );
@@ -491,23 +1418,18 @@ impl HtmlWriter {
         }
 
         self.start_tag_internal(&element.tag)?;
-        for attr in &element.attributes {
-            if !utils::is_safe_attribute_name(&attr.name) {
-                if self.options.strict {
-                    return Err(HtmlWriteError::InvalidHtmlAttribute(attr.name.to_string()));
-                } else {
-                    log::warn!("Invalid HTML attribute name '{}' in tag '{}'. Textualizing attribute in non-strict mode.", attr.name, element.tag);
-                    // Simple textualization of the attribute itself
-                    self.buffer.push(' ');
-                    self.buffer.push_str(&attr.name);
-                    self.buffer.push_str("=\"");
-                    self.buffer
-                        .push_str(html_escape::encode_text(&attr.value).as_ref()); // Attribute value should be escaped
-                    self.buffer.push('"');
-                    continue;
-                }
+        if self.options.deduplicate_attributes || self.options.sort_attributes {
+            let attributes = element.normalized_attributes(
+                self.options.deduplicate_attributes,
+                self.options.sort_attributes,
+            );
+            for attr in attributes {
+                self.write_element_attribute(&element.tag, attr)?;
+            }
+        } else {
+            for attr in &element.attributes {
+                self.write_element_attribute(&element.tag, attr)?;
             }
-            self.attribute_internal(&attr.name, &attr.value)?;
         }
 
         if element.self_closing {
@@ -525,6 +1447,56 @@ impl HtmlWriter {
         Ok(())
     }
 
+    fn write_element_attribute(&mut self, tag: &str, attr: &HtmlAttribute) -> HtmlWriteResult<()> {
+        if !utils::is_safe_attribute_name(&attr.name) {
+            if self.options.strict {
+                return Err(HtmlWriteError::InvalidHtmlAttribute(attr.name.to_string()));
+            } else {
+                diagnostics::warn!("Invalid HTML attribute name '{}' in tag '{}'. Textualizing attribute in non-strict mode.", attr.name, tag);
+                // Simple textualization of the attribute itself
+                self.buffer.push(' ');
+                self.buffer.push_str(&attr.name);
+                self.buffer.push_str("=\"");
+                self.buffer
+                    .push_str(html_escape::encode_quoted_attribute(&attr.value).as_ref()); // Attribute value should be escaped
+                self.buffer.push('"');
+                return Ok(());
+            }
+        }
+        if self.untrusted && !utils::is_untrusted_attribute_allowed(&attr.name) {
+            return self
+                .reject_untrusted(HtmlWriteError::InvalidHtmlAttribute(attr.name.to_string()));
+        }
+        if is_url_attribute(&attr.name) {
+            let url = self.checked_url(&attr.value)?;
+            return self.attribute_internal(&attr.name, url);
+        }
+        if let Some(email) = &self.options.email {
+            if tag.eq_ignore_ascii_case("img") && attr.name.eq_ignore_ascii_case("width") {
+                let width = email::capped_image_width(email, &attr.value);
+                return self.attribute_internal(&attr.name, &width);
+            }
+        }
+        self.attribute_internal(&attr.name, &attr.value)
+    }
+
+    /// `url` if its scheme is allowed by `allowed_url_schemes`
+    ///
+    /// Other URLs are an error in strict mode and replaced by an empty URL otherwise.
+    fn checked_url<'a>(&self, url: &'a str) -> HtmlWriteResult<&'a str> {
+        if self.options.allows_url(url) {
+            return Ok(url);
+        }
+        if self.options.strict {
+            return Err(HtmlWriteError::InvalidUrl(url.to_string()));
+        }
+        diagnostics::warn!(
+            "URL '{}' has a scheme that is not allowed. Writing an empty URL in non-strict mode.",
+            url
+        );
+        Ok("")
+    }
+
     fn textualize_full_element_node(&mut self, element: &HtmlElement) -> HtmlWriteResult<()> {
         self.text_internal("<")?;
         self.text_internal(&element.tag)?;
@@ -554,7 +1526,11 @@ impl HtmlWriter {
         // Soft line breaks in CommonMark are rendered as a newline in HTML source,
         // or a space if the line break was for wrapping.
         // Most browsers will treat a newline in HTML as a single space.
-        self.raw_html_internal("\n")
+        match self.options.soft_break_style {
+            SoftBreakStyle::Newline => self.raw_html_internal("\n"),
+            SoftBreakStyle::Space => self.raw_html_internal(" "),
+            SoftBreakStyle::HardBreak => self.write_hard_break_node(),
+        }
     }
 
     fn write_hard_break_node(&mut self) -> HtmlWriteResult<()> {
@@ -568,6 +1544,7 @@ impl HtmlWriter {
         title: &Option<EcoString>,
         content: &[Node],
     ) -> HtmlWriteResult<()> {
+        let url = self.checked_url(url)?;
         self.start_tag_internal("a")?;
         self.attribute_internal("href", url)?;
         if let Some(title_str) = title {
@@ -575,6 +1552,7 @@ impl HtmlWriter {
                 self.attribute_internal("title", title_str)?;
             }
         }
+        self.write_external_link_attributes(url)?;
         self.finish_tag_internal()?;
         for child in content {
             self.write_node_internal(child)?;
@@ -583,12 +1561,31 @@ impl HtmlWriter {
         Ok(())
     }
 
+    /// Add the `rel`/`target` attributes of the external link policy to an open `<a>` tag
+    fn write_external_link_attributes(&mut self, url: &str) -> HtmlWriteResult<()> {
+        let Some(policy) = &self.options.external_link_policy else {
+            return Ok(());
+        };
+        if !policy.is_external(url) {
+            return Ok(());
+        }
+        let (rel, target) = (policy.rel.clone(), policy.target.clone());
+        if let Some(rel) = rel {
+            self.attribute_internal("rel", &rel)?;
+        }
+        if let Some(target) = target {
+            self.attribute_internal("target", &target)?;
+        }
+        Ok(())
+    }
+
     fn write_image_node(
         &mut self,
         url: &str,
         title: &Option<EcoString>,
         alt: &[Node],
     ) -> HtmlWriteResult<()> {
+        let url = self.checked_url(url)?;
         self.start_tag_internal("img")?;
         self.attribute_internal("src", url)?;
         let mut alt_text_buffer = EcoString::new();
@@ -599,6 +1596,13 @@ impl HtmlWriter {
                 self.attribute_internal("title", title_str)?;
             }
         }
+        if let Some(loading) = self.options.img_loading {
+            self.attribute_internal("loading", loading.as_str())?;
+        }
+        if let Some(decoding) = self.options.img_decoding {
+            self.attribute_internal("decoding", decoding.as_str())?;
+        }
+        self.cap_email_image_width();
         self.finish_self_closing_tag_internal()?;
         Ok(())
     }
@@ -635,19 +1639,63 @@ impl HtmlWriter {
         Ok(())
     }
 
+    /// Write a single list item on its own, as the `<li>` element it renders to
+    /// inside a list
+    ///
+    /// The element doesn't depend on the kind of list (numbering comes from the
+    /// list's `start` attribute), so rendered items can be cached and joined
+    /// into any list later.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cmark_writer::ast::{ListItem, Node};
+    /// use cmark_writer::HtmlWriter;
+    ///
+    /// let item = ListItem::Unordered {
+    ///     content: vec![Node::Text("first".into())],
+    /// };
+    /// let mut writer = HtmlWriter::new();
+    /// writer.write_list_item_public(&item).unwrap();
+    /// assert_eq!(writer.into_string(), "<li>first</li>\n");
+    /// ```
+    pub fn write_list_item_public(&mut self, item: &ListItem) -> HtmlWriteResult<()> {
+        self.write_list_item_node(item)
+    }
+
     fn write_list_item_node(&mut self, item: &ListItem) -> HtmlWriteResult<()> {
+        self.write_list_item_node_with_marker(item, None)
+    }
+
+    fn write_list_item_node_with_marker(
+        &mut self,
+        item: &ListItem,
+        marker: Option<&str>,
+    ) -> HtmlWriteResult<()> {
         self.start_tag_internal("li")?;
+        if let Some(marker) = marker {
+            self.attribute_internal(
+                "style",
+                &format!("list-style-type: {}", css_string(&format!("{} ", marker))),
+            )?;
+        }
 
         #[cfg(feature = "gfm")]
-        if self.options.enable_gfm {
+        if self.supports(Capability::TaskList) {
             if let ListItem::Task { status, .. } = item {
-                let class_name = if *status == TaskListStatus::Checked {
-                    "task-list-item task-list-item-checked"
-                } else {
-                    "task-list-item" // GFM spec doesn't mandate a specific class for unchecked, just for checked.
-                                     // Some renderers use task-list-item-unchecked.
-                };
-                self.attribute_internal("class", class_name)?;
+                let classes = &self.options.task_list_classes;
+                let mut class_name = classes.item.clone().unwrap_or_default();
+                if *status == TaskListStatus::Checked {
+                    if let Some(checked) = &classes.checked_item {
+                        if !class_name.is_empty() {
+                            class_name.push(' ');
+                        }
+                        class_name.push_str(checked);
+                    }
+                }
+                if !class_name.is_empty() {
+                    self.attribute_internal("class", &class_name)?;
+                }
             }
         }
         self.finish_tag_internal()?; // Finish <li> tag
@@ -660,10 +1708,13 @@ impl HtmlWriter {
         };
 
         #[cfg(feature = "gfm")]
-        if self.options.enable_gfm {
+        if self.supports(Capability::TaskList) {
             if let ListItem::Task { status, .. } = item {
                 self.start_tag_internal("input")?;
                 self.attribute_internal("type", "checkbox")?;
+                if let Some(class_name) = self.options.task_list_classes.checkbox.clone() {
+                    self.attribute_internal("class", &class_name)?;
+                }
                 self.attribute_internal("disabled", "")?; // GFM task list items are disabled
                 if *status == TaskListStatus::Checked {
                     self.attribute_internal("checked", "")?;
@@ -678,15 +1729,42 @@ impl HtmlWriter {
         Ok(())
     }
 
+    /// Add the task list class to a list start tag if the list contains task items
+    fn write_task_list_class(&mut self, items: &[ListItem]) -> HtmlWriteResult<()> {
+        #[cfg(feature = "gfm")]
+        if self.supports(Capability::TaskList)
+            && items
+                .iter()
+                .any(|item| matches!(item, ListItem::Task { .. }))
+        {
+            if let Some(class_name) = self.options.task_list_classes.list.clone() {
+                self.attribute_internal("class", &class_name)?;
+            }
+        }
+        #[cfg(not(feature = "gfm"))]
+        let _ = items;
+        Ok(())
+    }
+
     fn write_ordered_list_node(&mut self, start: u32, items: &[ListItem]) -> HtmlWriteResult<()> {
+        let ordinals = self.options.ordinals.clone();
         self.start_tag_internal("ol")?;
         if start != 1 {
             self.attribute_internal("start", &start.to_string())?;
         }
+        if let Some(style) = ordinals.as_ref().and_then(OrdinalHook::counter_style) {
+            self.attribute_internal("style", &format!("list-style-type: {}", style))?;
+        }
+        self.write_task_list_class(items)?;
         self.finish_tag_internal()?;
         self.raw_html_internal("\n")?;
-        for item in items {
-            self.write_list_item_node(item)?;
+        for (index, item) in items.iter().enumerate() {
+            // Without a counter style, each item carries its marker as a string
+            let marker = ordinals
+                .as_ref()
+                .filter(|ordinals| ordinals.counter_style().is_none())
+                .map(|ordinals| ordinals.marker(start.saturating_add(index as u32)));
+            self.write_list_item_node_with_marker(item, marker.as_deref())?;
         }
         self.end_tag_internal("ol")?;
         self.raw_html_internal("\n")?;
@@ -695,6 +1773,7 @@ impl HtmlWriter {
 
     fn write_unordered_list_node(&mut self, items: &[ListItem]) -> HtmlWriteResult<()> {
         self.start_tag_internal("ul")?;
+        self.write_task_list_class(items)?;
         self.finish_tag_internal()?;
         self.raw_html_internal("\n")?;
         for item in items {
@@ -705,12 +1784,37 @@ impl HtmlWriter {
         Ok(())
     }
 
+    fn write_definition_list_node(&mut self, items: &[DefinitionItem]) -> HtmlWriteResult<()> {
+        self.start_tag_internal("dl")?;
+        self.finish_tag_internal()?;
+        self.raw_html_internal("\n")?;
+        for item in items {
+            self.start_tag_internal("dt")?;
+            self.finish_tag_internal()?;
+            for child in &item.term {
+                self.write_node_internal(child)?;
+            }
+            self.end_tag_internal("dt")?;
+            self.raw_html_internal("\n")?;
+            for definition in &item.definitions {
+                self.start_tag_internal("dd")?;
+                self.finish_tag_internal()?;
+                self.write_list_item_node_content(definition)?;
+                self.end_tag_internal("dd")?;
+                self.raw_html_internal("\n")?;
+            }
+        }
+        self.end_tag_internal("dl")?;
+        self.raw_html_internal("\n")?;
+        Ok(())
+    }
+
     #[cfg(feature = "gfm")]
     fn write_strikethrough_node(&mut self, children: &[Node]) -> HtmlWriteResult<()> {
-        if !self.options.enable_gfm {
+        if !self.supports(Capability::Strikethrough) {
             // If GFM is disabled (e.g. via a more granular gfm_strikethrough option if added),
             // render content as is. This case should ideally be guarded by options check.
-            log::warn!("Strikethrough node encountered but GFM (or GFM strikethrough) is not enabled. Rendering content as plain.");
+            diagnostics::warn!("Strikethrough node encountered but GFM (or GFM strikethrough) is not enabled. Rendering content as plain.");
             for child in children {
                 self.write_node_internal(child)?;
             }
@@ -725,13 +1829,23 @@ impl HtmlWriter {
         Ok(())
     }
 
-    fn write_table_node(
+    pub(crate) fn write_table_node(
         &mut self,
         headers: &[Node],
         #[cfg(feature = "gfm")] alignments: &[TableAlignment],
         rows: &[Vec<Node>],
     ) -> HtmlWriteResult<()> {
         self.start_tag_internal("table")?;
+        let email_width = match &self.options.email {
+            Some(email) => {
+                let table_width = email.table_width.clone();
+                self.attribute_internal("width", &table_width)?;
+                self.attribute_internal("style", "table-layout: fixed;")?;
+                // Header cells share the width of a fixed layout table
+                (!headers.is_empty()).then(|| format!("{}%", 100 / headers.len()))
+            }
+            None => None,
+        };
         self.finish_tag_internal()?;
         self.raw_html_internal("\n")?;
 
@@ -747,9 +1861,12 @@ impl HtmlWriter {
         #[cfg(feature = "gfm")]
         for (col_index, header_cell) in headers.iter().enumerate() {
             self.start_tag_internal("th")?;
+            if let Some(width) = &email_width {
+                self.attribute_internal("width", width)?;
+            }
 
             // Apply alignment styles if GFM is enabled
-            if self.options.enable_gfm && col_index < alignments.len() {
+            if self.supports(Capability::TableAlignment) && col_index < alignments.len() {
                 match alignments[col_index] {
                     TableAlignment::Left => {
                         self.attribute_internal("style", "text-align: left;")?;
@@ -773,6 +1890,9 @@ impl HtmlWriter {
         #[cfg(not(feature = "gfm"))]
         for header_cell in headers.iter() {
             self.start_tag_internal("th")?;
+            if let Some(width) = &email_width {
+                self.attribute_internal("width", width)?;
+            }
             self.finish_tag_internal()?;
             self.write_node_internal(header_cell)?;
             self.end_tag_internal("th")?;
@@ -789,8 +1909,11 @@ impl HtmlWriter {
         self.raw_html_internal("\n")?;
 
         // Process each row
-        for row_cells in rows {
+        for (row_index, row_cells) in rows.iter().enumerate() {
             self.start_tag_internal("tr")?;
+            if let Some(hook) = self.options.table_row_attributes.clone() {
+                self.write_row_attributes(&hook.attributes(row_index, row_cells))?;
+            }
             self.finish_tag_internal()?;
             self.raw_html_internal("\n")?;
 
@@ -800,7 +1923,7 @@ impl HtmlWriter {
                 self.start_tag_internal("td")?;
 
                 // Apply alignment styles if GFM is enabled
-                if self.options.enable_gfm && col_index < alignments.len() {
+                if self.supports(Capability::TableAlignment) && col_index < alignments.len() {
                     match alignments[col_index] {
                         TableAlignment::Left => {
                             self.attribute_internal("style", "text-align: left;")?;
@@ -842,30 +1965,51 @@ impl HtmlWriter {
         Ok(())
     }
 
+    fn write_row_attributes(&mut self, attributes: &RowAttributes) -> HtmlWriteResult<()> {
+        if let Some(class) = &attributes.class {
+            self.attribute_internal("class", class)?;
+        }
+        for (name, value) in &attributes.data {
+            let name = format!("data-{}", name);
+            if !utils::is_safe_attribute_name(&name) {
+                if self.options.strict {
+                    return Err(HtmlWriteError::InvalidHtmlAttribute(name));
+                }
+                diagnostics::warn!(
+                    "Invalid table row attribute name '{}'. Skipping it in non-strict mode.",
+                    name
+                );
+                continue;
+            }
+            self.attribute_internal(&name, value)?;
+        }
+        Ok(())
+    }
+
     fn write_autolink_node(&mut self, url: &str, is_email: bool) -> HtmlWriteResult<()> {
+        let (text, href) = autolink_parts(url, is_email, &self.options.autolink_default_scheme);
+        let href = self.checked_url(&href)?;
         self.start_tag_internal("a")?;
-        let href = if is_email && !url.starts_with("mailto:") {
-            format!("mailto:{}", url)
-        } else {
-            url.to_string()
-        };
-        self.attribute_internal("href", &href)?;
+        self.attribute_internal("href", href)?;
+        self.write_external_link_attributes(href)?;
         self.finish_tag_internal()?;
-        self.text_internal(url)?;
+        self.text_internal(&text)?;
         self.end_tag_internal("a")?;
         Ok(())
     }
 
     #[cfg(feature = "gfm")]
     fn write_extended_autolink_node(&mut self, url: &str) -> HtmlWriteResult<()> {
-        if !self.options.enable_gfm {
+        if !self.supports(Capability::ExtendedAutolink) {
             // Or a more specific gfm_autolinks option
-            log::warn!("ExtendedAutolink node encountered but GFM (or GFM autolinks) is not enabled. Rendering as plain text.");
+            diagnostics::warn!("ExtendedAutolink node encountered but GFM (or GFM autolinks) is not enabled. Rendering as plain text.");
             self.text_internal(url)?;
             return Ok(());
         }
+        let href = self.checked_url(url)?;
         self.start_tag_internal("a")?;
-        self.attribute_internal("href", url)?; // Assumes URL is already a valid href
+        self.attribute_internal("href", href)?;
+        self.write_external_link_attributes(href)?;
         self.finish_tag_internal()?;
         self.text_internal(url)?;
         self.end_tag_internal("a")?;
@@ -884,7 +2028,7 @@ impl HtmlWriter {
             )));
         }
 
-        log::warn!(
+        diagnostics::warn!(
             "Unresolved reference link for label '{}'. Rendering as plain text.",
             label
         );
@@ -950,9 +2094,14 @@ fn render_nodes_to_plain_text(
             Node::Strikethrough(children) => {
                 render_nodes_to_plain_text(children, buffer, _options);
             }
-            Node::Link { content, .. } => render_nodes_to_plain_text(content, buffer, _options),
+            Node::Link { content, .. }
+            | Node::CrossRef {
+                text: Some(content),
+                ..
+            } => render_nodes_to_plain_text(content, buffer, _options),
+            Node::CrossRef { target, .. } => buffer.push_str(target),
             Node::Image { alt, .. } => render_nodes_to_plain_text(alt, buffer, _options), // Recursively get alt text
-            Node::InlineCode(code) => buffer.push_str(code),
+            Node::InlineCode(code) | Node::Math { content: code, .. } => buffer.push_str(code),
             Node::SoftBreak | Node::HardBreak => buffer.push(' '), // Represent breaks as spaces in alt text
             Node::HtmlElement(element) => {
                 // Strip HTML tags, but render their text content
@@ -969,6 +2118,9 @@ fn render_nodes_to_plain_text(
                 render_nodes_to_plain_text(children, buffer, _options);
                 buffer.push(' '); // Add a space after block content for readability
             }
+            Node::Shared(node) => {
+                render_nodes_to_plain_text(std::slice::from_ref(&**node), buffer, _options)
+            }
             _ => {} // Ignore other node types (e.g., ThematicBreak, Table, List) for plain text rendering.
         }
     }
@@ -980,3 +2132,32 @@ fn render_nodes_to_plain_text_string(nodes: &[Node], options: &HtmlWriterOptions
     render_nodes_to_plain_text(nodes, &mut s, options);
     s
 }
+
+/// Quote `text` as a CSS string
+fn css_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        if matches!(c, '\'' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Render a custom node with its own `html_render`
+fn render_custom_node(node: &dyn CustomNode, writer: &mut HtmlWriter) -> HtmlWriteResult<()> {
+    node.html_render(writer).map_err(|e| match e {
+        WriteError::HtmlRenderingError(html_err) => html_err,
+        other => HtmlWriteError::CustomNodeError(other.to_string()),
+    })
+}
+
+/// Whether an `HtmlElement` attribute holds a URL that browsers may navigate to or load
+fn is_url_attribute(name: &str) -> bool {
+    ["href", "src", "action", "formaction"]
+        .iter()
+        .any(|attribute| name.eq_ignore_ascii_case(attribute))
+}
@@ -2,12 +2,22 @@
 //!
 //! This module provides functionality to convert AST nodes to various formats.
 
+pub mod assemble;
+pub mod bidi;
 pub mod cmark;
 pub mod context;
+pub mod filters;
+pub mod multi;
+pub mod ordinals;
+pub mod placeholders;
 pub mod processors;
 
-pub use self::cmark::CommonMarkWriter;
+pub use self::bidi::TextDirection;
+pub use self::cmark::{CommonMarkStreamWriter, CommonMarkWriter};
 pub use self::context::{NewlineContext, NewlineStrategy, RenderingMode};
+pub use self::multi::{render_multi, MultiResult, OutputFormat, OutputTarget};
+pub use self::ordinals::{Numerals, OrdinalFormatter, OrdinalHook};
+pub use self::placeholders::PlaceholderHook;
 
 /// HTML specific modules are now grouped under writer::html
 pub mod html;
@@ -0,0 +1,191 @@
+//! Rendering one document to several output formats at once.
+
+use crate::ast::{Node, NodePath};
+use crate::error::{WriteError, WriteResult};
+use crate::options::WriterOptions;
+use crate::writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions};
+use ecow::EcoString;
+
+/// An output requested from [`render_multi`]
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum OutputTarget {
+    /// CommonMark rendered with the given options
+    CommonMark(WriterOptions),
+    /// HTML rendered with the given options
+    Html(HtmlWriterOptions),
+}
+
+impl OutputTarget {
+    /// The format of the target
+    pub fn format(&self) -> OutputFormat {
+        match self {
+            OutputTarget::CommonMark(_) => OutputFormat::CommonMark,
+            OutputTarget::Html(_) => OutputFormat::Html,
+        }
+    }
+}
+
+/// Output format of an [`OutputTarget`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// CommonMark
+    CommonMark,
+    /// HTML
+    Html,
+}
+
+/// Outputs of [`render_multi`], one per target and in the order of the targets
+#[derive(Debug)]
+pub struct MultiResult {
+    /// Format and rendered output (or error) of each target
+    pub outputs: Vec<(OutputFormat, WriteResult<EcoString>)>,
+}
+
+impl MultiResult {
+    /// The output of the first CommonMark target
+    pub fn commonmark(&self) -> Option<&WriteResult<EcoString>> {
+        self.first(OutputFormat::CommonMark)
+    }
+
+    /// The output of the first HTML target
+    pub fn html(&self) -> Option<&WriteResult<EcoString>> {
+        self.first(OutputFormat::Html)
+    }
+
+    /// Whether every target rendered without error
+    pub fn is_ok(&self) -> bool {
+        self.outputs.iter().all(|(_, output)| output.is_ok())
+    }
+
+    fn first(&self, format: OutputFormat) -> Option<&WriteResult<EcoString>> {
+        self.outputs
+            .iter()
+            .find(|(output_format, _)| *output_format == format)
+            .map(|(_, output)| output)
+    }
+}
+
+/// A writer for one target, dropped from the traversal after its first error
+enum Sink {
+    CommonMark(CommonMarkWriter),
+    Html(Box<HtmlWriter>),
+    Failed(WriteError),
+}
+
+impl Sink {
+    /// Write a block, at `path` when it is a child of the document
+    fn write_block(&mut self, path: Option<&NodePath>, block: &Node) {
+        let result = match self {
+            Sink::CommonMark(writer) => writer.append(block),
+            Sink::Html(writer) => match path {
+                Some(path) => writer.write_top_level_block(path, block),
+                None => writer.write_node_internal(block),
+            }
+            .map_err(Into::into),
+            Sink::Failed(_) => return,
+        };
+        if let Err(error) = result {
+            *self = Sink::Failed(error);
+        }
+    }
+
+    /// Prepare for the blocks of `root`, `blocks` if it is a document
+    fn prepare_document(&mut self, root: &Node, blocks: Option<&[Node]>) {
+        match self {
+            Sink::CommonMark(writer) => writer.prepare_document(root),
+            Sink::Html(writer) => {
+                writer.prepare_document(root);
+                if let Some(blocks) = blocks {
+                    writer.start_footnotes(blocks);
+                }
+            }
+            Sink::Failed(_) => {}
+        }
+    }
+
+    /// Finish the output, after the footnote section of the document
+    /// `blocks` if there is one
+    fn finish(self, blocks: Option<&[Node]>) -> WriteResult<EcoString> {
+        match self {
+            Sink::CommonMark(writer) => Ok(writer.into_string()),
+            Sink::Html(mut writer) => {
+                if let Some(blocks) = blocks {
+                    writer.finish_footnotes(blocks)?;
+                }
+                Ok(writer.into_string())
+            }
+            Sink::Failed(error) => Err(error),
+        }
+    }
+}
+
+/// Render a node to several outputs in a single walk over its blocks
+///
+/// The top-level blocks of a document are visited once, and each block is
+/// handed to every target's writer while it's still hot in cache, instead of
+/// walking the whole document once per format. Each output is the same as
+/// rendering the node with `to_commonmark` or `to_html` on its own. A target
+/// that fails stops receiving blocks; the other targets are still rendered.
+///
+/// # Example
+///
+/// ```
+/// use cmark_writer::ast::Node;
+/// use cmark_writer::writer::multi::{render_multi, OutputTarget};
+/// use cmark_writer::{HtmlWriterOptions, WriterOptions};
+///
+/// let doc = Node::Document(vec![
+///     Node::heading(1, vec![Node::Text("Report".into())]),
+///     Node::Paragraph(vec![Node::Text("All good.".into())]),
+/// ]);
+/// let result = render_multi(
+///     &doc,
+///     &[
+///         OutputTarget::CommonMark(WriterOptions::default()),
+///         OutputTarget::Html(HtmlWriterOptions::default()),
+///     ],
+/// );
+/// assert_eq!(result.commonmark().unwrap().as_ref().unwrap(), "# Report\n\nAll good.\n");
+/// assert_eq!(
+///     result.html().unwrap().as_ref().unwrap(),
+///     "<h1>Report</h1>\n<p>All good.</p>\n"
+/// );
+/// ```
+pub fn render_multi(node: &Node, targets: &[OutputTarget]) -> MultiResult {
+    let mut sinks: Vec<Sink> = targets
+        .iter()
+        .map(|target| match target {
+            OutputTarget::CommonMark(options) => {
+                Sink::CommonMark(CommonMarkWriter::with_options(options.clone()))
+            }
+            OutputTarget::Html(options) => {
+                Sink::Html(Box::new(HtmlWriter::with_options(options.clone())))
+            }
+        })
+        .collect();
+
+    let (blocks, in_document) = match node.resolve_shared() {
+        Node::Document(children) => (children.as_slice(), true),
+        node => (std::slice::from_ref(node), false),
+    };
+    let document = in_document.then_some(blocks);
+    for sink in &mut sinks {
+        sink.prepare_document(node, document);
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        let path = in_document.then(|| NodePath::root().child(i));
+        let block = block.resolve_shared();
+        for sink in &mut sinks {
+            sink.write_block(path.as_ref(), block);
+        }
+    }
+
+    MultiResult {
+        outputs: targets
+            .iter()
+            .zip(sinks)
+            .map(|(target, sink)| (target.format(), sink.finish(document)))
+            .collect(),
+    }
+}
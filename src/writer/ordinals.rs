@@ -0,0 +1,148 @@
+//! Localized markers for ordered lists.
+
+use ecow::EcoString;
+use std::fmt;
+use std::sync::Arc;
+use unicode_width::UnicodeWidthStr;
+
+/// Renders the number of an ordered list item in a given numbering system
+///
+/// Only the HTML writer uses localized markers. CommonMark parsers recognize
+/// ASCII digits followed by `.` or `)` only, so the CommonMark writer always
+/// falls back to ASCII numbers.
+pub trait OrdinalFormatter: Send + Sync {
+    /// The digits of `number`, without the delimiter
+    fn format(&self, number: u32) -> EcoString;
+
+    /// Written after the digits, `.` by default
+    fn delimiter(&self) -> &str {
+        "."
+    }
+
+    /// Name of a CSS counter style producing the same markers (e.g.
+    /// `arabic-indic`), if there is one
+    ///
+    /// With a counter style, the browser numbers the items itself. Without
+    /// one, each item gets its marker as a string.
+    fn counter_style(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl<F> OrdinalFormatter for F
+where
+    F: Fn(u32) -> EcoString + Send + Sync,
+{
+    fn format(&self, number: u32) -> EcoString {
+        self(number)
+    }
+}
+
+/// Built-in numbering systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Numerals {
+    /// `1.`, `2.`, ...
+    #[default]
+    Ascii,
+    /// `١.`, `٢.`, ..., using the `arabic-indic` counter style
+    ArabicIndic,
+    /// `１．`, `２．`, ..., as used in CJK text
+    FullWidth,
+}
+
+impl Numerals {
+    fn zero(self) -> char {
+        match self {
+            Numerals::Ascii => '0',
+            Numerals::ArabicIndic => '\u{0660}',
+            Numerals::FullWidth => '\u{FF10}',
+        }
+    }
+}
+
+impl OrdinalFormatter for Numerals {
+    fn format(&self, number: u32) -> EcoString {
+        let zero = self.zero() as u32;
+        number
+            .to_string()
+            .chars()
+            .map(|digit| {
+                let offset = digit as u32 - '0' as u32;
+                char::from_u32(zero + offset).unwrap_or(digit)
+            })
+            .collect()
+    }
+
+    fn delimiter(&self) -> &str {
+        match self {
+            Numerals::FullWidth => "\u{FF0E}",
+            _ => ".",
+        }
+    }
+
+    fn counter_style(&self) -> Option<&str> {
+        match self {
+            Numerals::Ascii => Some("decimal"),
+            Numerals::ArabicIndic => Some("arabic-indic"),
+            // No predefined counter style uses full-width digits
+            Numerals::FullWidth => None,
+        }
+    }
+}
+
+/// A shareable [`OrdinalFormatter`] that can be stored in writer options
+///
+/// Set on [`HtmlWriterOptions::ordinals`](crate::HtmlWriterOptions::ordinals)
+/// to localize the markers of ordered lists.
+///
+/// # Example
+///
+/// ```rust
+/// use cmark_writer::writer::ordinals::{Numerals, OrdinalHook};
+///
+/// let hook = OrdinalHook::new(Numerals::FullWidth);
+/// assert_eq!(hook.marker(12), "１２．");
+/// // Full-width digits take two columns each
+/// assert_eq!(hook.marker_width(12), 6);
+/// ```
+#[derive(Clone)]
+pub struct OrdinalHook(Arc<dyn OrdinalFormatter>);
+
+impl OrdinalHook {
+    /// Wrap a formatter so it can be shared between writers
+    pub fn new<F: OrdinalFormatter + 'static>(formatter: F) -> Self {
+        Self(Arc::new(formatter))
+    }
+
+    /// The marker of item `number`, digits and delimiter
+    pub fn marker(&self, number: u32) -> EcoString {
+        let mut marker = self.0.format(number);
+        marker.push_str(self.0.delimiter());
+        marker
+    }
+
+    /// Display width of the marker of item `number`, in columns
+    pub fn marker_width(&self, number: u32) -> usize {
+        self.marker(number).width()
+    }
+
+    /// See [`OrdinalFormatter::counter_style`]
+    pub fn counter_style(&self) -> Option<&str> {
+        self.0.counter_style()
+    }
+}
+
+impl fmt::Debug for OrdinalHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OrdinalHook(..)")
+    }
+}
+
+/// Hooks compare equal only if they share the same formatter
+impl PartialEq for OrdinalHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for OrdinalHook {}
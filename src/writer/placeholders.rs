@@ -0,0 +1,102 @@
+//! Render-time substitution of `{{key}}` placeholders in text.
+
+use crate::traits::PlaceholderResolver;
+use ecow::EcoString;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+/// A shareable [`PlaceholderResolver`] that can be stored in writer options
+///
+/// When set on [`WriterOptions`](crate::WriterOptions) or
+/// [`HtmlWriterOptions`](crate::HtmlWriterOptions), every `{{key}}` token in a
+/// `Text` node is replaced with the resolved value before escaping is applied.
+/// Whitespace around the key is ignored, and tokens the resolver doesn't know
+/// are written unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use cmark_writer::options::WriterOptionsBuilder;
+/// use cmark_writer::writer::PlaceholderHook;
+/// use cmark_writer::{CommonMarkWriter, Node, ToCommonMark};
+/// use std::collections::HashMap;
+///
+/// let skeleton = Node::Paragraph(vec![Node::Text("Hello, {{ name }}!".into())]);
+///
+/// let values = HashMap::from([("name", "World")]);
+/// let options = WriterOptionsBuilder::new()
+///     .placeholder_resolver(PlaceholderHook::new(values))
+///     .build();
+///
+/// let mut writer = CommonMarkWriter::with_options(options);
+/// skeleton.to_commonmark(&mut writer).unwrap();
+/// assert_eq!(writer.into_string(), "Hello, World!\n");
+/// ```
+#[derive(Clone)]
+pub struct PlaceholderHook(Arc<dyn PlaceholderResolver>);
+
+impl PlaceholderHook {
+    /// Wrap a resolver so it can be shared between writers
+    pub fn new<R: PlaceholderResolver + 'static>(resolver: R) -> Self {
+        Self(Arc::new(resolver))
+    }
+
+    /// Replace all resolvable `{{key}}` tokens in `text`
+    ///
+    /// Returns the input unchanged (without allocating) if it contains no placeholders.
+    pub fn substitute<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let Some(first) = text.find("{{") else {
+            return Cow::Borrowed(text);
+        };
+
+        let mut output = EcoString::new();
+        output.push_str(&text[..first]);
+        let mut rest = &text[first..];
+        let mut replaced = false;
+
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let token = &rest[start..];
+            match token[2..].find("}}") {
+                Some(end) => {
+                    let key = token[2..2 + end].trim();
+                    match self.0.resolve(key) {
+                        Some(value) => {
+                            output.push_str(&value);
+                            replaced = true;
+                        }
+                        None => output.push_str(&token[..end + 4]),
+                    }
+                    rest = &token[end + 4..];
+                }
+                None => {
+                    output.push_str(token);
+                    rest = "";
+                }
+            }
+        }
+        output.push_str(rest);
+
+        if replaced {
+            Cow::Owned(output.into())
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+}
+
+impl fmt::Debug for PlaceholderHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PlaceholderHook(..)")
+    }
+}
+
+/// Hooks compare equal only if they share the same resolver
+impl PartialEq for PlaceholderHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for PlaceholderHook {}
@@ -72,6 +72,7 @@ impl Default for EnhancedBlockProcessor {
 
 impl NodeProcessor for EnhancedBlockProcessor {
     fn can_process(&self, node: &Node) -> bool {
+        let node = node.resolve_shared();
         matches!(
             node,
             Node::Document(_)
@@ -81,10 +82,13 @@ impl NodeProcessor for EnhancedBlockProcessor {
                 | Node::CodeBlock { .. }
                 | Node::UnorderedList(_)
                 | Node::OrderedList { .. }
+                | Node::DefinitionList(_)
                 | Node::ThematicBreak
+                | Node::ReadMoreMarker
                 | Node::Table { .. }
                 | Node::HtmlBlock(_)
                 | Node::LinkReferenceDefinition { .. }
+                | Node::FootnoteDefinition { .. }
         ) || matches!(node, Node::Custom(custom) if custom.is_block())
     }
 
@@ -93,6 +97,7 @@ impl NodeProcessor for EnhancedBlockProcessor {
         writer: &mut crate::writer::CommonMarkWriter,
         node: &Node,
     ) -> WriteResult<()> {
+        let node = node.resolve_shared();
         match node {
             Node::Document(children) => {
                 for (i, child) in children.iter().enumerate() {
@@ -107,7 +112,8 @@ impl NodeProcessor for EnhancedBlockProcessor {
                 level,
                 content,
                 heading_type,
-            } => writer.write_heading(*level, content, heading_type),
+                custom_id,
+            } => writer.write_heading_with_id(*level, content, heading_type, custom_id.as_deref()),
             Node::Paragraph(content) => writer.write_paragraph(content),
             Node::BlockQuote(content) => writer.write_blockquote(content),
             Node::CodeBlock {
@@ -117,7 +123,9 @@ impl NodeProcessor for EnhancedBlockProcessor {
             } => writer.write_code_block(language, content, block_type),
             Node::UnorderedList(items) => writer.write_unordered_list(items),
             Node::OrderedList { start, items } => writer.write_ordered_list(items, *start, true),
+            Node::DefinitionList(items) => writer.write_definition_list(items),
             Node::ThematicBreak => writer.write_thematic_break(),
+            Node::ReadMoreMarker => writer.write_html_block(crate::ops::READ_MORE_MARKER),
             #[cfg(feature = "gfm")]
             Node::Table {
                 headers,
@@ -132,9 +140,11 @@ impl NodeProcessor for EnhancedBlockProcessor {
                 destination,
                 title,
             } => writer.write_link_reference_definition(label, destination, title),
+            Node::FootnoteDefinition { label, content } => {
+                writer.write_footnote_definition(label, content)
+            }
             Node::Custom(custom_node) if custom_node.is_block() => {
-                // CustomNode implements CommonMarkRenderable
-                custom_node.render_commonmark(writer)
+                writer.write_custom_node(custom_node)
             }
             _ => Err(WriteError::UnsupportedNodeType),
         }?;
@@ -147,6 +157,7 @@ impl NodeProcessor for EnhancedBlockProcessor {
     }
 
     fn process_html(&self, writer: &mut crate::writer::HtmlWriter, node: &Node) -> WriteResult<()> {
+        let node = node.resolve_shared();
         writer.write_node_internal(node).map_err(WriteError::from)
     }
 
@@ -201,16 +212,22 @@ impl Default for EnhancedInlineProcessor {
 
 impl NodeProcessor for EnhancedInlineProcessor {
     fn can_process(&self, node: &Node) -> bool {
+        let node = node.resolve_shared();
         matches!(
             node,
             Node::Text(_)
+                | Node::NumberRef(..)
                 | Node::Emphasis(_)
                 | Node::Strong(_)
                 | Node::InlineCode(_)
+                | Node::Math { .. }
                 | Node::Link { .. }
+                | Node::CrossRef { .. }
                 | Node::Image { .. }
                 | Node::Autolink { .. }
                 | Node::ReferenceLink { .. }
+                | Node::FootnoteReference(_)
+                | Node::HtmlInline(_)
                 | Node::HtmlElement(_)
                 | Node::SoftBreak
                 | Node::HardBreak
@@ -224,38 +241,44 @@ impl NodeProcessor for EnhancedInlineProcessor {
         writer: &mut crate::writer::CommonMarkWriter,
         node: &Node,
     ) -> WriteResult<()> {
+        let node = node.resolve_shared();
         if self.config.strict_validation {
             self.validate_inline_content(node)?;
         }
 
         match node {
             Node::Text(content) => writer.write_text_content(content),
+            Node::NumberRef(kind, id) => writer.write_number_ref(kind, id),
             Node::Emphasis(content) => writer.write_emphasis(content),
             Node::Strong(content) => writer.write_strong(content),
             #[cfg(feature = "gfm")]
             Node::Strikethrough(content) => writer.write_strikethrough(content),
             Node::InlineCode(content) => writer.write_code_content(content),
+            Node::Math { display, content } => writer.write_math(*display, content),
             Node::Link {
                 url,
                 title,
                 content,
             } => writer.write_link(url, title, content),
+            Node::CrossRef { target, text } => writer.write_cross_ref(target, text.as_deref()),
             Node::Image { url, title, alt } => writer.write_image(url, title, alt),
             Node::Autolink { url, is_email } => writer.write_autolink(url, *is_email),
             #[cfg(feature = "gfm")]
             Node::ExtendedAutolink(url) => writer.write_extended_autolink(url),
             Node::ReferenceLink { label, content } => writer.write_reference_link(label, content),
+            Node::FootnoteReference(label) => writer.write_footnote_reference(label),
             Node::HtmlElement(element) => writer.write_html_element(element),
             Node::SoftBreak => writer.write_soft_break(),
             Node::HardBreak => writer.write_hard_break(),
             Node::Custom(custom_node) if !custom_node.is_block() => {
-                custom_node.render_commonmark(writer)
+                writer.write_custom_node(custom_node)
             }
             _ => Err(WriteError::UnsupportedNodeType),
         }
     }
 
     fn process_html(&self, writer: &mut crate::writer::HtmlWriter, node: &Node) -> WriteResult<()> {
+        let node = node.resolve_shared();
         writer.write_node_internal(node).map_err(WriteError::from)
     }
 
@@ -271,26 +294,24 @@ impl InlineNodeProcessor for EnhancedInlineProcessor {
             fn check_for_newlines(node: &Node) -> Result<(), String> {
                 match node {
                     // Direct text content nodes
-                    Node::Text(content) => {
-                        if content.contains('\n') {
-                            return Err(format!("Text node: {}", content));
-                        }
+                    Node::Text(content) if content.contains('\n') => {
+                        return Err(format!("Text node: {}", content));
                     }
-                    Node::InlineCode(content) => {
-                        if content.contains('\n') {
-                            return Err(format!("Inline code: {}", content));
-                        }
+                    Node::InlineCode(content) if content.contains('\n') => {
+                        return Err(format!("Inline code: {}", content));
                     }
-                    Node::Autolink { url, .. } => {
-                        if url.contains('\n') {
-                            return Err(format!("Autolink URL: {}", url));
-                        }
+                    Node::Math {
+                        display: false,
+                        content,
+                    } if content.contains('\n') => {
+                        return Err(format!("Math: {}", content));
+                    }
+                    Node::Autolink { url, .. } if url.contains('\n') => {
+                        return Err(format!("Autolink URL: {}", url));
                     }
                     #[cfg(feature = "gfm")]
-                    Node::ExtendedAutolink(url) => {
-                        if url.contains('\n') {
-                            return Err(format!("Extended autolink URL: {}", url));
-                        }
+                    Node::ExtendedAutolink(url) if url.contains('\n') => {
+                        return Err(format!("Extended autolink URL: {}", url));
                     }
 
                     // Nodes with child content that needs recursive checking
@@ -331,6 +352,14 @@ impl InlineNodeProcessor for EnhancedInlineProcessor {
                             check_for_newlines(child)?;
                         }
                     }
+                    Node::CrossRef { target, text } => {
+                        if target.contains('\n') {
+                            return Err(format!("Cross-reference target: {}", target));
+                        }
+                        for child in text.iter().flatten() {
+                            check_for_newlines(child)?;
+                        }
+                    }
                     Node::Image {
                         alt, url, title, ..
                     } => {
@@ -394,6 +423,7 @@ pub struct CustomNodeProcessor;
 
 impl NodeProcessor for CustomNodeProcessor {
     fn can_process(&self, node: &Node) -> bool {
+        let node = node.resolve_shared();
         matches!(node, Node::Custom(_))
     }
 
@@ -402,21 +432,15 @@ impl NodeProcessor for CustomNodeProcessor {
         writer: &mut crate::writer::CommonMarkWriter,
         node: &Node,
     ) -> WriteResult<()> {
+        let node = node.resolve_shared();
         match node {
-            Node::Custom(custom_node) => {
-                custom_node.render_commonmark(writer)?;
-
-                if custom_node.is_block() {
-                    // Newline handling is now context-aware and automatic
-                }
-
-                Ok(())
-            }
+            Node::Custom(custom_node) => writer.write_custom_node(custom_node),
             _ => Err(WriteError::UnsupportedNodeType),
         }
     }
 
     fn process_html(&self, writer: &mut crate::writer::HtmlWriter, node: &Node) -> WriteResult<()> {
+        let node = node.resolve_shared();
         match node {
             Node::Custom(custom_node) => {
                 // Use the html_render method from CustomNode trait
@@ -111,6 +111,7 @@ fn test_html_element_fluent_api() {
                 level: 1,
                 content: vec![Node::Text("Title".into())],
                 heading_type: HeadingType::Atx,
+                custom_id: None,
             },
             Node::Paragraph(vec![Node::Text("Content paragraph.".into())]),
         ])
@@ -155,3 +156,104 @@ fn test_html_attribute_clone_and_partial_eq() {
 
     assert_ne!(attr1, attr3);
 }
+
+#[test]
+fn test_html_element_get_attribute() {
+    let element = HtmlElement::new("div")
+        .with_attribute("class", "a")
+        .with_attribute("ID", "main")
+        .with_attribute("class", "b");
+
+    assert_eq!(element.get_attribute("id"), Some("main"));
+    // Later duplicates win
+    assert_eq!(element.get_attribute("class"), Some("b"));
+    assert_eq!(element.get_attribute("title"), None);
+}
+
+#[test]
+fn test_html_element_set_attribute() {
+    let mut element = HtmlElement::new("div")
+        .with_attribute("class", "a")
+        .with_attribute("id", "main")
+        .with_attribute("CLASS", "b");
+
+    element.set_attribute("class", "c");
+    element.set_attribute("title", "Hello");
+
+    let attributes: Vec<_> = element
+        .attributes
+        .iter()
+        .map(|attr| (attr.name.as_str(), attr.value.as_str()))
+        .collect();
+    assert_eq!(
+        attributes,
+        vec![("class", "c"), ("id", "main"), ("title", "Hello")]
+    );
+}
+
+#[test]
+fn test_html_element_normalized_attributes() {
+    let element = HtmlElement::new("div")
+        .with_attribute("id", "main")
+        .with_attribute("class", "a")
+        .with_attribute("data-x", "1")
+        .with_attribute("class", "b");
+
+    let names_values = |attrs: Vec<&HtmlAttribute>| -> Vec<(String, String)> {
+        attrs
+            .into_iter()
+            .map(|attr| (attr.name.to_string(), attr.value.to_string()))
+            .collect()
+    };
+
+    assert_eq!(element.normalized_attributes(false, false).len(), 4);
+    assert_eq!(
+        names_values(element.normalized_attributes(true, false)),
+        vec![
+            ("id".into(), "main".into()),
+            ("class".into(), "b".into()),
+            ("data-x".into(), "1".into()),
+        ]
+    );
+    assert_eq!(
+        names_values(element.normalized_attributes(true, true)),
+        vec![
+            ("class".into(), "b".into()),
+            ("data-x".into(), "1".into()),
+            ("id".into(), "main".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_html_element_class_and_style_builders() {
+    let element = HtmlElement::new("div")
+        .with_attribute("class", "base")
+        .class("a")
+        .class("b base")
+        .style("color", "red")
+        .style("margin", "0")
+        .style("COLOR", "blue")
+        .child(Node::Text("Hello".into()))
+        .child(Node::Text("!".into()));
+
+    assert_eq!(element.get_attribute("class"), Some("base a b"));
+    assert_eq!(
+        element.get_attribute("style"),
+        Some("color: blue; margin: 0;")
+    );
+    assert_eq!(element.attributes.len(), 2);
+    assert_eq!(element.children.len(), 2);
+}
+
+#[test]
+fn test_html_element_style_merges_existing_attribute() {
+    let element = HtmlElement::new("span")
+        .with_attribute("style", "text-align: left;font-weight:bold")
+        .style("text-align", "center");
+
+    assert_eq!(
+        element.get_attribute("style"),
+        Some("text-align: center; font-weight: bold;")
+    );
+}
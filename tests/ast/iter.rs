@@ -0,0 +1,140 @@
+//! Tests for the block and inline iterators
+
+use cmark_writer::ast::tables::simple_table;
+use cmark_writer::ast::{DefinitionItem, ListItem, Node, NodePath};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn sample() -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![text("Title")]),
+        Node::UnorderedList(vec![
+            ListItem::Unordered {
+                content: vec![Node::Paragraph(vec![text("one")])],
+            },
+            ListItem::Unordered {
+                content: vec![
+                    Node::Paragraph(vec![text("two")]),
+                    Node::BlockQuote(vec![Node::Paragraph(vec![text("quoted")])]),
+                ],
+            },
+        ]),
+        Node::shared(Node::Paragraph(vec![Node::Emphasis(vec![text("shared")])])),
+    ])
+}
+
+#[test]
+fn blocks_in_document_order() {
+    let doc = sample();
+    let paths: Vec<String> = doc.blocks().map(|(path, _)| path.to_string()).collect();
+
+    assert_eq!(
+        paths,
+        [
+            "/0",
+            "/1",
+            "/1/items[0]/0",
+            "/1/items[1]/0",
+            "/1/items[1]/1",
+            "/1/items[1]/1/0",
+            "/2",
+        ]
+    );
+}
+
+#[test]
+fn paths_address_the_yielded_nodes() {
+    let doc = sample();
+
+    for (path, node) in doc.blocks().chain(doc.inlines()) {
+        assert_eq!(doc.get(&path).map(Node::resolve_shared), Some(node));
+    }
+}
+
+#[test]
+fn inlines_follow_their_container() {
+    let doc = sample();
+    let inlines: Vec<(String, &Node)> = doc
+        .inlines()
+        .map(|(path, node)| (path.to_string(), node))
+        .collect();
+
+    assert_eq!(inlines.len(), 6);
+    assert_eq!(inlines[0], ("/0/0".to_string(), &text("Title")));
+    assert_eq!(inlines[4].0, "/2/0");
+    assert!(matches!(inlines[4].1, Node::Emphasis(_)));
+    assert_eq!(inlines[5], ("/2/0/0".to_string(), &text("shared")));
+}
+
+#[test]
+fn shared_nodes_are_looked_through() {
+    let doc = sample();
+    let (_, last) = doc.blocks().last().unwrap();
+
+    assert!(matches!(last, Node::Paragraph(_)));
+}
+
+#[test]
+fn table_cells_are_inlines() {
+    let table = simple_table(
+        vec![text("Name"), text("Age")],
+        vec![vec![text("Ada"), text("36")]],
+    );
+    let doc = Node::Document(vec![table]);
+
+    assert_eq!(doc.blocks().count(), 1);
+    let paths: Vec<String> = doc.inlines().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/0/headers[0]",
+            "/0/headers[1]",
+            "/0/rows[0][0]",
+            "/0/rows[0][1]"
+        ]
+    );
+}
+
+#[test]
+fn definition_lists_yield_terms_then_definitions() {
+    let doc = Node::DefinitionList(vec![
+        DefinitionItem {
+            term: vec![text("CPU")],
+            definitions: vec![
+                vec![Node::Paragraph(vec![text("Processor")])],
+                vec![Node::Paragraph(vec![text("Chip")])],
+            ],
+        },
+        DefinitionItem {
+            term: vec![],
+            definitions: vec![vec![Node::Paragraph(vec![text("Orphan")])]],
+        },
+    ]);
+
+    let blocks: Vec<String> = doc.blocks().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(
+        blocks,
+        [
+            "/definitions[0][0]/0",
+            "/definitions[0][1]/0",
+            "/definitions[1][0]/0"
+        ]
+    );
+    let (path, term) = doc.inlines().next().unwrap();
+    assert_eq!(path.to_string(), "/terms[0]/0");
+    assert_eq!(term, &text("CPU"));
+}
+
+#[test]
+fn root_is_not_yielded() {
+    let paragraph = Node::Paragraph(vec![text("only")]);
+
+    assert_eq!(paragraph.blocks().count(), 0);
+    assert_eq!(
+        paragraph.inlines().collect::<Vec<_>>(),
+        [(NodePath::root().child(0), &text("only"))]
+    );
+    assert_eq!(text("leaf").inlines().count(), 0);
+}
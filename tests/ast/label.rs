@@ -0,0 +1,17 @@
+use cmark_writer::ast::normalize_label;
+
+#[test]
+fn normalize_label_collapses_whitespace_and_folds_case() {
+    assert_eq!(normalize_label("Foo"), "FOO");
+    assert_eq!(normalize_label(" \tfoo\r\n  bar  "), "FOO BAR");
+    assert_eq!(normalize_label("ǅ"), normalize_label("ǆ"));
+    assert_eq!(normalize_label("ẞ"), normalize_label("ss"));
+    assert_eq!(normalize_label("   "), "");
+}
+
+#[test]
+fn normalize_label_keeps_other_characters() {
+    // Only spaces, tabs and line endings are whitespace here
+    assert_ne!(normalize_label("a\u{a0}b"), normalize_label("a b"));
+    assert_eq!(normalize_label("a*b* [c]"), "A*B* [C]");
+}
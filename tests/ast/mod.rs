@@ -1,5 +1,10 @@
 // Unit tests for AST types and helpers
 
 mod html;
+mod iter;
+mod label;
 mod node;
+mod path;
+mod slug;
 mod tables;
+mod url;
@@ -12,16 +12,19 @@ fn node_equality() {
         level: 2,
         content: vec![Node::Text("Title".into())],
         heading_type: HeadingType::Atx,
+        custom_id: None,
     };
     let h2 = Node::Heading {
         level: 2,
         content: vec![Node::Text("Title".into())],
         heading_type: HeadingType::Atx,
+        custom_id: None,
     };
     let h3 = Node::Heading {
         level: 3,
         content: vec![Node::Text("Title".into())],
         heading_type: HeadingType::Atx,
+        custom_id: None,
     };
     assert_eq!(h1, h2);
     assert_ne!(h1, h3);
@@ -34,6 +37,7 @@ fn document_construction() {
             level: 1,
             content: vec![Node::Text("Document Title".into())],
             heading_type: HeadingType::Atx,
+            custom_id: None,
         },
         Node::Paragraph(vec![
             Node::Text("Paragraph with ".into()),
@@ -92,6 +96,7 @@ fn node_type_checks() {
         level: 1,
         content: vec![Node::Text("Heading".into())],
         heading_type: HeadingType::Atx,
+        custom_id: None,
     };
     assert!(heading.is_block());
     assert!(!heading.is_inline());
@@ -108,11 +113,13 @@ fn constructors() {
         level,
         content,
         heading_type,
+        custom_id,
     } = &heading
     {
         assert_eq!(*level, 2);
         assert_eq!(content[0], Node::Text("标题".into()));
         assert_eq!(*heading_type, HeadingType::Atx);
+        assert_eq!(*custom_id, None);
     } else {
         panic!("expected heading");
     }
@@ -134,3 +141,111 @@ fn constructors() {
         panic!("expected code block");
     }
 }
+
+#[test]
+fn shared_nodes_render_like_their_target() {
+    use cmark_writer::{CommonMarkWriter, HtmlWriter, ToCommonMark, ToHtml};
+    use std::sync::Arc;
+
+    let footer = Arc::new(Node::Paragraph(vec![Node::Text("Legal footer".into())]));
+    let shared = Node::from(Arc::clone(&footer));
+    assert!(shared.is_block());
+    assert_eq!(shared.type_name(), "Paragraph");
+    assert_eq!(shared.resolve_shared(), &*footer);
+
+    // Cloning only bumps the reference count
+    let copy = shared.clone();
+    assert_eq!(Arc::strong_count(&footer), 3);
+    assert_eq!(copy, shared);
+
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Title".into())]),
+        copy,
+    ]);
+
+    let mut writer = CommonMarkWriter::new();
+    doc.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "# Title\n\nLegal footer\n");
+
+    let mut writer = HtmlWriter::new();
+    doc.to_html(&mut writer).unwrap();
+    assert_eq!(
+        writer.into_string(),
+        "<h1>Title</h1>\n<p>Legal footer</p>\n"
+    );
+
+    let inline = Node::Paragraph(vec![
+        Node::Text("A ".into()),
+        Node::shared(Node::Strong(vec![Node::Text("bold".into())])),
+    ]);
+    let mut writer = CommonMarkWriter::new();
+    inline.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "A **bold**\n");
+}
+
+#[test]
+fn text_multiline_splits_lines_into_soft_breaks() {
+    assert_eq!(
+        Node::text_multiline("a\r\nb\n\n  \nc\n"),
+        [
+            Node::Text("a".into()),
+            Node::SoftBreak,
+            Node::Text("b".into()),
+            Node::SoftBreak,
+            Node::Text("c".into()),
+        ]
+    );
+    assert_eq!(
+        Node::text_multiline("single"),
+        [Node::Text("single".into())]
+    );
+    assert!(Node::text_multiline("\n\n").is_empty());
+}
+
+#[test]
+fn text_multiline_passes_strict_mode() {
+    use cmark_writer::{CommonMarkWriter, ToCommonMark, WriterOptionsBuilder};
+
+    let paragraph = Node::Paragraph(Node::text_multiline("line one\nline two"));
+    let mut writer =
+        CommonMarkWriter::with_options(WriterOptionsBuilder::new().strict(true).build());
+    paragraph.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "line one\nline two\n");
+}
+
+#[test]
+fn deep_len_counts_every_node() {
+    let item = ListItem::Unordered {
+        content: vec![Node::Paragraph(vec![Node::Text("item".into())])],
+    };
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Title".into())]),
+        Node::UnorderedList(vec![item.clone(), item]),
+        Node::shared(Node::Paragraph(vec![Node::Text("shared".into())])),
+    ]);
+    // document, heading + text, list + 2 × (paragraph + text), shared + text
+    assert_eq!(doc.deep_len(), 10);
+    assert_eq!(Node::ThematicBreak.deep_len(), 1);
+}
+
+#[cfg(feature = "content-hash")]
+#[test]
+fn content_hash_follows_equality() {
+    let build = |text: &str| {
+        Node::Document(vec![Node::Paragraph(vec![
+            Node::Text(text.into()),
+            Node::Emphasis(vec![Node::Text("!".into())]),
+        ])])
+    };
+    assert_eq!(build("a").content_hash(), build("a").content_hash());
+    assert_ne!(build("a").content_hash(), build("b").content_hash());
+
+    assert_eq!(
+        Node::shared(build("a")).content_hash(),
+        Node::shared(build("a")).content_hash()
+    );
+    assert_ne!(
+        Node::Emphasis(vec![Node::Text("x".into())]).content_hash(),
+        Node::Strong(vec![Node::Text("x".into())]).content_hash()
+    );
+}
@@ -0,0 +1,122 @@
+//! Tests for node path addressing
+
+use cmark_writer::ast::{ListItem, Node, NodePath, PathSegment};
+use cmark_writer::{CommonMarkWriter, WriteError};
+
+fn sample() -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Title".into())]),
+        Node::UnorderedList(vec![
+            ListItem::Unordered {
+                content: vec![Node::Paragraph(vec![Node::Text("one".into())])],
+            },
+            ListItem::Unordered {
+                content: vec![
+                    Node::Paragraph(vec![Node::Text("two".into())]),
+                    Node::BlockQuote(vec![Node::Paragraph(vec![Node::Text("quoted".into())])]),
+                ],
+            },
+        ]),
+        Node::shared(Node::Paragraph(vec![Node::Emphasis(vec![Node::Text(
+            "shared".into(),
+        )])])),
+    ])
+}
+
+#[test]
+fn get_follows_segments() {
+    let doc = sample();
+
+    assert_eq!(doc.get(&NodePath::root()), Some(&doc));
+    assert_eq!(
+        doc.get(&NodePath::root().child(0).child(0)),
+        Some(&Node::Text("Title".into()))
+    );
+    assert_eq!(
+        doc.get(&NodePath::root().child(1).list_item(1, 1).child(0).child(0)),
+        Some(&Node::Text("quoted".into()))
+    );
+    // Shared wrappers are looked through
+    assert_eq!(
+        doc.get(&NodePath::root().child(2).child(0).child(0)),
+        Some(&Node::Text("shared".into()))
+    );
+
+    assert_eq!(doc.get(&NodePath::root().child(9)), None);
+    assert_eq!(doc.get(&NodePath::root().child(1).child(0)), None);
+    assert_eq!(doc.get(&NodePath::root().table_header(0)), None);
+}
+
+#[test]
+fn replace_and_get_mut() {
+    let mut doc = sample();
+    let path = NodePath::root().child(1).list_item(0, 0);
+
+    let old = doc.replace(&path, Node::ThematicBreak).unwrap();
+    assert_eq!(old, Node::Paragraph(vec![Node::Text("one".into())]));
+    assert_eq!(doc.get(&path), Some(&Node::ThematicBreak));
+
+    if let Some(Node::Text(text)) = doc.get_mut(&NodePath::root().child(0).child(0)) {
+        text.push_str("!");
+    }
+    assert_eq!(
+        doc.get(&NodePath::root().child(0).child(0)),
+        Some(&Node::Text("Title!".into()))
+    );
+
+    assert!(doc
+        .replace(&NodePath::root().child(7), Node::ThematicBreak)
+        .is_none());
+}
+
+#[test]
+fn get_mut_copies_shared_subtrees() {
+    let shared = Node::shared(Node::Paragraph(vec![Node::Text("a".into())]));
+    let mut first = Node::Document(vec![shared.clone()]);
+    let second = Node::Document(vec![shared]);
+
+    first.replace(&NodePath::root().child(0).child(0), Node::Text("b".into()));
+
+    let path = NodePath::root().child(0).child(0);
+    assert_eq!(first.get(&path), Some(&Node::Text("b".into())));
+    assert_eq!(second.get(&path), Some(&Node::Text("a".into())));
+}
+
+#[test]
+fn path_display_and_parent() {
+    let path = NodePath::root()
+        .child(2)
+        .list_item(0, 1)
+        .table_cell(3, 4)
+        .table_header(0);
+    assert_eq!(path.to_string(), "/2/items[0]/1/rows[3][4]/headers[0]");
+    assert_eq!(path.len(), 4);
+    assert_eq!(
+        path.parent().unwrap().segments().last(),
+        Some(&PathSegment::TableCell { row: 3, column: 4 })
+    );
+    assert_eq!(NodePath::root().to_string(), "/");
+    assert!(NodePath::root().parent().is_none());
+}
+
+#[test]
+fn locate_error_finds_innermost_node() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("ok".into())]),
+        Node::BlockQuote(vec![Node::Paragraph(vec![
+            Node::Text("before ".into()),
+            Node::Link {
+                url: "/x".into(),
+                title: None,
+                content: vec![Node::Text("line\nbreak".into())],
+            },
+        ])]),
+    ]);
+
+    let writer = CommonMarkWriter::new();
+    let (path, error) = writer.locate_error(&doc).unwrap();
+    assert_eq!(path, NodePath::root().child(1).child(0).child(1).child(0));
+    assert!(matches!(error, WriteError::NewlineInInlineElement(_)));
+
+    assert!(writer.locate_error(&sample()).is_none());
+}
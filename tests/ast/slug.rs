@@ -0,0 +1,48 @@
+//! Tests for heading slug generation
+
+use cmark_writer::ast::{slugify, slugify_with, SlugOptions, Slugger};
+
+#[test]
+fn slugify_follows_github_rules() {
+    assert_eq!(slugify("Hello, World!"), "hello-world");
+    assert_eq!(slugify("  snake_case and-dashes "), "snake_case-and-dashes");
+    assert_eq!(slugify("Ünïcödé Ω"), "ünïcödé-ω");
+    assert_eq!(slugify("a  b"), "a--b");
+}
+
+#[test]
+fn slugger_deduplicates() {
+    let mut slugger = Slugger::new();
+    assert_eq!(slugger.slug("Intro"), "intro");
+    assert_eq!(slugger.slug("Intro"), "intro-1");
+    assert_eq!(slugger.slug("Intro 1"), "intro-1-1");
+    assert_eq!(slugger.slug("Intro"), "intro-2");
+}
+
+#[test]
+fn slugify_with_custom_separator_and_length() {
+    let options = SlugOptions::default().with_separator('_');
+    assert_eq!(slugify_with("Hello big World", &options), "hello_big_world");
+
+    // Cut at the limit without leaving a dangling separator
+    let options = SlugOptions::default().with_max_length(Some(6));
+    assert_eq!(slugify_with("Hello big World", &options), "hello");
+    assert_eq!(slugify_with("Hi", &options), "hi");
+}
+
+#[test]
+fn slugify_with_transliteration() {
+    let options = SlugOptions::default().with_transliterate(true);
+    assert_eq!(slugify_with("Ünïcödé Straße", &options), "unicode-strasse");
+    assert_eq!(slugify_with("Œuvre Ω", &options), "oeuvre-ω");
+    // Transliteration is applied before the length limit
+    let options = options.with_max_length(Some(3));
+    assert_eq!(slugify_with("Ææb", &options), "aea");
+}
+
+#[test]
+fn slugger_uses_options() {
+    let mut slugger = Slugger::with_options(SlugOptions::default().with_separator('_'));
+    assert_eq!(slugger.slug("Set up"), "set_up");
+    assert_eq!(slugger.slug("Set up"), "set_up_1");
+}
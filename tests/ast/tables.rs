@@ -289,3 +289,253 @@ fn test_centered_table() {
         _ => panic!("Expected Table node"),
     }
 }
+
+fn column(rows: &[Vec<Node>], index: usize) -> Vec<String> {
+    rows.iter()
+        .map(|row| row[index].plain_text().to_string())
+        .collect()
+}
+
+fn text_rows(rows: &[&[&str]]) -> Vec<Vec<Node>> {
+    rows.iter()
+        .map(|row| row.iter().map(|cell| Node::Text((*cell).into())).collect())
+        .collect()
+}
+
+#[test]
+fn sort_rows_numeric_and_text() {
+    let mut rows = text_rows(&[&["10"], &["9"], &["n/a"], &["-2.5"], &["1,200"], &["50%"]]);
+
+    sort_rows(&mut rows, &[SortKey::new(0)]);
+    assert_eq!(column(&rows, 0), ["-2.5", "1,200", "10", "50%", "9", "n/a"]);
+
+    sort_rows(&mut rows, &[SortKey::new(0).kind(ColumnKind::Numeric)]);
+    assert_eq!(column(&rows, 0), ["-2.5", "9", "10", "50%", "1,200", "n/a"]);
+
+    sort_rows(
+        &mut rows,
+        &[SortKey::new(0).kind(ColumnKind::Numeric).descending()],
+    );
+    assert_eq!(column(&rows, 0), ["n/a", "1,200", "50%", "10", "9", "-2.5"]);
+}
+
+#[test]
+fn sort_rows_dates_and_multiple_keys() {
+    let mut rows = text_rows(&[
+        &["b", "2024-10-01"],
+        &["a", "2024-9-30"],
+        &["a", "2023/12/01"],
+        &["b", "2024-10"],
+    ]);
+
+    sort_rows(&mut rows, &[SortKey::new(1).kind(ColumnKind::Date)]);
+    assert_eq!(
+        column(&rows, 1),
+        ["2023/12/01", "2024-9-30", "2024-10", "2024-10-01"]
+    );
+
+    sort_rows(
+        &mut rows,
+        &[
+            SortKey::new(0).descending(),
+            SortKey::new(1).kind(ColumnKind::Date),
+        ],
+    );
+    assert_eq!(column(&rows, 0), ["b", "b", "a", "a"]);
+    assert_eq!(
+        column(&rows, 1),
+        ["2024-10", "2024-10-01", "2023/12/01", "2024-9-30"]
+    );
+}
+
+#[test]
+fn sort_rows_orders_nan_after_numbers() {
+    assert_eq!(
+        CellValue::Number(f64::NAN).compare(&CellValue::Number(1.0)),
+        std::cmp::Ordering::Greater
+    );
+
+    let mut rows = text_rows(&[&["2"], &["+NaN"], &["1"], &["-inf"], &["n/a"]]);
+    sort_rows(&mut rows, &[SortKey::new(0).kind(ColumnKind::Numeric)]);
+    assert_eq!(column(&rows, 0), ["-inf", "1", "2", "+NaN", "n/a"]);
+}
+
+#[cfg(feature = "collation")]
+#[test]
+fn collation_sorts_text_by_locale() {
+    let words = ["ähnlich", "zeit", "apfel", "Zebra"];
+    let rows = text_rows(&[&[words[0]], &[words[1]], &[words[2]], &[words[3]]]);
+
+    let mut german = rows.clone();
+    Collation::new("de")
+        .unwrap()
+        .sort_rows(&mut german, &[SortKey::new(0)]);
+    assert_eq!(column(&german, 0), ["ähnlich", "apfel", "Zebra", "zeit"]);
+
+    let mut swedish = rows.clone();
+    Collation::new("sv")
+        .unwrap()
+        .sort_rows(&mut swedish, &[SortKey::new(0)]);
+    assert_eq!(column(&swedish, 0), ["apfel", "Zebra", "zeit", "ähnlich"]);
+
+    // Numbers still sort as numbers
+    let mut numbers = text_rows(&[&["10"], &["9"]]);
+    Collation::new("de")
+        .unwrap()
+        .sort_rows(&mut numbers, &[SortKey::new(0).kind(ColumnKind::Numeric)]);
+    assert_eq!(column(&numbers, 0), ["9", "10"]);
+
+    assert!(Collation::new("not a locale!").is_none());
+}
+
+#[test]
+fn sort_rows_is_stable() {
+    let mut rows = text_rows(&[&["1", "first"], &["01", "second"], &["0", "third"]]);
+    sort_rows(&mut rows, &[SortKey::new(0).kind(ColumnKind::Auto)]);
+    assert_eq!(column(&rows, 1), ["third", "first", "second"]);
+}
+
+#[test]
+fn sort_rows_by_table_node() {
+    let mut table = TableBuilder::new()
+        .headers(vec![Node::Text("n".into())])
+        .add_rows(text_rows(&[&["10"], &["9"]]))
+        .build();
+    assert!(sort_rows_by(
+        &mut table,
+        &[SortKey::new(0).kind(ColumnKind::Numeric)]
+    ));
+    let Node::Table { rows, .. } = &table else {
+        unreachable!()
+    };
+    assert_eq!(column(rows, 0), ["9", "10"]);
+
+    assert!(!sort_rows_by(
+        &mut Node::Paragraph(vec![]),
+        &[SortKey::new(0)]
+    ));
+
+    let built = TableBuilder::new()
+        .headers(vec![Node::Text("n".into())])
+        .add_rows(text_rows(&[&["b"], &["a"]]))
+        .sort_rows_by(&[SortKey::new(0)])
+        .build();
+    let Node::Table { rows, .. } = &built else {
+        unreachable!()
+    };
+    assert_eq!(column(rows, 0), ["a", "b"]);
+}
+
+#[test]
+fn test_table_builder_pads_short_rows() {
+    let table = TableBuilder::new()
+        .headers(vec![
+            Node::Text("A".into()),
+            Node::Text("B".into()),
+            Node::Text("C".into()),
+        ])
+        .row([Node::Text("1".into())])
+        .row(vec![Node::Text("2".into()), Node::Text("3".into())])
+        .build();
+
+    match table {
+        Node::Table { rows, .. } => {
+            assert_eq!(
+                rows[0],
+                vec![
+                    Node::Text("1".into()),
+                    Node::Text("".into()),
+                    Node::Text("".into())
+                ]
+            );
+            assert_eq!(rows[1][2], Node::Text("".into()));
+        }
+        _ => panic!("Expected Table node"),
+    }
+}
+
+#[test]
+fn test_table_builder_truncates_long_rows() {
+    let table = TableBuilder::new()
+        .headers(vec![Node::Text("A".into())])
+        .row([Node::Text("1".into()), Node::Text("extra".into())])
+        .build();
+
+    match table {
+        Node::Table { rows, .. } => assert_eq!(rows[0], vec![Node::Text("1".into())]),
+        _ => panic!("Expected Table node"),
+    }
+}
+
+#[test]
+fn test_table_builder_try_build_rejects_long_rows() {
+    let result = TableBuilder::new()
+        .headers(vec![Node::Text("A".into()), Node::Text("B".into())])
+        .row([Node::Text("1".into())])
+        .row([
+            Node::Text("1".into()),
+            Node::Text("2".into()),
+            Node::Text("3".into()),
+        ])
+        .try_build();
+
+    match result {
+        Err(cmark_writer::WriteError::InvalidStructure(message)) => assert_eq!(
+            message,
+            "Table row 2 has 3 cells, but the table has 2 columns"
+        ),
+        other => panic!("Expected InvalidStructure, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_table_builder_columns_without_headers() {
+    let table = TableBuilder::new()
+        .row([Node::Text("1".into()), Node::Text("2".into())])
+        .row([Node::Text("3".into())])
+        .try_build()
+        .unwrap();
+
+    match table {
+        Node::Table { headers, rows, .. } => {
+            assert!(headers.is_empty());
+            assert_eq!(rows[1].len(), 2);
+        }
+        _ => panic!("Expected Table node"),
+    }
+}
+
+#[test]
+#[cfg(feature = "gfm")]
+fn test_table_builder_align_fills_defaults() {
+    let headers = vec![
+        Node::Text("A".into()),
+        Node::Text("B".into()),
+        Node::Text("C".into()),
+    ];
+
+    let table = TableBuilder::new()
+        .headers(headers.clone())
+        .align([TableAlignment::Right])
+        .build();
+    match table {
+        Node::Table { alignments, .. } => assert_eq!(
+            alignments,
+            vec![
+                TableAlignment::Right,
+                TableAlignment::default(),
+                TableAlignment::default()
+            ]
+        ),
+        _ => panic!("Expected Table node"),
+    }
+
+    let table = TableBuilder::new()
+        .headers(headers)
+        .align(vec![TableAlignment::Left; 5])
+        .build();
+    match table {
+        Node::Table { alignments, .. } => assert_eq!(alignments, vec![TableAlignment::Left; 3]),
+        _ => panic!("Expected Table node"),
+    }
+}
@@ -0,0 +1,177 @@
+use cmark_writer::ast::{MarkdownUrl, Node};
+use cmark_writer::{CommonMarkWriter, ToCommonMark, WriteError};
+
+#[test]
+fn parse_lowercases_scheme() {
+    let url = MarkdownUrl::parse("HTTPS://Example.com/Path?Q=1").unwrap();
+    assert_eq!(url.as_str(), "https://Example.com/Path?Q=1");
+    assert_eq!(url.scheme(), Some("https"));
+    assert!(url.is_absolute());
+}
+
+#[test]
+fn parse_keeps_relative_urls() {
+    let url = MarkdownUrl::parse("docs/Guide.md#Intro").unwrap();
+    assert_eq!(url.as_str(), "docs/Guide.md#Intro");
+    assert_eq!(url.scheme(), None);
+    assert!(!url.is_absolute());
+
+    // A single letter before ':' is not a scheme (e.g. Windows drive letters)
+    assert_eq!(MarkdownUrl::parse("C:/file").unwrap().scheme(), None);
+}
+
+#[test]
+fn parse_rejects_whitespace_and_controls() {
+    for bad in ["has space", "tab\there", "line\nbreak", "nul\0"] {
+        let err = MarkdownUrl::parse(bad).unwrap_err();
+        assert!(matches!(err, WriteError::InvalidUrl(_)), "{bad:?}");
+    }
+    assert!(MarkdownUrl::try_from("ok").is_ok());
+}
+
+#[test]
+fn host_strips_user_info_and_port() {
+    let host = |url: &str| MarkdownUrl::from_trusted(url).host().map(str::to_string);
+    assert_eq!(
+        host("https://user@Example.com:8080/a?b"),
+        Some("Example.com".into())
+    );
+    assert_eq!(host("//cdn.example.com"), Some("cdn.example.com".into()));
+    assert_eq!(host("http://[::1]:80/"), Some("[::1]".into()));
+    assert_eq!(host("mailto:me@example.com"), None);
+    assert_eq!(host("/relative/path"), None);
+}
+
+#[test]
+fn from_trusted_skips_validation() {
+    let url = MarkdownUrl::from_trusted("HTTP://a b");
+    assert_eq!(&*url, "HTTP://a b");
+}
+
+#[test]
+fn node_constructors_use_url() {
+    let url = MarkdownUrl::parse("Mailto:me@example.com").unwrap();
+    assert_eq!(
+        Node::autolink(url),
+        Node::Autolink {
+            url: "mailto:me@example.com".into(),
+            is_email: false,
+        }
+    );
+
+    let link = Node::link(
+        MarkdownUrl::parse("HTTPS://example.com").unwrap(),
+        None,
+        vec![Node::Text("site".into())],
+    );
+    let mut writer = CommonMarkWriter::new();
+    link.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "[site](https://example.com)");
+
+    let image = Node::image(
+        MarkdownUrl::parse("img.png").unwrap(),
+        Some("Title".into()),
+        vec![Node::Text("alt".into())],
+    );
+    assert!(matches!(image, Node::Image { ref url, .. } if url == "img.png"));
+}
+
+#[test]
+fn url_constructor_classifies_email() {
+    let email = Node::Autolink {
+        url: "me@example.com".into(),
+        is_email: true,
+    };
+    let url = |s: &str| Node::url(MarkdownUrl::parse(s).unwrap());
+
+    assert_eq!(url("mailto:me@example.com"), email);
+    assert_eq!(url("MAILTO:me@example.com"), email);
+    assert_eq!(url("me@example.com"), email);
+    assert_eq!(Node::email("mailto:me@example.com"), email);
+    assert_eq!(Node::email("me@example.com"), email);
+
+    assert_eq!(
+        url("https://user@example.com"),
+        Node::Autolink {
+            url: "https://user@example.com".into(),
+            is_email: false,
+        }
+    );
+    assert_eq!(
+        url("example.com/me@host/profile"),
+        Node::Autolink {
+            url: "example.com/me@host/profile".into(),
+            is_email: false,
+        }
+    );
+}
+
+#[test]
+fn autolinks_render_consistently() {
+    use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml, WriterOptionsBuilder};
+
+    let render = |node: &Node| {
+        let mut writer = CommonMarkWriter::new();
+        node.to_commonmark(&mut writer).unwrap();
+        let mut html = HtmlWriter::new();
+        node.to_html(&mut html).unwrap();
+        (
+            writer.into_string().to_string(),
+            html.into_string().to_string(),
+        )
+    };
+
+    // `mailto:` is dropped from email autolinks in both writers
+    let prefixed = Node::Autolink {
+        url: "mailto:me@example.com".into(),
+        is_email: true,
+    };
+    assert_eq!(
+        render(&prefixed),
+        (
+            "<me@example.com>".to_string(),
+            r#"<a href="mailto:me@example.com">me@example.com</a>"#.to_string()
+        )
+    );
+
+    // A URI autolink keeps its `mailto:` scheme
+    let uri = Node::Autolink {
+        url: "mailto:me@example.com".into(),
+        is_email: false,
+    };
+    assert_eq!(
+        render(&uri),
+        (
+            "<mailto:me@example.com>".to_string(),
+            r#"<a href="mailto:me@example.com">mailto:me@example.com</a>"#.to_string()
+        )
+    );
+
+    // Both writers add the default scheme
+    let bare = Node::Autolink {
+        url: "example.com".into(),
+        is_email: false,
+    };
+    assert_eq!(
+        render(&bare),
+        (
+            "<https://example.com>".to_string(),
+            r#"<a href="https://example.com">https://example.com</a>"#.to_string()
+        )
+    );
+
+    let options = WriterOptionsBuilder::new()
+        .autolink_default_scheme("http")
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    bare.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<http://example.com>");
+
+    let mut html =
+        HtmlWriter::with_options(HtmlWriterOptions::default().with_autolink_default_scheme("http"));
+    bare.to_html(&mut html).unwrap();
+    assert_eq!(
+        html.into_string(),
+        r#"<a href="http://example.com">http://example.com</a>"#
+    );
+}
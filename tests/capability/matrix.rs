@@ -0,0 +1,156 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{Capability, CommonMarkWriter, HtmlWriter};
+
+#[test]
+fn capability_names_are_unique() {
+    let mut names: Vec<_> = Capability::ALL.iter().map(|c| c.name()).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), Capability::ALL.len());
+    assert_eq!(Capability::TableAlignment.to_string(), "table_alignment");
+}
+
+#[test]
+fn default_commonmark_writer_capabilities() {
+    let writer = CommonMarkWriter::new();
+    let supported: Vec<_> = Capability::ALL
+        .iter()
+        .copied()
+        .filter(|c| writer.supports(*c))
+        .collect();
+    assert_eq!(supported, [Capability::Table, Capability::RawHtml]);
+}
+
+#[test]
+fn commonmark_capabilities_follow_options() {
+    let options = WriterOptionsBuilder::new()
+        .strict(false)
+        .attribute_blocks(true)
+        .math(true)
+        .definition_lists(true)
+        .footnotes(true)
+        .build();
+    let writer = CommonMarkWriter::with_options(options);
+    assert!(writer.supports(Capability::TableBlockContent));
+    assert!(writer.supports(Capability::HeadingIds));
+    assert!(writer.supports(Capability::Math));
+    assert!(writer.supports(Capability::DefinitionList));
+    assert!(writer.supports(Capability::Footnotes));
+}
+
+#[test]
+fn extensions_are_required_capabilities() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![
+            Node::Math {
+                display: false,
+                content: "x".into(),
+            },
+            Node::FootnoteReference("1".into()),
+        ]),
+        Node::DefinitionList(vec![cmark_writer::ast::DefinitionItem::new(
+            vec![Node::Text("Term".into())],
+            vec![vec![Node::Paragraph(vec![Node::Text("x".into())])]],
+        )]),
+    ]);
+    assert_eq!(
+        doc.required_capabilities(),
+        [
+            Capability::DefinitionList,
+            Capability::Footnotes,
+            Capability::Math
+        ]
+    );
+    let html = HtmlWriter::new();
+    assert!(doc
+        .required_capabilities()
+        .iter()
+        .all(|capability| html.supports(*capability)));
+}
+
+#[test]
+fn html_writer_supports_core_capabilities() {
+    let writer = HtmlWriter::new();
+    assert!(writer.supports(Capability::TableBlockContent));
+    assert!(writer.supports(Capability::HeadingIds));
+    assert!(!writer.supports(Capability::Strikethrough));
+}
+
+#[test]
+fn required_capabilities_are_sorted_and_unique() {
+    let doc = Node::Document(vec![
+        Node::heading_with_id(1, vec![Node::Text("T".into())], "t"),
+        Node::HtmlBlock("<div></div>".into()),
+        Node::Paragraph(vec![
+            Node::Strikethrough(vec![Node::Text("a".into())]),
+            Node::Strikethrough(vec![Node::Text("b".into())]),
+        ]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![Node::HtmlElement(
+                cmark_writer::HtmlElement::new("span"),
+            )])],
+        }]),
+    ]);
+    assert_eq!(
+        doc.required_capabilities(),
+        [
+            Capability::Strikethrough,
+            Capability::RawHtml,
+            Capability::HeadingIds
+        ]
+    );
+    assert!(Node::Paragraph(vec![Node::Text("plain".into())])
+        .required_capabilities()
+        .is_empty());
+}
+
+#[test]
+fn table_with_block_content_requires_fallback() {
+    let table = Node::Table {
+        headers: vec![Node::Text("h".into())],
+        #[cfg(feature = "gfm")]
+        alignments: vec![cmark_writer::ast::TableAlignment::None],
+        rows: vec![vec![Node::Paragraph(vec![Node::Text("p".into())])]],
+    };
+    assert_eq!(
+        table.required_capabilities(),
+        [Capability::Table, Capability::TableBlockContent]
+    );
+}
+
+#[cfg(feature = "gfm")]
+#[test]
+fn gfm_capabilities() {
+    use cmark_writer::ast::{TableAlignment, TaskListStatus};
+    use cmark_writer::HtmlWriterOptions;
+
+    let doc = Node::Document(vec![
+        Node::Table {
+            headers: vec![Node::Text("h".into())],
+            alignments: vec![TableAlignment::Center],
+            rows: vec![],
+        },
+        Node::UnorderedList(vec![ListItem::Task {
+            status: TaskListStatus::Checked,
+            content: vec![Node::Paragraph(vec![Node::ExtendedAutolink(
+                "www.example.com".into(),
+            )])],
+        }]),
+    ]);
+    assert_eq!(
+        doc.required_capabilities(),
+        [
+            Capability::Table,
+            Capability::TableAlignment,
+            Capability::TaskList,
+            Capability::ExtendedAutolink
+        ]
+    );
+
+    let gfm = CommonMarkWriter::with_options(WriterOptionsBuilder::new().enable_gfm().build());
+    assert!(doc.required_capabilities().iter().all(|c| gfm.supports(*c)));
+
+    let html = HtmlWriter::with_options(HtmlWriterOptions::default().with_gfm_enabled(true));
+    assert!(html.supports(Capability::TaskList));
+}
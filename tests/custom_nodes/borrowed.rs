@@ -0,0 +1,167 @@
+//! Custom nodes can't be cloned, so operations rebuilding parts of a tree must
+//! borrow or move them
+
+use cmark_writer::ast::{Node, NodePath};
+use cmark_writer::error::WriteResult;
+use cmark_writer::ops::{
+    merge_adjacent_paragraphs, retitle_headings, split_by_heading, split_paragraph_at,
+    truncate_document, CaseStyle, TruncateLimits,
+};
+use cmark_writer::text::truncate_with_ellipsis;
+use cmark_writer::{CommonMarkWriter, CustomNodeBoilerplate, Format, ToCommonMark};
+
+#[derive(Debug, Clone, PartialEq, CustomNodeBoilerplate)]
+struct Kbd {
+    key: &'static str,
+}
+
+impl Format<CommonMarkWriter> for Kbd {
+    fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        writer.write_str(&format!("<kbd>{}</kbd>", self.key))
+    }
+}
+
+fn kbd(key: &'static str) -> Node {
+    Node::Custom(Box::new(Kbd { key }))
+}
+
+fn text(value: &str) -> Node {
+    Node::Text(value.into())
+}
+
+fn render(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::new();
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn nested_emphasis_is_flattened_around_custom_nodes() {
+    let node = Node::Strong(vec![Node::Strong(vec![text("press ")]), kbd("A")]);
+    assert_eq!(render(&node), "**press <kbd>A</kbd>**");
+
+    let node = Node::Emphasis(vec![Node::Strong(vec![kbd("B")])]);
+    assert_eq!(render(&node), "***<kbd>B</kbd>***");
+}
+
+#[test]
+fn truncation_leaves_out_custom_nodes() {
+    let nodes = vec![
+        text("Press "),
+        kbd("A"),
+        text(" or "),
+        Node::Strong(vec![text("continue"), kbd("B")]),
+    ];
+    let truncated = truncate_with_ellipsis(&nodes, 15);
+    assert_eq!(
+        Node::Paragraph(truncated).to_string(),
+        "Press  or **cont**…\n"
+    );
+
+    let fitting = truncate_with_ellipsis(&nodes, 100);
+    assert_eq!(
+        Node::Paragraph(fitting).to_string(),
+        "Press  or **continue**\n"
+    );
+}
+
+#[test]
+fn document_previews_leave_out_custom_nodes() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![text("Press "), kbd("A")]),
+        Node::shared(Node::Paragraph(vec![kbd("B")])),
+        Node::ReadMoreMarker,
+        Node::Paragraph(vec![text("More")]),
+    ]);
+    let preview = truncate_document(&doc, &TruncateLimits::new());
+    assert_eq!(preview.to_string(), "Press \n\n<kbd>B</kbd>\n");
+}
+
+#[test]
+fn sections_leave_out_custom_nodes_unless_shared() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![text("Keys")]),
+        Node::Paragraph(vec![text("Press "), kbd("A")]),
+        kbd("B"),
+        Node::shared(Node::Paragraph(vec![kbd("C")])),
+    ]);
+    let sections = split_by_heading(&doc, 1);
+    assert_eq!(sections.len(), 1);
+    assert_eq!(
+        sections[0].1.to_string(),
+        "# Keys\n\nPress \n\n<kbd>C</kbd>\n"
+    );
+}
+
+#[test]
+fn shared_subtrees_with_custom_nodes_are_not_copied_for_edits() {
+    let shared = Node::shared(Node::Paragraph(vec![text("Press "), kbd("A")]));
+    let mut doc = Node::Document(vec![shared.clone()]);
+    let path = NodePath::root().child(0).child(0);
+    assert!(doc.get_mut(&path).is_none());
+    assert_eq!(doc.replace(&path, text("Hit ")), None);
+
+    // Once the subtree is no longer shared elsewhere, it is edited in place
+    drop(shared);
+    assert!(doc.replace(&path, text("Hit ")).is_some());
+    assert_eq!(doc.to_string(), "Hit <kbd>A</kbd>\n");
+}
+
+#[test]
+fn paragraphs_with_custom_nodes_are_merged_and_split_in_place() {
+    let mut doc = Node::Document(vec![
+        Node::Paragraph(vec![text("Press "), kbd("A")]),
+        Node::Paragraph(vec![Node::Strong(vec![text("then "), kbd("B")])]),
+    ]);
+    merge_adjacent_paragraphs(&mut doc);
+    assert_eq!(
+        doc.to_string(),
+        "Press <kbd>A</kbd>\n**then <kbd>B</kbd>**\n"
+    );
+
+    // "Press " and a soft break come first
+    let new = split_paragraph_at(&mut doc, &NodePath::root().child(0), 9).unwrap();
+    assert_eq!(new, NodePath::root().child(1));
+    assert_eq!(
+        doc.to_string(),
+        "Press <kbd>A</kbd>\n**th**\n\n**en <kbd>B</kbd>**\n"
+    );
+}
+
+#[test]
+fn shared_nodes_with_custom_nodes_are_only_split_if_not_shared_elsewhere() {
+    let shared = Node::shared(Node::Strong(vec![text("then "), kbd("B")]));
+    let mut doc = Node::Document(vec![Node::Paragraph(vec![shared.clone()])]);
+    let before = doc.to_string();
+    assert_eq!(
+        split_paragraph_at(&mut doc, &NodePath::root().child(0), 2),
+        None
+    );
+    assert_eq!(doc.to_string(), before);
+
+    drop(shared);
+    assert!(split_paragraph_at(&mut doc, &NodePath::root().child(0), 2).is_some());
+    assert_eq!(doc.to_string(), "**th**\n\n**en <kbd>B</kbd>**\n");
+}
+
+#[test]
+fn shared_nodes_with_custom_nodes_are_only_retitled_if_not_shared_elsewhere() {
+    let shared = Node::shared(Node::Strong(vec![text("the big "), kbd("K")]));
+    let mut doc = Node::Document(vec![Node::heading(
+        1,
+        vec![text("press "), shared.clone(), text(" to start")],
+    )]);
+    retitle_headings(&mut doc, CaseStyle::Title);
+    // The shared words are left as is, the words after them still line up
+    assert_eq!(
+        doc.to_string(),
+        "# Press **the big <kbd>K</kbd>** to Start\n"
+    );
+
+    drop(shared);
+    retitle_headings(&mut doc, CaseStyle::Title);
+    assert_eq!(
+        doc.to_string(),
+        "# Press **the Big <kbd>K</kbd>** to Start\n"
+    );
+}
@@ -0,0 +1,106 @@
+use cmark_writer::ast::Node;
+use cmark_writer::error::WriteResult;
+use cmark_writer::{
+    CommonMarkWriter, CustomNodeBoilerplate, Format, HtmlWriter, NodeContent, ToCommonMark, ToHtml,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug, Clone, PartialEq, Hash, CustomNodeBoilerplate)]
+#[custom_node(html, hash)]
+struct Kbd {
+    key: String,
+}
+
+impl Format<CommonMarkWriter> for Kbd {
+    fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        writer.write_str(&format!("[[{}]]", self.key))
+    }
+}
+
+impl Format<HtmlWriter> for Kbd {
+    fn format(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+        writer.start_tag("kbd")?;
+        writer.finish_tag()?;
+        writer.text(&self.key)?;
+        writer.end_tag("kbd")?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, CustomNodeBoilerplate)]
+#[custom_node(block)]
+struct Divider<T: Clone + PartialEq + std::fmt::Debug + Send + Sync + 'static> {
+    label: T,
+}
+
+impl<T> Format<CommonMarkWriter> for Divider<T>
+where
+    T: Clone + PartialEq + std::fmt::Debug + Send + Sync + 'static,
+{
+    fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        writer.write_str(&format!("--- {:?} ---", self.label))
+    }
+}
+
+fn kbd(key: &str) -> Node {
+    Node::Custom(Box::new(Kbd { key: key.into() }))
+}
+
+fn hash_of(node: &Node) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    node.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn renders_through_format_impls() {
+    let doc = Node::Paragraph(vec![Node::Text("Press ".into()), kbd("Esc")]);
+
+    let mut md = CommonMarkWriter::new();
+    doc.to_commonmark(&mut md).unwrap();
+    assert_eq!(md.into_string(), "Press [[Esc]]\n");
+
+    let mut html = HtmlWriter::new();
+    doc.to_html(&mut html).unwrap();
+    assert_eq!(html.into_string(), "<p>Press <kbd>Esc</kbd></p>\n");
+}
+
+#[test]
+fn clones_compares_and_hashes_by_content() {
+    let node = kbd("Esc");
+    let Node::Custom(custom) = &node else {
+        unreachable!()
+    };
+
+    assert!(custom.eq_box(custom.clone_box().as_ref()));
+    assert_eq!(node, kbd("Esc"));
+    assert_ne!(node, kbd("Tab"));
+    assert_eq!(hash_of(&node), hash_of(&kbd("Esc")));
+    assert_ne!(hash_of(&node), hash_of(&kbd("Tab")));
+}
+
+#[test]
+fn block_option_marks_blocks() {
+    let divider = Node::Custom(Box::new(Divider { label: 3 }));
+
+    assert!(Divider { label: 3 }.is_block());
+    assert!(!Kbd { key: "Esc".into() }.is_block());
+
+    let mut md = CommonMarkWriter::new();
+    Node::Document(vec![Node::Paragraph(vec![Node::Text("a".into())]), divider])
+        .to_commonmark(&mut md)
+        .unwrap();
+    assert_eq!(md.into_string(), "a\n\n--- 3 ---\n");
+}
+
+#[test]
+fn html_defaults_to_placeholder_comment() {
+    let divider = Node::Custom(Box::new(Divider { label: "x" }));
+
+    let mut html = HtmlWriter::new();
+    divider.to_html(&mut html).unwrap();
+    assert!(html
+        .into_string()
+        .contains("HTML rendering not implemented"));
+}
@@ -0,0 +1,150 @@
+use cmark_writer::ast::Node;
+use cmark_writer::error::WriteResult;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{
+    CommonMarkWriter, CustomNode, FallbackStrategy, Format, HtmlWriter, NodeClone, NodeContent,
+    ToCommonMark, WriteError,
+};
+use std::any::Any;
+
+/// Keyboard keys, which plain CommonMark has no syntax for
+#[derive(Debug, Clone, PartialEq, NodeClone)]
+struct Kbd {
+    keys: Vec<&'static str>,
+    fallback: FallbackStrategy,
+    block: bool,
+}
+
+impl NodeContent for Kbd {
+    fn is_block(&self) -> bool {
+        self.block
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Format<CommonMarkWriter> for Kbd {
+    fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        writer.write_str(&format!("[[{}]]", self.keys.join("+")))
+    }
+}
+
+impl CustomNode for Kbd {
+    fn html_render(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+        if self.block {
+            writer.start_tag("p")?;
+            writer.finish_tag()?;
+        }
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                writer.text("+")?;
+            }
+            writer.start_tag("kbd")?;
+            writer.finish_tag()?;
+            writer.text(key)?;
+            writer.end_tag("kbd")?;
+        }
+        if self.block {
+            writer.end_tag("p")?;
+            writer.raw_html("\n")?;
+        }
+        Ok(())
+    }
+
+    // Only writers that allow HTML in inline content can't express keys
+    fn supports_commonmark(&self, writer: &CommonMarkWriter) -> bool {
+        !writer.options().strict
+    }
+
+    fn fallback(&self) -> FallbackStrategy {
+        self.fallback
+    }
+}
+
+fn kbd(fallback: FallbackStrategy, block: bool) -> Node {
+    Node::Custom(Box::new(Kbd {
+        keys: vec!["Ctrl", "<C>"],
+        fallback,
+        block,
+    }))
+}
+
+fn render(node: &Node, strict: bool) -> WriteResult<String> {
+    let options = WriterOptionsBuilder::new().strict(strict).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+fn in_paragraph(fallback: FallbackStrategy) -> WriteResult<String> {
+    let paragraph = Node::Paragraph(vec![
+        Node::Text("Press ".into()),
+        kbd(fallback, false),
+        Node::Text(".".into()),
+    ]);
+    render(&paragraph, true)
+}
+
+#[test]
+fn supported_nodes_render_normally() {
+    let paragraph = Node::Paragraph(vec![kbd(FallbackStrategy::Error, false)]);
+    assert_eq!(render(&paragraph, false).unwrap(), "[[Ctrl+<C>]]\n");
+}
+
+#[test]
+fn html_fallback_embeds_html() {
+    assert_eq!(
+        in_paragraph(FallbackStrategy::Html).unwrap(),
+        "Press <kbd>Ctrl</kbd>+<kbd>&lt;C&gt;</kbd>.\n"
+    );
+
+    let doc = Node::Document(vec![
+        kbd(FallbackStrategy::Html, true),
+        Node::Paragraph(vec![Node::Text("after".into())]),
+    ]);
+    assert_eq!(
+        render(&doc, true).unwrap(),
+        "<p><kbd>Ctrl</kbd>+<kbd>&lt;C&gt;</kbd></p>\n\nafter\n"
+    );
+}
+
+#[test]
+fn plain_text_fallback_writes_text() {
+    assert_eq!(
+        in_paragraph(FallbackStrategy::PlainText).unwrap(),
+        "Press Ctrl+<C>.\n"
+    );
+
+    let doc = Node::Document(vec![kbd(FallbackStrategy::PlainText, true)]);
+    assert_eq!(render(&doc, true).unwrap(), "Ctrl+<C>\n");
+}
+
+#[test]
+fn skip_fallback_leaves_node_out() {
+    assert_eq!(in_paragraph(FallbackStrategy::Skip).unwrap(), "Press .\n");
+}
+
+#[test]
+fn error_fallback_fails() {
+    match in_paragraph(FallbackStrategy::Error) {
+        Err(WriteError::Custom { message, code }) => {
+            assert!(
+                message.ends_with("can't be written as CommonMark"),
+                "{message}"
+            );
+            assert_eq!(code.as_deref(), Some("unsupported_custom_node"));
+        }
+        other => panic!("expected an error, got {other:?}"),
+    }
+}
+
+#[test]
+fn default_fallback_is_html() {
+    assert_eq!(FallbackStrategy::default(), FallbackStrategy::Html);
+}
@@ -0,0 +1,110 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::error::WriteResult;
+use cmark_writer::{CommonMarkWriter, CustomNode, Format, NodeClone, NodeContent};
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+#[derive(Debug, Clone, PartialEq, Hash, NodeClone)]
+#[node_clone(hash)]
+struct Badge {
+    label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, NodeClone)]
+struct Marker {
+    id: u32,
+}
+
+macro_rules! inline_custom_node {
+    ($name:ident) => {
+        impl NodeContent for $name {
+            fn is_block(&self) -> bool {
+                false
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+
+        impl Format<CommonMarkWriter> for $name {
+            fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+                writer.write_str(&format!("{:?}", self))
+            }
+        }
+
+        impl CustomNode for $name {}
+    };
+}
+
+inline_custom_node!(Badge);
+inline_custom_node!(Marker);
+
+fn hash_of(node: &Node) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    node.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn badge(label: &str) -> Node {
+    Node::Custom(Box::new(Badge {
+        label: label.into(),
+    }))
+}
+
+fn doc(text: &str) -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Title".into())]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![Node::Text(text.into())])],
+        }]),
+    ])
+}
+
+#[test]
+fn equal_nodes_hash_equal() {
+    assert_eq!(doc("item"), doc("item"));
+    assert_eq!(hash_of(&doc("item")), hash_of(&doc("item")));
+    assert_ne!(hash_of(&doc("item")), hash_of(&doc("other")));
+    assert_eq!(hash_of(&badge("new")), hash_of(&badge("new")));
+}
+
+#[test]
+fn nodes_work_as_cache_keys() {
+    let mut cache = HashMap::new();
+    cache.insert(doc("item"), "rendered");
+    assert_eq!(cache.get(&doc("item")), Some(&"rendered"));
+    assert_eq!(cache.get(&doc("other")), None);
+}
+
+#[test]
+fn derived_hash_box_hashes_content() {
+    assert_ne!(hash_of(&badge("new")), hash_of(&badge("beta")));
+    assert_ne!(badge("new"), badge("beta"));
+}
+
+#[test]
+fn default_hash_box_is_consistent_with_equality() {
+    let a = Node::Custom(Box::new(Marker { id: 1 }));
+    let b = Node::Custom(Box::new(Marker { id: 1 }));
+    let c = Node::Custom(Box::new(Marker { id: 2 }));
+    assert_eq!(a, b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+    // Without a content hash, nodes of one type collide but stay unequal
+    assert_ne!(a, c);
+    assert_eq!(hash_of(&a), hash_of(&c));
+}
+
+#[test]
+fn derived_clone_box_and_eq_box() {
+    let marker = Marker { id: 7 };
+    let clone = marker.clone_box();
+    assert!(marker.eq_box(clone.as_ref()));
+    assert!(!marker.eq_box(&Badge { label: "x".into() }));
+}
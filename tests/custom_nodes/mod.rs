@@ -1,3 +1,9 @@
 mod attribute_macros;
 mod basic;
+mod borrowed;
+mod derive;
+mod fallback;
+mod hashing;
 mod matchers;
+mod nesting;
+mod untrusted;
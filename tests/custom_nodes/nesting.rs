@@ -0,0 +1,101 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::error::WriteResult;
+use cmark_writer::{
+    CommonMarkWriter, CustomNode, Format, HtmlWriter, NodeClone, NodeContent, ToCommonMark,
+};
+use std::any::Any;
+
+/// A fenced container block that ends its output with a newline
+#[derive(Debug, Clone, PartialEq, NodeClone)]
+struct Admonition {
+    body: &'static str,
+}
+
+impl NodeContent for Admonition {
+    fn is_block(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Format<CommonMarkWriter> for Admonition {
+    fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        writer.write_str(":::note\n")?;
+        writer.write_str(self.body)?;
+        writer.write_str("\n:::\n")
+    }
+}
+
+impl CustomNode for Admonition {
+    fn html_render(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+        writer.raw_html("<aside>")?;
+        writer.text(self.body)?;
+        writer.raw_html("</aside>\n")?;
+        Ok(())
+    }
+}
+
+fn admonition() -> Node {
+    Node::Custom(Box::new(Admonition { body: "body" }))
+}
+
+fn paragraph(text: &str) -> Node {
+    Node::Paragraph(vec![Node::Text(text.into())])
+}
+
+fn render(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::new();
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn multi_line_custom_block_in_blockquote() {
+    let doc = Node::Document(vec![Node::BlockQuote(vec![
+        admonition(),
+        paragraph("after"),
+    ])]);
+    assert_eq!(render(&doc), "> :::note\n> body\n> :::\n> \n> after\n");
+}
+
+#[test]
+fn multi_line_custom_block_in_list_item() {
+    let doc = Node::Document(vec![Node::OrderedList {
+        start: 1,
+        items: vec![ListItem::Ordered {
+            number: None,
+            content: vec![admonition(), paragraph("after")],
+        }],
+    }]);
+    assert_eq!(render(&doc), "1. :::note\n   body\n   :::\n   \n   after\n");
+}
+
+#[test]
+fn multi_line_custom_block_in_nested_containers() {
+    let doc = Node::Document(vec![Node::BlockQuote(vec![Node::UnorderedList(vec![
+        ListItem::Unordered {
+            content: vec![paragraph("before"), admonition()],
+        },
+    ])])]);
+    assert_eq!(
+        render(&doc),
+        "> - before\n>   \n>   :::note\n>   body\n>   :::\n"
+    );
+}
+
+#[test]
+fn custom_block_spacing_matches_builtin_blocks() {
+    let custom = Node::Document(vec![admonition(), paragraph("after")]);
+    let builtin = Node::Document(vec![
+        Node::HtmlBlock(":::note\nbody\n:::\n".into()),
+        paragraph("after"),
+    ]);
+    assert_eq!(render(&custom), render(&builtin));
+}
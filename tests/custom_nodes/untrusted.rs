@@ -0,0 +1,288 @@
+use cmark_writer::ast::Node;
+use cmark_writer::error::WriteResult;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::traits::DynHtmlSink;
+use cmark_writer::{
+    CommonMarkWriter, CustomNode, Format, HtmlWriter, HtmlWriterOptions, NodeClone, NodeContent,
+    ToCommonMark, ToHtml,
+};
+use std::any::Any;
+
+/// A third-party node trying to break out of its output
+#[derive(Debug, Clone, PartialEq, NodeClone)]
+struct Widget {
+    markdown: &'static str,
+    block: bool,
+}
+
+impl NodeContent for Widget {
+    fn is_block(&self) -> bool {
+        self.block
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Format<CommonMarkWriter> for Widget {
+    fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        writer.write_str(self.markdown)
+    }
+}
+
+impl CustomNode for Widget {
+    fn html_render(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+        writer.start_tag("a")?;
+        writer.attribute("href", "javascript:alert(1)")?;
+        writer.attribute("onclick", "steal()")?;
+        writer.attribute("title", "ok")?;
+        writer.finish_tag()?;
+        writer.text("link")?;
+        writer.end_tag("a")?;
+        writer.start_tag("script")?;
+        writer.finish_tag()?;
+        writer.text("steal()")?;
+        writer.end_tag("script")?;
+        writer.raw_html("<img src=x onerror=steal()>")?;
+        Ok(())
+    }
+}
+
+fn widget(markdown: &'static str, block: bool) -> Node {
+    Node::Custom(Box::new(Widget { markdown, block }))
+}
+
+fn commonmark(node: &Node, trust: bool) -> String {
+    let options = WriterOptionsBuilder::new()
+        .trust_custom_nodes(trust)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn html(node: &Node, options: HtmlWriterOptions) -> WriteResult<String> {
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+#[test]
+fn trusted_by_default() {
+    let node = Node::Paragraph(vec![widget("<script>x</script> **bold**", false)]);
+    assert_eq!(commonmark(&node, true), "<script>x</script> **bold**\n");
+}
+
+#[test]
+fn untrusted_commonmark_is_escaped_text() {
+    let node = Node::Paragraph(vec![
+        Node::Text("a ".into()),
+        widget("<script>x</script> **bold** [l](javascript:x)", false),
+    ]);
+    assert_eq!(
+        commonmark(&node, false),
+        "a \\<script\\>x\\</script\\> \\*\\*bold\\*\\* \\[l\\](javascript:x)\n"
+    );
+}
+
+#[test]
+fn untrusted_block_stays_one_paragraph() {
+    let doc = Node::Document(vec![
+        widget("# Title\n\n    code\n\n- item", true),
+        Node::Paragraph(vec![Node::Text("after".into())]),
+    ]);
+    assert_eq!(commonmark(&doc, false), "\\# Title code - item\n\nafter\n");
+}
+
+#[test]
+fn untrusted_html_strict_refuses_unsafe_markup() {
+    let options = HtmlWriterOptions::default().with_trust_custom_nodes(false);
+    assert!(html(&widget("", false), options).is_err());
+}
+
+#[test]
+fn untrusted_html_leaves_out_unsafe_markup() {
+    let options = HtmlWriterOptions {
+        strict: false,
+        trust_custom_nodes: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        html(&widget("", false), options).unwrap(),
+        "<a href=\"\" title=\"ok\">link</a>steal()&lt;img src=x onerror=steal()&gt;"
+    );
+}
+
+#[test]
+fn untrusted_html_raw_nodes_are_escaped() {
+    #[derive(Debug, Clone, PartialEq, NodeClone)]
+    struct Wrapper;
+
+    impl NodeContent for Wrapper {
+        fn is_block(&self) -> bool {
+            true
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    impl Format<CommonMarkWriter> for Wrapper {
+        fn format(&self, _writer: &mut CommonMarkWriter) -> WriteResult<()> {
+            Ok(())
+        }
+    }
+
+    impl CustomNode for Wrapper {
+        fn html_render(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+            DynHtmlSink::write_node(writer, &Node::HtmlBlock("<iframe src=x></iframe>".into()))
+        }
+    }
+
+    let node = Node::Custom(Box::new(Wrapper));
+    let trusted = HtmlWriterOptions::default();
+    assert_eq!(html(&node, trusted).unwrap(), "<iframe src=x></iframe>\n");
+    let untrusted = HtmlWriterOptions::default().with_trust_custom_nodes(false);
+    assert_eq!(
+        html(&node, untrusted).unwrap(),
+        "&lt;iframe src=x&gt;&lt;/iframe&gt;\n"
+    );
+}
+
+#[test]
+fn untrusted_html_fallback_in_commonmark_is_text() {
+    #[derive(Debug, Clone, PartialEq, NodeClone)]
+    struct HtmlOnly;
+
+    impl NodeContent for HtmlOnly {
+        fn is_block(&self) -> bool {
+            false
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    impl Format<CommonMarkWriter> for HtmlOnly {
+        fn format(&self, _writer: &mut CommonMarkWriter) -> WriteResult<()> {
+            Ok(())
+        }
+    }
+
+    impl CustomNode for HtmlOnly {
+        fn html_render(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+            writer.raw_html("<b>bold</b> &lt;i&gt;")?;
+            Ok(())
+        }
+
+        fn supports_commonmark(&self, _writer: &CommonMarkWriter) -> bool {
+            false
+        }
+    }
+
+    let node = Node::Paragraph(vec![Node::Custom(Box::new(HtmlOnly))]);
+    assert_eq!(commonmark(&node, true), "<b>bold</b> &lt;i&gt;\n");
+    assert_eq!(commonmark(&node, false), "bold \\<i\\>\n");
+}
+
+/// A node tampering with the writer it is given
+#[derive(Debug, Clone, Copy, PartialEq, NodeClone)]
+enum Tamper {
+    AllowAllSchemes,
+    ReplaceWriter,
+    CloseOuterElements,
+}
+
+impl NodeContent for Tamper {
+    fn is_block(&self) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Format<CommonMarkWriter> for Tamper {
+    fn format(&self, _writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        Ok(())
+    }
+}
+
+impl CustomNode for Tamper {
+    fn html_render(&self, writer: &mut HtmlWriter) -> WriteResult<()> {
+        match self {
+            Tamper::AllowAllSchemes => {
+                writer.options_mut().allowed_url_schemes = None;
+                writer.options_mut().strict = false;
+            }
+            Tamper::ReplaceWriter => drop(std::mem::replace(writer, HtmlWriter::new())),
+            Tamper::CloseOuterElements => {
+                writer.end_tag("p")?;
+                writer.raw_html("</div>")?;
+            }
+        }
+        writer.start_tag("a")?;
+        writer.attribute("href", "javascript:alert(1)")?;
+        writer.attribute("onclick", "steal()")?;
+        writer.finish_tag()?;
+        writer.raw_html("<script>steal()</script><em>click</em>")?;
+        writer.end_tag("a")?;
+        Ok(())
+    }
+}
+
+fn tampered(tamper: Tamper, strict: bool) -> WriteResult<String> {
+    let options = HtmlWriterOptions {
+        strict,
+        trust_custom_nodes: false,
+        ..Default::default()
+    };
+    let node = Node::Paragraph(vec![Node::Custom(Box::new(tamper))]);
+    html(&node, options)
+}
+
+#[test]
+fn untrusted_html_changing_its_options_is_still_checked() {
+    assert_eq!(
+        tampered(Tamper::AllowAllSchemes, false).unwrap(),
+        "<p><a href=\"\">&lt;script&gt;steal()&lt;/script&gt;&lt;em&gt;click&lt;/em&gt;</a></p>\n"
+    );
+    assert!(tampered(Tamper::AllowAllSchemes, true).is_err());
+}
+
+#[test]
+fn untrusted_html_replacing_its_writer_is_still_checked() {
+    assert_eq!(
+        tampered(Tamper::ReplaceWriter, false).unwrap(),
+        "<p><a href=\"\">steal()<em>click</em></a></p>\n"
+    );
+    assert!(tampered(Tamper::ReplaceWriter, true).is_err());
+}
+
+#[test]
+fn untrusted_html_cannot_close_elements_around_it() {
+    assert_eq!(
+        tampered(Tamper::CloseOuterElements, false).unwrap(),
+        "<p>&lt;/div&gt;<a href=\"\">&lt;script&gt;steal()&lt;/script&gt;&lt;em&gt;click&lt;/em&gt;</a></p>\n"
+    );
+}
@@ -0,0 +1,121 @@
+use cmark_writer::ast::{Node, NodePath};
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriteError, HtmlWriter, HtmlWriterOptions, ToCommonMark, ToHtml,
+    WriteError, WriterOptions,
+};
+use std::error::Error;
+
+fn link(text: &str) -> Node {
+    Node::Link {
+        url: "/docs".into(),
+        title: None,
+        content: vec![Node::Text(text.into())],
+    }
+}
+
+fn write(node: &Node) -> WriteError {
+    node.to_commonmark(&mut CommonMarkWriter::new())
+        .unwrap_err()
+}
+
+#[test]
+fn content_errors_carry_path_and_snippet() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("fine".into())]),
+        Node::Paragraph(vec![Node::Text("see ".into()), link("the\n\"guide\"")]),
+    ]);
+    let error = write(&doc);
+    let WriteError::AtNode {
+        path,
+        snippet,
+        error: inner,
+    } = &error
+    else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!(*path, NodePath::root().child(1).child(1).child(0));
+    assert_eq!(snippet, r#"the\n\"guide\""#);
+    assert!(matches!(**inner, WriteError::NewlineInInlineElement(_)));
+    assert!(error.source().is_some());
+    assert!(error
+        .to_string()
+        .ends_with(r#"(at /1/1/0: "the\n\"guide\"")"#));
+}
+
+#[test]
+fn snippets_are_truncated_before_escaping() {
+    let text = format!("{}\n{}", "a".repeat(38), "b".repeat(10));
+    let error = write(&Node::Paragraph(vec![link(&text)]));
+    let WriteError::AtNode { snippet, .. } = &error else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!(*snippet, format!("{}\\nb…", "a".repeat(38)));
+}
+
+#[test]
+fn paths_are_relative_to_the_written_node() {
+    let heading = Node::heading(9, vec![Node::Text("Too deep".into())]);
+    let error = write(&heading);
+    assert_eq!(error.path(), Some(&NodePath::root()));
+    assert!(matches!(
+        error.without_location(),
+        WriteError::InvalidHeadingLevel(9)
+    ));
+}
+
+#[test]
+fn other_errors_are_not_located() {
+    let mut writer = CommonMarkWriter::with_options(WriterOptions {
+        list_marker: 'x',
+        ..Default::default()
+    });
+    let error = Node::Paragraph(vec![Node::Text("text".into())])
+        .to_commonmark(&mut writer)
+        .unwrap_err();
+    assert!(error.path().is_none());
+    assert!(matches!(error, WriteError::InvalidOptions(_)));
+}
+
+#[test]
+fn html_content_errors_carry_path_and_snippet() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("fine".into())]),
+        Node::BlockQuote(vec![Node::Paragraph(vec![Node::Link {
+            url: "javascript:alert(1)".into(),
+            title: None,
+            content: vec![Node::Text("click".into())],
+        }])]),
+    ]);
+    let error = doc.to_html(&mut HtmlWriter::new()).unwrap_err();
+    assert_eq!(
+        error.path(),
+        Some(&NodePath::root().child(1).child(0).child(0))
+    );
+    assert!(error.to_string().ends_with(r#"(at /1/0/0: "click")"#));
+    assert!(matches!(
+        error.without_location(),
+        WriteError::InvalidUrl(_)
+    ));
+
+    // Errors raised by the HTML writer itself are located too
+    let empty = Node::Document(vec![Node::Paragraph(vec![Node::Strong(vec![])])]);
+    let error = empty.to_html(&mut HtmlWriter::new()).unwrap_err();
+    assert_eq!(error.path(), Some(&NodePath::root().child(0).child(0)));
+    assert!(matches!(
+        error.without_location(),
+        WriteError::HtmlRenderingError(HtmlWriteError::InvalidStructure(_))
+    ));
+}
+
+#[test]
+fn html_writer_api_errors_keep_their_variant() {
+    let link = Node::Link {
+        url: "javascript:alert(1)".into(),
+        title: None,
+        content: vec![],
+    };
+    assert!(matches!(
+        link.to_html_with(&HtmlWriterOptions::default()),
+        Err(HtmlWriteError::InvalidUrl(_))
+    ));
+}
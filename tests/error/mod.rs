@@ -1,4 +1,5 @@
 // Unit tests for error types and macros
 
+mod error_locations;
 mod error_types;
 mod write_error_additional;
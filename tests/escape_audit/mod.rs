@@ -0,0 +1,6 @@
+// Data-driven tests of the escaping report
+//
+// `corpus.txt` holds one text per line and `report.txt` the report for it.
+// Run with `BLESS=1` to rewrite the report after changing the escaping rules.
+
+mod report;
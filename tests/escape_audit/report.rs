@@ -0,0 +1,86 @@
+use cmark_writer::escape_audit::{audit, EscapeContext, EscapeKind, EscapeReport};
+use cmark_writer::{EscapeStrategy, WriterOptions, WriterOptionsBuilder};
+
+const CORPUS: &str = include_str!("corpus.txt");
+
+/// Options audited, with the fixture holding the report for each
+fn cases() -> [(&'static str, WriterOptions, &'static str); 3] {
+    [
+        (
+            "report.txt",
+            WriterOptions::default(),
+            include_str!("report.txt"),
+        ),
+        (
+            "report_escape_special_chars.txt",
+            WriterOptionsBuilder::new()
+                .escape_special_chars(true)
+                .build(),
+            include_str!("report_escape_special_chars.txt"),
+        ),
+        (
+            "report_escape_always.txt",
+            WriterOptionsBuilder::new()
+                .escape_special_chars(true)
+                .escape_strategy(EscapeStrategy::Always)
+                .build(),
+            include_str!("report_escape_always.txt"),
+        ),
+    ]
+}
+
+fn corpus_report(options: &WriterOptions) -> EscapeReport {
+    let corpus: Vec<&str> = CORPUS.lines().collect();
+    audit(&corpus, options)
+}
+
+#[test]
+fn reports_match_fixtures() {
+    for (fixture, options, expected) in cases() {
+        let report = corpus_report(&options).to_string();
+        if std::env::var_os("BLESS").is_some() {
+            let path = format!(
+                "{}/tests/escape_audit/{}",
+                env!("CARGO_MANIFEST_DIR"),
+                fixture
+            );
+            std::fs::write(path, &report).unwrap();
+            continue;
+        }
+        assert_eq!(
+            report, expected,
+            "escaping changed; rerun with BLESS=1 if intended ({})",
+            fixture
+        );
+    }
+}
+
+#[test]
+fn every_case_renders_and_aligns() {
+    for (fixture, options, _) in cases() {
+        for case in &corpus_report(&options).cases {
+            assert!(
+                case.output.is_ok()
+                    && case
+                        .escapes
+                        .iter()
+                        .all(|escape| escape.kind != EscapeKind::Unaligned),
+                "{}: {} {:?} -> {:?}",
+                fixture,
+                case.context,
+                case.input,
+                case.output
+            );
+        }
+    }
+}
+
+#[test]
+fn block_starts_are_only_escaped_in_paragraphs() {
+    let report = corpus_report(&WriterOptions::default());
+    let paragraph = report.escaped_chars(EscapeContext::Paragraph);
+    for ch in ['#', '.', ')', '-', '+', '>'] {
+        assert!(paragraph.contains(&ch), "{:?}", ch);
+    }
+    assert!(report.escaped_chars(EscapeContext::Heading).is_empty());
+}
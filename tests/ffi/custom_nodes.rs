@@ -0,0 +1,216 @@
+use cmark_writer::ast::Node;
+use cmark_writer::error::WriteResult;
+use cmark_writer::ffi::{
+    cmark_writer_free_string, cmark_writer_last_error, cmark_writer_render_commonmark,
+    node_from_json, CustomNodeRegistry, Json,
+};
+use cmark_writer::{CommonMarkWriter, CustomNode, Format, NodeClone, NodeContent, ToCommonMark};
+use std::any::Any;
+use std::ffi::{CStr, CString};
+use std::ptr;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq, NodeClone)]
+struct Badge {
+    label: String,
+}
+
+impl NodeContent for Badge {
+    fn is_block(&self) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Format<CommonMarkWriter> for Badge {
+    fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+        writer.write_str(&format!("[{}]", self.label))
+    }
+}
+
+impl CustomNode for Badge {}
+
+fn badge(data: &Json) -> Result<Box<dyn CustomNode>, String> {
+    match data {
+        Json::String(label) => Ok(Box::new(Badge {
+            label: label.to_string(),
+        })),
+        other => Err(format!("expected a string, found {}", other.kind())),
+    }
+}
+
+fn custom(custom_type: &str, data: &str) -> String {
+    format!(
+        r#"{{"type": "Paragraph", "children": [{{"type": "Custom", "custom_type": "{}", "data": {}}}]}}"#,
+        custom_type, data
+    )
+}
+
+fn commonmark(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::new();
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn render_commonmark(ast: &str) -> Result<String, String> {
+    let ast = CString::new(ast).unwrap();
+    unsafe {
+        let output = cmark_writer_render_commonmark(ast.as_ptr(), ptr::null());
+        if output.is_null() {
+            return Err(CStr::from_ptr(cmark_writer_last_error())
+                .to_string_lossy()
+                .into_owned());
+        }
+        let text = CStr::from_ptr(output).to_str().unwrap().to_owned();
+        cmark_writer_free_string(output);
+        Ok(text)
+    }
+}
+
+#[test]
+fn scoped_registry_reads_custom_nodes() {
+    let registry = CustomNodeRegistry::new().with("ScopedBadge", badge);
+    let node = registry
+        .node_from_json(&custom("ScopedBadge", r#""new""#))
+        .unwrap();
+    assert_eq!(commonmark(&node), "[new]\n");
+
+    // The scope covers the C entry points too, and ends with the closure
+    let ast = custom("ScopedBadge", r#""beta""#);
+    assert_eq!(
+        registry.scope(|| render_commonmark(&ast)).unwrap(),
+        "[beta]\n"
+    );
+    assert!(render_commonmark(&ast).is_err());
+}
+
+#[test]
+fn global_registration_from_several_threads() {
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            thread::spawn(move || {
+                CustomNodeRegistry::register_global(format!("GlobalBadge{}", i), badge)
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let ast = custom("GlobalBadge2", r#""shared""#);
+    let output = thread::spawn(move || render_commonmark(&ast))
+        .join()
+        .unwrap();
+    assert_eq!(output.unwrap(), "[shared]\n");
+
+    for i in 0..4 {
+        assert!(CustomNodeRegistry::unregister_global(&format!(
+            "GlobalBadge{}",
+            i
+        )));
+    }
+    assert!(render_commonmark(&custom("GlobalBadge2", r#""gone""#)).is_err());
+}
+
+#[test]
+fn scope_overrides_global_registry() {
+    CustomNodeRegistry::register_global("OverriddenBadge", badge);
+    let registry = CustomNodeRegistry::new().with("OverriddenBadge", |_: &Json| {
+        Ok(Box::new(Badge {
+            label: "scoped".into(),
+        }) as Box<dyn CustomNode>)
+    });
+
+    let ast = custom("OverriddenBadge", r#""global""#);
+    assert_eq!(render_commonmark(&ast).unwrap(), "[global]\n");
+    assert_eq!(
+        registry.scope(|| render_commonmark(&ast)).unwrap(),
+        "[scoped]\n"
+    );
+    CustomNodeRegistry::unregister_global("OverriddenBadge");
+}
+
+#[test]
+fn unknown_types_list_registered_ones() {
+    let registry = CustomNodeRegistry::new()
+        .with("ListedBadge", badge)
+        .with("OtherBadge", badge);
+    let err = registry
+        .node_from_json(&custom("Missing", "null"))
+        .unwrap_err();
+    assert!(
+        err.starts_with("Custom: unknown custom node type `Missing` (registered: "),
+        "{err}"
+    );
+    assert!(err.contains("ListedBadge, OtherBadge"), "{err}");
+}
+
+#[test]
+fn deserializer_errors_name_the_type() {
+    let registry = CustomNodeRegistry::new().with("StrictBadge", badge);
+    let err = registry
+        .node_from_json(&custom("StrictBadge", "42"))
+        .unwrap_err();
+    assert_eq!(err, "Custom `StrictBadge`: expected a string, found number");
+}
+
+#[test]
+fn deserializers_can_read_nested_nodes() {
+    #[derive(Debug, Clone, PartialEq, NodeClone)]
+    struct Wrapper {
+        inner: Vec<Node>,
+    }
+
+    impl NodeContent for Wrapper {
+        fn is_block(&self) -> bool {
+            false
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    impl Format<CommonMarkWriter> for Wrapper {
+        fn format(&self, writer: &mut CommonMarkWriter) -> WriteResult<()> {
+            writer.write_str(&format!("{} nested", self.inner.len()))
+        }
+    }
+
+    impl CustomNode for Wrapper {}
+
+    let registry =
+        CustomNodeRegistry::new()
+            .with("NestedBadge", badge)
+            .with("Wrapper", |data: &Json| match data {
+                Json::Array(items) => Ok(Box::new(Wrapper {
+                    inner: items.iter().map(node_from_json).collect::<Result<_, _>>()?,
+                }) as Box<dyn CustomNode>),
+                other => Err(format!("expected an array, found {}", other.kind())),
+            });
+    let node = registry
+        .node_from_json(&custom(
+            "Wrapper",
+            r#"[{"type": "Custom", "custom_type": "NestedBadge", "data": "inner"}]"#,
+        ))
+        .unwrap();
+    let Node::Paragraph(children) = &node else {
+        panic!("expected a paragraph, got {node:?}");
+    };
+    let Node::Custom(wrapper) = &children[0] else {
+        panic!("expected a custom node, got {:?}", children[0]);
+    };
+    let wrapper = wrapper.as_any().downcast_ref::<Wrapper>().unwrap();
+    assert_eq!(commonmark(&wrapper.inner[0]), "[inner]\n");
+}
@@ -0,0 +1,5 @@
+// Tests for the C-compatible functions
+
+mod custom_nodes;
+mod render;
+mod schema;
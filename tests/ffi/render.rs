@@ -0,0 +1,109 @@
+use cmark_writer::ffi::*;
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+fn render(
+    f: unsafe extern "C" fn(
+        *const std::ffi::c_char,
+        *const std::ffi::c_char,
+    ) -> *mut std::ffi::c_char,
+    ast: &str,
+    options: Option<&str>,
+) -> Result<String, String> {
+    let ast = CString::new(ast).unwrap();
+    let options = options.map(|o| CString::new(o).unwrap());
+    unsafe {
+        let output = f(
+            ast.as_ptr(),
+            options.as_ref().map_or(ptr::null(), |o| o.as_ptr()),
+        );
+        if output.is_null() {
+            let error = cmark_writer_last_error();
+            assert!(!error.is_null());
+            return Err(CStr::from_ptr(error).to_string_lossy().into_owned());
+        }
+        let text = CStr::from_ptr(output).to_str().unwrap().to_owned();
+        cmark_writer_free_string(output);
+        assert!(cmark_writer_last_error().is_null());
+        Ok(text)
+    }
+}
+
+const DOCUMENT: &str = r#"{"type": "Document", "children": [
+    {"type": "Heading", "level": 2, "content": [{"type": "Text", "text": "Café \"menu\""}]},
+    {"type": "Paragraph", "children": [
+        {"type": "Strong", "children": [{"type": "Text", "text": "bold"}]},
+        {"type": "Text", "text": " and "},
+        {"type": "Link", "url": "https://example.com", "title": null,
+         "content": [{"type": "InlineCode", "text": "code"}]}
+    ]},
+    {"type": "OrderedList", "start": 3, "items": [
+        {"content": [{"type": "Paragraph", "children": [{"type": "Text", "text": "one"}]}]},
+        {"content": [{"type": "Paragraph", "children": [{"type": "Text", "text": "two"}]}]}
+    ]},
+    {"type": "CodeBlock", "language": "rust", "content": "fn main() {}\n"}
+]}"#;
+
+#[test]
+fn renders_commonmark() {
+    let output = render(
+        cmark_writer_render_commonmark,
+        DOCUMENT,
+        Some(r#"{"strong_char": "_"}"#),
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "## Café \"menu\"\n\n__bold__ and [`code`](https://example.com)\n\n3. one\n4. two\n\n```rust\nfn main() {}\n```\n"
+    );
+}
+
+#[test]
+fn renders_html() {
+    let output = render(cmark_writer_render_html, DOCUMENT, None).unwrap();
+    assert!(output.starts_with("<h2>Café \"menu\"</h2>\n<p><strong>bold</strong> and "));
+    assert!(output.contains("<ol start=\"3\">"));
+    assert!(output.contains("class=\"language-rust\""));
+
+    let output = render(
+        cmark_writer_render_html,
+        DOCUMENT,
+        Some(r#"{"code_block_language_class_prefix": null}"#),
+    )
+    .unwrap();
+    assert!(!output.contains("language-rust"));
+}
+
+#[test]
+fn reports_errors() {
+    let err = render(cmark_writer_render_commonmark, "{", None).unwrap_err();
+    assert!(err.starts_with("invalid JSON"), "{err}");
+
+    let err = render(cmark_writer_render_commonmark, r#"{"type": "Bogus"}"#, None).unwrap_err();
+    assert_eq!(err, "unsupported node type `Bogus`");
+
+    let err = render(
+        cmark_writer_render_commonmark,
+        r#"{"type": "Text", "text": "x"}"#,
+        Some(r#"{"stritc": true}"#),
+    )
+    .unwrap_err();
+    assert_eq!(err, "options: unknown field `stritc`");
+
+    let err = render(
+        cmark_writer_render_commonmark,
+        r#"{"type": "Heading", "level": 9, "content": [{"type": "Text", "text": "x"}]}"#,
+        None,
+    )
+    .unwrap_err();
+    assert!(err.contains("level"), "{err}");
+
+    let deep = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+    let err = render(cmark_writer_render_commonmark, &deep, None).unwrap_err();
+    assert!(err.contains("nesting too deep"), "{err}");
+
+    unsafe {
+        assert!(cmark_writer_render_html(ptr::null(), ptr::null()).is_null());
+        cmark_writer_free_string(ptr::null_mut());
+    }
+}
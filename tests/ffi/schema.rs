@@ -0,0 +1,146 @@
+use cmark_writer::ast::{DefinitionItem, Node};
+use cmark_writer::ffi::{document_from_json, upgrade_document, Json, SCHEMA_VERSION};
+
+fn read(json: &str) -> Result<Node, String> {
+    document_from_json(Json::parse(json).unwrap())
+}
+
+const CACHED: &str = r#"{"type": "Document", "children": [
+    {"type": "CodeBlock", "language": "rust", "content": "fn main() {}\n"},
+    {"type": "Table", "headers": [{"type": "Text", "text": "a"}], "rows": []}
+]}"#;
+
+#[test]
+fn unversioned_documents_are_version_1() {
+    let unversioned = read(CACHED).unwrap();
+    let versioned = read(&CACHED.replacen('{', r#"{"version": 1, "#, 1)).unwrap();
+    assert_eq!(unversioned, versioned);
+}
+
+#[test]
+fn version_member_is_removed() {
+    let json = Json::parse(r#"{"version": 2, "type": "Text", "text": "x"}"#).unwrap();
+    let upgraded = upgrade_document(json).unwrap();
+    assert_eq!(
+        upgraded,
+        Json::parse(r#"{"type": "Text", "text": "x"}"#).unwrap()
+    );
+}
+
+#[test]
+fn newer_and_invalid_versions_are_rejected() {
+    let newer = format!(
+        r#"{{"version": {}, "type": "Text", "text": "x"}}"#,
+        SCHEMA_VERSION + 1
+    );
+    assert!(read(&newer).unwrap_err().contains("newer"));
+    for version in ["0", "1.5", "\"2\""] {
+        let json = format!(r#"{{"version": {}, "type": "Text", "text": "x"}}"#, version);
+        assert!(read(&json).unwrap_err().contains("`version`"));
+    }
+}
+
+#[test]
+fn version_2_nodes() {
+    let doc = read(
+        r#"{"version": 2, "type": "Document", "children": [
+            {"type": "Paragraph", "children": [{"type": "FootnoteReference", "label": "n"}]},
+            {"type": "FootnoteDefinition", "label": "n",
+             "content": [{"type": "Paragraph", "children": [{"type": "Text", "text": "note"}]}]},
+            {"type": "DefinitionList", "items": [
+                {"term": [{"type": "Text", "text": "term"}],
+                 "definitions": [[{"type": "Paragraph", "children": [{"type": "Text", "text": "def"}]}]]}
+            ]}
+        ]}"#,
+    )
+    .unwrap();
+    let paragraph = |text: &str| Node::Paragraph(vec![Node::Text(text.into())]);
+    assert_eq!(
+        doc,
+        Node::Document(vec![
+            Node::Paragraph(vec![Node::FootnoteReference("n".into())]),
+            Node::FootnoteDefinition {
+                label: "n".into(),
+                content: vec![paragraph("note")],
+            },
+            Node::DefinitionList(vec![DefinitionItem::new(
+                vec![Node::Text("term".into())],
+                vec![vec![paragraph("def")]],
+            )]),
+        ])
+    );
+}
+
+const INLINE_HTML: &str = r#"{"type": "Document", "children": [
+    {"type": "HtmlBlock", "text": "<div>"},
+    {"type": "Paragraph", "children": [
+        {"type": "Text", "text": "Press "},
+        {"type": "Strong", "children": [{"type": "HtmlBlock", "text": "<kbd>"}]},
+        {"type": "Link", "url": "/", "content": [{"type": "HtmlBlock", "text": "<br>"}]}
+    ]}
+]}"#;
+
+#[test]
+fn version_2_inline_html_blocks_become_inline_html() {
+    let doc = read(&INLINE_HTML.replacen('{', r#"{"version": 2, "#, 1)).unwrap();
+    assert_eq!(
+        doc,
+        Node::Document(vec![
+            Node::HtmlBlock("<div>".into()),
+            Node::Paragraph(vec![
+                Node::Text("Press ".into()),
+                Node::Strong(vec![Node::HtmlInline("<kbd>".into())]),
+                Node::Link {
+                    url: "/".into(),
+                    title: None,
+                    content: vec![Node::HtmlInline("<br>".into())],
+                },
+            ]),
+        ])
+    );
+    // Unversioned documents are upgraded through every version
+    assert_eq!(read(INLINE_HTML).unwrap(), doc);
+}
+
+#[test]
+fn version_3_documents_are_read_as_is() {
+    let doc = read(&INLINE_HTML.replacen('{', r#"{"version": 3, "#, 1)).unwrap();
+    let Node::Document(children) = doc else {
+        panic!("expected a document");
+    };
+    assert_eq!(
+        children[1],
+        Node::Paragraph(vec![
+            Node::Text("Press ".into()),
+            Node::Strong(vec![Node::HtmlBlock("<kbd>".into())]),
+            Node::Link {
+                url: "/".into(),
+                title: None,
+                content: vec![Node::HtmlBlock("<br>".into())],
+            },
+        ])
+    );
+
+    let doc = read(
+        r#"{"version": 3, "type": "Paragraph", "children": [
+            {"type": "Math", "content": "x"},
+            {"type": "HtmlInline", "text": "<br>"},
+            {"type": "CrossRef", "target": "intro"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        doc,
+        Node::Paragraph(vec![
+            Node::Math {
+                display: false,
+                content: "x".into(),
+            },
+            Node::HtmlInline("<br>".into()),
+            Node::CrossRef {
+                target: "intro".into(),
+                text: None,
+            },
+        ])
+    );
+}
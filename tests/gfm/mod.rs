@@ -10,6 +10,7 @@
 
 mod formatting;
 mod tables;
+mod tagfilter;
 mod tasks;
 
 mod gfm_tests {
@@ -138,3 +138,50 @@ fn test_centered_table_re_export() {
         _ => panic!("Expected Table node"),
     }
 }
+
+#[test]
+fn test_delimiter_row_style() {
+    use cmark_writer::options::WriterOptionsBuilder;
+    use cmark_writer::{CommonMarkWriter, ToCommonMark};
+
+    let table = Node::Table {
+        headers: vec![
+            Node::Text("A".into()),
+            Node::Text("B".into()),
+            Node::Text("C".into()),
+        ],
+        alignments: vec![
+            TableAlignment::Left,
+            TableAlignment::Center,
+            TableAlignment::Right,
+        ],
+        rows: vec![vec![
+            Node::Text("1".into()),
+            Node::Text("2".into()),
+            Node::Text("3".into()),
+        ]],
+    };
+
+    let render = |builder: WriterOptionsBuilder| {
+        let mut writer = CommonMarkWriter::with_options(builder.enable_gfm().build());
+        table.to_commonmark(&mut writer).unwrap();
+        writer.into_string().trim_end().to_string()
+    };
+
+    assert_eq!(
+        render(WriterOptionsBuilder::new()),
+        "| A | B | C |\n| :--- | :---: | ---: |\n| 1 | 2 | 3 |"
+    );
+    assert_eq!(
+        render(
+            WriterOptionsBuilder::new()
+                .table_delimiter_padding(false)
+                .table_delimiter_min_dashes(1)
+        ),
+        "| A | B | C |\n|:-|:-:|-:|\n| 1 | 2 | 3 |"
+    );
+    assert_eq!(
+        render(WriterOptionsBuilder::new().table_delimiter_min_dashes(0)),
+        "| A | B | C |\n| :- | :-: | -: |\n| 1 | 2 | 3 |"
+    );
+}
@@ -0,0 +1,129 @@
+//! Tests for GFM disallowed tag presets and the tagfilter
+
+#![cfg(feature = "gfm")]
+
+use cmark_writer::ast::{HtmlElement, Node};
+use cmark_writer::gfm::{
+    disallowed_tags, disallowed_tags_with, filter_disallowed_tags, is_tag_disallowed,
+    DisallowedTags, DISALLOWED_TAGS,
+};
+use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml, WriterOptionsBuilder};
+
+fn render_html(node: &Node, options: HtmlWriterOptions) -> String {
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn default_options_use_preset() {
+    let options = WriterOptionsBuilder::new().enable_gfm().build();
+    assert_eq!(options.gfm_disallowed_html_tags, disallowed_tags());
+    assert_eq!(disallowed_tags().len(), DISALLOWED_TAGS.len());
+}
+
+#[test]
+fn extend_skips_existing_tags() {
+    let tags = disallowed_tags_with(["Script", "object"]);
+    assert_eq!(tags.len(), DISALLOWED_TAGS.len() + 1);
+    assert!(is_tag_disallowed("OBJECT", &tags));
+
+    let options = WriterOptionsBuilder::new()
+        .enable_gfm()
+        .extend_gfm_disallowed_html_tags(["embed", "EMBED"])
+        .build();
+    assert_eq!(
+        options.gfm_disallowed_html_tags.len(),
+        DISALLOWED_TAGS.len() + 1
+    );
+
+    let html_options = HtmlWriterOptions::default()
+        .with_gfm_disallowed_tags(vec!["a".into()])
+        .with_additional_gfm_disallowed_tags(["A", "b"]);
+    assert_eq!(html_options.gfm_disallowed_html_tags, ["a", "b"]);
+}
+
+#[test]
+fn element_tags_match_case_insensitively() {
+    let element = Node::HtmlElement(HtmlElement::new("SCRIPT").with_children(vec![]));
+    let options = HtmlWriterOptions::default()
+        .with_gfm_enabled(true)
+        .with_gfm_disallowed_tags(disallowed_tags());
+    assert!(render_html(&element, options).starts_with("&lt;SCRIPT"));
+}
+
+#[test]
+fn tagfilter_only_matches_whole_tag_names() {
+    let html = "<script src=x></SCRIPT><scripts><style/><textarea\n>";
+    assert_eq!(
+        filter_disallowed_tags(html, DISALLOWED_TAGS),
+        "&lt;script src=x>&lt;/SCRIPT><scripts>&lt;style/>&lt;textarea\n>"
+    );
+    assert!(matches!(
+        filter_disallowed_tags("<div>ok</div>", DISALLOWED_TAGS),
+        std::borrow::Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn html_blocks_are_filtered_when_gfm_is_enabled() {
+    let block = Node::HtmlBlock("<div><Script>alert(1)</script></div>".into());
+    let options = HtmlWriterOptions::default().with_gfm_disallowed_tags(disallowed_tags());
+
+    assert_eq!(
+        render_html(&block, options.clone().with_gfm_enabled(true)),
+        "<div>&lt;Script>alert(1)&lt;/script></div>\n"
+    );
+    assert_eq!(
+        render_html(&block, options),
+        "<div><Script>alert(1)</script></div>\n"
+    );
+}
+
+#[test]
+fn disallowed_tags_keep_insertion_order_without_duplicates() {
+    let mut tags = DisallowedTags::from(["b", "A"]);
+    assert!(tags.insert("c"));
+    assert!(!tags.insert("a"));
+    assert!(!tags.insert("B"));
+    assert_eq!(tags, ["b", "A", "c"]);
+
+    assert!(tags.remove("a"));
+    assert!(!tags.remove("a"));
+    assert_eq!(tags, ["b", "c"]);
+    assert!(tags.contains("C"));
+    assert!(!tags.contains("A"));
+
+    // Re-adding a removed tag appends it
+    tags.insert("a");
+    assert_eq!(tags.as_slice(), ["b", "c", "a"]);
+}
+
+#[test]
+fn removing_tags_through_options() {
+    let options = WriterOptionsBuilder::new()
+        .enable_gfm()
+        .remove_gfm_disallowed_html_tags(["STYLE", "title"])
+        .build();
+    assert_eq!(
+        options.gfm_disallowed_html_tags.len(),
+        DISALLOWED_TAGS.len() - 2
+    );
+    assert!(!options.gfm_disallowed_html_tags.contains("style"));
+
+    let html_options = HtmlWriterOptions::default()
+        .with_gfm_enabled(true)
+        .with_gfm_disallowed_tags(DisallowedTags::gfm())
+        .without_gfm_disallowed_tags(["script"]);
+    let element = Node::HtmlElement(HtmlElement::new("script").with_children(vec![]));
+    assert_eq!(
+        render_html(&element, html_options.clone()),
+        "<script></script>"
+    );
+
+    let block = Node::HtmlBlock("<script></script><Style>".into());
+    assert_eq!(
+        render_html(&block, html_options),
+        "<script></script>&lt;Style>\n"
+    );
+}
@@ -0,0 +1,133 @@
+use cmark_writer::ast::{HtmlElement, Node};
+use cmark_writer::writer::html::EmailProfile;
+use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml};
+
+fn render(node: &Node, profile: EmailProfile) -> String {
+    let mut writer = HtmlWriter::with_options(HtmlWriterOptions::default().with_email(profile));
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+#[test]
+fn tags_get_inline_styles_and_lose_classes() {
+    let mut profile = EmailProfile::default();
+    profile.tag_styles.insert("p".into(), "margin: 0".into());
+    profile
+        .class_styles
+        .insert("note".into(), "color: #57606a;".into());
+    let element = HtmlElement::new("span")
+        .class("note")
+        .class("unknown")
+        .with_attribute("style", "font-weight: bold")
+        .child(text("hi"));
+    let html = render(&Node::Paragraph(vec![Node::HtmlElement(element)]), profile);
+    assert_eq!(
+        html,
+        "<p style=\"margin: 0;\"><span style=\"color: #57606a; font-weight: bold;\">hi</span></p>\n"
+    );
+}
+
+#[test]
+fn code_blocks_drop_language_classes() {
+    let html = render(
+        &Node::code_block(Some("rust".into()), "fn main() {}".into()),
+        EmailProfile::default(),
+    );
+    assert!(!html.contains("class="));
+    assert!(html.starts_with("<pre style=\"background-color: #f6f8fa;"));
+}
+
+#[test]
+fn tables_use_fixed_layout_with_widths() {
+    let table = Node::Table {
+        headers: vec![text("A"), text("B"), text("C")],
+        #[cfg(feature = "gfm")]
+        alignments: vec![Default::default(); 3],
+        rows: vec![vec![text("1"), text("2"), text("3")]],
+    };
+    let html = render(&table, EmailProfile::default());
+    assert!(html.starts_with(
+        "<table width=\"100%\" style=\"border-collapse: collapse; table-layout: fixed;\">"
+    ));
+    assert_eq!(html.matches("<th width=\"33%\"").count(), 3);
+    assert!(!html.contains("<td width="));
+}
+
+#[test]
+fn image_widths_are_capped() {
+    let profile = EmailProfile {
+        max_image_width: 480,
+        ..Default::default()
+    };
+    let wide = HtmlElement::new("img")
+        .with_attribute("src", "https://example.com/a.png")
+        .with_attribute("width", "1200")
+        .self_closing(true);
+    let narrow = HtmlElement::new("img")
+        .with_attribute("src", "https://example.com/b.png")
+        .with_attribute("width", "200")
+        .self_closing(true);
+    let html = render(
+        &Node::Paragraph(vec![Node::HtmlElement(wide), Node::HtmlElement(narrow)]),
+        profile.clone(),
+    );
+    assert!(html.contains("a.png\" width=\"480\""));
+    assert!(html.contains("b.png\" width=\"200\""));
+
+    let image = Node::Image {
+        url: "https://example.com/c.png".into(),
+        title: None,
+        alt: vec![text("c")],
+    };
+    // The image keeps its own width up to the cap
+    let html = render(&image, profile);
+    assert!(!html.contains("width=\""));
+    assert!(html.ends_with("border: 0; max-width: 480px;\" />"));
+}
+
+#[test]
+fn unsupported_tags_are_stripped() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::HtmlElement(
+            HtmlElement::new("video").child(text("fallback")),
+        )]),
+        Node::HtmlElement(HtmlElement::new("script").child(text("track()"))),
+        Node::HtmlBlock("<form action=\"/x\"><b>Sign up</b><input name=\"q\"></form>".into()),
+    ]);
+    let html = render(&doc, EmailProfile::default());
+    assert_eq!(html, "<p>fallback</p>\n<b>Sign up</b>\n");
+}
+
+#[test]
+fn raw_html_drops_hidden_content() {
+    let doc = Node::Document(vec![
+        Node::HtmlBlock("<p>a<SCRIPT src=\"x.js\">track(\"</b>\")</Script>b</p>".into()),
+        Node::HtmlBlock("<style>p { color: red }</style><p>c</p>".into()),
+        Node::HtmlBlock("<p>d</p><noscript>never closed".into()),
+    ]);
+    let html = render(&doc, EmailProfile::default());
+    assert_eq!(html, "<p>ab</p>\n<p>c</p>\n<p>d</p>\n");
+
+    let paragraph = Node::Paragraph(vec![
+        Node::HtmlInline("<video>".into()),
+        text("clip"),
+        Node::HtmlInline("</video>".into()),
+        Node::HtmlInline("<b>".into()),
+        text("!"),
+        Node::HtmlInline("</b>".into()),
+    ]);
+    let html = render(&paragraph, EmailProfile::default());
+    assert_eq!(html, "<p>clip<b>!</b></p>\n");
+}
+
+#[test]
+fn output_is_unchanged_without_profile() {
+    let element = HtmlElement::new("video").class("clip").child(text("x"));
+    let mut writer = HtmlWriter::new();
+    Node::HtmlElement(element).to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<video class=\"clip\">x</video>");
+}
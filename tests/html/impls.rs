@@ -8,7 +8,8 @@ fn test_html_writer_options() {
         #[cfg(feature = "gfm")]
         enable_gfm: true,
         #[cfg(feature = "gfm")]
-        gfm_disallowed_html_tags: vec!["script".into()],
+        gfm_disallowed_html_tags: ["script"].into(),
+        ..Default::default()
     };
 
     let mut writer = HtmlWriter::with_options(options);
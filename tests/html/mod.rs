@@ -1,5 +1,10 @@
 mod custom_options;
+mod email;
 mod error;
 mod impls;
 mod options;
+mod ordinals;
 mod render;
+mod string_buffer;
+mod tree;
+mod url_schemes;
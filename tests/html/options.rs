@@ -1,3 +1,4 @@
+use cmark_writer::writer::html::{Decoding, ExternalLinkPolicy, Loading};
 use cmark_writer::{
     CommonMarkWriter, HtmlAttribute, HtmlElement, HtmlWriter, HtmlWriterOptions, Node,
     ToCommonMark, ToHtml, WriterOptions,
@@ -15,15 +16,9 @@ fn test_derived_html_options() {
     let mut cmark_writer = CommonMarkWriter::with_options(cmark_options);
 
     // 创建一个包含 HTML 元素的节点
-    let html_element = HtmlElement {
-        tag: "div".into(),
-        attributes: vec![HtmlAttribute {
-            name: "class".into(),
-            value: "container".into(),
-        }],
-        children: vec![Node::Text("Content in HTML element".into())],
-        self_closing: false,
-    };
+    let html_element = HtmlElement::new("div")
+        .class("container")
+        .child(Node::Text("Content in HTML element".into()));
 
     // 使用 CommonMarkWriter 写入 HTML 元素
     Node::HtmlElement(html_element)
@@ -108,13 +103,126 @@ fn test_code_block_language_class() {
     assert!(!output_without_prefix.contains("class="));
 }
 
+#[test]
+fn test_code_block_default_language_and_aliases() {
+    let options = HtmlWriterOptions::default()
+        .with_default_code_language(Some("text"))
+        .with_code_language_alias("js", "javascript");
+
+    let render = |language: Option<&str>| {
+        let code_block = Node::CodeBlock {
+            language: language.map(Into::into),
+            content: "x".into(),
+            block_type: Default::default(),
+        };
+        let mut writer = HtmlWriter::with_options(options.clone());
+        code_block.to_html(&mut writer).unwrap();
+        writer.into_string()
+    };
+
+    assert_eq!(
+        render(Some("js")),
+        "<pre><code class=\"language-javascript\">x</code></pre>\n"
+    );
+    assert_eq!(
+        render(Some("rust")),
+        "<pre><code class=\"language-rust\">x</code></pre>\n"
+    );
+    assert_eq!(
+        render(None),
+        "<pre><code class=\"language-text\">x</code></pre>\n"
+    );
+    assert_eq!(
+        render(Some("")),
+        "<pre><code class=\"language-text\">x</code></pre>\n"
+    );
+
+    // Aliases also apply to the default language
+    let options = HtmlWriterOptions::default()
+        .with_default_code_language(Some("js"))
+        .with_code_language_alias("js", "javascript");
+    assert_eq!(options.resolve_code_language(None), Some("javascript"));
+    assert_eq!(
+        HtmlWriterOptions::default().resolve_code_language(None),
+        None
+    );
+}
+
+#[test]
+fn test_img_loading_and_decoding() {
+    let image = Node::Image {
+        url: "cat.png".into(),
+        title: None,
+        alt: vec![Node::Text("Cat".into())],
+    };
+
+    let mut writer = HtmlWriter::new();
+    image.to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<img src=\"cat.png\" alt=\"Cat\" />");
+
+    let options = HtmlWriterOptions::default()
+        .with_img_loading(Some(Loading::Lazy))
+        .with_img_decoding(Some(Decoding::Async));
+    let mut writer = HtmlWriter::with_options(options);
+    image.to_html(&mut writer).unwrap();
+    assert_eq!(
+        writer.into_string(),
+        "<img src=\"cat.png\" alt=\"Cat\" loading=\"lazy\" decoding=\"async\" />"
+    );
+}
+
+#[test]
+fn test_external_link_policy() {
+    let options = HtmlWriterOptions::default().with_external_link_policy(Some(
+        ExternalLinkPolicy::with_internal_hosts(["example.com"]),
+    ));
+    let render = |url: &str| {
+        let link = Node::Link {
+            url: url.into(),
+            title: None,
+            content: vec![Node::Text("x".into())],
+        };
+        let mut writer = HtmlWriter::with_options(options.clone());
+        link.to_html(&mut writer).unwrap();
+        writer.into_string()
+    };
+
+    assert_eq!(
+        render("https://other.org/page"),
+        "<a href=\"https://other.org/page\" rel=\"nofollow noopener\" target=\"_blank\">x</a>"
+    );
+    assert_eq!(
+        render("https://EXAMPLE.com/page"),
+        "<a href=\"https://EXAMPLE.com/page\">x</a>"
+    );
+    assert_eq!(render("/docs"), "<a href=\"/docs\">x</a>");
+    assert_eq!(render("#top"), "<a href=\"#top\">x</a>");
+
+    let options =
+        HtmlWriterOptions::default().with_external_link_policy(Some(ExternalLinkPolicy {
+            target: None,
+            ..Default::default()
+        }));
+    let mut writer = HtmlWriter::with_options(options);
+    Node::Autolink {
+        url: "http://other.org".into(),
+        is_email: false,
+    }
+    .to_html(&mut writer)
+    .unwrap();
+    assert_eq!(
+        writer.into_string(),
+        "<a href=\"http://other.org\" rel=\"nofollow noopener\">http://other.org</a>"
+    );
+}
+
 #[cfg(feature = "gfm")]
 #[test]
 fn test_gfm_html_filtering() {
     // 测试 GFM HTML 过滤功能
     let options = HtmlWriterOptions {
         enable_gfm: true,
-        gfm_disallowed_html_tags: vec!["script".into()],
+        gfm_disallowed_html_tags: ["script"].into(),
         ..Default::default()
     };
 
@@ -175,3 +283,61 @@ fn test_nested_html_structures() {
     assert!(output.contains("<p>Paragraph inside HTML</p>"));
     assert!(output.contains("</div></div>"));
 }
+
+#[test]
+fn test_html_options_attribute_normalization() {
+    let element = Node::HtmlElement(
+        HtmlElement::new("span")
+            .with_attribute("title", "x")
+            .with_attribute("class", "a")
+            .with_attribute("class", "b"),
+    );
+
+    let render = |options: HtmlWriterOptions| {
+        let mut writer = HtmlWriter::with_options(options);
+        element.to_html(&mut writer).unwrap();
+        writer.into_string()
+    };
+
+    assert_eq!(
+        render(HtmlWriterOptions::default()),
+        "<span title=\"x\" class=\"a\" class=\"b\"></span>"
+    );
+    assert_eq!(
+        render(HtmlWriterOptions::default().with_deduplicated_attributes(true)),
+        "<span title=\"x\" class=\"b\"></span>"
+    );
+    assert_eq!(
+        render(
+            HtmlWriterOptions::default()
+                .with_deduplicated_attributes(true)
+                .with_sorted_attributes(true)
+        ),
+        "<span class=\"b\" title=\"x\"></span>"
+    );
+}
+
+#[test]
+fn test_quote_escaping_in_text() {
+    let paragraph = Node::Paragraph(vec![Node::Link {
+        url: "/search?q=\"x\"".into(),
+        title: Some("it's \"quoted\"".into()),
+        content: vec![Node::Text("it's \"quoted\" & <b>".into())],
+    }]);
+
+    let render = |options: HtmlWriterOptions| {
+        let mut writer = HtmlWriter::with_options(options);
+        paragraph.to_html(&mut writer).unwrap();
+        writer.into_string()
+    };
+
+    // Attribute values always escape quotes; text only does when asked to
+    assert_eq!(
+        render(HtmlWriterOptions::default()),
+        "<p><a href=\"/search?q=&quot;x&quot;\" title=\"it&#x27;s &quot;quoted&quot;\">it's \"quoted\" &amp; &lt;b&gt;</a></p>\n"
+    );
+    assert_eq!(
+        render(HtmlWriterOptions::default().with_escape_quotes_in_text(true)),
+        "<p><a href=\"/search?q=&quot;x&quot;\" title=\"it&#x27;s &quot;quoted&quot;\">it&#x27;s &quot;quoted&quot; &amp; &lt;b&gt;</a></p>\n"
+    );
+}
@@ -0,0 +1,96 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriter, HtmlWriterOptions, Numerals, OrdinalHook, ToCommonMark, ToHtml,
+};
+use ecow::EcoString;
+
+fn item(text: &str) -> ListItem {
+    ListItem::Ordered {
+        number: None,
+        content: vec![Node::Text(text.into())],
+    }
+}
+
+fn render(options: HtmlWriterOptions, node: &Node) -> String {
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn counter_style_is_set_on_the_list() {
+    let list = Node::OrderedList {
+        start: 3,
+        items: vec![item("a"), item("b")],
+    };
+    let options =
+        HtmlWriterOptions::default().with_ordinals(OrdinalHook::new(Numerals::ArabicIndic));
+    assert_eq!(
+        render(options, &list),
+        "<ol start=\"3\" style=\"list-style-type: arabic-indic\">\n<li>a</li>\n<li>b</li>\n</ol>\n"
+    );
+}
+
+#[test]
+fn markers_without_counter_style_are_set_on_each_item() {
+    let list = Node::OrderedList {
+        start: 9,
+        items: vec![item("a"), item("b")],
+    };
+    let options = HtmlWriterOptions::default().with_ordinals(OrdinalHook::new(Numerals::FullWidth));
+    assert_eq!(
+        render(options, &list),
+        "<ol start=\"9\">\n\
+         <li style=\"list-style-type: &#x27;９． &#x27;\">a</li>\n\
+         <li style=\"list-style-type: &#x27;１０． &#x27;\">b</li>\n\
+         </ol>\n"
+    );
+}
+
+#[test]
+fn closure_formatters_are_quoted_as_css_strings() {
+    let list = Node::OrderedList {
+        start: 1,
+        items: vec![item("a")],
+    };
+    let hook = OrdinalHook::new(|number: u32| EcoString::from(format!("'{}\\", number)));
+    let options = HtmlWriterOptions::default().with_ordinals(hook);
+    assert_eq!(
+        render(options, &list),
+        "<ol>\n<li style=\"list-style-type: &#x27;\\&#x27;1\\\\. &#x27;\">a</li>\n</ol>\n"
+    );
+}
+
+#[test]
+fn unordered_lists_are_unaffected() {
+    let list = Node::UnorderedList(vec![ListItem::Unordered {
+        content: vec![Node::Text("a".into())],
+    }]);
+    let options = HtmlWriterOptions::default().with_ordinals(OrdinalHook::new(Numerals::FullWidth));
+    assert_eq!(render(options, &list), "<ul>\n<li>a</li>\n</ul>\n");
+}
+
+#[test]
+fn marker_width_counts_display_columns() {
+    assert_eq!(OrdinalHook::new(Numerals::Ascii).marker(42), "42.");
+    assert_eq!(OrdinalHook::new(Numerals::ArabicIndic).marker(42), "٤٢.");
+    assert_eq!(OrdinalHook::new(Numerals::ArabicIndic).marker_width(42), 3);
+    assert_eq!(OrdinalHook::new(Numerals::FullWidth).marker_width(42), 6);
+}
+
+#[test]
+fn commonmark_keeps_ascii_markers() {
+    let list = Node::OrderedList {
+        start: 10,
+        items: vec![ListItem::Ordered {
+            number: None,
+            content: vec![
+                Node::Paragraph(vec![Node::Text("a".into())]),
+                Node::Paragraph(vec![Node::Text("b".into())]),
+            ],
+        }],
+    };
+    let mut writer = CommonMarkWriter::new();
+    list.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "10. a\n    \n    b\n");
+}
@@ -4,6 +4,8 @@ mod tests {
     use cmark_writer::ast::{HtmlElement, ListItem, Node};
     #[cfg(feature = "gfm")]
     use cmark_writer::ast::{TableAlignment, TaskListStatus};
+    #[cfg(feature = "gfm")]
+    use cmark_writer::writer::html::TaskListClasses;
     use cmark_writer::writer::HtmlWriterOptions;
     use ecow::EcoString;
     use log::LevelFilter;
@@ -45,6 +47,7 @@ mod tests {
             level: 1,
             content: vec![Node::Text("Title".into())],
             heading_type: Default::default(),
+            custom_id: None,
         };
         let expected_html = "<h1>Title</h1>\n";
         assert_eq!(render_node_to_html_default(&node).unwrap(), expected_html);
@@ -107,6 +110,7 @@ mod tests {
         let options = HtmlWriterOptions {
             code_block_language_class_prefix: Some("lang-".into()),
             strict: false,
+            ..Default::default()
         };
         let expected_html = "<pre><code class=\"lang-python\">print(\"Hello\")</code></pre>\n";
         assert_eq!(render_node_to_html(&node, &options).unwrap(), expected_html);
@@ -129,6 +133,7 @@ mod tests {
         let options = HtmlWriterOptions {
             code_block_language_class_prefix: None,
             strict: false,
+            ..Default::default()
         };
         // No class attribute should be present if prefix is None
         let expected_html = "<pre><code>let _ = 1;</code></pre>\n";
@@ -268,8 +273,23 @@ mod tests {
             enable_gfm: true,
             ..HtmlWriterOptions::default()
         };
-        let expected_html = "<ul>\n<li class=\"task-list-item\"><input type=\"checkbox\" disabled=\"\" /> To do</li>\n<li class=\"task-list-item task-list-item-checked\"><input type=\"checkbox\" disabled=\"\" checked=\"\" /> Done</li>\n</ul>\n";
+        let expected_html = "<ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" /> To do</li>\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" checked=\"\" /> Done</li>\n</ul>\n";
         assert_eq!(render_node_to_html(&node, &options).unwrap(), expected_html);
+
+        let legacy = options.with_task_list_classes(TaskListClasses::legacy());
+        let expected_html = "<ul>\n<li class=\"task-list-item\"><input type=\"checkbox\" disabled=\"\" /> To do</li>\n<li class=\"task-list-item task-list-item-checked\"><input type=\"checkbox\" disabled=\"\" checked=\"\" /> Done</li>\n</ul>\n";
+        assert_eq!(render_node_to_html(&node, &legacy).unwrap(), expected_html);
+
+        let custom = HtmlWriterOptions::default()
+            .with_gfm_enabled(true)
+            .with_task_list_classes(TaskListClasses {
+                list: None,
+                item: None,
+                checked_item: Some("done".into()),
+                checkbox: None,
+            });
+        let expected_html = "<ul>\n<li><input type=\"checkbox\" disabled=\"\" /> To do</li>\n<li class=\"done\"><input type=\"checkbox\" disabled=\"\" checked=\"\" /> Done</li>\n</ul>\n";
+        assert_eq!(render_node_to_html(&node, &custom).unwrap(), expected_html);
     }
 
     #[test]
@@ -473,7 +493,7 @@ mod tests {
         let node = Node::HtmlElement(element);
         let options = HtmlWriterOptions {
             enable_gfm: true,
-            gfm_disallowed_html_tags: vec!["script".into()],
+            gfm_disallowed_html_tags: ["script"].into(),
             ..HtmlWriterOptions::default()
         };
 
@@ -0,0 +1,36 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::FinalNewline;
+use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml};
+
+#[test]
+fn appends_to_existing_content() {
+    let mut page = String::from("<body>");
+    let mut writer = HtmlWriter::from_string_buffer(&mut page);
+    Node::heading(1, vec![Node::Text("Title".into())])
+        .to_html(&mut writer)
+        .unwrap();
+    writer.start_tag("hr").unwrap();
+    writer.finish_self_closing_tag().unwrap();
+    writer.into_inner();
+    assert_eq!(page, "<body><h1>Title</h1>\n<hr />");
+}
+
+#[test]
+fn uses_the_given_options() {
+    let mut buffer = String::new();
+    let options = HtmlWriterOptions::default().with_final_newline(FinalNewline::Never);
+    let mut writer = HtmlWriter::from_string_buffer_with_options(&mut buffer, options);
+    Node::Paragraph(vec![Node::Text("a & b".into())])
+        .to_html(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_inner(), "<p>a &amp; b</p>");
+}
+
+#[test]
+fn nothing_is_written_until_into_inner() {
+    let mut buffer = String::from("kept");
+    let mut writer = HtmlWriter::from_string_buffer(&mut buffer);
+    Node::Text("dropped".into()).to_html(&mut writer).unwrap();
+    drop(writer);
+    assert_eq!(buffer, "kept");
+}
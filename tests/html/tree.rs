@@ -0,0 +1,99 @@
+#![cfg(feature = "html-tree")]
+
+use cmark_writer::ast::{HtmlElement, ListItem, Node};
+use cmark_writer::writer::html::tree::{self, TreeNode};
+use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml};
+
+#[test]
+fn renders_nested_structure() {
+    let doc = Node::Document(vec![
+        Node::heading(2, vec![Node::Text("Title".into())]),
+        Node::UnorderedList(vec![
+            ListItem::Unordered {
+                content: vec![Node::Text("one".into())],
+            },
+            ListItem::Unordered {
+                content: vec![Node::Emphasis(vec![Node::Text("two".into())])],
+            },
+        ]),
+    ]);
+
+    let nodes = tree::render_tree(&doc, &HtmlWriterOptions::default()).unwrap();
+
+    let h2 = tree::find_all(&nodes, "h2");
+    assert_eq!(h2.len(), 1);
+    assert_eq!(h2[0].text_content(), "Title");
+
+    let ul = tree::find_all(&nodes, "ul");
+    assert_eq!(ul[0].child_elements().count(), 2);
+    assert_eq!(ul[0].find_all("em")[0].text_content(), "two");
+}
+
+#[test]
+fn decodes_text_and_attributes() {
+    let link = Node::Link {
+        url: "https://example.com/?a=1&b=2".into(),
+        title: Some("A & B".into()),
+        content: vec![Node::Text("<tag> & text".into())],
+    };
+
+    let nodes = tree::render_tree(&link, &HtmlWriterOptions::default()).unwrap();
+    let a = nodes[0].as_element().unwrap();
+    assert_eq!(a.tag, "a");
+    assert_eq!(a.attr("href"), Some("https://example.com/?a=1&b=2"));
+    assert_eq!(a.attr("TITLE"), Some("A & B"));
+    assert_eq!(a.text_content(), "<tag> & text");
+}
+
+#[test]
+fn void_and_self_closing_elements() {
+    let para = Node::Paragraph(vec![
+        Node::Text("a".into()),
+        Node::HardBreak,
+        Node::Image {
+            url: "x.png".into(),
+            title: None,
+            alt: vec![Node::Text("pic".into())],
+        },
+        Node::HtmlElement(HtmlElement::new("span").with_children(vec![Node::Text("b".into())])),
+    ]);
+
+    let mut writer = HtmlWriter::new();
+    para.to_html(&mut writer).unwrap();
+    let nodes = writer.into_tree();
+
+    let p = nodes[0].as_element().unwrap();
+    let tags: Vec<_> = p.child_elements().map(|e| e.tag.as_str()).collect();
+    assert_eq!(tags, ["br", "img", "span"]);
+    assert_eq!(tree::find_all(&nodes, "img")[0].attr("alt"), Some("pic"));
+}
+
+#[test]
+fn parse_fragment_is_lenient() {
+    let nodes = tree::parse_fragment("<!-- c --><div><p>open</div></b>tail");
+    assert_eq!(nodes[0], TreeNode::Comment(" c ".into()));
+    let div = nodes[1].as_element().unwrap();
+    assert_eq!(div.find_all("p")[0].text_content(), "open");
+    assert_eq!(nodes[2], TreeNode::Text("tail".into()));
+}
+
+#[cfg(feature = "gfm")]
+#[test]
+fn table_alignment_is_queryable() {
+    use cmark_writer::ast::TableAlignment;
+
+    let table = Node::table_with_alignment(
+        vec![Node::Text("A".into()), Node::Text("B".into())],
+        vec![TableAlignment::Left, TableAlignment::Center],
+        vec![vec![Node::Text("1".into()), Node::Text("2".into())]],
+    );
+    let options = HtmlWriterOptions::default().with_gfm_enabled(true);
+    let nodes = tree::render_tree(&table, &options).unwrap();
+
+    let centered: Vec<_> = tree::find_all(&nodes, "th")
+        .into_iter()
+        .filter(|th| th.attr("style") == Some("text-align: center;"))
+        .collect();
+    assert_eq!(centered.len(), 1);
+    assert_eq!(centered[0].text_content(), "B");
+}
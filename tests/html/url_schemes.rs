@@ -0,0 +1,122 @@
+use cmark_writer::ast::{HtmlElement, Node};
+use cmark_writer::{HtmlWriteError, HtmlWriter, HtmlWriterOptions, ToHtml, WriteError};
+
+fn link(url: &str) -> Node {
+    Node::Link {
+        url: url.into(),
+        title: None,
+        content: vec![Node::Text("x".into())],
+    }
+}
+
+fn image(url: &str) -> Node {
+    Node::Image {
+        url: url.into(),
+        title: None,
+        alt: vec![],
+    }
+}
+
+fn render(node: &Node, options: HtmlWriterOptions) -> Result<String, WriteError> {
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+fn lenient() -> HtmlWriterOptions {
+    HtmlWriterOptions::default().with_strict(false)
+}
+
+#[test]
+fn allowed_and_relative_urls_are_kept() {
+    for url in [
+        "https://example.com",
+        "HTTP://example.com",
+        "mailto:me@example.com",
+        "/docs/page",
+        "#section",
+        "docs/a:b",
+        "?q=1",
+    ] {
+        let html = render(&link(url), HtmlWriterOptions::default()).unwrap();
+        assert!(html.contains(&format!("href=\"{}\"", url)), "{}", html);
+    }
+}
+
+#[test]
+fn disallowed_schemes_are_errors_in_strict_mode() {
+    for url in [
+        "javascript:alert(1)",
+        " JaVa\tScript:alert(1)",
+        "vbscript:x",
+    ] {
+        let error = render(&link(url), HtmlWriterOptions::default()).unwrap_err();
+        assert!(matches!(
+            error.without_location(),
+            WriteError::InvalidUrl(_)
+        ));
+    }
+    let error = render(
+        &image("data:image/svg+xml,<svg/>"),
+        HtmlWriterOptions::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        error.without_location(),
+        WriteError::InvalidUrl(_)
+    ));
+}
+
+#[test]
+fn disallowed_schemes_are_neutralized_in_lenient_mode() {
+    assert_eq!(
+        render(&link("javascript:alert(1)"), lenient()).unwrap(),
+        "<a href=\"\">x</a>"
+    );
+    assert_eq!(
+        render(&image("data:text/html,x"), lenient()).unwrap(),
+        "<img src=\"\" alt=\"\" />"
+    );
+    let autolink = Node::Autolink {
+        url: "javascript:alert(1)".into(),
+        is_email: false,
+    };
+    assert_eq!(
+        render(&autolink, lenient()).unwrap(),
+        "<a href=\"\">javascript:alert(1)</a>"
+    );
+}
+
+#[test]
+fn html_element_url_attributes_are_checked() {
+    let element = Node::HtmlElement(
+        HtmlElement::new("a")
+            .with_attribute("HREF", "javascript:alert(1)")
+            .with_attribute("title", "javascript:is fine here"),
+    );
+    assert_eq!(
+        render(&element, lenient()).unwrap(),
+        "<a HREF=\"\" title=\"javascript:is fine here\"></a>"
+    );
+}
+
+#[test]
+fn scheme_list_is_configurable() {
+    let data = image("data:image/png;base64,AAAA");
+    let options = HtmlWriterOptions::default().with_allowed_url_schemes(["https", "data"]);
+    assert!(render(&data, options.clone()).is_ok());
+    assert!(render(&link("http://example.com"), options).is_err());
+
+    let any = HtmlWriterOptions::default().with_any_url_scheme();
+    assert!(render(&link("javascript:void(0)"), any).is_ok());
+}
+
+#[test]
+fn invalid_url_error_converts() {
+    let error = HtmlWriteError::InvalidUrl("javascript:x".into());
+    assert_eq!(error.to_string(), "URL scheme not allowed: javascript:x");
+    assert!(matches!(
+        error.into_write_error(),
+        WriteError::InvalidUrl(_)
+    ));
+}
@@ -0,0 +1,155 @@
+use cmark_writer::ast::tables::simple_table;
+use cmark_writer::ast::{HtmlElement, Node, NodePath};
+use cmark_writer::lint::{a11y, A11yIssueKind};
+
+fn text(value: &str) -> Node {
+    Node::Text(value.into())
+}
+
+fn heading(level: u8, value: &str) -> Node {
+    Node::heading(level, vec![text(value)])
+}
+
+fn link(url: &str, label: &str) -> Node {
+    Node::Link {
+        url: url.into(),
+        title: None,
+        content: vec![text(label)],
+    }
+}
+
+fn image(alt: &str) -> Node {
+    Node::Image {
+        url: "/a.png".into(),
+        title: None,
+        alt: vec![text(alt)],
+    }
+}
+
+fn kinds(doc: &Node) -> Vec<A11yIssueKind> {
+    a11y(doc).into_iter().map(|issue| issue.kind).collect()
+}
+
+#[test]
+fn accessible_document_has_no_issues() {
+    let doc = Node::Document(vec![
+        heading(1, "Guide"),
+        heading(2, "Install"),
+        heading(3, "Linux"),
+        heading(2, "Usage"),
+        Node::Paragraph(vec![
+            image("Architecture diagram"),
+            link("/install", "installation guide"),
+            Node::Autolink {
+                url: "https://example.com".into(),
+                is_email: false,
+            },
+        ]),
+        simple_table(vec![text("Name")], vec![vec![text("a")]]),
+    ]);
+    assert!(a11y(&doc).is_empty());
+}
+
+#[test]
+fn images_without_alt_text() {
+    let doc = Node::Document(vec![Node::Paragraph(vec![
+        image(""),
+        image("  "),
+        Node::HtmlElement(HtmlElement::new("img").with_attribute("src", "/b.png")),
+        Node::HtmlElement(HtmlElement::new("IMG").with_attribute("alt", "Logo")),
+    ])]);
+    let issues = a11y(&doc);
+    assert_eq!(issues.len(), 3);
+    assert!(issues
+        .iter()
+        .all(|issue| issue.kind == A11yIssueKind::MissingAltText));
+    assert_eq!(issues[2].path, NodePath::root().child(0).child(2));
+}
+
+#[test]
+fn tables_without_headers() {
+    let doc = Node::Document(vec![
+        simple_table(vec![text(""), text(" ")], vec![vec![text("a"), text("b")]]),
+        simple_table(vec![], vec![vec![text("a")]]),
+    ]);
+    assert_eq!(
+        kinds(&doc),
+        [
+            A11yIssueKind::TableWithoutHeaders,
+            A11yIssueKind::TableWithoutHeaders
+        ]
+    );
+}
+
+#[test]
+fn heading_level_skips() {
+    let doc = Node::Document(vec![
+        heading(2, "Start"),
+        heading(4, "Too deep"),
+        heading(1, "Back up"),
+        Node::BlockQuote(vec![heading(3, "Nested")]),
+    ]);
+    assert_eq!(
+        kinds(&doc),
+        [
+            A11yIssueKind::HeadingLevelSkip { from: 2, to: 4 },
+            A11yIssueKind::HeadingLevelSkip { from: 1, to: 3 },
+        ]
+    );
+}
+
+#[test]
+fn non_descriptive_link_texts() {
+    let doc = Node::Document(vec![Node::Paragraph(vec![
+        link("/a", "Click here."),
+        link("/b", " Read more → "),
+        link("https://example.com/docs", "https://example.com/docs"),
+        link("/c", "www.example.com"),
+        link("/d", "the release notes"),
+        Node::ReferenceLink {
+            label: "changelog".into(),
+            content: vec![],
+        },
+        Node::ReferenceLink {
+            label: "x".into(),
+            content: vec![text("here")],
+        },
+    ])]);
+    assert_eq!(
+        kinds(&doc),
+        [
+            A11yIssueKind::NonDescriptiveLinkText("Click here.".into()),
+            A11yIssueKind::NonDescriptiveLinkText("Read more →".into()),
+            A11yIssueKind::NonDescriptiveLinkText("https://example.com/docs".into()),
+            A11yIssueKind::NonDescriptiveLinkText("www.example.com".into()),
+            A11yIssueKind::NonDescriptiveLinkText("here".into()),
+        ]
+    );
+}
+
+#[test]
+fn issues_display_their_path() {
+    let doc = Node::Document(vec![heading(1, "A"), heading(3, "B")]);
+    let issues = a11y(&doc);
+    assert_eq!(
+        issues[0].to_string(),
+        format!("heading level 3 follows level 1 at {}", issues[0].path)
+    );
+}
+
+#[test]
+fn shared_nodes_are_checked() {
+    let doc = Node::Document(vec![
+        heading(1, "Guide"),
+        Node::shared(heading(3, "Details")),
+        Node::shared(Node::Paragraph(vec![link("/install", "here")])),
+    ]);
+    let kinds: Vec<_> = a11y(&doc).into_iter().map(|issue| issue.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            A11yIssueKind::HeadingLevelSkip { from: 1, to: 3 },
+            A11yIssueKind::NonDescriptiveLinkText("here".into()),
+        ]
+    );
+}
@@ -0,0 +1,3 @@
+// Unit tests for lint passes
+
+mod a11y;
@@ -0,0 +1,7 @@
+// Unit tests for whole-document transformations
+
+mod paragraphs;
+mod retitle;
+mod split;
+mod toc;
+mod truncate;
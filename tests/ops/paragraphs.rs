@@ -0,0 +1,149 @@
+use cmark_writer::ast::{ListItem, Node, NodePath};
+use cmark_writer::ops::{merge_adjacent_paragraphs, split_paragraph_at};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn paragraph(s: &str) -> Node {
+    Node::Paragraph(vec![text(s)])
+}
+
+#[test]
+fn merges_runs_of_paragraphs_at_every_level() {
+    let mut doc = Node::Document(vec![
+        paragraph("a"),
+        paragraph("b"),
+        Node::ThematicBreak,
+        paragraph("c"),
+        Node::BlockQuote(vec![paragraph("d"), paragraph("e")]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![paragraph("f"), paragraph("g")],
+        }]),
+    ]);
+
+    merge_adjacent_paragraphs(&mut doc);
+    let joined = |a: &str, b: &str| Node::Paragraph(vec![text(a), Node::SoftBreak, text(b)]);
+    assert_eq!(
+        doc,
+        Node::Document(vec![
+            joined("a", "b"),
+            Node::ThematicBreak,
+            paragraph("c"),
+            Node::BlockQuote(vec![joined("d", "e")]),
+            Node::UnorderedList(vec![ListItem::Unordered {
+                content: vec![joined("f", "g")],
+            }]),
+        ])
+    );
+}
+
+#[test]
+fn merging_skips_breaks_around_empty_paragraphs() {
+    let mut doc = Node::Document(vec![
+        Node::Paragraph(vec![]),
+        paragraph("a"),
+        Node::Paragraph(vec![]),
+        paragraph("b"),
+    ]);
+    merge_adjacent_paragraphs(&mut doc);
+    assert_eq!(
+        doc,
+        Node::Document(vec![Node::Paragraph(vec![
+            text("a"),
+            Node::SoftBreak,
+            text("b")
+        ])])
+    );
+}
+
+#[test]
+fn splits_text_and_formatting() {
+    let mut doc = Node::Document(vec![Node::Paragraph(vec![
+        text("one "),
+        Node::Emphasis(vec![text("two three")]),
+        text(" four"),
+    ])]);
+
+    let new = split_paragraph_at(&mut doc, &NodePath::root().child(0), 7).unwrap();
+    assert_eq!(new, NodePath::root().child(1));
+    assert_eq!(
+        doc,
+        Node::Document(vec![
+            Node::Paragraph(vec![text("one "), Node::Emphasis(vec![text("two")])]),
+            Node::Paragraph(vec![Node::Emphasis(vec![text(" three")]), text(" four")]),
+        ])
+    );
+}
+
+#[test]
+fn splits_at_node_boundaries_and_drops_breaks() {
+    let mut doc = Node::Document(vec![Node::Paragraph(vec![
+        text("line one"),
+        Node::SoftBreak,
+        text("line two"),
+    ])]);
+    split_paragraph_at(&mut doc, &NodePath::root().child(0), 8).unwrap();
+    assert_eq!(
+        doc,
+        Node::Document(vec![paragraph("line one"), paragraph("line two")])
+    );
+
+    let mut doc = Node::Document(vec![paragraph("abc")]);
+    split_paragraph_at(&mut doc, &NodePath::root().child(0), 3).unwrap();
+    assert_eq!(
+        doc,
+        Node::Document(vec![paragraph("abc"), Node::Paragraph(vec![])])
+    );
+}
+
+#[test]
+fn splits_paragraphs_in_list_items() {
+    let mut doc = Node::Document(vec![Node::UnorderedList(vec![ListItem::Unordered {
+        content: vec![paragraph("firstsecond")],
+    }])]);
+    let new = split_paragraph_at(&mut doc, &NodePath::root().child(0).list_item(0, 0), 5);
+    assert_eq!(new, Some(NodePath::root().child(0).list_item(0, 1)));
+    assert_eq!(doc.to_string(), "- first\n  \n  second\n");
+}
+
+#[test]
+fn links_keep_their_destination_on_both_sides() {
+    let mut doc = Node::Document(vec![Node::Paragraph(vec![Node::Link {
+        url: "/docs".into(),
+        title: None,
+        content: vec![text("readme")],
+    }])]);
+    split_paragraph_at(&mut doc, &NodePath::root().child(0), 4).unwrap();
+    assert_eq!(doc.to_string(), "[read](/docs)\n\n[me](/docs)\n");
+}
+
+#[test]
+fn invalid_splits_leave_the_document_unchanged() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![
+            text("é"),
+            Node::Autolink {
+                url: "https://example.com".into(),
+                is_email: false,
+            },
+        ]),
+        Node::heading(1, vec![text("Title")]),
+    ]);
+    let paragraph_path = NodePath::root().child(0);
+    for (path, offset) in [
+        // Not on a character boundary
+        (&paragraph_path, 1),
+        // Inside the autolink
+        (&paragraph_path, 5),
+        // Past the end
+        (&paragraph_path, 100),
+        // Not a paragraph
+        (&NodePath::root().child(1), 1),
+        (&NodePath::root(), 0),
+    ] {
+        let mut edited = doc.clone();
+        assert_eq!(split_paragraph_at(&mut edited, path, offset), None);
+        assert_eq!(edited, doc);
+    }
+}
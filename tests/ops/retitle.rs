@@ -0,0 +1,102 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::ops::{retitle_headings, CaseStyle};
+
+fn heading(level: u8, text: &str) -> Node {
+    Node::heading(level, vec![Node::Text(text.into())])
+}
+
+fn retitled(mut doc: Node, style: CaseStyle) -> String {
+    retitle_headings(&mut doc, style);
+    doc.to_string()
+}
+
+#[test]
+fn title_case_keeps_small_words_lowercase() {
+    let doc = Node::Document(vec![heading(1, "the lord Of the rings")]);
+    assert_eq!(retitled(doc, CaseStyle::Title), "# The Lord of the Rings\n");
+}
+
+#[test]
+fn title_case_capitalizes_small_words_at_the_edges_and_after_colons() {
+    let doc = Node::Document(vec![
+        heading(2, "a guide to the tools to rely on"),
+        heading(2, "errors: an overview"),
+    ]);
+    assert_eq!(
+        retitled(doc, CaseStyle::Title),
+        "## A Guide to the Tools to Rely On\n\n## Errors: An Overview\n"
+    );
+}
+
+#[test]
+fn sentence_case_lowercases_all_but_the_first_word() {
+    let doc = Node::Document(vec![heading(1, "getting Started With The CLI: Notes")]);
+    assert_eq!(
+        retitled(doc, CaseStyle::Sentence),
+        "# Getting started with the CLI: notes\n"
+    );
+}
+
+#[test]
+fn acronyms_and_pronoun_i_are_kept() {
+    let doc = Node::Document(vec![heading(1, "why I'm using JSON and iOS")]);
+    assert_eq!(
+        retitled(doc.clone(), CaseStyle::Title),
+        "# Why I'm Using JSON and iOS\n"
+    );
+    assert_eq!(
+        retitled(doc, CaseStyle::Sentence),
+        "# Why I'm using JSON and iOS\n"
+    );
+}
+
+#[test]
+fn code_spans_and_links_are_protected() {
+    let doc = Node::Document(vec![Node::heading(
+        1,
+        vec![
+            Node::InlineCode("cargo run".into()),
+            Node::Text(" with ".into()),
+            Node::Link {
+                url: "https://example.com".into(),
+                title: None,
+                content: vec![Node::Text("the docs".into())],
+            },
+        ],
+    )]);
+    assert_eq!(
+        retitled(doc, CaseStyle::Title),
+        "# `cargo run` with [the docs](https://example.com)\n"
+    );
+}
+
+#[test]
+fn words_split_across_emphasis_are_one_word() {
+    let doc = Node::Document(vec![Node::heading(
+        1,
+        vec![
+            Node::Strong(vec![Node::Text("un".into())]),
+            Node::Text("safe code in ".into()),
+            Node::Strong(vec![Node::Text("practice".into())]),
+        ],
+    )]);
+    assert_eq!(
+        retitled(doc, CaseStyle::Title),
+        "# **Un**safe Code in **Practice**\n"
+    );
+}
+
+#[test]
+fn nested_headings_are_retitled_and_paragraphs_untouched() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("keep this as is".into())]),
+        Node::BlockQuote(vec![heading(2, "quoted heading")]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![heading(3, "listed heading")],
+        }]),
+    ]);
+    assert_eq!(
+        retitled(doc, CaseStyle::Title),
+        "keep this as is\n\n> ## Quoted Heading\n\n- ### Listed Heading\n"
+    );
+}
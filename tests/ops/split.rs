@@ -0,0 +1,124 @@
+use cmark_writer::ast::Node;
+use cmark_writer::ops::split_by_heading;
+
+fn text(s: &str) -> Node {
+    Node::Paragraph(vec![Node::Text(s.into())])
+}
+
+fn definition(label: &str) -> Node {
+    Node::LinkReferenceDefinition {
+        label: label.into(),
+        destination: format!("/{}", label).into(),
+        title: None,
+    }
+}
+
+fn reference(label: &str) -> Node {
+    Node::Paragraph(vec![Node::ReferenceLink {
+        label: label.into(),
+        content: vec![],
+    }])
+}
+
+#[test]
+fn splits_at_headings_up_to_level() {
+    let doc = Node::Document(vec![
+        text("prelude"),
+        Node::heading(2, vec![Node::Text("A".into())]),
+        text("a"),
+        Node::heading(3, vec![Node::Text("A.1".into())]),
+        text("a.1"),
+        Node::heading(1, vec![Node::Emphasis(vec![Node::Text("B".into())])]),
+        text("b"),
+    ]);
+
+    let sections = split_by_heading(&doc, 2);
+    assert_eq!(sections.len(), 2);
+
+    assert_eq!(sections[0].0, "A");
+    assert_eq!(
+        sections[0].1,
+        Node::Document(vec![
+            text("prelude"),
+            Node::heading(2, vec![Node::Text("A".into())]),
+            text("a"),
+            Node::heading(3, vec![Node::Text("A.1".into())]),
+            text("a.1"),
+        ])
+    );
+
+    assert_eq!(sections[1].0, "B");
+    assert_eq!(
+        sections[1].1,
+        Node::Document(vec![
+            Node::heading(1, vec![Node::Emphasis(vec![Node::Text("B".into())])]),
+            text("b"),
+        ])
+    );
+}
+
+#[test]
+fn copies_reference_definitions_to_sections_using_them() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Text("One".into())]),
+        reference("shared"),
+        reference("local"),
+        definition("local"),
+        Node::heading(1, vec![Node::Text("Two".into())]),
+        reference("shared"),
+        definition("shared"),
+    ]);
+
+    let sections = split_by_heading(&doc, 1);
+    assert_eq!(
+        sections[0].1,
+        Node::Document(vec![
+            Node::heading(1, vec![Node::Text("One".into())]),
+            reference("shared"),
+            reference("local"),
+            definition("local"),
+            definition("shared"),
+        ])
+    );
+    assert_eq!(
+        sections[1].1,
+        Node::Document(vec![
+            Node::heading(1, vec![Node::Text("Two".into())]),
+            reference("shared"),
+            definition("shared"),
+        ])
+    );
+}
+
+#[test]
+fn documents_without_headings() {
+    let doc = Node::Document(vec![text("only")]);
+    let sections = split_by_heading(&doc, 1);
+    assert_eq!(sections, vec![("".into(), doc)]);
+
+    assert!(split_by_heading(&Node::Document(vec![]), 1).is_empty());
+}
+
+#[test]
+fn matches_definitions_by_normalized_label() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Text("One".into())]),
+        reference("Foo  Bar"),
+        reference("foo bar"),
+        Node::heading(1, vec![Node::Text("Two".into())]),
+        definition("FOO BAR"),
+        definition("foo\nbar"),
+    ]);
+
+    let sections = split_by_heading(&doc, 1);
+    // Both references resolve to the first definition, copied once
+    assert_eq!(
+        sections[0].1,
+        Node::Document(vec![
+            Node::heading(1, vec![Node::Text("One".into())]),
+            reference("Foo  Bar"),
+            reference("foo bar"),
+            definition("FOO BAR"),
+        ])
+    );
+}
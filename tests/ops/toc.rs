@@ -0,0 +1,58 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::ops::table_of_contents;
+
+fn heading(level: u8, text: &str) -> Node {
+    Node::heading(level, vec![Node::Text(text.into())])
+}
+
+#[test]
+fn nests_headings_under_shallower_ones() {
+    let doc = Node::Document(vec![
+        heading(2, "Intro"),
+        heading(1, "Guide"),
+        heading(3, "Deep"),
+        heading(2, "Usage"),
+        heading(1, "Usage"),
+    ]);
+    assert_eq!(
+        table_of_contents(&doc).to_string(),
+        "- [Intro](#intro)\n\
+         - [Guide](#guide)\n  \
+           - [Deep](#deep)\n  \
+           - [Usage](#usage)\n\
+         - [Usage](#usage-1)\n"
+    );
+}
+
+#[test]
+fn links_to_explicit_ids_and_plain_text() {
+    let doc = Node::Document(vec![
+        Node::heading_with_id(1, vec![Node::Text("Custom".into())], "my-id"),
+        Node::heading(
+            1,
+            vec![Node::Text("Use ".into()), Node::InlineCode("render".into())],
+        ),
+    ]);
+    let link = |url: &str, text: &str| Node::Link {
+        url: url.into(),
+        title: None,
+        content: vec![Node::Text(text.into())],
+    };
+    assert_eq!(
+        table_of_contents(&doc),
+        Node::UnorderedList(vec![
+            ListItem::Unordered {
+                content: vec![link("#my-id", "Custom")],
+            },
+            ListItem::Unordered {
+                content: vec![link("#use-render", "Use render")],
+            },
+        ])
+    );
+}
+
+#[test]
+fn documents_without_headings_give_an_empty_list() {
+    let doc = Node::Document(vec![Node::Paragraph(vec![Node::Text("text".into())])]);
+    assert_eq!(table_of_contents(&doc), Node::UnorderedList(vec![]));
+}
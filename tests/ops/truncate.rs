@@ -0,0 +1,121 @@
+use cmark_writer::ast::Node;
+use cmark_writer::ops::{is_read_more_marker, truncate_document, TruncateLimits};
+use cmark_writer::{HtmlWriter, ToHtml};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn paragraph(s: &str) -> Node {
+    Node::Paragraph(vec![text(s)])
+}
+
+fn post() -> Node {
+    Node::Document(vec![
+        paragraph("Intro"),
+        paragraph("More intro"),
+        Node::heading(2, vec![text("Setup")]),
+        Node::CodeBlock {
+            language: None,
+            content: "make".into(),
+            block_type: Default::default(),
+        },
+        paragraph("Run it"),
+        paragraph("Then wait"),
+        Node::heading(2, vec![text("Usage")]),
+        paragraph("Call it"),
+    ])
+}
+
+#[test]
+fn stops_at_marker() {
+    let doc = Node::Document(vec![
+        paragraph("Teaser"),
+        Node::HtmlBlock("<!--  MORE -->\n".into()),
+        paragraph("Body"),
+    ]);
+    assert_eq!(
+        truncate_document(&doc, &TruncateLimits::new()),
+        Node::Document(vec![paragraph("Teaser")])
+    );
+
+    let limits = TruncateLimits::new().with_stop_at_marker(false);
+    assert_eq!(
+        truncate_document(&doc, &limits),
+        Node::Document(vec![paragraph("Teaser"), paragraph("Body")])
+    );
+}
+
+#[test]
+fn marker_detection() {
+    assert!(is_read_more_marker(&Node::ReadMoreMarker));
+    assert!(is_read_more_marker(&Node::HtmlBlock("<!--more-->".into())));
+    assert!(!is_read_more_marker(&Node::HtmlBlock(
+        "<!-- more info -->".into()
+    )));
+    assert!(!is_read_more_marker(&paragraph("<!-- more -->")));
+}
+
+#[test]
+fn first_blocks() {
+    let preview = truncate_document(&post(), &TruncateLimits::new().with_max_blocks(3));
+    assert_eq!(preview.to_string(), "Intro\n\nMore intro\n\n## Setup\n");
+}
+
+#[test]
+fn first_paragraph_per_section() {
+    let limits = TruncateLimits::new().with_first_paragraph_per_section(true);
+    assert_eq!(
+        truncate_document(&post(), &limits).to_string(),
+        "Intro\n\n## Setup\n\nRun it\n\n## Usage\n\nCall it\n"
+    );
+
+    let limits = limits.with_max_blocks(2);
+    assert_eq!(
+        truncate_document(&post(), &limits).to_string(),
+        "Intro\n\n## Setup\n"
+    );
+}
+
+#[test]
+fn used_definitions_are_kept() {
+    let doc = Node::Document(vec![
+        Node::LinkReferenceDefinition {
+            label: "unused".into(),
+            destination: "/a".into(),
+            title: None,
+        },
+        Node::Paragraph(vec![
+            Node::ReferenceLink {
+                label: "Docs".into(),
+                content: vec![],
+            },
+            Node::FootnoteReference("1".into()),
+        ]),
+        Node::ReadMoreMarker,
+        paragraph("Body"),
+        Node::LinkReferenceDefinition {
+            label: "docs".into(),
+            destination: "/docs".into(),
+            title: None,
+        },
+        Node::FootnoteDefinition {
+            label: "1".into(),
+            content: vec![paragraph("Note")],
+        },
+    ]);
+    assert_eq!(
+        truncate_document(&doc, &TruncateLimits::new()).to_string(),
+        "[Docs]\\[1\\]\n\n[docs]: /docs\n\n\\[1\\]: Note\n"
+    );
+}
+
+#[test]
+fn marker_rendering() {
+    let doc = Node::Document(vec![paragraph("a"), Node::ReadMoreMarker, paragraph("b")]);
+    assert_eq!(doc.to_string(), "a\n\n<!-- more -->\n\nb\n");
+
+    let mut writer = HtmlWriter::new();
+    doc.to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<p>a</p>\n<p>b</p>\n");
+}
@@ -0,0 +1,106 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::ops::split_by_heading;
+use cmark_writer::options::{DocumentOptions, WriterOptions, WriterOptionsBuilder};
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{HtmlWriter, ToCommonMark, ToHtml};
+
+fn body() -> Vec<Node> {
+    vec![
+        Node::Paragraph(vec![
+            Node::Emphasis(vec![Node::Text("em".into())]),
+            Node::Text(" and ".into()),
+            Node::Strong(vec![Node::Text("strong".into())]),
+        ]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![Node::Text("item".into())])],
+        }]),
+    ]
+}
+
+fn render(node: &Node, options: WriterOptions) -> String {
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn overrides_are_merged_over_writer_options() {
+    let doc = Node::ConfiguredDocument {
+        options: DocumentOptions::new()
+            .emphasis_char('*')
+            .strong_char('_')
+            .list_marker('+'),
+        children: body(),
+    };
+    let options = WriterOptionsBuilder::new().list_marker('*').build();
+    assert_eq!(render(&doc, options), "*em* and __strong__\n\n+ item\n");
+}
+
+#[test]
+fn empty_overrides_render_like_document() {
+    let configured = Node::ConfiguredDocument {
+        options: DocumentOptions::new(),
+        children: body(),
+    };
+    assert!(DocumentOptions::new().is_empty());
+    assert_eq!(
+        render(&configured, WriterOptions::default()),
+        render(&Node::Document(body()), WriterOptions::default())
+    );
+}
+
+#[test]
+fn invalid_override_keeps_writer_setting() {
+    let merged = DocumentOptions::new()
+        .emphasis_char('~')
+        .indent_spaces(2)
+        .merged_over(&WriterOptions::default());
+    assert_eq!(merged.emphasis_char, '_');
+    assert_eq!(merged.indent_spaces, 2);
+}
+
+#[test]
+fn overrides_end_with_the_document() {
+    let doc = Node::Document(vec![
+        Node::ConfiguredDocument {
+            options: DocumentOptions::new().emphasis_char('*'),
+            children: vec![Node::Paragraph(vec![Node::Emphasis(vec![Node::Text(
+                "inner".into(),
+            )])])],
+        },
+        Node::Paragraph(vec![Node::Emphasis(vec![Node::Text("outer".into())])]),
+    ]);
+    assert_eq!(
+        render(&doc, WriterOptions::default()),
+        "*inner*\n\n_outer_\n"
+    );
+}
+
+#[test]
+fn html_renders_children() {
+    let doc = Node::ConfiguredDocument {
+        options: DocumentOptions::new().emphasis_char('*'),
+        children: vec![Node::Paragraph(vec![Node::Text("hi".into())])],
+    };
+    let mut writer = HtmlWriter::new();
+    doc.to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<p>hi</p>\n");
+}
+
+#[test]
+fn split_sections_keep_overrides() {
+    let options = DocumentOptions::new().emphasis_char('*');
+    let doc = Node::ConfiguredDocument {
+        options: options.clone(),
+        children: vec![
+            Node::heading(1, vec![Node::Text("A".into())]),
+            Node::heading(1, vec![Node::Text("B".into())]),
+        ],
+    };
+    let sections = split_by_heading(&doc, 1);
+    assert_eq!(sections.len(), 2);
+    assert!(sections.iter().all(|(_, section)| matches!(
+        section,
+        Node::ConfiguredDocument { options: o, .. } if *o == options
+    )));
+}
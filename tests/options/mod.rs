@@ -1,4 +1,8 @@
 // Unit tests for options
 
+mod document_options;
 mod flexible_newline_control;
+#[cfg(feature = "unicode-normalization")]
+mod unicode_normalization;
+mod validation;
 mod writer_options;
@@ -0,0 +1,83 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, NormalizationForm, ToCommonMark, WriterOptions};
+
+/// "Café" with the accent as a combining character
+const DECOMPOSED: &str = "Cafe\u{301}";
+const COMPOSED: &str = "Caf\u{e9}";
+
+fn render(node: &Node, options: WriterOptions) -> String {
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn paragraph(text: &str) -> Node {
+    Node::Paragraph(vec![Node::Text(text.into())])
+}
+
+#[test]
+fn text_is_nfc_by_default() {
+    assert_eq!(
+        WriterOptions::default().unicode_normalization,
+        Some(NormalizationForm::Nfc)
+    );
+    let decomposed = render(&paragraph(DECOMPOSED), WriterOptions::default());
+    let composed = render(&paragraph(COMPOSED), WriterOptions::default());
+    assert_eq!(decomposed, format!("{}\n", COMPOSED));
+    assert_eq!(decomposed, composed);
+}
+
+#[test]
+fn other_forms_and_disabling() {
+    let nfd = WriterOptionsBuilder::new()
+        .unicode_normalization(Some(NormalizationForm::Nfd))
+        .build();
+    assert_eq!(
+        render(&paragraph(COMPOSED), nfd),
+        format!("{}\n", DECOMPOSED)
+    );
+
+    let nfkc = WriterOptionsBuilder::new()
+        .unicode_normalization(Some(NormalizationForm::Nfkc))
+        .build();
+    assert_eq!(render(&paragraph("\u{fb01}le"), nfkc), "file\n");
+
+    let off = WriterOptionsBuilder::new()
+        .unicode_normalization(None)
+        .build();
+    assert_eq!(
+        render(&paragraph(DECOMPOSED), off),
+        format!("{}\n", DECOMPOSED)
+    );
+}
+
+#[test]
+fn applies_to_text_inside_inline_nodes() {
+    let node = Node::Paragraph(vec![Node::Link {
+        url: "https://example.com".into(),
+        title: None,
+        content: vec![Node::Strong(vec![Node::Text(DECOMPOSED.into())])],
+    }]);
+    assert_eq!(
+        render(&node, WriterOptions::default()),
+        format!("[**{}**](https://example.com)\n", COMPOSED)
+    );
+}
+
+#[test]
+fn code_is_left_unchanged() {
+    let node = Node::Paragraph(vec![Node::InlineCode(DECOMPOSED.into())]);
+    assert_eq!(
+        render(&node, WriterOptions::default()),
+        format!("`{}`\n", DECOMPOSED)
+    );
+}
+
+#[test]
+fn normalize_borrows_normalized_text() {
+    assert!(matches!(
+        NormalizationForm::Nfc.normalize(COMPOSED),
+        std::borrow::Cow::Borrowed(_)
+    ));
+}
@@ -0,0 +1,117 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::{OptionsConflict, WriterOptions, WriterOptionsBuilder};
+use cmark_writer::writer::filters::TrimTrailingWhitespace;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{NodePath, ToCommonMark, WriteError};
+
+fn paragraph() -> Node {
+    Node::Paragraph(vec![Node::Text("text".into())])
+}
+
+fn trimming_hard_breaks() -> TrimTrailingWhitespace {
+    TrimTrailingWhitespace {
+        preserve_hard_breaks: false,
+    }
+}
+
+#[test]
+fn default_options_are_valid() {
+    assert_eq!(WriterOptions::default().validate(), Ok(()));
+}
+
+#[test]
+fn hard_break_spaces_conflict_with_trimming_post_processor() {
+    let options = WriterOptionsBuilder::new()
+        .hard_break_spaces(true)
+        .post_processor(trimming_hard_breaks())
+        .build();
+    assert_eq!(
+        options.validate(),
+        Err(vec![OptionsConflict::HardBreakSpacesTrimmed])
+    );
+
+    // Trimming that keeps hard breaks is fine
+    let options = WriterOptionsBuilder::new()
+        .hard_break_spaces(true)
+        .post_processor(TrimTrailingWhitespace::default())
+        .build();
+    assert_eq!(options.validate(), Ok(()));
+}
+
+#[test]
+fn setext_headings_conflict_with_offset_past_level_two() {
+    let options = WriterOptionsBuilder::new()
+        .setext_headings(true)
+        .heading_level_offset(2)
+        .build();
+    assert_eq!(
+        options.validate(),
+        Err(vec![OptionsConflict::SetextHeadingsUnreachable {
+            heading_level_offset: 2
+        }])
+    );
+
+    // Clamped back to level 2 by max_heading_level
+    let options = WriterOptionsBuilder::new()
+        .setext_headings(true)
+        .heading_level_offset(2)
+        .max_heading_level(2)
+        .build();
+    assert_eq!(options.validate(), Ok(()));
+}
+
+#[test]
+fn markers_set_directly_are_checked() {
+    let options = WriterOptions {
+        list_marker: 'x',
+        strong_char: '~',
+        ..Default::default()
+    };
+    assert_eq!(
+        options.validate(),
+        Err(vec![
+            OptionsConflict::InvalidMarker {
+                option: "list_marker",
+                marker: 'x'
+            },
+            OptionsConflict::InvalidMarker {
+                option: "strong_char",
+                marker: '~'
+            },
+        ])
+    );
+}
+
+#[test]
+fn strict_writer_refuses_conflicting_options() {
+    let options = WriterOptionsBuilder::new()
+        .hard_break_spaces(true)
+        .post_processor(trimming_hard_breaks())
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    let error = paragraph().to_commonmark(&mut writer).unwrap_err();
+    assert!(matches!(
+        &error,
+        WriteError::InvalidOptions(conflicts)
+            if conflicts == &[OptionsConflict::HardBreakSpacesTrimmed]
+    ));
+    assert!(error.to_string().contains("hard_break_spaces"));
+
+    let (path, error) = writer.locate_error(&paragraph()).unwrap();
+    assert_eq!(path, NodePath::root());
+    assert!(matches!(error, WriteError::InvalidOptions(_)));
+}
+
+#[test]
+fn non_strict_writer_writes_with_conflicting_options() {
+    let options = WriterOptionsBuilder::new()
+        .strict(false)
+        .setext_headings(true)
+        .heading_level_offset(3)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    Node::heading(1, vec![Node::Text("Title".into())])
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "#### Title\n");
+}
@@ -238,10 +238,154 @@ fn test_writer_options_default_gfm_disallowed_tags() {
         let options = WriterOptions::default();
         let disallowed_tags = &options.gfm_disallowed_html_tags;
 
-        assert!(disallowed_tags.contains(&"script".into()));
-        assert!(disallowed_tags.contains(&"iframe".into()));
-        assert!(disallowed_tags.contains(&"style".into()));
-        assert!(disallowed_tags.contains(&"title".into()));
+        assert!(disallowed_tags.contains("script"));
+        assert!(disallowed_tags.contains("iframe"));
+        assert!(disallowed_tags.contains("style"));
+        assert!(disallowed_tags.contains("title"));
         assert!(disallowed_tags.len() > 5); // Should have multiple disallowed tags
     }
 }
+
+#[test]
+fn test_shared_options_across_writers() {
+    use cmark_writer::ast::Node;
+    use cmark_writer::writer::CommonMarkWriter;
+    use cmark_writer::ToCommonMark;
+    use std::sync::Arc;
+    use std::thread;
+
+    let options = Arc::new(WriterOptionsBuilder::new().list_marker('*').build());
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let options = Arc::clone(&options);
+            thread::spawn(move || {
+                let mut writer = CommonMarkWriter::with_shared_options(options);
+                let list = Node::UnorderedList(vec![cmark_writer::ListItem::Unordered {
+                    content: vec![Node::Paragraph(vec![Node::Text(format!("{}", i).into())])],
+                }]);
+                list.to_commonmark(&mut writer).unwrap();
+                writer.into_string()
+            })
+        })
+        .collect();
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert_eq!(handle.join().unwrap(), format!("* {}\n", i));
+    }
+}
+
+#[test]
+fn test_options_mut_is_copy_on_write() {
+    use cmark_writer::writer::CommonMarkWriter;
+    use std::sync::Arc;
+
+    let options = Arc::new(WriterOptions::default());
+    let mut writer = CommonMarkWriter::with_shared_options(Arc::clone(&options));
+    writer.options_mut().strict = false;
+
+    assert!(!writer.options().strict);
+    assert!(options.strict);
+}
+
+#[test]
+fn test_final_newline_policy() {
+    use cmark_writer::{
+        CommonMarkWriter, FinalNewline, HtmlWriter, HtmlWriterOptions, Node, ToCommonMark, ToHtml,
+    };
+
+    let render = |node: &Node, policy: FinalNewline| {
+        let options = WriterOptionsBuilder::new().final_newline(policy).build();
+        let mut writer = CommonMarkWriter::with_options(options);
+        node.to_commonmark(&mut writer).unwrap();
+        writer.into_string()
+    };
+
+    let block = Node::Paragraph(vec![Node::Text("block".into())]);
+    let inline = Node::Text("inline".into());
+
+    assert_eq!(render(&block, FinalNewline::Preserve), "block\n");
+    assert_eq!(render(&inline, FinalNewline::Preserve), "inline");
+    assert_eq!(render(&block, FinalNewline::Always), "block\n");
+    assert_eq!(render(&inline, FinalNewline::Always), "inline\n");
+    assert_eq!(render(&block, FinalNewline::Never), "block");
+    assert_eq!(render(&Node::Document(vec![]), FinalNewline::Always), "");
+
+    let mut writer = HtmlWriter::with_options(
+        HtmlWriterOptions::default().with_final_newline(FinalNewline::Never),
+    );
+    block.to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<p>block</p>");
+
+    let mut writer = HtmlWriter::with_options(
+        HtmlWriterOptions::default().with_final_newline(FinalNewline::Always),
+    );
+    Node::Emphasis(vec![Node::Text("em".into())])
+        .to_html(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "<em>em</em>\n");
+}
+
+#[test]
+fn test_max_blank_lines_collapses_spacing() {
+    use cmark_writer::{CommonMarkWriter, Node, ToCommonMark};
+
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("first\n\n\n\nstill first".into())]),
+        Node::HtmlBlock("<div>\n\n\n</div>\n\n\n".into()),
+        Node::code_block(Some("text".into()), "a\n\n\n\nb\n".into()),
+        Node::Paragraph(vec![Node::Text("last".into())]),
+    ]);
+
+    let options = WriterOptionsBuilder::new()
+        .strict(false)
+        .max_blank_lines(1)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    doc.to_commonmark(&mut writer).unwrap();
+    let output = writer.into_string();
+
+    assert!(output.starts_with("first\n\nstill first\n\n<div>"));
+    assert!(output.contains("```text\na\n\n\n\nb\n```"));
+    assert!(!output.replace("a\n\n\n\nb", "").contains("\n\n\n"));
+
+    let without_limit = WriterOptionsBuilder::new().strict(false).build();
+    let mut writer = CommonMarkWriter::with_options(without_limit);
+    doc.to_commonmark(&mut writer).unwrap();
+    assert!(writer.into_string().contains("first\n\n\n\nstill first"));
+}
+
+#[test]
+fn test_link_title_quote() {
+    use cmark_writer::{CommonMarkWriter, Node, TitleQuote, ToCommonMark};
+
+    let render = |quote: TitleQuote, title: &str| {
+        let options = WriterOptionsBuilder::new().link_title_quote(quote).build();
+        let mut writer = CommonMarkWriter::with_options(options);
+        Node::Link {
+            url: "/url".into(),
+            title: Some(title.into()),
+            content: vec![Node::Text("x".into())],
+        }
+        .to_commonmark(&mut writer)
+        .unwrap();
+        writer.into_string()
+    };
+
+    assert_eq!(render(TitleQuote::Double, "t"), "[x](/url \"t\")");
+    assert_eq!(render(TitleQuote::Single, "t"), "[x](/url 't')");
+    assert_eq!(render(TitleQuote::Paren, "t"), "[x](/url (t))");
+
+    // Falls back to another style when the title contains the preferred quote
+    assert_eq!(
+        render(TitleQuote::Double, "say \"hi\""),
+        "[x](/url 'say \"hi\"')"
+    );
+    assert_eq!(render(TitleQuote::Paren, "f(x)"), "[x](/url \"f(x)\")");
+
+    // Escapes when every style conflicts
+    assert_eq!(
+        render(TitleQuote::Double, "\"a\" 'b' (c)"),
+        "[x](/url \"\\\"a\\\" 'b' (c)\")"
+    );
+}
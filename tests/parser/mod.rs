@@ -0,0 +1,3 @@
+// Unit tests for the CommonMark parser
+
+mod parse;
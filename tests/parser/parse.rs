@@ -0,0 +1,326 @@
+use cmark_writer::ast::{CodeBlockType, DefinitionItem, HeadingType, ListItem, Node};
+use cmark_writer::parser::{parse, parse_with, ParseOptions};
+use cmark_writer::{CommonMarkWriter, ToCommonMark, WriterOptions};
+
+fn blocks(source: &str) -> Vec<Node> {
+    match parse(source) {
+        Node::Document(children) => children,
+        other => panic!("expected a document, got {:?}", other),
+    }
+}
+
+fn write(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::with_options(WriterOptions {
+        math: true,
+        definition_lists: true,
+        footnotes: true,
+        ..Default::default()
+    });
+    node.to_commonmark(&mut writer).expect("write");
+    writer.into_string().to_string()
+}
+
+#[test]
+fn headings_keep_their_style() {
+    let doc = blocks("# Atx\n\nSetext\n------\n");
+    assert!(matches!(
+        &doc[0],
+        Node::Heading {
+            level: 1,
+            heading_type: HeadingType::Atx,
+            ..
+        }
+    ));
+    assert!(matches!(
+        &doc[1],
+        Node::Heading {
+            level: 2,
+            heading_type: HeadingType::Setext,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn heading_attributes_are_opt_in() {
+    let plain = blocks("# Title {#intro}\n");
+    assert!(matches!(
+        &plain[0],
+        Node::Heading {
+            custom_id: None,
+            ..
+        }
+    ));
+
+    let options = ParseOptions::new().with_heading_attributes(true);
+    let Node::Document(doc) = parse_with("# Title {#intro}\n", &options) else {
+        unreachable!()
+    };
+    match &doc[0] {
+        Node::Heading {
+            content, custom_id, ..
+        } => {
+            assert_eq!(custom_id.as_deref(), Some("intro"));
+            assert_eq!(content, &vec![Node::Text("Title".into())]);
+        }
+        other => panic!("expected a heading, got {:?}", other),
+    }
+}
+
+#[test]
+fn code_blocks() {
+    let doc = blocks("```rust\nfn main() {}\n```\n\n    indented\n");
+    assert_eq!(
+        doc[0],
+        Node::CodeBlock {
+            language: Some("rust".into()),
+            content: "fn main() {}\n".into(),
+            block_type: CodeBlockType::Fenced,
+        }
+    );
+    assert_eq!(
+        doc[1],
+        Node::CodeBlock {
+            language: None,
+            content: "indented\n".into(),
+            block_type: CodeBlockType::Indented,
+        }
+    );
+}
+
+#[test]
+fn tight_list_items_are_wrapped_in_paragraphs() {
+    let doc = blocks("3. one\n4. two\n   - nested\n");
+    assert_eq!(
+        doc[0],
+        Node::OrderedList {
+            start: 3,
+            items: vec![
+                ListItem::Ordered {
+                    number: None,
+                    content: vec![Node::Paragraph(vec![Node::Text("one".into())])],
+                },
+                ListItem::Ordered {
+                    number: None,
+                    content: vec![
+                        Node::Paragraph(vec![Node::Text("two".into())]),
+                        Node::UnorderedList(vec![ListItem::Unordered {
+                            content: vec![Node::Paragraph(vec![Node::Text("nested".into())])],
+                        }]),
+                    ],
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn inline_content() {
+    let doc = blocks("A [link](/u \"t\") and ![img](/i.png), `code` <https://x.org>  \nnext\n");
+    assert_eq!(
+        doc[0],
+        Node::Paragraph(vec![
+            Node::Text("A ".into()),
+            Node::Link {
+                url: "/u".into(),
+                title: Some("t".into()),
+                content: vec![Node::Text("link".into())],
+            },
+            Node::Text(" and ".into()),
+            Node::Image {
+                url: "/i.png".into(),
+                title: None,
+                alt: vec![Node::Text("img".into())],
+            },
+            Node::Text(", ".into()),
+            Node::InlineCode("code".into()),
+            Node::Text(" ".into()),
+            Node::Autolink {
+                url: "https://x.org".into(),
+                is_email: false,
+            },
+            Node::HardBreak,
+            Node::Text("next".into()),
+        ])
+    );
+}
+
+#[test]
+fn reference_links_are_resolved() {
+    let doc = blocks("[text][ref]\n\n[ref]: /target\n");
+    assert_eq!(
+        doc,
+        vec![Node::Paragraph(vec![Node::Link {
+            url: "/target".into(),
+            title: None,
+            content: vec![Node::Text("text".into())],
+        }])]
+    );
+}
+
+#[test]
+fn html_blocks_and_inline_html() {
+    let doc = blocks("<div>\nhi\n</div>\n\na <b>b</b>\n");
+    assert_eq!(doc[0], Node::HtmlBlock("<div>\nhi\n</div>\n".into()));
+    assert_eq!(
+        doc[1],
+        Node::Paragraph(vec![
+            Node::Text("a ".into()),
+            Node::HtmlInline("<b>".into()),
+            Node::Text("b".into()),
+            Node::HtmlInline("</b>".into()),
+        ])
+    );
+}
+
+#[test]
+fn inline_html_is_kept_as_markup() {
+    let source = "Press <kbd>Ctrl</kbd> <!-- key --> & _go_\n";
+    let doc = parse(source);
+    assert_eq!(write(&doc), source);
+
+    let mut writer = cmark_writer::HtmlWriter::new();
+    cmark_writer::ToHtml::to_html(&doc, &mut writer).unwrap();
+    assert_eq!(
+        writer.into_string(),
+        "<p>Press <kbd>Ctrl</kbd> <!-- key --> &amp; <em>go</em></p>\n"
+    );
+}
+
+#[test]
+fn definition_lists_are_opt_in() {
+    let source = "Term\n: First\n: Second\n\nOther *term*\n: Third\n";
+    assert!(matches!(blocks(source)[0], Node::Paragraph(_)));
+
+    let doc = parse_with(source, &ParseOptions::new().with_definition_lists(true));
+    let Node::Document(children) = &doc else {
+        unreachable!()
+    };
+    assert_eq!(
+        children[0],
+        Node::DefinitionList(vec![
+            DefinitionItem::new(
+                vec![Node::Text("Term".into())],
+                vec![
+                    vec![Node::Paragraph(vec![Node::Text("First".into())])],
+                    vec![Node::Paragraph(vec![Node::Text("Second".into())])],
+                ],
+            ),
+            DefinitionItem::new(
+                vec![
+                    Node::Text("Other ".into()),
+                    Node::Emphasis(vec![Node::Text("term".into())]),
+                ],
+                vec![vec![Node::Paragraph(vec![Node::Text("Third".into())])]],
+            ),
+        ])
+    );
+    assert_eq!(write(&doc), source.replace('*', "_"));
+}
+
+#[test]
+fn math_is_opt_in() {
+    let source = "Area $\\pi r^2$ and\n$$\\sum_k k$$\n";
+    assert_eq!(
+        blocks(source)[0],
+        Node::Paragraph(vec![
+            Node::Text("Area $\\pi r^2$ and".into()),
+            Node::SoftBreak,
+            Node::Text("$$\\sum_k k$$".into()),
+        ])
+    );
+
+    let doc = parse_with(source, &ParseOptions::new().with_math(true));
+    let Node::Document(children) = &doc else {
+        unreachable!()
+    };
+    assert_eq!(
+        children[0],
+        Node::Paragraph(vec![
+            Node::Text("Area ".into()),
+            Node::Math {
+                display: false,
+                content: "\\pi r^2".into(),
+            },
+            Node::Text(" and".into()),
+            Node::SoftBreak,
+            Node::Math {
+                display: true,
+                content: "\\sum_k k".into(),
+            },
+        ])
+    );
+    assert_eq!(write(&doc), source);
+}
+
+#[test]
+fn round_trip() {
+    let source = "# Title\n\nSome _emphasis_ and **strong** text.\n\n> quoted\n\n- one\n- two\n\n---\n\n```sh\nls\n```\n";
+    assert_eq!(write(&parse(source)), source);
+}
+
+#[cfg(feature = "gfm")]
+mod gfm {
+    use super::*;
+    use cmark_writer::ast::{TableAlignment, TaskListStatus};
+
+    #[test]
+    fn tables() {
+        let doc = blocks("| a | b |\n| :-- | --: |\n| 1 | *2* and 3 |\n");
+        assert_eq!(
+            doc[0],
+            Node::Table {
+                headers: vec![Node::Text("a".into()), Node::Text("b".into())],
+                alignments: vec![TableAlignment::Left, TableAlignment::Right],
+                rows: vec![vec![
+                    Node::Text("1".into()),
+                    Node::Paragraph(vec![
+                        Node::Emphasis(vec![Node::Text("2".into())]),
+                        Node::Text(" and 3".into()),
+                    ]),
+                ]],
+            }
+        );
+    }
+
+    #[test]
+    fn task_lists_and_strikethrough() {
+        let doc = blocks("- [x] ~~done~~\n- [ ] todo\n");
+        assert_eq!(
+            doc[0],
+            Node::UnorderedList(vec![
+                ListItem::Task {
+                    status: TaskListStatus::Checked,
+                    content: vec![Node::Paragraph(vec![Node::Strikethrough(vec![
+                        Node::Text("done".into())
+                    ])])],
+                },
+                ListItem::Task {
+                    status: TaskListStatus::Unchecked,
+                    content: vec![Node::Paragraph(vec![Node::Text("todo".into())])],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn footnotes() {
+        let source = "Claim[^1].\n\n[^1]: Source.\n";
+        let doc = blocks(source);
+        assert_eq!(
+            doc,
+            vec![
+                Node::Paragraph(vec![
+                    Node::Text("Claim".into()),
+                    Node::FootnoteReference("1".into()),
+                    Node::Text(".".into()),
+                ]),
+                Node::FootnoteDefinition {
+                    label: "1".into(),
+                    content: vec![Node::Paragraph(vec![Node::Text("Source.".into())])],
+                },
+            ]
+        );
+        assert_eq!(write(&parse(source)), source);
+    }
+}
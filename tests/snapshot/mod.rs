@@ -0,0 +1,3 @@
+// Unit tests for golden-test snapshots
+
+mod render;
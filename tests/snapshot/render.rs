@@ -0,0 +1,122 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::snapshot::SnapshotOptions;
+use cmark_writer::{HtmlWriterOptions, WriterOptions};
+
+fn section<'a>(snapshot: &'a str, name: &str) -> &'a str {
+    let start = snapshot
+        .find(&format!("--- {} ---\n", name))
+        .expect("section")
+        + name.len()
+        + 9;
+    let end = snapshot[start..]
+        .find("--- ")
+        .map_or(snapshot.len(), |end| start + end);
+    &snapshot[start..end]
+}
+
+#[test]
+fn outline_shows_lists_tables_and_attributes() {
+    let doc = Node::Document(vec![
+        Node::OrderedList {
+            start: 3,
+            items: vec![ListItem::Ordered {
+                number: None,
+                content: vec![Node::Paragraph(vec![Node::Link {
+                    url: "https://example.com".into(),
+                    title: None,
+                    content: vec![Node::Text("site".into())],
+                }])],
+            }],
+        },
+        Node::Table {
+            headers: vec![Node::Text("a".into())],
+            #[cfg(feature = "gfm")]
+            alignments: vec![Default::default()],
+            rows: vec![vec![Node::InlineCode("b".into())]],
+        },
+        Node::code_block(Some("rust".into()), "fn main() {}\n".into()),
+    ]);
+    assert_eq!(
+        section(&doc.render_snapshot(), "outline"),
+        "Document\n\
+         \x20 OrderedList start=3\n\
+         \x20   Item\n\
+         \x20     Paragraph\n\
+         \x20       Link https://example.com\n\
+         \x20         Text \"site\"\n\
+         \x20 Table\n\
+         \x20   Header\n\
+         \x20     Text \"a\"\n\
+         \x20   Row\n\
+         \x20     InlineCode \"b\"\n\
+         \x20 CodeBlock language=\"rust\" \"fn main() {}\\n\"\n"
+    );
+}
+
+#[test]
+fn render_errors_are_recorded_in_their_section() {
+    // Strict mode rejects newlines in text; the HTML writer doesn't
+    let doc = Node::Paragraph(vec![Node::Text("one\ntwo".into())]);
+    let snapshot = doc.render_snapshot();
+    assert!(section(&snapshot, "commonmark").starts_with("error: "));
+    assert_eq!(section(&snapshot, "html"), "<p>one\ntwo</p>\n");
+}
+
+#[test]
+fn sections_use_the_given_options() {
+    let doc = Node::Paragraph(vec![Node::Emphasis(vec![Node::Text("x".into())])]);
+    let options = SnapshotOptions::new()
+        .with_commonmark_options(WriterOptions {
+            emphasis_char: '_',
+            ..Default::default()
+        })
+        .with_html_options(HtmlWriterOptions::default());
+    assert_eq!(
+        section(&doc.render_snapshot_with(&options), "commonmark"),
+        "_x_\n"
+    );
+}
+
+#[test]
+fn redactions_apply_in_order_to_every_section() {
+    let doc = Node::Document(vec![Node::Paragraph(vec![Node::Text(
+        "built 2024-05-01 by ci-7".into(),
+    )])]);
+    let options = SnapshotOptions::new()
+        .redact_with(|text| {
+            text.split(' ')
+                .map(|word| {
+                    if word.len() == 10 && word.as_bytes()[4] == b'-' {
+                        "[date]"
+                    } else {
+                        word
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .redact("ci-7", "[runner]")
+        .redact("[runner]", "[agent]");
+    let snapshot = doc.render_snapshot_with(&options);
+    assert_eq!(
+        section(&snapshot, "commonmark"),
+        "built [date] by [agent]\n"
+    );
+    assert_eq!(
+        section(&snapshot, "html"),
+        "<p>built [date] by [agent]</p>\n"
+    );
+    assert!(section(&snapshot, "outline").contains("\"built [date] by [agent]\""));
+}
+
+#[test]
+fn snapshots_are_deterministic() {
+    let doc = Node::Document(vec![
+        Node::heading(2, vec![Node::Text("Title".into())]),
+        Node::shared(Node::Paragraph(vec![Node::Text("Body".into())])),
+    ]);
+    assert_eq!(doc.render_snapshot(), doc.clone().render_snapshot());
+    assert!(doc
+        .render_snapshot()
+        .ends_with("  Paragraph\n    Text \"Body\"\n"));
+}
@@ -48,7 +48,9 @@ pub mod logger {
 }
 
 pub mod cmark {
+    #[cfg(feature = "gfm")]
     use cmark_writer::options::WriterOptionsBuilder;
+    #[cfg(feature = "gfm")]
     use cmark_writer::writer::CommonMarkWriter;
 
     /// Create a CommonMark writer with GFM features enabled.
@@ -62,19 +64,14 @@ pub mod cmark {
 
 pub mod html {
     use cmark_writer::ast::Node;
-    use cmark_writer::writer::{HtmlWriteResult, HtmlWriter, HtmlWriterOptions};
+    use cmark_writer::writer::{HtmlWriteResult, HtmlWriterOptions};
     use cmark_writer::ToHtml;
     use ecow::EcoString;
 
     /// Render a node to HTML using provided options.
     #[allow(dead_code)]
     pub fn render_node(node: &Node, options: &HtmlWriterOptions) -> HtmlWriteResult<EcoString> {
-        let mut html_writer = HtmlWriter::with_options(options.clone());
-        match node.to_html(&mut html_writer) {
-            Ok(()) => {}
-            Err(e) => return Err(cmark_writer::HtmlWriteError::CustomNodeError(e.to_string())),
-        }
-        Ok(html_writer.into_string())
+        node.to_html_with(options)
     }
 
     /// Render a node to HTML using default options.
@@ -0,0 +1,3 @@
+// Unit tests for inline text measuring and truncation
+
+mod truncate;
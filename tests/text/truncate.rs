@@ -0,0 +1,80 @@
+use cmark_writer::ast::Node;
+use cmark_writer::text::{display_width, truncate_with_ellipsis};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+#[test]
+fn content_that_fits_is_unchanged() {
+    let nodes = vec![text("short "), Node::Emphasis(vec![text("text")])];
+    assert_eq!(truncate_with_ellipsis(&nodes, 10), nodes);
+    assert_eq!(truncate_with_ellipsis(&nodes, 100), nodes);
+}
+
+#[test]
+fn cuts_across_node_boundaries_keeping_formatting() {
+    let nodes = vec![
+        text("ab"),
+        Node::Strong(vec![text("cd"), Node::Emphasis(vec![text("efgh")])]),
+        text("ij"),
+    ];
+    assert_eq!(
+        truncate_with_ellipsis(&nodes, 6),
+        [
+            text("ab"),
+            Node::Strong(vec![text("cd"), Node::Emphasis(vec![text("e")])]),
+            text("…"),
+        ]
+    );
+    assert_eq!(truncate_with_ellipsis(&nodes, 3), [text("ab"), text("…")]);
+}
+
+#[test]
+fn never_splits_wide_or_multibyte_characters() {
+    let nodes = vec![text("日本語テキスト")];
+    assert_eq!(display_width(&nodes), 14);
+    // Five columns leave four for text: two wide characters
+    assert_eq!(truncate_with_ellipsis(&nodes, 5), [text("日本"), text("…")]);
+    // Combining marks stay with their base character
+    let nodes = vec![text("e\u{301}e\u{301}e\u{301}")];
+    assert_eq!(display_width(&nodes), 3);
+    assert_eq!(
+        truncate_with_ellipsis(&nodes, 2),
+        [text("e\u{301}"), text("…")]
+    );
+}
+
+#[test]
+fn links_keep_their_destination_and_atoms_are_dropped() {
+    let nodes = vec![
+        Node::Link {
+            url: "/docs".into(),
+            title: None,
+            content: vec![text("documentation")],
+        },
+        Node::Image {
+            url: "/logo.png".into(),
+            title: None,
+            alt: vec![text("logo")],
+        },
+    ];
+    let preview = Node::Paragraph(truncate_with_ellipsis(&nodes, 5));
+    assert_eq!(preview.to_string(), "[docu](/docs)…\n");
+
+    let nodes = vec![
+        text("see "),
+        Node::Autolink {
+            url: "https://example.com".into(),
+            is_email: false,
+        },
+    ];
+    assert_eq!(truncate_with_ellipsis(&nodes, 10), [text("see"), text("…")]);
+}
+
+#[test]
+fn tiny_widths() {
+    let nodes = vec![text("abc")];
+    assert_eq!(truncate_with_ellipsis(&nodes, 1), [text("…")]);
+    assert!(truncate_with_ellipsis(&nodes, 0).is_empty());
+}
@@ -0,0 +1,31 @@
+use cmark_writer::ast::{HtmlElement, Node};
+use cmark_writer::writer::HtmlWriteError;
+use cmark_writer::{HtmlWriterOptions, ToHtml};
+
+#[test]
+fn to_html_with_renders_with_the_given_options() {
+    let paragraph = Node::Paragraph(vec![Node::Text("\"quoted\"".into())]);
+    assert_eq!(
+        paragraph
+            .to_html_with(&HtmlWriterOptions::default())
+            .unwrap(),
+        "<p>\"quoted\"</p>\n"
+    );
+    let options = HtmlWriterOptions {
+        escape_quotes_in_text: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        paragraph.to_html_with(&options).unwrap(),
+        "<p>&quot;quoted&quot;</p>\n"
+    );
+}
+
+#[test]
+fn to_html_with_keeps_html_error_kinds() {
+    let element = Node::HtmlElement(HtmlElement::new("bad tag"));
+    let error = element
+        .to_html_with(&HtmlWriterOptions::default())
+        .unwrap_err();
+    assert!(matches!(error, HtmlWriteError::InvalidHtmlTag(tag) if tag == "bad tag"));
+}
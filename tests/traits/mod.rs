@@ -1,5 +1,7 @@
 // Unit tests for traits
 
 mod core;
+mod formatting;
 mod processing;
+mod sink;
 mod utils;
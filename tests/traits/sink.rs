@@ -0,0 +1,101 @@
+use cmark_writer::ast::Node;
+use cmark_writer::{CommonMarkWriter, DynCommonMarkSink, DynHtmlSink, HtmlWriter, WriteResult};
+
+/// A plugin that only sees the writers as trait objects
+fn render_badge(sink: &mut dyn DynCommonMarkSink, label: &str) -> WriteResult<()> {
+    sink.write_str("[")?;
+    sink.write_node(&Node::Strong(vec![Node::Text(label.into())]))?;
+    sink.write_char(']')
+}
+
+fn render_badge_html(sink: &mut dyn DynHtmlSink, label: &str) -> WriteResult<()> {
+    sink.start_tag("span")?;
+    sink.attribute("class", "badge")?;
+    sink.finish_tag()?;
+    sink.text(label)?;
+    sink.end_tag("span")
+}
+
+#[test]
+fn commonmark_sink_forwards_to_writer() {
+    let mut writer = CommonMarkWriter::new();
+    render_badge(&mut writer, "new").unwrap();
+    assert!(writer.is_strict());
+    assert_eq!(writer.into_string(), "[**new**]");
+}
+
+#[test]
+fn html_sink_forwards_to_writer() {
+    let mut writer = HtmlWriter::new();
+    render_badge_html(&mut writer, "a < b").unwrap();
+    assert_eq!(
+        writer.into_string(),
+        r#"<span class="badge">a &lt; b</span>"#
+    );
+}
+
+#[test]
+fn html_sink_errors_convert_to_write_error() {
+    let mut writer = HtmlWriter::new();
+    let sink: &mut dyn DynHtmlSink = &mut writer;
+    // No tag is open
+    assert!(sink.attribute("id", "x").is_err());
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_sink_functions() {
+    use cmark_writer::ffi::*;
+
+    let options = cmark_writer::WriterOptionsBuilder::new()
+        .escape_special_chars(true)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    let mut sink = CmarkSink::new(&mut writer);
+    let text = "a*b";
+    unsafe {
+        assert_eq!(
+            cmark_writer_sink_write_str(&mut sink, "x ".as_ptr(), 2),
+            CMARK_WRITER_OK
+        );
+        assert_eq!(
+            cmark_writer_sink_write_text(&mut sink, text.as_ptr(), text.len()),
+            CMARK_WRITER_OK
+        );
+        assert_eq!(
+            cmark_writer_sink_write_str(&mut sink, [0xff].as_ptr(), 1),
+            CMARK_WRITER_ERR_UTF8
+        );
+        assert_eq!(
+            cmark_writer_sink_write_str(std::ptr::null_mut(), text.as_ptr(), 1),
+            CMARK_WRITER_ERR_NULL
+        );
+    }
+    assert_eq!(writer.into_string(), r"x a\*b");
+
+    let mut html = HtmlWriter::new();
+    let mut sink = HtmlSink::new(&mut html);
+    unsafe {
+        assert_eq!(
+            cmark_writer_html_sink_start_tag(&mut sink, "b".as_ptr(), 1),
+            CMARK_WRITER_OK
+        );
+        assert_eq!(
+            cmark_writer_html_sink_attribute(&mut sink, "id".as_ptr(), 2, "x".as_ptr(), 1),
+            CMARK_WRITER_OK
+        );
+        assert_eq!(
+            cmark_writer_html_sink_finish_tag(&mut sink),
+            CMARK_WRITER_OK
+        );
+        assert_eq!(
+            cmark_writer_html_sink_end_tag(&mut sink, "b".as_ptr(), 1),
+            CMARK_WRITER_OK
+        );
+        assert_eq!(
+            cmark_writer_html_sink_attribute(&mut sink, "id".as_ptr(), 2, "x".as_ptr(), 1),
+            CMARK_WRITER_ERR_WRITE
+        );
+    }
+    assert_eq!(html.into_string(), r#"<b id="x"></b>"#);
+}
@@ -1,8 +1,20 @@
 // Unit test entrypoint to group fine-grained tests by submodules
 
 mod ast;
+mod capability;
 mod error;
+#[cfg(feature = "escape-audit")]
+mod escape_audit;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod lint;
+mod ops;
 mod options;
+#[cfg(feature = "parser")]
+mod parser;
+mod snapshot;
 mod support;
+mod text;
 mod traits;
+mod validate;
 mod writer;
@@ -0,0 +1,134 @@
+use cmark_writer::ast::{ListItem, Node, NodePath};
+use cmark_writer::validate::{check_links, LinkIssueKind};
+use cmark_writer::HtmlElement;
+
+fn link(url: &str) -> Node {
+    Node::Link {
+        url: url.into(),
+        title: None,
+        content: vec![Node::Text("link".into())],
+    }
+}
+
+fn reference(label: &str) -> Node {
+    Node::ReferenceLink {
+        label: label.into(),
+        content: vec![Node::Text(label.into())],
+    }
+}
+
+fn definition(label: &str) -> Node {
+    Node::LinkReferenceDefinition {
+        label: label.into(),
+        destination: "/url".into(),
+        title: None,
+    }
+}
+
+#[test]
+fn consistent_document_has_no_issues() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Intro".into())]),
+        Node::heading(2, vec![Node::Text("Intro".into())]),
+        Node::HtmlElement(HtmlElement::new("div").with_attribute("id", "custom")),
+        Node::Paragraph(vec![
+            link("#intro"),
+            link("#intro-1"),
+            link("#custom"),
+            link("#"),
+            link("https://example.com/#elsewhere"),
+            reference("docs"),
+        ]),
+        definition("docs"),
+    ]);
+    assert!(check_links(&doc).is_empty());
+}
+
+#[test]
+fn reports_issues_with_paths() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Strong(vec![Node::Text("Set up".into())])]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![reference("missing"), link("#setup")])],
+        }]),
+        Node::Paragraph(vec![link("#set-up")]),
+        definition("unused"),
+    ]);
+
+    let issues: Vec<_> = check_links(&doc)
+        .into_iter()
+        .map(|issue| (issue.kind, issue.path, issue.target.to_string()))
+        .collect();
+    assert_eq!(
+        issues,
+        vec![
+            (
+                LinkIssueKind::UndefinedReference,
+                NodePath::root().child(1).list_item(0, 0).child(0),
+                "missing".to_string()
+            ),
+            (
+                LinkIssueKind::BrokenAnchor,
+                NodePath::root().child(1).list_item(0, 0).child(1),
+                "setup".to_string()
+            ),
+            (
+                LinkIssueKind::UnusedDefinition,
+                NodePath::root().child(3),
+                "unused".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn labels_match_case_and_whitespace_insensitively() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![reference("Foo"), reference("  multi\n word ")]),
+        definition("foo"),
+        definition("MULTI WORD"),
+    ]);
+    assert!(check_links(&doc).is_empty());
+
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![reference("foo-bar")]),
+        definition("foo bar"),
+    ]);
+    let kinds: Vec<_> = check_links(&doc)
+        .into_iter()
+        .map(|issue| issue.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            LinkIssueKind::UndefinedReference,
+            LinkIssueKind::UnusedDefinition
+        ]
+    );
+}
+
+#[test]
+fn custom_heading_ids_are_anchors() {
+    let doc = Node::Document(vec![
+        Node::Heading {
+            level: 2,
+            content: vec![Node::Text("Install".into())],
+            heading_type: Default::default(),
+            custom_id: Some("setup".into()),
+        },
+        Node::Paragraph(vec![link("#setup"), link("#install")]),
+    ]);
+    let issues = check_links(&doc);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, LinkIssueKind::BrokenAnchor);
+    assert_eq!(issues[0].target, "install");
+}
+
+#[test]
+fn shared_headings_are_anchors() {
+    let doc = Node::Document(vec![
+        Node::shared(Node::heading(2, vec![Node::Text("Usage".into())])),
+        Node::Paragraph(vec![link("#usage")]),
+    ]);
+    assert!(check_links(&doc).is_empty());
+}
@@ -0,0 +1,4 @@
+// Unit tests for document-level validation
+
+mod links;
+mod slugs;
@@ -0,0 +1,55 @@
+use cmark_writer::ast::{Node, NodePath, SlugOptions};
+use cmark_writer::validate::heading_slug_collisions;
+
+fn heading(level: u8, text: &str) -> Node {
+    Node::heading(level, vec![Node::Text(text.into())])
+}
+
+#[test]
+fn reports_headings_sharing_a_slug() {
+    let doc = Node::Document(vec![
+        heading(1, "Install"),
+        heading(2, "Usage"),
+        Node::BlockQuote(vec![heading(3, "usage!")]),
+        heading(2, "Install"),
+        heading(2, "Other"),
+    ]);
+
+    let collisions = heading_slug_collisions(&doc, &SlugOptions::default());
+    let slugs: Vec<_> = collisions.iter().map(|c| c.slug.as_str()).collect();
+    assert_eq!(slugs, ["install", "usage"]);
+
+    assert_eq!(
+        collisions[1].headings,
+        [
+            (NodePath::root().child(1), "Usage".into()),
+            (NodePath::root().child(2).child(0), "usage!".into()),
+        ]
+    );
+    assert_eq!(
+        collisions[0].to_string(),
+        r#"slug #install is shared by 2 headings: "Install" at /0 "Install" at /3"#
+    );
+}
+
+#[test]
+fn collisions_depend_on_options() {
+    let doc = Node::Document(vec![heading(1, "Café"), heading(1, "Cafe")]);
+    assert!(heading_slug_collisions(&doc, &SlugOptions::default()).is_empty());
+
+    let options = SlugOptions::default().with_transliterate(true);
+    assert_eq!(heading_slug_collisions(&doc, &options).len(), 1);
+}
+
+#[test]
+fn custom_ids_replace_slugs() {
+    let doc = Node::Document(vec![
+        Node::heading_with_id(2, vec![Node::Text("Usage".into())], "usage-v1"),
+        heading(2, "Usage"),
+        Node::heading_with_id(2, vec![Node::Text("Other".into())], "usage"),
+    ]);
+    let collisions = heading_slug_collisions(&doc, &SlugOptions::default());
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].slug, "usage");
+    assert_eq!(collisions[0].headings[1].0, NodePath::root().child(2));
+}
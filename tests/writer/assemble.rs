@@ -0,0 +1,151 @@
+use cmark_writer::ast::{HtmlElement, Node, NodePath};
+use cmark_writer::capability::Capability;
+use cmark_writer::writer::assemble::{DocumentAssembler, Fragment};
+use cmark_writer::writer::OutputTarget;
+use cmark_writer::{HtmlWriterOptions, WriteError, WriterOptions};
+
+fn paragraph(text: &str) -> Node {
+    Node::Paragraph(vec![Node::Text(text.into())])
+}
+
+fn commonmark() -> OutputTarget {
+    OutputTarget::CommonMark(WriterOptions::default())
+}
+
+#[test]
+fn splices_documents_and_appends_other_nodes() {
+    let assembly = DocumentAssembler::new(commonmark())
+        .with_fragment(Fragment::new(
+            "header",
+            Node::Document(vec![paragraph("a"), paragraph("b")]),
+        ))
+        .with_fragment(Fragment::new("body", paragraph("c")))
+        .assemble()
+        .unwrap();
+
+    assert!(assembly.is_lossless());
+    assert_eq!(
+        assembly.document,
+        Node::Document(vec![paragraph("a"), paragraph("b"), paragraph("c")])
+    );
+    assert_eq!(assembly.document.to_string(), "a\n\nb\n\nc\n");
+}
+
+#[test]
+fn reports_degraded_nodes_with_their_fragment_and_path() {
+    let mut assembler = DocumentAssembler::new(commonmark());
+    assembler.push(Fragment::new("intro", paragraph("plain")));
+    assembler.push(Fragment::new(
+        "ids",
+        Node::Document(vec![
+            paragraph("x"),
+            Node::BlockQuote(vec![Node::heading_with_id(
+                2,
+                vec![Node::Text("Anchored".into())],
+                "anchored",
+            )]),
+        ]),
+    ));
+    let assembly = assembler.assemble().unwrap();
+
+    assert_eq!(assembly.degraded.len(), 1);
+    let degradation = &assembly.degraded[0];
+    assert_eq!(degradation.fragment, "ids");
+    assert_eq!(degradation.capability, Capability::HeadingIds);
+    assert_eq!(degradation.path, NodePath::root().child(2).child(0));
+    assert_eq!(
+        degradation.to_string(),
+        "heading_ids at /2/0 (from fragment \"ids\") is not supported by the target"
+    );
+}
+
+#[test]
+fn declared_requirements_must_be_supported() {
+    let fragment = Fragment::new("raw", paragraph("x")).requires(Capability::HeadingIds);
+    let error = DocumentAssembler::new(commonmark())
+        .with_fragment(fragment.clone())
+        .assemble()
+        .unwrap_err();
+    match error {
+        WriteError::Custom { message, code } => {
+            assert_eq!(code.as_deref(), Some("unsupported_capability"));
+            assert!(message.contains("\"raw\""));
+            assert!(message.contains("heading_ids"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    // The HTML writer supports heading ids
+    let assembly = DocumentAssembler::new(OutputTarget::Html(HtmlWriterOptions::default()))
+        .with_fragment(fragment)
+        .assemble()
+        .unwrap();
+    assert!(assembly.is_lossless());
+}
+
+#[test]
+fn targets_differ_in_what_degrades() {
+    let table = Node::Table {
+        headers: vec![paragraph("block")],
+        #[cfg(feature = "gfm")]
+        alignments: vec![],
+        rows: vec![],
+    };
+    let fragment = Fragment::new(
+        "table",
+        Node::Document(vec![table, Node::HtmlElement(HtmlElement::new("br"))]),
+    );
+
+    let strict = DocumentAssembler::new(commonmark())
+        .with_fragment(fragment.clone())
+        .assemble()
+        .unwrap();
+    let capabilities: Vec<_> = strict.degraded.iter().map(|d| d.capability).collect();
+    assert_eq!(capabilities, [Capability::TableBlockContent]);
+
+    let html = DocumentAssembler::new(OutputTarget::Html(HtmlWriterOptions::default()))
+        .with_fragment(fragment)
+        .assemble()
+        .unwrap();
+    assert!(html.is_lossless());
+}
+
+#[test]
+fn reports_extensions_the_target_writes_as_fallbacks() {
+    let notes = Node::Document(vec![
+        Node::Paragraph(vec![
+            Node::Text("See".into()),
+            Node::FootnoteReference("1".into()),
+        ]),
+        Node::FootnoteDefinition {
+            label: "1".into(),
+            content: vec![paragraph("Note")],
+        },
+    ]);
+    let assembly = DocumentAssembler::new(commonmark())
+        .with_fragment(Fragment::new("notes", notes.clone()))
+        .assemble()
+        .unwrap();
+    let degraded: Vec<_> = assembly
+        .degraded
+        .iter()
+        .map(|degradation| (degradation.capability, degradation.path.to_string()))
+        .collect();
+    assert_eq!(
+        degraded,
+        [
+            (Capability::Footnotes, "/0/1".to_string()),
+            (Capability::Footnotes, "/1".to_string())
+        ]
+    );
+
+    let target = OutputTarget::CommonMark(WriterOptions {
+        footnotes: true,
+        ..Default::default()
+    });
+    let assembly = DocumentAssembler::new(target)
+        .with_fragment(Fragment::new("notes", notes))
+        .assemble()
+        .unwrap();
+    assert!(assembly.is_lossless());
+}
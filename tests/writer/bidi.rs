@@ -0,0 +1,49 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::html::BidiIsolation;
+use cmark_writer::writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions, TextDirection};
+use cmark_writer::{ToCommonMark, ToHtml};
+
+fn mixed_paragraph() -> Node {
+    Node::Paragraph(vec![
+        Node::Text("Hello ".into()),
+        Node::Strong(vec![Node::Text("שלום".into())]),
+        Node::Text(" 123".into()),
+    ])
+}
+
+#[test]
+fn commonmark_wraps_opposite_direction_text_in_isolates() {
+    let options = WriterOptionsBuilder::new().bidi_isolation(true).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    mixed_paragraph().to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "Hello **\u{2068}שלום\u{2069}** 123\n");
+
+    let options = WriterOptionsBuilder::new()
+        .bidi_isolation(true)
+        .document_direction(TextDirection::Rtl)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    mixed_paragraph().to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "\u{2068}Hello \u{2069}**שלום** 123\n");
+}
+
+#[test]
+fn html_isolation_markup() {
+    let render = |isolation: Option<BidiIsolation>| {
+        let options = HtmlWriterOptions::default().with_bidi_isolation(isolation);
+        let mut writer = HtmlWriter::with_options(options);
+        mixed_paragraph().to_html(&mut writer).unwrap();
+        writer.into_string()
+    };
+
+    assert_eq!(render(None), "<p>Hello <strong>שלום</strong> 123</p>\n");
+    assert_eq!(
+        render(Some(BidiIsolation::Bdi)),
+        "<p>Hello <strong><bdi>שלום</bdi></strong> 123</p>\n"
+    );
+    assert_eq!(
+        render(Some(BidiIsolation::DirAuto)),
+        "<p>Hello <strong><span dir=\"auto\">שלום</span></strong> 123</p>\n"
+    );
+}
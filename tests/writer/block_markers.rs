@@ -0,0 +1,90 @@
+use cmark_writer::ast::{ListItem, Node, NodePath};
+use cmark_writer::writer::multi::{render_multi, OutputTarget};
+use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml};
+
+fn document() -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Title".into())]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![Node::Text("item".into())])],
+        }]),
+    ])
+}
+
+fn render(node: &Node, options: HtmlWriterOptions) -> String {
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn no_markers_by_default() {
+    let html = render(&document(), HtmlWriterOptions::default());
+    assert!(!html.contains("cmark:block"));
+}
+
+#[test]
+fn markers_wrap_each_top_level_block() {
+    let html = render(
+        &document(),
+        HtmlWriterOptions::default().with_block_markers(true),
+    );
+    assert_eq!(
+        html,
+        "<!-- cmark:block start path=/0 -->\n\
+         <h1>Title</h1>\n\
+         <!-- cmark:block end path=/0 -->\n\
+         <!-- cmark:block start path=/1 -->\n\
+         <ul>\n<li><p>item</p>\n</li>\n</ul>\n\
+         <!-- cmark:block end path=/1 -->\n"
+    );
+}
+
+#[test]
+fn markers_skip_nested_documents() {
+    let doc = Node::Document(vec![Node::BlockQuote(vec![Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("inner".into())]),
+    ])])]);
+    let html = render(&doc, HtmlWriterOptions::default().with_block_markers(true));
+    assert_eq!(html.matches("cmark:block start").count(), 1);
+    assert!(html.starts_with("<!-- cmark:block start path=/0 -->\n<blockquote>"));
+}
+
+#[test]
+fn markers_stay_outside_footnote_section() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::FootnoteReference("a".into())]),
+        Node::FootnoteDefinition {
+            label: "a".into(),
+            content: vec![Node::Paragraph(vec![Node::Text("note".into())])],
+        },
+    ]);
+    let html = render(&doc, HtmlWriterOptions::default().with_block_markers(true));
+    assert!(!html.contains("path=/1"));
+    let end = html.find("<!-- cmark:block end path=/0 -->").unwrap();
+    assert!(html[end..].contains("note"));
+}
+
+#[test]
+fn incremental_blocks_carry_markers() {
+    let options = HtmlWriterOptions::default().with_block_markers(true);
+    let doc = document();
+    let mut writer = HtmlWriter::with_options(options.clone());
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+    assert_eq!(blocks[1].0, NodePath::root().child(1));
+    assert!(blocks[1]
+        .1
+        .starts_with("<!-- cmark:block start path=/1 -->\n<ul>"));
+
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    assert_eq!(joined, render(&doc, options));
+}
+
+#[test]
+fn render_multi_html_carries_markers() {
+    let options = HtmlWriterOptions::default().with_block_markers(true);
+    let doc = document();
+    let result = render_multi(&doc, &[OutputTarget::Html(options.clone())]);
+    let html = result.html().unwrap().as_ref().unwrap();
+    assert_eq!(html.as_str(), render(&doc, options));
+}
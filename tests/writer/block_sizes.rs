@@ -0,0 +1,62 @@
+use cmark_writer::ast::{Node, NodePath};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions, ToCommonMark, ToHtml};
+
+fn document() -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Title".into())]),
+        Node::Paragraph(vec![Node::Text("body".into())]),
+    ])
+}
+
+#[test]
+fn block_sizes_are_empty_by_default() {
+    let mut writer = CommonMarkWriter::new();
+    document().to_commonmark(&mut writer).unwrap();
+    assert!(writer.block_sizes().is_empty());
+
+    let mut writer = HtmlWriter::new();
+    document().to_html(&mut writer).unwrap();
+    assert!(writer.block_sizes().is_empty());
+}
+
+#[test]
+fn commonmark_block_sizes_per_top_level_block() {
+    let options = WriterOptionsBuilder::new().track_block_sizes(true).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    document().to_commonmark(&mut writer).unwrap();
+    assert_eq!(
+        writer.block_sizes(),
+        [
+            (NodePath::root().child(0), 8),
+            (NodePath::root().child(1), 5)
+        ]
+    );
+}
+
+#[test]
+fn html_block_sizes_per_top_level_block() {
+    let options = HtmlWriterOptions::default().with_track_block_sizes(true);
+    let mut writer = HtmlWriter::with_options(options);
+    document().to_html(&mut writer).unwrap();
+    assert_eq!(
+        writer.block_sizes(),
+        [
+            (NodePath::root().child(0), 15),
+            (NodePath::root().child(1), 12)
+        ]
+    );
+    let total: usize = writer.block_sizes().iter().map(|(_, size)| size).sum();
+    assert_eq!(total, writer.into_string().len());
+}
+
+#[test]
+fn block_sizes_reset_for_each_document() {
+    let options = WriterOptionsBuilder::new().track_block_sizes(true).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    document().to_commonmark(&mut writer).unwrap();
+    Node::Document(vec![Node::Paragraph(vec![Node::Text("x".into())])])
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.block_sizes(), [(NodePath::root().child(0), 2)]);
+}
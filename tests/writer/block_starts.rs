@@ -0,0 +1,116 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, ToCommonMark};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn paragraph(content: Vec<Node>) -> String {
+    // Lenient, so text nodes may contain line breaks
+    let options = WriterOptionsBuilder::new().strict(false).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    Node::Paragraph(content).to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn escapes_block_markers_at_paragraph_start() {
+    let cases = [
+        ("# not a heading", "\\# not a heading"),
+        ("###### six", "\\###### six"),
+        ("> not a quote", "\\> not a quote"),
+        ("- not a list", "\\- not a list"),
+        ("+ not a list", "\\+ not a list"),
+        ("* not a list", "\\* not a list"),
+        ("1. not a list", "1\\. not a list"),
+        ("2026) not a list", "2026\\) not a list"),
+        ("***", "\\***"),
+        ("_ _ _", "\\_ _ _"),
+        ("```rust", "\\```rust"),
+        ("~~~", "\\~~~"),
+        ("   # indented", "   \\# indented"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(paragraph(vec![text(input)]), format!("{}\n", expected));
+    }
+}
+
+#[test]
+fn leaves_ordinary_text_alone() {
+    for input in [
+        "#hashtag",
+        "####### seven",
+        "-dash",
+        "*emphasis*",
+        "1.5 million",
+        "1234567890. too long",
+        "``code``",
+        "    # code block indent",
+    ] {
+        assert_eq!(paragraph(vec![text(input)]), format!("{}\n", input));
+    }
+}
+
+#[test]
+fn escapes_every_line_start() {
+    assert_eq!(
+        paragraph(vec![text("Title\n===\nmore\n---\n> quote")]),
+        "Title\n\\===\nmore\n\\---\n\\> quote\n"
+    );
+    assert_eq!(
+        paragraph(vec![text("one"), Node::SoftBreak, text("- two")]),
+        "one\n\\- two\n"
+    );
+    assert_eq!(
+        paragraph(vec![text("one"), Node::HardBreak, text("# two")]),
+        "one\\\n\\# two\n"
+    );
+}
+
+#[test]
+fn text_after_inline_content_is_not_escaped() {
+    assert_eq!(
+        paragraph(vec![Node::Strong(vec![text("Note")]), text(" - # > fine")]),
+        "**Note** - # > fine\n"
+    );
+    assert_eq!(
+        paragraph(vec![Node::Emphasis(vec![text("# inside")])]),
+        "_# inside_\n"
+    );
+}
+
+#[test]
+fn escapes_inside_containers() {
+    let doc = Node::Document(vec![
+        Node::BlockQuote(vec![Node::Paragraph(vec![text("# quoted")])]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![text("1. nested")])],
+        }]),
+    ]);
+    let mut writer = CommonMarkWriter::new();
+    doc.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "> \\# quoted\n\n- 1\\. nested\n");
+}
+
+#[test]
+fn composes_with_special_char_escaping() {
+    let options = WriterOptionsBuilder::new()
+        .strict(false)
+        .escape_special_chars(true)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    Node::Paragraph(vec![text("> *quote*\n- item")])
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "\\> \\*quote\\*\n\\- item\n");
+}
+
+#[test]
+fn headings_are_not_affected() {
+    let mut writer = CommonMarkWriter::new();
+    Node::heading(2, vec![text("1. Introduction")])
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "## 1. Introduction\n");
+}
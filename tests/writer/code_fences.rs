@@ -0,0 +1,52 @@
+use cmark_writer::ast::{CodeBlockType, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{ToCommonMark, WriteError};
+
+fn fenced(language: Option<&str>, content: &str) -> Node {
+    Node::CodeBlock {
+        language: language.map(Into::into),
+        content: content.into(),
+        block_type: CodeBlockType::Fenced,
+    }
+}
+
+fn render(node: &Node, strict: bool) -> Result<String, WriteError> {
+    let options = WriterOptionsBuilder::new().strict(strict).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+#[test]
+fn info_string_with_backticks_uses_tilde_fence() {
+    let node = fenced(Some("say`hi`"), "x");
+    assert_eq!(render(&node, true).unwrap(), "~~~say`hi`\nx\n~~~\n");
+}
+
+#[test]
+fn info_string_line_break_is_error_in_strict_mode() {
+    let node = fenced(Some("rust\n# heading"), "x");
+    assert!(matches!(
+        render(&node, true).unwrap_err().without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+}
+
+#[test]
+fn info_string_line_break_is_replaced_in_non_strict_mode() {
+    let node = fenced(Some("rust\r\nlinenos"), "x");
+    assert_eq!(render(&node, false).unwrap(), "```rust linenos\nx\n```\n");
+}
+
+#[test]
+fn fence_is_longer_than_fences_in_content() {
+    let node = fenced(Some("markdown"), "```rust\ncode\n```");
+    assert_eq!(
+        render(&node, true).unwrap(),
+        "````markdown\n```rust\ncode\n```\n````\n"
+    );
+
+    let node = fenced(Some("a`b"), "~~~~\n");
+    assert_eq!(render(&node, true).unwrap(), "~~~~~a`b\n~~~~\n~~~~~\n");
+}
@@ -0,0 +1,108 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, EscapeStrategy, ToCommonMark};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn render(node: &Node, strategy: EscapeStrategy) -> String {
+    let options = WriterOptionsBuilder::new()
+        .escape_special_chars(true)
+        .escape_strategy(strategy)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn paragraph(s: &str) -> String {
+    render(&Node::Paragraph(vec![text(s)]), EscapeStrategy::Contextual)
+}
+
+#[test]
+fn literal_characters_are_left_alone() {
+    let cases = [
+        "2 * 3 = 6",
+        "snake_case_name",
+        "a < b > c",
+        "C:\\Users",
+        "AT&T & co",
+        "1.5 # of items",
+    ];
+    for case in cases {
+        assert_eq!(paragraph(case), format!("{}\n", case), "{:?}", case);
+    }
+}
+
+#[test]
+fn characters_that_would_change_parsing_are_escaped() {
+    let cases = [
+        ("*not emphasis*", "\\*not emphasis\\*"),
+        ("_not emphasis_", "\\_not emphasis\\_"),
+        ("**not strong**", "\\*\\*not strong\\*\\*"),
+        ("foo*bar", "foo\\*bar"),
+        ("<div>", "\\<div>"),
+        ("<https://example.com>", "\\<https://example.com>"),
+        ("\\*", "\\\\\\*"),
+        ("&amp; &#35; &#x23;", "\\&amp; \\&#35; \\&#x23;"),
+        ("[label]", "\\[label\\]"),
+        ("`code`", "\\`code\\`"),
+        ("trailing\\", "trailing\\\\"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(paragraph(input), format!("{}\n", expected), "{:?}", input);
+    }
+}
+
+#[test]
+fn text_next_to_delimiters_is_escaped() {
+    let node = Node::Emphasis(vec![text("_x")]);
+    assert_eq!(render(&node, EscapeStrategy::Contextual), "_\\_x_");
+
+    let node = Node::Paragraph(vec![Node::Strong(vec![text("a")]), text("*b")]);
+    assert_eq!(render(&node, EscapeStrategy::Contextual), "**a**\\*b\n");
+}
+
+#[test]
+fn always_strategy_escapes_every_special_character() {
+    let node = Node::Paragraph(vec![text("2 * 3 = snake_case <")]);
+    assert_eq!(
+        render(&node, EscapeStrategy::Always),
+        "2 \\* 3 = snake\\_case \\<\n"
+    );
+}
+
+#[cfg(feature = "parser")]
+#[test]
+fn escaped_text_parses_back_to_itself() {
+    use cmark_writer::parser::parse;
+
+    let cases = [
+        "2 * 3 * 4",
+        "*a* _b_ **c** __d__",
+        "snake_case and _private",
+        "x_ *y",
+        "a<b, <b>, <!-- c -->, <http://x.y>",
+        "\\*, \\\\, \\a",
+        "&copy; &#169; &#xA9; & ;",
+        "[a](b) ![c](d) [e][f]",
+        "`a` ``b``",
+        "foo__bar__baz",
+        "émoji_✨_x *✨*",
+    ];
+    for case in cases {
+        let output = paragraph(case);
+        let Node::Document(blocks) = parse(&output) else {
+            panic!("not a document");
+        };
+        assert!(
+            matches!(&blocks[..], [Node::Paragraph(content)]
+                if content.iter().all(|node| matches!(node, Node::Text(_)))),
+            "{:?} -> {:?}",
+            case,
+            output
+        );
+        assert_eq!(blocks[0].plain_text(), case, "{:?} -> {:?}", case, output);
+    }
+}
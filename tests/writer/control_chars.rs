@@ -0,0 +1,64 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{ControlCharPolicy, ToCommonMark, WriteError};
+
+fn render(node: &Node, strict: bool, policy: ControlCharPolicy) -> Result<String, WriteError> {
+    let options = WriterOptionsBuilder::new()
+        .strict(strict)
+        .control_chars(policy)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+#[test]
+fn strict_mode_rejects_with_position() {
+    let text = Node::Text("ab\u{0}c".into());
+    let err = render(&text, true, ControlCharPolicy::Auto).unwrap_err();
+    match err.without_location() {
+        WriteError::InvalidCharacter {
+            context,
+            position,
+            character,
+        } => {
+            assert_eq!(context, "Text");
+            assert_eq!(*position, 2);
+            assert_eq!(*character, '\u{0}');
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    let code = Node::InlineCode("\u{FEFF}x".into());
+    assert!(matches!(
+        render(&code, true, ControlCharPolicy::Auto)
+            .unwrap_err()
+            .without_location(),
+        WriteError::InvalidCharacter { position: 0, .. }
+    ));
+}
+
+#[test]
+fn non_strict_mode_allows_by_default() {
+    let text = Node::Text("a\u{7}b".into());
+    assert_eq!(
+        render(&text, false, ControlCharPolicy::Auto).unwrap(),
+        "a\u{7}b"
+    );
+}
+
+#[test]
+fn strip_removes_controls_but_keeps_whitespace() {
+    let text = Node::Text("a\u{0}\tb\u{FEFF}\u{85}".into());
+    assert_eq!(
+        render(&text, true, ControlCharPolicy::Strip).unwrap(),
+        "a\tb"
+    );
+
+    let block = Node::code_block(Some("text".into()), "x\u{1b}[0m\ny\n".into());
+    assert_eq!(
+        render(&block, true, ControlCharPolicy::Strip).unwrap(),
+        "```text\nx[0m\ny\n```\n"
+    );
+}
@@ -0,0 +1,156 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::html::AnchorStyle;
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriteError, HtmlWriter, HtmlWriterOptions, ToCommonMark, ToHtml,
+    WriteError,
+};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn heading(level: u8, s: &str) -> Node {
+    Node::heading(level, vec![text(s)])
+}
+
+fn cross_ref(target: &str) -> Node {
+    Node::CrossRef {
+        target: target.into(),
+        text: None,
+    }
+}
+
+fn doc(reference: Node) -> Node {
+    Node::Document(vec![
+        Node::Paragraph(vec![text("See "), reference]),
+        heading(2, "Usage"),
+        heading(2, "Getting Started"),
+        heading(2, "Usage"),
+        Node::Heading {
+            level: 2,
+            content: vec![text("Install")],
+            heading_type: Default::default(),
+            custom_id: Some("setup".into()),
+        },
+    ])
+}
+
+fn cmark(node: &Node, strict: bool) -> Result<String, WriteError> {
+    let options = WriterOptionsBuilder::new().strict(strict).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+fn first_line(output: &str) -> &str {
+    output.lines().next().unwrap()
+}
+
+fn html(node: &Node, options: HtmlWriterOptions) -> Result<String, WriteError> {
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+#[test]
+fn forward_reference_links_to_heading_slug() {
+    let output = cmark(&doc(cross_ref("Getting Started")), true).unwrap();
+    assert_eq!(
+        first_line(&output),
+        "See [Getting Started](#getting-started)"
+    );
+}
+
+#[test]
+fn target_can_be_slug_or_explicit_id() {
+    let output = cmark(&doc(cross_ref("usage-1")), true).unwrap();
+    assert_eq!(first_line(&output), "See [Usage](#usage-1)");
+
+    let output = cmark(&doc(cross_ref("setup")), true).unwrap();
+    assert_eq!(first_line(&output), "See [Install](#setup)");
+
+    let output = cmark(&doc(cross_ref("Install")), true).unwrap();
+    assert_eq!(first_line(&output), "See [Install](#setup)");
+}
+
+#[test]
+fn explicit_text_replaces_heading_text() {
+    let reference = Node::CrossRef {
+        target: "getting-started".into(),
+        text: Some(vec![Node::Emphasis(vec![text("the guide")])]),
+    };
+    let output = cmark(&doc(reference), true).unwrap();
+    assert_eq!(first_line(&output), "See [_the guide_](#getting-started)");
+}
+
+#[test]
+fn missing_target_is_an_error_in_strict_mode() {
+    let error = cmark(&doc(cross_ref("Nowhere")), true).unwrap_err();
+    assert!(
+        matches!(error.without_location(), WriteError::InvalidStructure(message)
+        if message.contains("Unresolved cross-reference to 'Nowhere'"))
+    );
+
+    let output = cmark(&doc(cross_ref("Nowhere")), false).unwrap();
+    assert_eq!(first_line(&output), "See Nowhere");
+}
+
+#[test]
+fn text_shared_by_several_headings_is_ambiguous() {
+    let error = cmark(&doc(cross_ref("Usage")), true).unwrap_err();
+    assert!(
+        matches!(error.without_location(), WriteError::InvalidStructure(message)
+        if message.contains("#usage, #usage-1"))
+    );
+
+    let output = cmark(&doc(cross_ref("Usage")), false).unwrap();
+    assert_eq!(first_line(&output), "See [Usage](#usage)");
+}
+
+#[test]
+fn html_gives_referenced_headings_an_id() {
+    let output = html(&doc(cross_ref("usage-1")), HtmlWriterOptions::default()).unwrap();
+    assert_eq!(
+        output,
+        "<p>See <a href=\"#usage-1\">Usage</a></p>\n\
+         <h2>Usage</h2>\n\
+         <h2>Getting Started</h2>\n\
+         <h2 id=\"usage-1\">Usage</h2>\n\
+         <h2 id=\"setup\">Install</h2>\n"
+    );
+}
+
+#[test]
+fn html_links_match_heading_anchors() {
+    let options = HtmlWriterOptions::default().with_heading_anchor(Some(AnchorStyle::default()));
+    let output = html(&doc(cross_ref("Getting Started")), options).unwrap();
+    assert!(output.starts_with("<p>See <a href=\"#getting-started\">Getting Started</a></p>\n"));
+    assert!(output.contains("<h2 id=\"getting-started\">"));
+}
+
+#[test]
+fn html_missing_target_follows_strict_mode() {
+    let strict = HtmlWriterOptions::default().with_strict(true);
+    let error = html(&doc(cross_ref("Nowhere")), strict).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "/0/1");
+    assert!(
+        matches!(error.without_location(), WriteError::HtmlRenderingError(HtmlWriteError::InvalidStructure(message))
+        if message.contains("'Nowhere'"))
+    );
+
+    let lenient = HtmlWriterOptions::default().with_strict(false);
+    let output = html(&doc(cross_ref("Nowhere")), lenient).unwrap();
+    assert!(output.starts_with("<p>See Nowhere</p>\n"));
+}
+
+#[test]
+fn shared_headings_are_cross_ref_targets() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![text("See "), cross_ref("Usage")]),
+        Node::shared(heading(2, "Usage")),
+    ]);
+    let mut writer = HtmlWriter::new();
+    doc.to_html(&mut writer).unwrap();
+    assert!(writer.into_string().contains("<a href=\"#usage\">"));
+}
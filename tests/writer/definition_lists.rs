@@ -0,0 +1,163 @@
+use cmark_writer::ast::{DefinitionItem, ListItem, Node, NodePath};
+use cmark_writer::{CommonMarkWriter, HtmlWriter, ToCommonMark, ToHtml, WriterOptions};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn paragraph(s: &str) -> Node {
+    Node::Paragraph(vec![text(s)])
+}
+
+fn glossary() -> Node {
+    Node::DefinitionList(vec![
+        DefinitionItem::new(
+            vec![Node::Strong(vec![text("AST")])],
+            vec![vec![paragraph("Abstract syntax tree.")]],
+        ),
+        DefinitionItem::new(
+            vec![text("Writer")],
+            vec![
+                vec![paragraph("Turns nodes into text.")],
+                vec![
+                    paragraph("Comes in two flavors:"),
+                    Node::UnorderedList(vec![
+                        ListItem::Unordered {
+                            content: vec![paragraph("CommonMark")],
+                        },
+                        ListItem::Unordered {
+                            content: vec![paragraph("HTML")],
+                        },
+                    ]),
+                ],
+            ],
+        ),
+    ])
+}
+
+fn commonmark(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::with_options(WriterOptions {
+        definition_lists: true,
+        ..Default::default()
+    });
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn commonmark_terms_and_definitions() {
+    assert_eq!(
+        commonmark(&glossary()),
+        concat!(
+            "**AST**\n",
+            ": Abstract syntax tree.\n",
+            "\n",
+            "Writer\n",
+            ": Turns nodes into text.\n",
+            ": Comes in two flavors:\n",
+            "    \n",
+            "    - CommonMark\n",
+            "    - HTML\n",
+        )
+    );
+}
+
+#[test]
+fn commonmark_incomplete_items() {
+    let no_definitions =
+        Node::DefinitionList(vec![DefinitionItem::new(vec![text("Term")], vec![])]);
+    let no_term = Node::DefinitionList(vec![DefinitionItem::new(
+        vec![],
+        vec![vec![paragraph("x")]],
+    )]);
+    for node in [&no_definitions, &no_term] {
+        let mut writer = CommonMarkWriter::with_options(WriterOptions {
+            definition_lists: true,
+            ..Default::default()
+        });
+        assert!(node.to_commonmark(&mut writer).is_err());
+    }
+
+    let mut writer = CommonMarkWriter::with_options(WriterOptions {
+        strict: false,
+        definition_lists: true,
+        ..Default::default()
+    });
+    no_term.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), ": x\n");
+}
+
+#[test]
+fn commonmark_without_definition_lists_writes_paragraphs() {
+    let mut writer = CommonMarkWriter::new();
+    glossary().to_commonmark(&mut writer).unwrap();
+    assert_eq!(
+        writer.into_string(),
+        concat!(
+            "**AST**\n",
+            "\n",
+            "Abstract syntax tree.\n",
+            "\n",
+            "**Writer**\n",
+            "\n",
+            "Turns nodes into text.\n",
+            "\n",
+            "Comes in two flavors:\n",
+            "\n",
+            "- CommonMark\n",
+            "- HTML\n",
+        )
+    );
+}
+
+#[test]
+fn commonmark_multiline_term_is_rejected() {
+    let node = Node::DefinitionList(vec![DefinitionItem::new(
+        vec![text("two"), Node::HardBreak, text("lines")],
+        vec![vec![paragraph("x")]],
+    )]);
+    let mut writer = CommonMarkWriter::new();
+    assert!(node.to_commonmark(&mut writer).is_err());
+}
+
+#[test]
+fn html_dl() {
+    let mut writer = HtmlWriter::new();
+    glossary().to_html(&mut writer).unwrap();
+    assert_eq!(
+        writer.into_string(),
+        concat!(
+            "<dl>\n",
+            "<dt><strong>AST</strong></dt>\n",
+            "<dd><p>Abstract syntax tree.</p>\n</dd>\n",
+            "<dt>Writer</dt>\n",
+            "<dd><p>Turns nodes into text.</p>\n</dd>\n",
+            "<dd><p>Comes in two flavors:</p>\n\n<ul>\n<li><p>CommonMark</p>\n</li>\n",
+            "<li><p>HTML</p>\n</li>\n</ul>\n</dd>\n",
+            "</dl>\n",
+        )
+    );
+}
+
+#[test]
+fn paths_address_terms_and_definitions() {
+    let mut doc = Node::Document(vec![glossary()]);
+    let term = NodePath::root().child(0).definition_term(1, 0);
+    let block = NodePath::root().child(0).definition(1, 1, 0);
+    assert_eq!(doc.get(&term), Some(&text("Writer")));
+    assert_eq!(doc.get(&block), Some(&paragraph("Comes in two flavors:")));
+    assert_eq!(term.to_string(), "/0/terms[1]/0");
+    assert_eq!(block.to_string(), "/0/definitions[1][1]/0");
+
+    doc.replace(&term, text("Renderer"));
+    assert!(commonmark(&doc).contains("\nRenderer\n: "));
+
+    let mut visited = Vec::new();
+    doc.walk_with_paths(|path, node| {
+        if let Node::Text(text) = node {
+            visited.push((path.to_string(), text.to_string()));
+        }
+    });
+    assert_eq!(visited[0], ("/0/terms[0]/0/0".into(), "AST".into()));
+    assert_eq!(visited[2], ("/0/terms[1]/0".into(), "Renderer".into()));
+}
@@ -0,0 +1,67 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::ToCommonMark;
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn render(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::new();
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn nested_strong_is_collapsed() {
+    let node = Node::Strong(vec![Node::Strong(vec![text("bold")])]);
+    assert_eq!(render(&node), "**bold**");
+
+    let node = Node::Strong(vec![Node::Strong(vec![text("a")]), text(" b")]);
+    assert_eq!(render(&node), "**a b**");
+}
+
+#[test]
+fn nested_emphasis_is_collapsed() {
+    let node = Node::Emphasis(vec![Node::Emphasis(vec![text("it")])]);
+    assert_eq!(render(&node), "_it_");
+}
+
+#[test]
+fn strong_emphasis_uses_triple_delimiter() {
+    let node = Node::Strong(vec![Node::Emphasis(vec![text("both")])]);
+    assert_eq!(render(&node), "***both***");
+
+    let node = Node::Emphasis(vec![Node::Strong(vec![text("both")])]);
+    assert_eq!(render(&node), "***both***");
+
+    let node = Node::Strong(vec![Node::Emphasis(vec![Node::Strong(vec![text("x")])])]);
+    assert_eq!(render(&node), "***x***");
+}
+
+#[test]
+fn strong_emphasis_follows_strong_char() {
+    let options = WriterOptionsBuilder::new().strong_char('_').build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    Node::Strong(vec![Node::Emphasis(vec![text("both")])])
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "___both___");
+}
+
+#[test]
+fn mixed_content_keeps_separate_delimiters() {
+    let node = Node::Strong(vec![Node::Emphasis(vec![text("a")]), text(" b")]);
+    assert_eq!(render(&node), "**_a_ b**");
+
+    let link = Node::Link {
+        url: "https://example.com".into(),
+        title: None,
+        content: vec![Node::Strong(vec![text("inner")])],
+    };
+    assert_eq!(
+        render(&Node::Strong(vec![link])),
+        "**[**inner**](https://example.com)**"
+    );
+}
@@ -0,0 +1,71 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::options::{DocumentOptions, WriterOptionsBuilder};
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::ToCommonMark;
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn sample() -> Node {
+    Node::Document(vec![
+        Node::Paragraph(vec![
+            Node::Emphasis(vec![text("em")]),
+            text(" and "),
+            Node::Strong(vec![text("strong")]),
+        ]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![Node::Strong(vec![
+                Node::Emphasis(vec![text("a")]),
+                text(" b"),
+            ])])],
+        }]),
+    ])
+}
+
+fn render_with(emphasis: char, strong: char) -> String {
+    let options = WriterOptionsBuilder::new()
+        .emphasis_char(emphasis)
+        .strong_char(strong)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    sample().to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn every_combination_is_applied() {
+    assert_eq!(
+        render_with('_', '*'),
+        "_em_ and **strong**\n\n- **_a_ b**\n"
+    );
+    assert_eq!(
+        render_with('*', '*'),
+        "*em* and **strong**\n\n- ***a* b**\n"
+    );
+    assert_eq!(
+        render_with('_', '_'),
+        "_em_ and __strong__\n\n- ___a_ b__\n"
+    );
+    assert_eq!(
+        render_with('*', '_'),
+        "*em* and __strong__\n\n- __*a* b__\n"
+    );
+}
+
+#[test]
+fn invalid_characters_keep_the_default() {
+    assert_eq!(render_with('~', '+'), render_with('_', '*'));
+}
+
+#[test]
+fn document_overrides_apply_to_nested_content() {
+    let Node::Document(children) = sample() else {
+        unreachable!()
+    };
+    let doc = Node::ConfiguredDocument {
+        options: DocumentOptions::new().emphasis_char('*').strong_char('_'),
+        children,
+    };
+    assert_eq!(doc.to_string(), "*em* and __strong__\n\n- __*a* b__\n");
+}
@@ -0,0 +1,189 @@
+use cmark_writer::ast::{HeadingType, ListItem, Node};
+use cmark_writer::options::{EmptyContainerPolicy, WriterOptionsBuilder};
+use cmark_writer::writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions};
+use cmark_writer::{ToCommonMark, ToHtml, WriteError};
+
+fn render(node: &Node, policy: EmptyContainerPolicy) -> Result<String, WriteError> {
+    let options = WriterOptionsBuilder::new()
+        .empty_container_policy(policy)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+fn render_html(node: &Node, policy: EmptyContainerPolicy) -> Result<String, WriteError> {
+    let options = HtmlWriterOptions::default().with_empty_container_policy(policy);
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+fn document_with(empty: Node) -> Node {
+    Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("Before".into())]),
+        empty,
+        Node::Paragraph(vec![Node::Text("After".into())]),
+    ])
+}
+
+#[test]
+fn auto_policy_errors_in_strict_mode() {
+    let result = render(&Node::UnorderedList(vec![]), EmptyContainerPolicy::Auto);
+    assert!(matches!(
+        result.unwrap_err().without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+
+    let result = render(&Node::heading(1, vec![]), EmptyContainerPolicy::Auto);
+    assert!(matches!(
+        result.unwrap_err().without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+}
+
+#[test]
+fn auto_policy_omits_in_non_strict_mode() {
+    let options = WriterOptionsBuilder::new().strict(false).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    document_with(Node::UnorderedList(vec![]))
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "Before\n\nAfter\n");
+}
+
+#[test]
+fn omit_policy_skips_containers_and_spacing() {
+    let empties = vec![
+        Node::heading(2, vec![]),
+        Node::Paragraph(vec![]),
+        Node::BlockQuote(vec![]),
+        Node::UnorderedList(vec![]),
+        Node::OrderedList {
+            start: 1,
+            items: vec![],
+        },
+        Node::Table {
+            headers: vec![],
+            #[cfg(feature = "gfm")]
+            alignments: vec![],
+            rows: vec![],
+        },
+    ];
+
+    for empty in empties {
+        let output = render(&document_with(empty.clone()), EmptyContainerPolicy::Omit).unwrap();
+        assert_eq!(output, "Before\n\nAfter\n", "for {:?}", empty);
+    }
+}
+
+#[test]
+fn omit_policy_inside_containers() {
+    let quote = Node::BlockQuote(vec![
+        Node::Paragraph(vec![]),
+        Node::Paragraph(vec![Node::Text("Quoted".into())]),
+    ]);
+    assert_eq!(
+        render(&quote, EmptyContainerPolicy::Omit).unwrap(),
+        "> Quoted\n"
+    );
+
+    let list = Node::UnorderedList(vec![ListItem::Unordered {
+        content: vec![
+            Node::Paragraph(vec![]),
+            Node::Paragraph(vec![Node::Text("Item".into())]),
+        ],
+    }]);
+    assert_eq!(
+        render(&list, EmptyContainerPolicy::Omit).unwrap(),
+        "- Item\n"
+    );
+
+    let paragraph = Node::Paragraph(vec![
+        Node::Text("a".into()),
+        Node::Emphasis(vec![]),
+        Node::Text("b".into()),
+    ]);
+    assert_eq!(
+        render(&paragraph, EmptyContainerPolicy::Omit).unwrap(),
+        "ab\n"
+    );
+}
+
+#[test]
+fn placeholder_policy_writes_minimal_syntax() {
+    let policy = EmptyContainerPolicy::Placeholder;
+    assert_eq!(render(&Node::heading(2, vec![]), policy).unwrap(), "##\n");
+    assert_eq!(
+        render(
+            &Node::Heading {
+                level: 1,
+                content: vec![],
+                heading_type: HeadingType::Setext,
+                custom_id: None,
+            },
+            policy
+        )
+        .unwrap(),
+        "#\n"
+    );
+    assert_eq!(render(&Node::BlockQuote(vec![]), policy).unwrap(), ">\n");
+    assert_eq!(
+        render(&Node::Paragraph(vec![]), policy).unwrap(),
+        "<p></p>\n"
+    );
+    assert_eq!(
+        render(&Node::UnorderedList(vec![]), policy).unwrap(),
+        "<ul>\n</ul>\n"
+    );
+    assert_eq!(
+        render(
+            &Node::Paragraph(vec![Node::Text("a".into()), Node::Strong(vec![])]),
+            policy
+        )
+        .unwrap(),
+        "a<strong></strong>\n"
+    );
+}
+
+#[test]
+fn error_policy_applies_in_non_strict_mode() {
+    let options = WriterOptionsBuilder::new()
+        .strict(false)
+        .empty_container_policy(EmptyContainerPolicy::Error)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    let result = Node::BlockQuote(vec![]).to_commonmark(&mut writer);
+    assert!(matches!(
+        result.unwrap_err().without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+}
+
+#[test]
+fn empty_document_is_not_an_empty_container() {
+    assert!(!Node::Document(vec![]).is_empty_container());
+    assert_eq!(
+        render(&Node::Document(vec![]), EmptyContainerPolicy::Error).unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn html_writer_policies() {
+    let list = Node::UnorderedList(vec![]);
+    assert!(render_html(&list, EmptyContainerPolicy::Auto).is_err());
+    assert_eq!(
+        render_html(&document_with(list.clone()), EmptyContainerPolicy::Omit).unwrap(),
+        "<p>Before</p>\n<p>After</p>\n"
+    );
+    assert_eq!(
+        render_html(&list, EmptyContainerPolicy::Placeholder).unwrap(),
+        "<ul>\n</ul>\n"
+    );
+
+    let lenient = HtmlWriterOptions::default().with_strict(false);
+    let mut writer = HtmlWriter::with_options(lenient);
+    Node::heading(1, vec![]).to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "");
+}
@@ -0,0 +1,116 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::filters::{EnsureFinalNewline, MaxBlankLines, TrimTrailingWhitespace};
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{OutputFilter, ToCommonMark};
+use ecow::EcoString;
+
+#[test]
+fn trim_trailing_whitespace_keeps_hard_breaks() {
+    let filter = TrimTrailingWhitespace::default();
+    assert_eq!(
+        filter.apply("a   \nb\t\n  \nc  \n".into()),
+        "a  \nb\n\nc  \n"
+    );
+    assert_eq!(filter.apply("last line   ".into()), "last line");
+
+    let strict = TrimTrailingWhitespace {
+        preserve_hard_breaks: false,
+    };
+    assert_eq!(strict.apply("a   \nb".into()), "a\nb");
+}
+
+#[test]
+fn trim_trailing_whitespace_skips_code_blocks() {
+    let filter = TrimTrailingWhitespace {
+        preserve_hard_breaks: false,
+    };
+    assert_eq!(
+        filter.apply("text   \n```  \ncode   \n\n   \n```   \nafter \n".into()),
+        "text\n```\ncode   \n\n   \n```\nafter\n"
+    );
+    assert_eq!(
+        filter.apply("para   \n\n    code  \n    \n    more\t\nafter  \n".into()),
+        "para\n\n    code  \n    \n    more\t\nafter\n"
+    );
+    // Lazy continuation lines of a paragraph are not code
+    assert_eq!(
+        filter.apply("para\n    continued   \n".into()),
+        "para\n    continued\n"
+    );
+}
+
+#[test]
+fn ensure_final_newline() {
+    assert_eq!(EnsureFinalNewline.apply("text".into()), "text\n");
+    assert_eq!(EnsureFinalNewline.apply("text\n\n\n".into()), "text\n");
+    assert_eq!(EnsureFinalNewline.apply("text\n".into()), "text\n");
+    assert_eq!(EnsureFinalNewline.apply("\n".into()), "");
+}
+
+#[test]
+fn max_blank_lines_skips_fenced_code() {
+    let input = "a\n\n\n\nb\n```\nx\n\n\n\ny\n```\n\n\nc";
+    assert_eq!(
+        MaxBlankLines(1).apply(input.into()),
+        "a\n\nb\n```\nx\n\n\n\ny\n```\n\nc"
+    );
+    assert_eq!(MaxBlankLines(0).apply("a\n\n\nb".into()), "a\nb");
+}
+
+#[test]
+fn post_processors_run_on_final_output_only() {
+    let options = WriterOptionsBuilder::new()
+        .hard_break_spaces(true)
+        .post_processor(TrimTrailingWhitespace::default())
+        .post_processor(MaxBlankLines(1))
+        .post_processor(EnsureFinalNewline)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![
+            Node::Text("line one   ".into()),
+            Node::HardBreak,
+            Node::Text("line two".into()),
+        ]),
+        Node::HtmlBlock("<div>\n\n\n\n</div>".into()),
+        Node::BlockQuote(vec![Node::Paragraph(vec![Node::Text("quoted".into())])]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![Node::Text("item".into())])],
+        }]),
+    ]);
+    doc.to_commonmark(&mut writer).unwrap();
+
+    assert_eq!(
+        writer.into_string(),
+        "line one  \nline two\n\n<div>\n\n</div>\n\n> quoted\n\n- item\n"
+    );
+}
+
+#[derive(Debug, Clone)]
+struct Uppercase;
+
+impl OutputFilter for Uppercase {
+    fn apply(&self, output: EcoString) -> EcoString {
+        output.to_uppercase()
+    }
+
+    fn clone_box(&self) -> Box<dyn OutputFilter> {
+        Box::new(self.clone())
+    }
+}
+
+#[test]
+fn custom_filters_are_cloned_with_options() {
+    let options = WriterOptionsBuilder::new()
+        .post_processor(Uppercase)
+        .build();
+    let cloned = options.clone();
+
+    let mut writer = CommonMarkWriter::with_options(cloned);
+    Node::Text("shout".into())
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "SHOUT");
+}
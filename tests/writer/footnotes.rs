@@ -0,0 +1,228 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriter, HtmlWriterOptions, ToCommonMark, ToHtml, WriterOptions,
+};
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn reference(label: &str) -> Node {
+    Node::FootnoteReference(label.into())
+}
+
+fn definition(label: &str, content: Vec<Node>) -> Node {
+    Node::FootnoteDefinition {
+        label: label.into(),
+        content,
+    }
+}
+
+fn commonmark(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::with_options(WriterOptions {
+        footnotes: true,
+        ..Default::default()
+    });
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn html(node: &Node) -> String {
+    let mut writer = HtmlWriter::new();
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn commonmark_references_and_definitions() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![text("Claim"), reference("source"), text(".")]),
+        definition("source", vec![Node::Paragraph(vec![text("A book.")])]),
+    ]);
+    assert_eq!(commonmark(&doc), "Claim[^source].\n\n[^source]: A book.\n");
+}
+
+#[test]
+fn commonmark_without_footnotes_writes_bracketed_labels() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![text("Claim"), reference("source"), text(".")]),
+        definition(
+            "source",
+            vec![
+                Node::Paragraph(vec![text("A book.")]),
+                Node::Paragraph(vec![text("Second printing.")]),
+            ],
+        ),
+    ]);
+    let mut writer = CommonMarkWriter::new();
+    doc.to_commonmark(&mut writer).unwrap();
+    assert_eq!(
+        writer.into_string(),
+        "Claim\\[source\\].\n\n\\[source\\]: A book.\n\nSecond printing.\n"
+    );
+}
+
+#[test]
+fn commonmark_definition_blocks_are_indented() {
+    let doc = Node::Document(vec![definition(
+        "note",
+        vec![
+            Node::Paragraph(vec![text("First paragraph.")]),
+            Node::UnorderedList(vec![ListItem::Unordered {
+                content: vec![Node::Paragraph(vec![text("item")])],
+            }]),
+        ],
+    )]);
+    assert_eq!(
+        commonmark(&doc),
+        "[^note]: First paragraph.\n    \n    - item\n"
+    );
+}
+
+#[test]
+fn commonmark_empty_definition() {
+    assert_eq!(commonmark(&definition("empty", vec![])), "[^empty]:\n");
+}
+
+#[test]
+fn commonmark_invalid_labels() {
+    for label in ["", "two words", "a]b"] {
+        let mut writer = CommonMarkWriter::with_options(WriterOptions {
+            footnotes: true,
+            ..Default::default()
+        });
+        assert!(reference(label).to_commonmark(&mut writer).is_err());
+    }
+
+    let options = WriterOptions {
+        strict: false,
+        footnotes: true,
+        ..Default::default()
+    };
+    let mut writer = CommonMarkWriter::with_options(options);
+    reference("two words").to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "[^two words]");
+}
+
+#[test]
+fn html_footnote_section_with_backlinks() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![
+            text("One"),
+            reference("b"),
+            text(" two"),
+            reference("a"),
+            text(" three"),
+            reference("B"),
+        ]),
+        definition("a", vec![Node::Paragraph(vec![text("First.")])]),
+        definition("b", vec![Node::Paragraph(vec![text("Second.")])]),
+    ]);
+    assert_eq!(
+        html(&doc),
+        concat!(
+            "<p>One",
+            "<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b\">1</a></sup> two",
+            "<sup class=\"footnote-ref\"><a href=\"#fn-a\" id=\"fnref-a\">2</a></sup> three",
+            "<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b-2\">1</a></sup></p>\n",
+            "<section class=\"footnotes\">\n<ol>\n",
+            "<li id=\"fn-b\">\n<p>Second. ",
+            "<a href=\"#fnref-b\" class=\"footnote-backref\" aria-label=\"Back to reference 1\">↩</a> ",
+            "<a href=\"#fnref-b-2\" class=\"footnote-backref\" aria-label=\"Back to reference 1-2\">↩<sup>2</sup></a>",
+            "</p>\n</li>\n",
+            "<li id=\"fn-a\">\n<p>First. ",
+            "<a href=\"#fnref-a\" class=\"footnote-backref\" aria-label=\"Back to reference 2\">↩</a>",
+            "</p>\n</li>\n",
+            "</ol>\n</section>\n",
+        )
+    );
+}
+
+#[test]
+fn html_footnote_ids_are_unique() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![
+            reference("note 1"),
+            reference("note-1"),
+            reference("?"),
+        ]),
+        definition("note 1", vec![Node::Paragraph(vec![text("Spaced.")])]),
+        definition("note-1", vec![Node::Paragraph(vec![text("Dashed.")])]),
+        definition("?", vec![Node::Paragraph(vec![text("Symbol.")])]),
+    ]);
+    let output = html(&doc);
+    assert!(output.contains("<a href=\"#fn-note-1\" id=\"fnref-note-1\">1</a>"));
+    assert!(output.contains("<a href=\"#fn-note-1-1\" id=\"fnref-note-1-1\">2</a>"));
+    assert!(output.contains("<a href=\"#fn-3\" id=\"fnref-3\">3</a>"));
+    assert!(output.contains("<li id=\"fn-note-1\">\n<p>Spaced."));
+    assert!(output.contains("<li id=\"fn-note-1-1\">\n<p>Dashed."));
+}
+
+#[test]
+fn html_footnotes_are_the_same_with_render_multi() {
+    use cmark_writer::writer::multi::{render_multi, OutputTarget};
+
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![text("Claim"), reference("source")]),
+        definition("source", vec![Node::Paragraph(vec![text("A book.")])]),
+    ]);
+    let result = render_multi(&doc, &[OutputTarget::Html(HtmlWriterOptions::default())]);
+    let output = result.html().unwrap().as_ref().unwrap();
+    assert_eq!(output, &html(&doc));
+    assert!(output.contains("<section class=\"footnotes\">"));
+}
+
+#[test]
+fn html_backlinks_get_their_own_paragraph_after_other_blocks() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![reference("code")]),
+        definition(
+            "code",
+            vec![Node::CodeBlock {
+                language: None,
+                content: "x".into(),
+                block_type: Default::default(),
+            }],
+        ),
+    ]);
+    let output = html(&doc);
+    assert!(output.contains(
+        "</code></pre>\n<p><a href=\"#fnref-code\" class=\"footnote-backref\" aria-label=\"Back to reference 1\">↩</a></p>\n</li>"
+    ));
+}
+
+#[test]
+fn html_unreferenced_definitions_are_dropped() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![text("Text")]),
+        definition("unused", vec![Node::Paragraph(vec![text("Never shown.")])]),
+    ]);
+    assert_eq!(html(&doc), "<p>Text</p>\n");
+}
+
+#[test]
+fn html_undefined_reference() {
+    let doc = Node::Document(vec![Node::Paragraph(vec![reference("missing")])]);
+    let mut writer = HtmlWriter::new();
+    assert!(doc.to_html(&mut writer).is_err());
+
+    let mut writer = HtmlWriter::with_options(HtmlWriterOptions {
+        strict: false,
+        ..Default::default()
+    });
+    doc.to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<p>[^missing]</p>\n");
+}
+
+#[test]
+fn html_definition_outside_a_document() {
+    let node = definition("note", vec![Node::Paragraph(vec![text("Aside.")])]);
+    assert_eq!(
+        html(&node),
+        concat!(
+            "<div class=\"footnote-definition\" id=\"fn-note\">",
+            "<sup class=\"footnote-definition-label\">1</sup>\n",
+            "<p>Aside.</p>\n</div>\n",
+        )
+    );
+}
@@ -0,0 +1,65 @@
+use cmark_writer::ast::Node;
+use cmark_writer::writer::html::{AnchorPosition, AnchorStyle};
+use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml};
+
+fn html(node: &Node, anchor: AnchorStyle) -> String {
+    let options = HtmlWriterOptions::default()
+        .with_heading_anchor(Some(anchor))
+        .with_toc_marker(Some("[[TOC]]"));
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn heading(level: u8, text: &str) -> Node {
+    Node::heading(level, vec![Node::Text(text.into())])
+}
+
+#[test]
+fn anchor_follows_heading_text_by_default() {
+    let doc = Node::Document(vec![heading(2, "Getting Started")]);
+    assert_eq!(
+        html(&doc, AnchorStyle::default()),
+        "<h2 id=\"getting-started\">Getting Started<a class=\"anchor\" href=\"#getting-started\">#</a></h2>\n"
+    );
+}
+
+#[test]
+fn position_symbol_and_class_are_configurable() {
+    let doc = Node::Document(vec![heading(1, "Intro")]);
+    let anchor = AnchorStyle::default()
+        .with_position(AnchorPosition::Before)
+        .with_symbol("<§>")
+        .with_class(None::<&str>);
+    assert_eq!(
+        html(&doc, anchor),
+        "<h1 id=\"intro\"><a href=\"#intro\">&lt;§&gt;</a>Intro</h1>\n"
+    );
+}
+
+#[test]
+fn custom_ids_are_linked_and_slugs_match_the_table_of_contents() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::Text("[[TOC]]".into())]),
+        heading(2, "Usage"),
+        Node::heading_with_id(2, vec![Node::Text("Usage".into())], "usage-v2"),
+        heading(2, "Usage"),
+    ]);
+    let output = html(&doc, AnchorStyle::default());
+    assert!(output.contains("<a href=\"#usage\">Usage</a>"));
+    assert!(output.contains("<a href=\"#usage-v2\">Usage</a>"));
+    assert!(output.contains("<a href=\"#usage-1\">Usage</a>"));
+    assert!(output
+        .contains("<h2 id=\"usage-v2\">Usage<a class=\"anchor\" href=\"#usage-v2\">#</a></h2>"));
+    assert!(
+        output.contains("<h2 id=\"usage-1\">Usage<a class=\"anchor\" href=\"#usage-1\">#</a></h2>")
+    );
+}
+
+#[test]
+fn headings_have_no_anchor_by_default() {
+    let doc = Node::Document(vec![heading(2, "Usage")]);
+    let mut writer = HtmlWriter::new();
+    doc.to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<h2>Usage</h2>\n");
+}
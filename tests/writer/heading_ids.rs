@@ -0,0 +1,71 @@
+use cmark_writer::ast::{HeadingType, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{HtmlWriter, ToCommonMark, ToHtml, WriteError};
+
+fn title() -> Vec<Node> {
+    vec![Node::Text("Getting Started".into())]
+}
+
+fn render(node: &Node, attribute_blocks: bool, strict: bool) -> Result<String, WriteError> {
+    let options = WriterOptionsBuilder::new()
+        .attribute_blocks(attribute_blocks)
+        .strict(strict)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+#[test]
+fn custom_id_is_dropped_without_attribute_blocks() {
+    let heading = Node::heading_with_id(2, title(), "start");
+    assert_eq!(
+        render(&heading, false, true).unwrap(),
+        "## Getting Started\n"
+    );
+}
+
+#[test]
+fn custom_id_is_written_as_attribute_block() {
+    let heading = Node::heading_with_id(2, title(), "start");
+    assert_eq!(
+        render(&heading, true, true).unwrap(),
+        "## Getting Started {#start}\n"
+    );
+
+    let setext = Node::Heading {
+        level: 1,
+        content: title(),
+        heading_type: HeadingType::Setext,
+        custom_id: Some("start".into()),
+    };
+    assert_eq!(
+        render(&setext, true, true).unwrap(),
+        "Getting Started {#start}\n========================\n"
+    );
+}
+
+#[test]
+fn invalid_custom_id_in_attribute_block() {
+    let heading = Node::heading_with_id(2, title(), "two words");
+    assert!(matches!(
+        render(&heading, true, true).unwrap_err().without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+    assert_eq!(
+        render(&heading, true, false).unwrap(),
+        "## Getting Started\n"
+    );
+}
+
+#[test]
+fn html_uses_custom_id_verbatim() {
+    let heading = Node::heading_with_id(2, title(), "Start&Here");
+    let mut writer = HtmlWriter::new();
+    heading.to_html(&mut writer).unwrap();
+    assert_eq!(
+        writer.into_string(),
+        "<h2 id=\"Start&amp;Here\">Getting Started</h2>\n"
+    );
+}
@@ -0,0 +1,85 @@
+use cmark_writer::ast::{HeadingType, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions, ToCommonMark, ToHtml};
+
+fn document() -> Node {
+    Node::Document(
+        (1..=6)
+            .map(|level| Node::heading(level, vec![Node::Text(format!("h{}", level).into())]))
+            .collect(),
+    )
+}
+
+fn commonmark(node: &Node, offset: i8, max: u8) -> String {
+    let options = WriterOptionsBuilder::new()
+        .heading_level_offset(offset)
+        .max_heading_level(max)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn html(node: &Node, offset: i8, max: u8) -> String {
+    let options = HtmlWriterOptions::default()
+        .with_heading_level_offset(offset)
+        .with_max_heading_level(max);
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn defaults_keep_levels() {
+    assert_eq!(
+        commonmark(&document(), 0, 6),
+        "# h1\n\n## h2\n\n### h3\n\n#### h4\n\n##### h5\n\n###### h6\n"
+    );
+}
+
+#[test]
+fn offset_shifts_and_clamps() {
+    assert_eq!(
+        commonmark(&document(), 1, 4),
+        "## h1\n\n### h2\n\n#### h3\n\n#### h4\n\n#### h5\n\n#### h6\n"
+    );
+    assert_eq!(
+        commonmark(&document(), -2, 6),
+        "# h1\n\n# h2\n\n# h3\n\n## h4\n\n### h5\n\n#### h6\n"
+    );
+}
+
+#[test]
+fn html_offset_shifts_and_clamps() {
+    assert_eq!(
+        html(&document(), 2, 4),
+        "<h3>h1</h3>\n<h4>h2</h4>\n<h4>h3</h4>\n<h4>h4</h4>\n<h4>h5</h4>\n<h4>h6</h4>\n"
+    );
+}
+
+#[test]
+fn setext_headings_shifted_past_level_two_become_atx() {
+    let heading = Node::Heading {
+        level: 2,
+        content: vec![Node::Text("Title".into())],
+        heading_type: HeadingType::Setext,
+        custom_id: None,
+    };
+    assert_eq!(commonmark(&heading, -1, 6), "Title\n=====\n");
+    assert_eq!(commonmark(&heading, 1, 6), "### Title\n");
+}
+
+#[test]
+fn html_fallback_inherits_heading_options() {
+    let options = WriterOptionsBuilder::new()
+        .heading_level_offset(1)
+        .max_heading_level(3)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    let element = Node::HtmlElement(
+        cmark_writer::HtmlElement::new("div")
+            .with_children(vec![Node::heading(3, vec![Node::Text("Inner".into())])]),
+    );
+    element.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<div><h3>Inner</h3>\n</div>");
+}
@@ -0,0 +1,49 @@
+use cmark_writer::ast::{HtmlElement, Node};
+use cmark_writer::{CommonMarkWriter, ToCommonMark, WriterOptionsBuilder};
+
+fn span(children: Vec<Node>) -> Node {
+    Node::HtmlElement(HtmlElement {
+        tag: "span".into(),
+        attributes: vec![],
+        children,
+        self_closing: false,
+    })
+}
+
+fn render(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::new();
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn text_children_use_html_escaping() {
+    let node = Node::Paragraph(vec![span(vec![Node::Text("2 * 3_x [y] <z> & `w`".into())])]);
+    assert_eq!(
+        render(&node),
+        "<span>2 * 3_x [y] &lt;z&gt; &amp; `w`</span>\n"
+    );
+}
+
+#[test]
+fn inline_children_are_written_as_html() {
+    let node = Node::Paragraph(vec![span(vec![
+        Node::Emphasis(vec![Node::Text("c*d".into())]),
+        Node::InlineCode("e".into()),
+    ])]);
+    assert_eq!(render(&node), "<span><em>c*d</em><code>e</code></span>\n");
+}
+
+#[test]
+fn markdown_escaping_does_not_reach_children() {
+    let options = WriterOptionsBuilder::new()
+        .escape_special_chars(true)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    let node = Node::Paragraph(vec![
+        Node::Text("a*b ".into()),
+        span(vec![Node::Text("c*d".into())]),
+    ]);
+    node.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "a\\*b <span>c*d</span>\n");
+}
@@ -0,0 +1,197 @@
+use cmark_writer::ast::{Node, NodePath};
+use cmark_writer::writer::html::{AnchorStyle, HtmlOutputMode};
+use cmark_writer::{HtmlWriter, HtmlWriterOptions, ToHtml};
+
+fn paragraph(text: &str) -> Node {
+    Node::Paragraph(vec![Node::Text(text.into())])
+}
+
+fn full_html(doc: &Node) -> String {
+    let mut writer = HtmlWriter::new();
+    doc.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn render_dirty_matches_full_render() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Title".into())]),
+        paragraph("one"),
+        paragraph("two"),
+    ]);
+    let mut writer = HtmlWriter::new();
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+
+    let paths: Vec<_> = blocks.iter().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(paths, ["/0", "/1", "/2"]);
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    assert_eq!(joined, full_html(&doc));
+}
+
+#[test]
+fn render_dirty_reuses_clean_blocks() {
+    let mut writer = HtmlWriter::new();
+    let doc = Node::Document(vec![paragraph("one"), paragraph("two")]);
+    writer.render_dirty(&doc, &[]).unwrap();
+
+    // Only block 1 is marked dirty, so the stale cache for block 0 is kept
+    let edited = Node::Document(vec![paragraph("uno"), paragraph("dos")]);
+    let dirty = NodePath::root().child(1).child(0);
+    let blocks = writer.render_dirty(&edited, &[dirty]).unwrap();
+    assert_eq!(blocks[0].1, "<p>one</p>\n");
+    assert_eq!(blocks[1].1, "<p>dos</p>\n");
+
+    writer.clear_render_cache();
+    let blocks = writer.render_dirty(&edited, &[]).unwrap();
+    assert_eq!(blocks[0].1, "<p>uno</p>\n");
+}
+
+#[test]
+fn render_dirty_root_path_rerenders_everything() {
+    let mut writer = HtmlWriter::new();
+    writer
+        .render_dirty(&Node::Document(vec![paragraph("a")]), &[])
+        .unwrap();
+    let blocks = writer
+        .render_dirty(&Node::Document(vec![paragraph("b")]), &[NodePath::root()])
+        .unwrap();
+    assert_eq!(blocks[0].1, "<p>b</p>\n");
+}
+
+#[test]
+fn render_dirty_non_document_is_single_block() {
+    let mut writer = HtmlWriter::new();
+    let blocks = writer.render_dirty(&paragraph("solo"), &[]).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].0, NodePath::root());
+    assert_eq!(blocks[0].1, "<p>solo</p>\n");
+}
+
+#[test]
+fn render_dirty_writes_fragments_in_every_output_mode() {
+    let options = HtmlWriterOptions {
+        output_mode: HtmlOutputMode::Standalone,
+        lang: Some("en".into()),
+        ..Default::default()
+    };
+    let mut writer = HtmlWriter::with_options(options);
+    let doc = Node::Document(vec![paragraph("one"), paragraph("two")]);
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+    assert_eq!(blocks[0].1, "<p>one</p>\n");
+    assert_eq!(blocks[1].1, "<p>two</p>\n");
+}
+
+#[test]
+fn render_dirty_uses_the_writer_numbering() {
+    let mut writer = HtmlWriter::new();
+    writer.numbering_mut().assign("figure", "chart");
+    let doc = Node::Document(vec![Node::Paragraph(vec![Node::NumberRef(
+        "figure".into(),
+        "chart".into(),
+    )])]);
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+    assert_eq!(blocks[0].1, "<p>Figure 1</p>\n");
+}
+
+#[test]
+fn render_dirty_resolves_cross_refs_across_blocks() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![Node::CrossRef {
+            target: "Getting Started".into(),
+            text: None,
+        }]),
+        Node::heading(2, vec![Node::Text("Getting Started".into())]),
+    ]);
+    let mut writer = HtmlWriter::new();
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    assert_eq!(joined, full_html(&doc));
+    assert!(blocks[1].1.contains("id=\"getting-started\""));
+}
+
+#[test]
+fn render_dirty_issues_heading_ids_across_blocks() {
+    let heading = |text: &str| Node::heading(2, vec![Node::Text(text.into())]);
+    let options = HtmlWriterOptions::default().with_heading_anchor(Some(AnchorStyle::default()));
+    let mut writer = HtmlWriter::with_options(options.clone());
+    let doc = Node::Document(vec![heading("Usage"), paragraph("text"), heading("Usage")]);
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    let mut full = HtmlWriter::with_options(options);
+    doc.to_html(&mut full).unwrap();
+    assert_eq!(joined, full.into_string());
+    assert!(blocks[2].1.starts_with("<h2 id=\"usage-1\">"));
+
+    // Renaming the first heading frees its id for the last one
+    let edited = Node::Document(vec![heading("Setup"), paragraph("text"), heading("Usage")]);
+    let blocks = writer
+        .render_dirty(&edited, &[NodePath::root().child(0)])
+        .unwrap();
+    assert!(blocks[0].1.starts_with("<h2 id=\"setup\">"));
+    assert!(blocks[2].1.starts_with("<h2 id=\"usage\">"));
+}
+
+#[test]
+fn render_dirty_collects_footnotes_in_a_section() {
+    let reference = |label: &str| Node::FootnoteReference(label.into());
+    let definition = |label: &str, text: &str| Node::FootnoteDefinition {
+        label: label.into(),
+        content: vec![paragraph(text)],
+    };
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![reference("a")]),
+        definition("a", "First."),
+        Node::Paragraph(vec![reference("b")]),
+        definition("b", "Second."),
+    ]);
+    let mut writer = HtmlWriter::new();
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+    let paths: Vec<_> = blocks.iter().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(paths, ["/0", "/1", "/2", "/3", "/"]);
+    assert_eq!(blocks[1].1, "");
+    assert!(blocks[4].1.starts_with("<section class=\"footnotes\">"));
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    assert_eq!(joined, full_html(&doc));
+
+    // Referencing `b` first renumbers the clean block referencing `a`
+    let edited = Node::Document(vec![
+        Node::Paragraph(vec![reference("b"), reference("a")]),
+        definition("a", "First."),
+        Node::Paragraph(vec![reference("a")]),
+        definition("b", "Second."),
+    ]);
+    let blocks = writer
+        .render_dirty(&edited, &[NodePath::root().child(0)])
+        .unwrap();
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    assert_eq!(joined, full_html(&edited));
+}
+
+#[test]
+fn render_dirty_writes_the_table_of_contents_of_the_whole_document() {
+    let options = HtmlWriterOptions::default().with_toc_marker(Some("[[TOC]]"));
+    let render = |doc: &Node| {
+        let mut writer = HtmlWriter::with_options(options.clone());
+        doc.to_html(&mut writer).unwrap();
+        writer.into_string().to_string()
+    };
+    let doc = Node::Document(vec![
+        paragraph("[[TOC]]"),
+        Node::heading(2, vec![Node::Text("Intro".into())]),
+    ]);
+    let mut writer = HtmlWriter::with_options(options.clone());
+    let blocks = writer.render_dirty(&doc, &[]).unwrap();
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    assert_eq!(joined, render(&doc));
+    assert!(joined.starts_with("<ul>\n<li><a href=\"#intro\">Intro</a></li>\n</ul>\n"));
+
+    // Editing a heading updates the clean block showing the outline
+    let edited = Node::Document(vec![
+        paragraph("[[TOC]]"),
+        Node::heading(2, vec![Node::Text("Overview".into())]),
+    ]);
+    let dirty = NodePath::root().child(1).child(0);
+    let blocks = writer.render_dirty(&edited, &[dirty]).unwrap();
+    let joined: String = blocks.iter().map(|(_, html)| html.as_str()).collect();
+    assert_eq!(joined, render(&edited));
+}
@@ -0,0 +1,38 @@
+use cmark_writer::ast::Node;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::ToCommonMark;
+
+fn render(code: &str) -> String {
+    let mut writer = CommonMarkWriter::new();
+    Node::InlineCode(code.into())
+        .to_commonmark(&mut writer)
+        .unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn plain_code_uses_single_backticks() {
+    assert_eq!(render("let x = 1;"), "`let x = 1;`");
+}
+
+#[test]
+fn fence_is_longer_than_inner_backtick_runs() {
+    assert_eq!(render("a`b"), "``a`b``");
+    assert_eq!(render("a``b`c"), "```a``b`c```");
+}
+
+#[test]
+fn leading_or_trailing_backtick_is_padded() {
+    assert_eq!(render("`"), "`` ` ``");
+    assert_eq!(render("``foo"), "``` ``foo ```");
+    assert_eq!(render("foo`"), "`` foo` ``");
+}
+
+#[test]
+fn surrounding_spaces_are_preserved() {
+    assert_eq!(render(" a "), "`  a  `");
+    // Only one side has a space, so nothing is stripped
+    assert_eq!(render(" a"), "` a`");
+    // Content of only spaces is never stripped
+    assert_eq!(render("  "), "`  `");
+}
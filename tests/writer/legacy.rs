@@ -4,7 +4,7 @@ use cmark_writer::ast::{HeadingType, HtmlAttribute, HtmlElement, ListItem, Node}
 use cmark_writer::options::WriterOptionsBuilder;
 use cmark_writer::writer::CommonMarkWriter;
 use cmark_writer::ToCommonMark;
-use cmark_writer::{CodeBlockType, WriteError, WriterOptions};
+use cmark_writer::{CodeBlockType, EscapeStrategy, WriteError, WriterOptions};
 
 #[test]
 fn test_write_text() {
@@ -20,6 +20,7 @@ fn test_write_escaped_text() {
         WriterOptionsBuilder::new()
             .strict(true)
             .escape_special_chars(true)
+            .escape_strategy(EscapeStrategy::Always)
             .build(),
     );
     let text = Node::Text("Special chars: * _ [ ] < > ` \\".into());
@@ -91,6 +92,7 @@ fn test_write_heading() {
         level: 2,
         content: vec![Node::Text("Section Title".into())],
         heading_type: HeadingType::Atx, // 添加默认的 ATX 标题类型
+        custom_id: None,
     };
     heading.to_commonmark(&mut writer).unwrap();
     assert_eq!(writer.into_string(), "## Section Title\n");
@@ -239,7 +241,7 @@ fn test_table_with_block_elements_strict_mode() {
     // In strict mode, this should fail because code blocks are block-level elements
     let result = table.to_commonmark(&mut writer);
     assert!(result.is_err());
-    if let Err(WriteError::InvalidStructure(msg)) = result {
+    if let WriteError::InvalidStructure(msg) = result.as_ref().unwrap_err().without_location() {
         assert!(msg.contains("block-level elements"));
     } else {
         panic!("Expected InvalidStructure error, got: {:?}", result);
@@ -678,7 +680,7 @@ fn test_html_element_with_unsafe_tag() {
 
     let result = html_element.to_commonmark(&mut writer);
     assert!(result.is_err());
-    if let Err(WriteError::InvalidHtmlTag(tag)) = result {
+    if let WriteError::InvalidHtmlTag(tag) = result.unwrap_err().without_location() {
         assert_eq!(tag, "script<dangerous>");
     } else {
         panic!("Expected InvalidHtmlTag error");
@@ -704,7 +706,7 @@ fn test_html_element_with_unsafe_attribute() {
     // 应该返回错误
     let result = html_element.to_commonmark(&mut writer);
     assert!(result.is_err());
-    if let Err(WriteError::InvalidHtmlAttribute(attr)) = result {
+    if let WriteError::InvalidHtmlAttribute(attr) = result.unwrap_err().without_location() {
         assert_eq!(attr, "on<click>");
     } else {
         panic!("Expected InvalidHtmlAttribute error");
@@ -727,7 +729,7 @@ fn test_html_attribute_value_escaping() {
     html_element.to_commonmark(&mut writer).unwrap();
     assert_eq!(
         writer.into_string(),
-        "<div data-text=\"引号\"和&lt;标签&gt;以及&amp;符号\">内容</div>"
+        "<div data-text=\"引号&quot;和&lt;标签&gt;以及&amp;符号\">内容</div>"
     );
 }
 
@@ -993,6 +995,7 @@ fn test_nested_leaf_blocks_with_indentation() {
                 level: 3,
                 content: vec![Node::Text("列表中的标题".into())],
                 heading_type: HeadingType::Atx, // 添加默认的 ATX 标题类型
+                custom_id: None,
             }],
         },
         ListItem::Unordered {
@@ -1037,7 +1040,7 @@ fn test_nested_leaf_blocks_with_indentation() {
       println!("Hello");
   }
   ```
-- ---
+- ***
 - <div>
     <p>HTML 内容</p>
   </div>
@@ -1129,3 +1132,44 @@ fn test_nested_mixed_containers() {
 
     assert_eq!(result, expected);
 }
+
+#[test]
+fn test_ordered_list_number_limit() {
+    let list = |start: u32, number: Option<u32>| Node::OrderedList {
+        start,
+        items: vec![
+            ListItem::Ordered {
+                number,
+                content: vec![Node::Paragraph(vec![Node::Text("a".into())])],
+            },
+            ListItem::Ordered {
+                number: None,
+                content: vec![Node::Paragraph(vec![Node::Text("b".into())])],
+            },
+        ],
+    };
+
+    let mut writer = CommonMarkWriter::new();
+    list(999_999_998, None).to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "999999998. a\n999999999. b\n");
+
+    // The second item would need 10 digits
+    let mut writer = CommonMarkWriter::new();
+    let result = list(999_999_999, None).to_commonmark(&mut writer);
+    assert!(matches!(
+        result.unwrap_err().without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+
+    let mut writer = CommonMarkWriter::new();
+    let result = list(1, Some(u32::MAX)).to_commonmark(&mut writer);
+    assert!(matches!(
+        result.unwrap_err().without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+
+    let options = WriterOptionsBuilder::new().strict(false).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    list(1, Some(u32::MAX)).to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "999999999. a\n999999999. b\n");
+}
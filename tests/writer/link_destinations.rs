@@ -0,0 +1,65 @@
+use cmark_writer::ast::Node;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::ToCommonMark;
+
+fn render(node: &Node) -> String {
+    let mut writer = CommonMarkWriter::new();
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn link(url: &str) -> Node {
+    Node::Link {
+        url: url.into(),
+        title: None,
+        content: vec![Node::Text("text".into())],
+    }
+}
+
+#[test]
+fn balanced_parentheses_are_kept() {
+    assert_eq!(
+        render(&link(
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+        )),
+        "[text](https://en.wikipedia.org/wiki/Rust_(programming_language))"
+    );
+    assert_eq!(render(&link("a(b(c)d)e")), "[text](a(b(c)d)e)");
+}
+
+#[test]
+fn unbalanced_parentheses_are_escaped() {
+    assert_eq!(render(&link("a)b")), r"[text](a\)b)");
+    assert_eq!(render(&link("a(b")), r"[text](a\(b)");
+    assert_eq!(render(&link(")(")), r"[text](\)\()");
+}
+
+#[test]
+fn spaces_use_angle_brackets() {
+    assert_eq!(render(&link("my file.md")), "[text](<my file.md>)");
+    assert_eq!(render(&link("a <b>.md")), r"[text](<a \<b\>.md>)");
+    assert_eq!(render(&link("<x>")), r"[text](<\<x\>>)");
+}
+
+#[test]
+fn backslash_before_punctuation_is_escaped() {
+    assert_eq!(render(&link(r"a\*b")), r"[text](a\\*b)");
+    assert_eq!(render(&link(r"C:\dir")), r"[text](C:\dir)");
+}
+
+#[test]
+fn image_and_definition_destinations_are_escaped() {
+    let image = Node::Image {
+        url: "my pic (1).png".into(),
+        title: None,
+        alt: vec![Node::Text("alt".into())],
+    };
+    assert_eq!(render(&image), "![alt](<my pic (1).png>)");
+
+    let definition = Node::LinkReferenceDefinition {
+        label: "ref".into(),
+        destination: "a)b".into(),
+        title: None,
+    };
+    assert_eq!(render(&definition).trim_end(), r"[ref]: a\)b");
+}
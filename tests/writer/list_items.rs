@@ -0,0 +1,120 @@
+use cmark_writer::ast::{ListItem, ListStyle, Node};
+use cmark_writer::{CommonMarkWriter, HtmlWriter, ToCommonMark, ToHtml, WriteError};
+
+fn item() -> ListItem {
+    ListItem::Unordered {
+        content: vec![
+            Node::Paragraph(vec![Node::Text("Fruit".into())]),
+            Node::UnorderedList(vec![ListItem::Unordered {
+                content: vec![Node::Paragraph(vec![Node::Text("apple".into())])],
+            }]),
+        ],
+    }
+}
+
+fn standalone(item: &ListItem, style: ListStyle) -> Result<String, WriteError> {
+    let mut writer = CommonMarkWriter::new();
+    writer.write_list_item_public(item, style)?;
+    Ok(writer.into_string().to_string())
+}
+
+fn in_list(list: &Node) -> String {
+    let mut writer = CommonMarkWriter::new();
+    list.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn unordered_item_matches_list_rendering() {
+    let list = Node::UnorderedList(vec![item()]);
+    let output = standalone(&item(), ListStyle::Unordered).unwrap();
+    assert_eq!(output, "- Fruit\n  \n  - apple");
+    assert_eq!(format!("{}\n", output), in_list(&list));
+}
+
+#[test]
+fn ordered_item_uses_number_and_indent() {
+    let list = Node::OrderedList {
+        start: 10,
+        items: vec![item()],
+    };
+    let output = standalone(&item(), ListStyle::Ordered(10)).unwrap();
+    assert_eq!(output, "10. Fruit\n    \n    - apple");
+    assert_eq!(format!("{}\n", output), in_list(&list));
+}
+
+#[test]
+fn ordered_number_over_limit_is_rejected() {
+    let mut writer = CommonMarkWriter::with_options(
+        cmark_writer::WriterOptionsBuilder::new()
+            .strict(true)
+            .build(),
+    );
+    assert!(matches!(
+        writer.write_list_item_public(&item(), ListStyle::Ordered(1_000_000_000)),
+        Err(WriteError::InvalidStructure(_))
+    ));
+}
+
+#[test]
+fn html_item_matches_list_rendering() {
+    let mut writer = HtmlWriter::new();
+    writer.write_list_item_public(&item()).unwrap();
+    let output = writer.into_string();
+
+    let mut list_writer = HtmlWriter::new();
+    Node::UnorderedList(vec![item()])
+        .to_html(&mut list_writer)
+        .unwrap();
+    assert_eq!(
+        list_writer.into_string(),
+        format!("<ul>\n{}</ul>\n", output)
+    );
+}
+
+fn bullet(content: Vec<Node>) -> ListItem {
+    ListItem::Unordered { content }
+}
+
+#[test]
+fn thematic_break_after_marker_uses_another_character() {
+    // `- ---` would re-parse as a single thematic break
+    let list = Node::UnorderedList(vec![bullet(vec![Node::ThematicBreak])]);
+    assert_eq!(in_list(&list), "- ***\n");
+
+    let nested = Node::UnorderedList(vec![bullet(vec![Node::UnorderedList(vec![bullet(vec![
+        Node::ThematicBreak,
+    ])])])]);
+    assert_eq!(in_list(&nested), "- - ***\n");
+
+    let options = cmark_writer::options::WriterOptionsBuilder::new()
+        .list_marker('*')
+        .thematic_break_char('*')
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    list.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "* ---\n");
+}
+
+#[test]
+fn thematic_break_elsewhere_in_lists_is_unchanged() {
+    let ordered = Node::OrderedList {
+        start: 1,
+        items: vec![bullet(vec![Node::ThematicBreak])],
+    };
+    assert_eq!(in_list(&ordered), "1. ---\n");
+
+    let later = Node::UnorderedList(vec![bullet(vec![
+        Node::Paragraph(vec![Node::Text("rule".into())]),
+        Node::ThematicBreak,
+    ])]);
+    assert_eq!(in_list(&later), "- rule\n  \n  ---\n");
+}
+
+#[test]
+fn dashes_in_item_text_stay_text() {
+    let list = Node::UnorderedList(vec![bullet(vec![Node::Paragraph(vec![Node::Text(
+        "---".into(),
+    )])])]);
+    assert_eq!(in_list(&list), "- \\---\n");
+}
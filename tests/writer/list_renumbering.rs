@@ -0,0 +1,90 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, ListRenumberingPolicy, ToCommonMark};
+
+fn item(number: Option<u32>, text: &str) -> ListItem {
+    ListItem::Ordered {
+        number,
+        content: vec![Node::Paragraph(vec![Node::Text(text.into())])],
+    }
+}
+
+fn list() -> Node {
+    Node::OrderedList {
+        start: 1,
+        items: vec![
+            item(Some(5), "five"),
+            item(None, "six"),
+            item(Some(3), "three"),
+            item(None, "four"),
+        ],
+    }
+}
+
+fn render(node: &Node, policy: ListRenumberingPolicy) -> String {
+    let options = WriterOptionsBuilder::new()
+        .list_renumbering_policy(policy)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn warn_keeps_numbers() {
+    assert_eq!(
+        render(&list(), ListRenumberingPolicy::Warn),
+        "5. five\n6. six\n3. three\n4. four\n"
+    );
+}
+
+#[test]
+fn renumber_continues_the_sequence() {
+    assert_eq!(
+        render(&list(), ListRenumberingPolicy::Renumber),
+        "5. five\n6. six\n7. three\n8. four\n"
+    );
+}
+
+#[test]
+fn split_starts_a_new_list() {
+    assert_eq!(
+        render(&list(), ListRenumberingPolicy::Split),
+        "5. five\n6. six\n\n<!-- -->\n\n3. three\n4. four\n"
+    );
+}
+
+#[test]
+fn increasing_numbers_are_unaffected() {
+    let list = Node::OrderedList {
+        start: 1,
+        items: vec![
+            item(Some(2), "two"),
+            item(Some(7), "seven"),
+            item(None, "eight"),
+        ],
+    };
+    for policy in [
+        ListRenumberingPolicy::Renumber,
+        ListRenumberingPolicy::Split,
+    ] {
+        assert_eq!(render(&list, policy), "2. two\n7. seven\n8. eight\n");
+    }
+}
+
+#[test]
+fn split_inside_a_list_item_keeps_indentation() {
+    let outer = Node::UnorderedList(vec![ListItem::Unordered {
+        content: vec![
+            Node::Paragraph(vec![Node::Text("steps".into())]),
+            Node::OrderedList {
+                start: 1,
+                items: vec![item(Some(2), "two"), item(Some(1), "one")],
+            },
+        ],
+    }]);
+    assert_eq!(
+        render(&outer, ListRenumberingPolicy::Split),
+        "- steps\n  \n  2. two\n  \n  <!-- -->\n  \n  1. one\n"
+    );
+}
@@ -0,0 +1,137 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriter, MathDelimiters, ToCommonMark, ToHtml, WriterOptions,
+};
+
+fn math(display: bool, content: &str) -> Node {
+    Node::Math {
+        display,
+        content: content.into(),
+    }
+}
+
+fn formula() -> Node {
+    Node::Paragraph(vec![
+        Node::Text("Euler: ".into()),
+        math(false, "e^{i\\pi} + 1 = 0"),
+        Node::Text(", and ".into()),
+        math(
+            true,
+            "\\sum_{n=1}^\\infty \\frac{1}{n^2} = \\frac{\\pi^2}{6}",
+        ),
+    ])
+}
+
+fn commonmark_with(node: &Node, options: WriterOptions) -> String {
+    let mut writer = CommonMarkWriter::with_options(WriterOptions {
+        math: true,
+        ..options
+    });
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn html(node: &Node) -> String {
+    let mut writer = HtmlWriter::new();
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn commonmark_dollars_by_default() {
+    assert_eq!(
+        commonmark_with(&formula(), WriterOptions::default()),
+        "Euler: $e^{i\\pi} + 1 = 0$, and $$\\sum_{n=1}^\\infty \\frac{1}{n^2} = \\frac{\\pi^2}{6}$$\n"
+    );
+}
+
+#[test]
+fn commonmark_without_math_writes_inline_code() {
+    let node = Node::Paragraph(vec![
+        math(false, "x^2"),
+        Node::Text(" and ".into()),
+        math(true, "a `b`"),
+    ]);
+    let mut writer = CommonMarkWriter::new();
+    node.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "`x^2` and `` a `b` ``\n");
+}
+
+#[test]
+fn commonmark_brackets() {
+    let options = WriterOptionsBuilder::new()
+        .math_delimiters(MathDelimiters::Brackets)
+        .build();
+    let node = Node::Paragraph(vec![
+        math(false, "x^2"),
+        Node::Text(" ".into()),
+        math(true, "y"),
+    ]);
+    assert_eq!(commonmark_with(&node, options), "\\(x^2\\) \\[y\\]\n");
+}
+
+#[test]
+fn commonmark_rejects_unreadable_formulas() {
+    let brackets = WriterOptionsBuilder::new()
+        .math_delimiters(MathDelimiters::Brackets)
+        .build();
+    let cases = [
+        (math(false, ""), WriterOptions::default()),
+        (math(false, " x"), WriterOptions::default()),
+        (math(false, "a $ b"), WriterOptions::default()),
+        (math(true, "a $$ b"), WriterOptions::default()),
+        (math(false, "a\nb"), WriterOptions::default()),
+        (math(false, "f\\)"), brackets),
+    ];
+    for (node, options) in cases {
+        let mut writer = CommonMarkWriter::with_options(WriterOptions {
+            math: true,
+            ..options
+        });
+        assert!(
+            node.to_commonmark(&mut writer).is_err(),
+            "expected an error for {:?}",
+            node
+        );
+    }
+}
+
+#[test]
+fn commonmark_accepts_escaped_delimiters() {
+    let node = math(false, "\\$5 + \\$3");
+    assert_eq!(
+        commonmark_with(&node, WriterOptions::default()),
+        "$\\$5 + \\$3$"
+    );
+    // Only the delimiters differ for display math
+    assert_eq!(
+        commonmark_with(&math(true, " a $ b "), WriterOptions::default()),
+        "$$ a $ b $$"
+    );
+}
+
+#[test]
+fn commonmark_non_strict_writes_anyway() {
+    let options = WriterOptions {
+        strict: false,
+        ..Default::default()
+    };
+    assert_eq!(commonmark_with(&math(false, " x "), options), "$ x $");
+}
+
+#[test]
+fn html_spans() {
+    assert_eq!(
+        html(&Node::Paragraph(vec![math(false, "a < b"), math(true, "x")])),
+        "<p><span class=\"math math-inline\">a &lt; b</span><span class=\"math math-display\">x</span></p>\n"
+    );
+}
+
+#[test]
+fn plain_text_is_the_source() {
+    assert_eq!(
+        Node::Paragraph(vec![Node::Text("f: ".into()), math(false, "x^2")]).plain_text(),
+        "f: x^2"
+    );
+}
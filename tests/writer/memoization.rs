@@ -0,0 +1,71 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, ToCommonMark};
+use std::sync::Arc;
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn terms() -> Node {
+    Node::BlockQuote(vec![
+        Node::heading(3, vec![text("Terms")]),
+        Node::UnorderedList(vec![
+            ListItem::Unordered {
+                content: vec![Node::Paragraph(vec![text("Net *30* days")])],
+            },
+            ListItem::Unordered {
+                content: vec![Node::Paragraph(vec![
+                    Node::Strong(vec![text("No")]),
+                    text(" refunds"),
+                ])],
+            },
+        ]),
+    ])
+}
+
+fn render(node: &Node, memoize: bool) -> String {
+    let options = WriterOptionsBuilder::new().memoize_blocks(memoize).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn repeated_blocks_render_identically() {
+    let shared = Node::shared(terms());
+    let doc = Node::Document(vec![
+        terms(),
+        Node::Paragraph(vec![text("Invoice 2")]),
+        shared.clone(),
+        Node::Paragraph(vec![text("Invoice 3")]),
+        shared,
+    ]);
+    let output = render(&doc, true);
+    assert_eq!(output, render(&doc, false));
+    assert_eq!(output.matches("> - Net *30* days").count(), 3);
+}
+
+#[test]
+fn same_block_in_different_containers() {
+    let doc = Node::Document(vec![
+        terms(),
+        Node::OrderedList {
+            start: 1,
+            items: vec![ListItem::Ordered {
+                number: None,
+                content: vec![terms()],
+            }],
+        },
+        Node::BlockQuote(vec![terms()]),
+        terms(),
+    ]);
+    assert_eq!(render(&doc, true), render(&doc, false));
+}
+
+#[test]
+fn many_repetitions() {
+    let block = Arc::new(terms());
+    let doc = Node::Document((0..200).map(|_| Node::Shared(Arc::clone(&block))).collect());
+    assert_eq!(render(&doc, true), render(&doc, false));
+}
@@ -1,7 +1,48 @@
 // Unit tests for writer internals and processors
 
+mod assemble;
+mod bidi;
+mod block_markers;
+mod block_sizes;
+mod block_starts;
+mod code_fences;
+mod contextual_escaping;
+mod control_chars;
+mod cross_refs;
+mod definition_lists;
+mod emphasis_nesting;
+mod emphasis_style;
+mod empty_containers;
+mod filters;
+mod footnotes;
+mod heading_anchors;
+mod heading_ids;
+mod heading_levels;
+mod html_element_children;
 #[cfg(feature = "gfm")]
 mod html_fallback;
+mod incremental;
+mod inline_code;
 mod legacy;
+mod link_destinations;
+mod list_items;
+mod list_renumbering;
+mod math;
+mod memoization;
+mod multi;
+mod numbering;
+mod output_mode;
+mod placeholders;
+mod pretty_tables;
 mod processors;
+mod setext_headings;
+mod soft_break_style;
+mod streaming;
+mod table_row_attributes;
+mod table_soft_breaks;
+mod tabs;
+mod toc_marker;
+#[cfg(feature = "tracing")]
+mod tracing_spans;
 mod utils;
+mod write_trace;
@@ -0,0 +1,83 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::writer::multi::{render_multi, OutputFormat, OutputTarget};
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriter, HtmlWriterOptions, ToCommonMark, ToHtml, WriteError,
+    WriterOptions,
+};
+
+fn sample() -> Node {
+    Node::Document(vec![
+        Node::heading(2, vec![Node::Text("Title".into())]),
+        Node::Paragraph(vec![
+            Node::Text("Some ".into()),
+            Node::Strong(vec![Node::Text("bold".into())]),
+        ]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![Node::Paragraph(vec![Node::Text("item".into())])],
+        }]),
+    ])
+}
+
+#[test]
+fn outputs_match_separate_renders() {
+    let doc = sample();
+    let cmark_options = WriterOptions {
+        list_marker: '*',
+        ..Default::default()
+    };
+    let html_options = HtmlWriterOptions::default();
+    let result = render_multi(
+        &doc,
+        &[
+            OutputTarget::Html(html_options.clone()),
+            OutputTarget::CommonMark(cmark_options.clone()),
+        ],
+    );
+
+    let mut cmark = CommonMarkWriter::with_options(cmark_options);
+    doc.to_commonmark(&mut cmark).unwrap();
+    let mut html = HtmlWriter::with_options(html_options);
+    doc.to_html(&mut html).unwrap();
+
+    assert!(result.is_ok());
+    assert_eq!(result.outputs[0].0, OutputFormat::Html);
+    assert_eq!(
+        result.commonmark().unwrap().as_ref().unwrap(),
+        &cmark.into_string()
+    );
+    assert_eq!(
+        result.html().unwrap().as_ref().unwrap(),
+        &html.into_string()
+    );
+}
+
+#[test]
+fn single_node_is_rendered_like_to_commonmark() {
+    let node = Node::Strong(vec![Node::Text("x".into())]);
+    let result = render_multi(&node, &[OutputTarget::CommonMark(WriterOptions::default())]);
+    assert_eq!(result.commonmark().unwrap().as_ref().unwrap(), "**x**");
+    assert!(result.html().is_none());
+}
+
+#[test]
+fn failing_target_does_not_stop_others() {
+    let doc = Node::Document(vec![Node::heading(9, vec![Node::Text("deep".into())])]);
+    let result = render_multi(
+        &doc,
+        &[
+            OutputTarget::CommonMark(WriterOptions::default()),
+            OutputTarget::Html(HtmlWriterOptions::default()),
+        ],
+    );
+    assert!(!result.is_ok());
+    assert!(matches!(
+        result
+            .commonmark()
+            .unwrap()
+            .as_ref()
+            .unwrap_err()
+            .without_location(),
+        WriteError::InvalidHeadingLevel(9)
+    ));
+    assert_eq!(result.html().unwrap().as_ref().unwrap(), "<h6>deep</h6>\n");
+}
@@ -0,0 +1,103 @@
+use cmark_writer::numbering::{Counter, Numbering};
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriter, HtmlWriterOptions, Node, ToCommonMark, ToHtml, WriterOptions,
+};
+
+fn reference(kind: &str, id: &str) -> Node {
+    Node::NumberRef(kind.into(), id.into())
+}
+
+fn paragraph() -> Node {
+    Node::Paragraph(vec![
+        Node::Text("See ".into()),
+        reference("figure", "results"),
+        Node::Text(" and ".into()),
+        reference("table", "totals"),
+        Node::Text(".".into()),
+    ])
+}
+
+fn numbering() -> Numbering {
+    let mut numbering = Numbering::default();
+    numbering.assign("figure", "setup");
+    numbering.assign("figure", "results");
+    numbering.assign("table", "totals");
+    numbering
+}
+
+#[test]
+fn counter_keeps_first_number() {
+    let mut counter = Counter::new("Figure");
+    assert_eq!(counter.assign("a"), 1);
+    assert_eq!(counter.assign("b"), 2);
+    assert_eq!(counter.assign("a"), 1);
+    assert_eq!(counter.get("b"), Some(2));
+    assert_eq!(counter.get("c"), None);
+    assert_eq!(counter.len(), 2);
+}
+
+#[test]
+fn custom_kinds_and_labels() {
+    let mut numbering = Numbering::empty().with_counter("listing", "Listing");
+    assert_eq!(numbering.assign("figure", "a"), None);
+    assert_eq!(numbering.assign("listing", "main"), Some(1));
+
+    let numbering = numbering.with_counter("listing", "Code");
+    assert_eq!(
+        numbering.reference_text("listing", "main").unwrap(),
+        "Code 1"
+    );
+}
+
+#[test]
+fn commonmark_resolves_references() {
+    let mut writer = CommonMarkWriter::new();
+    writer.set_numbering(numbering());
+    paragraph().to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "See Figure 2 and Table 1.\n");
+}
+
+#[test]
+fn html_resolves_references() {
+    let mut writer = HtmlWriter::new();
+    writer.set_numbering(numbering());
+    paragraph().to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<p>See Figure 2 and Table 1.</p>\n");
+}
+
+#[test]
+fn nested_content_shares_numbering() {
+    let doc = Node::BlockQuote(vec![paragraph()]);
+    let mut writer = CommonMarkWriter::new();
+    writer.set_numbering(numbering());
+    doc.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "> See Figure 2 and Table 1.\n");
+}
+
+#[test]
+fn unresolved_reference_fails_in_strict_mode() {
+    let mut writer = CommonMarkWriter::new();
+    assert!(reference("figure", "missing")
+        .to_commonmark(&mut writer)
+        .is_err());
+
+    let mut writer = HtmlWriter::new();
+    assert!(reference("figure", "missing").to_html(&mut writer).is_err());
+}
+
+#[test]
+fn unresolved_reference_is_marked_when_lenient() {
+    let options = WriterOptions {
+        strict: false,
+        ..Default::default()
+    };
+    let mut writer = CommonMarkWriter::with_options(options);
+    reference("figure", "missing")
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "??");
+
+    let mut writer = HtmlWriter::with_options(HtmlWriterOptions::default().with_strict(false));
+    reference("figure", "missing").to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "??");
+}
@@ -0,0 +1,83 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::{EmptyContainerPolicy, FinalNewline};
+use cmark_writer::writer::html::HtmlOutputMode;
+use cmark_writer::writer::TextDirection;
+use cmark_writer::{CommonMarkWriter, HtmlWriterOptions, ToCommonMark, ToHtml, WriterOptions};
+
+fn paragraph(text: &str) -> Node {
+    Node::Paragraph(vec![Node::Text(text.into())])
+}
+
+fn arabic() -> HtmlWriterOptions {
+    HtmlWriterOptions::default()
+        .with_lang(Some("ar"))
+        .with_dir(Some(TextDirection::Rtl))
+}
+
+#[test]
+fn fragment_ignores_lang_and_dir() {
+    let html = paragraph("مرحبا").to_html_with(&arabic()).unwrap();
+    assert_eq!(html, "<p>مرحبا</p>\n");
+}
+
+#[test]
+fn wrapped_fragment_sets_lang_and_dir_on_a_div() {
+    let options = arabic().with_output_mode(HtmlOutputMode::WrappedFragment);
+    let html = paragraph("مرحبا").to_html_with(&options).unwrap();
+    assert_eq!(
+        html,
+        "<div lang=\"ar\" dir=\"rtl\">\n<p>مرحبا</p>\n</div>\n"
+    );
+}
+
+#[test]
+fn wrapped_fragment_without_attributes() {
+    let options = HtmlWriterOptions::default().with_output_mode(HtmlOutputMode::WrappedFragment);
+    let html = Node::Text("hi".into()).to_html_with(&options).unwrap();
+    assert_eq!(html, "<div>\nhi\n</div>\n");
+}
+
+#[test]
+fn standalone_sets_lang_and_dir_on_html() {
+    let options = arabic().with_output_mode(HtmlOutputMode::Standalone);
+    let html = paragraph("مرحبا").to_html_with(&options).unwrap();
+    assert_eq!(
+        html,
+        concat!(
+            "<!DOCTYPE html>\n",
+            "<html lang=\"ar\" dir=\"rtl\">\n",
+            "<head>\n",
+            "<meta charset=\"utf-8\" />\n",
+            "</head>\n",
+            "<body>\n",
+            "<p>مرحبا</p>\n",
+            "</body>\n",
+            "</html>\n",
+        )
+    );
+}
+
+#[test]
+fn lang_is_escaped_and_final_newline_applies_to_the_frame() {
+    let options = HtmlWriterOptions::default()
+        .with_lang(Some("en\"x"))
+        .with_output_mode(HtmlOutputMode::WrappedFragment)
+        .with_final_newline(FinalNewline::Never);
+    let html = paragraph("hi").to_html_with(&options).unwrap();
+    assert_eq!(html, "<div lang=\"en&quot;x\">\n<p>hi</p>\n</div>");
+}
+
+#[test]
+fn html_fallback_in_commonmark_is_never_framed() {
+    let html_options = arabic().with_output_mode(HtmlOutputMode::Standalone);
+    let options = WriterOptions {
+        html_writer_options: Some(html_options),
+        empty_container_policy: EmptyContainerPolicy::Placeholder,
+        ..Default::default()
+    };
+    let mut writer = CommonMarkWriter::with_options(options);
+    Node::Document(vec![Node::UnorderedList(vec![])])
+        .to_commonmark(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_string(), "<ul>\n</ul>\n");
+}
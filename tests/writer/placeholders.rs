@@ -0,0 +1,81 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions, PlaceholderHook};
+use cmark_writer::{ToCommonMark, ToHtml};
+use ecow::EcoString;
+use std::collections::HashMap;
+
+fn skeleton() -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Invoice {{number}}".into())]),
+        Node::Paragraph(vec![
+            Node::Text("Dear {{ name }}, ".into()),
+            Node::Strong(vec![Node::Text("{{amount}}".into())]),
+            Node::Text(" is due. {{unknown}} {{unclosed".into()),
+        ]),
+    ])
+}
+
+fn render(node: &Node, hook: PlaceholderHook) -> EcoString {
+    let options = WriterOptionsBuilder::new()
+        .placeholder_resolver(hook)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string()
+}
+
+#[test]
+fn substitutes_placeholders_from_map() {
+    let values = HashMap::from([("number", "42"), ("name", "Ada"), ("amount", "$10")]);
+    assert_eq!(
+        render(&skeleton(), PlaceholderHook::new(values)),
+        "# Invoice 42\n\nDear Ada, **$10** is due. {{unknown}} {{unclosed\n"
+    );
+}
+
+#[test]
+fn same_skeleton_renders_with_different_values() {
+    let node = Node::Paragraph(vec![Node::Text("Hi {{name}}".into())]);
+    let lookup = |name: &'static str| {
+        PlaceholderHook::new(move |key: &str| (key == "name").then(|| EcoString::from(name)))
+    };
+
+    assert_eq!(render(&node, lookup("Ada")), "Hi Ada\n");
+    assert_eq!(render(&node, lookup("Grace")), "Hi Grace\n");
+}
+
+#[test]
+fn escaping_applies_after_substitution() {
+    let node = Node::Paragraph(vec![Node::Text("{{value}}".into())]);
+    let options = WriterOptionsBuilder::new()
+        .escape_special_chars(true)
+        .placeholder_resolver(PlaceholderHook::new(HashMap::from([(
+            "value",
+            "*not emphasis*",
+        )])))
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "\\*not emphasis\\*\n");
+
+    let options = HtmlWriterOptions::default()
+        .with_placeholder_resolver(PlaceholderHook::new(HashMap::from([("value", "<b>&</b>")])));
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer).unwrap();
+    assert_eq!(writer.into_string(), "<p>&lt;b&gt;&amp;&lt;/b&gt;</p>\n");
+}
+
+#[test]
+fn text_without_placeholders_is_borrowed() {
+    let hook = PlaceholderHook::new(HashMap::from([("a", "b")]));
+    assert!(matches!(
+        hook.substitute("plain {text}"),
+        std::borrow::Cow::Borrowed(_)
+    ));
+    assert!(matches!(
+        hook.substitute("{{missing}}"),
+        std::borrow::Cow::Borrowed(_)
+    ));
+    assert_eq!(hook.substitute("{{a}}{{a}}"), "bb");
+}
@@ -0,0 +1,121 @@
+use cmark_writer::ast::{tables::TableBuilder, Node};
+use cmark_writer::options::{WriterOptions, WriterOptionsBuilder};
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::ToCommonMark;
+
+#[cfg(feature = "gfm")]
+use cmark_writer::ast::TableAlignment;
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn render(node: &Node, options: WriterOptions) -> String {
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().trim_end().to_string()
+}
+
+fn pretty() -> WriterOptionsBuilder {
+    WriterOptionsBuilder::new().pretty_tables(true)
+}
+
+#[test]
+fn pads_cells_to_column_width() {
+    let table = TableBuilder::new()
+        .headers(vec![text("Name"), text("Role")])
+        .row([text("Ada"), text("Engineer")])
+        .row([Node::Strong(vec![text("Grace Hopper")]), text("Admiral")])
+        .build();
+
+    assert_eq!(
+        render(&table, pretty().build()),
+        "| Name             | Role     |\n\
+         | ---------------- | -------- |\n\
+         | Ada              | Engineer |\n\
+         | **Grace Hopper** | Admiral  |"
+    );
+}
+
+#[test]
+fn measures_display_width() {
+    let table = TableBuilder::new()
+        .headers(vec![text("City"), text("Country")])
+        .row([text("東京"), text("日本")])
+        .build();
+
+    assert_eq!(
+        render(&table, pretty().build()),
+        "| City | Country |\n\
+         | ---- | ------- |\n\
+         | 東京 | 日本    |"
+    );
+}
+
+#[test]
+fn short_columns_keep_minimum_dashes() {
+    let table = TableBuilder::new()
+        .headers(vec![text("A"), text("B")])
+        .row([text("1"), text("22")])
+        .build();
+
+    assert_eq!(
+        render(&table, pretty().build()),
+        "| A   | B   |\n| --- | --- |\n| 1   | 22  |"
+    );
+    assert_eq!(
+        render(&table, pretty().table_delimiter_padding(false).build()),
+        "| A   | B   |\n|-----|-----|\n| 1   | 22  |"
+    );
+}
+
+#[test]
+fn completes_short_rows() {
+    let table = Node::Table {
+        headers: vec![text("A"), text("B")],
+        #[cfg(feature = "gfm")]
+        alignments: vec![],
+        rows: vec![vec![text("1")]],
+    };
+
+    assert_eq!(
+        render(&table, pretty().build()),
+        "| A   | B   |\n| --- | --- |\n| 1   |     |"
+    );
+}
+
+#[test]
+fn unpadded_by_default() {
+    let table = TableBuilder::new()
+        .headers(vec![text("Name")])
+        .row([text("Ada Lovelace")])
+        .build();
+
+    assert_eq!(
+        render(&table, WriterOptions::default()),
+        "| Name |\n| --- |\n| Ada Lovelace |"
+    );
+}
+
+#[test]
+#[cfg(feature = "gfm")]
+fn aligns_cells_like_their_column() {
+    let table = TableBuilder::new()
+        .headers(vec![text("Item"), text("Size"), text("Price")])
+        .align([
+            TableAlignment::Left,
+            TableAlignment::Center,
+            TableAlignment::Right,
+        ])
+        .row([text("Coffee"), text("M"), text("3.50")])
+        .row([text("Tea"), text("Large"), text("12")])
+        .build();
+
+    assert_eq!(
+        render(&table, pretty().enable_gfm().build()),
+        "| Item   | Size  | Price |\n\
+         | :----- | :---: | ----: |\n\
+         | Coffee |   M   |  3.50 |\n\
+         | Tea    | Large |    12 |"
+    );
+}
@@ -0,0 +1,77 @@
+use cmark_writer::ast::{HeadingType, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{CommonMarkWriter, ToCommonMark, WriteError, WriterOptions};
+
+fn setext(level: u8, content: Vec<Node>) -> Node {
+    Node::Heading {
+        level,
+        content,
+        heading_type: HeadingType::Setext,
+        custom_id: None,
+    }
+}
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn render(node: &Node, options: WriterOptions) -> Result<String, WriteError> {
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+#[test]
+fn underline_matches_content_width() {
+    let heading = setext(1, vec![text("Release "), Node::Strong(vec![text("notes")])]);
+    assert_eq!(
+        render(&heading, WriterOptions::default()).unwrap(),
+        "Release **notes**\n=================\n"
+    );
+    // Wide characters take two columns
+    assert_eq!(
+        render(&setext(2, vec![text("概要")]), WriterOptions::default()).unwrap(),
+        "概要\n----\n"
+    );
+    assert_eq!(
+        render(&setext(2, vec![text("A")]), WriterOptions::default()).unwrap(),
+        "A\n---\n"
+    );
+}
+
+#[test]
+fn underline_matches_longest_line() {
+    let heading = setext(2, vec![text("First line"), Node::HardBreak, text("Second")]);
+    assert_eq!(
+        render(&heading, WriterOptions::default()).unwrap(),
+        "First line\\\nSecond\n-----------\n"
+    );
+}
+
+#[test]
+fn deep_setext_levels_are_rejected() {
+    let heading = setext(3, vec![text("Deep")]);
+    assert!(matches!(
+        render(&heading, WriterOptions::default())
+            .unwrap_err()
+            .without_location(),
+        WriteError::InvalidStructure(_)
+    ));
+
+    let options = WriterOptionsBuilder::new().strict(false).build();
+    assert_eq!(render(&heading, options).unwrap(), "### Deep\n");
+}
+
+#[test]
+fn option_forces_setext_for_levels_one_and_two() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![text("Title")]),
+        Node::heading(2, vec![text("Section")]),
+        Node::heading(3, vec![text("Subsection")]),
+    ]);
+    let options = WriterOptionsBuilder::new().setext_headings(true).build();
+    assert_eq!(
+        render(&doc, options).unwrap(),
+        "Title\n=====\n\nSection\n-------\n\n### Subsection\n"
+    );
+}
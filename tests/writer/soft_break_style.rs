@@ -0,0 +1,79 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::{
+    CommonMarkWriter, HtmlWriter, HtmlWriterOptions, SoftBreakStyle, ToCommonMark, ToHtml,
+};
+
+fn paragraph() -> Node {
+    Node::Paragraph(vec![
+        Node::Text("first".into()),
+        Node::SoftBreak,
+        Node::Text("second".into()),
+    ])
+}
+
+fn commonmark(node: &Node, style: SoftBreakStyle) -> String {
+    let options = WriterOptionsBuilder::new()
+        .strict(true)
+        .soft_break_style(style)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn html(node: &Node, style: SoftBreakStyle) -> String {
+    let mut writer =
+        HtmlWriter::with_options(HtmlWriterOptions::default().with_soft_break_style(style));
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn commonmark_soft_break_styles() {
+    assert_eq!(
+        commonmark(&paragraph(), SoftBreakStyle::Newline),
+        "first\nsecond\n"
+    );
+    assert_eq!(
+        commonmark(&paragraph(), SoftBreakStyle::Space),
+        "first second\n"
+    );
+    assert_eq!(
+        commonmark(&paragraph(), SoftBreakStyle::HardBreak),
+        "first\\\nsecond\n"
+    );
+}
+
+#[test]
+fn html_soft_break_styles() {
+    assert_eq!(
+        html(&paragraph(), SoftBreakStyle::Newline),
+        "<p>first\nsecond</p>\n"
+    );
+    assert_eq!(
+        html(&paragraph(), SoftBreakStyle::Space),
+        "<p>first second</p>\n"
+    );
+    assert_eq!(
+        html(&paragraph(), SoftBreakStyle::HardBreak),
+        "<p>first<br />\nsecond</p>\n"
+    );
+}
+
+#[test]
+fn space_style_allows_soft_breaks_in_link_text() {
+    let link = Node::Paragraph(vec![Node::Link {
+        url: "https://example.com".into(),
+        title: None,
+        content: vec![
+            Node::Text("two".into()),
+            Node::SoftBreak,
+            Node::Text("lines".into()),
+        ],
+    }]);
+    assert_eq!(
+        commonmark(&link, SoftBreakStyle::Space),
+        "[two lines](https://example.com)\n"
+    );
+}
@@ -0,0 +1,178 @@
+use cmark_writer::ast::Node;
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::ToCommonMark;
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn paragraph(s: &str) -> Node {
+    Node::Paragraph(vec![text(s)])
+}
+
+fn as_document(nodes: &[Node]) -> String {
+    let mut writer = CommonMarkWriter::new();
+    Node::Document(nodes.to_vec())
+        .to_commonmark(&mut writer)
+        .unwrap();
+    writer.into_string().to_string()
+}
+
+fn streamed(nodes: &[Node]) -> String {
+    let mut writer = CommonMarkWriter::new();
+    for node in nodes {
+        writer.append(node).unwrap();
+    }
+    writer.into_string().to_string()
+}
+
+#[test]
+fn write_all_matches_document_output() {
+    let sequences = [
+        vec![paragraph("a"), paragraph("b")],
+        vec![
+            Node::heading(1, vec![text("T")]),
+            Node::ThematicBreak,
+            paragraph("x"),
+        ],
+        vec![paragraph("a"), text("inline"), paragraph("b")],
+        vec![text("one"), text("two")],
+    ];
+    for nodes in &sequences {
+        let mut writer = CommonMarkWriter::new();
+        writer.write_all(nodes).unwrap();
+        assert_eq!(writer.into_string(), as_document(nodes));
+        assert_eq!(streamed(nodes), as_document(nodes));
+    }
+}
+
+#[test]
+fn write_all_skips_omitted_nodes() {
+    let options = WriterOptionsBuilder::new().strict(false).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    writer
+        .write_all(&[paragraph("a"), Node::Paragraph(vec![]), paragraph("b")])
+        .unwrap();
+    assert_eq!(writer.into_string(), "a\n\nb\n");
+}
+
+#[test]
+fn write_all_accepts_iterators() {
+    let nodes = [paragraph("keep"), paragraph("skip"), paragraph("also")];
+    let mut writer = CommonMarkWriter::new();
+    writer
+        .write_all(nodes.iter().filter(|node| **node != paragraph("skip")))
+        .unwrap();
+    assert_eq!(writer.into_string(), "keep\n\nalso\n");
+}
+
+#[test]
+fn write_all_continues_across_calls() {
+    let mut writer = CommonMarkWriter::new();
+    writer.write_all(&[paragraph("a")]).unwrap();
+    writer.write_all(&[paragraph("b"), paragraph("c")]).unwrap();
+    assert_eq!(writer.into_string(), "a\n\nb\n\nc\n");
+}
+
+mod stream_writer {
+    use super::*;
+    use cmark_writer::options::FinalNewline;
+    use cmark_writer::writer::filters::MaxBlankLines;
+    use cmark_writer::{CommonMarkStreamWriter, WriterOptions};
+    use std::io::{self, Write};
+
+    fn lines(first: &str, second: &str) -> Node {
+        Node::Paragraph(vec![text(first), Node::SoftBreak, text(second)])
+    }
+
+    fn blocks() -> Vec<Node> {
+        vec![
+            Node::heading(1, vec![text("Title")]),
+            lines("first", "paragraph"),
+            Node::BlockQuote(vec![paragraph("quoted"), paragraph("twice")]),
+            text("inline"),
+            text("more"),
+            Node::ThematicBreak,
+            paragraph("last"),
+        ]
+    }
+
+    fn buffered(nodes: &[Node], options: WriterOptions) -> String {
+        let mut writer = CommonMarkWriter::with_options(options);
+        writer.write_all(nodes).unwrap();
+        writer.into_string().to_string()
+    }
+
+    fn streamed(nodes: &[Node], options: WriterOptions) -> String {
+        let mut writer = CommonMarkStreamWriter::with_options(Vec::new(), options);
+        writer.write_all(nodes).unwrap();
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn output_matches_buffered_writer() {
+        let nodes = blocks();
+        for final_newline in [
+            FinalNewline::Preserve,
+            FinalNewline::Always,
+            FinalNewline::Never,
+        ] {
+            let options = WriterOptionsBuilder::new()
+                .final_newline(final_newline)
+                .build();
+            assert_eq!(streamed(&nodes, options.clone()), buffered(&nodes, options));
+        }
+    }
+
+    #[test]
+    fn complete_lines_are_flushed_after_each_node() {
+        let mut writer = CommonMarkStreamWriter::new(Vec::new());
+        writer.append(&paragraph("one")).unwrap();
+        assert!(writer.get_ref().is_empty());
+        assert_eq!(writer.buffered(), "one\n");
+
+        writer.append(&lines("two", "lines")).unwrap();
+        assert_eq!(writer.get_ref(), b"one\n\ntwo\n");
+        assert_eq!(writer.buffered(), "lines\n");
+
+        assert_eq!(writer.finish().unwrap(), b"one\n\ntwo\nlines\n");
+    }
+
+    #[test]
+    fn post_processors_see_the_whole_output() {
+        let nodes = [
+            paragraph("a"),
+            Node::HtmlBlock("<br>\n\n\n".into()),
+            paragraph("b"),
+        ];
+        let options = WriterOptionsBuilder::new()
+            .post_processor(MaxBlankLines(1))
+            .build();
+        let mut writer = CommonMarkStreamWriter::with_options(Vec::new(), options.clone());
+        writer.write_all(&nodes).unwrap();
+        assert!(writer.get_ref().is_empty());
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(output, buffered(&nodes, options));
+    }
+
+    struct FailingSink;
+
+    impl Write for FailingSink {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sink_errors_are_reported() {
+        let mut writer = CommonMarkStreamWriter::new(FailingSink);
+        writer.append(&paragraph("a")).unwrap();
+        let err = writer.append(&paragraph("b")).unwrap_err();
+        assert!(err.to_string().contains("disk full"));
+    }
+}
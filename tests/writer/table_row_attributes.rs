@@ -0,0 +1,81 @@
+use cmark_writer::ast::tables::simple_table;
+use cmark_writer::ast::Node;
+use cmark_writer::writer::html::{RowAttributes, TableRowHook};
+use cmark_writer::{HtmlWriteError, HtmlWriterOptions, ToHtml};
+
+fn text(value: &str) -> Node {
+    Node::Text(value.into())
+}
+
+fn report() -> Node {
+    simple_table(
+        vec![text("Job"), text("Status")],
+        vec![
+            vec![text("build"), text("ok")],
+            vec![text("test"), text("failed")],
+            vec![text("deploy"), text("ok")],
+        ],
+    )
+}
+
+#[test]
+fn rows_are_striped_and_marked_by_status() {
+    let hook = TableRowHook::new(|index, cells: &[Node]| {
+        let mut attributes = RowAttributes::new().with_data("status", cells[1].plain_text());
+        if index % 2 == 1 {
+            attributes = attributes.with_class("odd");
+        }
+        attributes
+    });
+    let options = HtmlWriterOptions::default().with_table_row_attributes(hook);
+    let html = report().to_html_with(&options).unwrap();
+    assert_eq!(
+        html,
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n<th>Job</th>\n<th>Status</th>\n</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr data-status=\"ok\">\n<td>build</td>\n<td>ok</td>\n</tr>\n",
+            "<tr class=\"odd\" data-status=\"failed\">\n<td>test</td>\n<td>failed</td>\n</tr>\n",
+            "<tr data-status=\"ok\">\n<td>deploy</td>\n<td>ok</td>\n</tr>\n",
+            "</tbody>\n",
+            "</table>\n",
+        )
+    );
+}
+
+#[test]
+fn values_are_escaped() {
+    let hook = TableRowHook::new(|_, _: &[Node]| RowAttributes::new().with_class("a\"b<"));
+    let options = HtmlWriterOptions::default().with_table_row_attributes(hook);
+    let html = report().to_html_with(&options).unwrap();
+    assert!(html.contains("<tr class=\"a&quot;b&lt;\">"));
+}
+
+#[test]
+fn invalid_data_names_are_rejected_in_strict_mode() {
+    let hook = TableRowHook::new(|_, _: &[Node]| {
+        RowAttributes::new()
+            .with_data("bad name", "x")
+            .with_data("row", "1")
+    });
+    let options = HtmlWriterOptions::default().with_table_row_attributes(hook);
+    let err = report().to_html_with(&options).unwrap_err();
+    assert!(matches!(err, HtmlWriteError::InvalidHtmlAttribute(name) if name == "data-bad name"));
+
+    let lenient = options.with_strict(false);
+    let html = report().to_html_with(&lenient).unwrap();
+    assert!(html.contains("<tr data-row=\"1\">"));
+}
+
+#[test]
+fn hooks_compare_by_identity() {
+    let hook = TableRowHook::new(|_, _: &[Node]| RowAttributes::new());
+    assert_eq!(hook, hook.clone());
+    assert_ne!(
+        hook,
+        TableRowHook::new(|_, _: &[Node]| RowAttributes::new())
+    );
+}
@@ -0,0 +1,65 @@
+use cmark_writer::ast::{tables::simple_table, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{ToCommonMark, WriteError};
+
+fn wrapped(first: &str, second: &str) -> Node {
+    Node::Strong(vec![
+        Node::Text(first.into()),
+        Node::SoftBreak,
+        Node::Text(second.into()),
+    ])
+}
+
+fn render(node: &Node, soft_breaks_as_spaces: bool) -> Result<String, WriteError> {
+    let options = WriterOptionsBuilder::new()
+        .strict(true)
+        .table_soft_breaks_as_spaces(soft_breaks_as_spaces)
+        .build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer)?;
+    Ok(writer.into_string().to_string())
+}
+
+#[test]
+fn soft_breaks_in_cells_become_spaces() {
+    let table = simple_table(
+        vec![wrapped("Unit", "price"), Node::Text("Qty".into())],
+        vec![vec![Node::Text("1.50".into()), wrapped("two", "dozen")]],
+    );
+    assert_eq!(
+        render(&table, true).unwrap(),
+        "| **Unit price** | Qty |\n| --- | --- |\n| 1.50 | **two dozen** |\n\n"
+    );
+}
+
+#[test]
+fn soft_breaks_outside_tables_are_unchanged() {
+    let doc = Node::Document(vec![Node::Paragraph(vec![wrapped("a", "b")])]);
+    assert_eq!(render(&doc, true).unwrap(), "**a\nb**\n");
+}
+
+#[test]
+fn hard_breaks_in_cells_are_still_rejected() {
+    let table = simple_table(
+        vec![Node::Emphasis(vec![
+            Node::Text("a".into()),
+            Node::HardBreak,
+            Node::Text("b".into()),
+        ])],
+        vec![],
+    );
+    assert!(matches!(
+        render(&table, true).unwrap_err().without_location(),
+        WriteError::NewlineInInlineElement(_)
+    ));
+}
+
+#[test]
+fn soft_breaks_are_rejected_when_disabled() {
+    let table = simple_table(vec![wrapped("Unit", "price")], vec![]);
+    assert!(matches!(
+        render(&table, false).unwrap_err().without_location(),
+        WriteError::NewlineInInlineElement(_)
+    ));
+}
@@ -0,0 +1,151 @@
+use cmark_writer::ast::{CodeBlockType, ListItem, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::CommonMarkWriter;
+use cmark_writer::{TabPolicy, ToCommonMark};
+
+fn render(node: &Node, policy: TabPolicy) -> String {
+    let options = WriterOptionsBuilder::new().tab_policy(policy).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn text(s: &str) -> Node {
+    Node::Text(s.into())
+}
+
+fn item(content: Vec<Node>) -> ListItem {
+    ListItem::Unordered { content }
+}
+
+fn code_block(content: &str) -> Node {
+    Node::CodeBlock {
+        language: None,
+        content: content.into(),
+        block_type: CodeBlockType::Fenced,
+    }
+}
+
+#[test]
+fn preserve_writes_tabs_unchanged() {
+    let doc = Node::Document(vec![
+        Node::Paragraph(vec![text("a\tb")]),
+        code_block("x\ty\n"),
+    ]);
+
+    assert_eq!(
+        render(&doc, TabPolicy::Preserve),
+        "a\tb\n\n```\nx\ty\n```\n"
+    );
+}
+
+#[test]
+fn expands_to_next_tab_stop() {
+    let paragraph = Node::Paragraph(vec![text("a\tb\tc"), Node::Emphasis(vec![text("d\te")])]);
+
+    assert_eq!(
+        render(&paragraph, TabPolicy::ExpandToSpaces(4)),
+        "a   b   c_d e_\n"
+    );
+    assert_eq!(
+        render(&paragraph, TabPolicy::ExpandToSpaces(2)),
+        "a b c_d e_\n"
+    );
+}
+
+#[test]
+fn wide_characters_count_double() {
+    let paragraph = Node::Paragraph(vec![text("日\tx")]);
+
+    assert_eq!(render(&paragraph, TabPolicy::ExpandToSpaces(4)), "日  x\n");
+}
+
+#[test]
+fn leading_tabs_cannot_indent_prose() {
+    let paragraph = Node::Paragraph(vec![text("\tnot code"), Node::SoftBreak, text(" \tnext")]);
+
+    assert_eq!(
+        render(&paragraph, TabPolicy::ExpandToSpaces(4)),
+        "&#9;not code\n &#9;next\n"
+    );
+}
+
+#[test]
+fn list_markers_count_towards_prose_columns() {
+    let list = Node::UnorderedList(vec![item(vec![Node::Paragraph(vec![text("ab\tc")])])]);
+    let ordered = Node::OrderedList {
+        start: 10,
+        items: vec![ListItem::Ordered {
+            number: None,
+            content: vec![Node::Paragraph(vec![text("ab\tc")])],
+        }],
+    };
+
+    assert_eq!(render(&list, TabPolicy::ExpandToSpaces(4)), "- ab    c\n");
+    assert_eq!(
+        render(&ordered, TabPolicy::ExpandToSpaces(4)),
+        "10. ab  c\n"
+    );
+}
+
+#[test]
+fn leading_tabs_in_list_items_cannot_indent_prose() {
+    let list = Node::UnorderedList(vec![item(vec![Node::Paragraph(vec![
+        text("\tfirst"),
+        Node::SoftBreak,
+        text("\tsecond"),
+    ])])]);
+
+    assert_eq!(
+        render(&list, TabPolicy::ExpandToSpaces(8)),
+        "- &#9;first\n  &#9;second\n"
+    );
+}
+
+#[cfg(feature = "parser")]
+#[test]
+fn leading_tabs_in_list_items_parse_back_as_tabs() {
+    use cmark_writer::parser::parse;
+
+    for width in [2, 4, 8] {
+        let list = Node::UnorderedList(vec![item(vec![Node::Paragraph(vec![text("\tnot code")])])]);
+        let written = render(&list, TabPolicy::ExpandToSpaces(width));
+        assert_eq!(
+            parse(&written),
+            Node::Document(vec![list]),
+            "tab width {width} wrote {written:?}"
+        );
+    }
+}
+
+#[test]
+fn code_columns_ignore_list_indentation() {
+    let list = Node::UnorderedList(vec![item(vec![
+        Node::Paragraph(vec![text("Example:")]),
+        code_block("a\tb\n\tc\n"),
+        Node::Paragraph(vec![Node::InlineCode("k\tv".into())]),
+    ])]);
+
+    assert_eq!(
+        render(&list, TabPolicy::ExpandToSpaces(4)),
+        "- Example:\n  \n  ```\n  a   b\n      c\n  ```\n  \n  `k   v`\n"
+    );
+}
+
+#[test]
+fn indented_code_blocks_expand_before_indenting() {
+    let code = Node::CodeBlock {
+        language: None,
+        content: "\tx".into(),
+        block_type: CodeBlockType::Indented,
+    };
+
+    assert_eq!(render(&code, TabPolicy::ExpandToSpaces(2)), "      x\n");
+}
+
+#[test]
+fn apply_counts_from_the_given_column() {
+    assert_eq!(TabPolicy::ExpandToSpaces(4).apply("a\tb", 2), "a b");
+    assert_eq!(TabPolicy::ExpandToSpaces(0).apply("\t", 0), " ");
+    assert_eq!(TabPolicy::Preserve.apply("\t", 0), "\t");
+}
@@ -0,0 +1,130 @@
+use cmark_writer::ast::{ListItem, Node};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::multi::{render_multi, OutputTarget};
+use cmark_writer::{CommonMarkWriter, HtmlWriter, HtmlWriterOptions, ToCommonMark, ToHtml};
+
+fn marker() -> Node {
+    Node::Paragraph(vec![Node::Text("[[TOC]]".into())])
+}
+
+fn document() -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Guide".into())]),
+        marker(),
+        Node::heading(2, vec![Node::Text("Install".into())]),
+        Node::Paragraph(vec![Node::Text("Run it.".into())]),
+    ])
+}
+
+fn commonmark(node: &Node) -> String {
+    let options = WriterOptionsBuilder::new().toc_marker("[[TOC]]").build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+fn html(node: &Node) -> String {
+    let options = HtmlWriterOptions::default().with_toc_marker(Some("[[TOC]]"));
+    let mut writer = HtmlWriter::with_options(options);
+    node.to_html(&mut writer).unwrap();
+    writer.into_string().to_string()
+}
+
+#[test]
+fn marker_is_replaced_by_the_outline() {
+    assert_eq!(
+        commonmark(&document()),
+        "# Guide\n\n- [Guide](#guide)\n  - [Install](#install)\n\n## Install\n\nRun it.\n"
+    );
+    assert_eq!(
+        html(&document()),
+        "<h1 id=\"guide\">Guide</h1>\n\
+         <ul>\n<li><a href=\"#guide\">Guide</a><ul>\n\
+         <li><a href=\"#install\">Install</a></li>\n</ul>\n</li>\n</ul>\n\
+         <h2 id=\"install\">Install</h2>\n<p>Run it.</p>\n"
+    );
+}
+
+#[test]
+fn headings_get_ids_only_where_the_outline_is_shown() {
+    let doc = Node::Document(vec![
+        Node::heading(1, vec![Node::Text("Guide".into())]),
+        Node::Paragraph(vec![Node::Text("Run it.".into())]),
+    ]);
+    assert_eq!(html(&doc), "<h1>Guide</h1>\n<p>Run it.</p>\n");
+}
+
+#[test]
+fn marker_is_plain_text_without_the_option() {
+    assert_eq!(
+        document().to_string(),
+        "# Guide\n\n[[TOC]]\n\n## Install\n\nRun it.\n"
+    );
+}
+
+#[test]
+fn marker_in_nested_containers_uses_the_whole_document() {
+    let doc = Node::Document(vec![
+        Node::BlockQuote(vec![marker()]),
+        Node::heading(1, vec![Node::Text("Only".into())]),
+    ]);
+    assert_eq!(commonmark(&doc), "> - [Only](#only)\n\n# Only\n");
+}
+
+#[test]
+fn marker_without_headings_is_dropped() {
+    let doc = Node::Document(vec![
+        marker(),
+        Node::Paragraph(vec![Node::Text("text".into())]),
+    ]);
+    assert_eq!(commonmark(&doc), "text\n");
+    assert_eq!(html(&doc), "<p>text</p>\n");
+}
+
+#[test]
+fn render_multi_replaces_markers_too() {
+    let result = render_multi(
+        &document(),
+        &[
+            OutputTarget::CommonMark(WriterOptionsBuilder::new().toc_marker("[[TOC]]").build()),
+            OutputTarget::Html(HtmlWriterOptions::default().with_toc_marker(Some("[[TOC]]"))),
+        ],
+    );
+    assert_eq!(
+        result.commonmark().unwrap().as_ref().unwrap(),
+        &commonmark(&document())
+    );
+    assert_eq!(result.html().unwrap().as_ref().unwrap(), &html(&document()));
+}
+
+#[test]
+fn nested_lists_after_inline_item_content_stay_tight() {
+    let list = Node::UnorderedList(vec![ListItem::Unordered {
+        content: vec![
+            Node::Text("parent".into()),
+            Node::OrderedList {
+                start: 1,
+                items: vec![ListItem::Ordered {
+                    number: None,
+                    content: vec![Node::Text("child".into())],
+                }],
+            },
+        ],
+    }]);
+    assert_eq!(list.to_string(), "- parent\n  1. child\n");
+
+    // An ordered list starting elsewhere can't interrupt a paragraph
+    let list = Node::UnorderedList(vec![ListItem::Unordered {
+        content: vec![
+            Node::Text("parent".into()),
+            Node::OrderedList {
+                start: 3,
+                items: vec![ListItem::Ordered {
+                    number: None,
+                    content: vec![Node::Text("child".into())],
+                }],
+            },
+        ],
+    }]);
+    assert_eq!(list.to_string(), "- parent\n  \n  3. child\n");
+}
@@ -0,0 +1,111 @@
+use cmark_writer::ast::Node;
+use cmark_writer::{CommonMarkWriter, HtmlWriter, ToCommonMark, ToHtml};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records `(kind, depth)` of every `render_node` span
+#[derive(Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<(String, u64)>>>,
+    next_id: AtomicU64,
+}
+
+#[derive(Default)]
+struct Fields {
+    kind: String,
+    depth: u64,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "kind" {
+            self.kind = value.to_string();
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "depth" {
+            self.depth = value;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        if span.metadata().name() == "render_node" {
+            let mut fields = Fields::default();
+            span.record(&mut fields);
+            self.spans.lock().unwrap().push((fields.kind, fields.depth));
+        }
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    fn event(&self, _event: &Event<'_>) {}
+    fn enter(&self, _span: &Id) {}
+    fn exit(&self, _span: &Id) {}
+}
+
+fn record_spans(render: impl FnOnce()) -> Vec<(String, u64)> {
+    let recorder = Recorder::default();
+    let spans = recorder.spans.clone();
+    tracing::subscriber::with_default(recorder, render);
+    let spans = spans.lock().unwrap().clone();
+    spans
+}
+
+fn span(kind: &str, depth: u64) -> (String, u64) {
+    (kind.to_string(), depth)
+}
+
+fn doc() -> Node {
+    Node::Document(vec![Node::Paragraph(vec![
+        Node::Text("a ".into()),
+        Node::Strong(vec![Node::Text("b".into())]),
+    ])])
+}
+
+#[test]
+fn commonmark_writer_spans_each_node() {
+    let spans = record_spans(|| {
+        doc().to_commonmark(&mut CommonMarkWriter::new()).unwrap();
+    });
+    assert_eq!(
+        spans,
+        [
+            span("Document", 0),
+            span("Paragraph", 1),
+            span("Text", 2),
+            span("Strong", 2),
+            span("Text", 3),
+        ]
+    );
+}
+
+#[test]
+fn html_writer_spans_each_node() {
+    let spans = record_spans(|| {
+        doc().to_html(&mut HtmlWriter::new()).unwrap();
+    });
+    assert_eq!(
+        spans,
+        [
+            span("Document", 0),
+            span("Paragraph", 1),
+            span("Text", 2),
+            span("Strong", 2),
+            span("Text", 3),
+        ]
+    );
+}
@@ -0,0 +1,156 @@
+use cmark_writer::ast::{ListItem, Node, NodePath};
+use cmark_writer::options::WriterOptionsBuilder;
+use cmark_writer::writer::cmark::{WriteEdit, WriteTrace};
+use cmark_writer::{CommonMarkWriter, ToCommonMark};
+
+fn text(value: &str) -> Node {
+    Node::Text(value.into())
+}
+
+fn traced(node: &Node) -> (String, WriteTrace) {
+    let options = WriterOptionsBuilder::new().trace_writes(true).build();
+    let mut writer = CommonMarkWriter::with_options(options);
+    node.to_commonmark(&mut writer).unwrap();
+    let trace = writer.write_trace().unwrap().clone();
+    (writer.into_string().to_string(), trace)
+}
+
+fn document() -> Node {
+    Node::Document(vec![
+        Node::heading(1, vec![text("Title")]),
+        Node::UnorderedList(vec![ListItem::Unordered {
+            content: vec![
+                Node::Paragraph(vec![text("item")]),
+                Node::BlockQuote(vec![Node::Paragraph(vec![text("quoted")])]),
+            ],
+        }]),
+        Node::HtmlBlock("<hr>\n".into()),
+    ])
+}
+
+/// Apply the events of the outermost writer to an empty buffer
+fn replay(trace: &WriteTrace) -> String {
+    let mut buffer = String::new();
+    for event in trace
+        .events()
+        .iter()
+        .filter(|event| event.writer_depth == 0)
+    {
+        match &event.edit {
+            WriteEdit::Insert(text) => {
+                assert_eq!(event.offset, buffer.len());
+                buffer.push_str(text);
+            }
+            WriteEdit::Remove(text) => {
+                assert!(buffer.ends_with(text.as_str()));
+                buffer.truncate(buffer.len() - text.len());
+                assert_eq!(event.offset, buffer.len());
+            }
+        }
+    }
+    buffer
+}
+
+#[test]
+fn tracing_is_off_by_default() {
+    let writer = CommonMarkWriter::new();
+    assert!(writer.write_trace().is_none());
+}
+
+#[test]
+fn replaying_the_trace_rebuilds_the_output() {
+    let (output, trace) = traced(&document());
+    assert_eq!(replay(&trace), output);
+}
+
+#[test]
+fn child_writer_events_are_nested_and_attributed() {
+    let (_, trace) = traced(&document());
+    let quoted = trace
+        .events()
+        .iter()
+        .find(|event| event.edit == WriteEdit::Insert("quoted".into()))
+        .unwrap();
+    // The list item writer, then the block quote writer inside it
+    assert_eq!(quoted.writer_depth, 2);
+    assert_eq!(
+        quoted.nodes,
+        [
+            "Document",
+            "UnorderedList",
+            "BlockQuote",
+            "Paragraph",
+            "Text"
+        ]
+    );
+
+    let item = trace
+        .events()
+        .iter()
+        .find(|event| {
+            event.writer_depth == 0
+                && matches!(&event.edit, WriteEdit::Insert(text) if text.contains("> quoted"))
+        })
+        .unwrap();
+    assert!(item.location.file().ends_with("block.rs"));
+    assert_eq!(item.nodes, ["Document", "UnorderedList"]);
+}
+
+#[test]
+fn events_record_the_path_of_the_node_being_written() {
+    let (_, trace) = traced(&document());
+    let path_of = |inserted: &str| {
+        trace
+            .events()
+            .iter()
+            .find(|event| event.edit == WriteEdit::Insert(inserted.into()))
+            .unwrap()
+            .path
+            .clone()
+    };
+    assert_eq!(path_of("Title"), Some(NodePath::root().child(0).child(0)));
+    assert_eq!(
+        path_of("quoted"),
+        Some(NodePath::root().child(1).list_item(0, 1).child(0).child(0))
+    );
+    assert_eq!(path_of("<hr>\n"), Some(NodePath::root().child(2)));
+}
+
+#[test]
+fn shared_nodes_are_traced_at_each_place() {
+    let shared = Node::shared(Node::Paragraph(vec![text("again")]));
+    let doc = Node::Document(vec![
+        Node::BlockQuote(vec![shared.clone()]),
+        Node::BlockQuote(vec![shared]),
+    ]);
+    let (_, trace) = traced(&doc);
+    let paths: Vec<_> = trace
+        .events()
+        .iter()
+        .filter(|event| event.edit == WriteEdit::Insert("again".into()))
+        .map(|event| event.path.clone().unwrap().to_string())
+        .collect();
+    assert_eq!(paths, ["/0/0/0", "/1/0/0"]);
+}
+
+#[test]
+fn removals_are_recorded() {
+    let (_, trace) = traced(&document());
+    assert!(trace
+        .events()
+        .iter()
+        .any(|event| event.edit == WriteEdit::Remove("\n".into())));
+}
+
+#[test]
+fn display_lists_one_event_per_line() {
+    let (_, trace) = traced(&Node::Paragraph(vec![text("hi")]));
+    let dump = trace.to_string();
+    assert_eq!(dump.lines().count(), trace.events().len());
+    let first = dump.lines().next().unwrap();
+    assert!(
+        first.starts_with("    0 +\"hi\" Paragraph > Text at /0 ("),
+        "{}",
+        first
+    );
+}